@@ -76,6 +76,7 @@ export default defineConfig({
             { text: 'MySQL', link: '/tools/mysql' },
             { text: 'PostgreSQL', link: '/tools/pgsql' },
             { text: 'Redis', link: '/tools/redis' },
+            { text: 'SQLite', link: '/tools/sqlite' },
             { text: 'VS Code', link: '/tools/vscode' },
             { text: 'PyCharm', link: '/tools/pycharm' },
             { text: 'MinGW', link: '/tools/mingw' },
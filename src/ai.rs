@@ -0,0 +1,1678 @@
+use anyhow::{Context, Result};
+use dialoguer::{Confirm, Input, Password, Select};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::ui;
+
+// ── 目标 CLI ─────────────────────────────────────────────────────────────────
+
+/// 可切换 API 来源的 AI 命令行工具
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum AiTarget {
+    /// Claude Code：~/.claude/settings.json 的 env 段
+    #[default]
+    Claude,
+    /// Codex CLI：~/.codex/config.toml + auth.json
+    Codex,
+    /// Gemini CLI：~/.gemini/.env + settings.json
+    Gemini,
+}
+
+impl AiTarget {
+    pub const ALL: [AiTarget; 3] = [AiTarget::Claude, AiTarget::Codex, AiTarget::Gemini];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AiTarget::Claude => "Claude Code",
+            AiTarget::Codex => "Codex CLI",
+            AiTarget::Gemini => "Gemini CLI",
+        }
+    }
+
+    /// 配置目录：~/.claude、~/.codex、~/.gemini
+    fn config_dir(&self) -> Result<PathBuf> {
+        let home = dirs::home_dir().context("无法获取用户主目录")?;
+        Ok(home.join(match self {
+            AiTarget::Claude => ".claude",
+            AiTarget::Codex => ".codex",
+            AiTarget::Gemini => ".gemini",
+        }))
+    }
+
+    /// 添加 Provider 时 Base URL 的示例
+    fn base_url_hint(&self) -> &'static str {
+        match self {
+            AiTarget::Claude => "https://api.anthropic.com",
+            AiTarget::Codex => "https://api.openai.com/v1",
+            AiTarget::Gemini => "https://generativelanguage.googleapis.com",
+        }
+    }
+
+    /// 添加 Provider 时 API Key 的示例前缀
+    fn key_hint(&self) -> &'static str {
+        match self {
+            AiTarget::Claude => "sk-ant-...",
+            AiTarget::Codex => "sk-...",
+            AiTarget::Gemini => "AIza...",
+        }
+    }
+}
+
+// ── Provider 配置 ────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiProvider {
+    /// 所属 CLI，旧文件没有此字段时视为 Claude Code
+    #[serde(default)]
+    pub target: AiTarget,
+    pub name: String,
+    pub base_url: String,
+    /// 内存中始终为明文；导出档案时未带 --include-secrets 则为空
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub api_key: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub reasoning_model: Option<String>,
+    #[serde(default)]
+    pub haiku_model: Option<String>,
+    #[serde(default)]
+    pub sonnet_model: Option<String>,
+    #[serde(default)]
+    pub opus_model: Option<String>,
+    #[serde(default)]
+    pub small_fast_model: Option<String>,
+    /// 额外写入的环境变量（如 ANTHROPIC_CUSTOM_HEADERS），Codex 不支持
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra_env: BTreeMap<String, String>,
+}
+
+impl AiProvider {
+    fn new(target: AiTarget, name: String, base_url: String, api_key: String) -> Self {
+        Self {
+            target,
+            name,
+            base_url,
+            api_key,
+            model: None,
+            reasoning_model: None,
+            haiku_model: None,
+            sonnet_model: None,
+            opus_model: None,
+            small_fast_model: None,
+            extra_env: BTreeMap::new(),
+        }
+    }
+
+    /// Claude Code 模型相关的 env 键及其取值
+    fn model_fields(&self) -> [(&'static str, &Option<String>); 6] {
+        [
+            ("ANTHROPIC_MODEL", &self.model),
+            ("ANTHROPIC_REASONING_MODEL", &self.reasoning_model),
+            ("ANTHROPIC_SMALL_FAST_MODEL", &self.small_fast_model),
+            ("ANTHROPIC_DEFAULT_HAIKU_MODEL", &self.haiku_model),
+            ("ANTHROPIC_DEFAULT_SONNET_MODEL", &self.sonnet_model),
+            ("ANTHROPIC_DEFAULT_OPUS_MODEL", &self.opus_model),
+        ]
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AiProviders {
+    /// Windows 上用 DPAPI 加密保存 api_key（仅当前用户可解密）；需要跨机器同步此文件时设为 false
+    #[serde(default = "default_true")]
+    pub encrypt_keys: bool,
+    #[serde(default)]
+    pub providers: Vec<AiProvider>,
+    /// 加载时是否发现明文保存的 api_key（用于提示加密迁移）
+    #[serde(skip)]
+    has_plaintext: bool,
+}
+
+impl Default for AiProviders {
+    fn default() -> Self {
+        Self {
+            encrypt_keys: true,
+            providers: Vec::new(),
+            has_plaintext: false,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl AiProviders {
+    /// Provider 存储路径: %USERPROFILE%\.hudo\cc-providers.toml（所有目标共用，便携模式见 paths 模块）
+    pub fn path() -> Result<PathBuf> {
+        crate::paths::providers_path()
+    }
+
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let s = std::fs::read_to_string(&path)
+            .with_context(|| format!("读取 {} 失败", path.display()))?;
+        let mut store: Self =
+            toml::from_str(&s).with_context(|| format!("解析 {} 失败", path.display()))?;
+        store.decrypt_keys()?;
+        Ok(store)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let s = toml::to_string_pretty(&self.to_stored()?).context("序列化 providers 失败")?;
+        std::fs::write(&path, s)
+            .with_context(|| format!("写入 {} 失败", path.display()))
+    }
+
+    /// 属于某个目标的 provider 在 `providers` 中的下标
+    fn indices(&self, target: AiTarget) -> Vec<usize> {
+        (0..self.providers.len())
+            .filter(|&i| self.providers[i].target == target)
+            .collect()
+    }
+
+    /// 解密 "dpapi:" 前缀的 api_key，并记录是否存在明文 key
+    fn decrypt_keys(&mut self) -> Result<()> {
+        for p in &mut self.providers {
+            match p.api_key.strip_prefix(DPAPI_PREFIX) {
+                Some(enc) => {
+                    p.api_key = unprotect_key(enc)
+                        .with_context(|| format!("解密 Provider [{}] 的 API Key 失败", p.name))?;
+                }
+                None if !p.api_key.is_empty() => self.has_plaintext = true,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// 生成写入文件的副本：启用加密时（仅 Windows）api_key 替换为 DPAPI 密文
+    fn to_stored(&self) -> Result<Self> {
+        let providers = self.providers.clone();
+        #[cfg(windows)]
+        let providers = {
+            let mut providers = providers;
+            if self.encrypt_keys {
+                for p in &mut providers {
+                    if !p.api_key.is_empty() {
+                        p.api_key = protect_key(&p.api_key)?;
+                    }
+                }
+            }
+            providers
+        };
+        Ok(Self {
+            encrypt_keys: self.encrypt_keys,
+            providers,
+            has_plaintext: false,
+        })
+    }
+
+    /// 文件中有明文 key 且启用了加密（仅 Windows 可加密）
+    fn needs_encryption(&self) -> bool {
+        cfg!(windows) && self.encrypt_keys && self.has_plaintext
+    }
+}
+
+// ── API Key 加密（DPAPI） ──────────────────────────────────────────────────────
+
+/// 加密后 api_key 的前缀，其后为 base64 编码的 DPAPI 密文
+const DPAPI_PREFIX: &str = "dpapi:";
+
+/// 调用 CryptProtectData / CryptUnprotectData（按当前用户加密，无 UI）
+#[cfg(windows)]
+fn dpapi(data: &[u8], protect: bool) -> Result<Vec<u8>> {
+    use std::ptr::null;
+    use windows_sys::Win32::Foundation::LocalFree;
+    use windows_sys::Win32::Security::Cryptography::{
+        CryptProtectData, CryptUnprotectData, CRYPTPROTECT_UI_FORBIDDEN, CRYPT_INTEGER_BLOB,
+    };
+
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: data.len() as u32,
+        pbData: data.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB {
+        cbData: 0,
+        pbData: std::ptr::null_mut(),
+    };
+    let ok = unsafe {
+        if protect {
+            CryptProtectData(&input, null(), null(), null(), null(), CRYPTPROTECT_UI_FORBIDDEN, &mut output)
+        } else {
+            CryptUnprotectData(
+                &input,
+                std::ptr::null_mut(),
+                null(),
+                null(),
+                null(),
+                CRYPTPROTECT_UI_FORBIDDEN,
+                &mut output,
+            )
+        }
+    };
+    if ok == 0 {
+        anyhow::bail!("DPAPI 调用失败: {}", std::io::Error::last_os_error());
+    }
+    let out = unsafe { std::slice::from_raw_parts(output.pbData, output.cbData as usize) }.to_vec();
+    unsafe { LocalFree(output.pbData as _) };
+    Ok(out)
+}
+
+#[cfg(windows)]
+fn protect_key(key: &str) -> Result<String> {
+    use base64::Engine;
+    let enc = dpapi(key.as_bytes(), true)?;
+    Ok(format!("{}{}", DPAPI_PREFIX, base64::engine::general_purpose::STANDARD.encode(enc)))
+}
+
+#[cfg(windows)]
+fn unprotect_key(enc: &str) -> Result<String> {
+    use base64::Engine;
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(enc)
+        .context("密文不是有效的 base64")?;
+    // 其他用户或其他机器加密的密文无法解密
+    let plain = dpapi(&data, false).context("只能由加密时的 Windows 用户解密")?;
+    String::from_utf8(plain).context("解密结果不是有效的 UTF-8")
+}
+
+#[cfg(not(windows))]
+fn unprotect_key(_enc: &str) -> Result<String> {
+    anyhow::bail!("DPAPI 加密的 API Key 只能在 Windows 上解密")
+}
+
+// ── 写入各 CLI 的配置文件 ─────────────────────────────────────────────────────
+
+/// 读取 JSON 配置文件，不存在则返回空对象
+fn read_json(path: &Path) -> Result<serde_json::Value> {
+    if !path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+    let s = std::fs::read_to_string(path)
+        .with_context(|| format!("读取 {} 失败", path.display()))?;
+    serde_json::from_str(&s).with_context(|| format!("解析 {} 失败", path.display()))
+}
+
+/// 将修改后的 JSON 写回
+fn write_json(path: &Path, val: &serde_json::Value) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let s = serde_json::to_string_pretty(val)
+        .with_context(|| format!("序列化 {} 失败", path.display()))?;
+    std::fs::write(path, s)
+        .with_context(|| format!("写入 {} 失败", path.display()))
+}
+
+/// 将 provider 写入所属 CLI 的配置文件
+/// `previous` 为同一目标切换前激活的 provider，其 extra_env 中新 provider 没有的键会被移除
+fn apply_provider(p: &AiProvider, previous: Option<&AiProvider>) -> Result<()> {
+    let dir = p.target.config_dir()?;
+    match p.target {
+        AiTarget::Claude => {
+            write_claude(&dir.join("settings.json"), p, previous)?;
+            // 使用第三方 API 时，需要在 ~/.claude.json 中标记 onboarding 已完成
+            // 否则 Claude Code 会卡在引导流程
+            ensure_onboarding_completed()
+        }
+        AiTarget::Codex => {
+            // Codex 只读取 config.toml 与 auth.json，额外环境变量（来自导入的文件或手动编辑）不会生效
+            if !p.extra_env.is_empty() {
+                let keys: Vec<&str> = p.extra_env.keys().map(String::as_str).collect();
+                ui::print_warning(&format!("Codex 不支持额外环境变量，以下变量未应用: {}", keys.join(", ")));
+            }
+            write_codex(&dir.join("config.toml"), &dir.join("auth.json"), p)
+        }
+        AiTarget::Gemini => {
+            write_gemini(&dir.join(".env"), &dir.join("settings.json"), p, previous)
+        }
+    }
+}
+
+/// Claude Code：地址、密钥、模型和额外变量写入 settings.json 的 env 段
+fn write_claude(path: &Path, p: &AiProvider, previous: Option<&AiProvider>) -> Result<()> {
+    let mut settings = read_json(path)?;
+
+    // 确保 env 对象存在
+    if !settings["env"].is_object() {
+        settings["env"] = serde_json::json!({});
+    }
+    let env = settings["env"].as_object_mut().context("settings.json 的 env 不是对象")?;
+
+    env.insert("ANTHROPIC_AUTH_TOKEN".to_string(), p.api_key.clone().into());
+    env.insert("ANTHROPIC_BASE_URL".to_string(), p.base_url.clone().into());
+
+    // 写入模型配置（有值则设置，无值则清除）
+    for (key, val) in p.model_fields() {
+        match val {
+            Some(v) => {
+                env.insert(key.to_string(), v.clone().into());
+            }
+            None => {
+                env.remove(key);
+            }
+        }
+    }
+
+    // 移除上一个 provider 专属的额外变量，避免残留
+    if let Some(prev) = previous {
+        for key in prev.extra_env.keys() {
+            if !p.extra_env.contains_key(key) {
+                env.remove(key);
+            }
+        }
+    }
+    for (key, val) in &p.extra_env {
+        env.insert(key.clone(), val.clone().into());
+    }
+
+    write_json(path, &settings)
+}
+
+/// Codex 配置中 hudo 写入的 model_providers 名称
+const CODEX_PROVIDER: &str = "hudo";
+
+/// Codex CLI：config.toml 中指向 [model_providers.hudo]，密钥写入 auth.json 的 OPENAI_API_KEY
+fn write_codex(config_path: &Path, auth_path: &Path, p: &AiProvider) -> Result<()> {
+    let mut config: toml::Table = if config_path.exists() {
+        let s = std::fs::read_to_string(config_path)
+            .with_context(|| format!("读取 {} 失败", config_path.display()))?;
+        toml::from_str(&s).with_context(|| format!("解析 {} 失败", config_path.display()))?
+    } else {
+        toml::Table::new()
+    };
+
+    config.insert("model_provider".to_string(), CODEX_PROVIDER.into());
+    match &p.model {
+        Some(m) => {
+            config.insert("model".to_string(), m.as_str().into());
+        }
+        None => {
+            config.remove("model");
+        }
+    }
+
+    let mut provider = toml::Table::new();
+    provider.insert("name".to_string(), p.name.as_str().into());
+    provider.insert("base_url".to_string(), p.base_url.as_str().into());
+    provider.insert("wire_api".to_string(), "responses".into());
+    provider.insert("requires_openai_auth".to_string(), true.into());
+
+    let providers = config
+        .entry("model_providers")
+        .or_insert_with(|| toml::Table::new().into());
+    if !providers.is_table() {
+        *providers = toml::Table::new().into();
+    }
+    if let Some(t) = providers.as_table_mut() {
+        t.insert(CODEX_PROVIDER.to_string(), provider.into());
+    }
+
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let s = toml::to_string_pretty(&config).context("序列化 config.toml 失败")?;
+    std::fs::write(config_path, s)
+        .with_context(|| format!("写入 {} 失败", config_path.display()))?;
+
+    let mut auth = read_json(auth_path)?;
+    if !auth.is_object() {
+        auth = serde_json::json!({});
+    }
+    auth["OPENAI_API_KEY"] = p.api_key.clone().into();
+    write_json(auth_path, &auth)
+}
+
+/// Gemini CLI：地址、密钥、模型和额外变量写入 ~/.gemini/.env，并在 settings.json 中选择 API Key 认证
+fn write_gemini(
+    env_path: &Path,
+    settings_path: &Path,
+    p: &AiProvider,
+    previous: Option<&AiProvider>,
+) -> Result<()> {
+    let mut set: Vec<(&str, &str)> = vec![
+        ("GOOGLE_GEMINI_BASE_URL", &p.base_url),
+        ("GEMINI_API_KEY", &p.api_key),
+    ];
+    let mut remove: Vec<&str> = Vec::new();
+    match &p.model {
+        Some(m) => set.push(("GEMINI_MODEL", m)),
+        None => remove.push("GEMINI_MODEL"),
+    }
+    if let Some(prev) = previous {
+        remove.extend(
+            prev.extra_env
+                .keys()
+                .filter(|k| !p.extra_env.contains_key(*k))
+                .map(|k| k.as_str()),
+        );
+    }
+    set.extend(p.extra_env.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+
+    let content = std::fs::read_to_string(env_path).unwrap_or_default();
+    if let Some(parent) = env_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(env_path, update_dotenv(&content, &set, &remove))
+        .with_context(|| format!("写入 {} 失败", env_path.display()))?;
+
+    // 未选择认证方式时 Gemini CLI 会先进入登录引导
+    let mut settings = read_json(settings_path)?;
+    if !settings.is_object() {
+        settings = serde_json::json!({});
+    }
+    if !settings["security"].is_object() {
+        settings["security"] = serde_json::json!({});
+    }
+    if !settings["security"]["auth"].is_object() {
+        settings["security"]["auth"] = serde_json::json!({});
+    }
+    settings["security"]["auth"]["selectedType"] = "gemini-api-key".into();
+    write_json(settings_path, &settings)
+}
+
+/// 更新 .env 内容：已有键原地替换，新键追加到末尾，`remove` 中的键删除，其余行（含注释）保留
+fn update_dotenv(content: &str, set: &[(&str, &str)], remove: &[&str]) -> String {
+    let key_of = |line: &str| -> Option<String> {
+        let line = line.trim_start();
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        if line.starts_with('#') {
+            return None;
+        }
+        line.split_once('=').map(|(k, _)| k.trim().to_string())
+    };
+
+    let mut written = Vec::new();
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        match key_of(line) {
+            Some(key) if remove.contains(&key.as_str()) => {}
+            Some(key) => match set.iter().find(|(k, _)| *k == key) {
+                Some((k, v)) => {
+                    lines.push(format!("{}={}", k, v));
+                    written.push(key);
+                }
+                None => lines.push(line.to_string()),
+            },
+            None => lines.push(line.to_string()),
+        }
+    }
+    for (k, v) in set {
+        if !written.iter().any(|w| w == k) {
+            lines.push(format!("{}={}", k, v));
+        }
+    }
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// 确保 ~/.claude.json 中 hasCompletedOnboarding = true
+fn ensure_onboarding_completed() -> Result<()> {
+    let home = dirs::home_dir().context("无法获取用户主目录")?;
+    let path = home.join(".claude.json");
+
+    let mut val = if path.exists() {
+        let s = std::fs::read_to_string(&path)
+            .with_context(|| format!("读取 {} 失败", path.display()))?;
+        serde_json::from_str(&s)
+            .with_context(|| format!("解析 {} 失败", path.display()))?
+    } else {
+        serde_json::json!({})
+    };
+
+    if val.get("hasCompletedOnboarding") == Some(&serde_json::Value::Bool(true)) {
+        return Ok(());
+    }
+
+    val["hasCompletedOnboarding"] = serde_json::Value::Bool(true);
+
+    let s = serde_json::to_string_pretty(&val).context("序列化 .claude.json 失败")?;
+    std::fs::write(&path, s)
+        .with_context(|| format!("写入 {} 失败", path.display()))?;
+
+    Ok(())
+}
+
+/// 脱敏显示 API Key：保留 sk-ant- / sk- 前缀与末 4 位，如 "sk-ant-***abcd"
+pub fn mask_key(key: &str) -> String {
+    let prefix = ["sk-ant-", "sk-"]
+        .iter()
+        .find(|p| key.starts_with(*p))
+        .copied()
+        .unwrap_or("");
+    let chars: Vec<char> = key.chars().collect();
+    // 过短的 key 不显示末尾，避免泄露大部分内容
+    if chars.len() < prefix.len() + 8 {
+        return format!("{}***", prefix);
+    }
+    let last4: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}***{}", prefix, last4)
+}
+
+/// 从目标 CLI 的配置文件读取当前激活的 base_url
+fn current_base_url(target: AiTarget) -> Option<String> {
+    let dir = target.config_dir().ok()?;
+    match target {
+        AiTarget::Claude => read_json(&dir.join("settings.json"))
+            .ok()
+            .and_then(|s| s["env"]["ANTHROPIC_BASE_URL"].as_str().map(|v| v.to_string())),
+        AiTarget::Codex => {
+            let s = std::fs::read_to_string(dir.join("config.toml")).ok()?;
+            let config: toml::Table = toml::from_str(&s).ok()?;
+            let name = config.get("model_provider")?.as_str()?;
+            config
+                .get("model_providers")?
+                .get(name)?
+                .get("base_url")?
+                .as_str()
+                .map(|v| v.to_string())
+        }
+        AiTarget::Gemini => {
+            let s = std::fs::read_to_string(dir.join(".env")).ok()?;
+            s.lines()
+                .find_map(|l| l.trim().strip_prefix("GOOGLE_GEMINI_BASE_URL="))
+                .map(|v| v.trim().to_string())
+        }
+    }
+}
+
+// ── 连接测试 ──────────────────────────────────────────────────────────────────
+
+/// 连接测试超时（秒）
+const TEST_TIMEOUT_SECS: u64 = 10;
+
+/// Claude 测试请求未配置模型时使用的模型
+const TEST_DEFAULT_MODEL: &str = "claude-haiku-4-5";
+
+/// 按 HTTP 状态码判断的测试结果
+#[derive(Debug, PartialEq, Eq)]
+enum TestOutcome {
+    /// 可达且认证通过（400 表示参数问题，如模型名不被支持，但 key 有效）
+    Ok,
+    /// 401/403：API Key 无效或无权限
+    BadKey,
+    /// 其他状态码（如 404 地址错误、5xx 服务异常）
+    Unexpected(u16),
+}
+
+fn classify_response(status: u16, is_json: bool) -> TestOutcome {
+    match status {
+        200..=299 => TestOutcome::Ok,
+        400 if is_json => TestOutcome::Ok,
+        401 | 403 => TestOutcome::BadKey,
+        _ => TestOutcome::Unexpected(status),
+    }
+}
+
+/// 发送最小请求验证地址与密钥，打印结果与耗时，返回是否通过
+/// Claude 请求 {base_url}/v1/messages，Codex 请求 {base_url}/models，Gemini 请求 {base_url}/v1beta/models
+async fn test_provider(p: &AiProvider) -> Result<bool> {
+    let base = p.base_url.trim_end_matches('/');
+    let client = crate::version::http_client(TEST_TIMEOUT_SECS).context("创建 HTTP client 失败")?;
+
+    let (request, hint) = match p.target {
+        AiTarget::Claude => {
+            let model = p
+                .small_fast_model
+                .as_deref()
+                .or(p.haiku_model.as_deref())
+                .or(p.model.as_deref())
+                .unwrap_or(TEST_DEFAULT_MODEL);
+            let body = serde_json::json!({
+                "model": model,
+                "max_tokens": 1,
+                "messages": [{ "role": "user", "content": "ping" }],
+            });
+            // 官方 API 使用 x-api-key，中转服务多使用 Bearer（与 ANTHROPIC_AUTH_TOKEN 一致），两者都带上
+            let request = client
+                .post(format!("{}/v1/messages", base))
+                .header("x-api-key", &p.api_key)
+                .bearer_auth(&p.api_key)
+                .header("anthropic-version", "2023-06-01")
+                .json(&body);
+            (request, format!("model: {}", model))
+        }
+        AiTarget::Codex => (
+            client.get(format!("{}/models", base)).bearer_auth(&p.api_key),
+            "GET /models".to_string(),
+        ),
+        AiTarget::Gemini => (
+            client
+                .get(format!("{}/v1beta/models", base))
+                .header("x-goog-api-key", &p.api_key),
+            "GET /v1beta/models".to_string(),
+        ),
+    };
+
+    ui::print_info(&format!(
+        "测试 [{}]  {}  (key: {}, {})",
+        p.name,
+        base,
+        mask_key(&p.api_key),
+        hint
+    ));
+
+    let start = std::time::Instant::now();
+    let result = request.send().await;
+    let elapsed = start.elapsed().as_millis();
+
+    let resp = match result {
+        Ok(resp) => resp,
+        Err(e) => {
+            let reason = if e.is_timeout() {
+                format!("{} 秒内无响应", TEST_TIMEOUT_SECS)
+            } else if e.is_connect() {
+                "无法建立连接".to_string()
+            } else {
+                "请求失败".to_string()
+            };
+            ui::print_error(&format!("{}，请检查 Base URL 或代理设置（HTTPS_PROXY）", reason));
+            return Ok(false);
+        }
+    };
+
+    let status = resp.status().as_u16();
+    let is_json = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("json"));
+
+    match classify_response(status, is_json) {
+        TestOutcome::Ok => {
+            ui::print_success(&format!("连接正常，认证通过 (HTTP {}, {} ms)", status, elapsed));
+            if status == 400 {
+                ui::print_warning("服务端返回 400，配置的模型可能不被支持");
+            }
+            Ok(true)
+        }
+        TestOutcome::BadKey => {
+            ui::print_error(&format!("API Key 无效或无权限 (HTTP {}, {} ms)", status, elapsed));
+            Ok(false)
+        }
+        TestOutcome::Unexpected(code) => {
+            ui::print_error(&format!("服务响应异常 (HTTP {}, {} ms)，请检查 Base URL", code, elapsed));
+            Ok(false)
+        }
+    }
+}
+
+/// 询问是否立即测试连接
+async fn offer_test(p: &AiProvider) -> Result<()> {
+    if Confirm::with_theme(&crate::ui::theme())
+        .with_prompt("是否测试连接？")
+        .default(true)
+        .interact()?
+    {
+        test_provider(p).await?;
+    }
+    Ok(())
+}
+
+/// hudo cc test [name]：测试指定 Provider，未指定时测试当前激活的
+pub async fn cmd_test(target: AiTarget, name: Option<&str>) -> Result<()> {
+    let store = AiProviders::load()?;
+    let mut candidates = store.providers.iter().filter(|p| p.target == target);
+    let p = match name {
+        Some(name) => candidates
+            .find(|p| p.name == name)
+            .with_context(|| format!("未找到 Provider: {}", name))?,
+        None => {
+            let url = current_base_url(target).context("当前未激活任何 Provider，请指定名称")?;
+            candidates
+                .find(|p| p.base_url == url)
+                .context("当前激活的 Base URL 不在 Provider 列表中，请指定名称")?
+        }
+    };
+    if !test_provider(p).await? {
+        anyhow::bail!("连接测试未通过");
+    }
+    Ok(())
+}
+
+// ── Provider 导入导出 ─────────────────────────────────────────────────────────
+
+/// 加密 bundle 的格式标识：Argon2id（默认参数）派生密钥，AES-256-GCM 加密；
+/// 派生参数随格式固定，不从文件读取，避免构造的文件让导入耗尽时间或内存
+const BUNDLE_FORMAT: &str = "hudo-providers-v2";
+
+/// 交互菜单导出/导入的默认文件名
+const BUNDLE_DEFAULT_FILE: &str = "hudo-providers.toml";
+
+/// 独立导出的 Provider 文件：不加密时直接是 providers 列表，加密时只有 [encrypted] 段
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Bundle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encrypted: Option<EncryptedPayload>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    providers: Vec<AiProvider>,
+}
+
+/// 口令加密的内容：data 为 AES-256-GCM 密文（含认证标签），格式标识作为附加数据参与认证
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedPayload {
+    format: String,
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+/// 由口令派生 AES-256 密钥
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("派生密钥失败: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt_payload(plain: &[u8], passphrase: &str) -> Result<EncryptedPayload> {
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+    use base64::Engine;
+    let b64 = base64::engine::general_purpose::STANDARD;
+
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    getrandom::getrandom(&mut salt)
+        .and_then(|_| getrandom::getrandom(&mut nonce))
+        .map_err(|e| anyhow::anyhow!("生成随机数失败: {}", e))?;
+
+    let key = derive_key(passphrase, &salt)?;
+    let data = aes_gcm::Aes256Gcm::new(&key.into())
+        .encrypt(&nonce.into(), Payload { msg: plain, aad: BUNDLE_FORMAT.as_bytes() })
+        .map_err(|_| anyhow::anyhow!("加密失败"))?;
+
+    Ok(EncryptedPayload {
+        format: BUNDLE_FORMAT.to_string(),
+        salt: b64.encode(salt),
+        nonce: b64.encode(nonce),
+        data: b64.encode(data),
+    })
+}
+
+fn decrypt_payload(payload: &EncryptedPayload, passphrase: &str) -> Result<Vec<u8>> {
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+    use base64::Engine;
+    let b64 = base64::engine::general_purpose::STANDARD;
+
+    if payload.format != BUNDLE_FORMAT {
+        anyhow::bail!("不支持的加密格式: {}", payload.format);
+    }
+    let salt = b64.decode(&payload.salt).context("salt 不是有效的 base64")?;
+    let nonce: [u8; 12] = b64
+        .decode(&payload.nonce)
+        .ok()
+        .and_then(|n| n.try_into().ok())
+        .context("nonce 格式错误")?;
+    let data = b64.decode(&payload.data).context("data 不是有效的 base64")?;
+
+    let key = derive_key(passphrase, &salt)?;
+    aes_gcm::Aes256Gcm::new(&key.into())
+        .decrypt(&nonce.into(), Payload { msg: &data, aad: BUNDLE_FORMAT.as_bytes() })
+        .map_err(|_| anyhow::anyhow!("口令错误或文件已损坏"))
+}
+
+/// 序列化 providers，`passphrase` 不为空时加密
+fn encode_bundle(providers: &[AiProvider], passphrase: Option<&str>) -> Result<String> {
+    let plain = Bundle {
+        encrypted: None,
+        providers: providers.to_vec(),
+    };
+    let plain = toml::to_string_pretty(&plain).context("序列化 providers 失败")?;
+    let bundle = match passphrase {
+        Some(pass) => Bundle {
+            encrypted: Some(encrypt_payload(plain.as_bytes(), pass)?),
+            providers: Vec::new(),
+        },
+        None => return Ok(plain),
+    };
+    toml::to_string_pretty(&bundle).context("序列化 providers 失败")
+}
+
+/// 解析 bundle，加密时通过 `ask_passphrase` 获取口令
+fn decode_bundle(
+    content: &str,
+    ask_passphrase: impl FnOnce() -> Result<String>,
+) -> Result<Vec<AiProvider>> {
+    let bundle: Bundle = toml::from_str(content).context("文件格式错误")?;
+    match bundle.encrypted {
+        Some(payload) => {
+            let plain = decrypt_payload(&payload, &ask_passphrase()?)?;
+            let plain = String::from_utf8(plain).context("解密结果不是有效的 UTF-8")?;
+            let inner: Bundle = toml::from_str(&plain).context("解密后的内容格式错误")?;
+            Ok(inner.providers)
+        }
+        None => Ok(bundle.providers),
+    }
+}
+
+/// 同名（同目标）Provider 配置不同时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Conflict {
+    KeepExisting,
+    Replace,
+    /// 两者都保留，导入的改名为 "<name> (导入)"
+    KeepBoth,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct MergeSummary {
+    added: usize,
+    replaced: usize,
+    unchanged: usize,
+    skipped: usize,
+}
+
+/// 合并导入的 providers：新的追加；同名且配置完全一致的忽略；配置不同时交给 `resolve` 决定
+fn merge_providers(
+    store: &mut AiProviders,
+    incoming: Vec<AiProvider>,
+    mut resolve: impl FnMut(&AiProvider, &AiProvider) -> Result<Conflict>,
+) -> Result<MergeSummary> {
+    let mut summary = MergeSummary::default();
+    for mut p in incoming {
+        let existing = store
+            .providers
+            .iter()
+            .position(|e| e.target == p.target && e.name == p.name);
+        let Some(i) = existing else {
+            store.providers.push(p);
+            summary.added += 1;
+            continue;
+        };
+
+        // 比较序列化结果，避免逐字段比较遗漏新字段
+        let same = toml::to_string(&store.providers[i]).ok() == toml::to_string(&p).ok();
+        if same {
+            summary.unchanged += 1;
+            continue;
+        }
+        match resolve(&store.providers[i], &p)? {
+            Conflict::KeepExisting => summary.skipped += 1,
+            Conflict::Replace => {
+                // 导入文件不含 Key 时保留现有 Key
+                if p.api_key.is_empty() {
+                    p.api_key = store.providers[i].api_key.clone();
+                }
+                store.providers[i] = p;
+                summary.replaced += 1;
+            }
+            Conflict::KeepBoth => {
+                let mut name = format!("{} (导入)", p.name);
+                let mut n = 2;
+                while store.providers.iter().any(|e| e.target == p.target && e.name == name) {
+                    name = format!("{} (导入 {})", p.name, n);
+                    n += 1;
+                }
+                p.name = name;
+                store.providers.push(p);
+                summary.added += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// 交互询问同名 Provider 的冲突处理方式
+fn prompt_conflict(existing: &AiProvider, incoming: &AiProvider) -> Result<Conflict> {
+    println!();
+    ui::print_warning(&format!(
+        "[{}] {} 已存在且配置不同",
+        existing.target.name(),
+        existing.name
+    ));
+    println!(
+        "    现有: {}  {}",
+        existing.base_url,
+        console::style(mask_key(&existing.api_key)).dim()
+    );
+    println!(
+        "    导入: {}  {}",
+        incoming.base_url,
+        console::style(mask_key(&incoming.api_key)).dim()
+    );
+    let items = ["保留现有", "使用导入的", "两者都保留（导入的重命名）"];
+    let sel = Select::with_theme(&crate::ui::theme())
+        .items(&items)
+        .default(0)
+        .interact_opt()?;
+    Ok(match sel {
+        Some(1) => Conflict::Replace,
+        Some(2) => Conflict::KeepBoth,
+        _ => Conflict::KeepExisting,
+    })
+}
+
+/// hudo cc export <file>：导出全部 Provider，可设置口令加密
+pub fn cmd_export(file: &str) -> Result<()> {
+    let store = AiProviders::load()?;
+    if store.providers.is_empty() {
+        ui::print_warning("暂无 Provider，无需导出");
+        return Ok(());
+    }
+
+    let pass = Password::with_theme(&crate::ui::theme())
+        .with_prompt("设置口令（回车不加密）")
+        .allow_empty_password(true)
+        .interact()?;
+    let passphrase = if pass.is_empty() {
+        ui::print_warning("未设置口令，API Key 将以明文写入文件，请妥善保管");
+        None
+    } else {
+        let confirm = Password::with_theme(&crate::ui::theme())
+            .with_prompt("再次输入口令")
+            .interact()?;
+        if confirm != pass {
+            anyhow::bail!("两次输入的口令不一致");
+        }
+        Some(pass)
+    };
+
+    let content = encode_bundle(&store.providers, passphrase.as_deref())?;
+    std::fs::write(file, content).with_context(|| format!("写入 {} 失败", file))?;
+    ui::print_success(&format!("已导出 {} 个 Provider 到 {}", store.providers.len(), file));
+    Ok(())
+}
+
+/// hudo cc import <file> [--activate name]：合并导入 Provider，可选导入后直接切换
+pub fn cmd_import(file: &str, activate: Option<&str>) -> Result<()> {
+    let content = std::fs::read_to_string(file).with_context(|| format!("读取 {} 失败", file))?;
+    let incoming = decode_bundle(&content, || {
+        Ok(Password::with_theme(&crate::ui::theme())
+            .with_prompt("文件已加密，请输入口令")
+            .interact()?)
+    })
+    .with_context(|| format!("解析 {} 失败", file))?;
+
+    let mut store = AiProviders::load()?;
+    let summary = merge_providers(&mut store, incoming, prompt_conflict)?;
+    store.save()?;
+    ui::print_success(&format!(
+        "新增 {} 个，覆盖 {} 个，相同 {} 个，保留现有 {} 个",
+        summary.added, summary.replaced, summary.unchanged, summary.skipped
+    ));
+
+    if let Some(name) = activate {
+        // 同名时优先 Claude Code（hudo cc 的默认目标）
+        let p = store
+            .providers
+            .iter()
+            .filter(|p| p.name == name)
+            .min_by_key(|p| p.target != AiTarget::Claude)
+            .with_context(|| format!("未找到 Provider: {}", name))?;
+        if p.api_key.is_empty() {
+            anyhow::bail!("Provider [{}] 未包含 API Key，请先在 hudo ai 中编辑补充", name);
+        }
+        let active = current_base_url(p.target).and_then(|url| {
+            store
+                .providers
+                .iter()
+                .find(|e| e.target == p.target && e.base_url == url)
+        });
+        apply_provider(p, active)?;
+        ui::print_success(&format!("已切换到 [{}]  {}", p.name, p.base_url));
+        ui::print_info(&format!("重启终端或 {} 后生效", p.target.name()));
+    }
+    Ok(())
+}
+
+// ── 交互菜单 ──────────────────────────────────────────────────────────────────
+
+/// hudo ai [target]：未指定目标时先选择要管理的 CLI
+pub async fn cmd_ai(target: Option<AiTarget>) -> Result<()> {
+    let target = match target {
+        Some(t) => t,
+        None => {
+            let store = AiProviders::load()?;
+            let items: Vec<String> = AiTarget::ALL
+                .iter()
+                .map(|t| {
+                    let count = store.indices(*t).len();
+                    format!("{:<12}  {}", t.name(), console::style(format!("{} 个 Provider", count)).dim())
+                })
+                .chain(std::iter::once("退出".to_string()))
+                .collect();
+            let sel = Select::with_theme(&crate::ui::theme())
+                .with_prompt("选择要管理 API 来源的工具")
+                .items(&items)
+                .default(0)
+                .interact_opt()?;
+            match sel {
+                Some(i) if i < AiTarget::ALL.len() => AiTarget::ALL[i],
+                _ => return Ok(()),
+            }
+        }
+    };
+    cmd_target(target).await
+}
+
+/// 管理某个目标 CLI 的 Provider（切换/添加/编辑/测试/删除）
+async fn cmd_target(target: AiTarget) -> Result<()> {
+    ui::print_title(&format!("{} API 来源管理", target.name()));
+
+    offer_encryption()?;
+
+    loop {
+        let mut store = AiProviders::load()?;
+        let active_url = current_base_url(target);
+        let indices = store.indices(target);
+
+        if indices.is_empty() {
+            println!("  {}", console::style("暂无 Provider，请先添加").dim());
+            println!();
+            let items = ["添加 Provider", "退出"];
+            let sel = Select::with_theme(&crate::ui::theme())
+                .items(&items)
+                .default(0)
+                .interact_opt()?;
+            match sel {
+                Some(0) => {
+                    add_provider(&mut store, target)?;
+                    store.save()?;
+                    if let Some(p) = store.providers.last() {
+                        offer_test(p).await?;
+                    }
+                }
+                _ => break,
+            }
+            continue;
+        }
+
+        // 构建列表项：当前激活的前面显示 *
+        let items: Vec<String> = indices
+            .iter()
+            .map(|&i| {
+                let p = &store.providers[i];
+                let active = active_url.as_deref() == Some(&p.base_url);
+                let mark = if active {
+                    console::style("* ").green().to_string()
+                } else {
+                    "  ".to_string()
+                };
+                format!(
+                    "{}{:<20}  {}  {}",
+                    mark,
+                    p.name,
+                    console::style(&p.base_url).dim(),
+                    console::style(mask_key(&p.api_key)).dim()
+                )
+            })
+            .chain(std::iter::once("  [+] 添加 Provider".to_string()))
+            .chain(std::iter::once("  [e] 编辑 Provider".to_string()))
+            .chain(std::iter::once("  [t] 测试连接".to_string()))
+            .chain(std::iter::once("  [x] 删除 Provider".to_string()))
+            .chain(std::iter::once("  [o] 导出到文件".to_string()))
+            .chain(std::iter::once("  [i] 从文件导入".to_string()))
+            .chain(std::iter::once("  退出".to_string()))
+            .collect();
+
+        let n = indices.len();
+        let sel = Select::with_theme(&crate::ui::theme())
+            .with_prompt("选择 Provider（* = 当前激活）")
+            .items(&items)
+            .default(0)
+            .interact_opt()?;
+
+        // 当前激活的 provider（按 base_url 匹配），切换时用于清理其专属变量
+        let active_idx = active_url.as_deref().and_then(|url| {
+            indices
+                .iter()
+                .copied()
+                .find(|&i| store.providers[i].base_url == url)
+        });
+        let active = active_idx.map(|i| store.providers[i].clone());
+
+        match sel {
+            None => break,
+            Some(i) if i < n => {
+                // 切换到选中的 provider
+                let p = &store.providers[indices[i]];
+                apply_provider(p, active.as_ref())?;
+                ui::print_success(&format!("已切换到 [{}]  {}", p.name, p.base_url));
+                ui::print_info(&format!("重启终端或 {} 后生效", target.name()));
+                offer_test(p).await?;
+                break;
+            }
+            Some(i) if i == n => {
+                // 添加
+                add_provider(&mut store, target)?;
+                store.save()?;
+                if let Some(p) = store.providers.last() {
+                    offer_test(p).await?;
+                }
+            }
+            Some(i) if i == n + 1 => {
+                // 编辑
+                if let Some(i) = edit_provider(&mut store, &indices)? {
+                    store.save()?;
+                    // 编辑的是当前激活的 provider 时，同步写入配置文件
+                    if active_idx == Some(i) {
+                        apply_provider(&store.providers[i], active.as_ref())?;
+                        ui::print_info(&format!(
+                            "已同步到当前 {} 配置，重启终端或 {} 后生效",
+                            target.name(),
+                            target.name()
+                        ));
+                    }
+                }
+            }
+            Some(i) if i == n + 2 => {
+                // 测试连接
+                if let Some(i) = pick_provider(&store, &indices, "选择要测试的 Provider")? {
+                    test_provider(&store.providers[i]).await?;
+                }
+            }
+            Some(i) if i == n + 3 => {
+                // 删除
+                if delete_provider(&mut store, &indices)? {
+                    store.save()?;
+                }
+            }
+            Some(i) if i == n + 4 || i == n + 5 => {
+                // 导出 / 导入（所有工具的 Provider）
+                let file: String = Input::with_theme(&crate::ui::theme())
+                    .with_prompt("文件路径")
+                    .default(BUNDLE_DEFAULT_FILE.to_string())
+                    .interact_text()?;
+                let result = if i == n + 4 { cmd_export(&file) } else { cmd_import(&file, None) };
+                if let Err(e) = result {
+                    ui::print_error(&format!("{:#}", e));
+                }
+            }
+            _ => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// 首次发现明文保存的 API Key 时，询问是否改为加密保存；拒绝则关闭加密，不再询问
+fn offer_encryption() -> Result<()> {
+    let mut store = AiProviders::load()?;
+    if !store.needs_encryption() {
+        return Ok(());
+    }
+    let encrypt = Confirm::with_theme(&crate::ui::theme())
+        .with_prompt("cc-providers.toml 中的 API Key 为明文保存，是否加密？（DPAPI，仅当前 Windows 用户可解密）")
+        .default(true)
+        .interact()?;
+    if encrypt {
+        store.save()?;
+        ui::print_success("API Key 已加密保存");
+    } else {
+        store.encrypt_keys = false;
+        store.save()?;
+        ui::print_info("已保持明文，如需加密请将 cc-providers.toml 中的 encrypt_keys 改为 true");
+    }
+    Ok(())
+}
+
+/// 输入 API Key（不回显）；`current` 不为空时回车保留原值
+fn ask_api_key(target: AiTarget, current: &str) -> Result<String> {
+    let prompt = if current.is_empty() {
+        format!("API Key（{}，输入不回显）", target.key_hint())
+    } else {
+        format!("API Key（当前 {}，回车保留）", mask_key(current))
+    };
+    let key = Password::with_theme(&crate::ui::theme())
+        .with_prompt(prompt)
+        .allow_empty_password(!current.is_empty())
+        .interact()?;
+    Ok(if key.is_empty() { current.to_string() } else { key })
+}
+
+/// 输入可选字段：回车保留默认值，输入 "-" 清空
+fn ask_optional(prompt: &str, current: &Option<String>) -> Result<Option<String>> {
+    let hint = if current.is_some() { "回车保留，- 清空" } else { "回车跳过" };
+    let v: String = Input::with_theme(&crate::ui::theme())
+        .with_prompt(format!("{}（{}）", prompt, hint))
+        .with_initial_text(current.clone().unwrap_or_default())
+        .allow_empty(true)
+        .interact_text()?;
+    let v = v.trim();
+    Ok(if v.is_empty() || v == "-" { None } else { Some(v.to_string()) })
+}
+
+/// 依次输入目标 CLI 支持的模型字段，以 `p` 的当前值为默认值
+fn ask_models(p: &mut AiProvider) -> Result<()> {
+    match p.target {
+        AiTarget::Claude => {
+            p.model = ask_optional("默认模型 (ANTHROPIC_MODEL)", &p.model)?;
+            p.reasoning_model =
+                ask_optional("推理模型 (ANTHROPIC_REASONING_MODEL)", &p.reasoning_model)?;
+            p.small_fast_model =
+                ask_optional("快速模型 (ANTHROPIC_SMALL_FAST_MODEL)", &p.small_fast_model)?;
+            p.haiku_model =
+                ask_optional("Haiku 模型 (ANTHROPIC_DEFAULT_HAIKU_MODEL)", &p.haiku_model)?;
+            p.sonnet_model =
+                ask_optional("Sonnet 模型 (ANTHROPIC_DEFAULT_SONNET_MODEL)", &p.sonnet_model)?;
+            p.opus_model = ask_optional("Opus 模型 (ANTHROPIC_DEFAULT_OPUS_MODEL)", &p.opus_model)?;
+        }
+        AiTarget::Codex => p.model = ask_optional("模型 (config.toml 的 model)", &p.model)?,
+        AiTarget::Gemini => p.model = ask_optional("模型 (GEMINI_MODEL)", &p.model)?,
+    }
+    Ok(())
+}
+
+/// 编辑额外环境变量：逐个修改已有变量（"-" 删除），再追加新变量（变量名留空结束）
+fn ask_extra_env(env: &mut BTreeMap<String, String>) -> Result<()> {
+    for key in env.keys().cloned().collect::<Vec<_>>() {
+        let v: String = Input::with_theme(&crate::ui::theme())
+            .with_prompt(format!("{}（- 删除）", key))
+            .with_initial_text(env[&key].clone())
+            .allow_empty(true)
+            .interact_text()?;
+        if v.trim().is_empty() || v.trim() == "-" {
+            env.remove(&key);
+        } else {
+            env.insert(key, v);
+        }
+    }
+    loop {
+        let key: String = Input::with_theme(&crate::ui::theme())
+            .with_prompt("新增环境变量名（回车结束）")
+            .allow_empty(true)
+            .interact_text()?;
+        let key = key.trim().to_string();
+        if key.is_empty() {
+            return Ok(());
+        }
+        let value: String = Input::with_theme(&crate::ui::theme())
+            .with_prompt(format!("{} 的值", key))
+            .interact_text()?;
+        env.insert(key, value);
+    }
+}
+
+/// 交互式添加 Provider
+fn add_provider(store: &mut AiProviders, target: AiTarget) -> Result<()> {
+    println!();
+    let name: String = Input::with_theme(&crate::ui::theme())
+        .with_prompt("名称（如: 官方 / 中转）")
+        .interact_text()?;
+
+    let base_url: String = Input::with_theme(&crate::ui::theme())
+        .with_prompt(format!("Base URL（如: {}）", target.base_url_hint()))
+        .interact_text()?;
+
+    let api_key = ask_api_key(target, "")?;
+
+    let mut provider = AiProvider::new(target, name, base_url, api_key);
+
+    // 可选：配置自定义模型
+    if Confirm::with_theme(&crate::ui::theme())
+        .with_prompt("是否配置自定义模型？（第三方 API 通常需要）")
+        .default(false)
+        .interact()?
+    {
+        ask_models(&mut provider)?;
+    }
+
+    // 可选：额外环境变量（Codex 通过 config.toml 配置，不支持）
+    if target != AiTarget::Codex
+        && Confirm::with_theme(&crate::ui::theme())
+            .with_prompt("是否配置额外环境变量？")
+            .default(false)
+            .interact()?
+    {
+        ask_extra_env(&mut provider.extra_env)?;
+    }
+
+    store.providers.push(provider);
+
+    ui::print_success("Provider 已添加");
+    Ok(())
+}
+
+/// 从 `indices` 对应的 Provider 中选择一个，返回其在 `providers` 中的下标，取消时返回 None
+fn pick_provider(store: &AiProviders, indices: &[usize], prompt: &str) -> Result<Option<usize>> {
+    let items: Vec<String> = indices
+        .iter()
+        .map(|&i| format!("{} — {}", store.providers[i].name, store.providers[i].base_url))
+        .chain(std::iter::once("取消".to_string()))
+        .collect();
+
+    let sel = Select::with_theme(&crate::ui::theme())
+        .with_prompt(prompt)
+        .items(&items)
+        .default(0)
+        .interact_opt()?;
+
+    Ok(sel.and_then(|i| indices.get(i).copied()))
+}
+
+/// 交互式编辑 Provider，当前值作为默认值，返回被编辑的下标
+fn edit_provider(store: &mut AiProviders, indices: &[usize]) -> Result<Option<usize>> {
+    let Some(i) = pick_provider(store, indices, "选择要编辑的 Provider")? else {
+        return Ok(None);
+    };
+
+    println!();
+    let mut p = store.providers[i].clone();
+    p.name = Input::with_theme(&crate::ui::theme())
+        .with_prompt("名称")
+        .with_initial_text(p.name.clone())
+        .interact_text()?;
+    p.base_url = Input::with_theme(&crate::ui::theme())
+        .with_prompt("Base URL")
+        .with_initial_text(p.base_url.clone())
+        .interact_text()?;
+    p.api_key = ask_api_key(p.target, &p.api_key)?;
+    ask_models(&mut p)?;
+    if p.target != AiTarget::Codex {
+        ask_extra_env(&mut p.extra_env)?;
+    }
+
+    ui::print_success(&format!("已更新 [{}]", p.name));
+    store.providers[i] = p;
+    Ok(Some(i))
+}
+
+/// 交互式删除 Provider，返回是否删除了
+fn delete_provider(store: &mut AiProviders, indices: &[usize]) -> Result<bool> {
+    if indices.is_empty() {
+        return Ok(false);
+    }
+    match pick_provider(store, indices, "选择要删除的 Provider")? {
+        Some(i) => {
+            let removed = store.providers.remove(i);
+            ui::print_success(&format!("已删除 [{}]", removed.name));
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(name: &str, base_url: &str) -> AiProvider {
+        AiProvider::new(
+            AiTarget::Claude,
+            name.to_string(),
+            base_url.to_string(),
+            format!("key-{}", name),
+        )
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hudo-ai-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_write_claude() {
+        let dir = temp_dir("write");
+        let path = dir.join("settings.json");
+        std::fs::write(&path, r#"{"theme":"dark","env":{"OTHER":"1"}}"#).unwrap();
+
+        let mut p = provider("relay", "https://relay.example.com");
+        p.model = Some("m1".to_string());
+        p.small_fast_model = Some("fast".to_string());
+        p.extra_env.insert("ANTHROPIC_CUSTOM_HEADERS".to_string(), "x-a: b".to_string());
+        write_claude(&path, &p, None).unwrap();
+
+        let s = read_json(&path).unwrap();
+        assert_eq!(s["theme"], "dark");
+        assert_eq!(s["env"]["OTHER"], "1");
+        assert_eq!(s["env"]["ANTHROPIC_AUTH_TOKEN"], "key-relay");
+        assert_eq!(s["env"]["ANTHROPIC_BASE_URL"], "https://relay.example.com");
+        assert_eq!(s["env"]["ANTHROPIC_MODEL"], "m1");
+        assert_eq!(s["env"]["ANTHROPIC_SMALL_FAST_MODEL"], "fast");
+        assert_eq!(s["env"]["ANTHROPIC_CUSTOM_HEADERS"], "x-a: b");
+        assert!(s["env"].get("ANTHROPIC_DEFAULT_OPUS_MODEL").is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_switch_removes_stale_keys() {
+        let dir = temp_dir("switch");
+        let path = dir.join("settings.json");
+
+        let mut old = provider("relay", "https://relay.example.com");
+        old.model = Some("m1".to_string());
+        old.opus_model = Some("o1".to_string());
+        old.extra_env.insert("ANTHROPIC_CUSTOM_HEADERS".to_string(), "x-a: b".to_string());
+        old.extra_env.insert("SHARED".to_string(), "old".to_string());
+        write_claude(&path, &old, None).unwrap();
+
+        let mut new = provider("official", "https://api.anthropic.com");
+        new.extra_env.insert("SHARED".to_string(), "new".to_string());
+        write_claude(&path, &new, Some(&old)).unwrap();
+
+        let s = read_json(&path).unwrap();
+        let env = s["env"].as_object().unwrap();
+        assert_eq!(env["ANTHROPIC_BASE_URL"], "https://api.anthropic.com");
+        assert_eq!(env["SHARED"], "new");
+        assert!(!env.contains_key("ANTHROPIC_MODEL"));
+        assert!(!env.contains_key("ANTHROPIC_DEFAULT_OPUS_MODEL"));
+        assert!(!env.contains_key("ANTHROPIC_CUSTOM_HEADERS"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_write_codex() {
+        let dir = temp_dir("codex");
+        let config = dir.join("config.toml");
+        let auth = dir.join("auth.json");
+        std::fs::write(&config, "approval_policy = \"never\"\nmodel = \"old\"\n").unwrap();
+
+        let mut p = provider("relay", "https://relay.example.com/v1");
+        p.target = AiTarget::Codex;
+        write_codex(&config, &auth, &p).unwrap();
+
+        let t: toml::Table = toml::from_str(&std::fs::read_to_string(&config).unwrap()).unwrap();
+        assert_eq!(t["approval_policy"].as_str(), Some("never"));
+        assert_eq!(t["model_provider"].as_str(), Some("hudo"));
+        assert!(t.get("model").is_none());
+        assert_eq!(
+            t["model_providers"]["hudo"]["base_url"].as_str(),
+            Some("https://relay.example.com/v1")
+        );
+        assert_eq!(read_json(&auth).unwrap()["OPENAI_API_KEY"], "key-relay");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_write_gemini() {
+        let dir = temp_dir("gemini");
+        let env_path = dir.join(".env");
+        let settings = dir.join("settings.json");
+        std::fs::write(&env_path, "# 注释\nGEMINI_MODEL=old\nHTTPS_PROXY=http://127.0.0.1:7890\nX_OLD=1\n").unwrap();
+
+        let mut old = provider("old", "https://old.example.com");
+        old.target = AiTarget::Gemini;
+        old.extra_env.insert("X_OLD".to_string(), "1".to_string());
+        let mut p = provider("relay", "https://relay.example.com");
+        p.target = AiTarget::Gemini;
+        write_gemini(&env_path, &settings, &p, Some(&old)).unwrap();
+
+        let env = std::fs::read_to_string(&env_path).unwrap();
+        assert!(env.contains("# 注释"));
+        assert!(env.contains("HTTPS_PROXY=http://127.0.0.1:7890"));
+        assert!(env.contains("GOOGLE_GEMINI_BASE_URL=https://relay.example.com"));
+        assert!(env.contains("GEMINI_API_KEY=key-relay"));
+        assert!(!env.contains("GEMINI_MODEL"));
+        assert!(!env.contains("X_OLD"));
+        assert_eq!(read_json(&settings).unwrap()["security"]["auth"]["selectedType"], "gemini-api-key");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_update_dotenv() {
+        let out = update_dotenv("A=1\n# c\nexport B=2\n", &[("B", "3"), ("C", "4")], &["A"]);
+        assert_eq!(out, "# c\nB=3\nC=4\n");
+        assert_eq!(update_dotenv("", &[("A", "1")], &[]), "A=1\n");
+    }
+
+    #[test]
+    fn test_bundle_plain_roundtrip() {
+        let p = provider("relay", "https://relay.example.com");
+        let s = encode_bundle(&[p], None).unwrap();
+        assert!(s.contains("key-relay"));
+        let out = decode_bundle(&s, || panic!("未加密时不应询问口令")).unwrap();
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].api_key, "key-relay");
+    }
+
+    #[test]
+    fn test_bundle_encrypted_roundtrip() {
+        let mut p = provider("relay", "https://relay.example.com");
+        p.model = Some("m1".to_string());
+        let s = encode_bundle(&[p], Some("口令 pass")).unwrap();
+        assert!(!s.contains("key-relay"));
+        assert!(!s.contains("relay.example.com"));
+
+        let out = decode_bundle(&s, || Ok("口令 pass".to_string())).unwrap();
+        assert_eq!(out[0].name, "relay");
+        assert_eq!(out[0].api_key, "key-relay");
+        assert_eq!(out[0].model.as_deref(), Some("m1"));
+
+        let err = decode_bundle(&s, || Ok("wrong".to_string())).unwrap_err();
+        assert!(err.to_string().contains("口令错误"));
+    }
+
+    #[test]
+    fn test_bundle_tampered() {
+        let s = encode_bundle(&[provider("a", "https://a")], Some("pw")).unwrap();
+        let mut bundle: Bundle = toml::from_str(&s).unwrap();
+        let payload = bundle.encrypted.as_mut().unwrap();
+        payload.data = format!("A{}", &payload.data[1..]);
+        let s = toml::to_string(&bundle).unwrap();
+        assert!(decode_bundle(&s, || Ok("pw".to_string())).is_err());
+
+        // 旧格式（口令派生参数写在文件里）不再接受，不会按文件中的迭代次数派生密钥
+        let old = "[encrypted]\nformat = \"hudo-providers-v1\"\niterations = 4294967295\nsalt = \"\"\nnonce = \"\"\nmac = \"\"\ndata = \"\"\n";
+        let err = decode_bundle(old, || Ok("pw".to_string())).unwrap_err();
+        assert!(err.to_string().contains("不支持的加密格式"));
+    }
+
+    #[test]
+    fn test_merge_providers() {
+        let mut store = AiProviders::default();
+        store.providers.push(provider("same", "https://same"));
+        store.providers.push(provider("keep", "https://old"));
+        store.providers.push(provider("replace", "https://old"));
+        store.providers.push(provider("both", "https://old"));
+
+        let mut codex = provider("same", "https://codex");
+        codex.target = AiTarget::Codex;
+        let mut replace = provider("replace", "https://new");
+        replace.api_key.clear();
+        let incoming = vec![
+            provider("same", "https://same"),
+            provider("keep", "https://new"),
+            replace,
+            provider("both", "https://new"),
+            provider("fresh", "https://fresh"),
+            codex,
+        ];
+
+        let summary = merge_providers(&mut store, incoming, |existing, _| {
+            Ok(match existing.name.as_str() {
+                "keep" => Conflict::KeepExisting,
+                "replace" => Conflict::Replace,
+                _ => Conflict::KeepBoth,
+            })
+        })
+        .unwrap();
+
+        assert_eq!(
+            summary,
+            MergeSummary { added: 3, replaced: 1, unchanged: 1, skipped: 1 }
+        );
+        let find = |name: &str| store.providers.iter().find(|p| p.name == name).unwrap();
+        assert_eq!(find("keep").base_url, "https://old");
+        assert_eq!(find("replace").base_url, "https://new");
+        // 导入文件不含 Key 时沿用现有 Key
+        assert_eq!(find("replace").api_key, "key-replace");
+        assert_eq!(find("both").base_url, "https://old");
+        assert_eq!(find("both (导入)").base_url, "https://new");
+        assert_eq!(find("fresh").base_url, "https://fresh");
+        assert!(store.providers.iter().any(|p| p.target == AiTarget::Codex && p.name == "same"));
+    }
+
+    #[test]
+    fn test_mask_key() {
+        assert_eq!(mask_key("sk-ant-api03-abcdefgh1234"), "sk-ant-***1234");
+        assert_eq!(mask_key("sk-0123456789wxyz"), "sk-***wxyz");
+        assert_eq!(mask_key("token-0123456789"), "***6789");
+        assert_eq!(mask_key("sk-ant-short"), "sk-ant-***");
+        assert_eq!(mask_key(""), "***");
+    }
+
+    #[test]
+    fn test_classify_response() {
+        assert_eq!(classify_response(200, true), TestOutcome::Ok);
+        assert_eq!(classify_response(400, true), TestOutcome::Ok);
+        assert_eq!(classify_response(400, false), TestOutcome::Unexpected(400));
+        assert_eq!(classify_response(401, true), TestOutcome::BadKey);
+        assert_eq!(classify_response(403, false), TestOutcome::BadKey);
+        assert_eq!(classify_response(404, false), TestOutcome::Unexpected(404));
+        assert_eq!(classify_response(502, true), TestOutcome::Unexpected(502));
+    }
+
+    #[test]
+    fn test_old_providers_file_loads() {
+        let s = r#"
+[[providers]]
+name = "官方"
+base_url = "https://api.anthropic.com"
+api_key = "sk-ant-xxx"
+model = "claude-sonnet"
+"#;
+        let mut store: AiProviders = toml::from_str(s).unwrap();
+        store.decrypt_keys().unwrap();
+        assert!(store.encrypt_keys);
+        assert!(store.has_plaintext);
+        let p = &store.providers[0];
+        assert_eq!(p.target, AiTarget::Claude);
+        assert_eq!(p.api_key, "sk-ant-xxx");
+        assert_eq!(p.model.as_deref(), Some("claude-sonnet"));
+        assert!(p.small_fast_model.is_none());
+        assert!(p.extra_env.is_empty());
+    }
+
+    #[test]
+    fn test_keyless_provider_roundtrip() {
+        // 未带 --include-secrets 导出的 provider 不含 api_key
+        let mut p = provider("relay", "https://relay.example.com");
+        p.target = AiTarget::Codex;
+        p.api_key.clear();
+        let store = AiProviders {
+            encrypt_keys: false,
+            providers: vec![p],
+            has_plaintext: false,
+        };
+        let s = toml::to_string_pretty(&store.to_stored().unwrap()).unwrap();
+        assert!(!s.contains("api_key"));
+        assert!(s.contains("target = \"codex\""));
+
+        let mut loaded: AiProviders = toml::from_str(&s).unwrap();
+        loaded.decrypt_keys().unwrap();
+        assert!(!loaded.has_plaintext);
+        assert_eq!(loaded.providers[0].target, AiTarget::Codex);
+        assert!(loaded.providers[0].api_key.is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::OnceLock;
+
+/// 目标 CPU 架构（决定下载哪个平台的安装包）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Arch {
+    X64,
+    Arm64,
+}
+
+impl Arch {
+    /// 本机架构（只检测一次）。x64 的 hudo 在 ARM64 Windows 上仿真运行时，仍返回 Arm64
+    pub fn native() -> Self {
+        static NATIVE: OnceLock<Arch> = OnceLock::new();
+        *NATIVE.get_or_init(detect)
+    }
+
+    /// 显示名称
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::X64 => "x64",
+            Self::Arm64 => "ARM64",
+        }
+    }
+
+    fn from_rust_arch(arch: &str) -> Self {
+        match arch {
+            "aarch64" => Self::Arm64,
+            _ => Self::X64,
+        }
+    }
+}
+
+/// IsWow64Process2 返回的是系统原生架构，不受当前进程是否仿真影响；
+/// 旧系统上该 API 不存在时退回编译目标架构
+#[cfg(windows)]
+fn detect() -> Arch {
+    use windows_sys::Win32::System::SystemInformation::IMAGE_FILE_MACHINE_ARM64;
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, IsWow64Process2};
+
+    let mut process_machine = 0u16;
+    let mut native_machine = 0u16;
+    let ok = unsafe {
+        IsWow64Process2(GetCurrentProcess(), &mut process_machine, &mut native_machine)
+    };
+    if ok != 0 {
+        if native_machine == IMAGE_FILE_MACHINE_ARM64 {
+            Arch::Arm64
+        } else {
+            Arch::X64
+        }
+    } else {
+        Arch::from_rust_arch(std::env::consts::ARCH)
+    }
+}
+
+#[cfg(not(windows))]
+fn detect() -> Arch {
+    Arch::from_rust_arch(std::env::consts::ARCH)
+}
@@ -0,0 +1,219 @@
+//! hudo cache info：列出下载缓存目录的内容
+//!
+//! 安装包可以重复使用；`*-extract` 是解压时的临时目录，`*.tmp` 是未完成的下载，
+//! 正常安装结束后都不会留下，留下的是中断后的残留，可以删除。只读，不修改任何文件。
+
+use anyhow::Result;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::config::HudoConfig;
+
+/// 缓存项的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// 下载的安装包，可再次使用
+    Archive,
+    /// 残留的解压目录（*-extract）
+    Extract,
+    /// 未完成的下载（*.tmp）
+    Partial,
+    /// 其他文件或目录
+    Other,
+}
+
+impl EntryKind {
+    /// 中断安装留下的残留，可以删除
+    pub fn is_stale(&self) -> bool {
+        matches!(self, EntryKind::Extract | EntryKind::Partial)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EntryKind::Archive => "安装包",
+            EntryKind::Extract => "残留的解压目录",
+            EntryKind::Partial => "未完成的下载",
+            EntryKind::Other => "其他",
+        }
+    }
+}
+
+/// 缓存目录下的一项（安装包旁的 .meta.json 计入安装包大小，不单独列出）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub name: String,
+    pub kind: EntryKind,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// 按名称判断缓存项类型
+pub fn classify(name: &str, is_dir: bool) -> EntryKind {
+    let lower = name.to_ascii_lowercase();
+    if is_dir {
+        return match lower.ends_with("-extract") {
+            true => EntryKind::Extract,
+            false => EntryKind::Other,
+        };
+    }
+    let archive = [".zip", ".exe", ".msi", ".tar.gz", ".tgz", ".ps1"];
+    if lower.ends_with(".tmp") {
+        EntryKind::Partial
+    } else if archive.iter().any(|ext| lower.ends_with(ext)) {
+        EntryKind::Archive
+    } else {
+        EntryKind::Other
+    }
+}
+
+/// 文件或目录（递归）的总大小，无法读取的项按 0 计
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| dir_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+/// 列出缓存目录的内容，按大小从大到小排列；目录不存在时为空
+pub fn scan(cache_dir: &Path) -> Result<Vec<CacheEntry>> {
+    let Ok(read) = std::fs::read_dir(cache_dir) else {
+        return Ok(Vec::new());
+    };
+    let mut entries = Vec::new();
+    let mut meta_sizes = Vec::new();
+    for item in read.filter_map(|e| e.ok()) {
+        let name = item.file_name().to_string_lossy().to_string();
+        let path = item.path();
+        if let Some(archive) = name.strip_suffix(".meta.json") {
+            meta_sizes.push((archive.to_string(), dir_size(&path)));
+            continue;
+        }
+        let is_dir = path.is_dir();
+        entries.push(CacheEntry {
+            kind: classify(&name, is_dir),
+            size: dir_size(&path),
+            modified: item.metadata().and_then(|m| m.modified()).ok(),
+            name,
+        });
+    }
+    // 来源记录的大小计入对应安装包；安装包已不在时单独列为其他
+    for (archive, size) in meta_sizes {
+        match entries.iter_mut().find(|e| e.name == archive) {
+            Some(entry) => entry.size += size,
+            None => entries.push(CacheEntry {
+                name: format!("{}.meta.json", archive),
+                kind: EntryKind::Other,
+                size,
+                modified: None,
+            }),
+        }
+    }
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    Ok(entries)
+}
+
+/// 距今多久：今天 / N 天前
+pub fn format_age(age: Duration) -> String {
+    match age.as_secs() / 86400 {
+        0 => "今天".to_string(),
+        days => format!("{} 天前", days),
+    }
+}
+
+/// hudo cache info
+pub fn cmd_info(config: &HudoConfig) -> Result<()> {
+    let cache_dir = config.cache_dir();
+    crate::ui::print_title("下载缓存");
+    crate::ui::print_info(&format!("目录: {}", cache_dir.display()));
+
+    let entries = scan(&cache_dir)?;
+    if entries.is_empty() {
+        crate::ui::print_info("缓存为空");
+        return Ok(());
+    }
+
+    let name_width = entries.iter().map(|e| console::measure_text_width(&e.name)).max().unwrap_or(0) + 2;
+    let now = SystemTime::now();
+    println!();
+    for entry in &entries {
+        let age = entry
+            .modified
+            .and_then(|m| now.duration_since(m).ok())
+            .map(format_age)
+            .unwrap_or_default();
+        let kind = match entry.kind {
+            EntryKind::Archive => String::new(),
+            kind if kind.is_stale() => console::style(kind.label()).yellow().to_string(),
+            kind => console::style(kind.label()).dim().to_string(),
+        };
+        println!(
+            "  {}  {}  {}  {}",
+            crate::ui::pad(&entry.name, name_width),
+            crate::ui::pad(&crate::ui::format_size(entry.size), 10),
+            crate::ui::pad(&age, 8),
+            kind
+        );
+    }
+
+    let total: u64 = entries.iter().map(|e| e.size).sum();
+    let stale: u64 = entries.iter().filter(|e| e.kind.is_stale()).map(|e| e.size).sum();
+    println!();
+    crate::ui::print_info(&format!("共 {} 项，{}", entries.len(), crate::ui::format_size(total)));
+    if stale > 0 {
+        crate::ui::print_info(&format!(
+            "其中 {} 是中断安装留下的解压目录或未完成的下载，可以删除",
+            crate::ui::format_size(stale)
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("go1.23.4.windows-amd64.zip", false), EntryKind::Archive);
+        assert_eq!(classify("Git-2.47.0-64-bit.EXE", false), EntryKind::Archive);
+        assert_eq!(classify("vscode-win32-x64.zip.tmp", false), EntryKind::Partial);
+        assert_eq!(classify("vscode-extract", true), EntryKind::Extract);
+        assert_eq!(classify("pgsql-pwfile", false), EntryKind::Other);
+        assert_eq!(classify("fnm", true), EntryKind::Other);
+        assert!(EntryKind::Partial.is_stale() && !EntryKind::Archive.is_stale());
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(Duration::from_secs(3600)), "今天");
+        assert_eq!(format_age(Duration::from_secs(3 * 86400 + 5)), "3 天前");
+    }
+
+    #[test]
+    fn test_scan() {
+        let dir = std::env::temp_dir().join(format!("hudo-test-cache-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("go-extract").join("bin")).unwrap();
+        std::fs::write(dir.join("go-extract").join("bin").join("go.exe"), [0u8; 300]).unwrap();
+        std::fs::write(dir.join("go.zip"), [0u8; 100]).unwrap();
+        std::fs::write(dir.join("go.zip.meta.json"), [0u8; 20]).unwrap();
+        std::fs::write(dir.join("gone.zip.meta.json"), [0u8; 5]).unwrap();
+
+        let entries = scan(&dir).unwrap();
+        let summary: Vec<_> = entries.iter().map(|e| (e.name.as_str(), e.kind, e.size)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("go-extract", EntryKind::Extract, 300),
+                ("go.zip", EntryKind::Archive, 120),
+                ("gone.zip.meta.json", EntryKind::Other, 5),
+            ]
+        );
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(scan(&dir).unwrap().is_empty());
+    }
+}
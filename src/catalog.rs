@@ -0,0 +1,122 @@
+//! hudo catalog：输出全部工具的元数据，供外部系统（如入职门户）渲染工具目录
+//!
+//! JSON 结构带 `format` 版本号，字段只增不改；修改已有字段时需要把 CATALOG_FORMAT 加一。
+
+use anyhow::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::arch::Arch;
+use crate::config::HudoConfig;
+use crate::installer::{EnvAction, Installer};
+use crate::ui::ToolCategory;
+
+/// catalog JSON 的结构版本
+pub const CATALOG_FORMAT: u32 = 1;
+
+/// 下载地址与环境变量中的占位符：安装根目录、工具的安装目录
+pub const ROOT_PLACEHOLDER: &str = "{root_dir}";
+pub const INSTALL_PLACEHOLDER: &str = "{install_dir}";
+
+#[derive(Debug, Serialize)]
+pub struct Catalog {
+    pub format: u32,
+    pub hudo_version: &'static str,
+    pub tools: Vec<ToolEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ToolEntry {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub homepage: Option<&'static str>,
+    /// tool / language / database / ide
+    pub category: &'static str,
+    pub category_label: &'static str,
+    /// 查询最新版本失败时使用的内置版本，总是安装最新版的工具为 null
+    pub default_version: Option<&'static str>,
+    /// 可用 hudo install --pick 选择版本
+    pub pickable: bool,
+    /// 默认配置（无镜像、x64）下的下载地址；版本号为内置版本
+    pub download_url: String,
+    pub dependencies: &'static [&'static str],
+    pub env: Vec<EnvEntry>,
+    pub service: Option<&'static str>,
+    pub arm64_native: bool,
+}
+
+/// 安装后写入的环境变量
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EnvEntry {
+    Set { name: String, value: String },
+    Path { path: String },
+}
+
+/// 用于生成 catalog 的默认配置：根目录为占位符，固定 x64，不含镜像与版本覆盖
+fn catalog_config() -> HudoConfig {
+    let mut config: HudoConfig = toml::from_str(&format!("root_dir = '{}'", ROOT_PLACEHOLDER))
+        .expect("默认配置可以解析");
+    config.arch_override = Some(Arch::X64);
+    config
+}
+
+pub fn build(installers: &[Box<dyn Installer>]) -> Catalog {
+    let config = catalog_config();
+    let install_path = PathBuf::from(INSTALL_PLACEHOLDER);
+    let tools = installers
+        .iter()
+        .map(|inst| {
+            let info = inst.info();
+            let category = ToolCategory::from_id(info.id);
+            let env = inst
+                .env_actions(&install_path, &config)
+                .into_iter()
+                .map(|action| match action {
+                    EnvAction::Set { name, value } => EnvEntry::Set { name, value },
+                    EnvAction::AppendPath { path } => EnvEntry::Path { path },
+                })
+                .collect();
+            ToolEntry {
+                id: info.id,
+                name: info.name,
+                description: info.description,
+                homepage: info.homepage,
+                category: category.key(),
+                category_label: category.label(),
+                default_version: inst.default_version(),
+                pickable: crate::version::PICKABLE_TOOLS.contains(&info.id),
+                download_url: inst.resolve_download(&config).0,
+                dependencies: inst.dependencies(),
+                env,
+                service: inst.service_name(),
+                arm64_native: inst.arm64_native(),
+            }
+        })
+        .collect();
+    Catalog { format: CATALOG_FORMAT, hudo_version: env!("CARGO_PKG_VERSION"), tools }
+}
+
+/// hudo catalog [--json]
+pub fn cmd_catalog(json: bool) -> Result<()> {
+    let catalog = build(&crate::installer::all_installers());
+    if json {
+        println!("{}", serde_json::to_string_pretty(&catalog)?);
+        return Ok(());
+    }
+
+    crate::ui::print_title("工具目录");
+    for tool in &catalog.tools {
+        println!(
+            "  {}  {}  {}  {}",
+            crate::ui::pad(tool.id, 12),
+            crate::ui::pad(tool.category_label, 12),
+            crate::ui::pad(tool.name, 20),
+            console::style(tool.default_version.unwrap_or("最新")).dim()
+        );
+    }
+    println!();
+    crate::ui::print_info("hudo catalog --json 输出完整元数据（下载地址、环境变量、依赖等）");
+    Ok(())
+}
@@ -1,5 +1,11 @@
 use clap::{Parser, Subcommand};
 
+use crate::ai::AiTarget;
+use crate::arch::Arch;
+use crate::env::ExportFormat;
+use crate::list::ListSort;
+use crate::registry::ExternalPolicy;
+
 #[derive(Parser)]
 #[command(name = "hudo", version, about = "混沌 - 开发环境一键引导工具", disable_version_flag = true)]
 pub struct Cli {
@@ -7,6 +13,38 @@ pub struct Cli {
     #[arg(short = 'v', long, action = clap::ArgAction::Version)]
     version: Option<bool>,
 
+    /// 选择安装磁盘时同时列出可移动磁盘与网络驱动器
+    #[arg(long, global = true)]
+    pub all_drives: bool,
+
+    /// 允许把安装目录设在 OneDrive / Dropbox 同步目录、网络驱动器或可移动磁盘上
+    #[arg(long, global = true)]
+    pub allow_synced_root: bool,
+
+    /// 下载缓存目录（默认 {root_dir}\cache，也可用 HUDO_CACHE_DIR 环境变量指定）
+    #[arg(long, global = true, value_name = "PATH")]
+    pub cache_dir: Option<std::path::PathBuf>,
+
+    /// 安装包的目标架构（默认自动检测；ARM64 原生版本有问题时可用 --arch x64）
+    #[arg(long, global = true, value_enum)]
+    pub arch: Option<Arch>,
+
+    /// 本次运行的下载限速（如 512K、5MB/s，0 为不限速），覆盖配置文件中的 max_download_bps
+    #[arg(long, global = true, value_name = "RATE", value_parser = parse_limit_rate)]
+    pub limit_rate: Option<u64>,
+
+    /// 用 ASCII 符号代替 emoji 与制表符（默认在传统控制台的非 UTF-8 代码页下自动启用，也可设 HUDO_ASCII=1）
+    #[arg(long, global = true)]
+    pub ascii: bool,
+
+    /// 不修改用户环境变量与 PATH，只在配置目录生成会话脚本 hudo-env.ps1 / hudo-env.cmd（便携模式下可在 hudo.toml 中设 no_registry = true）
+    #[arg(long, global = true)]
+    pub no_registry: bool,
+
+    /// 界面语言（zh / en），覆盖配置文件中的 lang；默认按系统语言
+    #[arg(long, global = true, value_enum)]
+    pub lang: Option<crate::lang::Lang>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -14,35 +52,127 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
     /// 交互式多选安装开发工具
-    Setup,
+    Setup {
+        /// 不分类，在全部工具中按关键字筛选后多选
+        #[arg(long)]
+        flat: bool,
+    },
     /// 安装单个工具
     Install {
-        /// 工具名称（git, uv, nodejs, bun, rust, go, jdk, c, miniconda, mysql, pgsql, vscode, pycharm）
-        tool: String,
+        /// 工具名称，不填时按分类列出全部可安装的工具
+        #[arg(value_parser = ToolIdParser)]
+        tool: Option<String>,
+        /// 从最近的发布版本中选择要安装的版本（git, gh, go, pgsql, maven, gradle, pycharm）
+        #[arg(long)]
+        pick: bool,
+        /// 忽略已记录的选择，重新询问是否接管系统已有的版本
+        #[arg(long)]
+        ask_again: bool,
+        /// 只列出会一并安装的依赖（如 maven → jdk）及是否已满足，不执行安装
+        #[arg(long, conflicts_with = "pick")]
+        list_deps: bool,
+        /// 不询问，直接应用环境变量更改并执行接管清理；配置失败时保留已安装的文件
+        #[arg(short, long)]
+        yes: bool,
+        /// 系统已有该工具时只列出接管将清理的内容，不做任何更改
+        #[arg(long, conflicts_with = "list_deps")]
+        dry_run: bool,
+        /// 本次安装使用的发布渠道（如 nodejs 的 lts / current），不写入配置文件
+        #[arg(long)]
+        channel: Option<String>,
     },
     /// 卸载由 hudo 安装的工具，或卸载 hudo 自身
     Uninstall {
-        /// 工具名称（与 --self 二选一）
+        /// 工具名称（与 --all / --self 三选一）
         tool: Option<String>,
         /// 卸载 hudo 自身
-        #[arg(long = "self")]
+        #[arg(long = "self", conflicts_with_all = ["tool", "all"])]
         uninstall_self: bool,
+        /// 卸载 hudo 管理的全部工具
+        #[arg(long, conflicts_with = "tool")]
+        all: bool,
+        /// 跳过确认
+        #[arg(short, long)]
+        yes: bool,
+        /// 保留用户数据（VS Code 的扩展与设置、数据库数据目录），重新安装后恢复
+        #[arg(long, conflicts_with = "uninstall_self")]
+        keep_config: bool,
     },
     /// 列出所有工具及安装状态
     List {
         /// 显示所有工具（含未安装）
         #[arg(long)]
         all: bool,
+        /// 跳过 state.json 快速检测，重新探测所有工具并校正记录的版本
+        #[arg(long)]
+        verify: bool,
+        /// 只显示一个分类：tool、language、database、ide，或中文名称（如 数据库）
+        #[arg(long)]
+        category: Option<String>,
+        /// 排序方式（不指定时按分类分组）
+        #[arg(long, value_enum)]
+        sort: Option<ListSort>,
+        /// 以 JSON 输出（筛选与排序同上）
+        #[arg(long)]
+        json: bool,
+    },
+    /// 显示工具的说明、官网、安装状态与安装后的提示
+    Info {
+        /// 工具名称
+        tool: String,
+    },
+    /// 列出全部工具的元数据（分类、内置版本、下载地址、环境变量、依赖）
+    Catalog {
+        /// 以 JSON 输出（结构见 format 字段，供外部系统使用）
+        #[arg(long)]
+        json: bool,
     },
     /// 导出环境档案（已安装工具 + 配置）
     Export {
-        /// 输出文件路径（默认 hudo-profile.toml）
+        /// 输出文件路径（默认 hudo-profile.toml，--target wsl 时为 hudo-wsl.sh）
         file: Option<String>,
+        /// 导出目标：hudo 档案，或在 WSL 中安装同一组工具的 bash 脚本（尽力而为）
+        #[arg(long, value_enum, default_value = "hudo")]
+        target: ExportTarget,
+        /// 导出 Claude Code provider 的 API Key（明文写入档案）
+        #[arg(long)]
+        include_secrets: bool,
+        /// 额外写入 [lock] 段：各工具的精确版本、下载地址和安装包 SHA256
+        #[arg(long)]
+        lock: bool,
+        /// 不询问，直接写入文件
+        #[arg(short, long)]
+        yes: bool,
+        /// 不写入导出时间，同样的环境每次导出的文件相同（便于提交到 git 对比）
+        #[arg(long)]
+        no_timestamp: bool,
     },
     /// 从环境档案导入并安装工具
     Import {
         /// profile 文件路径
         file: String,
+        /// 严格按 [lock] 段安装：下载地址或 SHA256 不一致时失败
+        #[arg(long)]
+        frozen: bool,
+        /// 逐个询问是否接管系统已有的版本（默认按已记录的选择处理）
+        #[arg(long)]
+        ask_again: bool,
+        /// 本地版本与档案不一致时一律安装档案中的版本
+        #[arg(long, conflicts_with = "prefer_local")]
+        strict: bool,
+        /// 本地版本与档案不一致时一律保留本地版本
+        #[arg(long)]
+        prefer_local: bool,
+    },
+    /// 固定工具的当前版本：hudo import 不会切换它的版本
+    Pin {
+        /// 工具名称（需由 hudo 安装）
+        tool: String,
+    },
+    /// 取消固定
+    Unpin {
+        /// 工具名称
+        tool: String,
     },
     /// 配置管理
     Config {
@@ -51,8 +181,66 @@ pub enum Commands {
     },
     /// 更新 hudo 到最新版本
     Update,
-    /// 管理 Claude Code API 来源（切换/添加/删除 Provider）
-    Cc,
+    /// 管理 Claude Code API 来源（切换/添加/删除 Provider），等同于 hudo ai claude
+    Cc {
+        #[command(subcommand)]
+        action: Option<CcAction>,
+    },
+    /// 管理 AI 命令行工具的 API 来源（Claude Code / Codex CLI / Gemini CLI）
+    Ai {
+        /// 目标工具，不填则交互选择
+        #[arg(value_enum)]
+        target: Option<AiTarget>,
+    },
+    /// 扫描注册表与 winget，列出系统中已安装的开发工具
+    Scan,
+    /// 检查 hudo 管理的工具（快捷方式、PATH 冲突等）
+    Doctor {
+        /// 修复可自动修复的问题（如重建缺失的开始菜单快捷方式）
+        #[arg(long)]
+        fix: bool,
+    },
+    /// 一屏概览：版本、配置、安装目录与磁盘空间、已安装工具、缓存、服务状态与环境检查（提交问题时附上）
+    Status {
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+    },
+    /// 按 PATH 查找顺序列出命令的全部位置，标出实际生效的一个及其来源
+    Which {
+        /// 命令名（如 node、java、git）
+        command: String,
+    },
+    /// 导出 hudo 设置的环境变量（供 CI、devcontainer 使用）
+    Env {
+        #[command(subcommand)]
+        action: EnvVarAction,
+    },
+    /// 启动 / 停止 / 查看数据库等工具注册的 Windows 服务
+    Service {
+        /// 工具名称（mysql、pgsql、redis）
+        tool: String,
+        /// 操作
+        #[arg(value_enum)]
+        action: ServiceAction,
+    },
+    /// 镜像管理
+    Mirrors {
+        #[command(subcommand)]
+        action: MirrorsAction,
+    },
+    /// WSL：检查 hudo 的 PATH 是否泄漏进 WSL 发行版
+    Wsl {
+        #[command(subcommand)]
+        action: WslAction,
+    },
+    /// 下载缓存
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// 彻底清除 hudo：卸载全部工具，删除安装根目录与 ~/.hudo，清理环境变量后卸载自身
+    Nuke,
 }
 
 #[derive(Subcommand)]
@@ -65,9 +253,165 @@ pub enum ConfigAction {
         key: String,
         /// 配置值
         value: String,
+        /// 修改 root_dir 时不询问（旧目录中已安装的工具将不再由 hudo 管理）
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// 把已安装的工具整体迁移到新的安装目录：移动文件，更新安装记录与 root_dir，再重设环境变量与快捷方式
+    MigrateRoot {
+        /// 新的安装目录
+        dir: String,
+        /// 不询问，直接迁移（仍在运行的程序会被关闭）
+        #[arg(short, long)]
+        yes: bool,
     },
     /// 用编辑器打开配置文件
     Edit,
+    /// 显示配置文件、状态文件等路径
+    Path,
+    /// 导出 config.toml 到指定文件（不含已安装工具）
+    Export {
+        /// 输出文件路径
+        file: String,
+    },
+    /// 从文件导入 config.toml（校验格式后覆盖当前配置）
+    Import {
+        /// 配置文件路径
+        file: String,
+    },
     /// 重置配置为默认值
     Reset,
+    /// 设置系统已有工具的接管策略（ask 每次询问 / ignore 保留系统版本 / takeover 由 hudo 接管）
+    ExternalPolicy {
+        /// 工具名称
+        tool: String,
+        /// 策略
+        #[arg(value_enum)]
+        policy: ExternalPolicy,
+    },
+    /// 把安装目录（或其中的部分目录）加入 Windows Defender 排除项，需管理员权限
+    DefenderExclude {
+        /// 移除 hudo 添加过的排除项
+        #[arg(long)]
+        remove: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum EnvVarAction {
+    /// 输出已安装工具设置的环境变量（JAVA_HOME、GOROOT 等）与 PATH 追加项
+    Export {
+        /// 只导出指定工具
+        #[arg(long)]
+        tool: Option<String>,
+        /// 输出格式
+        #[arg(long, value_enum, default_value = "dotenv")]
+        format: ExportFormat,
+    },
+    /// 应用安装时选择暂不应用的环境变量更改
+    Apply {
+        /// 只应用指定工具
+        tool: Option<String>,
+    },
+    /// 恢复最近一次接管时移除的环境变量与 PATH 项
+    Restore {
+        /// 只恢复指定工具最近一次的记录
+        tool: Option<String>,
+    },
+    /// 从系统 PATH 移除条目（--restore 时加回），需管理员权限；由接管与恢复流程提权调用
+    #[command(hide = true)]
+    SystemPath {
+        #[arg(long)]
+        restore: bool,
+        #[arg(required = true)]
+        entries: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MirrorsAction {
+    /// 检查各镜像（未设置时为官方地址）能否访问：状态码、耗时与跳转目标
+    Test,
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// 列出缓存目录中的文件、大小与时间，标出可以删除的残留（只读）
+    Info,
+}
+
+#[derive(Subcommand)]
+pub enum WslAction {
+    /// 检查各发行版的 PATH 中是否有 hudo 安装的 Windows 工具，并给出过滤方法
+    Check,
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ExportTarget {
+    /// hudo 环境档案（TOML），可用 hudo import 导入
+    Hudo,
+    /// 在 WSL 中安装 Linux 版本的 bash 脚本
+    Wsl,
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ServiceAction {
+    /// 启动服务
+    Start,
+    /// 停止服务
+    Stop,
+    /// 查看服务状态
+    Status,
+}
+
+#[derive(Subcommand)]
+pub enum CcAction {
+    /// 测试 Provider 连接（默认测试当前激活的 Provider）
+    Test {
+        /// Provider 名称
+        name: Option<String>,
+    },
+    /// 导出全部 Provider 到文件（可设置口令加密）
+    Export {
+        /// 输出文件路径
+        file: String,
+    },
+    /// 从文件导入 Provider（与现有合并，同名冲突时逐个询问）
+    Import {
+        /// 文件路径
+        file: String,
+        /// 导入后切换到指定 Provider
+        #[arg(long)]
+        activate: Option<String>,
+    },
+}
+
+/// 工具名称：可选值在运行时取自 all_installers()，用于 --help 与补全；
+/// 不在其中的名称也原样接受，由命令给出按分类分组的可用列表
+#[derive(Clone)]
+pub struct ToolIdParser;
+
+impl clap::builder::TypedValueParser for ToolIdParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<String, clap::Error> {
+        clap::builder::StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
+        let values: Vec<clap::builder::PossibleValue> = crate::installer::all_installers()
+            .iter()
+            .map(|i| clap::builder::PossibleValue::new(i.info().id).help(i.info().name))
+            .collect();
+        Some(Box::new(values.into_iter()))
+    }
+}
+
+fn parse_limit_rate(value: &str) -> Result<u64, String> {
+    crate::download::parse_rate(value).ok_or_else(|| "需要字节数，可带 K / M / G 后缀（如 2M、5MB/s）".to_string())
 }
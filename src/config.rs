@@ -1,23 +1,113 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
+use crate::arch::Arch;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HudoConfig {
     /// 安装根目录（如 D:\hudo）
     pub root_dir: String,
 
+    /// 打开交互菜单时检查 hudo 新版本（每天最多一次）
+    #[serde(default = "default_true")]
+    pub check_updates: bool,
+
+    /// 安装 MySQL / PostgreSQL / Redis 时是否注册并启动 Windows 服务，关闭后只初始化、需手动启动
+    #[serde(default = "default_true")]
+    pub db_autoservice: bool,
+
+    #[serde(default)]
+    pub git: GitConfig,
+
     #[serde(default)]
     pub java: JavaConfig,
 
     #[serde(default)]
     pub go: GoConfig,
 
+    #[serde(default)]
+    pub mysql: MysqlConfig,
+
+    #[serde(default)]
+    pub pgsql: PgsqlConfig,
+
+    #[serde(default)]
+    pub install: InstallConfig,
+
     #[serde(default)]
     pub versions: VersionConfig,
 
     #[serde(default)]
     pub mirrors: MirrorConfig,
+
+    /// 工具 id → 发布渠道（如 nodejs = "current"），未设置时使用安装器的默认渠道
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub channels: BTreeMap<String, String>,
+
+    /// 安装 GUI 工具时是否创建桌面快捷方式，不设置则每次询问
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desktop_shortcuts: Option<bool>,
+
+    /// GitHub API token，用于提高版本查询的限流额度（GITHUB_TOKEN / GH_TOKEN 环境变量优先）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_token: Option<String>,
+
+    /// 界面语言（zh / en），不设置则按系统语言
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+
+    /// 下载限速（字节/秒），不设置则不限速
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_download_bps: Option<u64>,
+
+    /// 命令行 --cache-dir / HUDO_CACHE_DIR 指定的下载缓存目录（不写入配置文件）
+    #[serde(skip)]
+    pub cache_dir_override: Option<PathBuf>,
+
+    /// 命令行 --arch 指定的目标架构（不写入配置文件）
+    #[serde(skip)]
+    pub arch_override: Option<Arch>,
+
+    /// 命令行 --ask-again：忽略已记录的接管选择，重新询问（不写入配置文件）
+    #[serde(skip)]
+    pub ask_external_again: bool,
+
+    /// 已由 hudo 安装时仍重新安装，用于切换到固定的版本（不写入配置文件）
+    #[serde(skip)]
+    pub reinstall: bool,
+
+    /// 命令行 -y：不询问直接应用环境变量更改，安装子进程卡住时也不询问（不写入配置文件）
+    #[serde(skip)]
+    pub yes: bool,
+
+    /// 命令行 --dry-run：只列出接管系统已有版本时将清理的内容（不写入配置文件）
+    #[serde(skip)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GitConfig {
+    /// 安装方式：installer（官方安装程序）或 portable（PortableGit 绿色版）
+    #[serde(default = "default_git_install_mode")]
+    pub install_mode: String,
+}
+
+impl Default for GitConfig {
+    fn default() -> Self {
+        Self {
+            install_mode: default_git_install_mode(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_git_install_mode() -> String {
+    "installer".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -56,6 +146,106 @@ fn default_go_version() -> String {
     "latest".to_string()
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MysqlConfig {
+    /// 监听端口，系统中已有 MySQL 占用 3306 时可改用其他端口
+    #[serde(default = "default_mysql_port")]
+    pub port: u16,
+}
+
+impl Default for MysqlConfig {
+    fn default() -> Self {
+        Self {
+            port: default_mysql_port(),
+        }
+    }
+}
+
+fn default_mysql_port() -> u16 {
+    3306
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PgsqlConfig {
+    /// 监听端口，系统中已有 PostgreSQL 占用 5432 时可改用其他端口
+    #[serde(default = "default_pgsql_port")]
+    pub port: u16,
+}
+
+impl Default for PgsqlConfig {
+    fn default() -> Self {
+        Self {
+            port: default_pgsql_port(),
+        }
+    }
+}
+
+pub fn default_pgsql_port() -> u16 {
+    5432
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstallConfig {
+    /// 安装步骤的子进程超过多少分钟既无输出也未退出时询问如何处理，0 表示一直等待
+    #[serde(default = "default_idle_minutes")]
+    pub idle_minutes: u64,
+    /// msiexec、Git、Miniconda 等静默安装程序的无输出超时（分钟），不设置时为 idle_minutes 的 3 倍
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub silent_idle_minutes: Option<u64>,
+}
+
+impl Default for InstallConfig {
+    fn default() -> Self {
+        Self {
+            idle_minutes: default_idle_minutes(),
+            silent_idle_minutes: None,
+        }
+    }
+}
+
+fn default_idle_minutes() -> u64 {
+    10
+}
+
+/// msiexec、静默安装程序本来就没有输出，未设置 silent_idle_minutes 时等待时间放宽为几倍
+const SILENT_INSTALLER_FACTOR: u32 = 3;
+
+/// -y 时不询问：卡住后再等几个周期仍没有输出则中止
+const UNATTENDED_WAIT_ROUNDS: u32 = 2;
+
+impl InstallConfig {
+    /// 有输出的安装步骤（fnm install、rustup-init 等）的无输出超时
+    pub fn idle_timeout(&self) -> Option<std::time::Duration> {
+        (self.idle_minutes > 0).then(|| std::time::Duration::from_secs(self.idle_minutes * 60))
+    }
+
+    /// msiexec /qn、/VERYSILENT 等静默安装程序的无输出超时
+    pub fn silent_idle_timeout(&self) -> Option<std::time::Duration> {
+        match self.silent_idle_minutes {
+            Some(0) => None,
+            Some(m) => Some(std::time::Duration::from_secs(m * 60)),
+            None => self.idle_timeout().map(|d| d * SILENT_INSTALLER_FACTOR),
+        }
+    }
+
+    /// 有输出的安装步骤的 watchdog 设置，yes 为命令行 -y（卡住时不询问）
+    pub fn watch(&self, yes: bool) -> crate::watchdog::Watch {
+        crate::watchdog::Watch { idle: self.idle_timeout(), silent: false, on_stall: on_stall(yes) }
+    }
+
+    /// 静默安装程序的 watchdog 设置
+    pub fn silent_watch(&self, yes: bool) -> crate::watchdog::Watch {
+        crate::watchdog::Watch { idle: self.silent_idle_timeout(), silent: true, on_stall: on_stall(yes) }
+    }
+}
+
+fn on_stall(yes: bool) -> crate::watchdog::OnStall {
+    match yes {
+        true => crate::watchdog::OnStall::Wait(UNATTENDED_WAIT_ROUNDS),
+        false => crate::watchdog::OnStall::Ask,
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct MirrorConfig {
     pub uv: Option<String>,
@@ -69,6 +259,124 @@ pub struct MirrorConfig {
     pub maven: Option<String>,
     pub gradle: Option<String>,
     pub redis: Option<String>,
+    pub sqlite: Option<String>,
+}
+
+type MirrorGetter = fn(&MirrorConfig) -> &Option<String>;
+type MirrorSetter = fn(&mut MirrorConfig) -> &mut Option<String>;
+
+/// 镜像键表：config set/show、交互菜单、profile 导入导出共用，新增镜像只需加一行
+const MIRROR_FIELDS: &[(&str, MirrorGetter, MirrorSetter)] = &[
+    ("uv", |m| &m.uv, |m| &mut m.uv),
+    ("fnm", |m| &m.fnm, |m| &mut m.fnm),
+    ("go", |m| &m.go, |m| &mut m.go),
+    ("java", |m| &m.java, |m| &mut m.java),
+    ("vscode", |m| &m.vscode, |m| &mut m.vscode),
+    ("pycharm", |m| &m.pycharm, |m| &mut m.pycharm),
+    ("mysql", |m| &m.mysql, |m| &mut m.mysql),
+    ("pgsql", |m| &m.pgsql, |m| &mut m.pgsql),
+    ("maven", |m| &m.maven, |m| &mut m.maven),
+    ("gradle", |m| &m.gradle, |m| &mut m.gradle),
+    ("redis", |m| &m.redis, |m| &mut m.redis),
+    ("sqlite", |m| &m.sqlite, |m| &mut m.sqlite),
+];
+
+impl MirrorConfig {
+    /// 所有镜像键（不含 "mirrors." 前缀）
+    pub fn keys() -> impl Iterator<Item = &'static str> {
+        MIRROR_FIELDS.iter().map(|(k, _, _)| *k)
+    }
+
+    /// 按键读取镜像值，未知键返回 None
+    pub fn get(&self, key: &str) -> Option<&String> {
+        MIRROR_FIELDS
+            .iter()
+            .find(|(k, _, _)| *k == key)
+            .and_then(|(_, get, _)| get(self).as_ref())
+    }
+
+    /// 按键设置镜像值，未知键返回 false
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        match MIRROR_FIELDS.iter().find(|(k, _, _)| *k == key) {
+            Some((_, _, field)) => {
+                *field(self) = Some(value.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// versions.* 可用的键（go 对应 go.version，其余对应 VersionConfig 的字段）
+pub const VERSION_KEYS: &[&str] =
+    &["git", "gh", "go", "fnm", "mysql", "pgsql", "pycharm", "maven", "gradle", "claude_code", "redis"];
+
+type VersionGetter = fn(&HudoConfig) -> Option<&str>;
+type VersionSetter = fn(&mut HudoConfig, Option<String>);
+
+/// 固定版本键表：VERSION_KEYS 加上 JDK 主版本号 java；version_of、set_version_key、pin_version 共用，
+/// 值为 None 表示取消指定（go 恢复 latest，java 恢复默认主版本）
+const VERSION_FIELDS: &[(&str, VersionGetter, VersionSetter)] = &[
+    ("git", |c| c.versions.git.as_deref(), |c, v| c.versions.git = v),
+    ("gh", |c| c.versions.gh.as_deref(), |c, v| c.versions.gh = v),
+    (
+        "go",
+        |c| Some(c.go.version.as_str()).filter(|v| !v.is_empty() && *v != "latest"),
+        |c, v| c.go.version = v.unwrap_or_else(default_go_version),
+    ),
+    ("fnm", |c| c.versions.fnm.as_deref(), |c, v| c.versions.fnm = v),
+    ("mysql", |c| c.versions.mysql.as_deref(), |c, v| c.versions.mysql = v),
+    ("pgsql", |c| c.versions.pgsql.as_deref(), |c, v| c.versions.pgsql = v),
+    ("pycharm", |c| c.versions.pycharm.as_deref(), |c, v| c.versions.pycharm = v),
+    ("maven", |c| c.versions.maven.as_deref(), |c, v| c.versions.maven = v),
+    ("gradle", |c| c.versions.gradle.as_deref(), |c, v| c.versions.gradle = v),
+    ("claude_code", |c| c.versions.claude_code.as_deref(), |c, v| c.versions.claude_code = v),
+    ("redis", |c| c.versions.redis.as_deref(), |c, v| c.versions.redis = v),
+    (
+        "java",
+        |c| Some(c.java.version.as_str()),
+        |c, v| c.java.version = v.map(|v| java_major(&v)).unwrap_or_else(default_java_version),
+    ),
+];
+
+/// JDK 版本号中的主版本号："21.0.5+11" → "21"，旧式 "1.8.0_392" → "8"，无法解析时原样返回
+fn java_major(version: &str) -> String {
+    let normalized = crate::version::normalize_version(version).unwrap_or_else(|| version.to_string());
+    let mut parts = normalized.split('.');
+    match (parts.next(), parts.next()) {
+        (Some("1"), Some(minor)) => minor.to_string(),
+        (Some(major), _) => major.to_string(),
+        _ => normalized,
+    }
+}
+
+/// 可以固定版本的配置键（config set 与交互菜单「固定版本」共用）及其 versions 键：
+/// versions.<键>（go 写作 go.version），以及 JDK 主版本号 java.version
+pub fn pin_keys() -> Vec<(String, &'static str)> {
+    VERSION_KEYS
+        .iter()
+        .map(|&k| match k {
+            "go" => ("go.version".to_string(), k),
+            _ => (format!("versions.{}", k), k),
+        })
+        .chain(std::iter::once(("java.version".to_string(), "java")))
+        .collect()
+}
+
+fn version_field(key: &str) -> Option<&'static (&'static str, VersionGetter, VersionSetter)> {
+    VERSION_FIELDS.iter().find(|(k, _, _)| *k == key)
+}
+
+/// 工具 id 对应的固定版本配置键及其 versions 键（如 gh → versions.gh、nodejs → versions.fnm、jdk → java.version），
+/// 版本不由配置决定的工具为 None
+pub fn version_key_for(tool_id: &str) -> Option<(String, &'static str)> {
+    let key = match tool_id {
+        "nodejs" => "fnm",
+        "jdk" => "java",
+        "claude-code" => "claude_code",
+        id => id,
+    };
+    pin_keys().into_iter().find(|(_, k)| *k == key)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -86,10 +394,9 @@ pub struct VersionConfig {
 }
 
 impl HudoConfig {
-    /// 配置文件路径: %USERPROFILE%\.hudo\config.toml
+    /// 配置文件路径: %USERPROFILE%\.hudo\config.toml（便携模式见 paths 模块）
     pub fn config_path() -> Result<PathBuf> {
-        let home = dirs::home_dir().context("无法获取用户主目录")?;
-        Ok(home.join(".hudo").join("config.toml"))
+        crate::paths::config_path()
     }
 
     /// 加载配置文件，不存在则返回 None
@@ -98,29 +405,106 @@ impl HudoConfig {
         if !path.exists() {
             return Ok(None);
         }
-        let content = std::fs::read_to_string(&path)
+        Ok(Some(Self::load_from(&path)?))
+    }
+
+    /// 从任意路径加载并校验配置文件（用于 config import）
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
             .with_context(|| format!("无法读取配置文件: {}", path.display()))?;
-        let config: HudoConfig = toml::from_str(&content)
+        let config: Self = toml::from_str(&content)
             .with_context(|| format!("配置文件格式错误: {}", path.display()))?;
-        Ok(Some(config))
+        config
+            .validate()
+            .with_context(|| format!("配置文件有误: {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// 检查配置值中的模板语法（加载和保存时调用）
+    pub fn validate(&self) -> Result<()> {
+        for key in MirrorConfig::keys() {
+            if let Some(value) = self.mirrors.get(key) {
+                crate::template::check(value, crate::template::MIRROR_PLACEHOLDERS)
+                    .with_context(|| format!("mirrors.{} 的值无效", key))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 展开镜像地址中的 %环境变量% 与 {version} / {tool} / {root}，并去掉末尾的 /
+    pub fn expand_mirror(&self, base: &str, version: &str, tool: &str) -> String {
+        let root = self.root_path().to_string_lossy().to_string();
+        let vars = [("version", version), ("tool", tool), ("root", root.as_str())];
+        crate::template::expand(base, &vars, |name| std::env::var(name).ok())
+            .unwrap_or_else(|_| base.to_string())
+            .trim_end_matches('/')
+            .to_string()
     }
 
     /// 保存配置到文件
     pub fn save(&self) -> Result<()> {
+        self.validate()?;
         let path = Self::config_path()?;
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)
                 .with_context(|| format!("无法创建配置目录: {}", parent.display()))?;
         }
+        self.save_to(&path)
+    }
+
+    /// 保存配置到指定路径（用于 config export）
+    pub fn save_to(&self, path: &Path) -> Result<()> {
         let content = toml::to_string_pretty(self).context("序列化配置失败")?;
-        std::fs::write(&path, content)
+        std::fs::write(path, content)
             .with_context(|| format!("无法写入配置文件: {}", path.display()))?;
         Ok(())
     }
 
-    /// 从 root_dir 派生各子目录
+    /// 工具使用的发布渠道，未设置时为 None（安装器使用默认渠道）
+    pub fn channel(&self, tool_id: &str) -> Option<&str> {
+        self.channels.get(tool_id).map(String::as_str)
+    }
+
+    /// versions.<键> 的当前值；go 存放在 go.version，"latest" 视为未指定
+    pub fn version_of(&self, key: &str) -> Option<&str> {
+        version_field(key).filter(|_| VERSION_KEYS.contains(&key)).and_then(|(_, get, _)| get(self))
+    }
+
+    /// pin_keys() 中配置键的固定值，未固定时为 None（java.version 总有值，默认为 21）
+    pub fn pinned(&self, key: &str) -> Option<&str> {
+        match key {
+            "java.version" => Some(self.java.version.as_str()),
+            "go.version" => self.version_of("go"),
+            _ => self.version_of(key.strip_prefix("versions.")?),
+        }
+    }
+
+    /// 设置 versions.<键>，值为 latest 时取消指定；未知键返回 false
+    pub fn set_version_key(&mut self, key: &str, value: &str) -> bool {
+        match version_field(key).filter(|_| VERSION_KEYS.contains(&key)) {
+            Some((_, _, set)) => {
+                set(self, Some(value.trim().to_string()).filter(|v| !v.is_empty() && v != "latest"));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 为本次安装临时固定工具版本（如 hudo install --pick 选中的版本），JDK 取主版本号；
+    /// 版本不由配置决定的工具返回 false
+    pub fn pin_version(&mut self, tool_id: &str, version: &str) -> bool {
+        match version_key_for(tool_id).and_then(|(_, key)| version_field(key)) {
+            Some((_, _, set)) => {
+                set(self, Some(version.to_string()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// root_dir 的实际路径（各子目录由此派生）：展开其中的 %VAR%，便携模式下相对路径按 exe 所在目录解析
     pub fn root_path(&self) -> PathBuf {
-        PathBuf::from(&self.root_dir)
+        crate::paths::resolve_root(Path::new(&crate::template::expand_env(&self.root_dir)))
     }
 
     pub fn tools_dir(&self) -> PathBuf {
@@ -135,14 +519,40 @@ impl HudoConfig {
         self.root_path().join("ide")
     }
 
+    /// 下载缓存目录：默认 {root_dir}\cache，可通过 --cache-dir / HUDO_CACHE_DIR 覆盖
     pub fn cache_dir(&self) -> PathBuf {
-        self.root_path().join("cache")
+        match &self.cache_dir_override {
+            Some(dir) => dir.clone(),
+            None => self.root_path().join("cache"),
+        }
+    }
+
+    /// 安装包的目标架构：--arch 优先，否则为本机架构
+    pub fn arch(&self) -> Arch {
+        self.arch_override.unwrap_or_else(Arch::native)
+    }
+
+    /// uninstall --keep-config 保留的用户数据：{root_dir}\kept\<工具 id>，重新安装后移回
+    pub fn kept_dir(&self, tool_id: &str) -> PathBuf {
+        self.root_path().join("kept").join(tool_id)
     }
 
     pub fn state_path(&self) -> PathBuf {
         self.root_path().join("state.json")
     }
 
+    /// root_dir 所在磁盘不存在时返回盘符（如 "E:"），例如移动硬盘已拔出
+    pub fn missing_root_drive(&self) -> Option<String> {
+        match self.root_path().components().next() {
+            Some(std::path::Component::Prefix(prefix)) => {
+                let drive = prefix.as_os_str().to_string_lossy().to_string();
+                let root = PathBuf::from(format!("{}\\", drive));
+                (!root.exists()).then_some(drive)
+            }
+            _ => None,
+        }
+    }
+
     /// 创建安装根目录下的标准子目录
     pub fn ensure_dirs(&self) -> Result<()> {
         for dir in [self.tools_dir(), self.lang_dir(), self.ide_dir(), self.cache_dir()] {
@@ -167,6 +577,8 @@ impl HudoConfig {
                     letter: letter as char,
                     is_system,
                     free_gb,
+                    label: get_volume_label(&drive),
+                    drive_type: get_drive_type(&drive),
                 });
             }
         }
@@ -186,25 +598,361 @@ pub struct DriveInfo {
     pub letter: char,
     pub is_system: bool,
     pub free_gb: u64,
+    /// 卷标（如 "Data"），未设置时为空
+    pub label: String,
+    pub drive_type: DriveType,
+}
+
+/// 磁盘类型（GetDriveTypeW）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriveType {
+    Fixed,
+    Removable,
+    Network,
+    CdRom,
+    Other,
+}
+
+impl DriveType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DriveType::Fixed => "本地磁盘",
+            DriveType::Removable => "可移动磁盘",
+            DriveType::Network => "网络驱动器",
+            DriveType::CdRom => "光驱",
+            DriveType::Other => "其他",
+        }
+    }
+}
+
+/// 不适合作为安装根目录的位置：同步客户端会反复上传工具文件，
+/// 数据库数据目录被同步或磁盘断开时可能损坏
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RootRisk {
+    /// 位于 OneDrive / Dropbox 同步目录内
+    Synced { service: &'static str, root: PathBuf },
+    /// 网络驱动器或 UNC 共享路径
+    Network,
+    /// 可移动磁盘
+    Removable,
+}
+
+impl RootRisk {
+    pub fn describe(&self) -> String {
+        match self {
+            RootRisk::Synced { service, root } => {
+                format!("位于 {} 同步目录 {} 内", service, root.display())
+            }
+            RootRisk::Network => "位于网络驱动器上".to_string(),
+            RootRisk::Removable => "位于可移动磁盘上".to_string(),
+        }
+    }
+}
+
+/// 检查安装根目录是否位于同步目录、网络驱动器或可移动磁盘
+pub fn root_dir_risk(path: &Path) -> Option<RootRisk> {
+    classify_root(path, |key| std::env::var(key).ok(), drive_type_of(path))
+}
+
+/// 按同步目录 → UNC 路径 → 磁盘类型的顺序判断；env 用于读取环境变量，drive_type 为路径所在磁盘的类型
+pub fn classify_root(
+    path: &Path,
+    env: impl Fn(&str) -> Option<String>,
+    drive_type: Option<DriveType>,
+) -> Option<RootRisk> {
+    if let Some((service, root)) = sync_roots(&env)
+        .into_iter()
+        .find(|(_, root)| crate::running::is_under(path, root))
+    {
+        return Some(RootRisk::Synced { service, root });
+    }
+    let raw = path.to_string_lossy();
+    if raw.starts_with(r"\\") || raw.starts_with("//") {
+        return Some(RootRisk::Network);
+    }
+    match drive_type {
+        Some(DriveType::Network) => Some(RootRisk::Network),
+        Some(DriveType::Removable) => Some(RootRisk::Removable),
+        _ => None,
+    }
+}
+
+/// 同步客户端的根目录：OneDrive 由环境变量给出，Dropbox 记录在 info.json 中
+fn sync_roots(env: &impl Fn(&str) -> Option<String>) -> Vec<(&'static str, PathBuf)> {
+    let mut roots = Vec::new();
+    for key in ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"] {
+        if let Some(dir) = env(key).filter(|d| !d.is_empty()) {
+            roots.push(("OneDrive", PathBuf::from(dir)));
+        }
+    }
+    for base in ["APPDATA", "LOCALAPPDATA"] {
+        let Some(base) = env(base) else {
+            continue;
+        };
+        let info = Path::new(&base).join("Dropbox").join("info.json");
+        let Ok(content) = std::fs::read_to_string(&info) else {
+            continue;
+        };
+        // {"personal": {"path": "C:\\Users\\x\\Dropbox", ...}, "business": {...}}
+        let Ok(serde_json::Value::Object(accounts)) = serde_json::from_str(&content) else {
+            continue;
+        };
+        for account in accounts.values() {
+            if let Some(dir) = account["path"].as_str() {
+                roots.push(("Dropbox", PathBuf::from(dir)));
+            }
+        }
+    }
+    roots
+}
+
+#[cfg(windows)]
+fn drive_type_of(path: &Path) -> Option<DriveType> {
+    match path.components().next() {
+        Some(std::path::Component::Prefix(prefix)) => {
+            let drive = prefix.as_os_str().to_string_lossy().to_string();
+            Some(get_drive_type(&format!("{}\\", drive)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(not(windows))]
+fn drive_type_of(_path: &Path) -> Option<DriveType> {
+    None
+}
+
+/// 转换为以 0 结尾的 UTF-16 字符串
+#[cfg(windows)]
+fn to_wide(s: &str) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+    std::ffi::OsStr::new(s).encode_wide().chain(std::iter::once(0)).collect()
+}
+
+#[cfg(windows)]
+fn get_drive_type(root: &str) -> DriveType {
+    let wide = to_wide(root);
+    // 返回值：2 = DRIVE_REMOVABLE, 3 = DRIVE_FIXED, 4 = DRIVE_REMOTE, 5 = DRIVE_CDROM
+    match unsafe { windows_sys::Win32::Storage::FileSystem::GetDriveTypeW(wide.as_ptr()) } {
+        2 => DriveType::Removable,
+        3 => DriveType::Fixed,
+        4 => DriveType::Network,
+        5 => DriveType::CdRom,
+        _ => DriveType::Other,
+    }
+}
+
+#[cfg(windows)]
+fn get_volume_label(root: &str) -> String {
+    let wide = to_wide(root);
+    let mut name = [0u16; 261];
+    let ok = unsafe {
+        windows_sys::Win32::Storage::FileSystem::GetVolumeInformationW(
+            wide.as_ptr(),
+            name.as_mut_ptr(),
+            name.len() as u32,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ok == 0 {
+        return String::new();
+    }
+    let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    String::from_utf16_lossy(&name[..len])
 }
 
 #[cfg(windows)]
 fn get_free_space_gb(path: &str) -> u64 {
+    disk_space(Path::new(path)).map_or(0, |(free, _)| free / (1024 * 1024 * 1024))
+}
+
+/// path 所在磁盘的 (可用字节数, 总字节数)；path 不存在时查询失败
+#[cfg(windows)]
+pub fn disk_space(path: &Path) -> Option<(u64, u64)> {
     use std::os::windows::ffi::OsStrExt;
-    use std::ffi::OsStr;
 
-    let wide: Vec<u16> = OsStr::new(path).encode_wide().chain(std::iter::once(0)).collect();
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
     let mut free_bytes: u64 = 0;
     let mut total_bytes: u64 = 0;
     let mut total_free: u64 = 0;
 
-    unsafe {
+    let ok = unsafe {
         windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW(
             wide.as_ptr(),
             &mut free_bytes as *mut u64,
             &mut total_bytes as *mut u64,
             &mut total_free as *mut u64,
+        )
+    };
+    (ok != 0).then_some((free_bytes, total_bytes))
+}
+
+#[cfg(not(windows))]
+pub fn disk_space(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_root() {
+        let dir = std::env::temp_dir().join(format!("hudo-classify-root-{}", std::process::id()));
+        let dropbox = dir.join("Dropbox");
+        std::fs::create_dir_all(&dropbox).unwrap();
+        std::fs::write(
+            dropbox.join("info.json"),
+            r#"{"personal":{"path":"D:\\Dropbox","host":1},"business":{"path":"E:\\Dropbox (Acme)"}}"#,
+        )
+        .unwrap();
+        let local = dir.to_string_lossy().to_string();
+        let env = |key: &str| match key {
+            "OneDrive" => Some(r"C:\Users\张 三\OneDrive".to_string()),
+            "LOCALAPPDATA" => Some(local.clone()),
+            _ => None,
+        };
+        let fixed = Some(DriveType::Fixed);
+
+        assert_eq!(
+            classify_root(Path::new(r"C:\Users\张 三\onedrive\hudo"), env, fixed),
+            Some(RootRisk::Synced { service: "OneDrive", root: PathBuf::from(r"C:\Users\张 三\OneDrive") })
         );
+        assert!(matches!(
+            classify_root(Path::new(r"E:\Dropbox (Acme)\dev\hudo"), env, fixed),
+            Some(RootRisk::Synced { service: "Dropbox", .. })
+        ));
+        // 与同步目录同名前缀的兄弟目录不算
+        assert_eq!(classify_root(Path::new(r"C:\Users\张 三\OneDrive-backup\hudo"), env, fixed), None);
+        assert_eq!(classify_root(Path::new(r"D:\hudo"), env, fixed), None);
+
+        assert_eq!(classify_root(Path::new(r"\\nas\share\hudo"), env, None), Some(RootRisk::Network));
+        assert_eq!(classify_root(Path::new(r"Z:\hudo"), env, Some(DriveType::Network)), Some(RootRisk::Network));
+        assert_eq!(classify_root(Path::new(r"F:\hudo"), env, Some(DriveType::Removable)), Some(RootRisk::Removable));
+        assert_eq!(classify_root(Path::new(r"D:\hudo"), |_| None, fixed), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_version_keys() {
+        let mut config: HudoConfig = toml::from_str("root_dir = 'D:\\hudo'").unwrap();
+        for key in VERSION_KEYS {
+            assert_eq!(config.version_of(key), None, "{}", key);
+            assert!(config.set_version_key(key, "1.2.3"), "{}", key);
+            assert_eq!(config.version_of(key), Some("1.2.3"), "{}", key);
+        }
+        // versions.go 与 go.version 是同一项
+        assert_eq!(config.go.version, "1.2.3");
+        assert!(config.set_version_key("go", "latest"));
+        assert_eq!(config.go.version, "latest");
+        assert_eq!(config.version_of("go"), None);
+        assert!(config.set_version_key("gh", "latest"));
+        assert_eq!(config.versions.gh, None);
+        assert!(!config.set_version_key("java", "21"));
+    }
+
+    #[test]
+    fn test_pin_keys() {
+        let mut config: HudoConfig = toml::from_str("root_dir = 'D:\\hudo'").unwrap();
+        let keys: Vec<String> = pin_keys().into_iter().map(|(k, _)| k).collect();
+        assert_eq!(keys.len(), VERSION_KEYS.len() + 1);
+        assert!(keys.contains(&"go.version".to_string()) && keys.contains(&"java.version".to_string()));
+        assert!(!keys.contains(&"versions.go".to_string()));
+        for key in &keys {
+            let expected = match key.as_str() {
+                "java.version" => Some("21"),
+                _ => None,
+            };
+            assert_eq!(config.pinned(key), expected, "{}", key);
+        }
+
+        // 取消固定后不写入空字符串，而是去掉这一项
+        assert!(config.set_version_key("git", "2.47.0"));
+        assert_eq!(config.pinned("versions.git"), Some("2.47.0"));
+        assert!(config.set_version_key("git", "latest"));
+        let value: toml::Value = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert!(value["versions"].get("git").is_none(), "{}", value);
+        assert_eq!(config.pinned("versions.git"), None);
+
+        assert_eq!(version_key_for("gh"), Some(("versions.gh".to_string(), "gh")));
+        assert_eq!(version_key_for("nodejs"), Some(("versions.fnm".to_string(), "fnm")));
+        assert_eq!(version_key_for("claude-code"), Some(("versions.claude_code".to_string(), "claude_code")));
+        assert_eq!(version_key_for("jdk"), Some(("java.version".to_string(), "java")));
+        assert_eq!(version_key_for("vscode"), None);
+    }
+
+    #[test]
+    fn test_pin_version() {
+        // pin_keys() 中的每一项都能按工具 id 临时固定（--pick、--frozen、导入切换版本）
+        let mut config: HudoConfig = toml::from_str("root_dir = 'D:\\hudo'").unwrap();
+        for (key, vkey) in pin_keys() {
+            let tool_id = match vkey {
+                "fnm" => "nodejs",
+                "java" => "jdk",
+                "claude_code" => "claude-code",
+                k => k,
+            };
+            assert_eq!(version_key_for(tool_id).map(|(k, _)| k), Some(key.clone()), "{}", tool_id);
+            assert!(config.pin_version(tool_id, "17.0.13+11"), "{}", tool_id);
+            let expected = match vkey {
+                "java" => "17",
+                _ => "17.0.13+11",
+            };
+            assert_eq!(config.pinned(&key), Some(expected), "{}", key);
+        }
+        assert!(!config.pin_version("sqlite", "3.47.0"));
+        // versions.java 不是配置项，JDK 只能通过 java.version 设置
+        assert!(!config.set_version_key("java", "17"));
+        assert_eq!(java_major("1.8.0_392"), "8");
+        assert_eq!(java_major("JDK 21"), "21");
+    }
+
+    #[test]
+    fn test_install_idle_timeout() {
+        let config: HudoConfig = toml::from_str("root_dir = 'D:\\hudo'").unwrap();
+        assert_eq!(config.install.idle_timeout(), Some(std::time::Duration::from_secs(600)));
+        assert_eq!(config.install.silent_idle_timeout(), Some(std::time::Duration::from_secs(1800)));
+        let config: HudoConfig = toml::from_str("root_dir = 'D:\\hudo'\n[install]\nidle_minutes = 0").unwrap();
+        assert_eq!(config.install.idle_timeout(), None);
+        assert_eq!(config.install.silent_idle_timeout(), None);
+        let config: HudoConfig = toml::from_str("root_dir = 'D:\\hudo'\n[install]\nsilent_idle_minutes = 45").unwrap();
+        assert_eq!(config.install.silent_idle_timeout(), Some(std::time::Duration::from_secs(2700)));
+        assert!(config.install.silent_watch(false).silent);
+        assert_eq!(config.install.watch(true).on_stall, crate::watchdog::OnStall::Wait(UNATTENDED_WAIT_ROUNDS));
+    }
+
+    #[test]
+    fn test_expand_mirror() {
+        let config: HudoConfig = toml::from_str("root_dir = 'D:\\hudo'").unwrap();
+        assert_eq!(
+            config.expand_mirror("https://mirror.internal/{version}/{tool}/", "1.24.0", "go"),
+            "https://mirror.internal/1.24.0/go"
+        );
+        assert_eq!(config.expand_mirror("file:///{root}/mirror", "", "go"), "file:///D:\\hudo/mirror");
+        assert_eq!(config.expand_mirror("https://go.dev/dl", "1.24.0", "go"), "https://go.dev/dl");
+    }
+
+    #[test]
+    fn test_validate_mirror_templates() {
+        let mut config: HudoConfig = toml::from_str("root_dir = 'D:\\hudo'").unwrap();
+        assert!(config.validate().is_ok());
+        config.mirrors.set("go", "https://mirror.internal/{version}/go");
+        assert!(config.validate().is_ok());
+        config.mirrors.set("maven", "https://mirror.internal/{ver}");
+        let err = format!("{:#}", config.validate().unwrap_err());
+        assert!(err.contains("mirrors.maven") && err.contains("未知占位符 {ver}"), "{}", err);
+        config.mirrors.set("maven", "https://mirror.internal/{version");
+        assert!(config.validate().is_err());
+
+        // 加载时同样检查
+        let path = std::env::temp_dir().join(format!("hudo-validate-{}.toml", std::process::id()));
+        std::fs::write(&path, "root_dir = 'D:\\hudo'\n[mirrors]\ngo = 'https://m/{tool'\n").unwrap();
+        assert!(HudoConfig::load_from(&path).is_err());
+        std::fs::remove_file(&path).ok();
     }
-    free_bytes / (1024 * 1024 * 1024)
 }
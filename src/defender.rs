@@ -0,0 +1,135 @@
+//! Windows Defender 排除项（可选功能，需用户主动开启）
+//!
+//! 实时扫描会拖慢编译器和包管理器对大量小文件的读写（node_modules、cargo 构建）。
+//! hudo config defender-exclude 把 root_dir 或其中的部分目录加入排除列表，
+//! 添加的路径记录到 state.json，卸载 hudo 自身或 --remove 时移除。
+
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::config::HudoConfig;
+
+/// 提示中给出的安全说明
+pub const SECURITY_NOTE: &str =
+    "排除后 Defender 不再实时扫描这些目录，从不可信来源下载到其中的文件不会被自动检查";
+
+/// Defender 当前的工作状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefenderStatus {
+    /// 实时保护由 Defender 负责
+    Active,
+    /// 安装了其他杀毒软件，Defender 处于被动模式或已禁用
+    Passive,
+    /// 无法查询（Defender 被移除、组策略禁止或非 Windows）
+    Unavailable,
+}
+
+impl DefenderStatus {
+    /// 不能添加排除项时的说明
+    pub fn explain(self) -> Option<&'static str> {
+        match self {
+            DefenderStatus::Active => None,
+            DefenderStatus::Passive => Some("当前由其他杀毒软件提供实时保护，Defender 排除项不起作用，请在该软件中添加排除目录"),
+            DefenderStatus::Unavailable => Some("无法查询 Windows Defender 状态（可能已被移除或由组策略管理），未做任何更改"),
+        }
+    }
+}
+
+/// 解析 `(Get-MpComputerStatus).AMRunningMode` 的输出
+///
+/// Normal 为正常运行；Passive Mode / EDR Block Mode 表示另有杀毒软件；为空表示命令失败
+pub fn parse_running_mode(output: &str) -> DefenderStatus {
+    match output.trim().to_lowercase().as_str() {
+        "" => DefenderStatus::Unavailable,
+        "normal" => DefenderStatus::Active,
+        _ => DefenderStatus::Passive,
+    }
+}
+
+#[cfg(windows)]
+pub fn status() -> DefenderStatus {
+    match crate::proc::powershell("(Get-MpComputerStatus -ErrorAction Stop).AMRunningMode").output() {
+        Ok(out) if out.status.success() => parse_running_mode(&crate::proc::decode_output(&out.stdout)),
+        _ => DefenderStatus::Unavailable,
+    }
+}
+
+#[cfg(not(windows))]
+pub fn status() -> DefenderStatus {
+    DefenderStatus::Unavailable
+}
+
+/// `whoami /groups` 的输出中是否有 Administrators 组（S-1-5-32-544）；
+/// UAC 下未提权的管理员也会列出该组，可以通过 UAC 提示提权
+pub fn has_admin_group(output: &str) -> bool {
+    output.contains("S-1-5-32-544")
+}
+
+/// 当前用户能否通过 UAC 提权
+#[cfg(windows)]
+pub fn elevation_available() -> bool {
+    std::process::Command::new("whoami")
+        .arg("/groups")
+        .output()
+        .is_ok_and(|out| has_admin_group(&crate::proc::decode_output(&out.stdout)))
+}
+
+#[cfg(not(windows))]
+pub fn elevation_available() -> bool {
+    false
+}
+
+/// 可选的排除目录：(说明, 路径)，第一项为整个安装目录
+pub fn candidates(config: &HudoConfig) -> Vec<(&'static str, PathBuf)> {
+    vec![
+        ("整个安装目录", config.root_path()),
+        ("Rust（cargo 构建与依赖缓存）", config.lang_dir().join("cargo")),
+        ("Go（GOPATH 模块缓存）", config.lang_dir().join("gopath")),
+        ("下载缓存", config.cache_dir()),
+    ]
+}
+
+/// Add-MpPreference / Remove-MpPreference 脚本，路径逐个单引号引用
+pub fn exclusion_script(add: bool, paths: &[String]) -> String {
+    let quoted: Vec<String> = paths.iter().map(|p| crate::proc::ps_quote(p)).collect();
+    let cmdlet = if add { "Add-MpPreference" } else { "Remove-MpPreference" };
+    format!("{} -ExclusionPath {}", cmdlet, quoted.join(","))
+}
+
+/// 以管理员身份添加（add = true）或移除排除路径
+#[cfg(windows)]
+pub fn apply(add: bool, paths: &[String]) -> Result<()> {
+    let script = exclusion_script(add, paths);
+    crate::installer::run_as_admin("powershell", &["-NoProfile", "-Command", &script])
+}
+
+#[cfg(not(windows))]
+pub fn apply(_add: bool, _paths: &[String]) -> Result<()> {
+    anyhow::bail!("Windows Defender 排除项仅支持 Windows")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_running_mode() {
+        assert_eq!(parse_running_mode("Normal\r\n"), DefenderStatus::Active);
+        assert_eq!(parse_running_mode("Passive Mode"), DefenderStatus::Passive);
+        assert_eq!(parse_running_mode("EDR Block Mode"), DefenderStatus::Passive);
+        assert_eq!(parse_running_mode(""), DefenderStatus::Unavailable);
+        assert!(DefenderStatus::Active.explain().is_none());
+    }
+
+    #[test]
+    fn test_exclusion_script() {
+        let paths = vec![r"D:\hudo".to_string(), r"C:\Users\O'Brien\hudo\cache".to_string()];
+        assert_eq!(
+            exclusion_script(true, &paths),
+            r"Add-MpPreference -ExclusionPath 'D:\hudo','C:\Users\O''Brien\hudo\cache'"
+        );
+        assert!(exclusion_script(false, &paths[..1]).starts_with("Remove-MpPreference"));
+        assert!(has_admin_group("BUILTIN\\Administrators  Alias  S-1-5-32-544  Group used for deny only"));
+        assert!(!has_admin_group("BUILTIN\\Users  Alias  S-1-5-32-545"));
+    }
+}
@@ -1,37 +1,248 @@
 use anyhow::{Context, Result};
 use futures_util::StreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::HudoConfig;
+use crate::report::{Console, Reporter};
+
+/// 下载限速（字节/秒），0 为不限速
+static MAX_DOWNLOAD_BPS: AtomicU64 = AtomicU64::new(0);
+
+/// 加载配置后调用：应用下载限速
+pub fn init(config: &HudoConfig) {
+    MAX_DOWNLOAD_BPS.store(config.max_download_bps.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// 只对本次运行覆盖下载限速（--limit-rate），0 为不限速
+pub fn set_max_bps(bps: u64) {
+    MAX_DOWNLOAD_BPS.store(bps, Ordering::Relaxed);
+}
+
+/// hudo import --frozen 锁定的 (下载地址, SHA256)：这些地址的下载不再按 ETag 重新验证，
+/// 缓存或新下载的文件与锁定值不符时报错，保证安装器实际使用的文件经过校验
+static FROZEN: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// 锁定某个下载地址的 SHA256，本次运行中安装器的下载都按它校验
+pub fn freeze(url: &str, sha256: &str) {
+    let mut frozen = FROZEN.lock().unwrap_or_else(|e| e.into_inner());
+    frozen.retain(|(u, _)| u != url);
+    frozen.push((url.to_string(), sha256.to_string()));
+}
+
+fn frozen_sha256(url: &str) -> Option<String> {
+    let frozen = FROZEN.lock().unwrap_or_else(|e| e.into_inner());
+    frozen.iter().find(|(u, _)| u == url).map(|(_, sha)| sha.clone())
+}
+
+/// 解析下载速度：字节数，可带 K / M / G 后缀（按 1024 换算），
+/// 后缀后的 B 与 /s 可省略（如 512K、2M、5MB/s）；无法解析时返回 None
+pub fn parse_rate(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let value = value.strip_suffix("/s").unwrap_or(value);
+    let value = value.strip_suffix(['B', 'b']).unwrap_or(value);
+    let (digits, unit) = match value.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&value[..i], 1u64 << 10),
+        Some((i, 'm' | 'M')) => (&value[..i], 1 << 20),
+        Some((i, 'g' | 'G')) => (&value[..i], 1 << 30),
+        _ => (value, 1),
+    };
+    digits.trim().parse::<u64>().ok()?.checked_mul(unit)
+}
+
+/// 令牌桶：每秒补充 rate 个字节的令牌，最多攒 1 秒的量
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, now: Instant) -> Self {
+        Self { rate: rate as f64, tokens: rate as f64, last: now }
+    }
+
+    /// 取走 n 个字节的令牌，返回令牌不足时需要等待的时长
+    fn take(&mut self, n: usize, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate) - n as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+/// 响应头中的 ETag / Last-Modified：重新验证缓存时带上；从「最新版」地址安装的工具
+/// 还会记入 state.json，之后不下载就能判断地址上的内容是否变化
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validator {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl Validator {
+    fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let header = |name| headers.get(name).and_then(|v| v.to_str().ok()).map(|v| v.to_string());
+        Self {
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// 附加 If-None-Match / If-Modified-Since
+    fn apply(&self, mut req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(etag) = &self.etag {
+            req = req.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(lm) = &self.last_modified {
+            req = req.header(reqwest::header::IF_MODIFIED_SINCE, lm);
+        }
+        req
+    }
+
+    /// 另一次响应的内容是否与之相同：有 ETag 时比较 ETag，否则比较 Last-Modified
+    fn matches(&self, other: &Validator) -> bool {
+        match &self.etag {
+            Some(etag) => other.etag.as_ref() == Some(etag),
+            None => self.last_modified.is_some() && other.last_modified == self.last_modified,
+        }
+    }
+}
+
+/// 缓存文件的来源记录（{filename}.meta.json），文件名相同但来源地址不同时视为未命中
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheMeta {
+    url: String,
+    #[serde(flatten)]
+    validator: Validator,
+}
+
+fn meta_path(cache_dir: &Path, filename: &str) -> PathBuf {
+    cache_dir.join(format!("{}.meta.json", filename))
+}
+
+fn load_meta(cache_dir: &Path, filename: &str) -> Option<CacheMeta> {
+    let content = std::fs::read_to_string(meta_path(cache_dir, filename)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_meta(cache_dir: &Path, filename: &str, meta: &CacheMeta) {
+    if let Ok(content) = serde_json::to_string_pretty(meta) {
+        std::fs::write(meta_path(cache_dir, filename), content).ok();
+    }
+}
+
+/// 没有来源记录的旧缓存：URL 末段就是文件名时（版本号写在文件名里）沿用并补写记录，
+/// 否则（如 fnm-windows.zip、pycharm-community.zip）无法确认内容对应哪个版本，重新下载
+fn adopt_legacy(url: &str, cache_dir: &Path, filename: &str) -> Option<CacheMeta> {
+    let last = url.split(['?', '#']).next().unwrap_or(url).rsplit('/').next();
+    if last != Some(filename) {
+        return None;
+    }
+    let meta = CacheMeta { url: url.to_string(), validator: Validator::default() };
+    save_meta(cache_dir, filename, &meta);
+    Some(meta)
+}
+
+/// 下载时服务器返回的错误状态；404 多为配置的版本不存在，hudo install 据此提示改用最新版本
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpError {
+    pub status: u16,
+    pub url: String,
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HTTP 错误 {}: {}", self.status, self.url)
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// 错误（含外层附加的上下文）中的 HTTP 状态码
+pub fn http_status(err: &anyhow::Error) -> Option<u16> {
+    err.downcast_ref::<HttpError>().map(|e| e.status)
+}
 
 /// 异步下载文件到 cache_dir，返回本地文件路径
-/// 如果文件已存在则跳过下载
+/// 缓存中有同一地址下载的文件则跳过下载
 pub async fn download(url: &str, cache_dir: &Path, filename: &str) -> Result<PathBuf> {
+    fetch(url, cache_dir, filename, false).await
+}
+
+/// 下载「最新版」地址（地址不变、内容随发布变化，如 VS Code、JDK latest）：
+/// 有缓存时带 If-None-Match / If-Modified-Since 重新验证，服务器返回 304 才使用缓存
+pub async fn download_latest(url: &str, cache_dir: &Path, filename: &str) -> Result<PathBuf> {
+    fetch(url, cache_dir, filename, true).await
+}
+
+async fn fetch(url: &str, cache_dir: &Path, filename: &str, revalidate: bool) -> Result<PathBuf> {
+    match frozen_sha256(url) {
+        Some(expected) => fetch_verified(url, cache_dir, filename, &expected).await,
+        None => fetch_unchecked(url, cache_dir, filename, revalidate).await,
+    }
+}
+
+async fn fetch_unchecked(url: &str, cache_dir: &Path, filename: &str, revalidate: bool) -> Result<PathBuf> {
     let dest = cache_dir.join(filename);
 
+    let meta = match dest.exists().then(|| load_meta(cache_dir, filename)) {
+        Some(Some(m)) if m.url == url => Some(m),
+        Some(Some(_)) => {
+            println!("  {} 缓存文件来自其他地址，重新下载: {}", console::style("!").yellow(), filename);
+            None
+        }
+        Some(None) => adopt_legacy(url, cache_dir, filename),
+        None => None,
+    };
+
     // 缓存命中，跳过下载
-    if dest.exists() {
-        println!("  {} 使用缓存: {}", console::style("↓").cyan(), filename);
+    if meta.is_some() && !revalidate {
+        println!("  {} 使用缓存: {}", console::style(crate::ui::Glyph::Download).cyan(), filename);
         return Ok(dest);
     }
 
     std::fs::create_dir_all(cache_dir)
         .with_context(|| format!("无法创建缓存目录: {}", cache_dir.display()))?;
 
-    println!("  {} {}", console::style("↓").cyan(), console::style(url).dim());
+    println!("  {} {}", console::style(crate::ui::Glyph::Download).cyan(), console::style(url).dim());
 
     let client = reqwest::Client::new();
-    let resp = client
-        .get(url)
-        .send()
-        .await
-        .with_context(|| format!("请求失败: {}", url))?
-        .error_for_status()
-        .with_context(|| format!("HTTP 错误: {}", url))?;
+    let mut req = client.get(url);
+    if let Some(m) = &meta {
+        req = m.validator.apply(req);
+    }
+    let resp = req.send().await.with_context(|| format!("请求失败: {}", url))?;
+    let status = resp.status();
+    if status.is_client_error() || status.is_server_error() {
+        return Err(HttpError { status: status.as_u16(), url: url.to_string() }.into());
+    }
+
+    if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+        println!("  {} 使用缓存（未变化）: {}", console::style(crate::ui::Glyph::Download).cyan(), filename);
+        return Ok(dest);
+    }
+
+    let new_meta = CacheMeta { url: url.to_string(), validator: Validator::from_headers(resp.headers()) };
 
     // 写入临时文件，下载完成后再重命名，避免中断导致损坏
     let tmp_dest = cache_dir.join(format!("{}.tmp", filename));
     let result = download_to_tmp(&tmp_dest, resp).await;
 
+    let result = result.and_then(|()| check_content(&tmp_dest, filename, url));
     if let Err(e) = result {
         std::fs::remove_file(&tmp_dest).ok();
         return Err(e);
@@ -40,30 +251,280 @@ pub async fn download(url: &str, cache_dir: &Path, filename: &str) -> Result<Pat
     // 重命名为正式文件
     std::fs::rename(&tmp_dest, &dest)
         .with_context(|| format!("重命名临时文件失败: {}", tmp_dest.display()))?;
+    save_meta(cache_dir, filename, &new_meta);
 
-    println!("  {} {}", console::style("✓").green(), filename);
+    println!("  {} {}", console::style(crate::ui::Glyph::Success).green(), filename);
     Ok(dest)
 }
 
+/// 检查「最新版」地址是否有更新的超时
+const CHECK_TIMEOUT_SECS: u64 = 10;
+
+/// 缓存中该地址文件的 ETag / Last-Modified（安装后记入 state.json），都没有时为 None
+pub fn cached_validator(url: &str, cache_dir: &Path, filename: &str) -> Option<Validator> {
+    load_meta(cache_dir, filename)
+        .filter(|m| m.url == url)
+        .map(|m| m.validator)
+        .filter(|v| !v.is_empty())
+}
+
+/// 「最新版」地址上的内容是否与 validator 记录的相同，不下载文件：带 If-None-Match /
+/// If-Modified-Since 发 HEAD（服务器拒绝 HEAD 时改为 GET，只读响应头），
+/// 304 或响应的 ETag（没有 ETag 时为 Last-Modified）与记录一致视为未变
+pub async fn is_unchanged(url: &str, validator: &Validator) -> Result<bool> {
+    let client = crate::version::http_client(CHECK_TIMEOUT_SECS)?;
+    let mut resp = validator.apply(client.head(url)).send().await.with_context(|| format!("请求失败: {}", url))?;
+    if matches!(resp.status().as_u16(), 403 | 405 | 501) {
+        resp = validator.apply(client.get(url)).send().await.with_context(|| format!("请求失败: {}", url))?;
+    }
+    let status = resp.status();
+    if status == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(true);
+    }
+    if status.is_client_error() || status.is_server_error() {
+        return Err(HttpError { status: status.as_u16(), url: url.to_string() }.into());
+    }
+    Ok(validator.matches(&Validator::from_headers(resp.headers())))
+}
+
+/// 小于该大小的安装包视为无效（空响应或被截断）
+const MIN_PACKAGE_SIZE: u64 = 64;
+
+/// 镜像出错时常以 200 返回 HTML 错误页或验证码页面：按文件扩展名检查开头的魔数，
+/// 识别不了的类型（如 .ps1 脚本）只排除 HTML
+fn check_content(path: &Path, filename: &str, url: &str) -> Result<()> {
+    use std::io::Read;
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("无法读取文件: {}", path.display()))?
+        .len();
+    let mut head = Vec::with_capacity(512);
+    std::fs::File::open(path)
+        .and_then(|f| f.take(512).read_to_end(&mut head))
+        .with_context(|| format!("无法读取文件: {}", path.display()))?;
+    if !content_matches(filename, size, &head) {
+        anyhow::bail!("下载内容不是预期的文件类型（可能是镜像返回了错误页面）: {}", url);
+    }
+    Ok(())
+}
+
+/// 文件开头 head 是否与按扩展名预期的类型一致
+fn content_matches(filename: &str, size: u64, head: &[u8]) -> bool {
+    let lower = filename.to_ascii_lowercase();
+    let magic: &[u8] = if lower.ends_with(".zip") {
+        b"PK"
+    } else if lower.ends_with(".exe") {
+        b"MZ"
+    } else if lower.ends_with(".msi") {
+        // OLE 复合文档
+        &[0xD0, 0xCF, 0x11, 0xE0]
+    } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        &[0x1F, 0x8B]
+    } else {
+        return !looks_like_html(head);
+    };
+    size >= MIN_PACKAGE_SIZE && head.starts_with(magic)
+}
+
+fn looks_like_html(head: &[u8]) -> bool {
+    let text = String::from_utf8_lossy(head).trim_start().to_ascii_lowercase();
+    text.starts_with("<!doctype html") || text.starts_with("<html")
+}
+
+/// 预估下载大小的缓存文件名（位于下载缓存目录下）：URL → 大小
+const SIZE_CACHE_FILE: &str = "size-cache.json";
+
+/// 下载大小缓存有效期：7 天（「最新版」地址的内容会随发布变化）
+const SIZE_CACHE_TTL: u64 = 7 * 24 * 60 * 60;
+
+/// 并行查询下载大小时同时进行的请求数与单个请求的超时
+const SIZE_CONCURRENCY: usize = 8;
+const SIZE_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Default, Serialize, Deserialize)]
+struct SizeCache {
+    #[serde(default)]
+    entries: std::collections::BTreeMap<String, SizeRecord>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct SizeRecord {
+    size: u64,
+    /// 查询时间（Unix 秒）
+    checked_at: u64,
+}
+
+impl SizeCache {
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).ok();
+        }
+        if let Ok(s) = serde_json::to_string(self) {
+            std::fs::write(path, s).ok();
+        }
+    }
+
+    fn fresh(&self, url: &str, now: u64) -> Option<u64> {
+        self.entries
+            .get(url)
+            .filter(|e| now.saturating_sub(e.checked_at) < SIZE_CACHE_TTL)
+            .map(|e| e.size)
+    }
+}
+
+/// 安装前预估的下载量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeEstimate {
+    /// 需要下载的字节数
+    Bytes(u64),
+    /// 缓存中已有同一地址下载的文件，不需要下载
+    Cached,
+    /// 服务器没有给出大小或请求失败
+    Unknown,
+}
+
+impl SizeEstimate {
+    /// 需要下载的字节数（已缓存为 0，未知为 None）
+    pub fn bytes(&self) -> Option<u64> {
+        match self {
+            SizeEstimate::Bytes(n) => Some(*n),
+            SizeEstimate::Cached => Some(0),
+            SizeEstimate::Unknown => None,
+        }
+    }
+}
+
+/// 缓存中是否已有该地址下载的文件（.meta.json 记录的来源地址一致）
+pub fn is_cached(url: &str, cache_dir: &Path, filename: &str) -> bool {
+    !filename.is_empty()
+        && cache_dir.join(filename).exists()
+        && load_meta(cache_dir, filename).is_some_and(|m| m.url == url)
+}
+
+/// 预估每个 (URL, 缓存文件名) 的下载大小，结果与 targets 顺序一致：
+/// 已下载的文件记为已缓存，7 天内查询过的地址直接用记录，其余并行发送 HEAD 请求
+pub async fn estimate_sizes(cache_dir: &Path, targets: &[(String, String)]) -> Vec<SizeEstimate> {
+    let path = cache_dir.join(SIZE_CACHE_FILE);
+    let mut cache = SizeCache::load(&path);
+    let now = unix_now();
+
+    let mut estimates: Vec<Option<SizeEstimate>> = targets
+        .iter()
+        .map(|(url, filename)| match is_cached(url, cache_dir, filename) {
+            true => Some(SizeEstimate::Cached),
+            false => cache.fresh(url, now).map(SizeEstimate::Bytes),
+        })
+        .collect();
+
+    let pending: Vec<usize> = (0..targets.len()).filter(|&i| estimates[i].is_none()).collect();
+    if pending.is_empty() {
+        return estimates.into_iter().flatten().collect();
+    }
+    let Ok(client) = crate::version::http_client(SIZE_TIMEOUT_SECS) else {
+        return estimates.into_iter().map(|e| e.unwrap_or(SizeEstimate::Unknown)).collect();
+    };
+    let sizes: Vec<Option<u64>> = futures_util::stream::iter(&pending)
+        .map(|&i| {
+            let client = &client;
+            let url = &targets[i].0;
+            async move { crate::mirrors::probe(client, url).await.size }
+        })
+        .buffered(SIZE_CONCURRENCY)
+        .collect()
+        .await;
+
+    for (&i, size) in pending.iter().zip(sizes) {
+        estimates[i] = Some(size.map_or(SizeEstimate::Unknown, SizeEstimate::Bytes));
+        if let Some(size) = size {
+            cache.entries.insert(targets[i].0.clone(), SizeRecord { size, checked_at: now });
+        }
+    }
+    cache.save(&path);
+    estimates.into_iter().map(|e| e.unwrap_or(SizeEstimate::Unknown)).collect()
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 下载并校验 SHA256（用于 hudo import --frozen）
+/// 缓存文件哈希不符时删除后重新下载，仍不符则报错
+pub async fn download_verified(
+    url: &str,
+    cache_dir: &Path,
+    filename: &str,
+    sha256: Option<&str>,
+) -> Result<PathBuf> {
+    match sha256 {
+        Some(expected) => fetch_verified(url, cache_dir, filename, expected).await,
+        None => download(url, cache_dir, filename).await,
+    }
+}
+
+/// 与锁定 SHA256 一致的缓存直接使用（不重新验证），否则重新下载并校验
+async fn fetch_verified(url: &str, cache_dir: &Path, filename: &str, expected: &str) -> Result<PathBuf> {
+    let cached = cache_dir.join(filename);
+    if cached.exists() && !sha256_file(&cached)?.eq_ignore_ascii_case(expected) {
+        println!("  {} 缓存文件校验失败，重新下载: {}", console::style("!").yellow(), filename);
+        std::fs::remove_file(&cached).ok();
+    }
+
+    let path = fetch_unchecked(url, cache_dir, filename, false).await?;
+    let actual = sha256_file(&path)?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        std::fs::remove_file(&path).ok();
+        anyhow::bail!(
+            "SHA256 校验失败: {}\n  期望: {}\n  实际: {}",
+            filename,
+            expected,
+            actual
+        );
+    }
+    Ok(path)
+}
+
+/// 计算文件 SHA256
+pub fn sha256_file(path: &Path) -> Result<String> {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    let mut file = std::fs::File::open(path)
+        .with_context(|| format!("无法打开文件: {}", path.display()))?;
+    std::io::copy(&mut file, &mut hasher).context("计算 SHA256 失败")?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
 /// 下载内容到临时文件
 async fn download_to_tmp(tmp_dest: &Path, resp: reqwest::Response) -> Result<()> {
-    let total_size = resp.content_length().unwrap_or(0);
-
-    let pb = ProgressBar::new(total_size);
-    pb.set_style(
-        ProgressStyle::default_bar()
-            .template("  {bar:40.cyan/blue}  {bytes}/{total_bytes}  {eta}")
-            .unwrap()
-            .progress_chars("━╸─"),
-    );
+    let pb = download_progress(resp.content_length().unwrap_or(0));
 
     let mut file = std::fs::File::create(tmp_dest)
         .with_context(|| format!("无法创建临时文件: {}", tmp_dest.display()))?;
 
+    let mut bucket = match MAX_DOWNLOAD_BPS.load(Ordering::Relaxed) {
+        0 => None,
+        bps => Some(TokenBucket::new(bps, Instant::now())),
+    };
+
     let mut stream = resp.bytes_stream();
     while let Some(chunk) = stream.next().await {
         let chunk = chunk.context("下载数据流错误")?;
         std::io::Write::write_all(&mut file, &chunk).context("写入文件失败")?;
+        // 限速时等令牌补足后再更新进度，进度条的速度与剩余时间按限速后的速率计算
+        if let Some(bucket) = bucket.as_mut() {
+            let wait = bucket.take(chunk.len(), Instant::now());
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+        }
         pb.inc(chunk.len() as u64);
     }
 
@@ -71,36 +532,126 @@ async fn download_to_tmp(tmp_dest: &Path, resp: reqwest::Response) -> Result<()>
     Ok(())
 }
 
+/// 下载进度条：带速度显示；服务器未返回大小时用转圈样式，避免进度条一直停在 0%
+fn download_progress(total_size: u64) -> ProgressBar {
+    if total_size == 0 {
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(
+            ProgressStyle::default_spinner()
+                .template("  {spinner:.cyan} {bytes}  {binary_bytes_per_sec}  {elapsed}")
+                .unwrap(),
+        );
+        pb.enable_steady_tick(Duration::from_millis(100));
+        return pb;
+    }
+    let pb = ProgressBar::new(total_size);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("  {bar:40.cyan/blue}  {bytes}/{total_bytes}  {binary_bytes_per_sec}  {eta}")
+            .unwrap()
+            .progress_chars(crate::ui::progress_chars()),
+    );
+    pb
+}
+
+/// 条目数达到该值时并行解压（JDK、PyCharm 等大包），小包保持单线程
+const PARALLEL_EXTRACT_THRESHOLD: usize = 1000;
+
+/// 并行解压的最大线程数
+const MAX_EXTRACT_THREADS: usize = 8;
+
 /// 解压 zip 文件到目标目录
 #[allow(dead_code)]
 pub fn extract_zip(zip_path: &Path, dest_dir: &Path) -> Result<()> {
     std::fs::create_dir_all(dest_dir)
         .with_context(|| format!("无法创建解压目录: {}", dest_dir.display()))?;
 
-    let file = std::fs::File::open(zip_path)
-        .with_context(|| format!("无法打开 zip 文件: {}", zip_path.display()))?;
-    let mut archive = zip::ZipArchive::new(file)
-        .with_context(|| format!("无效的 zip 文件: {}", zip_path.display()))?;
+    let mut archive = open_zip(zip_path)?;
 
+    // 先按中央目录创建全部目录并统计总字节数（用于进度），并行解压时各线程只写文件，避免目录创建竞争
+    let mut total_size = 0u64;
+    let mut files = Vec::new();
     for i in 0..archive.len() {
-        let mut entry = archive.by_index(i).context("读取 zip 条目失败")?;
-        let name = entry.name().to_string();
-
-        let out_path = dest_dir.join(&name);
-
+        let entry = archive.by_index(i).context("读取 zip 条目失败")?;
+        let out_path = dest_dir.join(entry.name());
         if entry.is_dir() {
             std::fs::create_dir_all(&out_path).ok();
         } else {
             if let Some(parent) = out_path.parent() {
                 std::fs::create_dir_all(parent).ok();
             }
-            let mut outfile = std::fs::File::create(&out_path)
-                .with_context(|| format!("无法创建文件: {}", out_path.display()))?;
-            std::io::copy(&mut entry, &mut outfile)
-                .with_context(|| format!("解压文件失败: {}", name))?;
+            total_size += entry.size();
+            files.push(i);
         }
     }
 
+    let pb = ProgressBar::new(total_size);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("  {bar:40.cyan/blue}  {bytes}/{total_bytes}  解压中")
+            .unwrap()
+            .progress_chars(crate::ui::progress_chars()),
+    );
+
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(MAX_EXTRACT_THREADS);
+
+    if files.len() < PARALLEL_EXTRACT_THRESHOLD || threads < 2 {
+        extract_entries(&mut archive, &files, dest_dir, &pb)?;
+    } else {
+        // 按步长分配条目，各线程打开独立的 ZipArchive 句柄
+        let groups: Vec<Vec<usize>> = (0..threads)
+            .map(|t| files.iter().skip(t).step_by(threads).copied().collect())
+            .collect();
+        std::thread::scope(|s| {
+            let handles: Vec<_> = groups
+                .iter()
+                .map(|group| {
+                    let pb = &pb;
+                    s.spawn(move || -> Result<()> {
+                        let mut archive = open_zip(zip_path)?;
+                        extract_entries(&mut archive, group, dest_dir, pb)
+                    })
+                })
+                .collect();
+            handles.into_iter().try_for_each(|h| {
+                h.join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("解压线程崩溃")))
+            })
+        })?;
+    }
+
+    pb.finish_and_clear();
+    Ok(())
+}
+
+fn open_zip(zip_path: &Path) -> Result<zip::ZipArchive<std::fs::File>> {
+    let file = std::fs::File::open(zip_path)
+        .with_context(|| format!("无法打开 zip 文件: {}", zip_path.display()))?;
+    zip::ZipArchive::new(file)
+        .with_context(|| format!("无效的 zip 文件: {}", zip_path.display()))
+}
+
+/// 解压指定下标的文件条目（父目录需已创建）
+fn extract_entries(
+    archive: &mut zip::ZipArchive<std::fs::File>,
+    indices: &[usize],
+    dest_dir: &Path,
+    pb: &ProgressBar,
+) -> Result<()> {
+    for &i in indices {
+        let mut entry = archive.by_index(i).context("读取 zip 条目失败")?;
+        let name = entry.name().to_string();
+        let out_path = dest_dir.join(&name);
+
+        let mut outfile = std::fs::File::create(&out_path)
+            .with_context(|| format!("无法创建文件: {}", out_path.display()))?;
+        std::io::copy(&mut entry, &mut outfile)
+            .with_context(|| format!("解压文件失败: {}", name))?;
+        pb.inc(entry.size());
+    }
     Ok(())
 }
 
@@ -118,11 +669,150 @@ pub fn find_single_subdir(dir: &Path) -> Option<PathBuf> {
     }
 }
 
-/// 运行 exe 安装程序（如 rustup-init.exe）
-pub fn run_installer(exe_path: &Path, args: &[&str]) -> Result<()> {
-    let status = std::process::Command::new(exe_path)
-        .args(args)
-        .status()
+/// 用已解压好的 `staged` 目录替换 `final_dir`，中断时不会留下删了一半的旧版本
+///
+/// 先把 `staged` 移到同级的 `*.new`（跨盘时复制），再把旧目录改名为 `*.old`，
+/// 最后一次 rename 就位并删除 `*.old`。上次替换中断残留的 `*.old` 会先恢复。
+pub fn swap_dir(staged: &Path, final_dir: &Path) -> Result<()> {
+    let sibling = |suffix: &str| {
+        let name = final_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        final_dir.with_file_name(format!("{}.{}", name, suffix))
+    };
+    let new_dir = sibling("new");
+    let old_dir = sibling("old");
+
+    // 上次在两次 rename 之间中断：旧版本还在 *.old
+    if !final_dir.exists() && old_dir.exists() {
+        std::fs::rename(&old_dir, final_dir).ok();
+    }
+    if new_dir.exists() {
+        std::fs::remove_dir_all(&new_dir)
+            .with_context(|| format!("无法清理临时目录: {}", new_dir.display()))?;
+    }
+    if old_dir.exists() {
+        std::fs::remove_dir_all(&old_dir)
+            .with_context(|| format!("无法清理旧版本目录: {}", old_dir.display()))?;
+    }
+    if let Some(parent) = final_dir.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("无法创建目录: {}", parent.display()))?;
+    }
+
+    // 缓存目录可能在其他磁盘（--cache-dir），rename 失败时改为复制
+    if std::fs::rename(staged, &new_dir).is_err() {
+        let copied = copy_dir_all(staged, &new_dir);
+        if let Err(e) = copied {
+            std::fs::remove_dir_all(&new_dir).ok();
+            return Err(e);
+        }
+        std::fs::remove_dir_all(staged).ok();
+    }
+
+    let had_old = final_dir.exists();
+    if had_old {
+        if let Err(e) = std::fs::rename(final_dir, &old_dir) {
+            std::fs::remove_dir_all(&new_dir).ok();
+            return Err(e).with_context(|| {
+                format!("无法替换 {}，请关闭正在使用其中文件的程序后重试", final_dir.display())
+            });
+        }
+    }
+
+    if let Err(e) = std::fs::rename(&new_dir, final_dir) {
+        if had_old {
+            std::fs::rename(&old_dir, final_dir).ok();
+        }
+        std::fs::remove_dir_all(&new_dir).ok();
+        return Err(e).with_context(|| format!("无法移动到安装目录: {}", final_dir.display()));
+    }
+
+    if had_old {
+        std::fs::remove_dir_all(&old_dir).ok();
+    }
+    Ok(())
+}
+
+/// `install_zip` 的选项
+#[derive(Default)]
+pub struct ZipInstall<'a> {
+    /// 显示名，用于「解压 xxx...」提示
+    pub name: &'a str,
+    /// zip 内的顶层目录名（如 pgsql 的 `pgsql/`），不存在时退回唯一子目录，再退回解压根目录
+    pub subdir: Option<&'a str>,
+    /// 就位前必须存在的文件（相对于解压出的目录），缺失时保留原有版本并报错
+    pub required_file: Option<&'a str>,
+    /// 地址固定指向最新版（如 releases/latest），有缓存时重新验证
+    pub latest: bool,
+    /// 「解压 xxx...」等提示的接收方，None 时直接输出到终端
+    pub reporter: Option<&'a dyn Reporter>,
+}
+
+/// 下载 zip 并解压到 `install_dir`：解压到缓存目录下的临时目录，
+/// 取出顶层子目录后用 `swap_dir` 替换原有版本（跨盘时复制），最后清理临时目录
+pub async fn install_zip(
+    url: &str,
+    filename: &str,
+    cache_dir: &Path,
+    install_dir: &Path,
+    opts: &ZipInstall<'_>,
+) -> Result<()> {
+    let zip_path = match opts.latest {
+        true => download_latest(url, cache_dir, filename).await?,
+        false => download(url, cache_dir, filename).await?,
+    };
+
+    opts.reporter.unwrap_or(&Console).action(&format!("解压 {}...", opts.name));
+    let dir_name = install_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let tmp_dir = cache_dir.join(format!("{}-extract", dir_name));
+    if tmp_dir.exists() {
+        std::fs::remove_dir_all(&tmp_dir)
+            .with_context(|| format!("无法清理临时目录: {}", tmp_dir.display()))?;
+    }
+
+    let result = extract_zip(&zip_path, &tmp_dir).and_then(|_| {
+        let staged = opts
+            .subdir
+            .map(|name| tmp_dir.join(name))
+            .filter(|p| p.is_dir())
+            .or_else(|| find_single_subdir(&tmp_dir))
+            .unwrap_or_else(|| tmp_dir.clone());
+        if let Some(file) = opts.required_file {
+            if !staged.join(file).exists() {
+                anyhow::bail!("解压后未找到 {}，安装可能失败", file);
+            }
+        }
+        swap_dir(&staged, install_dir)
+    });
+    std::fs::remove_dir_all(&tmp_dir).ok();
+    result
+}
+
+/// 递归复制目录
+pub fn copy_dir_all(src: &Path, dest: &Path) -> Result<()> {
+    std::fs::create_dir_all(dest).with_context(|| format!("无法创建目录: {}", dest.display()))?;
+    for entry in std::fs::read_dir(src).with_context(|| format!("无法读取目录: {}", src.display()))? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)
+                .with_context(|| format!("复制文件失败: {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// 运行 exe 安装程序（如 Git 的静默安装程序），watch 见 watchdog::run_watched
+pub fn run_installer(exe_path: &Path, args: &[&str], watch: crate::watchdog::Watch) -> Result<()> {
+    let name = exe_path.file_name().map_or_else(|| exe_path.display().to_string(), |n| n.to_string_lossy().to_string());
+    let status = crate::watchdog::run_watched(std::process::Command::new(exe_path).args(args), &name, watch)
         .with_context(|| format!("无法启动安装程序: {}", exe_path.display()))?;
 
     if !status.success() {
@@ -134,3 +824,73 @@ pub fn run_installer(exe_path: &Path, args: &[&str]) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let t0 = Instant::now();
+        let mut bucket = TokenBucket::new(1000, t0);
+        // 起始有 1 秒的令牌
+        assert_eq!(bucket.take(1000, t0), Duration::ZERO);
+        assert_eq!(bucket.take(500, t0), Duration::from_millis(500));
+        // 等待结束后令牌恰好补平
+        assert_eq!(bucket.take(0, t0 + Duration::from_millis(500)), Duration::ZERO);
+        // 空闲很久也最多攒 1 秒
+        let later = t0 + Duration::from_secs(10);
+        assert_eq!(bucket.take(1500, later), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate("2097152"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_rate("512K"), Some(512 * 1024));
+        assert_eq!(parse_rate("2m"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_rate("5MB/s"), Some(5 * 1024 * 1024));
+        assert_eq!(parse_rate(" 1 GB "), Some(1 << 30));
+        assert_eq!(parse_rate("100B/s"), Some(100));
+        assert_eq!(parse_rate("0"), Some(0));
+        assert_eq!(parse_rate("fast"), None);
+        assert_eq!(parse_rate("1.5M"), None);
+        assert_eq!(parse_rate("99999999999G"), None);
+    }
+
+    #[test]
+    fn test_validator_matches() {
+        let etag = |e: &str| Validator { etag: Some(e.to_string()), last_modified: None };
+        let lm = |t: &str| Validator { etag: None, last_modified: Some(t.to_string()) };
+        assert!(etag("\"a1\"").matches(&etag("\"a1\"")));
+        assert!(!etag("\"a1\"").matches(&etag("\"b2\"")));
+        assert!(lm("Tue, 01 Jul 2025 08:00:00 GMT").matches(&lm("Tue, 01 Jul 2025 08:00:00 GMT")));
+        assert!(!Validator::default().matches(&Validator::default()));
+
+        // 来源记录的 JSON 格式不变
+        let meta: CacheMeta = serde_json::from_str(r#"{"url":"https://x/a.zip","etag":"\"a1\""}"#).unwrap();
+        assert_eq!(meta.validator, etag("\"a1\""));
+    }
+
+    #[test]
+    fn test_content_matches() {
+        let zip = b"PK\x03\x04rest";
+        assert!(content_matches("go1.24.0.windows-amd64.zip", 4096, zip));
+        assert!(content_matches("Git-2.47.1-64-bit.EXE", 4096, b"MZ\x90\x00"));
+        assert!(content_matches("chrome.msi", 4096, &[0xD0, 0xCF, 0x11, 0xE0, 0xA1]));
+        assert!(content_matches("node.tar.gz", 4096, &[0x1F, 0x8B, 0x08]));
+        // 错误页面、空响应
+        assert!(!content_matches("go.zip", 4096, b"<!DOCTYPE html><html>404</html>"));
+        assert!(!content_matches("go.zip", 4, zip));
+        assert!(!content_matches("rustup-init.exe", 4096, b"PK\x03\x04"));
+        // 未知类型只排除 HTML
+        assert!(content_matches("uv-installer.ps1", 10, b"$ErrorActionPreference"));
+        assert!(!content_matches("uv-installer.ps1", 4096, b"\n  <HTML><body>captcha"));
+        assert!(content_matches("claude-2.0.0-linux-x64", 4096, b"\x7fELF"));
+    }
+
+    #[test]
+    fn test_download_progress_unknown_size() {
+        assert_eq!(download_progress(0).length(), None);
+        assert_eq!(download_progress(2048).length(), Some(2048));
+    }
+}
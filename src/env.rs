@@ -1,8 +1,36 @@
 use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use crate::config::HudoConfig;
+use crate::installer::{EnvAction, Installer};
+use crate::registry::InstallRegistry;
 
 /// 跨平台环境变量管理器
 pub struct EnvManager;
 
+/// 隔离位置，设置后不再读写真实的用户环境
+static SANDBOX: OnceLock<String> = OnceLock::new();
+
+/// 把环境变量的读写重定向到隔离位置，供集成测试使用（进程内只能设置一次）：
+/// Windows 读写 HKCU\{location} 而非 HKCU\Environment，Unix 读写 {location} 文件而非 ~/.hudo/env.sh
+pub fn redirect_to(location: &str) -> bool {
+    SANDBOX.set(location.to_string()).is_ok()
+}
+
+/// --no-registry：不修改用户环境变量与系统 PATH，只生成会话脚本（见 write_session_scripts）
+static SESSION_ONLY: AtomicBool = AtomicBool::new(false);
+
+pub fn set_session_only(on: bool) {
+    SESSION_ONLY.store(on, Ordering::Relaxed);
+}
+
+pub fn session_only() -> bool {
+    SESSION_ONLY.load(Ordering::Relaxed)
+}
+
 // ── Windows 实现：注册表 ────────────────────────────────────────────────────
 
 #[cfg(windows)]
@@ -15,9 +43,17 @@ mod platform {
 
     const ENV_KEY: &str = "Environment";
 
-    pub fn get_var(name: &str) -> Result<Option<String>> {
+    fn open_env(flags: u32) -> std::io::Result<RegKey> {
         let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-        let env = hkcu.open_subkey(ENV_KEY).context("无法打开注册表 HKCU\\Environment")?;
+        match SANDBOX.get() {
+            // 隔离键不一定已存在
+            Some(key) => hkcu.create_subkey_with_flags(key, flags).map(|(k, _)| k),
+            None => hkcu.open_subkey_with_flags(ENV_KEY, flags),
+        }
+    }
+
+    pub fn get_var(name: &str) -> Result<Option<String>> {
+        let env = open_env(KEY_READ).context("无法打开注册表 HKCU\\Environment")?;
         match env.get_value::<String, _>(name) {
             Ok(val) => Ok(Some(val)),
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
@@ -26,10 +62,7 @@ mod platform {
     }
 
     pub fn set_var(name: &str, value: &str) -> Result<()> {
-        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-        let env = hkcu
-            .open_subkey_with_flags(ENV_KEY, KEY_SET_VALUE)
-            .context("无法打开注册表 HKCU\\Environment（写入）")?;
+        let env = open_env(KEY_SET_VALUE).context("无法打开注册表 HKCU\\Environment（写入）")?;
         env.set_raw_value(name, &winreg::RegValue {
             vtype: REG_EXPAND_SZ,
             bytes: to_reg_sz(value),
@@ -39,10 +72,7 @@ mod platform {
     }
 
     pub fn delete_var(name: &str) -> Result<()> {
-        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-        let env = hkcu
-            .open_subkey_with_flags(ENV_KEY, KEY_SET_VALUE)
-            .context("无法打开注册表 HKCU\\Environment（写入）")?;
+        let env = open_env(KEY_SET_VALUE).context("无法打开注册表 HKCU\\Environment（写入）")?;
         match env.delete_value(name) {
             Ok(()) => Ok(()),
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
@@ -80,9 +110,44 @@ mod platform {
         Ok(())
     }
 
+    /// 用户 PATH 的各项（保留 %JAVA_HOME% 等未展开的写法）
+    pub fn user_path() -> Result<Vec<String>> {
+        let current = get_var("Path")?.unwrap_or_default();
+        Ok(current.split(';').filter(|s| !s.is_empty()).map(String::from).collect())
+    }
+
+    const SYSTEM_ENV_KEY: &str = r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment";
+
+    /// 系统 PATH（HKLM），读取失败时返回 None
+    pub fn system_path() -> Option<String> {
+        RegKey::predef(HKEY_LOCAL_MACHINE)
+            .open_subkey_with_flags(SYSTEM_ENV_KEY, KEY_READ)
+            .and_then(|k| k.get_value::<String, _>("Path"))
+            .ok()
+    }
+
+    /// 写入系统 PATH（HKLM），需要管理员权限
+    pub fn set_system_path(value: &str) -> Result<()> {
+        if SANDBOX.get().is_some() {
+            anyhow::bail!("隔离环境中不修改系统 PATH");
+        }
+        let env = RegKey::predef(HKEY_LOCAL_MACHINE)
+            .open_subkey_with_flags(SYSTEM_ENV_KEY, KEY_SET_VALUE)
+            .context("无法打开注册表 HKLM 系统环境变量（写入需要管理员权限）")?;
+        env.set_raw_value("Path", &winreg::RegValue {
+            vtype: REG_EXPAND_SZ,
+            bytes: to_reg_sz(value),
+        })
+        .context("写入系统 PATH 失败")
+    }
+
     pub fn broadcast_change() {
         use windows_sys::Win32::UI::WindowsAndMessaging::*;
 
+        if SANDBOX.get().is_some() {
+            return;
+        }
+
         let env_wide: Vec<u16> = OsStr::new("Environment")
             .encode_wide()
             .chain(std::iter::once(0))
@@ -143,12 +208,17 @@ mod platform {
 
     /// hudo 专用的环境配置文件
     fn hudo_env_path() -> Result<PathBuf> {
-        let home = dirs::home_dir().context("无法获取用户主目录")?;
-        Ok(home.join(".hudo").join("env.sh"))
+        if let Some(path) = SANDBOX.get() {
+            return Ok(PathBuf::from(path));
+        }
+        crate::paths::env_sh_path()
     }
 
     /// 确保 shell profile source 了 hudo env 文件
     fn ensure_sourced() -> Result<()> {
+        if SANDBOX.get().is_some() {
+            return Ok(());
+        }
         let env_path = hudo_env_path()?;
         let profile = shell_profile()?;
 
@@ -266,6 +336,28 @@ mod platform {
         Ok(())
     }
 
+    /// hudo env.sh 中加入 PATH 的目录，按在 PATH 中的先后顺序
+    pub fn user_path() -> Result<Vec<String>> {
+        // 每行都加在 $PATH 前面，后写入的排在前面
+        let mut entries: Vec<String> = read_env_lines()?
+            .iter()
+            .filter_map(|l| l.strip_prefix("export PATH=\""))
+            .filter_map(|rest| rest.strip_suffix(":$PATH\""))
+            .map(String::from)
+            .collect();
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// Unix 没有系统 / 用户 PATH 的长度限制
+    pub fn system_path() -> Option<String> {
+        None
+    }
+
+    pub fn set_system_path(_value: &str) -> Result<()> {
+        anyhow::bail!("系统 PATH 仅在 Windows 上可修改")
+    }
+
     pub fn broadcast_change() {
         // Unix 下无需广播，环境变量在新 shell 中自动生效
     }
@@ -279,22 +371,436 @@ impl EnvManager {
     }
 
     pub fn set_var(name: &str, value: &str) -> Result<()> {
+        if session_only() {
+            return Ok(());
+        }
         platform::set_var(name, value)
     }
 
     pub fn append_to_path(new_path: &str) -> Result<()> {
+        if session_only() {
+            return Ok(());
+        }
         platform::append_to_path(new_path)
     }
 
     pub fn delete_var(name: &str) -> Result<()> {
+        if session_only() {
+            return Ok(());
+        }
         platform::delete_var(name)
     }
 
     pub fn remove_from_path(target: &str) -> Result<()> {
+        if session_only() {
+            return Ok(());
+        }
         platform::remove_from_path(target)
     }
 
+    pub fn user_path() -> Result<Vec<String>> {
+        platform::user_path()
+    }
+
+    pub fn system_path() -> Option<String> {
+        platform::system_path()
+    }
+
+    /// 系统 PATH 的各项，读取失败时为空
+    pub fn system_path_entries() -> Vec<String> {
+        Self::system_path()
+            .unwrap_or_default()
+            .split(';')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn set_system_path(value: &str) -> Result<()> {
+        if session_only() {
+            return Ok(());
+        }
+        platform::set_system_path(value)
+    }
+
     pub fn broadcast_change() {
         platform::broadcast_change()
     }
 }
+
+// ── 环境变量更改预览 ────────────────────────────────────────────────────────
+
+/// 系统 PATH 与用户 PATH 展开后合计超过该长度（UTF-16 字符）时，
+/// 部分程序和 cmd 会截断或忽略超出的部分，新加的目录看似已加入却找不到命令
+pub const PATH_EXPAND_LIMIT: usize = 2047;
+
+/// 距离上限不足该长度时提示
+const PATH_WARN_MARGIN: usize = 200;
+
+/// 新目录在 PATH 中的位置：Unix 的 env.sh 把目录加在 $PATH 前面，Windows 追加到用户 PATH 末尾
+const PATH_PREPENDS: bool = cfg!(unix);
+
+/// 单项环境变量更改
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvChange {
+    /// 设置变量，old 为 None 表示新增
+    Set {
+        name: String,
+        old: Option<String>,
+        new: String,
+    },
+    /// 加入 PATH：position 为应用后在用户 PATH 中的位置（从 1 开始），exists 表示已在 PATH 中
+    AppendPath {
+        path: String,
+        position: usize,
+        total: usize,
+        exists: bool,
+    },
+}
+
+/// 一次安装将要应用的环境变量更改
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvPreview {
+    pub changes: Vec<EnvChange>,
+    /// 应用后系统 PATH + 用户 PATH 展开后的长度，无法读取系统 PATH 时为 None
+    pub path_len: Option<usize>,
+}
+
+impl EnvPreview {
+    /// 读取当前用户环境变量与系统 PATH，计算 actions 应用后的结果
+    pub fn build(actions: &[EnvAction]) -> Result<Self> {
+        let user_path = EnvManager::user_path()?;
+        let mut user_vars = BTreeMap::new();
+        for action in actions {
+            if let EnvAction::Set { name, .. } = action {
+                user_vars.insert(name.clone(), EnvManager::get_var(name)?);
+            }
+        }
+        let system_path = EnvManager::system_path();
+        Ok(Self::compute(
+            actions,
+            user_path,
+            |name| user_vars.get(name).cloned().flatten(),
+            system_path.as_deref(),
+            |name| {
+                EnvManager::get_var(name)
+                    .ok()
+                    .flatten()
+                    .or_else(|| std::env::var(name).ok())
+            },
+        ))
+    }
+
+    /// 纯计算部分：user_var 读取变量当前值，lookup 用于展开 PATH 中的 %VAR%（不含本次设置的变量）
+    pub fn compute(
+        actions: &[EnvAction],
+        mut user_path: Vec<String>,
+        user_var: impl Fn(&str) -> Option<String>,
+        system_path: Option<&str>,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let mut sets = BTreeMap::new();
+        let mut appended = Vec::new();
+        let mut changes = Vec::new();
+        for action in actions {
+            match action {
+                EnvAction::Set { name, value } => {
+                    sets.insert(name.to_lowercase(), value.clone());
+                    changes.push(EnvChange::Set {
+                        name: name.clone(),
+                        old: user_var(name),
+                        new: value.clone(),
+                    });
+                }
+                EnvAction::AppendPath { path } => {
+                    let exists = user_path.iter().any(|p| p.eq_ignore_ascii_case(path));
+                    if !exists {
+                        match PATH_PREPENDS {
+                            true => user_path.insert(0, path.clone()),
+                            false => user_path.push(path.clone()),
+                        }
+                    }
+                    appended.push((path.clone(), exists));
+                }
+            }
+        }
+        for (path, exists) in appended {
+            let position = user_path
+                .iter()
+                .position(|p| p.eq_ignore_ascii_case(&path))
+                .map_or(0, |i| i + 1);
+            changes.push(EnvChange::AppendPath {
+                path,
+                position,
+                total: user_path.len(),
+                exists,
+            });
+        }
+
+        let lookup = |name: &str| sets.get(&name.to_lowercase()).cloned().or_else(|| lookup(name));
+        let path_len = system_path.map(|system| expanded_path_len(system, &user_path.join(";"), lookup));
+        Self { changes, path_len }
+    }
+
+    /// 没有任何实际变化（变量值相同、PATH 项都已存在）
+    pub fn is_noop(&self) -> bool {
+        self.changes.iter().all(|c| match c {
+            EnvChange::Set { old, new, .. } => old.as_deref() == Some(new.as_str()),
+            EnvChange::AppendPath { exists, .. } => *exists,
+        })
+    }
+
+    /// PATH 展开后的长度已接近或超过上限
+    pub fn path_near_limit(&self) -> bool {
+        self.path_len
+            .is_some_and(|len| len + PATH_WARN_MARGIN > PATH_EXPAND_LIMIT)
+    }
+}
+
+/// 进程看到的 PATH = 系统 PATH + ";" + 用户 PATH，两者中的 %VAR%（REG_EXPAND_SZ）均先展开；
+/// 返回 UTF-16 长度
+pub fn expanded_path_len(system: &str, user: &str, lookup: impl Fn(&str) -> Option<String>) -> usize {
+    let system = expand_vars(system.trim_end_matches(';'), &lookup);
+    let user = expand_vars(user.trim_end_matches(';'), &lookup);
+    let sep = usize::from(!system.is_empty() && !user.is_empty());
+    system.encode_utf16().count() + sep + user.encode_utf16().count()
+}
+
+/// 按 ExpandEnvironmentStrings 的规则展开 %VAR%：变量不存在时保留原样
+pub(crate) fn expand_vars(value: &str, lookup: &impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('%') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('%') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        match lookup(&after[..end]).filter(|_| end > 0) {
+            Some(v) => {
+                out.push_str(&v);
+                rest = &after[end + 1..];
+            }
+            // 第二个 % 可能是下一个变量的开头
+            None => {
+                out.push('%');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// ── hudo env export ─────────────────────────────────────────────────────────
+
+/// `hudo env export` 的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// KEY=value，每行一个
+    Dotenv,
+    /// {"vars": {...}, "path": [...]}
+    Json,
+    /// 追加到 $GITHUB_ENV / $GITHUB_PATH 的 shell 命令
+    GithubActions,
+    /// PowerShell 会话脚本（. .\hudo-env.ps1）
+    Powershell,
+    /// cmd 会话脚本（call hudo-env.cmd）
+    Cmd,
+}
+
+/// 已安装工具设置的环境变量与 PATH 追加项（值为配置中的原始路径，不展开当前进程的环境变量）
+#[derive(Debug, Default, serde::Serialize)]
+pub struct EnvExport {
+    pub vars: BTreeMap<String, String>,
+    pub path: Vec<String>,
+}
+
+impl EnvExport {
+    /// 按 state.json 中记录的安装路径重新计算各工具的 env_actions；`tool` 为 Some 时只导出该工具
+    pub fn collect(
+        reg: &InstallRegistry,
+        installers: &[Box<dyn Installer>],
+        config: &HudoConfig,
+        tool: Option<&str>,
+    ) -> Self {
+        let mut export = Self::default();
+        for inst in installers {
+            let id = inst.info().id;
+            if tool.is_some_and(|t| t != id) {
+                continue;
+            }
+            if let Some(state) = reg.get(id) {
+                let install_path = PathBuf::from(&state.install_path);
+                export.add(&inst.env_actions(&install_path, config));
+            }
+        }
+        export
+    }
+
+    pub fn add(&mut self, actions: &[EnvAction]) {
+        for action in actions {
+            match action {
+                EnvAction::Set { name, value } => {
+                    self.vars.insert(name.clone(), value.clone());
+                }
+                EnvAction::AppendPath { path } => {
+                    if !self.path.contains(path) {
+                        self.path.push(path.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// 按格式输出；dotenv 中 PATH 追加项写成 HUDO_PATH（; 分隔），不覆盖 PATH
+    pub fn render(&self, format: ExportFormat) -> String {
+        let mut out = String::new();
+        match format {
+            ExportFormat::Dotenv => {
+                for (name, value) in &self.vars {
+                    out.push_str(&format!("{}={}\n", name, dotenv_quote(value)));
+                }
+                if !self.path.is_empty() {
+                    out.push_str(&format!("HUDO_PATH={}\n", dotenv_quote(&self.path.join(";"))));
+                }
+            }
+            ExportFormat::Json => {
+                out = serde_json::to_string_pretty(self).unwrap_or_default();
+                out.push('\n');
+            }
+            ExportFormat::GithubActions => {
+                for (name, value) in &self.vars {
+                    out.push_str(&format!(
+                        "echo {} >> \"$GITHUB_ENV\"\n",
+                        sh_quote(&format!("{}={}", name, value))
+                    ));
+                }
+                for path in &self.path {
+                    out.push_str(&format!("echo {} >> \"$GITHUB_PATH\"\n", sh_quote(path)));
+                }
+            }
+            ExportFormat::Powershell => {
+                for (name, value) in &self.vars {
+                    out.push_str(&format!("$env:{} = {}\n", name, ps_quote(value)));
+                }
+                if !self.path.is_empty() {
+                    out.push_str(&format!("$env:Path = {} + ';' + $env:Path\n", ps_quote(&self.path.join(";"))));
+                }
+            }
+            ExportFormat::Cmd => {
+                out.push_str("@echo off\r\n");
+                for (name, value) in &self.vars {
+                    out.push_str(&format!("set \"{}={}\"\r\n", name, value));
+                }
+                if !self.path.is_empty() {
+                    out.push_str(&format!("set \"PATH={};%PATH%\"\r\n", self.path.join(";")));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// dotenv 值：含空白、引号或 # 时加双引号（反斜杠保持原样，Windows 路径无需转义）
+fn dotenv_quote(value: &str) -> String {
+    if value.contains([' ', '\t', '"', '\'', '#']) {
+        format!("\"{}\"", value.replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// shell 单引号字符串：内容不展开，单引号写成 '\''
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// PowerShell 单引号字符串：内容不展开，单引号写成两个
+fn ps_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// --no-registry 时按安装记录生成会话脚本 hudo-env.ps1 / hudo-env.cmd，返回内容有变化、重新写入的路径
+pub fn write_session_scripts(
+    reg: &InstallRegistry,
+    installers: &[Box<dyn Installer>],
+    config: &HudoConfig,
+) -> Result<Vec<PathBuf>> {
+    let export = EnvExport::collect(reg, installers, config, None);
+    let mut written = Vec::new();
+    for (ext, format) in [("ps1", ExportFormat::Powershell), ("cmd", ExportFormat::Cmd)] {
+        let path = crate::paths::session_script_path(ext)?;
+        let content = export.render(format);
+        if std::fs::read_to_string(&path).is_ok_and(|old| old == content) {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("无法创建目录: {}", parent.display()))?;
+        }
+        std::fs::write(&path, content)
+            .with_context(|| format!("无法写入会话脚本: {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+// ── PATH 项归属（hudo doctor） ──────────────────────────────────────────────
+
+/// PATH 中与 hudo 相关的一项
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathOwner {
+    pub entry: String,
+    /// 加入该目录的工具 id；None 表示位于安装根目录下、但没有已安装的工具声明它（孤立项）
+    pub tool: Option<String>,
+}
+
+/// 按 state.json 中记录的安装路径重新计算各工具 env_actions 中加入 PATH 的目录：(工具 id, 目录)
+pub fn path_claims(
+    reg: &InstallRegistry,
+    installers: &[Box<dyn Installer>],
+    config: &HudoConfig,
+) -> Vec<(String, String)> {
+    let mut claims = Vec::new();
+    for inst in installers {
+        let id = inst.info().id;
+        let Some(state) = reg.get(id) else {
+            continue;
+        };
+        for action in inst.env_actions(&PathBuf::from(&state.install_path), config) {
+            if let EnvAction::AppendPath { path } = action {
+                claims.push((id.to_string(), path));
+            }
+        }
+    }
+    claims
+}
+
+/// 标注 PATH 各项的归属：与 claims 中目录相同的项归对应工具，其余位于 root 下的为孤立项，
+/// 与 hudo 无关的项不返回；两边的 %VAR% 都用 lookup 展开后比较
+pub fn path_owners(
+    entries: &[String],
+    claims: &[(String, String)],
+    root: &std::path::Path,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Vec<PathOwner> {
+    let expand = |p: &str| PathBuf::from(expand_vars(p, &lookup));
+    let same = |a: &std::path::Path, b: &std::path::Path| {
+        crate::running::is_under(a, b) && crate::running::is_under(b, a)
+    };
+    entries
+        .iter()
+        .filter(|e| !e.trim().is_empty())
+        .filter_map(|entry| {
+            let dir = expand(entry);
+            let tool = claims
+                .iter()
+                .find(|(_, path)| same(&dir, &expand(path)))
+                .map(|(id, _)| id.clone());
+            (tool.is_some() || crate::running::is_under(&dir, root))
+                .then(|| PathOwner { entry: entry.clone(), tool })
+        })
+        .collect()
+}
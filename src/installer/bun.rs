@@ -1,4 +1,4 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use async_trait::async_trait;
 use std::path::PathBuf;
 
@@ -15,6 +15,8 @@ impl Installer for BunInstaller {
             id: "bun",
             name: "Bun",
             description: "JavaScript/TypeScript 运行时与包管理器",
+            homepage: Some("https://bun.sh"),
+            post_install_tips: &[],
         }
     }
 
@@ -23,7 +25,7 @@ impl Installer for BunInstaller {
         if bun_exe.exists() {
             if let Ok(out) = std::process::Command::new(&bun_exe).arg("--version").output() {
                 if out.status.success() {
-                    let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                    let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                     return Ok(DetectResult::InstalledByHudo(version));
                 }
             }
@@ -31,7 +33,7 @@ impl Installer for BunInstaller {
 
         if let Ok(out) = std::process::Command::new("bun").arg("--version").output() {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -53,30 +55,20 @@ impl Installer for BunInstaller {
         let install_dir = config.tools_dir().join("bun");
         let (url, filename) = self.resolve_download(config);
 
-        let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
-
-        // 解压到临时目录，再把内容移到 tools/bun/
-        crate::ui::print_action("解压 Bun...");
-        let tmp_dir = config.cache_dir().join("bun-extract");
-        if tmp_dir.exists() {
-            std::fs::remove_dir_all(&tmp_dir).ok();
-        }
-        download::extract_zip(&zip_path, &tmp_dir)?;
-
-        // zip 内有 bun-windows-x64/ 子目录，把内容移到 install_dir
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir).ok();
-        }
-        let inner = tmp_dir.join("bun-windows-x64");
-        if inner.exists() {
-            std::fs::rename(&inner, &install_dir)
-                .context("移动 Bun 文件失败")?;
-        } else {
-            // 如果没有子目录，直接重命名 tmp
-            std::fs::rename(&tmp_dir, &install_dir)
-                .context("移动 Bun 文件失败")?;
-        }
-        std::fs::remove_dir_all(&tmp_dir).ok();
+        download::install_zip(
+            &url,
+            &filename,
+            &config.cache_dir(),
+            &install_dir,
+            &download::ZipInstall {
+                name: "Bun",
+                reporter: Some(ctx.reporter),
+                subdir: Some("bun-windows-x64"),
+                latest: true,
+                ..Default::default()
+            },
+        )
+        .await?;
 
         let version = get_bun_version(&install_dir).unwrap_or_else(|| "unknown".to_string());
 
@@ -86,6 +78,14 @@ impl Installer for BunInstaller {
         })
     }
 
+    fn latest_download(&self) -> bool {
+        true
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        &["bun.exe"]
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![EnvAction::AppendPath {
             path: install_path.to_string_lossy().to_string(),
@@ -100,5 +100,5 @@ fn get_bun_version(install_dir: &PathBuf) -> Option<String> {
         .output()
         .ok()
         .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .map(|o| crate::proc::decode_output(&o.stdout).trim().to_string())
 }
@@ -1,8 +1,8 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use super::{run_as_admin, DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use super::{run_as_admin, DetectResult, EnvAction, InstallContext, InstallResult, Installer, ShortcutSpec, ToolInfo};
 use crate::config::HudoConfig;
 use crate::download;
 use crate::registry::InstallRegistry;
@@ -19,6 +19,8 @@ impl Installer for ChromeInstaller {
             id: "chrome",
             name: "Google Chrome",
             description: "Google Chrome 浏览器（路径由 Google 安装程序决定）",
+            homepage: Some("https://www.google.com/chrome/"),
+            post_install_tips: &[],
         }
     }
 
@@ -48,20 +50,26 @@ impl Installer for ChromeInstaller {
         let config = ctx.config;
         let (url, filename) = self.resolve_download(config);
 
-        let msi_path = download::download(&url, &config.cache_dir(), &filename).await?;
+        // 地址固定指向最新版，有缓存时重新验证
+        let msi_path = download::download_latest(&url, &config.cache_dir(), &filename).await?;
         let msi_str = msi_path.to_string_lossy().to_string();
 
-        crate::ui::print_action("安装 Google Chrome（需要管理员权限）...");
+        ctx.reporter.action("安装 Google Chrome（需要管理员权限）...");
 
         // 先直接尝试（hudo 以管理员运行时直接成功）
-        let direct_ok = std::process::Command::new("msiexec")
-            .args(["/i", &msi_str, "/quiet", "/norestart"])
-            .status()
-            .map(|s| matches!(s.code(), Some(0) | Some(3010)))
-            .unwrap_or(false);
+        let direct = crate::watchdog::run_watched(
+            std::process::Command::new("msiexec").args(["/i", &msi_str, "/quiet", "/norestart"]),
+            "msiexec",
+            config.install.silent_watch(config.yes),
+        );
+        let direct_ok = match direct {
+            Ok(s) => matches!(s.code(), Some(0) | Some(3010)),
+            Err(e) if crate::watchdog::is_aborted(&e) => return Err(e),
+            Err(_) => false,
+        };
 
         if !direct_ok {
-            crate::ui::print_info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
+            ctx.reporter.info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
             run_as_admin("msiexec", &["/i", &msi_str, "/quiet", "/norestart"])
                 .context("Chrome 安装失败")?;
         }
@@ -77,11 +85,19 @@ impl Installer for ChromeInstaller {
         })
     }
 
+    fn binaries(&self) -> &'static [&'static str] {
+        &["chrome.exe"]
+    }
+
+    fn shortcuts(&self, install_path: &Path) -> Vec<ShortcutSpec> {
+        vec![ShortcutSpec::new("Google Chrome", install_path.join("chrome.exe"))]
+    }
+
     fn env_actions(&self, _install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![] // Chrome 不是命令行工具，不需要添加到 PATH
     }
 
-    async fn pre_uninstall(&self, _ctx: &InstallContext<'_>) -> Result<()> {
+    async fn pre_uninstall(&self, ctx: &InstallContext<'_>) -> Result<()> {
         // Chrome 自带卸载程序位于 Application/<version>/Installer/setup.exe
         if let Some(app_dir) = find_chrome_app_dir() {
             if let Ok(entries) = std::fs::read_dir(&app_dir) {
@@ -89,7 +105,7 @@ impl Installer for ChromeInstaller {
                     let versioned = entry.path();
                     let setup = versioned.join("Installer").join("setup.exe");
                     if versioned.is_dir() && setup.exists() {
-                        crate::ui::print_action("运行 Chrome 卸载程序...");
+                        ctx.reporter.action("运行 Chrome 卸载程序...");
                         let _ = std::process::Command::new(&setup)
                             .args(["--uninstall", "--force-uninstall"])
                             .status();
@@ -98,7 +114,7 @@ impl Installer for ChromeInstaller {
                 }
             }
         }
-        crate::ui::print_warning("未找到 Chrome 内置卸载程序，请通过「控制面板」手动卸载");
+        ctx.reporter.warning("未找到 Chrome 内置卸载程序，请通过「控制面板」手动卸载");
         Ok(())
     }
 }
@@ -132,18 +148,16 @@ fn find_chrome_app_dir() -> Option<PathBuf> {
 }
 
 fn get_chrome_version(chrome_exe: &PathBuf) -> Option<String> {
-    let path_str = chrome_exe.to_string_lossy();
     let ps_cmd = format!(
-        "(Get-Item '{}').VersionInfo.FileVersion",
-        path_str.replace('\'', "''")
+        "(Get-Item {}).VersionInfo.FileVersion",
+        crate::proc::ps_quote(&chrome_exe.to_string_lossy())
     );
-    std::process::Command::new("powershell")
-        .args(["-NoProfile", "-Command", &ps_cmd])
+    crate::proc::powershell(&ps_cmd)
         .output()
         .ok()
         .filter(|o| o.status.success())
         .and_then(|o| {
-            let v = String::from_utf8_lossy(&o.stdout).trim().to_string();
+            let v = crate::proc::decode_output(&o.stdout).trim().to_string();
             if v.is_empty() { None } else { Some(v) }
         })
 }
@@ -3,9 +3,9 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 
 use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use crate::arch::Arch;
 use crate::config::HudoConfig;
 use crate::download;
-use crate::ui;
 
 pub struct ClaudeCodeInstaller;
 
@@ -36,8 +36,8 @@ fn is_musl() -> bool {
     }
     // 方法 2：通过 ldd 输出判断
     if let Ok(out) = std::process::Command::new("ldd").arg("--version").output() {
-        let stderr = String::from_utf8_lossy(&out.stderr);
-        let stdout = String::from_utf8_lossy(&out.stdout);
+        let stderr = crate::proc::decode_output(&out.stderr);
+        let stdout = crate::proc::decode_output(&out.stdout);
         if stderr.contains("musl") || stdout.contains("musl") {
             return true;
         }
@@ -45,23 +45,27 @@ fn is_musl() -> bool {
     false
 }
 
-/// 根据操作系统和 CPU 架构返回 GCS 平台标识
-fn platform_key() -> String {
-    let arch = match std::env::consts::ARCH {
-        "aarch64" => "arm64",
-        _ => "x64",
+/// 根据操作系统和目标架构返回 GCS 平台标识
+fn platform_key(arch: Arch) -> String {
+    #[cfg(target_os = "linux")]
+    let musl = is_musl();
+    #[cfg(not(target_os = "linux"))]
+    let musl = false;
+    gcs_platform(std::env::consts::OS, arch, musl)
+}
+
+/// ("windows", Arm64, _) → "win32-arm64"，("linux", X64, true) → "linux-x64-musl"
+fn gcs_platform(os: &str, arch: Arch, musl: bool) -> String {
+    let arch = match arch {
+        Arch::X64 => "x64",
+        Arch::Arm64 => "arm64",
     };
 
-    match std::env::consts::OS {
+    match os {
         "windows" => format!("win32-{}", arch),
         "macos" => format!("darwin-{}", arch),
-        "linux" => {
-            #[cfg(target_os = "linux")]
-            if is_musl() {
-                return format!("linux-{}-musl", arch);
-            }
-            format!("linux-{}", arch)
-        }
+        "linux" if musl => format!("linux-{}-musl", arch),
+        "linux" => format!("linux-{}", arch),
         other => format!("{}-{}", other, arch),
     }
 }
@@ -107,16 +111,6 @@ async fn fetch_manifest_sha256(version: &str, platform: &str) -> Result<String>
     Ok(sha.to_string())
 }
 
-/// 计算文件 SHA256
-fn sha256_file(path: &std::path::Path) -> Result<String> {
-    use sha2::{Digest, Sha256};
-    let mut hasher = Sha256::new();
-    let mut file = std::fs::File::open(path)
-        .with_context(|| format!("无法打开文件: {}", path.display()))?;
-    std::io::copy(&mut file, &mut hasher).context("计算 SHA256 失败")?;
-    Ok(format!("{:x}", hasher.finalize()))
-}
-
 #[async_trait]
 impl Installer for ClaudeCodeInstaller {
     fn info(&self) -> ToolInfo {
@@ -124,6 +118,11 @@ impl Installer for ClaudeCodeInstaller {
             id: "claude-code",
             name: "Claude Code",
             description: "Anthropic Claude AI 命令行工具",
+            homepage: Some("https://docs.anthropic.com/claude-code"),
+            post_install_tips: &[
+                "登录: claude login（或设置环境变量 ANTHROPIC_API_KEY）",
+                "切换 API 供应商: hudo cc",
+            ],
         }
     }
 
@@ -132,7 +131,7 @@ impl Installer for ClaudeCodeInstaller {
         if exe.exists() {
             if let Ok(out) = std::process::Command::new(&exe).arg("--version").output() {
                 if out.status.success() {
-                    let version = parse_claude_version(&String::from_utf8_lossy(&out.stdout));
+                    let version = parse_claude_version(&crate::proc::decode_output(&out.stdout));
                     return Ok(DetectResult::InstalledByHudo(version));
                 }
             }
@@ -142,7 +141,7 @@ impl Installer for ClaudeCodeInstaller {
         // 回退检查系统 PATH
         if let Ok(out) = std::process::Command::new("claude").arg("--version").output() {
             if out.status.success() {
-                let version = parse_claude_version(&String::from_utf8_lossy(&out.stdout));
+                let version = parse_claude_version(&crate::proc::decode_output(&out.stdout));
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -156,7 +155,7 @@ impl Installer for ClaudeCodeInstaller {
             .claude_code
             .as_deref()
             .unwrap_or(DEFAULT_VERSION);
-        let platform = platform_key();
+        let platform = platform_key(config.arch());
         let exe = exe_name();
         let url = format!("{}/{}/{}/{}", GCS_BUCKET, version, platform, exe);
         let filename = format!("claude-{}-{}{}", version, platform,
@@ -164,6 +163,14 @@ impl Installer for ClaudeCodeInstaller {
         (url, filename)
     }
 
+    fn default_version(&self) -> Option<&'static str> {
+        Some(DEFAULT_VERSION)
+    }
+
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("可用版本见 https://www.npmjs.com/package/@anthropic-ai/claude-code?activeTab=versions")
+    }
+
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
         let config = ctx.config;
         let install_dir = config.tools_dir().join("claude-code");
@@ -172,18 +179,16 @@ impl Installer for ClaudeCodeInstaller {
         let version = match &config.versions.claude_code {
             Some(v) => v.clone(),
             None => {
-                ui::print_action("查询 Claude Code 最新版本...");
-                crate::version::claude_code_latest()
-                    .await
-                    .unwrap_or_else(|| DEFAULT_VERSION.to_string())
+                ctx.reporter.action("查询 Claude Code 最新版本...");
+                crate::version::or_default(ctx.reporter, crate::version::claude_code_latest().await, DEFAULT_VERSION)
             }
         };
 
-        let platform = platform_key();
+        let platform = platform_key(config.arch());
         let exe = exe_name();
 
         // 2. 获取 manifest SHA256
-        ui::print_action("获取校验信息...");
+        ctx.reporter.action("获取校验信息...");
         let expected_sha = fetch_manifest_sha256(&version, &platform).await?;
 
         // 3. 下载可执行文件
@@ -193,13 +198,13 @@ impl Installer for ClaudeCodeInstaller {
         let cached_path = download::download(&url, &config.cache_dir(), &filename).await?;
 
         // 4. SHA256 校验（失败时清除缓存自动重试一次）
-        ui::print_action("校验文件完整性...");
-        let actual_sha = sha256_file(&cached_path)?;
+        ctx.reporter.action("校验文件完整性...");
+        let actual_sha = download::sha256_file(&cached_path)?;
         let cached_path = if actual_sha != expected_sha {
-            ui::print_action("SHA256 不匹配，清除缓存重新下载...");
+            ctx.reporter.action("SHA256 不匹配，清除缓存重新下载...");
             std::fs::remove_file(&cached_path).ok();
             let retry_path = download::download(&url, &config.cache_dir(), &filename).await?;
-            let retry_sha = sha256_file(&retry_path)?;
+            let retry_sha = download::sha256_file(&retry_path)?;
             if retry_sha != expected_sha {
                 std::fs::remove_file(&retry_path).ok();
                 bail!(
@@ -212,7 +217,7 @@ impl Installer for ClaudeCodeInstaller {
         } else {
             cached_path
         };
-        ui::print_success("SHA256 校验通过");
+        ctx.reporter.success("SHA256 校验通过");
 
         // 5. 安装到 tools/claude-code/
         std::fs::create_dir_all(&install_dir)
@@ -236,17 +241,35 @@ impl Installer for ClaudeCodeInstaller {
         })
     }
 
+    fn arm64_native(&self) -> bool {
+        true
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        if cfg!(windows) {
+            &["claude.exe"]
+        } else {
+            &["claude"]
+        }
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![EnvAction::AppendPath {
             path: install_path.to_string_lossy().to_string(),
         }]
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    async fn configure(&self, _ctx: &InstallContext<'_>) -> Result<()> {
-        ui::print_title("配置 Claude Code");
-        ui::print_info("运行以下命令登录 Claude:");
-        ui::print_info("  claude login");
-        ui::print_info("或设置环境变量 ANTHROPIC_API_KEY");
-        Ok(())
+    #[test]
+    fn test_gcs_platform_per_arch() {
+        assert_eq!(gcs_platform("windows", Arch::X64, false), "win32-x64");
+        assert_eq!(gcs_platform("windows", Arch::Arm64, false), "win32-arm64");
+        assert_eq!(gcs_platform("macos", Arch::Arm64, false), "darwin-arm64");
+        assert_eq!(gcs_platform("linux", Arch::X64, true), "linux-x64-musl");
+        assert_eq!(gcs_platform("linux", Arch::Arm64, false), "linux-arm64");
     }
 }
@@ -3,9 +3,9 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 
 use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use crate::arch::Arch;
 use crate::config::HudoConfig;
 use crate::download;
-use crate::ui;
 
 pub struct GhInstaller;
 
@@ -18,6 +18,10 @@ impl Installer for GhInstaller {
             id: "gh",
             name: "GitHub CLI",
             description: "GitHub 官方命令行工具",
+            homepage: Some("https://cli.github.com"),
+            post_install_tips: &[
+                "登录 GitHub: gh auth login",
+            ],
         }
     }
 
@@ -33,7 +37,7 @@ impl Installer for GhInstaller {
                 if let Ok(out) = std::process::Command::new(gh_exe).arg("--version").output() {
                     if out.status.success() {
                         let version =
-                            parse_gh_version(&String::from_utf8_lossy(&out.stdout));
+                            parse_gh_version(&crate::proc::decode_output(&out.stdout));
                         return Ok(DetectResult::InstalledByHudo(version));
                     }
                 }
@@ -43,7 +47,7 @@ impl Installer for GhInstaller {
         // 系统 PATH
         if let Ok(out) = std::process::Command::new("gh").arg("--version").output() {
             if out.status.success() {
-                let version = parse_gh_version(&String::from_utf8_lossy(&out.stdout));
+                let version = parse_gh_version(&crate::proc::decode_output(&out.stdout));
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -53,12 +57,15 @@ impl Installer for GhInstaller {
 
     fn resolve_download(&self, config: &HudoConfig) -> (String, String) {
         let version = config.versions.gh.as_deref().unwrap_or(GH_VERSION_DEFAULT);
-        let filename = format!("gh_{}_windows_amd64.zip", version);
-        let url = format!(
-            "https://github.com/cli/cli/releases/download/v{}/{}",
-            version, filename
-        );
-        (url, filename)
+        build_url(version, config.arch())
+    }
+
+    fn default_version(&self) -> Option<&'static str> {
+        Some(GH_VERSION_DEFAULT)
+    }
+
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("可用版本见 https://github.com/cli/cli/releases")
     }
 
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
@@ -68,35 +75,26 @@ impl Installer for GhInstaller {
         let version = match &config.versions.gh {
             Some(v) => v.clone(),
             None => {
-                crate::ui::print_action("查询 GitHub CLI 最新版本...");
-                crate::version::gh_latest()
-                    .await
-                    .unwrap_or_else(|| GH_VERSION_DEFAULT.to_string())
+                ctx.reporter.action("查询 GitHub CLI 最新版本...");
+                crate::version::or_default(ctx.reporter, crate::version::gh_latest().await, GH_VERSION_DEFAULT)
             }
         };
 
-        let filename = format!("gh_{}_windows_amd64.zip", version);
-        let url = format!(
-            "https://github.com/cli/cli/releases/download/v{}/{}",
-            version, filename
-        );
-
-        let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
-
-        crate::ui::print_action("解压 GitHub CLI...");
-        let tmp_dir = config.cache_dir().join("gh-extract");
-        if tmp_dir.exists() {
-            std::fs::remove_dir_all(&tmp_dir).ok();
-        }
-        download::extract_zip(&zip_path, &tmp_dir)?;
-
-        // zip 内有形如 gh_{version}_windows_amd64/ 的子目录
-        let inner = download::find_single_subdir(&tmp_dir).unwrap_or(tmp_dir.clone());
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir).ok();
-        }
-        std::fs::rename(&inner, &install_dir).ok();
-        std::fs::remove_dir_all(&tmp_dir).ok();
+        let (url, filename) = build_url(&version, ctx.arch());
+
+        // zip 内有形如 gh_{version}_windows_{arch}/ 的子目录
+        download::install_zip(
+            &url,
+            &filename,
+            &config.cache_dir(),
+            &install_dir,
+            &download::ZipInstall {
+                name: "GitHub CLI",
+                reporter: Some(ctx.reporter),
+                ..Default::default()
+            },
+        )
+        .await?;
 
         Ok(InstallResult {
             install_path: install_dir,
@@ -104,6 +102,14 @@ impl Installer for GhInstaller {
         })
     }
 
+    fn binaries(&self) -> &'static [&'static str] {
+        &["bin\\gh.exe", "gh.exe"]
+    }
+
+    fn arm64_native(&self) -> bool {
+        true
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         // gh zip 解压后 gh.exe 可能在 bin/ 下或根目录，动态判断
         let bin_dir = install_path.join("bin");
@@ -118,23 +124,23 @@ impl Installer for GhInstaller {
     async fn configure(&self, ctx: &InstallContext<'_>) -> Result<()> {
         let gh = find_gh(ctx.config);
 
-        ui::print_title("配置 GitHub CLI");
+        ctx.reporter.title("配置 GitHub CLI");
 
         // 检查是否已登录
         if let Ok(out) = std::process::Command::new(&gh).arg("auth").arg("status").output() {
             if out.status.success() {
-                let info = String::from_utf8_lossy(&out.stderr);
+                let info = crate::proc::decode_output(&out.stderr);
                 for line in info.lines() {
-                    ui::print_info(line.trim());
+                    ctx.reporter.info(line.trim());
                 }
-                ui::print_success("GitHub CLI 已登录");
+                ctx.reporter.success("GitHub CLI 已登录");
                 return Ok(());
             }
         }
 
         // 未登录，提示并运行 gh auth login
-        ui::print_info("尚未登录 GitHub，即将打开浏览器进行授权...");
-        ui::print_info("如需跳过，按 Ctrl+C 取消");
+        ctx.reporter.info("尚未登录 GitHub，即将打开浏览器进行授权...");
+        ctx.reporter.info("如需跳过，按 Ctrl+C 取消");
         println!();
 
         let status = std::process::Command::new(&gh)
@@ -143,9 +149,9 @@ impl Installer for GhInstaller {
             .context("启动 gh auth login 失败")?;
 
         if status.success() {
-            ui::print_success("GitHub CLI 登录成功");
+            ctx.reporter.success("GitHub CLI 登录成功");
         } else {
-            ui::print_warning("登录未完成，可稍后手动运行: gh auth login");
+            ctx.reporter.warning("登录未完成，可稍后手动运行: gh auth login");
         }
 
         Ok(())
@@ -176,3 +182,31 @@ fn find_gh(config: &HudoConfig) -> PathBuf {
     }
     PathBuf::from("gh")
 }
+
+/// GitHub Releases 下载地址：gh_{version}_windows_{amd64|arm64}.zip
+fn build_url(version: &str, arch: Arch) -> (String, String) {
+    let goarch = match arch {
+        Arch::X64 => "amd64",
+        Arch::Arm64 => "arm64",
+    };
+    let filename = format!("gh_{}_windows_{}.zip", version, goarch);
+    let url = format!(
+        "https://github.com/cli/cli/releases/download/v{}/{}",
+        version, filename
+    );
+    (url, filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_url_per_arch() {
+        assert_eq!(
+            build_url("2.87.3", Arch::X64).0,
+            "https://github.com/cli/cli/releases/download/v2.87.3/gh_2.87.3_windows_amd64.zip"
+        );
+        assert_eq!(build_url("2.87.3", Arch::Arm64).1, "gh_2.87.3_windows_arm64.zip");
+    }
+}
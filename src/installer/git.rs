@@ -4,8 +4,9 @@ use dialoguer::Input;
 use std::path::PathBuf;
 
 use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use crate::arch::Arch;
 use crate::config::HudoConfig;
-use crate::{download, ui};
+use crate::download;
 
 pub struct GitInstaller;
 
@@ -18,6 +19,10 @@ impl Installer for GitInstaller {
             id: "git",
             name: "Git",
             description: "分布式版本控制系统",
+            homepage: Some("https://git-scm.com"),
+            post_install_tips: &[
+                "配置用户信息: git config --global user.name / user.email",
+            ],
         }
     }
 
@@ -26,7 +31,7 @@ impl Installer for GitInstaller {
         let git_exe = ctx.config.tools_dir().join("git").join("cmd").join("git.exe");
         if git_exe.exists() {
             if let Ok(out) = std::process::Command::new(&git_exe).arg("--version").output() {
-                let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                 return Ok(DetectResult::InstalledByHudo(version));
             }
         }
@@ -34,7 +39,7 @@ impl Installer for GitInstaller {
         // 再检查系统 PATH
         if let Ok(out) = std::process::Command::new("git").arg("--version").output() {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -44,13 +49,15 @@ impl Installer for GitInstaller {
 
     fn resolve_download(&self, config: &HudoConfig) -> (String, String) {
         let version = config.versions.git.as_deref().unwrap_or(GIT_VERSION_DEFAULT);
-        let tag = git_version_to_tag(version);
-        let filename = format!("Git-{}-64-bit.exe", version);
-        let url = format!(
-            "https://github.com/git-for-windows/git/releases/download/{}/{}",
-            tag, filename
-        );
-        (url, filename)
+        build_url(config, version)
+    }
+
+    fn default_version(&self) -> Option<&'static str> {
+        Some(GIT_VERSION_DEFAULT)
+    }
+
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("可用版本见 https://github.com/git-for-windows/git/releases")
     }
 
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
@@ -61,36 +68,51 @@ impl Installer for GitInstaller {
         let version = match &config.versions.git {
             Some(v) => v.clone(),
             None => {
-                crate::ui::print_action("查询 Git 最新版本...");
-                crate::version::git_latest()
-                    .await
-                    .unwrap_or_else(|| GIT_VERSION_DEFAULT.to_string())
+                ctx.reporter.action("查询 Git 最新版本...");
+                crate::version::or_default(ctx.reporter, crate::version::git_latest().await, GIT_VERSION_DEFAULT)
             }
         };
 
-        let tag = git_version_to_tag(&version);
-        let filename = format!("Git-{}-64-bit.exe", version);
-        let url = format!(
-            "https://github.com/git-for-windows/git/releases/download/{}/{}",
-            tag, filename
-        );
+        let (url, filename) = build_url(config, &version);
 
         // 下载安装包
         let exe_path = download::download(&url, &config.cache_dir(), &filename).await?;
 
-        // 静默安装到指定目录
-        crate::ui::print_action("安装 Git（静默模式）...");
-        download::run_installer(
-            &exe_path,
-            &[
-                "/VERYSILENT",
-                "/NORESTART",
-                &format!("/DIR={}", install_dir.display()),
-                "/NOICONS",
-                "/COMPONENTS=ext,ext\\shellhere,ext\\guihere,gitlfs,assoc,assoc_sh,scalar",
-            ],
-        )
-        .context("Git 安装失败")?;
+        if is_portable(config) {
+            // PortableGit 是 7z 自解压包，直接解压到安装目录，不写注册表
+            ctx.reporter.action("解压 PortableGit...");
+            let tmp_dir = config.cache_dir().join("git-extract");
+            if tmp_dir.exists() {
+                std::fs::remove_dir_all(&tmp_dir).ok();
+            }
+            download::run_installer(
+                &exe_path,
+                &[&format!("-o{}", tmp_dir.display()), "-y"],
+                config.install.silent_watch(config.yes),
+            )
+            .context("PortableGit 解压失败")?;
+
+            if !tmp_dir.join("cmd").join("git.exe").exists() {
+                std::fs::remove_dir_all(&tmp_dir).ok();
+                anyhow::bail!("解压后未找到 cmd\\git.exe，安装可能失败");
+            }
+            download::swap_dir(&tmp_dir, &install_dir)?;
+        } else {
+            // 静默安装到指定目录
+            ctx.reporter.action("安装 Git（静默模式）...");
+            download::run_installer(
+                &exe_path,
+                &[
+                    "/VERYSILENT",
+                    "/NORESTART",
+                    &format!("/DIR={}", install_dir.display()),
+                    "/NOICONS",
+                    "/COMPONENTS=ext,ext\\shellhere,ext\\guihere,gitlfs,assoc,assoc_sh,scalar",
+                ],
+                config.install.silent_watch(config.yes),
+            )
+            .context("Git 安装失败")?;
+        }
 
         Ok(InstallResult {
             install_path: install_dir,
@@ -98,6 +120,18 @@ impl Installer for GitInstaller {
         })
     }
 
+    fn install_mode(&self, config: &HudoConfig) -> Option<String> {
+        Some(config.git.install_mode.clone())
+    }
+
+    fn arm64_native(&self) -> bool {
+        true
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        &["cmd\\git.exe"]
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![EnvAction::AppendPath {
             path: install_path.join("cmd").to_string_lossy().to_string(),
@@ -105,7 +139,7 @@ impl Installer for GitInstaller {
     }
 
     async fn configure(&self, ctx: &InstallContext<'_>) -> Result<()> {
-        ui::print_title("配置 Git");
+        ctx.reporter.title("配置 Git");
 
         let git = find_git(ctx.config);
 
@@ -113,8 +147,8 @@ impl Installer for GitInstaller {
         let current_name = git_config_get(&git, "user.name");
         let current_email = git_config_get(&git, "user.email");
 
-        ui::print_info("Git 需要你的身份信息，用于标记 commit 的作者");
-        ui::print_info("这不是登录账号，只是显示在代码历史中的名字和邮箱");
+        ctx.reporter.info("Git 需要你的身份信息，用于标记 commit 的作者");
+        ctx.reporter.info("这不是登录账号，只是显示在代码历史中的名字和邮箱");
         println!();
 
         // user.name
@@ -147,7 +181,7 @@ impl Installer for GitInstaller {
         git_config_set(&git, "user.name", &name)?;
         git_config_set(&git, "user.email", &email)?;
 
-        ui::print_success("Git 配置成功");
+        ctx.reporter.success("Git 配置成功");
 
         Ok(())
     }
@@ -194,7 +228,7 @@ fn git_config_get(git: &str, key: &str) -> Option<String> {
         .output()
         .ok()
         .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .map(|o| crate::proc::decode_output(&o.stdout).trim().to_string())
         .filter(|s| !s.is_empty())
 }
 
@@ -210,6 +244,36 @@ fn git_config_set(git: &str, key: &str, value: &str) -> Result<()> {
     Ok(())
 }
 
+/// 是否使用 PortableGit 绿色版安装
+fn is_portable(config: &HudoConfig) -> bool {
+    config.git.install_mode == "portable"
+}
+
+/// 根据安装方式返回 (下载 URL, 文件名)
+/// installer → Git-{ver}-64-bit.exe，portable → PortableGit-{ver}-64-bit.7z.exe
+fn build_url(config: &HudoConfig, version: &str) -> (String, String) {
+    release_url(version, is_portable(config), config.arch())
+}
+
+/// Git for Windows 发布文件：Git-{version}-{64-bit|arm64}.exe，PortableGit 为 .7z.exe
+fn release_url(version: &str, portable: bool, arch: Arch) -> (String, String) {
+    let tag = git_version_to_tag(version);
+    let suffix = match arch {
+        Arch::X64 => "64-bit",
+        Arch::Arm64 => "arm64",
+    };
+    let filename = if portable {
+        format!("PortableGit-{}-{}.7z.exe", version, suffix)
+    } else {
+        format!("Git-{}-{}.exe", version, suffix)
+    };
+    let url = format!(
+        "https://github.com/git-for-windows/git/releases/download/{}/{}",
+        tag, filename
+    );
+    (url, filename)
+}
+
 /// 从版本号推导 Git for Windows 的 release tag
 /// "2.47.1.2" → "v2.47.1.windows.2"
 /// "2.48.0"   → "v2.48.0.windows.1"
@@ -221,3 +285,24 @@ fn git_version_to_tag(version: &str) -> String {
         format!("v{}.windows.1", version)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_url_per_arch() {
+        assert_eq!(
+            release_url("2.47.1.2", false, Arch::X64),
+            (
+                "https://github.com/git-for-windows/git/releases/download/v2.47.1.windows.2/Git-2.47.1.2-64-bit.exe".to_string(),
+                "Git-2.47.1.2-64-bit.exe".to_string()
+            )
+        );
+        assert_eq!(
+            release_url("2.48.0", false, Arch::Arm64).0,
+            "https://github.com/git-for-windows/git/releases/download/v2.48.0.windows.1/Git-2.48.0-arm64.exe"
+        );
+        assert_eq!(release_url("2.48.0", true, Arch::Arm64).1, "PortableGit-2.48.0-arm64.7z.exe");
+    }
+}
@@ -3,6 +3,7 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 
 use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use crate::arch::Arch;
 use crate::config::HudoConfig;
 use crate::download;
 
@@ -17,6 +18,8 @@ impl Installer for GoInstaller {
             id: "go",
             name: "Go",
             description: "Go 编程语言",
+            homepage: Some("https://go.dev"),
+            post_install_tips: &[],
         }
     }
 
@@ -26,7 +29,7 @@ impl Installer for GoInstaller {
         if go_exe.exists() {
             if let Ok(out) = std::process::Command::new(&go_exe).arg("version").output() {
                 if out.status.success() {
-                    let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                    let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                     return Ok(DetectResult::InstalledByHudo(version));
                 }
             }
@@ -35,7 +38,7 @@ impl Installer for GoInstaller {
         // 检查系统 PATH
         if let Ok(out) = std::process::Command::new("go").arg("version").output() {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -48,10 +51,16 @@ impl Installer for GoInstaller {
             "" | "latest" => GO_VERSION_DEFAULT,
             v => v,
         };
-        let filename = format!("go{}.windows-amd64.zip", version);
-        let base = config.mirrors.go.as_deref().unwrap_or("https://go.dev/dl");
-        let url = format!("{}/{}", base.trim_end_matches('/'), filename);
-        (url, filename)
+        let mirror = config.mirrors.go.as_deref().map(|m| config.expand_mirror(m, version, "go"));
+        build_url(mirror.as_deref(), version, config.arch())
+    }
+
+    fn default_version(&self) -> Option<&'static str> {
+        Some(GO_VERSION_DEFAULT)
+    }
+
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("可用版本见 https://go.dev/dl/")
     }
 
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
@@ -61,27 +70,27 @@ impl Installer for GoInstaller {
         // 解析版本: config > API > hardcoded
         let version = match config.go.version.as_str() {
             "" | "latest" => {
-                crate::ui::print_action("查询 Go 最新版本...");
-                crate::version::go_latest()
-                    .await
-                    .unwrap_or_else(|| GO_VERSION_DEFAULT.to_string())
+                ctx.reporter.action("查询 Go 最新版本...");
+                crate::version::or_default(ctx.reporter, crate::version::go_latest().await, GO_VERSION_DEFAULT)
             }
             v => v.to_string(),
         };
 
-        let filename = format!("go{}.windows-amd64.zip", version);
-        let base = config.mirrors.go.as_deref().unwrap_or("https://go.dev/dl");
-        let url = format!("{}/{}", base.trim_end_matches('/'), filename);
+        let mirror = config.mirrors.go.as_deref().map(|m| config.expand_mirror(m, &version, "go"));
+        let (url, filename) = build_url(mirror.as_deref(), &version, ctx.arch());
 
         // 下载 zip
         let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
 
-        // 解压到 lang/ 目录（zip 内有 go/ 顶层目录，解压后即为 lang/go/）
-        crate::ui::print_action("解压 Go...");
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir).ok();
+        // 解压到临时目录（zip 内有 go/ 顶层目录），再替换 lang/go/
+        ctx.reporter.action("解压 Go...");
+        let tmp_dir = config.cache_dir().join("go-extract");
+        if tmp_dir.exists() {
+            std::fs::remove_dir_all(&tmp_dir).ok();
         }
-        download::extract_zip(&zip_path, &config.lang_dir())?;
+        download::extract_zip(&zip_path, &tmp_dir)?;
+        download::swap_dir(&tmp_dir.join("go"), &install_dir)?;
+        std::fs::remove_dir_all(&tmp_dir).ok();
 
         // 创建 GOPATH 目录
         let gopath = config.lang_dir().join("gopath");
@@ -95,6 +104,14 @@ impl Installer for GoInstaller {
         })
     }
 
+    fn binaries(&self) -> &'static [&'static str] {
+        &["bin\\go.exe"]
+    }
+
+    fn arm64_native(&self) -> bool {
+        true
+    }
+
     fn env_actions(&self, install_path: &PathBuf, config: &HudoConfig) -> Vec<EnvAction> {
         let gopath = config.lang_dir().join("gopath");
         vec![
@@ -123,5 +140,37 @@ fn get_go_version(install_dir: &PathBuf) -> Option<String> {
         .output()
         .ok()
         .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .map(|o| crate::proc::decode_output(&o.stdout).trim().to_string())
+}
+
+/// go.dev/dl 下载地址：go{version}.windows-{amd64|arm64}.zip
+fn build_url(mirror: Option<&str>, version: &str, arch: Arch) -> (String, String) {
+    let goarch = match arch {
+        Arch::X64 => "amd64",
+        Arch::Arm64 => "arm64",
+    };
+    let filename = format!("go{}.windows-{}.zip", version, goarch);
+    let base = mirror.unwrap_or("https://go.dev/dl");
+    let url = format!("{}/{}", base, filename);
+    (url, filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_url_per_arch() {
+        assert_eq!(
+            build_url(None, "1.24.0", Arch::X64),
+            (
+                "https://go.dev/dl/go1.24.0.windows-amd64.zip".to_string(),
+                "go1.24.0.windows-amd64.zip".to_string()
+            )
+        );
+        assert_eq!(
+            build_url(Some("https://mirrors.example.com/go"), "1.24.0", Arch::Arm64).0,
+            "https://mirrors.example.com/go/go1.24.0.windows-arm64.zip"
+        );
+    }
 }
@@ -17,6 +17,8 @@ impl Installer for GradleInstaller {
             id: "gradle",
             name: "Gradle",
             description: "Gradle 构建工具 (Java/Android)",
+            homepage: Some("https://gradle.org"),
+            post_install_tips: &[],
         }
     }
 
@@ -24,12 +26,11 @@ impl Installer for GradleInstaller {
         // 检查 hudo 安装目录（gradle.bat 需通过 cmd /c 执行）
         let gradle_bat = ctx.config.tools_dir().join("gradle").join("bin").join("gradle.bat");
         if gradle_bat.exists() {
-            if let Ok(out) = std::process::Command::new("cmd")
-                .args(["/c", &gradle_bat.to_string_lossy(), "--version"])
+            if let Ok(out) = crate::proc::cmd(&gradle_bat.to_string_lossy(), &["--version"])
                 .output()
             {
                 if out.status.success() {
-                    let version = String::from_utf8_lossy(&out.stdout)
+                    let version = crate::proc::decode_output(&out.stdout)
                         .lines()
                         .find(|l| l.starts_with("Gradle "))
                         .unwrap_or("已安装")
@@ -40,12 +41,11 @@ impl Installer for GradleInstaller {
         }
 
         // 检查系统 PATH（gradle 是 .bat，通过 cmd /c 调用）
-        if let Ok(out) = std::process::Command::new("cmd")
-            .args(["/c", "gradle", "--version"])
+        if let Ok(out) = crate::proc::cmd("gradle", &["--version"])
             .output()
         {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout)
+                let version = crate::proc::decode_output(&out.stdout)
                     .lines()
                     .find(|l| l.starts_with("Gradle "))
                     .unwrap_or("已安装")
@@ -62,40 +62,43 @@ impl Installer for GradleInstaller {
         build_url(config, version)
     }
 
+    fn default_version(&self) -> Option<&'static str> {
+        Some(GRADLE_VERSION_DEFAULT)
+    }
+
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("可用版本见 https://gradle.org/releases/")
+    }
+
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
         let config = ctx.config;
         let install_dir = config.tools_dir().join("gradle");
 
         // 检测 JDK 是否可用
-        super::jdk::ensure_jdk(ctx, "Gradle").await?;
+        super::jdk::ensure_jdk(ctx, &self.info()).await?;
 
         let version = match &config.versions.gradle {
             Some(v) => v.clone(),
             None => {
-                crate::ui::print_action("查询 Gradle 最新版本...");
-                crate::version::gradle_latest()
-                    .await
-                    .unwrap_or_else(|| GRADLE_VERSION_DEFAULT.to_string())
+                ctx.reporter.action("查询 Gradle 最新版本...");
+                crate::version::or_default(ctx.reporter, crate::version::gradle_latest().await, GRADLE_VERSION_DEFAULT)
             }
         };
 
         let (url, filename) = build_url(config, &version);
-        let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
-
-        crate::ui::print_action("解压 Gradle...");
-        let tmp_dir = config.cache_dir().join("gradle-extract");
-        if tmp_dir.exists() {
-            std::fs::remove_dir_all(&tmp_dir).ok();
-        }
-        download::extract_zip(&zip_path, &tmp_dir)?;
-
         // zip 内有 gradle-{version}/ 子目录
-        let inner = download::find_single_subdir(&tmp_dir).unwrap_or(tmp_dir.clone());
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir).ok();
-        }
-        std::fs::rename(&inner, &install_dir).ok();
-        std::fs::remove_dir_all(&tmp_dir).ok();
+        download::install_zip(
+            &url,
+            &filename,
+            &config.cache_dir(),
+            &install_dir,
+            &download::ZipInstall {
+                name: "Gradle",
+                reporter: Some(ctx.reporter),
+                ..Default::default()
+            },
+        )
+        .await?;
 
         Ok(InstallResult {
             install_path: install_dir,
@@ -103,6 +106,19 @@ impl Installer for GradleInstaller {
         })
     }
 
+    fn arm64_native(&self) -> bool {
+        // Gradle 运行于 JVM，与架构无关
+        true
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["jdk"]
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        &["bin\\gradle.bat"]
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![
             EnvAction::Set {
@@ -117,14 +133,14 @@ impl Installer for GradleInstaller {
 }
 
 fn build_url(config: &HudoConfig, version: &str) -> (String, String) {
-    let base = config
-        .mirrors
-        .gradle
-        .as_deref()
-        .unwrap_or("https://services.gradle.org/distributions");
+    let base = config.expand_mirror(
+        config.mirrors.gradle.as_deref().unwrap_or("https://services.gradle.org/distributions"),
+        version,
+        "gradle",
+    );
     let url = format!(
         "{}/gradle-{}-bin.zip",
-        base.trim_end_matches('/'),
+        base,
         version
     );
     let filename = format!("gradle-{}-bin.zip", version);
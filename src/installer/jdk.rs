@@ -1,9 +1,10 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use dialoguer::Confirm;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use crate::arch::Arch;
 use crate::config::HudoConfig;
 use crate::download;
 
@@ -11,6 +12,9 @@ pub struct JdkInstaller;
 
 const JDK_MAJOR_DEFAULT: &str = "21";
 
+/// Temurin 提供 Windows ARM64 构建的最低大版本
+const JDK_ARM64_MIN_MAJOR: u32 = 21;
+
 #[async_trait]
 impl Installer for JdkInstaller {
     fn info(&self) -> ToolInfo {
@@ -18,6 +22,8 @@ impl Installer for JdkInstaller {
             id: "jdk",
             name: "Java JDK",
             description: "Adoptium Temurin JDK",
+            homepage: Some("https://adoptium.net"),
+            post_install_tips: &[],
         }
     }
 
@@ -27,7 +33,7 @@ impl Installer for JdkInstaller {
         if java_exe.exists() {
             if let Ok(out) = std::process::Command::new(&java_exe).arg("-version").output() {
                 // java -version 输出到 stderr
-                let version = String::from_utf8_lossy(&out.stderr)
+                let version = crate::proc::decode_output(&out.stderr)
                     .lines()
                     .next()
                     .unwrap_or("unknown")
@@ -39,7 +45,7 @@ impl Installer for JdkInstaller {
         // 检查系统 PATH
         if let Ok(out) = std::process::Command::new("java").arg("-version").output() {
             if out.status.success() || !out.stderr.is_empty() {
-                let version = String::from_utf8_lossy(&out.stderr)
+                let version = crate::proc::decode_output(&out.stderr)
                     .lines()
                     .next()
                     .unwrap_or("unknown")
@@ -56,32 +62,26 @@ impl Installer for JdkInstaller {
             "" => JDK_MAJOR_DEFAULT,
             v => v,
         };
-        let base = config.mirrors.java.as_deref()
-            .unwrap_or("https://api.adoptium.net/v3/binary/latest");
-        let url = format!(
-            "{}/{}/ga/windows/x64/jdk/hotspot/normal/eclipse",
-            base.trim_end_matches('/'),
-            major
-        );
-        let filename = format!("adoptium-jdk{}-latest.zip", major);
-        (url, filename)
+        let mirror = config.mirrors.java.as_deref().map(|m| config.expand_mirror(m, major, "jdk"));
+        build_url(mirror.as_deref(), major, config.arch())
     }
 
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
         let config = ctx.config;
         let install_dir = config.lang_dir().join("java");
         let (url, filename) = self.resolve_download(config);
-
-        // 总是下载最新版（API 返回的是 latest）
-        let cached = config.cache_dir().join(&filename);
-        if cached.exists() {
-            std::fs::remove_file(&cached).ok();
+        if ctx.arch() == Arch::Arm64 && adoptium_arch(&config.java.version, Arch::Arm64) == "x64" {
+            ctx.reporter.warning(&format!(
+                "Temurin JDK {} 暂无 Windows ARM64 版本，将安装 x64 版本（JDK {} 起提供 ARM64）",
+                config.java.version, JDK_ARM64_MIN_MAJOR
+            ));
         }
 
-        let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
+        // API 返回的是 latest，有缓存时重新验证
+        let zip_path = download::download_latest(&url, &config.cache_dir(), &filename).await?;
 
         // 解压到临时目录
-        crate::ui::print_action("解压 JDK...");
+        ctx.reporter.action("解压 JDK...");
         let tmp_dir = config.cache_dir().join("jdk-extract");
         if tmp_dir.exists() {
             std::fs::remove_dir_all(&tmp_dir).ok();
@@ -90,10 +90,7 @@ impl Installer for JdkInstaller {
 
         // zip 内有 jdk-21.0.6+7/ 子目录，移到 lang/java/
         let inner = download::find_single_subdir(&tmp_dir).unwrap_or(tmp_dir.clone());
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir).ok();
-        }
-        std::fs::rename(&inner, &install_dir).ok();
+        download::swap_dir(&inner, &install_dir)?;
         std::fs::remove_dir_all(&tmp_dir).ok();
 
         let major = match config.java.version.as_str() {
@@ -108,6 +105,18 @@ impl Installer for JdkInstaller {
         })
     }
 
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("java.version 是 JDK 主版本号（如 21），可用版本见 https://adoptium.net/temurin/releases/")
+    }
+
+    fn arm64_native(&self) -> bool {
+        true
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        &["bin\\java.exe"]
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![
             EnvAction::Set {
@@ -129,37 +138,131 @@ fn get_java_version(install_dir: &PathBuf) -> Option<String> {
         .output()
         .ok()
         .and_then(|o| {
-            String::from_utf8_lossy(&o.stderr)
+            crate::proc::decode_output(&o.stderr)
                 .lines()
                 .next()
                 .map(|s| s.to_string())
         })
 }
 
-/// 检测 Java 是否可用（hudo 路径优先，然后系统 PATH）
-pub fn detect_java(config: &HudoConfig) -> bool {
-    let java_hudo = config.lang_dir().join("java").join("bin").join("java.exe");
-    if java_hudo.exists() {
-        return true;
+/// 当前可用的 Java
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JavaStatus {
+    /// 完整的 JDK（有 javac）
+    Jdk,
+    /// 只有 JRE：java 可以运行，但没有 javac，Maven / Gradle 无法编译
+    JreOnly,
+    Missing,
+}
+
+/// hudo 安装的 JDK 优先；否则用 runs 检查系统 PATH 中的 java 与 javac 能否运行
+fn classify_java(hudo_jdk: bool, runs: impl Fn(&str) -> bool) -> JavaStatus {
+    if hudo_jdk {
+        return JavaStatus::Jdk;
+    }
+    match (runs("java"), runs("javac")) {
+        (_, true) => JavaStatus::Jdk,
+        (true, false) => JavaStatus::JreOnly,
+        (false, false) => JavaStatus::Missing,
     }
-    std::process::Command::new("java")
-        .arg("-version")
-        .output()
-        .map(|o| o.status.success() || !o.stderr.is_empty())
-        .unwrap_or(false)
+}
+
+/// 检测 Java 是否可用（hudo 路径优先，然后系统 PATH），区分 JDK 与只有 JRE
+pub fn detect_java(config: &HudoConfig) -> JavaStatus {
+    let bin = config.lang_dir().join("java").join("bin");
+    let hudo_jdk = bin.join("java.exe").exists() && bin.join("javac.exe").exists();
+    classify_java(hudo_jdk, |cmd| {
+        // 旧版本把 -version 的输出写到 stderr
+        std::process::Command::new(cmd)
+            .arg("-version")
+            .output()
+            .map(|o| o.status.success() || !o.stderr.is_empty())
+            .unwrap_or(false)
+    })
+}
+
+/// JAVA_HOME 是否指向可用的 Java（目录下有 bin\java.exe）
+pub fn java_home_ok(home: &Path) -> bool {
+    home.join("bin").join("java.exe").is_file()
+}
+
+/// JAVA_HOME 的当前值：用户环境变量优先，其次当前进程（可能来自系统环境变量）
+fn java_home_value() -> Option<String> {
+    crate::env::EnvManager::get_var("JAVA_HOME")
+        .ok()
+        .flatten()
+        .or_else(|| std::env::var("JAVA_HOME").ok())
+        .map(|v| crate::template::expand_env(v.trim().trim_matches('"')))
+        .filter(|v| !v.is_empty())
+}
+
+/// 已设置但失效的 JAVA_HOME（如指向已删除的 JDK）；未设置或有效时为 None
+pub fn stale_java_home() -> Option<String> {
+    java_home_value().filter(|v| !java_home_ok(Path::new(v)))
+}
+
+/// hudo 安装的 JDK 目录，未安装时为 None
+pub fn hudo_jdk_home(config: &HudoConfig) -> Option<PathBuf> {
+    let dir = config.lang_dir().join("java");
+    java_home_ok(&dir).then_some(dir)
+}
+
+/// 把 JAVA_HOME 改为 hudo 的 JDK，取值与安装 JDK 时相同，并同步到当前进程
+pub fn repair_java_home(config: &HudoConfig, jdk_home: &Path) -> Result<()> {
+    let value = JdkInstaller
+        .env_actions(&jdk_home.to_path_buf(), config)
+        .into_iter()
+        .find_map(|action| match action {
+            EnvAction::Set { name, value } if name == "JAVA_HOME" => Some(value),
+            _ => None,
+        })
+        .context("JDK 安装器没有设置 JAVA_HOME")?;
+    crate::env::EnvManager::set_var("JAVA_HOME", &value)?;
+    crate::env::EnvManager::broadcast_change();
+    std::env::set_var("JAVA_HOME", &value);
+    Ok(())
 }
 
 /// 确保 JDK 可用；若不可用则提示用户选择安装或取消
-/// `tool_name` 用于提示信息，如 "Maven"、"Gradle"
-pub async fn ensure_jdk(ctx: &InstallContext<'_>, tool_name: &str) -> Result<()> {
-    if detect_java(ctx.config) {
-        return Ok(());
+/// `dependent` 为需要 JDK 的工具（如 Maven、Gradle），用于提示信息和依赖记录
+pub async fn ensure_jdk(ctx: &InstallContext<'_>, dependent: &ToolInfo) -> Result<()> {
+    // mvn / gradle 启动脚本优先使用 JAVA_HOME，失效时即使 PATH 中有 JDK 也无法运行
+    let mut stale_home = stale_java_home();
+    if let Some(home) = &stale_home {
+        ctx.reporter.warning(&format!(
+            "JAVA_HOME 指向的目录中没有 bin\\java.exe: {}，{} 会因此无法运行",
+            home, dependent.name
+        ));
+        if let Some(jdk) = hudo_jdk_home(ctx.config) {
+            let repair = ctx.config.yes
+                || Confirm::new()
+                    .with_prompt(format!("  是否把 JAVA_HOME 改为 hudo 安装的 JDK（{}）？", jdk.display()))
+                    .default(true)
+                    .interact()
+                    .unwrap_or(false);
+            if repair {
+                repair_java_home(ctx.config, &jdk)?;
+                ctx.reporter.success(&format!("JAVA_HOME 已改为 {}", jdk.display()));
+                stale_home = None;
+            }
+        }
     }
 
-    crate::ui::print_warning(&format!(
-        "未检测到 Java，{} 需要 JDK 才能运行",
-        tool_name
-    ));
+    match detect_java(ctx.config) {
+        JavaStatus::Jdk if stale_home.is_none() => {
+            return super::record_dependent(ctx.config, "jdk", dependent.id)
+        }
+        // 安装 hudo 的 JDK 会同时重设 JAVA_HOME
+        JavaStatus::Jdk => {}
+        JavaStatus::JreOnly => ctx.reporter.warning(&format!(
+            "系统中的 Java 只有 JRE（找不到 javac），{} 需要完整的 JDK",
+            dependent.name
+        )),
+        JavaStatus::Missing => ctx.reporter.warning(&format!(
+            "未检测到 Java，{} 需要 JDK 才能运行",
+            dependent.name
+        )),
+    }
 
     let install_now = Confirm::new()
         .with_prompt("  是否现在安装 Java JDK？")
@@ -171,33 +274,10 @@ pub async fn ensure_jdk(ctx: &InstallContext<'_>, tool_name: &str) -> Result<()>
         anyhow::bail!("请先安装 JDK：hudo install jdk");
     }
 
-    crate::ui::print_title("安装 Java JDK");
-    let result = JdkInstaller.install(ctx).await?;
-    crate::ui::print_success(&format!(
-        "Java {} 安装完成",
-        console::style(&result.version).green()
-    ));
-
-    // 持久化环境变量
-    let install_path = &result.install_path;
-    let actions = JdkInstaller.env_actions(install_path, ctx.config);
-    for action in &actions {
-        match action {
-            super::EnvAction::AppendPath { path } => {
-                crate::ui::print_info(&format!("PATH += {}", path));
-                crate::env::EnvManager::append_to_path(path)?;
-            }
-            super::EnvAction::Set { name, value } => {
-                crate::ui::print_info(&format!("{} = {}", name, value));
-                crate::env::EnvManager::set_var(name, value)?;
-            }
-        }
-    }
-    if !actions.is_empty() {
-        crate::env::EnvManager::broadcast_change();
-    }
+    let result = super::install_as_dependency(ctx, &JdkInstaller, dependent.id).await?;
 
     // 将 java/bin 和 JAVA_HOME 注入当前进程，让后续工具能立即找到 java
+    let install_path = &result.install_path;
     let java_bin = install_path.join("bin");
     if let Ok(old_path) = std::env::var("PATH") {
         std::env::set_var("PATH", format!("{};{}", java_bin.display(), old_path));
@@ -205,7 +285,84 @@ pub async fn ensure_jdk(ctx: &InstallContext<'_>, tool_name: &str) -> Result<()>
     std::env::set_var("JAVA_HOME", install_path.to_string_lossy().as_ref());
 
     // 恢复原工具安装标题
-    crate::ui::print_title(&format!("安装 {}", tool_name));
+    ctx.reporter.title(&format!("安装 {}", dependent.name));
 
     Ok(())
 }
+
+/// Adoptium 架构参数：ARM64 仅 JDK 21 起提供，更早的版本退回 x64
+fn adoptium_arch(major: &str, arch: Arch) -> &'static str {
+    let major = match major {
+        "" => JDK_MAJOR_DEFAULT,
+        v => v,
+    };
+    match arch {
+        Arch::Arm64 if major.parse::<u32>().is_ok_and(|m| m >= JDK_ARM64_MIN_MAJOR) => "aarch64",
+        _ => "x64",
+    }
+}
+
+/// Adoptium API 下载地址（x64 沿用原缓存文件名）
+fn build_url(mirror: Option<&str>, major: &str, arch: Arch) -> (String, String) {
+    let base = mirror.unwrap_or("https://api.adoptium.net/v3/binary/latest");
+    let adoptium_arch = adoptium_arch(major, arch);
+    let url = format!(
+        "{}/{}/ga/windows/{}/jdk/hotspot/normal/eclipse",
+        base,
+        major,
+        adoptium_arch
+    );
+    let filename = match adoptium_arch {
+        "x64" => format!("adoptium-jdk{}-latest.zip", major),
+        other => format!("adoptium-jdk{}-{}-latest.zip", major, other),
+    };
+    (url, filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_url_per_arch() {
+        assert_eq!(
+            build_url(None, "21", Arch::X64),
+            (
+                "https://api.adoptium.net/v3/binary/latest/21/ga/windows/x64/jdk/hotspot/normal/eclipse".to_string(),
+                "adoptium-jdk21-latest.zip".to_string()
+            )
+        );
+        assert_eq!(
+            build_url(None, "21", Arch::Arm64),
+            (
+                "https://api.adoptium.net/v3/binary/latest/21/ga/windows/aarch64/jdk/hotspot/normal/eclipse".to_string(),
+                "adoptium-jdk21-aarch64-latest.zip".to_string()
+            )
+        );
+        // JDK 17 没有 Windows ARM64 构建
+        assert_eq!(build_url(None, "17", Arch::Arm64).1, "adoptium-jdk17-latest.zip");
+    }
+
+    #[test]
+    fn test_classify_java() {
+        let only = |found: &'static [&'static str]| move |cmd: &str| found.contains(&cmd);
+        assert_eq!(classify_java(false, only(&["java", "javac"])), JavaStatus::Jdk);
+        // 只有 JRE：不能跳过安装
+        assert_eq!(classify_java(false, only(&["java"])), JavaStatus::JreOnly);
+        assert_eq!(classify_java(false, only(&[])), JavaStatus::Missing);
+        // hudo 的 JDK 已安装时不检查系统 PATH
+        assert_eq!(classify_java(true, |_| panic!("不应检查 PATH")), JavaStatus::Jdk);
+    }
+
+    #[test]
+    fn test_java_home_ok() {
+        let dir = std::env::temp_dir().join(format!("hudo-test-java-home-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        // 只有目录、没有 java.exe（JDK 被删除后残留的空目录）
+        assert!(!java_home_ok(&dir));
+        std::fs::write(dir.join("bin").join("java.exe"), b"MZ").unwrap();
+        assert!(java_home_ok(&dir));
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(!java_home_ok(&dir));
+    }
+}
@@ -17,6 +17,8 @@ impl Installer for MavenInstaller {
             id: "maven",
             name: "Maven",
             description: "Apache Maven 构建工具 (Java)",
+            homepage: Some("https://maven.apache.org"),
+            post_install_tips: &[],
         }
     }
 
@@ -24,12 +26,11 @@ impl Installer for MavenInstaller {
         // 检查 hudo 安装目录（mvn.cmd 需通过 cmd /c 执行）
         let mvn_cmd = ctx.config.tools_dir().join("maven").join("bin").join("mvn.cmd");
         if mvn_cmd.exists() {
-            if let Ok(out) = std::process::Command::new("cmd")
-                .args(["/c", &mvn_cmd.to_string_lossy(), "--version"])
+            if let Ok(out) = crate::proc::cmd(&mvn_cmd.to_string_lossy(), &["--version"])
                 .output()
             {
                 if out.status.success() {
-                    let version = String::from_utf8_lossy(&out.stdout)
+                    let version = crate::proc::decode_output(&out.stdout)
                         .lines()
                         .next()
                         .unwrap_or("已安装")
@@ -40,12 +41,11 @@ impl Installer for MavenInstaller {
         }
 
         // 检查系统 PATH（mvn 是 .cmd，通过 cmd /c 调用）
-        if let Ok(out) = std::process::Command::new("cmd")
-            .args(["/c", "mvn", "--version"])
+        if let Ok(out) = crate::proc::cmd("mvn", &["--version"])
             .output()
         {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout)
+                let version = crate::proc::decode_output(&out.stdout)
                     .lines()
                     .next()
                     .unwrap_or("已安装")
@@ -63,40 +63,43 @@ impl Installer for MavenInstaller {
         (url, filename)
     }
 
+    fn default_version(&self) -> Option<&'static str> {
+        Some(MAVEN_VERSION_DEFAULT)
+    }
+
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("downloads.apache.org 只保留最新的几个版本，旧版本可把 Maven 镜像设为 https://archive.apache.org/dist/maven/maven-3")
+    }
+
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
         let config = ctx.config;
         let install_dir = config.tools_dir().join("maven");
 
         // 检测 JDK 是否可用
-        super::jdk::ensure_jdk(ctx, "Maven").await?;
+        super::jdk::ensure_jdk(ctx, &self.info()).await?;
 
         let version = match &config.versions.maven {
             Some(v) => v.clone(),
             None => {
-                crate::ui::print_action("查询 Maven 最新版本...");
-                crate::version::maven_latest()
-                    .await
-                    .unwrap_or_else(|| MAVEN_VERSION_DEFAULT.to_string())
+                ctx.reporter.action("查询 Maven 最新版本...");
+                crate::version::or_default(ctx.reporter, crate::version::maven_latest().await, MAVEN_VERSION_DEFAULT)
             }
         };
 
         let (url, filename) = build_url(config, &version);
-        let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
-
-        crate::ui::print_action("解压 Maven...");
-        let tmp_dir = config.cache_dir().join("maven-extract");
-        if tmp_dir.exists() {
-            std::fs::remove_dir_all(&tmp_dir).ok();
-        }
-        download::extract_zip(&zip_path, &tmp_dir)?;
-
         // zip 内有 apache-maven-{version}/ 子目录
-        let inner = download::find_single_subdir(&tmp_dir).unwrap_or(tmp_dir.clone());
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir).ok();
-        }
-        std::fs::rename(&inner, &install_dir).ok();
-        std::fs::remove_dir_all(&tmp_dir).ok();
+        download::install_zip(
+            &url,
+            &filename,
+            &config.cache_dir(),
+            &install_dir,
+            &download::ZipInstall {
+                name: "Maven",
+                reporter: Some(ctx.reporter),
+                ..Default::default()
+            },
+        )
+        .await?;
 
         Ok(InstallResult {
             install_path: install_dir,
@@ -104,6 +107,19 @@ impl Installer for MavenInstaller {
         })
     }
 
+    fn arm64_native(&self) -> bool {
+        // Maven 运行于 JVM，与架构无关
+        true
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["jdk"]
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        &["bin\\mvn.cmd"]
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![
             EnvAction::Set {
@@ -118,14 +134,14 @@ impl Installer for MavenInstaller {
 }
 
 fn build_url(config: &HudoConfig, version: &str) -> (String, String) {
-    let base = config
-        .mirrors
-        .maven
-        .as_deref()
-        .unwrap_or("https://downloads.apache.org/maven/maven-3");
+    let base = config.expand_mirror(
+        config.mirrors.maven.as_deref().unwrap_or("https://downloads.apache.org/maven/maven-3"),
+        version,
+        "maven",
+    );
     let url = format!(
         "{}/{}/binaries/apache-maven-{}-bin.zip",
-        base.trim_end_matches('/'),
+        base,
         version,
         version
     );
@@ -20,6 +20,8 @@ impl Installer for MingwInstaller {
             id: "c",
             name: "C/C++",
             description: "GCC 编译器 (MinGW-w64)",
+            homepage: Some("https://www.mingw-w64.org"),
+            post_install_tips: &[],
         }
     }
 
@@ -29,7 +31,7 @@ impl Installer for MingwInstaller {
         if gcc_exe.exists() {
             if let Ok(out) = std::process::Command::new(&gcc_exe).arg("--version").output() {
                 if out.status.success() {
-                    let version = String::from_utf8_lossy(&out.stdout)
+                    let version = crate::proc::decode_output(&out.stdout)
                         .lines()
                         .next()
                         .unwrap_or("unknown")
@@ -42,7 +44,7 @@ impl Installer for MingwInstaller {
         // 检查系统 PATH
         if let Ok(out) = std::process::Command::new("gcc").arg("--version").output() {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout)
+                let version = crate::proc::decode_output(&out.stdout)
                     .lines()
                     .next()
                     .unwrap_or("unknown")
@@ -63,39 +65,48 @@ impl Installer for MingwInstaller {
         (url, filename)
     }
 
+    fn default_version(&self) -> Option<&'static str> {
+        Some(MINGW_GCC_VERSION)
+    }
+
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
         let config = ctx.config;
         let install_dir = config.tools_dir().join("mingw64");
 
-        crate::ui::print_action("查询 MinGW-w64 最新版本...");
+        ctx.reporter.action("查询 MinGW-w64 最新版本...");
         let (url, filename, gcc_version) = match crate::version::mingw_latest().await {
-            Some((tag, filename, gcc_version)) => {
+            Ok((tag, filename, gcc_version)) => {
                 let url = format!(
                     "https://github.com/brechtsanders/winlibs_mingw/releases/download/{}/{}",
                     tag, filename
                 );
                 (url, filename, gcc_version)
             }
-            None => {
+            Err(e) => {
+                ctx.reporter.warning(&format!(
+                    "获取最新版本失败（{}），使用内置版本 {}",
+                    e, MINGW_GCC_VERSION
+                ));
                 let (url, filename) = self.resolve_download(config);
                 (url, filename, MINGW_GCC_VERSION.to_string())
             }
         };
 
-        let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
-
-        // 解压（zip 内有 mingw64/ 顶层目录）
-        crate::ui::print_action("解压 MinGW-w64...");
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir).ok();
-        }
-        download::extract_zip(&zip_path, &config.tools_dir())?;
-
-        // 验证
-        let gcc = install_dir.join("bin").join("gcc.exe");
-        if !gcc.exists() {
-            anyhow::bail!("解压后未找到 gcc.exe，安装可能失败");
-        }
+        // 解压不完整时保留原有版本
+        download::install_zip(
+            &url,
+            &filename,
+            &config.cache_dir(),
+            &install_dir,
+            &download::ZipInstall {
+                name: "MinGW-w64",
+                reporter: Some(ctx.reporter),
+                subdir: Some("mingw64"),
+                required_file: Some("bin/gcc.exe"),
+                ..Default::default()
+            },
+        )
+        .await?;
 
         let version = get_gcc_version(&install_dir).unwrap_or(gcc_version);
 
@@ -105,6 +116,10 @@ impl Installer for MingwInstaller {
         })
     }
 
+    fn binaries(&self) -> &'static [&'static str] {
+        &["bin\\gcc.exe"]
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![EnvAction::AppendPath {
             path: install_path.join("bin").to_string_lossy().to_string(),
@@ -120,7 +135,7 @@ fn get_gcc_version(install_dir: &PathBuf) -> Option<String> {
         .ok()
         .filter(|o| o.status.success())
         .and_then(|o| {
-            String::from_utf8_lossy(&o.stdout)
+            crate::proc::decode_output(&o.stdout)
                 .lines()
                 .next()
                 .map(|s| s.to_string())
@@ -1,8 +1,8 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ShellIntegration, ToolInfo};
 use crate::config::HudoConfig;
 use crate::download;
 
@@ -15,6 +15,8 @@ impl Installer for MinicondaInstaller {
             id: "miniconda",
             name: "Miniconda",
             description: "Conda 包管理器（最小安装）",
+            homepage: Some("https://docs.conda.io/projects/miniconda"),
+            post_install_tips: &[],
         }
     }
 
@@ -23,7 +25,7 @@ impl Installer for MinicondaInstaller {
         if conda_exe.exists() {
             if let Ok(out) = std::process::Command::new(&conda_exe).arg("--version").output() {
                 if out.status.success() {
-                    let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                    let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                     return Ok(DetectResult::InstalledByHudo(version));
                 }
             }
@@ -31,7 +33,7 @@ impl Installer for MinicondaInstaller {
 
         if let Ok(out) = std::process::Command::new("conda").arg("--version").output() {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -51,20 +53,24 @@ impl Installer for MinicondaInstaller {
         let install_dir = config.tools_dir().join("miniconda");
         let (url, filename) = self.resolve_download(config);
 
-        let exe_path = download::download(&url, &config.cache_dir(), &filename).await?;
+        // 地址固定指向最新版，有缓存时重新验证
+        let exe_path = download::download_latest(&url, &config.cache_dir(), &filename).await?;
 
         // Miniconda 支持静默安装到指定目录
-        crate::ui::print_action("安装 Miniconda（静默模式）...");
-        let status = std::process::Command::new(&exe_path)
-            .args([
-                "/InstallationType=JustMe",                     // 仅当前用户，不写 HKLM
-                "/RegisterPython=0",                            // 不注册为系统 Python
-                "/AddToPath=0",                                 // 不自动加 PATH
-                "/S",                                           // 静默
-                &format!("/D={}", install_dir.display()),       // 指定安装目录（必须最后）
-            ])
-            .status()
-            .context("启动 Miniconda 安装程序失败")?;
+        ctx.reporter.action("安装 Miniconda（静默模式）...");
+        let status = crate::watchdog::run_watched(
+            std::process::Command::new(&exe_path)
+                .args([
+                    "/InstallationType=JustMe",                     // 仅当前用户，不写 HKLM
+                    "/RegisterPython=0",                            // 不注册为系统 Python
+                    "/AddToPath=0",                                 // 不自动加 PATH
+                    "/S",                                           // 静默
+                    &format!("/D={}", install_dir.display()),       // 指定安装目录（必须最后）
+                ]),
+            "Miniconda 安装程序",
+            config.install.silent_watch(config.yes),
+        )
+        .context("启动 Miniconda 安装程序失败")?;
 
         if !status.success() {
             anyhow::bail!(
@@ -81,6 +87,31 @@ impl Installer for MinicondaInstaller {
         })
     }
 
+    fn latest_download(&self) -> bool {
+        true
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        &["Scripts\\conda.exe"]
+    }
+
+    fn shell_integration(&self, install_path: &Path) -> Option<ShellIntegration> {
+        // 与 conda init powershell 写入的内容相同，使 conda activate 在 PowerShell 中可用
+        Some(ShellIntegration {
+            name: "miniconda",
+            script: format!(
+                "(& {} 'shell.powershell' 'hook') | Out-String | ?{{$_}} | Invoke-Expression",
+                crate::proc::ps_quote(&install_path.join("Scripts").join("conda.exe").to_string_lossy())
+            ),
+            legacy_comment: None,
+        })
+    }
+
+    async fn configure(&self, ctx: &InstallContext<'_>) -> Result<()> {
+        super::write_shell_integration(ctx, self, &ctx.config.tools_dir().join("miniconda"));
+        Ok(())
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![
             EnvAction::AppendPath {
@@ -103,5 +134,5 @@ fn get_conda_version(install_dir: &PathBuf) -> Option<String> {
         .output()
         .ok()
         .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .map(|o| crate::proc::decode_output(&o.stdout).trim().to_string())
 }
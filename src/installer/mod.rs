@@ -30,6 +30,8 @@ pub mod pgsql;
 #[cfg(windows)]
 pub mod redis;
 #[cfg(windows)]
+pub mod sqlite;
+#[cfg(windows)]
 pub mod pycharm;
 #[cfg(windows)]
 pub mod rustup;
@@ -38,11 +40,13 @@ pub mod uv;
 #[cfg(windows)]
 pub mod vscode;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::arch::Arch;
 use crate::config::HudoConfig;
+use crate::report::{Console, Reporter};
 
 /// 工具基本信息
 #[derive(Debug, Clone)]
@@ -53,6 +57,10 @@ pub struct ToolInfo {
     pub name: &'static str,
     /// 简短描述
     pub description: &'static str,
+    /// 官网（hudo info 中显示）
+    pub homepage: Option<&'static str>,
+    /// 安装完成后在「下一步」中显示的提示（登录、启动服务等）
+    pub post_install_tips: &'static [&'static str],
 }
 
 /// 环境变量操作
@@ -64,6 +72,45 @@ pub enum EnvAction {
     AppendPath { path: String },
 }
 
+/// 开始菜单 / 桌面快捷方式
+#[derive(Debug, Clone)]
+pub struct ShortcutSpec {
+    /// 快捷方式名称（不含 .lnk）
+    pub name: String,
+    /// 目标程序
+    pub target: PathBuf,
+    /// 启动参数
+    pub args: String,
+    /// 图标文件，默认取目标程序的图标
+    pub icon: Option<PathBuf>,
+}
+
+impl ShortcutSpec {
+    /// 无参数、使用程序自身图标的快捷方式
+    pub fn new(name: &str, target: PathBuf) -> Self {
+        Self { name: name.to_string(), target, args: String::new(), icon: None }
+    }
+}
+
+/// 写入 PowerShell profile 的初始化脚本，由 hudo 管理的区块包裹（见 psprofile）
+#[derive(Debug, Clone)]
+pub struct ShellIntegration {
+    /// 区块名（`# >>> hudo <name> >>>`），通常为工具 id
+    pub name: &'static str,
+    /// 区块中的脚本
+    pub script: String,
+    /// 旧版本 hudo 不带区块直接追加的注释行，写入或删除区块时连同下一行一并删除
+    pub legacy_comment: Option<&'static str>,
+}
+
+/// 安装目录之外由 hudo 创建的目录，卸载时一并删除
+#[derive(Debug, Clone)]
+pub struct CreatedDir {
+    pub path: PathBuf,
+    /// 目录中有用户自己的数据时说明是什么（如全局 npm 包）：删除前询问，--keep-config 时保留；None 时直接删除
+    pub user_data: Option<&'static str>,
+}
+
 /// 检测结果
 #[derive(Debug)]
 pub enum DetectResult {
@@ -87,6 +134,20 @@ pub struct InstallResult {
 /// 安装上下文，传递给安装器
 pub struct InstallContext<'a> {
     pub config: &'a HudoConfig,
+    /// 安装过程的输出，安装器不直接打印
+    pub reporter: &'a dyn Reporter,
+}
+
+impl<'a> InstallContext<'a> {
+    /// 输出到终端的上下文
+    pub fn new(config: &'a HudoConfig) -> Self {
+        Self { config, reporter: &Console }
+    }
+
+    /// 安装包的目标架构（--arch 覆盖或本机架构）
+    pub fn arch(&self) -> Arch {
+        self.config.arch()
+    }
 }
 
 /// 安装器 trait
@@ -107,6 +168,74 @@ pub trait Installer: Send + Sync {
     /// 安装后需要执行的环境变量操作
     fn env_actions(&self, install_path: &PathBuf, config: &HudoConfig) -> Vec<EnvAction>;
 
+    /// 主程序相对安装目录的路径（任一存在即视为可用），用于 hudo list 快速检测；
+    /// 默认为空，仅检查安装目录是否存在
+    fn binaries(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// GUI 程序的快捷方式（开始菜单 hudo 目录，可选桌面），默认无
+    fn shortcuts(&self, _install_path: &Path) -> Vec<ShortcutSpec> {
+        vec![]
+    }
+
+    /// 可选的发布渠道（如 Node.js 的 lts / current），第一个为默认渠道；默认只有一个渠道
+    fn channels(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// 未指定版本且查询最新版本失败时使用的内置版本，供 hudo catalog 展示；默认无（总是安装最新版）
+    fn default_version(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// 依赖的其他工具 id（如 Maven 依赖 jdk），安装时缺失会提示一并安装；默认无依赖
+    fn dependencies(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// 安装目录下的用户数据目录（如 VS Code 的 data/、数据库的数据目录），
+    /// uninstall --keep-config 时保留，重新安装后恢复；默认无
+    fn user_data_dirs(&self, _install_path: &Path) -> Vec<PathBuf> {
+        vec![]
+    }
+
+    /// 安装目录之外创建的目录（如 rustup 的工具链目录、fnm 下载的 Node.js 版本），卸载时一并删除；默认无
+    fn created_dirs(&self, _config: &HudoConfig) -> Vec<CreatedDir> {
+        vec![]
+    }
+
+    /// 配置的版本下载不到（404）时附加的提示，如在哪里查看可用版本；默认无
+    fn versions_hint(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// 需要写入 PowerShell profile 的初始化脚本（如 fnm env、conda hook），configure 时写入，卸载时删除；默认无
+    fn shell_integration(&self, _install_path: &Path) -> Option<ShellIntegration> {
+        None
+    }
+
+    /// 注册的 Windows 服务名（如 MySQL），供 hudo service 使用；默认无服务
+    fn service_name(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// 是否提供 ARM64 原生版本（或与架构无关，如 Maven）；默认否，ARM64 上安装 x64 版本
+    fn arm64_native(&self) -> bool {
+        false
+    }
+
+    /// 是否从「最新版」地址安装（地址不带版本号，如 uv 安装脚本、Bun 的 releases/latest）：
+    /// 是则安装时记录响应的 ETag，重新安装前先检查地址上的内容是否变化；默认否
+    fn latest_download(&self) -> bool {
+        false
+    }
+
+    /// 安装方式（如 Git 的 installer / portable），写入 state.json，默认不记录
+    fn install_mode(&self, _config: &HudoConfig) -> Option<String> {
+        None
+    }
+
     /// 安装后的交互式配置（默认无操作）
     async fn configure(&self, _ctx: &InstallContext<'_>) -> Result<()> {
         Ok(())
@@ -138,6 +267,26 @@ pub enum ServiceState {
     NotFound,
 }
 
+#[cfg(windows)]
+impl ServiceState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ServiceState::Running => "运行中",
+            ServiceState::Stopped => "已停止",
+            ServiceState::NotFound => "未注册",
+        }
+    }
+
+    /// --json 中的取值
+    pub fn key(&self) -> &'static str {
+        match self {
+            ServiceState::Running => "running",
+            ServiceState::Stopped => "stopped",
+            ServiceState::NotFound => "not_found",
+        }
+    }
+}
+
 #[cfg(windows)]
 pub fn query_service_exists(name: &str) -> bool {
     std::process::Command::new("sc")
@@ -151,7 +300,7 @@ pub fn query_service_exists(name: &str) -> bool {
 pub fn query_service_state(name: &str) -> ServiceState {
     match std::process::Command::new("sc").args(["query", name]).output() {
         Ok(out) if out.status.success() => {
-            if String::from_utf8_lossy(&out.stdout).contains("RUNNING") {
+            if crate::proc::decode_output(&out.stdout).contains("RUNNING") {
                 ServiceState::Running
             } else {
                 ServiceState::Stopped
@@ -164,30 +313,12 @@ pub fn query_service_state(name: &str) -> ServiceState {
 /// 通过 PowerShell Start-Process -Verb RunAs 以管理员身份运行命令
 #[cfg(windows)]
 pub fn run_as_admin(program: &str, args: &[&str]) -> anyhow::Result<()> {
-    let prog_escaped = program.replace('\'', "''");
-    let arg_list: Vec<String> = args
-        .iter()
-        .map(|a| format!("'{}'", a.replace('\'', "''")))
-        .collect();
-
-    let ps_cmd = format!(
-        "try {{ \
-           $p = Start-Process -FilePath '{}' -ArgumentList @({}) \
-                -Verb RunAs -Wait -PassThru -WindowStyle Hidden; \
-           if ($p) {{ exit $p.ExitCode }} else {{ exit 1 }} \
-         }} catch {{ exit 1 }}",
-        prog_escaped,
-        arg_list.join(", ")
-    );
-
-    let output = std::process::Command::new("powershell")
-        .args(["-NoProfile", "-Command", &ps_cmd])
-        .output()?;
+    let output = crate::proc::powershell(&crate::proc::elevate_script(program, args)).output()?;
 
     if output.status.success() {
         Ok(())
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = crate::proc::decode_output(&output.stderr);
         if stderr.trim().is_empty() {
             anyhow::bail!("管理员权限操作失败（用户可能拒绝了 UAC 提示）: {}", program)
         } else {
@@ -196,6 +327,255 @@ pub fn run_as_admin(program: &str, args: &[&str]) -> anyhow::Result<()> {
     }
 }
 
+// ── 端口检查（mysql、pgsql 配置前确认监听端口可用） ─────────────────────────
+
+/// 占用端口的进程
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortOwner {
+    pub pid: u32,
+    pub name: String,
+    /// 可执行文件路径，无权读取（如其他用户的服务进程）时为 None
+    pub path: Option<PathBuf>,
+}
+
+/// 端口是否空闲：127.0.0.1 与 0.0.0.0 都能绑定才算空闲
+/// （Windows 上只绑定 127.0.0.1 发现不了监听 0.0.0.0 的进程）
+pub fn port_is_free(port: u16) -> bool {
+    ["127.0.0.1", "0.0.0.0"]
+        .iter()
+        .all(|ip| std::net::TcpListener::bind((*ip, port)).is_ok())
+}
+
+/// port 之后 100 个端口内第一个空闲的端口，作为改用的建议
+pub fn next_free_port(port: u16) -> Option<u16> {
+    (port..=u16::MAX).skip(1).take(100).find(|&p| port_is_free(p))
+}
+
+/// 监听该端口的进程
+#[cfg(windows)]
+pub fn port_owner(port: u16) -> Option<PortOwner> {
+    let script = format!(
+        "$c = Get-NetTCPConnection -LocalPort {} -State Listen -ErrorAction SilentlyContinue | Select-Object -First 1; \
+         if ($c) {{ $p = Get-Process -Id $c.OwningProcess -ErrorAction SilentlyContinue; \
+         [pscustomobject]@{{ Id = $c.OwningProcess; Name = $p.ProcessName; Path = $p.Path }} | ConvertTo-Json -Compress }}",
+        port
+    );
+    let out = crate::proc::powershell(&script).output().ok()?;
+    parse_port_owner(&crate::proc::decode_output(&out.stdout))
+}
+
+#[cfg(not(windows))]
+pub fn port_owner(_port: u16) -> Option<PortOwner> {
+    None
+}
+
+/// 解析 port_owner 脚本输出的 `{"Id":..,"Name":..,"Path":..}`
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_port_owner(json: &str) -> Option<PortOwner> {
+    let value: serde_json::Value = serde_json::from_str(json.trim()).ok()?;
+    Some(PortOwner {
+        pid: u32::try_from(value["Id"].as_u64()?).ok()?,
+        name: value["Name"].as_str().unwrap_or("未知进程").to_string(),
+        path: value["Path"].as_str().filter(|p| !p.is_empty()).map(PathBuf::from),
+    })
+}
+
+/// 注册服务前确认端口可用，返回最终使用的端口。
+/// 被占用时列出占用进程，可改用建议端口、输入其他端口或取消配置；-y 时直接改用建议端口。
+/// 占用者是 own_dir 下的程序（hudo 自己装的数据库正在运行）时不视为冲突
+pub fn ensure_port_free(ctx: &InstallContext<'_>, name: &str, port: u16, own_dir: &Path) -> Result<u16> {
+    if port_is_free(port) {
+        return Ok(port);
+    }
+    let owner = port_owner(port);
+    if owner.as_ref().and_then(|o| o.path.as_deref()).is_some_and(|p| crate::running::is_under(p, own_dir)) {
+        return Ok(port);
+    }
+    let owner = owner.map(|o| format!("（{}，PID {}）", o.name, o.pid)).unwrap_or_default();
+    ctx.reporter.warning(&format!("端口 {} 已被占用{}，{} 服务将无法启动", port, owner, name));
+
+    let suggested = next_free_port(port);
+    if ctx.config.yes {
+        let p = suggested.with_context(|| format!("端口 {} 已被占用，且附近没有空闲端口", port))?;
+        ctx.reporter.info(&format!("改用端口 {}", p));
+        return Ok(p);
+    }
+
+    let mut items: Vec<String> = suggested.iter().map(|p| format!("改用端口 {}", p)).collect();
+    items.push("输入其他端口".to_string());
+    items.push("取消配置".to_string());
+    let choice = dialoguer::Select::new()
+        .with_prompt("  如何处理？")
+        .items(&items)
+        .default(0)
+        .interact()?;
+    if choice == items.len() - 1 {
+        anyhow::bail!("端口 {} 已被占用，已取消配置 {}", port, name);
+    }
+    if let Some(p) = suggested.filter(|_| choice == 0) {
+        return Ok(p);
+    }
+    loop {
+        let p: u16 = dialoguer::Input::new()
+            .with_prompt("  端口")
+            .validate_with(|p: &u16| if *p == 0 { Err("端口不能为 0") } else { Ok(()) })
+            .interact_text()?;
+        if port_is_free(p) {
+            return Ok(p);
+        }
+        ctx.reporter.warning(&format!("端口 {} 也已被占用", p));
+    }
+}
+
+/// 安装器当前使用的渠道：配置中指定且在 channels() 中的渠道，否则为默认渠道；没有渠道的工具返回 None
+pub fn active_channel(installer: &dyn Installer, config: &HudoConfig) -> Option<&'static str> {
+    let channels = installer.channels();
+    let chosen = config.channel(installer.info().id);
+    channels
+        .iter()
+        .find(|c| Some(**c) == chosen)
+        .or_else(|| channels.first())
+        .copied()
+}
+
+/// 校验渠道名，不支持渠道或渠道不存在时报错并列出可用渠道
+pub fn check_channel(installer: &dyn Installer, channel: &str) -> Result<()> {
+    let info = installer.info();
+    let channels = installer.channels();
+    if channels.is_empty() {
+        anyhow::bail!("{} 没有可选的发布渠道", info.name);
+    }
+    if !channels.contains(&channel) {
+        anyhow::bail!("{} 没有 {} 渠道，可用: {}", info.name, channel, channels.join(", "));
+    }
+    Ok(())
+}
+
+/// ARM64 上安装没有原生版本的工具时提示将通过 x64 仿真运行
+pub fn warn_x64_fallback(ctx: &InstallContext<'_>, installer: &dyn Installer) {
+    if ctx.arch() == Arch::Arm64 && !installer.arm64_native() {
+        ctx.reporter.warning(&format!(
+            "{} 暂无 {} 原生版本，将安装 {} 版本（由系统仿真运行）",
+            installer.info().name,
+            Arch::Arm64.name(),
+            Arch::X64.name()
+        ));
+    }
+}
+
+/// 安装失败是因为配置中固定的版本不存在（下载返回 404）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingVersion {
+    /// 配置键，如 versions.gh
+    pub key: String,
+    /// versions 键（见 config::VERSION_KEYS），用于查询最新版本
+    pub version_key: &'static str,
+    pub version: String,
+}
+
+/// err 为 404 且该工具在配置中固定了版本时返回缺失的版本；未固定版本时 404 不是版本问题
+pub fn missing_version(err: &anyhow::Error, config: &HudoConfig, tool_id: &str) -> Option<MissingVersion> {
+    if crate::download::http_status(err) != Some(404) {
+        return None;
+    }
+    let (key, version_key) = crate::config::version_key_for(tool_id)?;
+    let version = config.pinned(&key)?.to_string();
+    Some(MissingVersion { key, version_key, version })
+}
+
+/// 把 shell_integration 的区块写入 PowerShell profile（configure 中调用），失败时提示手动添加
+#[cfg(windows)]
+pub fn write_shell_integration(ctx: &InstallContext<'_>, installer: &dyn Installer, install_path: &Path) {
+    let Some(integration) = installer.shell_integration(install_path) else {
+        return;
+    };
+    let name = installer.info().name;
+    match crate::psprofile::profile_path().and_then(|p| crate::psprofile::write_block(&p, &integration)) {
+        Ok(true) => ctx.reporter.success(&format!("已将 {} 初始化脚本写入 PowerShell profile，重开终端后生效", name)),
+        Ok(false) => ctx.reporter.info(&format!("PowerShell profile 已包含 {} 初始化脚本", name)),
+        Err(e) => {
+            ctx.reporter.warning(&format!("写入 PowerShell profile 失败: {:#}", e));
+            ctx.reporter.info("请手动在 $PROFILE 中添加：");
+            for line in integration.script.lines() {
+                ctx.reporter.info(&format!("  {}", line));
+            }
+        }
+    }
+}
+
+/// 从 PowerShell profile 中删除 shell_integration 的区块（卸载时调用），返回 profile 路径
+#[cfg(windows)]
+pub fn remove_shell_integration(installer: &dyn Installer, install_path: &Path) -> Result<Option<PathBuf>> {
+    let Some(integration) = installer.shell_integration(install_path) else {
+        return Ok(None);
+    };
+    let profile = crate::psprofile::profile_path()?;
+    Ok(crate::psprofile::remove_block(&profile, &integration)?.then_some(profile))
+}
+
+/// 作为其他工具的依赖自动安装（如 Maven 需要的 JDK、Rust 需要的 MinGW-w64）
+/// 安装并写入环境变量，同时在 state.json 中记录依赖方，卸载依赖方时可提示一并移除
+#[cfg(windows)]
+pub async fn install_as_dependency(
+    ctx: &InstallContext<'_>,
+    installer: &dyn Installer,
+    dependent_id: &str,
+) -> Result<InstallResult> {
+    let info = installer.info();
+    ctx.reporter.title(&format!("安装 {}", info.name));
+    warn_x64_fallback(ctx, installer);
+    let result = installer.install(ctx).await?;
+    ctx.reporter.success(&format!(
+        "{} {} 安装完成",
+        info.name,
+        console::style(&result.version).green()
+    ));
+
+    // 持久化环境变量
+    let actions = installer.env_actions(&result.install_path, ctx.config);
+    for action in &actions {
+        match action {
+            EnvAction::AppendPath { path } => {
+                ctx.reporter.info(&format!("PATH += {}", path));
+                crate::env::EnvManager::append_to_path(path)?;
+            }
+            EnvAction::Set { name, value } => {
+                ctx.reporter.info(&format!("{} = {}", name, value));
+                crate::env::EnvManager::set_var(name, value)?;
+            }
+        }
+    }
+    if !actions.is_empty() {
+        crate::env::EnvManager::broadcast_change();
+    }
+
+    let state_path = ctx.config.state_path();
+    let mut reg = crate::registry::InstallRegistry::load(&state_path)?;
+    reg.mark_installed_as_dependency(
+        info.id,
+        &result.version,
+        &result.install_path.to_string_lossy(),
+        dependent_id,
+    );
+    if let Some(mode) = installer.install_mode(ctx.config) {
+        reg.set_install_mode(info.id, &mode);
+    }
+    reg.save(&state_path)?;
+
+    Ok(result)
+}
+
+/// 依赖已存在且本身是被自动安装的，追加一个依赖方记录（如 Gradle 复用随 Maven 安装的 JDK）
+#[cfg(windows)]
+pub fn record_dependent(config: &HudoConfig, dep_id: &str, dependent_id: &str) -> Result<()> {
+    let state_path = config.state_path();
+    let mut reg = crate::registry::InstallRegistry::load(&state_path)?;
+    if reg.add_dependent(dep_id, dependent_id) {
+        reg.save(&state_path)?;
+    }
+    Ok(())
+}
+
 /// 返回所有可用的安装器
 pub fn all_installers() -> Vec<Box<dyn Installer>> {
     let mut list: Vec<Box<dyn Installer>> = vec![
@@ -221,6 +601,7 @@ pub fn all_installers() -> Vec<Box<dyn Installer>> {
         list.push(Box::new(mysql::MysqlInstaller));
         list.push(Box::new(pgsql::PgsqlInstaller));
         list.push(Box::new(redis::RedisInstaller));
+        list.push(Box::new(sqlite::SqliteInstaller));
         // 编辑器 / IDE
         list.push(Box::new(vscode::VscodeInstaller));
         list.push(Box::new(pycharm::PycharmInstaller));
@@ -229,3 +610,367 @@ pub fn all_installers() -> Vec<Box<dyn Installer>> {
 
     list
 }
+
+/// 卸载时要一并删除的 created_dirs：不存在的跳过，含用户数据的在 keep_config 时保留、否则由 confirm 决定；
+/// 返回 (要删除的, 保留的)
+pub fn split_created_dirs(
+    dirs: Vec<CreatedDir>,
+    keep_config: bool,
+    mut confirm: impl FnMut(&CreatedDir) -> Result<bool>,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let (mut remove, mut keep) = (Vec::new(), Vec::new());
+    for dir in dirs.into_iter().filter(|d| d.path.exists()) {
+        let delete = match dir.user_data {
+            None => true,
+            Some(_) if keep_config => false,
+            Some(_) => confirm(&dir)?,
+        };
+        match delete {
+            true => remove.push(dir.path),
+            false => keep.push(dir.path),
+        }
+    }
+    Ok((remove, keep))
+}
+
+/// 按 id 查找安装器（install / uninstall / info / pin / service 等命令共用）；
+/// 未知时的错误信息按分类列出可用工具，与某个 id 或名称拼写相近时先问一句
+/// 「你是否想{action} 'xxx'？」，action 是命令的动作（安装、卸载、查看…）
+pub fn find<'a>(installers: &'a [Box<dyn Installer>], tool_id: &str, action: &str) -> Result<&'a dyn Installer> {
+    if let Some(inst) = installers.iter().find(|i| i.info().id == tool_id) {
+        return Ok(inst.as_ref());
+    }
+    let ids: Vec<&str> = installers.iter().map(|i| i.info().id).collect();
+    let names: Vec<&str> = installers.iter().map(|i| i.info().name).collect();
+    // 先比 id，再比名称（postgres → PostgreSQL → pgsql）
+    let suggestion = suggest(tool_id, &ids).or_else(|| {
+        let name = suggest(tool_id, &names)?;
+        installers.iter().find(|i| i.info().name == name).map(|i| i.info().id)
+    });
+    let unknown = format!("未知工具 '{}'，可用:\n{}", tool_id, group_by_category(&ids));
+    match suggestion {
+        Some(id) => anyhow::bail!("你是否想{} '{}'？\n{}", action, id, unknown),
+        None => anyhow::bail!("{}", unknown),
+    }
+}
+
+/// 与 input 编辑距离最近的候选（不区分大小写），只认拼写相近的：
+/// 距离超过 2、或不小于 input 的长度（如 "x" 与 "c"）时不算拼写错误
+pub fn suggest<'a>(input: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let input = input.to_lowercase();
+    let len = input.chars().count();
+    candidates
+        .iter()
+        .map(|&c| (edit_distance(&input, &c.to_lowercase()), c))
+        .filter(|&(d, _)| d <= 2 && d < len)
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, c)| c)
+}
+
+/// Levenshtein 距离（按字符计）
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            row[j + 1] = (prev[j] + usize::from(ca != cb)).min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// 按分类分组的工具 id，每个分类一行（hudo install 不带工具名、工具名未知时列出）
+pub fn available_by_category() -> String {
+    let ids: Vec<&str> = all_installers().iter().map(|i| i.info().id).collect();
+    group_by_category(&ids)
+}
+
+fn group_by_category(ids: &[&str]) -> String {
+    use crate::ui::ToolCategory;
+    let width = ToolCategory::ALL.iter().map(|c| console::measure_text_width(c.label())).max().unwrap_or(0) + 2;
+    ToolCategory::ALL
+        .into_iter()
+        .filter_map(|category| {
+            let group: Vec<&str> = ids.iter().copied().filter(|id| ToolCategory::from_id(id) == category).collect();
+            (!group.is_empty()).then(|| format!("  {}{}", crate::ui::pad(category.label(), width), group.join(", ")))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// 工具的全部依赖（含间接依赖），被依赖的排在前面，不含工具自身；
+/// `deps_of` 返回单个工具直接声明的依赖
+pub fn resolve_dependencies<'a, F>(tool_id: &str, deps_of: F) -> Vec<&'a str>
+where
+    F: Fn(&str) -> &'a [&'a str],
+{
+    fn visit<'a, F>(id: &str, deps_of: &F, stack: &mut Vec<String>, out: &mut Vec<&'a str>)
+    where
+        F: Fn(&str) -> &'a [&'a str],
+    {
+        stack.push(id.to_string());
+        for &dep in deps_of(id) {
+            // 已收集或出现循环时跳过
+            if out.contains(&dep) || stack.iter().any(|s| s == dep) {
+                continue;
+            }
+            visit(dep, deps_of, stack, out);
+            out.push(dep);
+        }
+        stack.pop();
+    }
+
+    let mut out = Vec::new();
+    visit(tool_id, &deps_of, &mut Vec::new(), &mut out);
+    out
+}
+
+/// 并行调用 detect_installed（每个工具一个线程，子进程检测互不阻塞），结果与 `tools` 顺序一致
+/// 需在 tokio runtime 中调用
+pub fn detect_parallel(tools: &[&dyn Installer], config: &HudoConfig) -> Vec<Result<DetectResult>> {
+    // 获取当前 tokio runtime 句柄，供非 tokio 线程使用
+    let handle = tokio::runtime::Handle::current();
+    std::thread::scope(|s| {
+        // 并行启动所有子进程检测
+        let handles: Vec<_> = tools
+            .iter()
+            .map(|&inst| {
+                let handle = handle.clone();
+                s.spawn(move || {
+                    let ctx = InstallContext::new(config);
+                    handle.block_on(inst.detect_installed(&ctx))
+                })
+            })
+            .collect();
+
+        // 等待所有线程完成（已并行执行）
+        handles
+            .into_iter()
+            .map(|h| {
+                h.join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("检测线程崩溃")))
+            })
+            .collect()
+    })
+}
+
+/// 把安装目录下的用户数据目录移到 `kept` 下（保持相对路径），返回实际移走的目录
+pub fn stash_user_data(dirs: &[PathBuf], install_path: &Path, kept: &Path) -> Result<Vec<PathBuf>> {
+    let mut moved = Vec::new();
+    for dir in dirs.iter().filter(|d| d.exists()) {
+        let Ok(rel) = dir.strip_prefix(install_path) else {
+            continue;
+        };
+        let target = kept.join(rel);
+        if target.exists() {
+            std::fs::remove_dir_all(&target)
+                .with_context(|| format!("无法清理上次保留的数据: {}", target.display()))?;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("无法创建目录: {}", parent.display()))?;
+        }
+        std::fs::rename(dir, &target)
+            .with_context(|| format!("无法移出用户数据: {}", dir.display()))?;
+        moved.push(target);
+    }
+    Ok(moved)
+}
+
+/// 重新安装后把 `kept` 下保留的用户数据移回安装目录，返回恢复的目录；
+/// 安装目录中已有非空的同名目录时不覆盖，保留的数据留在原处
+pub fn restore_user_data(dirs: &[PathBuf], install_path: &Path, kept: &Path) -> Result<Vec<PathBuf>> {
+    let mut restored = Vec::new();
+    for dir in dirs {
+        let Ok(rel) = dir.strip_prefix(install_path) else {
+            continue;
+        };
+        let source = kept.join(rel);
+        if !source.exists() {
+            continue;
+        }
+        let is_empty = dir.read_dir().map(|mut d| d.next().is_none()).unwrap_or(true);
+        if !is_empty {
+            continue;
+        }
+        if dir.exists() {
+            std::fs::remove_dir(dir).with_context(|| format!("无法替换目录: {}", dir.display()))?;
+        }
+        std::fs::rename(&source, dir)
+            .with_context(|| format!("无法恢复用户数据: {}", dir.display()))?;
+        restored.push(dir.clone());
+    }
+    // 全部恢复后 kept 下只剩空目录
+    if kept.exists() && only_empty_dirs(kept) {
+        std::fs::remove_dir_all(kept).ok();
+    }
+    Ok(restored)
+}
+
+fn only_empty_dirs(dir: &Path) -> bool {
+    std::fs::read_dir(dir).map_or(true, |entries| {
+        entries.filter_map(|e| e.ok()).all(|e| e.path().is_dir() && only_empty_dirs(&e.path()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_created_dirs() {
+        let root = std::env::temp_dir().join(format!("hudo-test-created-dirs-{}", std::process::id()));
+        let (rustup, node) = (root.join("tools").join("rustup"), root.join("lang").join("node"));
+        std::fs::create_dir_all(&rustup).unwrap();
+        std::fs::create_dir_all(&node).unwrap();
+        let dirs = vec![
+            CreatedDir { path: rustup.clone(), user_data: None },
+            CreatedDir { path: node.clone(), user_data: Some("全局 npm 包") },
+            CreatedDir { path: root.join("missing"), user_data: None },
+        ];
+
+        // 只询问含用户数据的目录，不存在的目录跳过
+        let mut asked = Vec::new();
+        let (remove, keep) = split_created_dirs(dirs.clone(), false, |d| {
+            asked.push(d.path.clone());
+            Ok(false)
+        })
+        .unwrap();
+        assert_eq!((remove, keep), (vec![rustup.clone()], vec![node.clone()]));
+        assert_eq!(asked, vec![node.clone()]);
+
+        let (remove, _) = split_created_dirs(dirs.clone(), false, |_| Ok(true)).unwrap();
+        assert_eq!(remove, [rustup.clone(), node.clone()]);
+
+        // --keep-config 时不询问，直接保留
+        let (remove, keep) = split_created_dirs(dirs, true, |_| panic!("不应询问")).unwrap();
+        assert_eq!((remove, keep), (vec![rustup], vec![node]));
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_suggest() {
+        let ids = ["git", "gh", "go", "rust", "nodejs", "mysql", "pgsql", "c"];
+        assert_eq!(edit_distance("nodjs", "nodejs"), 1);
+        assert_eq!(edit_distance("rsut", "rust"), 2);
+        assert_eq!(edit_distance("", "go"), 2);
+        assert_eq!(suggest("nodjs", &ids), Some("nodejs"));
+        assert_eq!(suggest("RUST", &ids), Some("rust"));
+        assert_eq!(suggest("mysq", &ids), Some("mysql"));
+        // 距离相同时取靠前的候选；相差太多或输入太短时不猜
+        assert_eq!(suggest("gi", &ids), Some("git"));
+        assert_eq!(suggest("python", &ids), None);
+        assert_eq!(suggest("x", &ids), None);
+    }
+
+    #[test]
+    fn test_find() {
+        let installers = all_installers();
+        assert_eq!(find(&installers, "claude-code", "安装").unwrap().info().id, "claude-code");
+        let err = find(&installers, "claude-cod", "卸载").err().unwrap().to_string();
+        assert!(err.starts_with("你是否想卸载 'claude-code'？\n未知工具 'claude-cod'"), "{}", err);
+        let err = find(&installers, "python3.12", "安装").err().unwrap().to_string();
+        assert!(err.starts_with("未知工具 'python3.12'"), "{}", err);
+    }
+
+    #[test]
+    fn test_group_by_category() {
+        let text = group_by_category(&["git", "go", "uv", "vscode"]);
+        let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("  工具") && lines[0].ends_with("git"), "{}", text);
+        assert!(lines[1].starts_with("  语言环境") && lines[1].ends_with("go, uv"), "{}", text);
+        assert!(lines[2].starts_with("  编辑器 / IDE") && lines[2].ends_with("vscode"), "{}", text);
+        // 工具 id 所在的列对齐
+        let column = |line: &str| console::measure_text_width(&line[..line.rfind("  ").unwrap() + 2]);
+        assert_eq!(column(lines[0]), column(lines[1]));
+        assert_eq!(column(lines[1]), column(lines[2]));
+    }
+
+    #[test]
+    fn test_resolve_dependencies() {
+        let deps_of = |id: &str| -> &'static [&'static str] {
+            match id {
+                "maven" => &["jdk"],
+                "rust" => &["c"],
+                "kotlin" => &["gradle", "jdk"],
+                "gradle" => &["jdk"],
+                "a" => &["b"],
+                "b" => &["a"],
+                _ => &[],
+            }
+        };
+        assert_eq!(resolve_dependencies("maven", deps_of), ["jdk"]);
+        assert!(resolve_dependencies("git", deps_of).is_empty());
+        // 间接依赖排在前面，共同依赖只出现一次
+        assert_eq!(resolve_dependencies("kotlin", deps_of), ["jdk", "gradle"]);
+        // 循环依赖不会死循环
+        assert_eq!(resolve_dependencies("a", deps_of), ["b"]);
+    }
+
+    #[test]
+    fn test_port_is_free() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(!port_is_free(port));
+        let next = next_free_port(port).unwrap();
+        assert!(next > port && port_is_free(next));
+        drop(listener);
+        assert!(port_is_free(port));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_port_owner() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert_eq!(port_owner(port).map(|o| o.pid), Some(std::process::id()));
+    }
+
+    #[test]
+    fn test_parse_port_owner() {
+        assert_eq!(
+            parse_port_owner(r#"{"Id":1234,"Name":"mysqld","Path":"C:\\mysql\\bin\\mysqld.exe"}"#),
+            Some(PortOwner {
+                pid: 1234,
+                name: "mysqld".to_string(),
+                path: Some(PathBuf::from(r"C:\mysql\bin\mysqld.exe")),
+            })
+        );
+        // 无权读取的服务进程只有 Id
+        assert_eq!(
+            parse_port_owner(r#"{"Id":4,"Name":null,"Path":null}"#),
+            Some(PortOwner { pid: 4, name: "未知进程".to_string(), path: None })
+        );
+        assert_eq!(parse_port_owner(""), None);
+    }
+
+    #[test]
+    fn test_stash_and_restore_user_data() {
+        let root = std::env::temp_dir().join(format!("hudo-test-keep-{}", std::process::id()));
+        let install = root.join("vscode");
+        let kept = root.join("kept").join("vscode");
+        let data = install.join("data");
+        std::fs::create_dir_all(data.join("extensions")).unwrap();
+        std::fs::write(data.join("user-data.json"), "{}").unwrap();
+        let dirs = vec![data.clone(), install.join("missing")];
+
+        let moved = stash_user_data(&dirs, &install, &kept).unwrap();
+        assert_eq!(moved, vec![kept.join("data")]);
+        assert!(!data.exists());
+        assert!(kept.join("data").join("extensions").exists());
+
+        // 重新安装后 data/ 为空目录（VS Code 安装时自动创建），被保留的数据替换
+        std::fs::create_dir_all(&data).unwrap();
+        let restored = restore_user_data(&dirs, &install, &kept).unwrap();
+        assert_eq!(restored, vec![data.clone()]);
+        assert_eq!(std::fs::read_to_string(data.join("user-data.json")).unwrap(), "{}");
+        assert!(!kept.exists());
+
+        // 没有保留数据时什么都不做
+        assert!(restore_user_data(&dirs, &install, &kept).unwrap().is_empty());
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
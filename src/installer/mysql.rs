@@ -1,6 +1,8 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use std::path::PathBuf;
+use dialoguer::{Confirm, Password};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
 use crate::config::HudoConfig;
@@ -9,7 +11,6 @@ use crate::download;
 pub struct MysqlInstaller;
 
 const MYSQL_VERSION_DEFAULT: &str = "8.4.8";
-const MYSQL_SERVICE_NAME: &str = "MySQL";
 
 #[async_trait]
 impl Installer for MysqlInstaller {
@@ -18,6 +19,11 @@ impl Installer for MysqlInstaller {
             id: "mysql",
             name: "MySQL",
             description: "MySQL Community Server",
+            homepage: Some("https://dev.mysql.com/downloads/mysql/"),
+            post_install_tips: &[
+                "启动 / 停止: hudo service mysql start|stop",
+                "卸载服务: mysqld --remove MySQL（需管理员）",
+            ],
         }
     }
 
@@ -26,7 +32,7 @@ impl Installer for MysqlInstaller {
         if mysql_exe.exists() {
             if let Ok(out) = std::process::Command::new(&mysql_exe).arg("--version").output() {
                 if out.status.success() {
-                    let version = parse_mysql_version(&String::from_utf8_lossy(&out.stdout));
+                    let version = parse_mysql_version(&crate::proc::decode_output(&out.stdout));
                     return Ok(DetectResult::InstalledByHudo(version));
                 }
             }
@@ -34,7 +40,7 @@ impl Installer for MysqlInstaller {
 
         if let Ok(out) = std::process::Command::new("mysql").arg("--version").output() {
             if out.status.success() {
-                let version = parse_mysql_version(&String::from_utf8_lossy(&out.stdout));
+                let version = parse_mysql_version(&crate::proc::decode_output(&out.stdout));
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -46,40 +52,45 @@ impl Installer for MysqlInstaller {
         let version = config.versions.mysql.as_deref().unwrap_or(MYSQL_VERSION_DEFAULT);
         let filename = format!("mysql-{}-winx64.zip", version);
         let major_minor = version.rsplitn(2, '.').last().unwrap_or(version);
-        let base = config
-            .mirrors
-            .mysql
-            .as_deref()
-            .unwrap_or("https://cdn.mysql.com/Downloads");
+        let base = config.expand_mirror(
+            config.mirrors.mysql.as_deref().unwrap_or("https://cdn.mysql.com/Downloads"),
+            version,
+            "mysql",
+        );
         let url = format!(
             "{}/MySQL-{}/{}",
-            base.trim_end_matches('/'),
+            base,
             major_minor,
             filename
         );
         (url, filename)
     }
 
+    fn default_version(&self) -> Option<&'static str> {
+        Some(MYSQL_VERSION_DEFAULT)
+    }
+
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("可用版本见 https://dev.mysql.com/downloads/mysql/，cdn.mysql.com 只保留各系列的最新版本")
+    }
+
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
         let config = ctx.config;
         let install_dir = config.tools_dir().join("mysql");
         let (url, filename) = self.resolve_download(config);
 
-        let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
-
-        crate::ui::print_action("解压 MySQL...");
-        let tmp_dir = config.cache_dir().join("mysql-extract");
-        if tmp_dir.exists() {
-            std::fs::remove_dir_all(&tmp_dir).ok();
-        }
-        download::extract_zip(&zip_path, &tmp_dir)?;
-
-        let inner = download::find_single_subdir(&tmp_dir).unwrap_or(tmp_dir.clone());
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir).ok();
-        }
-        std::fs::rename(&inner, &install_dir).ok();
-        std::fs::remove_dir_all(&tmp_dir).ok();
+        download::install_zip(
+            &url,
+            &filename,
+            &config.cache_dir(),
+            &install_dir,
+            &download::ZipInstall {
+                name: "MySQL",
+                reporter: Some(ctx.reporter),
+                ..Default::default()
+            },
+        )
+        .await?;
 
         let version = config
             .versions
@@ -93,6 +104,18 @@ impl Installer for MysqlInstaller {
         })
     }
 
+    fn binaries(&self) -> &'static [&'static str] {
+        &["bin\\mysql.exe"]
+    }
+
+    fn user_data_dirs(&self, install_path: &Path) -> Vec<PathBuf> {
+        vec![install_path.join("data")]
+    }
+
+    fn service_name(&self) -> Option<&'static str> {
+        Some("MySQL")
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![EnvAction::AppendPath {
             path: install_path.join("bin").to_string_lossy().to_string(),
@@ -100,6 +123,14 @@ impl Installer for MysqlInstaller {
     }
 
     async fn pre_uninstall(&self, ctx: &InstallContext<'_>) -> Result<()> {
+        let Some(service) = self.service_name() else {
+            return Ok(());
+        };
+        // 未注册服务（如 db_autoservice = false）时无需停止和移除
+        if !query_service_exists(service) {
+            return Ok(());
+        }
+
         let mysqld = ctx
             .config
             .tools_dir()
@@ -108,13 +139,13 @@ impl Installer for MysqlInstaller {
             .join("mysqld.exe");
 
         // 停止服务（忽略失败：可能服务未运行）
-        crate::ui::print_action("停止 MySQL 服务...");
-        let _ = run_as_admin("net", &["stop", MYSQL_SERVICE_NAME]);
+        ctx.reporter.action("停止 MySQL 服务...");
+        let _ = run_as_admin("net", &["stop", service]);
 
         // 移除服务注册（忽略失败：可能服务未注册）
-        crate::ui::print_action("移除 MySQL 服务注册...");
+        ctx.reporter.action("移除 MySQL 服务注册...");
         let mysqld_str = mysqld.to_string_lossy().to_string();
-        let _ = run_as_admin(&mysqld_str, &["--remove", MYSQL_SERVICE_NAME]);
+        let _ = run_as_admin(&mysqld_str, &["--remove", service]);
 
         Ok(())
     }
@@ -124,10 +155,24 @@ impl Installer for MysqlInstaller {
         let mysqld = install_dir.join("bin").join("mysqld.exe");
         let data_dir = install_dir.join("data");
 
-        // 1. 生成 my.ini
-        crate::ui::print_action("生成 my.ini...");
-        let my_ini = write_my_ini(&install_dir)?;
-        crate::ui::print_info(&format!("配置文件: {}", my_ini.display()));
+        // 1. 确认端口可用（hudo 的 MySQL 服务正在运行时端口就是它占用的），生成 my.ini
+        let own_running = self
+            .service_name()
+            .is_some_and(|s| matches!(query_service_state(s), ServiceState::Running));
+        let mut port = ctx.config.mysql.port;
+        if !own_running {
+            port = ensure_port_free(ctx, "MySQL", port, &install_dir)?;
+        }
+        if port != ctx.config.mysql.port {
+            // 记入 mysql.port，重新读取配置文件，不保存本次运行的临时覆盖
+            if let Some(mut saved) = HudoConfig::load()? {
+                saved.mysql.port = port;
+                saved.save()?;
+            }
+        }
+        ctx.reporter.action("生成 my.ini...");
+        let my_ini = write_my_ini(&install_dir, port)?;
+        ctx.reporter.info(&format!("配置文件: {}", my_ini.display()));
 
         // 2. 初始化数据目录（若为空，不需要管理员权限）
         let is_data_empty = data_dir
@@ -136,20 +181,24 @@ impl Installer for MysqlInstaller {
             .unwrap_or(true);
 
         if is_data_empty {
-            crate::ui::print_action("初始化 MySQL 数据目录...");
+            ctx.reporter.action("初始化 MySQL 数据目录...");
             let basedir_arg = format!("--basedir={}", install_dir.display());
             let datadir_arg = format!("--datadir={}", data_dir.display());
-            let status = std::process::Command::new(&mysqld)
-                .args(["--initialize-insecure", &basedir_arg, &datadir_arg])
-                .status();
+            let status = crate::watchdog::run_watched(
+                std::process::Command::new(&mysqld)
+                    .args(["--initialize-insecure", &basedir_arg, &datadir_arg]),
+                "mysqld --initialize-insecure",
+                ctx.config.install.watch(ctx.config.yes),
+            );
 
             match status {
                 Ok(s) if s.success() => {
-                    crate::ui::print_success("数据目录初始化完成（root 用户无密码）");
+                    ctx.reporter.success("数据目录初始化完成（root 用户无密码）");
                 }
+                Err(e) if crate::watchdog::is_aborted(&e) => return Err(e),
                 _ => {
-                    crate::ui::print_warning("数据目录初始化失败");
-                    crate::ui::print_info(&format!(
+                    ctx.reporter.warning("数据目录初始化失败");
+                    ctx.reporter.info(&format!(
                         "  请手动执行: {} --initialize-insecure {} {}",
                         mysqld.display(),
                         basedir_arg,
@@ -160,35 +209,52 @@ impl Installer for MysqlInstaller {
             }
         }
 
+        if !ctx.config.db_autoservice {
+            ctx.reporter.info("db_autoservice = false，跳过 Windows 服务注册");
+            ctx.reporter.info(&format!(
+                "前台启动: \"{}\" --defaults-file=\"{}\" --console",
+                mysqld.display(),
+                my_ini.display()
+            ));
+            let port_arg = if port != 3306 { format!(" -P {}", port) } else { String::new() };
+            ctx.reporter.info(&format!("停止: mysqladmin -u root{} shutdown", port_arg));
+            return Ok(());
+        }
+
+        let Some(service) = self.service_name() else {
+            return Ok(());
+        };
+
         // 3. 注册 Windows 服务（需要管理员权限）
-        if !query_service_exists(MYSQL_SERVICE_NAME) {
-            crate::ui::print_action("注册 MySQL Windows 服务...");
+        if !query_service_exists(service) {
+            ctx.reporter.action("注册 MySQL Windows 服务...");
             let mysqld_str = mysqld.to_string_lossy().to_string();
             let defaults_arg = format!("--defaults-file={}", my_ini.display());
 
             // 先直接尝试（hudo 以管理员运行时无需 UAC）
             let _ = std::process::Command::new(&mysqld_str)
-                .args(["--install", MYSQL_SERVICE_NAME, &defaults_arg])
+                .args(["--install", service, &defaults_arg])
                 .status();
 
             // mysqld --install 权限不足时可能返回 0，用 sc query 验证注册是否成功
-            if !query_service_exists(MYSQL_SERVICE_NAME) {
-                crate::ui::print_info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
-                run_as_admin(&mysqld_str, &["--install", MYSQL_SERVICE_NAME, &defaults_arg])?;
+            if !query_service_exists(service) {
+                ctx.reporter.info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
+                run_as_admin(&mysqld_str, &["--install", service, &defaults_arg])?;
 
-                if !query_service_exists(MYSQL_SERVICE_NAME) {
+                if !query_service_exists(service) {
                     anyhow::bail!("MySQL 服务注册失败，请以管理员身份运行 hudo 后重试");
                 }
             }
-            crate::ui::print_success("MySQL 服务注册成功");
+            ctx.reporter.success("MySQL 服务注册成功");
         } else {
-            crate::ui::print_info("MySQL 服务已存在，跳过注册");
+            ctx.reporter.info("MySQL 服务已存在，跳过注册");
         }
 
         // 4. 启动服务
-        match query_service_state(MYSQL_SERVICE_NAME) {
+        let running = match query_service_state(service) {
             ServiceState::Running => {
-                crate::ui::print_success("MySQL 服务已在运行");
+                ctx.reporter.success("MySQL 服务已在运行");
+                true
             }
             ServiceState::Stopped => {
                 // net start 是同步阻塞调用，用 spinner 显示等待状态
@@ -201,9 +267,9 @@ impl Installer for MysqlInstaller {
                 pb.set_message("MySQL 服务启动中...");
                 pb.enable_steady_tick(std::time::Duration::from_millis(100));
 
-                let direct_ok = tokio::task::spawn_blocking(|| {
+                let direct_ok = tokio::task::spawn_blocking(move || {
                     std::process::Command::new("net")
-                        .args(["start", MYSQL_SERVICE_NAME])
+                        .args(["start", service])
                         .status()
                         .map(|s| s.success())
                         .unwrap_or(false)
@@ -214,44 +280,128 @@ impl Installer for MysqlInstaller {
                 pb.finish_and_clear();
 
                 if direct_ok {
-                    crate::ui::print_success("MySQL 服务已启动");
+                    ctx.reporter.success("MySQL 服务已启动");
+                    true
                 } else {
                     // 需要提权，触发 UAC
-                    crate::ui::print_info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
-                    match run_as_admin("net", &["start", MYSQL_SERVICE_NAME]) {
-                        Ok(_) => crate::ui::print_success("MySQL 服务已启动"),
+                    ctx.reporter.info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
+                    match run_as_admin("net", &["start", service]) {
+                        Ok(_) => {
+                            ctx.reporter.success("MySQL 服务已启动");
+                            true
+                        }
                         Err(_) => {
-                            crate::ui::print_warning("MySQL 服务未能自动启动");
-                            crate::ui::print_info("请以管理员身份手动运行: net start MySQL");
+                            ctx.reporter.warning("MySQL 服务未能自动启动");
+                            ctx.reporter.info(&format!("请以管理员身份手动运行: net start {}", service));
+                            false
                         }
                     }
                 }
             }
             ServiceState::NotFound => {
-                crate::ui::print_warning("MySQL 服务未找到，请重新安装");
+                ctx.reporter.warning("MySQL 服务未找到，请重新安装");
                 return Ok(());
             }
+        };
+
+        // 5. 可选：为 root 设置密码（--initialize-insecure 初始化后 root 无密码）
+        let mysql = install_dir.join("bin").join("mysql.exe");
+        let mut with_password = running && !root_has_empty_password(&mysql);
+        if running && !with_password {
+            let set = Confirm::new()
+                .with_prompt("  root 用户当前无密码，是否现在设置？")
+                .default(true)
+                .interact()
+                .unwrap_or(false);
+            with_password = set && set_root_password(ctx, &mysql)?;
+        }
+        // my.ini 的 [client] 段已写入端口，这里显示出来便于其他客户端连接
+        let mut connect = String::from("mysql -u root");
+        if with_password {
+            connect.push_str(" -p");
         }
-        crate::ui::print_info("连接: mysql -u root");
-        crate::ui::print_info("停止: net stop MySQL");
-        crate::ui::print_info("卸载服务: mysqld --remove MySQL（需管理员）");
+        if port != 3306 {
+            connect.push_str(&format!(" -P {}", port));
+        }
+        ctx.reporter.info(&format!("连接: {}", connect));
 
         Ok(())
     }
 }
 
+/// root 是否仍可无密码登录（已设置密码或连接失败都返回 false）
+fn root_has_empty_password(mysql: &Path) -> bool {
+    std::process::Command::new(mysql)
+        .args(["-u", "root", "--skip-password", "-e", "SELECT 1"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// 交互输入新密码并通过 mysql 客户端修改 root 密码，返回是否设置成功。
+/// SQL 经 stdin 传入，密码不出现在命令行参数中，也不写入任何文件
+fn set_root_password(ctx: &InstallContext<'_>, mysql: &Path) -> Result<bool> {
+    let password = Password::new()
+        .with_prompt("  root 密码")
+        .with_confirmation("  再次输入密码", "两次输入的密码不一致")
+        .interact()?;
+    let sql = format!(
+        "ALTER USER 'root'@'localhost' IDENTIFIED BY '{}';\n",
+        escape_sql_string(&password)
+    );
+
+    let child = std::process::Command::new(mysql)
+        .args(["-u", "root", "--skip-password"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn();
+    let output = child.and_then(|mut child| {
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(sql.as_bytes())?;
+        }
+        child.wait_with_output()
+    });
+
+    match output {
+        Ok(out) if out.status.success() => {
+            ctx.reporter.success("root 密码已设置");
+            Ok(true)
+        }
+        Ok(out) => {
+            ctx.reporter.warning(&format!(
+                "设置 root 密码失败: {}",
+                crate::proc::decode_output(&out.stderr).trim()
+            ));
+            ctx.reporter.info("可稍后手动执行: mysql -u root，然后 ALTER USER 'root'@'localhost' IDENTIFIED BY '新密码';");
+            Ok(false)
+        }
+        Err(e) => {
+            ctx.reporter.warning(&format!("无法运行 mysql 客户端: {}", e));
+            Ok(false)
+        }
+    }
+}
+
+/// 转义 SQL 单引号字符串中的反斜杠和单引号
+fn escape_sql_string(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
 /// 生成 my.ini 配置文件
-fn write_my_ini(install_dir: &PathBuf) -> Result<PathBuf> {
+fn write_my_ini(install_dir: &PathBuf, port: u16) -> Result<PathBuf> {
     let my_ini = install_dir.join("my.ini");
-    // MySQL 配置文件中路径使用正斜杠
+    // MySQL 配置文件中路径使用正斜杠，并加双引号（路径可能含空格或 #）
     let basedir = install_dir.to_string_lossy().replace('\\', "/");
     let datadir = install_dir.join("data").to_string_lossy().replace('\\', "/");
 
     let content = format!(
         "[mysqld]\n\
-        basedir={basedir}\n\
-        datadir={datadir}\n\
-        port=3306\n\
+        basedir=\"{basedir}\"\n\
+        datadir=\"{datadir}\"\n\
+        port={port}\n\
         character-set-server=utf8mb4\n\
         collation-server=utf8mb4_unicode_ci\n\
         default-storage-engine=INNODB\n\
@@ -263,16 +413,17 @@ fn write_my_ini(install_dir: &PathBuf) -> Result<PathBuf> {
         \n\
         [client]\n\
         default-character-set=utf8mb4\n\
-        port=3306\n",
+        port={port}\n",
         basedir = basedir,
         datadir = datadir,
+        port = port,
     );
 
     std::fs::write(&my_ini, content)?;
     Ok(my_ini)
 }
 
-use super::{query_service_exists, query_service_state, run_as_admin, ServiceState};
+use super::{ensure_port_free, query_service_exists, query_service_state, run_as_admin, ServiceState};
 
 /// 从 `mysql --version` 输出中提取版本号
 /// "Ver 14.14 Distrib 5.7.44, for Win64" → "5.7.44"
@@ -288,3 +439,32 @@ fn parse_mysql_version(output: &str) -> String {
         .to_string()
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_sql_string() {
+        assert_eq!(escape_sql_string("p@ss"), "p@ss");
+        assert_eq!(escape_sql_string("it's"), "it\\'s");
+        assert_eq!(escape_sql_string("a\\b'"), "a\\\\b\\'");
+    }
+
+    #[test]
+    fn test_write_my_ini_with_spaces_and_cjk() {
+        let install_dir = std::env::temp_dir()
+            .join(format!("hudo-test-张 三-mysql-{}", std::process::id()))
+            .join("mysql");
+        std::fs::create_dir_all(&install_dir).unwrap();
+
+        let my_ini = write_my_ini(&install_dir, 3307).unwrap();
+        let content = std::fs::read_to_string(&my_ini).unwrap();
+        let basedir = install_dir.to_string_lossy().replace('\\', "/");
+        assert!(content.contains(&format!("basedir=\"{}\"\n", basedir)));
+        assert!(content.contains(&format!("datadir=\"{}/data\"\n", basedir)));
+        assert!(content.contains("port=3307"));
+
+        std::fs::remove_dir_all(install_dir.parent().unwrap()).ok();
+    }
+}
@@ -1,8 +1,9 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use super::{CreatedDir, DetectResult, EnvAction, InstallContext, InstallResult, Installer, ShellIntegration, ToolInfo};
+use crate::arch::Arch;
 use crate::config::HudoConfig;
 use crate::download;
 
@@ -17,6 +18,10 @@ impl Installer for NodejsInstaller {
             id: "nodejs",
             name: "Node.js",
             description: "Node.js 运行时 (via fnm)",
+            homepage: Some("https://nodejs.org"),
+            post_install_tips: &[
+                "切换版本: fnm use <版本>",
+            ],
         }
     }
 
@@ -26,7 +31,7 @@ impl Installer for NodejsInstaller {
         if fnm_exe.exists() {
             if let Ok(out) = std::process::Command::new(&fnm_exe).arg("--version").output() {
                 if out.status.success() {
-                    let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                    let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                     return Ok(DetectResult::InstalledByHudo(version));
                 }
             }
@@ -35,13 +40,13 @@ impl Installer for NodejsInstaller {
         // 检查系统 PATH 上的 fnm 或 node
         if let Ok(out) = std::process::Command::new("fnm").arg("--version").output() {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
         if let Ok(out) = std::process::Command::new("node").arg("--version").output() {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -57,17 +62,25 @@ impl Installer for NodejsInstaller {
                     "https://github.com/Schniz/fnm/releases/download/v{}",
                     fnm_version
                 );
-                let base = config.mirrors.fnm.as_deref().unwrap_or(&default_base);
-                let url = format!("{}/{}", base.trim_end_matches('/'), filename);
+                let base = config.expand_mirror(
+                    config.mirrors.fnm.as_deref().unwrap_or(&default_base),
+                    fnm_version,
+                    "nodejs",
+                );
+                let url = format!("{}/{}", base, filename);
                 (url, filename)
             }
             None => {
-                let base = config
-                    .mirrors
-                    .fnm
-                    .as_deref()
-                    .unwrap_or("https://github.com/Schniz/fnm/releases/latest/download");
-                let url = format!("{}/{}", base.trim_end_matches('/'), filename);
+                let base = config.expand_mirror(
+                    config
+                        .mirrors
+                        .fnm
+                        .as_deref()
+                        .unwrap_or("https://github.com/Schniz/fnm/releases/latest/download"),
+                    "latest",
+                    "nodejs",
+                );
+                let url = format!("{}/{}", base, filename);
                 (url, filename)
             }
         }
@@ -79,33 +92,41 @@ impl Installer for NodejsInstaller {
         let node_dir = config.lang_dir().join("node");
         let (url, filename) = self.resolve_download(config);
 
-        // 使用 latest redirect 时删除缓存（版本未知，文件名相同但内容可能变化）
-        if config.versions.fnm.is_none() {
-            let cached = config.cache_dir().join(&filename);
-            if cached.exists() {
-                std::fs::remove_file(&cached).ok();
-            }
-        }
-
-        // 下载 fnm zip
-        let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
+        // 下载 fnm zip（使用 latest redirect 时版本未知，有缓存时重新验证）
+        let zip_path = match config.versions.fnm {
+            Some(_) => download::download(&url, &config.cache_dir(), &filename).await?,
+            None => download::download_latest(&url, &config.cache_dir(), &filename).await?,
+        };
 
         // 解压 fnm.exe 到 tools/fnm/
-        crate::ui::print_action("解压 fnm...");
+        ctx.reporter.action("解压 fnm...");
         std::fs::create_dir_all(&fnm_dir).ok();
         download::extract_zip(&zip_path, &fnm_dir)?;
 
         // 创建 FNM_DIR
         std::fs::create_dir_all(&node_dir).ok();
 
-        // 用 fnm 安装最新 LTS 版 Node.js
-        crate::ui::print_action("通过 fnm 安装 Node.js LTS...");
+        // 用 fnm 安装 Node.js：默认最新 LTS，current 渠道安装最新版
+        // （fnm 只有 x64 版本，默认跟随自身架构，需显式指定）
+        let current = super::active_channel(self, config) == Some("current");
+        let flag = match current {
+            true => "--latest",
+            false => "--lts",
+        };
+        match current {
+            true => ctx.reporter.action("通过 fnm 安装 Node.js 最新版（current）..."),
+            false => ctx.reporter.action("通过 fnm 安装 Node.js LTS..."),
+        }
         let fnm_exe = fnm_dir.join("fnm.exe");
-        let status = std::process::Command::new(&fnm_exe)
-            .args(["install", "--lts"])
-            .env("FNM_DIR", &node_dir)
-            .status()
-            .context("fnm install --lts 失败")?;
+        let status = crate::watchdog::run_watched(
+            std::process::Command::new(&fnm_exe)
+                .args(["install", flag])
+                .env("FNM_DIR", &node_dir)
+                .env("FNM_ARCH", node_arch(ctx.arch())),
+            &format!("fnm install {}", flag),
+            config.install.watch(config.yes),
+        )
+        .with_context(|| format!("fnm install {} 失败", flag))?;
 
         if !status.success() {
             anyhow::bail!(
@@ -114,12 +135,18 @@ impl Installer for NodejsInstaller {
             );
         }
 
-        // 设置默认版本
-        std::process::Command::new(&fnm_exe)
-            .args(["default", "lts-latest"])
-            .env("FNM_DIR", &node_dir)
-            .status()
-            .ok();
+        // 设置默认版本：LTS 用 fnm 自动创建的 lts-latest 别名，current 取已安装的最高版本
+        let default = match current {
+            true => newest_installed(&fnm_exe, &node_dir),
+            false => Some("lts-latest".to_string()),
+        };
+        if let Some(default) = default {
+            std::process::Command::new(&fnm_exe)
+                .args(["default", &default])
+                .env("FNM_DIR", &node_dir)
+                .status()
+                .ok();
+        }
 
         let version = get_fnm_version(&fnm_dir).unwrap_or_else(|| {
             config
@@ -136,9 +163,45 @@ impl Installer for NodejsInstaller {
         })
     }
 
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("versions.fnm 是 fnm 的版本，可用版本见 https://github.com/Schniz/fnm/releases")
+    }
+
+    fn shell_integration(&self, install_path: &Path) -> Option<ShellIntegration> {
+        // 使用 fnm.exe 的绝对路径，PATH 尚未刷新的终端中同样可用
+        Some(ShellIntegration {
+            name: "nodejs",
+            script: format!(
+                "& {} env --use-on-cd --shell power-shell | Out-String | Invoke-Expression",
+                crate::proc::ps_quote(&install_path.join("fnm.exe").to_string_lossy())
+            ),
+            legacy_comment: Some("# fnm (Node.js version manager)"),
+        })
+    }
+
+    fn arm64_native(&self) -> bool {
+        true
+    }
+
+    fn channels(&self) -> &'static [&'static str] {
+        &["lts", "current"]
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        &["fnm.exe"]
+    }
+
+    fn created_dirs(&self, config: &HudoConfig) -> Vec<CreatedDir> {
+        // FNM_DIR：fnm 下载的 Node.js 版本，全局 npm 包也装在各版本目录下
+        vec![CreatedDir {
+            path: config.lang_dir().join("node"),
+            user_data: Some("fnm 安装的 Node.js 版本与全局 npm 包"),
+        }]
+    }
+
     fn env_actions(&self, install_path: &PathBuf, config: &HudoConfig) -> Vec<EnvAction> {
         let node_dir = config.lang_dir().join("node");
-        vec![
+        let mut actions = vec![
             EnvAction::Set {
                 name: "FNM_DIR".to_string(),
                 value: node_dir.to_string_lossy().to_string(),
@@ -146,12 +209,19 @@ impl Installer for NodejsInstaller {
             EnvAction::AppendPath {
                 path: install_path.to_string_lossy().to_string(),
             },
-        ]
+        ];
+        // 之后手动 fnm install 的版本同样使用 ARM64 原生 Node.js
+        if config.arch() == Arch::Arm64 {
+            actions.push(EnvAction::Set {
+                name: "FNM_ARCH".to_string(),
+                value: node_arch(Arch::Arm64).to_string(),
+            });
+        }
+        actions
     }
 
     async fn configure(&self, ctx: &InstallContext<'_>) -> Result<()> {
         let fnm_dir = ctx.config.tools_dir().join("fnm");
-        let fnm_exe = fnm_dir.join("fnm.exe");
 
         // 设置 PowerShell 执行策略，允许 profile 脚本运行
         let policy_status = std::process::Command::new("powershell")
@@ -163,80 +233,85 @@ impl Installer for NodejsInstaller {
             .status();
         match policy_status {
             Ok(s) if s.success() => {
-                crate::ui::print_success("已设置 PowerShell 执行策略 (RemoteSigned)");
+                ctx.reporter.success("已设置 PowerShell 执行策略 (RemoteSigned)");
             }
             _ => {
-                crate::ui::print_warning("设置执行策略失败，如 node 命令不可用，请手动运行：");
-                crate::ui::print_info("  Set-ExecutionPolicy RemoteSigned -Scope CurrentUser");
+                ctx.reporter.warning("设置执行策略失败，如 node 命令不可用，请手动运行：");
+                ctx.reporter.info("  Set-ExecutionPolicy RemoteSigned -Scope CurrentUser");
             }
         }
 
         // 写入 PowerShell profile
-        if let Err(e) = write_powershell_profile(&fnm_exe) {
-            crate::ui::print_warning(&format!("写入 PowerShell profile 失败: {}", e));
-            crate::ui::print_info("请手动在 $PROFILE 中添加：");
-            crate::ui::print_info("  fnm env --use-on-cd --shell power-shell | Out-String | Invoke-Expression");
-        }
+        super::write_shell_integration(ctx, self, &fnm_dir);
 
         Ok(())
     }
 }
 
-/// 将 fnm 初始化行写入 PowerShell profile（幂等，已存在则跳过）
-fn write_powershell_profile(fnm_exe: &std::path::Path) -> Result<()> {
-    // 获取 PowerShell profile 路径
-    let output = std::process::Command::new("powershell")
-        .args(["-NoProfile", "-Command", "$PROFILE"])
+fn get_fnm_version(fnm_dir: &PathBuf) -> Option<String> {
+    let fnm_exe = fnm_dir.join("fnm.exe");
+    std::process::Command::new(fnm_exe)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| crate::proc::decode_output(&o.stdout).trim().to_string())
+}
+
+/// fnm --arch / FNM_ARCH 的取值
+/// fnm list 中版本号最高的 Node.js（如 "v23.6.0"）
+fn newest_installed(fnm_exe: &std::path::Path, node_dir: &std::path::Path) -> Option<String> {
+    let output = std::process::Command::new(fnm_exe)
+        .arg("list")
+        .env("FNM_DIR", node_dir)
         .output()
-        .context("无法获取 PowerShell profile 路径")?;
+        .ok()?;
+    newest_in_list(&crate::proc::decode_output(&output.stdout))
+}
 
-    let profile_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    if profile_path.is_empty() {
-        anyhow::bail!("PowerShell $PROFILE 路径为空");
-    }
-    let profile_path = std::path::Path::new(&profile_path);
+/// "* v20.11.0 default, lts-latest\n* v23.6.0\n* system" → "v23.6.0"
+fn newest_in_list(output: &str) -> Option<String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().trim_start_matches('*').split_whitespace().next())
+        .filter_map(|ver| {
+            let parts: Vec<u32> = ver.strip_prefix('v')?.split('.').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+            Some((parts, ver))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, ver)| ver.to_string())
+}
 
-    // 确保 profile 目录存在
-    if let Some(parent) = profile_path.parent() {
-        std::fs::create_dir_all(parent).ok();
+fn node_arch(arch: Arch) -> &'static str {
+    match arch {
+        Arch::X64 => "x64",
+        Arch::Arm64 => "arm64",
     }
+}
 
-    // fnm 初始化行，使用 fnm.exe 的绝对路径确保可用
-    let init_line = format!(
-        "# fnm (Node.js version manager)\r\n& '{}' env --use-on-cd --shell power-shell | Out-String | Invoke-Expression",
-        fnm_exe.display()
-    );
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // 读取现有 profile 内容，已存在则跳过
-    let existing = std::fs::read_to_string(profile_path).unwrap_or_default();
-    if existing.contains("fnm env") {
-        crate::ui::print_info("PowerShell profile 已包含 fnm 初始化，跳过");
-        return Ok(());
+    #[test]
+    fn test_newest_in_list() {
+        let output = "* v9.11.2\n* v20.11.0 default, lts-latest\n* v23.6.0\n* system\n";
+        assert_eq!(newest_in_list(output).as_deref(), Some("v23.6.0"));
+        assert_eq!(newest_in_list("* system\n"), None);
     }
 
-    // 追加写入
-    use std::io::Write;
-    let mut file = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(profile_path)
-        .context("打开 PowerShell profile 失败")?;
-
-    if !existing.is_empty() && !existing.ends_with('\n') {
-        writeln!(file)?;
+    #[test]
+    fn test_node_arch() {
+        assert_eq!(node_arch(Arch::X64), "x64");
+        assert_eq!(node_arch(Arch::Arm64), "arm64");
     }
-    writeln!(file, "\r\n{}", init_line)?;
-
-    crate::ui::print_success("已写入 PowerShell profile，重开终端后 node 命令即可使用");
-    Ok(())
-}
 
-fn get_fnm_version(fnm_dir: &PathBuf) -> Option<String> {
-    let fnm_exe = fnm_dir.join("fnm.exe");
-    std::process::Command::new(fnm_exe)
-        .arg("--version")
-        .output()
-        .ok()
-        .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    #[test]
+    fn test_shell_integration_quotes_path() {
+        let integration = NodejsInstaller.shell_integration(Path::new(r"C:\Users\张 三\hudo tools\fnm")).unwrap();
+        assert_eq!(
+            integration.script,
+            r"& 'C:\Users\张 三\hudo tools\fnm\fnm.exe' env --use-on-cd --shell power-shell | Out-String | Invoke-Expression"
+        );
+    }
 }
@@ -1,18 +1,18 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
-use std::path::PathBuf;
+use dialoguer::{Input, Password};
+use std::path::{Path, PathBuf};
 
 use super::{
-    query_service_exists, query_service_state, run_as_admin, DetectResult, EnvAction,
+    ensure_port_free, query_service_exists, query_service_state, run_as_admin, DetectResult, EnvAction,
     InstallContext, InstallResult, Installer, ServiceState, ToolInfo,
 };
-use crate::config::HudoConfig;
+use crate::config::{default_pgsql_port, HudoConfig};
 use crate::download;
 
 pub struct PgsqlInstaller;
 
 const PG_VERSION_DEFAULT: &str = "17.8";
-const PG_SERVICE_NAME: &str = "PostgreSQL";
 
 #[async_trait]
 impl Installer for PgsqlInstaller {
@@ -21,6 +21,11 @@ impl Installer for PgsqlInstaller {
             id: "pgsql",
             name: "PostgreSQL",
             description: "PostgreSQL 数据库",
+            homepage: Some("https://www.postgresql.org"),
+            post_install_tips: &[
+                "启动 / 停止: hudo service pgsql start|stop",
+                "卸载服务: pg_ctl unregister -N PostgreSQL（需管理员）",
+            ],
         }
     }
 
@@ -29,7 +34,7 @@ impl Installer for PgsqlInstaller {
         if psql_exe.exists() {
             if let Ok(out) = std::process::Command::new(&psql_exe).arg("--version").output() {
                 if out.status.success() {
-                    let version = parse_pgsql_version(&String::from_utf8_lossy(&out.stdout));
+                    let version = parse_pgsql_version(&crate::proc::decode_output(&out.stdout));
                     return Ok(DetectResult::InstalledByHudo(version));
                 }
             }
@@ -37,7 +42,7 @@ impl Installer for PgsqlInstaller {
 
         if let Ok(out) = std::process::Command::new("psql").arg("--version").output() {
             if out.status.success() {
-                let version = parse_pgsql_version(&String::from_utf8_lossy(&out.stdout));
+                let version = parse_pgsql_version(&crate::proc::decode_output(&out.stdout));
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -48,15 +53,23 @@ impl Installer for PgsqlInstaller {
     fn resolve_download(&self, config: &HudoConfig) -> (String, String) {
         let version = config.versions.pgsql.as_deref().unwrap_or(PG_VERSION_DEFAULT);
         let filename = format!("postgresql-{}-1-windows-x64-binaries.zip", version);
-        let base = config
-            .mirrors
-            .pgsql
-            .as_deref()
-            .unwrap_or("https://get.enterprisedb.com/postgresql");
-        let url = format!("{}/{}", base.trim_end_matches('/'), filename);
+        let base = config.expand_mirror(
+            config.mirrors.pgsql.as_deref().unwrap_or("https://get.enterprisedb.com/postgresql"),
+            version,
+            "pgsql",
+        );
+        let url = format!("{}/{}", base, filename);
         (url, filename)
     }
 
+    fn default_version(&self) -> Option<&'static str> {
+        Some(PG_VERSION_DEFAULT)
+    }
+
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("可用版本见 https://www.enterprisedb.com/download-postgresql-binaries")
+    }
+
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
         let config = ctx.config;
         let install_dir = config.tools_dir().join("pgsql");
@@ -64,42 +77,32 @@ impl Installer for PgsqlInstaller {
         let version = match &config.versions.pgsql {
             Some(v) => v.clone(),
             None => {
-                crate::ui::print_action("查询 PostgreSQL 最新版本...");
-                crate::version::pgsql_latest()
-                    .await
-                    .unwrap_or_else(|| PG_VERSION_DEFAULT.to_string())
+                ctx.reporter.action("查询 PostgreSQL 最新版本...");
+                crate::version::or_default(ctx.reporter, crate::version::pgsql_latest().await, PG_VERSION_DEFAULT)
             }
         };
 
         let filename = format!("postgresql-{}-1-windows-x64-binaries.zip", version);
-        let base = config
-            .mirrors
-            .pgsql
-            .as_deref()
-            .unwrap_or("https://get.enterprisedb.com/postgresql");
-        let url = format!("{}/{}", base.trim_end_matches('/'), filename);
-
-        let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
-
-        crate::ui::print_action("解压 PostgreSQL...");
-        let tmp_dir = config.cache_dir().join("pgsql-extract");
-        if tmp_dir.exists() {
-            std::fs::remove_dir_all(&tmp_dir).ok();
-        }
-        download::extract_zip(&zip_path, &tmp_dir)?;
-
-        // zip 内有 pgsql/ 子目录
-        let inner = tmp_dir.join("pgsql");
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir).ok();
-        }
-        if inner.exists() {
-            std::fs::rename(&inner, &install_dir).ok();
-        } else {
-            let sub = download::find_single_subdir(&tmp_dir).unwrap_or(tmp_dir.clone());
-            std::fs::rename(&sub, &install_dir).ok();
-        }
-        std::fs::remove_dir_all(&tmp_dir).ok();
+        let base = config.expand_mirror(
+            config.mirrors.pgsql.as_deref().unwrap_or("https://get.enterprisedb.com/postgresql"),
+            &version,
+            "pgsql",
+        );
+        let url = format!("{}/{}", base, filename);
+
+        download::install_zip(
+            &url,
+            &filename,
+            &config.cache_dir(),
+            &install_dir,
+            &download::ZipInstall {
+                name: "PostgreSQL",
+                reporter: Some(ctx.reporter),
+                subdir: Some("pgsql"),
+                ..Default::default()
+            },
+        )
+        .await?;
 
         Ok(InstallResult {
             install_path: install_dir,
@@ -107,6 +110,18 @@ impl Installer for PgsqlInstaller {
         })
     }
 
+    fn binaries(&self) -> &'static [&'static str] {
+        &["bin\\psql.exe"]
+    }
+
+    fn user_data_dirs(&self, install_path: &Path) -> Vec<PathBuf> {
+        vec![install_path.join("data")]
+    }
+
+    fn service_name(&self) -> Option<&'static str> {
+        Some("PostgreSQL")
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![EnvAction::AppendPath {
             path: install_path.join("bin").to_string_lossy().to_string(),
@@ -119,6 +134,11 @@ impl Installer for PgsqlInstaller {
         let pg_ctl = install_dir.join("bin").join("pg_ctl.exe");
         let data_dir = install_dir.join("data");
 
+        // hudo 的 PostgreSQL 服务正在运行时端口就是它占用的，不用检查
+        let own_running = self
+            .service_name()
+            .is_some_and(|s| matches!(query_service_state(s), ServiceState::Running));
+
         // 1. 初始化数据目录（无需管理员权限）
         let is_data_empty = data_dir
             .read_dir()
@@ -126,59 +146,109 @@ impl Installer for PgsqlInstaller {
             .unwrap_or(true);
 
         if is_data_empty {
-            crate::ui::print_action("初始化 PostgreSQL 数据目录...");
-            let status = std::process::Command::new(&initdb)
-                .args([
-                    "-D",
-                    &data_dir.to_string_lossy(),
-                    "-U",
-                    "postgres",
-                    "-E",
-                    "UTF8",
-                    "--no-locale",
-                ])
-                .status();
+            let password = Password::new()
+                .with_prompt("  postgres 超级用户密码（留空则本机连接无需密码）")
+                .with_confirmation("  再次输入密码", "两次输入的密码不一致")
+                .allow_empty_password(true)
+                .interact()?;
+            let port: u16 = Input::new()
+                .with_prompt("  监听端口")
+                .default(ctx.config.pgsql.port)
+                .interact_text()?;
+            let port = ensure_port_free(ctx, "PostgreSQL", port, &install_dir)?;
+
+            ctx.reporter.action("初始化 PostgreSQL 数据目录...");
+            let data_str = data_dir.to_string_lossy().to_string();
+            let mut args = vec!["-D", &data_str, "-U", "postgres", "-E", "UTF8", "--no-locale"];
+
+            // 密码经临时 pwfile 传给 initdb，初始化后立即删除；设置了密码才启用密码认证
+            let pwfile = ctx.config.cache_dir().join("pgsql-pwfile");
+            let pwfile_arg = format!("--pwfile={}", pwfile.display());
+            if !password.is_empty() {
+                std::fs::create_dir_all(ctx.config.cache_dir()).ok();
+                std::fs::write(&pwfile, &password).context("写入临时密码文件失败")?;
+                args.extend([pwfile_arg.as_str(), "--auth=scram-sha-256"]);
+            }
+            let status = crate::watchdog::run_watched(
+                std::process::Command::new(&initdb).args(&args),
+                "initdb",
+                ctx.config.install.watch(ctx.config.yes),
+            );
+            std::fs::remove_file(&pwfile).ok();
 
             match status {
                 Ok(s) if s.success() => {
-                    crate::ui::print_success("数据目录初始化完成");
+                    ctx.reporter.success("数据目录初始化完成");
                 }
+                Err(e) if crate::watchdog::is_aborted(&e) => return Err(e),
                 _ => {
-                    crate::ui::print_warning("PostgreSQL 初始化失败，请手动执行: initdb -D <data_dir>");
+                    ctx.reporter.warning("PostgreSQL 初始化失败，请手动执行: initdb -D <data_dir>");
                     return Ok(());
                 }
             }
+
+            if port != default_pgsql_port() {
+                set_port(&data_dir, port)?;
+                ctx.reporter.info(&format!("监听端口: {}", port));
+            }
+            remember_port(ctx, port)?;
+        } else {
+            // 已有数据目录：按配置中的 pgsql.port 同步 postgresql.conf，服务未运行时先确认端口可用
+            let mut port = ctx.config.pgsql.port;
+            if !own_running {
+                port = ensure_port_free(ctx, "PostgreSQL", port, &install_dir)?;
+                remember_port(ctx, port)?;
+            }
+            if read_port(&data_dir).unwrap_or(default_pgsql_port()) != port {
+                set_port(&data_dir, port)?;
+                ctx.reporter.info(&format!("监听端口已改为 {}（重启服务后生效）", port));
+            }
         }
 
+        if !ctx.config.db_autoservice {
+            ctx.reporter.info("db_autoservice = false，跳过 Windows 服务注册");
+            ctx.reporter.info(&format!(
+                "启动: pg_ctl -D \"{}\" -l \"{}\" start",
+                data_dir.display(),
+                data_dir.join("postgresql.log").display()
+            ));
+            ctx.reporter.info(&format!("停止: pg_ctl -D \"{}\" stop", data_dir.display()));
+            return Ok(());
+        }
+
+        let Some(service) = self.service_name() else {
+            return Ok(());
+        };
+
         // 2. 注册 Windows 服务（需要管理员权限）
-        if !query_service_exists(PG_SERVICE_NAME) {
-            crate::ui::print_action("注册 PostgreSQL Windows 服务...");
+        if !query_service_exists(service) {
+            ctx.reporter.action("注册 PostgreSQL Windows 服务...");
             let pg_ctl_str = pg_ctl.to_string_lossy().to_string();
             let data_str = data_dir.to_string_lossy().to_string();
 
             // 先直接尝试（hudo 以管理员运行时无需 UAC）
             let _ = std::process::Command::new(&pg_ctl_str)
-                .args(["register", "-N", PG_SERVICE_NAME, "-D", &data_str])
+                .args(["register", "-N", service, "-D", &data_str])
                 .status();
 
             // pg_ctl register 权限不足时可能返回 0，用 sc query 验证
-            if !query_service_exists(PG_SERVICE_NAME) {
-                crate::ui::print_info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
-                run_as_admin(&pg_ctl_str, &["register", "-N", PG_SERVICE_NAME, "-D", &data_str])?;
+            if !query_service_exists(service) {
+                ctx.reporter.info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
+                run_as_admin(&pg_ctl_str, &["register", "-N", service, "-D", &data_str])?;
 
-                if !query_service_exists(PG_SERVICE_NAME) {
+                if !query_service_exists(service) {
                     anyhow::bail!("PostgreSQL 服务注册失败，请以管理员身份运行 hudo 后重试");
                 }
             }
-            crate::ui::print_success("PostgreSQL 服务注册成功");
+            ctx.reporter.success("PostgreSQL 服务注册成功");
         } else {
-            crate::ui::print_info("PostgreSQL 服务已存在，跳过注册");
+            ctx.reporter.info("PostgreSQL 服务已存在，跳过注册");
         }
 
         // 3. 启动服务
-        match query_service_state(PG_SERVICE_NAME) {
+        match query_service_state(service) {
             ServiceState::Running => {
-                crate::ui::print_success("PostgreSQL 服务已在运行");
+                ctx.reporter.success("PostgreSQL 服务已在运行");
             }
             ServiceState::Stopped => {
                 let pb = indicatif::ProgressBar::new_spinner();
@@ -190,9 +260,9 @@ impl Installer for PgsqlInstaller {
                 pb.set_message("PostgreSQL 服务启动中...");
                 pb.enable_steady_tick(std::time::Duration::from_millis(100));
 
-                let direct_ok = tokio::task::spawn_blocking(|| {
+                let direct_ok = tokio::task::spawn_blocking(move || {
                     std::process::Command::new("net")
-                        .args(["start", PG_SERVICE_NAME])
+                        .args(["start", service])
                         .status()
                         .map(|s| s.success())
                         .unwrap_or(false)
@@ -203,32 +273,43 @@ impl Installer for PgsqlInstaller {
                 pb.finish_and_clear();
 
                 if direct_ok {
-                    crate::ui::print_success("PostgreSQL 服务已启动");
+                    ctx.reporter.success("PostgreSQL 服务已启动");
                 } else {
-                    crate::ui::print_info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
-                    match run_as_admin("net", &["start", PG_SERVICE_NAME]) {
-                        Ok(_) => crate::ui::print_success("PostgreSQL 服务已启动"),
+                    ctx.reporter.info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
+                    match run_as_admin("net", &["start", service]) {
+                        Ok(_) => ctx.reporter.success("PostgreSQL 服务已启动"),
                         Err(_) => {
-                            crate::ui::print_warning("PostgreSQL 服务未能自动启动");
-                            crate::ui::print_info("请以管理员身份手动运行: net start PostgreSQL");
+                            ctx.reporter.warning("PostgreSQL 服务未能自动启动");
+                            ctx.reporter.info(&format!("请以管理员身份手动运行: net start {}", service));
                         }
                     }
                 }
             }
             ServiceState::NotFound => {
-                crate::ui::print_warning("PostgreSQL 服务未找到，请重新安装");
+                ctx.reporter.warning("PostgreSQL 服务未找到，请重新安装");
                 return Ok(());
             }
         }
 
-        crate::ui::print_info("连接: psql -U postgres");
-        crate::ui::print_info("停止: net stop PostgreSQL");
-        crate::ui::print_info("卸载服务: pg_ctl unregister -N PostgreSQL（需管理员）");
+        match read_port(&data_dir) {
+            Some(port) if port != default_pgsql_port() => {
+                ctx.reporter.info(&format!("连接: psql -U postgres -p {}", port))
+            }
+            _ => ctx.reporter.info("连接: psql -U postgres"),
+        }
 
         Ok(())
     }
 
     async fn pre_uninstall(&self, ctx: &InstallContext<'_>) -> Result<()> {
+        let Some(service) = self.service_name() else {
+            return Ok(());
+        };
+        // 未注册服务（如 db_autoservice = false）时无需停止和移除
+        if !query_service_exists(service) {
+            return Ok(());
+        }
+
         let pg_ctl = ctx
             .config
             .tools_dir()
@@ -237,16 +318,68 @@ impl Installer for PgsqlInstaller {
             .join("pg_ctl.exe");
         let pg_ctl_str = pg_ctl.to_string_lossy().to_string();
 
-        crate::ui::print_action("停止 PostgreSQL 服务...");
-        let _ = run_as_admin("net", &["stop", PG_SERVICE_NAME]);
+        ctx.reporter.action("停止 PostgreSQL 服务...");
+        let _ = run_as_admin("net", &["stop", service]);
 
-        crate::ui::print_action("移除 PostgreSQL 服务注册...");
-        let _ = run_as_admin(&pg_ctl_str, &["unregister", "-N", PG_SERVICE_NAME]);
+        ctx.reporter.action("移除 PostgreSQL 服务注册...");
+        let _ = run_as_admin(&pg_ctl_str, &["unregister", "-N", service]);
 
         Ok(())
     }
 }
 
+/// 把端口写入 data 目录下的 postgresql.conf
+/// 记入 pgsql.port，避免之后重新配置时被改回；重新读取配置文件，不保存本次运行的临时覆盖
+fn remember_port(ctx: &InstallContext<'_>, port: u16) -> Result<()> {
+    if port != ctx.config.pgsql.port {
+        if let Some(mut saved) = HudoConfig::load()? {
+            saved.pgsql.port = port;
+            saved.save()?;
+        }
+    }
+    Ok(())
+}
+
+fn set_port(data_dir: &Path, port: u16) -> Result<()> {
+    let conf = data_dir.join("postgresql.conf");
+    let content = std::fs::read_to_string(&conf)
+        .with_context(|| format!("无法读取 {}", conf.display()))?;
+    std::fs::write(&conf, replace_port(&content, port))
+        .with_context(|| format!("无法写入 {}", conf.display()))
+}
+
+/// postgresql.conf 中设置的端口，未设置或读取失败时返回 None
+fn read_port(data_dir: &Path) -> Option<u16> {
+    let content = std::fs::read_to_string(data_dir.join("postgresql.conf")).ok()?;
+    content.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("port")?.trim_start().strip_prefix('=')?;
+        value.split('#').next()?.trim().parse().ok()
+    })
+}
+
+/// 替换（或取消注释）postgresql.conf 中的 port 行，没有则追加
+fn replace_port(content: &str, port: u16) -> String {
+    let line = format!("port = {}", port);
+    let mut replaced = false;
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|l| {
+            let key = l.trim_start().trim_start_matches('#').trim_start();
+            let is_port = key.strip_prefix("port").is_some_and(|r| r.trim_start().starts_with('='));
+            if is_port && !replaced {
+                replaced = true;
+                line.clone()
+            } else {
+                l.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(line);
+    }
+    lines.join("\n") + "\n"
+}
+
 /// 从 `psql --version` 输出中提取版本号
 /// "psql (PostgreSQL) 17.8" → "17.8"
 fn parse_pgsql_version(output: &str) -> String {
@@ -257,3 +390,21 @@ fn parse_pgsql_version(output: &str) -> String {
         .unwrap_or("已安装")
         .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_port() {
+        let conf = "listen_addresses = 'localhost'\n#port = 5432\t\t# (change requires restart)\nmax_connections = 100\n";
+        assert_eq!(
+            replace_port(conf, 5433),
+            "listen_addresses = 'localhost'\nport = 5433\nmax_connections = 100\n"
+        );
+        // 已有 port 行时替换，不产生重复
+        assert_eq!(replace_port("port = 5433\n", 5434), "port = 5434\n");
+        // 没有 port 行时追加；port_xxx 之类的键不受影响
+        assert_eq!(replace_port("portal = 1\n", 5433), "portal = 1\nport = 5433\n");
+    }
+}
@@ -1,8 +1,8 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ShortcutSpec, ToolInfo};
 use crate::config::HudoConfig;
 use crate::download;
 
@@ -17,6 +17,8 @@ impl Installer for PycharmInstaller {
             id: "pycharm",
             name: "PyCharm",
             description: "PyCharm Community IDE",
+            homepage: Some("https://www.jetbrains.com/pycharm/"),
+            post_install_tips: &[],
         }
     }
 
@@ -48,16 +50,27 @@ impl Installer for PycharmInstaller {
 
     fn resolve_download(&self, config: &HudoConfig) -> (String, String) {
         let version = config.versions.pycharm.as_deref().unwrap_or(PYCHARM_VERSION_DEFAULT);
-        let base = config.mirrors.pycharm.as_deref()
-            .unwrap_or("https://download.jetbrains.com");
+        let base = config.expand_mirror(
+            config.mirrors.pycharm.as_deref().unwrap_or("https://download.jetbrains.com"),
+            version,
+            "pycharm",
+        );
         let url = format!(
             "{}/python/pycharm-community-{}.win.zip",
-            base.trim_end_matches('/'),
+            base,
             version
         );
         (url, "pycharm-community.zip".to_string())
     }
 
+    fn default_version(&self) -> Option<&'static str> {
+        Some(PYCHARM_VERSION_DEFAULT)
+    }
+
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("可用版本见 https://www.jetbrains.com/pycharm/download/other.html")
+    }
+
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
         let config = ctx.config;
         let install_dir = config.ide_dir().join("pycharm");
@@ -66,43 +79,36 @@ impl Installer for PycharmInstaller {
         let version = match &config.versions.pycharm {
             Some(v) => v.clone(),
             None => {
-                crate::ui::print_action("查询 PyCharm 最新版本...");
-                crate::version::pycharm_latest()
-                    .await
-                    .unwrap_or_else(|| PYCHARM_VERSION_DEFAULT.to_string())
+                ctx.reporter.action("查询 PyCharm 最新版本...");
+                crate::version::or_default(ctx.reporter, crate::version::pycharm_latest().await, PYCHARM_VERSION_DEFAULT)
             }
         };
 
-        let base = config
-            .mirrors
-            .pycharm
-            .as_deref()
-            .unwrap_or("https://download.jetbrains.com");
+        let base = config.expand_mirror(
+            config.mirrors.pycharm.as_deref().unwrap_or("https://download.jetbrains.com"),
+            &version,
+            "pycharm",
+        );
         let url = format!(
             "{}/python/pycharm-community-{}.win.zip",
-            base.trim_end_matches('/'),
+            base,
             version
         );
         let filename = "pycharm-community.zip".to_string();
 
-        let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
-
-        crate::ui::print_action("解压 PyCharm Community...");
         // zip 内有版本号子目录如 pycharm-community-2024.3.5/
-        let tmp_dir = config.cache_dir().join("pycharm-extract");
-        if tmp_dir.exists() {
-            std::fs::remove_dir_all(&tmp_dir).ok();
-        }
-        download::extract_zip(&zip_path, &tmp_dir)?;
-
-        // 找到解压出的子目录
-        let inner = download::find_single_subdir(&tmp_dir).unwrap_or(tmp_dir.clone());
-
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir).ok();
-        }
-        std::fs::rename(&inner, &install_dir).ok();
-        std::fs::remove_dir_all(&tmp_dir).ok();
+        download::install_zip(
+            &url,
+            &filename,
+            &config.cache_dir(),
+            &install_dir,
+            &download::ZipInstall {
+                name: "PyCharm Community",
+                reporter: Some(ctx.reporter),
+                ..Default::default()
+            },
+        )
+        .await?;
 
         Ok(InstallResult {
             install_path: install_dir,
@@ -110,6 +116,14 @@ impl Installer for PycharmInstaller {
         })
     }
 
+    fn binaries(&self) -> &'static [&'static str] {
+        &["bin\\pycharm64.exe"]
+    }
+
+    fn shortcuts(&self, install_path: &Path) -> Vec<ShortcutSpec> {
+        vec![ShortcutSpec::new("PyCharm Community Edition", install_path.join("bin").join("pycharm64.exe"))]
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![EnvAction::AppendPath {
             path: install_path.join("bin").to_string_lossy().to_string(),
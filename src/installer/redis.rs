@@ -12,7 +12,6 @@ use crate::download;
 pub struct RedisInstaller;
 
 const REDIS_VERSION_DEFAULT: &str = "8.6.1";
-const REDIS_SERVICE_NAME: &str = "Redis";
 
 #[async_trait]
 impl Installer for RedisInstaller {
@@ -21,6 +20,11 @@ impl Installer for RedisInstaller {
             id: "redis",
             name: "Redis",
             description: "Redis 内存数据库",
+            homepage: Some("https://github.com/redis-windows/redis-windows"),
+            post_install_tips: &[
+                "连接: redis-cli",
+                "启动 / 停止: hudo service redis start|stop",
+            ],
         }
     }
 
@@ -33,7 +37,7 @@ impl Installer for RedisInstaller {
         if redis_exe.exists() {
             if let Ok(out) = std::process::Command::new(&redis_exe).arg("--version").output() {
                 if out.status.success() {
-                    let version = parse_redis_version(&String::from_utf8_lossy(&out.stdout));
+                    let version = parse_redis_version(&crate::proc::decode_output(&out.stdout));
                     return Ok(DetectResult::InstalledByHudo(version));
                 }
             }
@@ -44,7 +48,7 @@ impl Installer for RedisInstaller {
             .output()
         {
             if out.status.success() {
-                let version = parse_redis_version(&String::from_utf8_lossy(&out.stdout));
+                let version = parse_redis_version(&crate::proc::decode_output(&out.stdout));
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -62,18 +66,30 @@ impl Installer for RedisInstaller {
             "Redis-{}-Windows-x64-msys2-with-Service.zip",
             version
         );
-        let base = config.mirrors.redis.as_deref().unwrap_or(
-            "https://github.com/redis-windows/redis-windows/releases/download",
+        let base = config.expand_mirror(
+            config.mirrors.redis.as_deref().unwrap_or(
+                "https://github.com/redis-windows/redis-windows/releases/download",
+            ),
+            version,
+            "redis",
         );
         let url = format!(
             "{}/{}.1/{}",
-            base.trim_end_matches('/'),
+            base,
             version,
             filename
         );
         (url, filename)
     }
 
+    fn default_version(&self) -> Option<&'static str> {
+        Some(REDIS_VERSION_DEFAULT)
+    }
+
+    fn versions_hint(&self) -> Option<&'static str> {
+        Some("可用版本见 https://github.com/redis-windows/redis-windows/releases")
+    }
+
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
         let config = ctx.config;
         let install_dir = config.tools_dir().join("redis");
@@ -81,10 +97,8 @@ impl Installer for RedisInstaller {
         let version = match &config.versions.redis {
             Some(v) => v.clone(),
             None => {
-                crate::ui::print_action("查询 Redis 最新版本...");
-                crate::version::redis_latest()
-                    .await
-                    .unwrap_or_else(|| REDIS_VERSION_DEFAULT.to_string())
+                ctx.reporter.action("查询 Redis 最新版本...");
+                crate::version::or_default(ctx.reporter, crate::version::redis_latest().await, REDIS_VERSION_DEFAULT)
             }
         };
 
@@ -92,19 +106,23 @@ impl Installer for RedisInstaller {
             "Redis-{}-Windows-x64-msys2-with-Service.zip",
             version
         );
-        let base = config.mirrors.redis.as_deref().unwrap_or(
-            "https://github.com/redis-windows/redis-windows/releases/download",
+        let base = config.expand_mirror(
+            config.mirrors.redis.as_deref().unwrap_or(
+                "https://github.com/redis-windows/redis-windows/releases/download",
+            ),
+            &version,
+            "redis",
         );
         let url = format!(
             "{}/{}.1/{}",
-            base.trim_end_matches('/'),
+            base,
             version,
             filename
         );
 
         let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
 
-        crate::ui::print_action("解压 Redis...");
+        ctx.reporter.action("解压 Redis...");
         let tmp_dir = config.cache_dir().join("redis-extract");
         if tmp_dir.exists() {
             std::fs::remove_dir_all(&tmp_dir).ok();
@@ -112,10 +130,7 @@ impl Installer for RedisInstaller {
         download::extract_zip(&zip_path, &tmp_dir)?;
 
         let inner = download::find_single_subdir(&tmp_dir).unwrap_or(tmp_dir.clone());
-        if install_dir.exists() {
-            std::fs::remove_dir_all(&install_dir).ok();
-        }
-        std::fs::rename(&inner, &install_dir).ok();
+        download::swap_dir(&inner, &install_dir)?;
         std::fs::remove_dir_all(&tmp_dir).ok();
 
         Ok(InstallResult {
@@ -124,6 +139,14 @@ impl Installer for RedisInstaller {
         })
     }
 
+    fn binaries(&self) -> &'static [&'static str] {
+        &["redis-server.exe"]
+    }
+
+    fn service_name(&self) -> Option<&'static str> {
+        Some("Redis")
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![EnvAction::AppendPath {
             path: install_path.to_string_lossy().to_string(),
@@ -134,20 +157,31 @@ impl Installer for RedisInstaller {
         let install_dir = ctx.config.tools_dir().join("redis");
 
         // 1. 生成 redis.conf
-        crate::ui::print_action("生成 redis.conf...");
+        ctx.reporter.action("生成 redis.conf...");
         let conf_path = write_redis_conf(&install_dir)?;
-        crate::ui::print_info(&format!("配置文件: {}", conf_path.display()));
+        ctx.reporter.info(&format!("配置文件: {}", conf_path.display()));
+
+        if !ctx.config.db_autoservice {
+            ctx.reporter.info("db_autoservice = false，跳过 Windows 服务注册");
+            ctx.reporter.info(&format!("前台启动: redis-server \"{}\"", conf_path.display()));
+            ctx.reporter.info("停止: redis-cli shutdown");
+            return Ok(());
+        }
 
         // 2. 注册 Windows 服务（with-Service 版自带 RedisService.exe）
         let service_exe = install_dir.join("RedisService.exe");
         if !service_exe.exists() {
-            crate::ui::print_warning("未找到 RedisService.exe，跳过服务注册");
-            crate::ui::print_info("可手动启动: redis-server redis.conf");
+            ctx.reporter.warning("未找到 RedisService.exe，跳过服务注册");
+            ctx.reporter.info("可手动启动: redis-server redis.conf");
             return Ok(());
         }
 
-        if !query_service_exists(REDIS_SERVICE_NAME) {
-            crate::ui::print_action("注册 Redis Windows 服务...");
+        let Some(service) = self.service_name() else {
+            return Ok(());
+        };
+
+        if !query_service_exists(service) {
+            ctx.reporter.action("注册 Redis Windows 服务...");
             let service_str = service_exe.to_string_lossy().to_string();
 
             // 先直接尝试
@@ -155,23 +189,23 @@ impl Installer for RedisInstaller {
                 .arg("install")
                 .status();
 
-            if !query_service_exists(REDIS_SERVICE_NAME) {
-                crate::ui::print_info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
+            if !query_service_exists(service) {
+                ctx.reporter.info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
                 run_as_admin(&service_str, &["install"])?;
 
-                if !query_service_exists(REDIS_SERVICE_NAME) {
+                if !query_service_exists(service) {
                     anyhow::bail!("Redis 服务注册失败，请以管理员身份运行 hudo 后重试");
                 }
             }
-            crate::ui::print_success("Redis 服务注册成功");
+            ctx.reporter.success("Redis 服务注册成功");
         } else {
-            crate::ui::print_info("Redis 服务已存在，跳过注册");
+            ctx.reporter.info("Redis 服务已存在，跳过注册");
         }
 
         // 3. 启动服务
-        match query_service_state(REDIS_SERVICE_NAME) {
+        match query_service_state(service) {
             ServiceState::Running => {
-                crate::ui::print_success("Redis 服务已在运行");
+                ctx.reporter.success("Redis 服务已在运行");
             }
             ServiceState::Stopped => {
                 let pb = indicatif::ProgressBar::new_spinner();
@@ -183,9 +217,9 @@ impl Installer for RedisInstaller {
                 pb.set_message("Redis 服务启动中...");
                 pb.enable_steady_tick(std::time::Duration::from_millis(100));
 
-                let direct_ok = tokio::task::spawn_blocking(|| {
+                let direct_ok = tokio::task::spawn_blocking(move || {
                     std::process::Command::new("net")
-                        .args(["start", REDIS_SERVICE_NAME])
+                        .args(["start", service])
                         .status()
                         .map(|s| s.success())
                         .unwrap_or(false)
@@ -196,39 +230,44 @@ impl Installer for RedisInstaller {
                 pb.finish_and_clear();
 
                 if direct_ok {
-                    crate::ui::print_success("Redis 服务已启动");
+                    ctx.reporter.success("Redis 服务已启动");
                 } else {
-                    crate::ui::print_info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
-                    match run_as_admin("net", &["start", REDIS_SERVICE_NAME]) {
-                        Ok(_) => crate::ui::print_success("Redis 服务已启动"),
+                    ctx.reporter.info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
+                    match run_as_admin("net", &["start", service]) {
+                        Ok(_) => ctx.reporter.success("Redis 服务已启动"),
                         Err(_) => {
-                            crate::ui::print_warning("Redis 服务未能自动启动");
-                            crate::ui::print_info("请以管理员身份手动运行: net start Redis");
+                            ctx.reporter.warning("Redis 服务未能自动启动");
+                            ctx.reporter.info(&format!("请以管理员身份手动运行: net start {}", service));
                         }
                     }
                 }
             }
             ServiceState::NotFound => {
-                crate::ui::print_warning("Redis 服务未找到，请重新安装");
+                ctx.reporter.warning("Redis 服务未找到，请重新安装");
                 return Ok(());
             }
         }
 
-        crate::ui::print_info("连接: redis-cli");
-        crate::ui::print_info("停止: net stop Redis");
-
         Ok(())
     }
 
     async fn pre_uninstall(&self, ctx: &InstallContext<'_>) -> Result<()> {
+        let Some(service) = self.service_name() else {
+            return Ok(());
+        };
+        // 未注册服务（如 db_autoservice = false）时无需停止和移除
+        if !query_service_exists(service) {
+            return Ok(());
+        }
+
         let install_dir = ctx.config.tools_dir().join("redis");
         let service_exe = install_dir.join("RedisService.exe");
 
-        crate::ui::print_action("停止 Redis 服务...");
-        let _ = run_as_admin("net", &["stop", REDIS_SERVICE_NAME]);
+        ctx.reporter.action("停止 Redis 服务...");
+        let _ = run_as_admin("net", &["stop", service]);
 
         if service_exe.exists() {
-            crate::ui::print_action("移除 Redis 服务注册...");
+            ctx.reporter.action("移除 Redis 服务注册...");
             let service_str = service_exe.to_string_lossy().to_string();
             let _ = run_as_admin(&service_str, &["uninstall"]);
         }
@@ -3,7 +3,7 @@ use async_trait::async_trait;
 use dialoguer::Confirm;
 use std::path::PathBuf;
 
-use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use super::{CreatedDir, DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
 use super::mingw::MingwInstaller;
 use crate::config::HudoConfig;
 use crate::download;
@@ -17,6 +17,8 @@ impl Installer for RustupInstaller {
             id: "rust",
             name: "Rust",
             description: "Rust 编程语言 (via rustup)",
+            homepage: Some("https://www.rust-lang.org"),
+            post_install_tips: &[],
         }
     }
 
@@ -28,7 +30,7 @@ impl Installer for RustupInstaller {
         if rustc.exists() && rustup_home.exists() {
             if let Ok(out) = std::process::Command::new(&rustc).arg("--version").output() {
                 if out.status.success() {
-                    let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                    let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                     return Ok(DetectResult::InstalledByHudo(version));
                 }
             }
@@ -37,7 +39,7 @@ impl Installer for RustupInstaller {
         // 检查系统 PATH
         if let Ok(out) = std::process::Command::new("rustc").arg("--version").output() {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -66,24 +68,27 @@ impl Installer for RustupInstaller {
         std::fs::create_dir_all(&rustup_home).ok();
         std::fs::create_dir_all(&cargo_home).ok();
 
-        // 下载 rustup-init.exe
-        let exe_path = download::download(&url, &config.cache_dir(), &filename).await?;
+        // 下载 rustup-init.exe（地址固定指向最新版，有缓存时重新验证）
+        let exe_path = download::download_latest(&url, &config.cache_dir(), &filename).await?;
 
         // 使用 GNU 工具链（依赖 MinGW-w64 的 gcc，无需 MSVC）
-        crate::ui::print_action("安装 Rust (GNU 工具链)...");
-        let status = std::process::Command::new(&exe_path)
-            .args([
-                "-y",
-                "--no-modify-path",
-                "--default-host",
-                "x86_64-pc-windows-gnu",
-                "--default-toolchain",
-                "stable",
-            ])
-            .env("RUSTUP_HOME", &rustup_home)
-            .env("CARGO_HOME", &cargo_home)
-            .status()
-            .context("启动 rustup-init 失败")?;
+        ctx.reporter.action("安装 Rust (GNU 工具链)...");
+        let status = crate::watchdog::run_watched(
+            std::process::Command::new(&exe_path)
+                .args([
+                    "-y",
+                    "--no-modify-path",
+                    "--default-host",
+                    "x86_64-pc-windows-gnu",
+                    "--default-toolchain",
+                    "stable",
+                ])
+                .env("RUSTUP_HOME", &rustup_home)
+                .env("CARGO_HOME", &cargo_home),
+            "rustup-init",
+            config.install.watch(config.yes),
+        )
+        .context("启动 rustup-init 失败")?;
 
         if !status.success() {
             anyhow::bail!(
@@ -100,6 +105,18 @@ impl Installer for RustupInstaller {
         })
     }
 
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["c"]
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        &["bin\\rustc.exe"]
+    }
+
+    fn created_dirs(&self, config: &HudoConfig) -> Vec<CreatedDir> {
+        vec![CreatedDir { path: config.tools_dir().join("rustup"), user_data: None }]
+    }
+
     fn env_actions(&self, _install_path: &PathBuf, config: &HudoConfig) -> Vec<EnvAction> {
         let rustup_home = config.tools_dir().join("rustup");
         let cargo_home = config.lang_dir().join("cargo");
@@ -122,10 +139,10 @@ impl Installer for RustupInstaller {
 /// 检测 gcc 是否可用；若不可用则提示用户选择安装 MinGW-w64 或取消
 async fn ensure_gcc(ctx: &InstallContext<'_>) -> Result<()> {
     if detect_gcc(ctx.config) {
-        return Ok(());
+        return super::record_dependent(ctx.config, "c", "rust");
     }
 
-    crate::ui::print_warning("未检测到 gcc，Rust GNU 工具链需要 MinGW-w64 作为链接器");
+    ctx.reporter.warning("未检测到 gcc，Rust GNU 工具链需要 MinGW-w64 作为链接器");
 
     let install_now = Confirm::new()
         .with_prompt("  是否现在安装 C/C++ (MinGW-w64)？")
@@ -137,41 +154,17 @@ async fn ensure_gcc(ctx: &InstallContext<'_>) -> Result<()> {
         anyhow::bail!("请先安装 MinGW-w64：hudo install c");
     }
 
-    // 以和直接 hudo install c 相同的样式安装 MinGW-w64
-    crate::ui::print_title("安装 C/C++ (MinGW-w64)");
-    let result = MingwInstaller.install(ctx).await?;
-    crate::ui::print_success(&format!(
-        "C/C++ {} 安装完成",
-        console::style(&result.version).green()
-    ));
-
-    // 持久化环境变量（PATH += mingw64/bin）
-    let install_path = ctx.config.tools_dir().join("mingw64");
-    let actions = MingwInstaller.env_actions(&install_path, ctx.config);
-    for action in &actions {
-        match action {
-            super::EnvAction::AppendPath { path } => {
-                crate::ui::print_info(&format!("PATH += {}", path));
-                crate::env::EnvManager::append_to_path(path)?;
-            }
-            super::EnvAction::Set { name, value } => {
-                crate::ui::print_info(&format!("{} = {}", name, value));
-                crate::env::EnvManager::set_var(name, value)?;
-            }
-        }
-    }
-    if !actions.is_empty() {
-        crate::env::EnvManager::broadcast_change();
-    }
+    // 以和直接 hudo install c 相同的样式安装 MinGW-w64，并记录为 Rust 的依赖
+    let result = super::install_as_dependency(ctx, &MingwInstaller, "rust").await?;
 
     // 将 mingw64/bin 加入当前进程 PATH，让后续 rustup-init 能找到 gcc
-    let mingw_bin = install_path.join("bin");
+    let mingw_bin = result.install_path.join("bin");
     if let Ok(old_path) = std::env::var("PATH") {
         std::env::set_var("PATH", format!("{};{}", mingw_bin.display(), old_path));
     }
 
     // 恢复 Rust 安装的标题，视觉上衔接
-    crate::ui::print_title("安装 Rust");
+    ctx.reporter.title("安装 Rust");
 
     Ok(())
 }
@@ -199,5 +192,5 @@ fn get_rustc_version(cargo_home: &PathBuf) -> Option<String> {
         .output()
         .ok()
         .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .map(|o| crate::proc::decode_output(&o.stdout).trim().to_string())
 }
@@ -0,0 +1,132 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use crate::config::HudoConfig;
+use crate::download;
+
+pub struct SqliteInstaller;
+
+/// 获取最新版本失败时使用的内置版本：(版本号, sqlite.org 上的相对路径)
+const SQLITE_DEFAULT: (&str, &str) = ("3.50.4", "2025/sqlite-tools-win-x64-3500400.zip");
+
+#[async_trait]
+impl Installer for SqliteInstaller {
+    fn info(&self) -> ToolInfo {
+        ToolInfo {
+            id: "sqlite",
+            name: "SQLite",
+            description: "SQLite 命令行工具（sqlite3、sqldiff）",
+            homepage: Some("https://www.sqlite.org"),
+            post_install_tips: &[],
+        }
+    }
+
+    async fn detect_installed(&self, ctx: &InstallContext<'_>) -> Result<DetectResult> {
+        let sqlite_exe = ctx.config.tools_dir().join("sqlite").join("sqlite3.exe");
+        if sqlite_exe.exists() {
+            if let Ok(out) = std::process::Command::new(&sqlite_exe).arg("--version").output() {
+                if out.status.success() {
+                    let version = parse_sqlite_version(&crate::proc::decode_output(&out.stdout));
+                    return Ok(DetectResult::InstalledByHudo(version));
+                }
+            }
+        }
+
+        if let Ok(out) = std::process::Command::new("sqlite3").arg("--version").output() {
+            if out.status.success() {
+                let version = parse_sqlite_version(&crate::proc::decode_output(&out.stdout));
+                return Ok(DetectResult::InstalledExternal(version));
+            }
+        }
+
+        Ok(DetectResult::NotInstalled)
+    }
+
+    fn resolve_download(&self, config: &HudoConfig) -> (String, String) {
+        build_url(config, SQLITE_DEFAULT.0, SQLITE_DEFAULT.1)
+    }
+
+    async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
+        let config = ctx.config;
+        let install_dir = config.tools_dir().join("sqlite");
+
+        ctx.reporter.action("查询 SQLite 最新版本...");
+        let (version, path) = match crate::version::sqlite_latest().await {
+            Ok(latest) => latest,
+            Err(e) => {
+                ctx.reporter.warning(&format!(
+                    "获取最新版本失败（{}），使用内置版本 {}",
+                    e, SQLITE_DEFAULT.0
+                ));
+                (SQLITE_DEFAULT.0.to_string(), SQLITE_DEFAULT.1.to_string())
+            }
+        };
+
+        // zip 内没有顶层目录，sqlite3.exe 等直接在根目录
+        let (url, filename) = build_url(config, &version, &path);
+        download::install_zip(
+            &url,
+            &filename,
+            &config.cache_dir(),
+            &install_dir,
+            &download::ZipInstall {
+                name: "SQLite",
+                reporter: Some(ctx.reporter),
+                required_file: Some("sqlite3.exe"),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        Ok(InstallResult {
+            install_path: install_dir,
+            version,
+        })
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        &["sqlite3.exe"]
+    }
+
+    fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
+        vec![EnvAction::AppendPath {
+            path: install_path.to_string_lossy().to_string(),
+        }]
+    }
+}
+
+/// 相对路径（如 "2025/sqlite-tools-win-x64-3500400.zip"）→ (下载 URL, 文件名)
+fn build_url(config: &HudoConfig, version: &str, path: &str) -> (String, String) {
+    let base = config.expand_mirror(
+        config.mirrors.sqlite.as_deref().unwrap_or("https://www.sqlite.org"),
+        version,
+        "sqlite",
+    );
+    let filename = path.rsplit('/').next().unwrap_or(path).to_string();
+    (format!("{}/{}", base, path), filename)
+}
+
+/// `sqlite3 --version` 输出 "3.50.4 2025-07-30 19:33:53 4d8adfb3... (64-bit)" → "3.50.4"
+fn parse_sqlite_version(output: &str) -> String {
+    output
+        .split_whitespace()
+        .next()
+        .unwrap_or("已安装")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sqlite_version() {
+        assert_eq!(
+            parse_sqlite_version("3.50.4 2025-07-30 19:33:53 4d8adfb30e03f9cf27f800a2c1ba3c48fb4ca1b08b0f5ed59a4d5ecbf45e20a3 (64-bit)\n"),
+            "3.50.4"
+        );
+        assert_eq!(parse_sqlite_version(""), "已安装");
+    }
+}
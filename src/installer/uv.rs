@@ -15,6 +15,8 @@ impl Installer for UvInstaller {
             id: "uv",
             name: "uv",
             description: "Python 包管理器与项目管理工具",
+            homepage: Some("https://docs.astral.sh/uv/"),
+            post_install_tips: &[],
         }
     }
 
@@ -24,7 +26,7 @@ impl Installer for UvInstaller {
         if uv_exe.exists() {
             if let Ok(out) = std::process::Command::new(&uv_exe).arg("--version").output() {
                 if out.status.success() {
-                    let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                    let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                     return Ok(DetectResult::InstalledByHudo(version));
                 }
             }
@@ -33,7 +35,7 @@ impl Installer for UvInstaller {
         // 检查系统 PATH
         if let Ok(out) = std::process::Command::new("uv").arg("--version").output() {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout).trim().to_string();
+                let version = crate::proc::decode_output(&out.stdout).trim().to_string();
                 return Ok(DetectResult::InstalledExternal(version));
             }
         }
@@ -42,9 +44,11 @@ impl Installer for UvInstaller {
     }
 
     fn resolve_download(&self, config: &HudoConfig) -> (String, String) {
-        let url = config.mirrors.uv.as_deref()
-            .unwrap_or("https://astral.sh/uv/install.ps1")
-            .to_string();
+        let url = config.expand_mirror(
+            config.mirrors.uv.as_deref().unwrap_or("https://astral.sh/uv/install.ps1"),
+            "latest",
+            "uv",
+        );
         (url, "uv-installer.ps1".to_string())
     }
 
@@ -53,28 +57,25 @@ impl Installer for UvInstaller {
         let install_dir = config.tools_dir().join("uv");
         let (url, filename) = self.resolve_download(config);
 
-        // 安装脚本不缓存，总是下载最新版以获取最新 uv
-        let cached = config.cache_dir().join(&filename);
-        if cached.exists() {
-            std::fs::remove_file(&cached).ok();
-        }
-
-        // 下载安装脚本
-        let ps1_path = download::download(&url, &config.cache_dir(), &filename).await?;
+        // 下载安装脚本（地址固定、内容随 uv 发布更新，有缓存时重新验证）
+        let ps1_path = download::download_latest(&url, &config.cache_dir(), &filename).await?;
 
         // 用 PowerShell 执行官方安装脚本
-        crate::ui::print_action("安装 uv...");
-        let status = std::process::Command::new("powershell")
-            .args([
-                "-ExecutionPolicy",
-                "ByPass",
-                "-File",
-                &ps1_path.to_string_lossy(),
-            ])
-            .env("UV_INSTALL_DIR", &install_dir)
-            .env("UV_NO_MODIFY_PATH", "1")
-            .status()
-            .context("启动 PowerShell 安装脚本失败")?;
+        ctx.reporter.action("安装 uv...");
+        let status = crate::watchdog::run_watched(
+            std::process::Command::new("powershell")
+                .args([
+                    "-ExecutionPolicy",
+                    "ByPass",
+                    "-File",
+                    &ps1_path.to_string_lossy(),
+                ])
+                .env("UV_INSTALL_DIR", &install_dir)
+                .env("UV_NO_MODIFY_PATH", "1"),
+            "uv 安装脚本",
+            config.install.watch(config.yes),
+        )
+        .context("启动 PowerShell 安装脚本失败")?;
 
         if !status.success() {
             anyhow::bail!(
@@ -91,6 +92,19 @@ impl Installer for UvInstaller {
         })
     }
 
+    fn arm64_native(&self) -> bool {
+        // 官方安装脚本会按本机架构选择安装包
+        true
+    }
+
+    fn latest_download(&self) -> bool {
+        true
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        &["uv.exe"]
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         let dir = install_path.to_string_lossy();
         vec![
@@ -121,5 +135,5 @@ fn get_uv_version(install_dir: &PathBuf) -> Option<String> {
         .output()
         .ok()
         .filter(|o| o.status.success())
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .map(|o| crate::proc::decode_output(&o.stdout).trim().to_string())
 }
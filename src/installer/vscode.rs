@@ -1,16 +1,76 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use std::path::PathBuf;
+use dialoguer::Confirm;
+use std::path::{Path, PathBuf};
 
-use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use super::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ShortcutSpec, ToolInfo};
+use crate::arch::Arch;
 use crate::config::HudoConfig;
 use crate::download;
+use crate::winshell;
 
-/// 右键菜单注册表路径
-const CONTEXT_MENU_KEYS: &[&str] = &[
-    r"Software\Classes\*\shell\VSCode",                    // 右键文件
-    r"Software\Classes\Directory\shell\VSCode",            // 右键文件夹
-    r"Software\Classes\Directory\Background\shell\VSCode", // 右键文件夹空白处
+/// 发布渠道对应的安装目录、程序名与资源管理器集成的注册表项；两个渠道可并存
+struct Edition {
+    /// 下载地址中的渠道名
+    quality: &'static str,
+    /// ide_dir() 下的安装目录名，也用作缓存文件名前缀
+    dir: &'static str,
+    exe: &'static str,
+    /// 快捷方式与「打开方式」中显示的名称
+    name: &'static str,
+    /// PATH 上的命令名
+    command: &'static str,
+    /// 官方安装程序的安装目录名（%LOCALAPPDATA%\Programs、%ProgramFiles% 下）
+    system_dir: &'static str,
+    /// 右键菜单 verb 名称
+    verb: &'static str,
+    /// 「打开方式」列表中的 ProgID
+    prog_id: &'static str,
+}
+
+const STABLE: Edition = Edition {
+    quality: "stable",
+    dir: "vscode",
+    exe: "Code.exe",
+    name: "Visual Studio Code",
+    command: "code",
+    system_dir: "Microsoft VS Code",
+    verb: "VSCode",
+    prog_id: "hudo.vscode",
+};
+
+const INSIDERS: Edition = Edition {
+    quality: "insider",
+    dir: "vscode-insiders",
+    exe: "Code - Insiders.exe",
+    name: "Visual Studio Code - Insiders",
+    command: "code-insiders",
+    system_dir: "Microsoft VS Code Insiders",
+    verb: "VSCodeInsiders",
+    prog_id: "hudo.vscode-insiders",
+};
+
+/// 配置的发布渠道（channels.vscode）对应的版本
+fn edition(config: &HudoConfig) -> &'static Edition {
+    match super::active_channel(&VscodeInstaller, config) {
+        Some("insider") => &INSIDERS,
+        _ => &STABLE,
+    }
+}
+
+/// 按安装目录名判断是哪个渠道（卸载、快捷方式等只拿到安装路径的场景）
+fn edition_of(install_path: &Path) -> &'static Edition {
+    match install_path.file_name().is_some_and(|n| n == INSIDERS.dir) {
+        true => &INSIDERS,
+        false => &STABLE,
+    }
+}
+
+/// 加入「打开方式」列表的常见扩展名（不修改默认程序）
+const OPEN_WITH_EXTENSIONS: &[&str] = &[
+    "txt", "md", "json", "jsonc", "yaml", "yml", "toml", "ini", "cfg", "conf", "log", "csv",
+    "xml", "html", "css", "js", "mjs", "ts", "tsx", "jsx", "vue", "py", "rs", "go", "java",
+    "kt", "c", "h", "cpp", "hpp", "cs", "sh", "ps1", "bat", "cmd", "sql", "gitignore", "env",
 ];
 
 pub struct VscodeInstaller;
@@ -22,16 +82,20 @@ impl Installer for VscodeInstaller {
             id: "vscode",
             name: "VS Code",
             description: "Visual Studio Code 编辑器",
+            homepage: Some("https://code.visualstudio.com"),
+            post_install_tips: &[],
         }
     }
 
     async fn detect_installed(&self, ctx: &InstallContext<'_>) -> Result<DetectResult> {
+        let edition = edition(ctx.config);
+
         // 1. hudo 安装目录
-        let code_exe = ctx.config.ide_dir().join("vscode").join("Code.exe");
+        let code_exe = ctx.config.ide_dir().join(edition.dir).join(edition.exe);
         if code_exe.exists() {
             if let Ok(out) = std::process::Command::new(&code_exe).arg("--version").output() {
                 if out.status.success() {
-                    let version = String::from_utf8_lossy(&out.stdout)
+                    let version = crate::proc::decode_output(&out.stdout)
                         .lines()
                         .next()
                         .unwrap_or("unknown")
@@ -49,16 +113,16 @@ impl Installer for VscodeInstaller {
                 paths.push(
                     std::path::PathBuf::from(&local)
                         .join("Programs")
-                        .join("Microsoft VS Code")
-                        .join("Code.exe"),
+                        .join(edition.system_dir)
+                        .join(edition.exe),
                 );
             }
             // 系统级安装: %ProgramFiles%\Microsoft VS Code\Code.exe
             if let Ok(pf) = std::env::var("ProgramFiles") {
                 paths.push(
                     std::path::PathBuf::from(&pf)
-                        .join("Microsoft VS Code")
-                        .join("Code.exe"),
+                        .join(edition.system_dir)
+                        .join(edition.exe),
                 );
             }
             paths
@@ -68,7 +132,7 @@ impl Installer for VscodeInstaller {
             if path.exists() {
                 if let Ok(out) = std::process::Command::new(path).arg("--version").output() {
                     if out.status.success() {
-                        let version = String::from_utf8_lossy(&out.stdout)
+                        let version = crate::proc::decode_output(&out.stdout)
                             .lines()
                             .next()
                             .unwrap_or("unknown")
@@ -80,12 +144,11 @@ impl Installer for VscodeInstaller {
         }
 
         // 3. PATH 上的 code 命令（通过 cmd /c 处理 .cmd 扩展名）
-        if let Ok(out) = std::process::Command::new("cmd")
-            .args(["/c", "code", "--version"])
+        if let Ok(out) = crate::proc::cmd(edition.command, &["--version"])
             .output()
         {
             if out.status.success() {
-                let version = String::from_utf8_lossy(&out.stdout)
+                let version = crate::proc::decode_output(&out.stdout)
                     .lines()
                     .next()
                     .unwrap_or("unknown")
@@ -98,51 +161,51 @@ impl Installer for VscodeInstaller {
     }
 
     fn resolve_download(&self, config: &HudoConfig) -> (String, String) {
-        let url = config.mirrors.vscode.as_deref()
-            .unwrap_or("https://update.code.visualstudio.com/latest/win32-x64-archive/stable")
-            .to_string();
-        (url, "vscode-win32-x64.zip".to_string())
+        let mirror = config.mirrors.vscode.as_deref().map(|m| config.expand_mirror(m, "latest", "vscode"));
+        build_url(edition(config), mirror.as_deref(), config.arch())
+    }
+
+    fn channels(&self) -> &'static [&'static str] {
+        &["stable", "insider"]
     }
 
     async fn install(&self, ctx: &InstallContext<'_>) -> Result<InstallResult> {
         let config = ctx.config;
-        let install_dir = config.ide_dir().join("vscode");
+        let edition = edition(config);
+        let install_dir = config.ide_dir().join(edition.dir);
         let (url, filename) = self.resolve_download(config);
 
-        // 每次下载最新版
-        let cached = config.cache_dir().join(&filename);
-        if cached.exists() {
-            std::fs::remove_file(&cached).ok();
+        // latest 地址，有缓存时重新验证
+        let zip_path = download::download_latest(&url, &config.cache_dir(), &filename).await?;
+
+        ctx.reporter.action(&format!("解压 {}...", edition.name));
+        let tmp_dir = config.cache_dir().join(format!("{}-extract", edition.dir));
+        if tmp_dir.exists() {
+            std::fs::remove_dir_all(&tmp_dir).ok();
         }
+        download::extract_zip(&zip_path, &tmp_dir)?;
 
-        let zip_path = download::download(&url, &config.cache_dir(), &filename).await?;
-
-        crate::ui::print_action("解压 VS Code...");
-        if install_dir.exists() {
-            // 保留 data/ 目录（用户配置和扩展）
-            let data_dir = install_dir.join("data");
-            let has_data = data_dir.exists();
-            let tmp_data = config.cache_dir().join("vscode-data-backup");
-            if has_data {
-                if tmp_data.exists() {
-                    std::fs::remove_dir_all(&tmp_data).ok();
-                }
-                std::fs::rename(&data_dir, &tmp_data).ok();
-            }
-            std::fs::remove_dir_all(&install_dir).ok();
-            download::extract_zip(&zip_path, &install_dir)?;
-            if has_data {
-                std::fs::rename(&tmp_data, &install_dir.join("data")).ok();
+        // 保留 data/ 目录（用户配置和扩展）：替换期间暂存在安装目录旁，
+        // 上次中断残留的暂存目录在本次替换后恢复
+        let data_dir = install_dir.join("data");
+        let data_backup = config.ide_dir().join(format!("{}-data.bak", edition.dir));
+        if data_dir.exists() {
+            if data_backup.exists() {
+                std::fs::remove_dir_all(&data_backup).ok();
             }
-        } else {
-            download::extract_zip(&zip_path, &install_dir)?;
+            std::fs::rename(&data_dir, &data_backup).context("无法暂存 VS Code data 目录")?;
+        }
+        let swapped = download::swap_dir(&tmp_dir, &install_dir);
+        if data_backup.exists() {
+            std::fs::rename(&data_backup, &data_dir).context("无法恢复 VS Code data 目录")?;
         }
+        swapped?;
 
         // 创建 data/ 目录使其成为 portable 模式
         let data_dir = install_dir.join("data");
         std::fs::create_dir_all(&data_dir).ok();
 
-        let version = get_vscode_version(&install_dir).unwrap_or_else(|| "unknown".to_string());
+        let version = get_vscode_version(&install_dir.join(edition.exe)).unwrap_or_else(|| "unknown".to_string());
 
         Ok(InstallResult {
             install_path: install_dir,
@@ -150,6 +213,27 @@ impl Installer for VscodeInstaller {
         })
     }
 
+    fn user_data_dirs(&self, install_path: &Path) -> Vec<PathBuf> {
+        vec![install_path.join("data")]
+    }
+
+    fn arm64_native(&self) -> bool {
+        true
+    }
+
+    fn latest_download(&self) -> bool {
+        true
+    }
+
+    fn binaries(&self) -> &'static [&'static str] {
+        &["Code.exe", "Code - Insiders.exe"]
+    }
+
+    fn shortcuts(&self, install_path: &Path) -> Vec<ShortcutSpec> {
+        let edition = edition_of(install_path);
+        vec![ShortcutSpec::new(edition.name, install_path.join(edition.exe))]
+    }
+
     fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
         vec![
             EnvAction::AppendPath {
@@ -162,69 +246,137 @@ impl Installer for VscodeInstaller {
     }
 
     async fn configure(&self, ctx: &InstallContext<'_>) -> Result<()> {
-        register_context_menu(ctx.config)
+        register_shell_integration(ctx)
     }
 
-    async fn pre_uninstall(&self, _ctx: &InstallContext<'_>) -> Result<()> {
-        unregister_context_menu();
+    async fn pre_uninstall(&self, ctx: &InstallContext<'_>) -> Result<()> {
+        unregister_shell_integration(edition(ctx.config));
         Ok(())
     }
 }
 
-fn get_vscode_version(install_dir: &PathBuf) -> Option<String> {
-    let code_exe = install_dir.join("Code.exe");
+fn get_vscode_version(code_exe: &Path) -> Option<String> {
     std::process::Command::new(code_exe)
         .arg("--version")
         .output()
         .ok()
         .filter(|o| o.status.success())
         .and_then(|o| {
-            String::from_utf8_lossy(&o.stdout)
+            crate::proc::decode_output(&o.stdout)
                 .lines()
                 .next()
                 .map(|s| s.to_string())
         })
 }
 
-/// 注册 Windows 右键菜单「通过 Code 打开」
-fn register_context_menu(config: &HudoConfig) -> Result<()> {
-    use winreg::enums::*;
-    use winreg::RegKey;
-
-    let code_exe = config.ide_dir().join("vscode").join("Code.exe");
-    let code_path = code_exe.to_string_lossy();
-    let icon_value = format!("{},0", code_path);
-
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-
-    for key_path in CONTEXT_MENU_KEYS {
-        let (shell_key, _) = hkcu
-            .create_subkey(key_path)
-            .with_context(|| format!("创建注册表项 {} 失败", key_path))?;
-        shell_key.set_value("", &"通过 Code 打开")?;
-        shell_key.set_value("Icon", &icon_value)?;
-
-        let (cmd_key, _) = shell_key.create_subkey("command")?;
-        // 文件夹空白处使用 %V 表示当前目录，文件/文件夹使用 %1
-        let cmd_value = if key_path.contains("Background") {
-            format!("\"{}\" \"%V\"", code_path)
-        } else {
-            format!("\"{}\" \"%1\"", code_path)
-        };
-        cmd_key.set_value("", &cmd_value)?;
+/// 注册资源管理器集成：右键菜单「通过 Code 打开」，并加入常见文件的「打开方式」列表。
+/// 已注册到当前 Code.exe 时直接刷新，否则先询问（全部写入 HKCU，无需管理员权限）
+fn register_shell_integration(ctx: &InstallContext<'_>) -> Result<()> {
+    let edition = edition(ctx.config);
+    let code_exe = ctx.config.ide_dir().join(edition.dir).join(edition.exe);
+    let registered = winshell::context_menu_command(winshell::USER_CLASSES, edition.verb)
+        .is_some_and(|cmd| cmd.contains(&*code_exe.to_string_lossy()));
+    if !registered {
+        let enable = Confirm::new()
+            .with_prompt(format!(
+                "  是否注册右键菜单「{}」，并把 {} 加入常见文件的「打开方式」？（不修改默认程序）",
+                menu_label(edition),
+                edition.name
+            ))
+            .default(true)
+            .interact()
+            .unwrap_or(false);
+        if !enable {
+            ctx.reporter.info("跳过资源管理器集成，可重新运行 hudo install vscode 注册");
+            return Ok(());
+        }
     }
 
-    crate::ui::print_action("已注册右键菜单「通过 Code 打开」");
+    winshell::register_context_menu(
+        winshell::USER_CLASSES,
+        edition.verb,
+        &menu_label(edition),
+        &code_exe,
+    )?;
+    winshell::register_open_with(
+        winshell::USER_CLASSES,
+        edition.prog_id,
+        edition.name,
+        &code_exe,
+        OPEN_WITH_EXTENSIONS,
+    )?;
+    ctx.reporter.action(&format!("已注册右键菜单「{}」和「打开方式」", menu_label(edition)));
     Ok(())
 }
 
-/// 卸载时清理右键菜单注册表项
-fn unregister_context_menu() {
-    use winreg::enums::*;
-    use winreg::RegKey;
+/// 右键菜单文字，Insiders 与正式版并存时需要区分
+fn menu_label(edition: &Edition) -> String {
+    match edition.quality {
+        "insider" => "通过 Code - Insiders 打开".to_string(),
+        _ => "通过 Code 打开".to_string(),
+    }
+}
+
+/// 卸载时清理 register_shell_integration 写入的注册表项
+fn unregister_shell_integration(edition: &Edition) {
+    winshell::unregister_context_menu(winshell::USER_CLASSES, edition.verb);
+    winshell::unregister_open_with(winshell::USER_CLASSES, edition.prog_id, OPEN_WITH_EXTENSIONS);
+}
+
+/// 官方 zip 包下载地址（win32-x64-archive / win32-arm64-archive）；
+/// 镜像地址原样使用，镜像只提供正式版，Insiders 总是从官方下载
+fn build_url(edition: &Edition, mirror: Option<&str>, arch: Arch) -> (String, String) {
+    let platform = match arch {
+        Arch::X64 => "win32-x64",
+        Arch::Arm64 => "win32-arm64",
+    };
+    let url = match mirror.filter(|_| edition.quality == STABLE.quality) {
+        Some(m) => m.to_string(),
+        None => format!(
+            "https://update.code.visualstudio.com/latest/{}-archive/{}",
+            platform, edition.quality
+        ),
+    };
+    (url, format!("{}-{}.zip", edition.dir, platform))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_url_per_arch() {
+        assert_eq!(
+            build_url(&STABLE, None, Arch::X64),
+            (
+                "https://update.code.visualstudio.com/latest/win32-x64-archive/stable".to_string(),
+                "vscode-win32-x64.zip".to_string()
+            )
+        );
+        assert_eq!(
+            build_url(&STABLE, None, Arch::Arm64),
+            (
+                "https://update.code.visualstudio.com/latest/win32-arm64-archive/stable".to_string(),
+                "vscode-win32-arm64.zip".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_build_url_insiders() {
+        assert_eq!(
+            build_url(&INSIDERS, Some("https://mirror.example/vscode.zip"), Arch::X64),
+            (
+                "https://update.code.visualstudio.com/latest/win32-x64-archive/insider".to_string(),
+                "vscode-insiders-win32-x64.zip".to_string()
+            )
+        );
+        assert_eq!(build_url(&STABLE, Some("https://mirror.example/vscode.zip"), Arch::X64).0, "https://mirror.example/vscode.zip");
+    }
 
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    for key_path in CONTEXT_MENU_KEYS {
-        let _ = hkcu.delete_subkey_all(key_path);
+    #[test]
+    fn test_edition_of() {
+        assert_eq!(edition_of(Path::new(r"D:\hudo\ide\vscode-insiders")).exe, "Code - Insiders.exe");
+        assert_eq!(edition_of(Path::new(r"D:\hudo\ide\vscode")).exe, "Code.exe");
     }
 }
@@ -0,0 +1,284 @@
+//! 界面语言：`t(key)` 按当前语言查表，`tf(key, args)` 依次替换文本中的 `{}`
+//!
+//! 语言按 --lang、配置文件中的 lang、LC_ALL / LC_MESSAGES / LANG、系统界面语言的顺序决定，
+//! 都没有时为中文。目前翻译了最常见的界面文字（菜单、安装 / 卸载汇总等），
+//! 其余输出仍为中文；英文表中缺少的键回退到中文。
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Lang {
+    /// 中文
+    Zh,
+    /// English
+    En,
+}
+
+impl Lang {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lang::Zh => "zh",
+            Lang::En => "en",
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn set(lang: Lang) {
+    CURRENT.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn current() -> Lang {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Lang::En,
+        _ => Lang::Zh,
+    }
+}
+
+/// 解析语言设置：zh / en，或 zh_CN.UTF-8、en-US 这类区域名称；
+/// C / POSIX 与空值不表示语言，返回 None
+pub fn parse(value: &str) -> Option<Lang> {
+    let value = value.trim();
+    let code = value.split(['_', '-', '.', '@']).next().unwrap_or("").to_ascii_lowercase();
+    match code.as_str() {
+        "" | "c" | "posix" => None,
+        "zh" => Some(Lang::Zh),
+        _ => Some(Lang::En),
+    }
+}
+
+/// 按优先级决定语言：命令行、配置文件、环境变量（LC_ALL、LC_MESSAGES、LANG 中第一个有效的）、系统界面语言
+pub fn resolve(flag: Option<Lang>, config: Option<&str>, env: &[Option<String>], system: Option<Lang>) -> Lang {
+    flag.or_else(|| config.and_then(parse))
+        .or_else(|| env.iter().flatten().find_map(|v| parse(v)))
+        .or(system)
+        .unwrap_or(Lang::Zh)
+}
+
+/// 按当前环境决定语言
+pub fn detect(flag: Option<Lang>, config: Option<&str>) -> Lang {
+    let env: Vec<Option<String>> =
+        ["LC_ALL", "LC_MESSAGES", "LANG"].iter().map(|k| std::env::var(k).ok()).collect();
+    resolve(flag, config, &env, system_lang())
+}
+
+/// Windows 的界面语言：中文为 zh，其他语言为 en
+#[cfg(windows)]
+fn system_lang() -> Option<Lang> {
+    const LANG_CHINESE: u16 = 0x04;
+    match unsafe { windows_sys::Win32::Globalization::GetUserDefaultUILanguage() } {
+        0 => None,
+        id if id & 0x3ff == LANG_CHINESE => Some(Lang::Zh),
+        _ => Some(Lang::En),
+    }
+}
+
+#[cfg(not(windows))]
+fn system_lang() -> Option<Lang> {
+    None
+}
+
+/// 中文文本；新增键时 EN 中同时加一行
+const ZH: &[(&str, &str)] = &[
+    ("banner.tagline", "混沌 — 开发环境一键引导工具"),
+    ("ui.next_steps", "下一步"),
+    ("ui.press_any_key", "按任意键返回..."),
+    ("ui.seconds", "{} 秒"),
+    ("ui.minutes", "{} 分 {} 秒"),
+    ("category.tool", "工具"),
+    ("category.language", "语言环境"),
+    ("category.database", "数据库"),
+    ("category.ide", "编辑器 / IDE"),
+    ("menu.install", "📦  安装工具"),
+    ("menu.list", "📋  查看已安装"),
+    ("menu.uninstall", "🗑   卸载工具"),
+    ("menu.profile", "📁  环境档案"),
+    ("menu.config", "⚙   配置"),
+    ("menu.ai", "🔑  AI 工具 API 来源"),
+    ("menu.exit", "🚪  退出"),
+    ("menu.prompt", "请选择操作 (Esc 退出)"),
+    ("menu.update_available", "hudo v{} 已发布"),
+    ("menu.update_hint", "运行 hudo update 升级"),
+    ("common.cancelled", "已取消"),
+    ("setup.choose_category", "选择工具分类"),
+    ("setup.category_prompt", "选择分类 (Esc 返回)"),
+    ("setup.all_tools", "全部工具"),
+    ("setup.tool_count", "{} 个工具"),
+    ("setup.select_tools", "{} — 选择要安装的工具"),
+    ("setup.filter_prompt", "输入关键字筛选，留空显示全部"),
+    ("setup.no_match", "没有匹配「{}」的工具"),
+    ("setup.select_hint", "空格勾选/取消，回车确认，Esc 返回"),
+    ("setup.nothing_selected", "未选择任何工具"),
+    ("setup.querying_sizes", "查询下载大小..."),
+    ("setup.about_to_install", "即将安装 {} 个工具:"),
+    ("setup.confirm", "确认开始？"),
+    ("setup.continue_prompt", "是否继续安装其余工具？"),
+    ("setup.aborted", "用户中止安装"),
+    ("setup.open_new_terminal", "请打开新终端以使环境变量生效"),
+    ("setup.size_unknown", "（另有 {} 个工具大小未知）"),
+    ("setup.download_total", "预计下载 {}{}"),
+    ("install.title", "安装 {}"),
+    ("install.done", "{} {} 安装完成"),
+    ("install.failed", "{} 安装失败: {}"),
+    ("install.total_time", "总耗时 {}"),
+    ("install.all_done", "全部 {} 个工具安装完成"),
+    ("install.some_done", "{} 个工具安装成功"),
+    ("install.some_failed", "{} 个工具安装失败: {}"),
+    ("uninstall.page", "卸载工具"),
+    ("uninstall.none", "当前没有由 hudo 安装的工具"),
+    ("uninstall.prompt", "选择要卸载的工具 (Esc 返回)"),
+    ("uninstall.title", "卸载 {} ({})"),
+    ("uninstall.done", "{} 已卸载"),
+    ("uninstall.all_title", "卸载全部 {} 个 hudo 管理的工具"),
+    ("uninstall.all_done", "已卸载 {} 个工具"),
+    ("uninstall.some_failed", "已卸载 {} 个工具，{} 个失败: {}"),
+    ("list.all_title", "所有可用工具"),
+    ("list.installed_title", "已安装工具"),
+    ("list.summary", "共 {} 个工具已安装 (hudo: {}, 系统: {})"),
+    ("list.root", "安装根目录: {}"),
+    ("list.empty", "尚未安装任何工具，运行 hudo setup 开始安装"),
+    ("list.empty_category", "「{}」分类下没有已安装的工具"),
+    ("list.hint_all", "使用 hudo list --all 查看所有可用工具"),
+];
+
+const EN: &[(&str, &str)] = &[
+    ("banner.tagline", "Chaos — one-step developer environment bootstrapper"),
+    ("ui.next_steps", "Next steps"),
+    ("ui.press_any_key", "Press any key to return..."),
+    ("ui.seconds", "{}s"),
+    ("ui.minutes", "{}m {}s"),
+    ("category.tool", "Tools"),
+    ("category.language", "Languages"),
+    ("category.database", "Databases"),
+    ("category.ide", "Editors / IDE"),
+    ("menu.install", "📦  Install tools"),
+    ("menu.list", "📋  Installed tools"),
+    ("menu.uninstall", "🗑   Uninstall tools"),
+    ("menu.profile", "📁  Environment profiles"),
+    ("menu.config", "⚙   Settings"),
+    ("menu.ai", "🔑  AI tool API providers"),
+    ("menu.exit", "🚪  Exit"),
+    ("menu.prompt", "Choose an action (Esc to exit)"),
+    ("menu.update_available", "hudo v{} is available"),
+    ("menu.update_hint", "run hudo update to upgrade"),
+    ("common.cancelled", "Cancelled"),
+    ("setup.choose_category", "Choose a category"),
+    ("setup.category_prompt", "Choose a category (Esc to go back)"),
+    ("setup.all_tools", "All tools"),
+    ("setup.tool_count", "{} tool(s)"),
+    ("setup.select_tools", "{} — select tools to install"),
+    ("setup.filter_prompt", "Filter by keyword, leave empty to show all"),
+    ("setup.no_match", "No tools match \"{}\""),
+    ("setup.select_hint", "Space to toggle, Enter to confirm, Esc to go back"),
+    ("setup.nothing_selected", "No tools selected"),
+    ("setup.querying_sizes", "Checking download sizes..."),
+    ("setup.about_to_install", "About to install {} tool(s):"),
+    ("setup.confirm", "Start now?"),
+    ("setup.continue_prompt", "Continue installing the remaining tools?"),
+    ("setup.aborted", "Installation aborted"),
+    ("setup.open_new_terminal", "Open a new terminal for the environment changes to take effect"),
+    ("setup.size_unknown", " ({} more of unknown size)"),
+    ("setup.download_total", "Estimated download {}{}"),
+    ("install.title", "Install {}"),
+    ("install.done", "{} {} installed"),
+    ("install.failed", "Failed to install {}: {}"),
+    ("install.total_time", "Total time {}"),
+    ("install.all_done", "All {} tool(s) installed"),
+    ("install.some_done", "{} tool(s) installed"),
+    ("install.some_failed", "{} tool(s) failed: {}"),
+    ("uninstall.page", "Uninstall tools"),
+    ("uninstall.none", "No tools are currently installed by hudo"),
+    ("uninstall.prompt", "Choose a tool to uninstall (Esc to go back)"),
+    ("uninstall.title", "Uninstall {} ({})"),
+    ("uninstall.done", "{} uninstalled"),
+    ("uninstall.all_title", "Uninstall all {} tool(s) managed by hudo"),
+    ("uninstall.all_done", "{} tool(s) uninstalled"),
+    ("uninstall.some_failed", "{} tool(s) uninstalled, {} failed: {}"),
+    ("list.all_title", "All available tools"),
+    ("list.installed_title", "Installed tools"),
+    ("list.summary", "{} tool(s) installed (hudo: {}, system: {})"),
+    ("list.root", "Install root: {}"),
+    ("list.empty", "No tools installed yet, run hudo setup to get started"),
+    ("list.empty_category", "No installed tools in \"{}\""),
+    ("list.hint_all", "Run hudo list --all to see all available tools"),
+];
+
+fn lookup(table: &[(&str, &'static str)], key: &str) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+/// lang 中 key 的文本；英文表缺少时回退到中文，都没有时返回 key 本身
+pub fn text(lang: Lang, key: &'static str) -> &'static str {
+    let table = match lang {
+        Lang::Zh => ZH,
+        Lang::En => EN,
+    };
+    lookup(table, key).or_else(|| lookup(ZH, key)).unwrap_or(key)
+}
+
+/// 当前语言中 key 的文本
+pub fn t(key: &'static str) -> &'static str {
+    text(current(), key)
+}
+
+/// 依次用 args 替换文本中的 `{}`
+pub fn tf(key: &'static str, args: &[&dyn std::fmt::Display]) -> String {
+    fill(t(key), args)
+}
+
+fn fill(template: &str, args: &[&dyn std::fmt::Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    out.push_str(parts.next().unwrap_or(""));
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_resolve() {
+        assert_eq!(parse("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(parse("zh-CN"), Some(Lang::Zh));
+        assert_eq!(parse("ja_JP"), Some(Lang::En));
+        assert_eq!(parse("C.UTF-8"), None);
+        assert_eq!(parse(""), None);
+
+        let env = |v: &[&str]| v.iter().map(|s| Some(s.to_string())).collect::<Vec<_>>();
+        assert_eq!(resolve(Some(Lang::En), Some("zh"), &env(&["zh_CN"]), None), Lang::En);
+        assert_eq!(resolve(None, Some("en"), &env(&["zh_CN"]), Some(Lang::Zh)), Lang::En);
+        // LC_ALL 为 C 时看下一个变量
+        assert_eq!(resolve(None, None, &env(&["C", "en_GB.UTF-8"]), Some(Lang::Zh)), Lang::En);
+        assert_eq!(resolve(None, None, &[None, None], Some(Lang::En)), Lang::En);
+        assert_eq!(resolve(None, Some("klingon?"), &[], None), Lang::En);
+        assert_eq!(resolve(None, None, &[], None), Lang::Zh);
+    }
+
+    #[test]
+    fn test_tables_have_same_keys() {
+        let keys = |table: &'static [(&'static str, &'static str)]| table.iter().map(|(k, _)| *k).collect::<Vec<_>>();
+        assert_eq!(keys(ZH), keys(EN));
+        // 占位符数量一致
+        for ((key, zh), (_, en)) in ZH.iter().zip(EN) {
+            assert_eq!(zh.matches("{}").count(), en.matches("{}").count(), "{}", key);
+        }
+    }
+
+    #[test]
+    fn test_text_and_fill() {
+        assert_eq!(text(Lang::En, "install.done"), "{} {} installed");
+        assert_eq!(text(Lang::Zh, "install.done"), "{} {} 安装完成");
+        assert_eq!(text(Lang::En, "no.such.key"), "no.such.key");
+        assert_eq!(fill("{} {} 安装完成", &[&"Go", &"1.24.1"]), "Go 1.24.1 安装完成");
+        assert_eq!(fill("共 {} 个", &[]), "共  个");
+    }
+}
@@ -0,0 +1,36 @@
+//! hudo 的核心模块（安装器、下载、配置、环境变量等），命令行入口见 main.rs；
+//! 拆成库是为了让 tests/ 下的集成测试直接调用这些模块
+
+pub mod ai;
+pub mod arch;
+pub mod cache;
+pub mod catalog;
+pub mod cli;
+pub mod config;
+pub mod defender;
+pub mod download;
+pub mod env;
+pub mod installer;
+pub mod lang;
+pub mod list;
+pub mod mirrors;
+pub mod paths;
+pub mod proc;
+pub mod profile;
+pub mod psprofile;
+pub mod registry;
+pub mod report;
+pub mod running;
+#[cfg(windows)]
+pub mod scan;
+#[cfg(windows)]
+pub mod shortcut;
+pub mod status;
+pub mod takeover;
+pub mod template;
+pub mod ui;
+pub mod version;
+pub mod watchdog;
+#[cfg(windows)]
+pub mod winshell;
+pub mod wsl;
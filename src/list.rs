@@ -0,0 +1,253 @@
+//! hudo list 的筛选与排序
+//!
+//! 先按分类挑出要检测的工具（不显示的工具不运行检测子进程），检测结果转成行后
+//! 再按是否安装筛选、按选定的列排序；终端表格与 --json 使用同一组行。
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::installer::{DetectResult, Installer, ToolInfo};
+use crate::lang::Lang;
+use crate::registry::InstallRegistry;
+use crate::ui::ToolCategory;
+
+/// --sort 的取值；不指定时按分类分组显示
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListSort {
+    /// 按名称
+    Name,
+    /// 按安装时间，最近安装的在前
+    #[value(name = "installed_at")]
+    InstalledAt,
+    /// 按版本号
+    Version,
+}
+
+/// hudo list 的选项
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// --all：包含未安装的工具
+    pub show_all: bool,
+    /// --verify：跳过 state.json 快速检测
+    pub verify: bool,
+    pub category: Option<ToolCategory>,
+    pub sort: Option<ListSort>,
+    pub json: bool,
+}
+
+/// 工具的安装状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Hudo,
+    External,
+    NotInstalled,
+    Failed,
+}
+
+impl Status {
+    pub fn installed(&self) -> bool {
+        matches!(self, Status::Hudo | Status::External)
+    }
+}
+
+/// 列表中的一行
+#[derive(Debug, Clone, Serialize)]
+pub struct Row {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    #[serde(serialize_with = "category_key")]
+    pub category: ToolCategory,
+    pub status: Status,
+    pub version: Option<String>,
+    /// hudo 安装的时间（state.json 中的记录）
+    pub installed_at: Option<String>,
+    pub pinned: bool,
+    /// 作为哪些工具（名称）的依赖安装
+    pub dependency_of: Vec<String>,
+}
+
+pub(crate) fn category_key<S: serde::Serializer>(category: &ToolCategory, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(category.key())
+}
+
+/// 解析 --category：英文标识（tool / language / database / ide）或中文、英文名称，
+/// 「编辑器 / IDE」这类名称的任一部分也可以
+pub fn parse_category(value: &str) -> Result<ToolCategory> {
+    let value = value.trim();
+    ToolCategory::ALL
+        .into_iter()
+        .find(|c| {
+            c.key().eq_ignore_ascii_case(value)
+                || [Lang::Zh, Lang::En].into_iter().map(|l| c.label_in(l)).any(|label| {
+                    label.eq_ignore_ascii_case(value)
+                        || label.split(" / ").any(|part| part.eq_ignore_ascii_case(value))
+                })
+        })
+        .ok_or_else(|| {
+            let known: Vec<String> = ToolCategory::ALL
+                .iter()
+                .map(|c| format!("{}（{}）", c.key(), c.label()))
+                .collect();
+            anyhow::anyhow!("未知分类 '{}'，可用: {}", value, known.join("、"))
+        })
+}
+
+/// 按分类挑出要检测的工具，None 表示全部
+pub fn select(installers: &[Box<dyn Installer>], category: Option<ToolCategory>) -> Vec<&dyn Installer> {
+    installers
+        .iter()
+        .map(|i| i.as_ref())
+        .filter(|i| category.is_none_or(|c| ToolCategory::from_id(i.info().id) == c))
+        .collect()
+}
+
+/// 检测结果 → 行；依赖关系中的工具 id 换成名称（找不到时保留 id）
+pub fn build_rows(results: &[(ToolInfo, Result<DetectResult>)], reg: &InstallRegistry) -> Vec<Row> {
+    let infos: Vec<ToolInfo> = crate::installer::all_installers().iter().map(|i| i.info()).collect();
+    let name_of = |id: &str| infos.iter().find(|i| i.id == id).map_or(id.to_string(), |i| i.name.to_string());
+    results
+        .iter()
+        .map(|(info, detect)| {
+            let (status, version) = match detect {
+                Ok(DetectResult::InstalledByHudo(v)) => (Status::Hudo, Some(v.clone())),
+                Ok(DetectResult::InstalledExternal(v)) => (Status::External, Some(v.clone())),
+                Ok(DetectResult::NotInstalled) => (Status::NotInstalled, None),
+                Err(_) => (Status::Failed, None),
+            };
+            let state = reg.get(info.id).filter(|_| status == Status::Hudo);
+            Row {
+                id: info.id,
+                name: info.name,
+                description: info.description,
+                category: ToolCategory::from_id(info.id),
+                status,
+                version,
+                installed_at: state.map(|s| s.installed_at.clone()),
+                pinned: state.is_some_and(|s| s.pinned),
+                dependency_of: state
+                    .map(|s| s.installed_as_dependency_of.iter().map(|id| name_of(id)).collect())
+                    .unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/// 不带 --all 时只保留已安装的工具
+pub fn filter_rows(rows: Vec<Row>, show_all: bool) -> Vec<Row> {
+    rows.into_iter().filter(|r| show_all || r.status.installed()).collect()
+}
+
+/// 版本号中的数字部分（"git version 2.47.1" → [2, 47, 1]），用于按数值比较
+fn version_parts(version: &str) -> Vec<u64> {
+    let start = version.find(|c: char| c.is_ascii_digit()).unwrap_or(version.len());
+    version[start..]
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .next()
+        .unwrap_or("")
+        .split('.')
+        .map_while(|p| p.parse().ok())
+        .collect()
+}
+
+/// 排序；缺少排序依据的行（未安装、没有安装记录）排在最后，同值按名称
+pub fn sort_rows(rows: &mut [Row], sort: ListSort) {
+    let name = |r: &Row| r.name.to_lowercase();
+    match sort {
+        ListSort::Name => rows.sort_by_key(name),
+        ListSort::InstalledAt => rows.sort_by(|a, b| {
+            b.installed_at.is_some().cmp(&a.installed_at.is_some())
+                .then_with(|| b.installed_at.cmp(&a.installed_at))
+                .then_with(|| name(a).cmp(&name(b)))
+        }),
+        ListSort::Version => rows.sort_by(|a, b| {
+            let key = |r: &Row| r.version.as_deref().map(version_parts);
+            b.version.is_some().cmp(&a.version.is_some())
+                .then_with(|| key(a).cmp(&key(b)))
+                .then_with(|| name(a).cmp(&name(b)))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &'static str, status: Status, version: Option<&str>, installed_at: Option<&str>) -> Row {
+        Row {
+            id,
+            name: id,
+            description: "",
+            category: ToolCategory::from_id(id),
+            status,
+            version: version.map(str::to_string),
+            installed_at: installed_at.map(str::to_string),
+            pinned: false,
+            dependency_of: Vec::new(),
+        }
+    }
+
+    fn ids(rows: &[Row]) -> Vec<&str> {
+        rows.iter().map(|r| r.id).collect()
+    }
+
+    fn sample() -> Vec<Row> {
+        vec![
+            row("mysql", Status::Hudo, Some("8.4.3"), Some("2025-03-01 10:00:00")),
+            row("go", Status::Hudo, Some("1.24.0"), Some("2025-05-20 09:30:00")),
+            row("git", Status::External, Some("git version 2.47.1.windows.1"), None),
+            row("redis", Status::NotInstalled, None, None),
+            row("jdk", Status::Hudo, Some("openjdk version \"21.0.5\" 2024-10-15"), Some("2025-01-10 08:00:00")),
+            row("pgsql", Status::Failed, None, None),
+        ]
+    }
+
+    #[test]
+    fn test_parse_category() {
+        assert_eq!(parse_category("database").unwrap(), ToolCategory::Database);
+        assert_eq!(parse_category("IDE").unwrap(), ToolCategory::Ide);
+        assert_eq!(parse_category("数据库").unwrap(), ToolCategory::Database);
+        assert_eq!(parse_category("编辑器").unwrap(), ToolCategory::Ide);
+        assert_eq!(parse_category(" 语言环境 ").unwrap(), ToolCategory::Language);
+        assert_eq!(parse_category("Databases").unwrap(), ToolCategory::Database);
+        let err = parse_category("db").unwrap_err().to_string();
+        assert!(err.contains("database（数据库）"), "{}", err);
+    }
+
+    #[test]
+    fn test_filter_rows() {
+        assert_eq!(ids(&filter_rows(sample(), false)), ["mysql", "go", "git", "jdk"]);
+        assert_eq!(filter_rows(sample(), true).len(), 6);
+    }
+
+    #[test]
+    fn test_sort_rows() {
+        let mut rows = sample();
+        sort_rows(&mut rows, ListSort::Name);
+        assert_eq!(ids(&rows), ["git", "go", "jdk", "mysql", "pgsql", "redis"]);
+
+        sort_rows(&mut rows, ListSort::InstalledAt);
+        assert_eq!(ids(&rows), ["go", "mysql", "jdk", "git", "pgsql", "redis"]);
+
+        // 按数值而不是字符串比较：1.24.0 < 2.47.1 < 8.4.3 < 21.0.5
+        sort_rows(&mut rows, ListSort::Version);
+        assert_eq!(ids(&rows), ["go", "git", "mysql", "jdk", "pgsql", "redis"]);
+    }
+
+    #[test]
+    fn test_version_parts() {
+        assert_eq!(version_parts("git version 2.47.1.windows.1"), [2, 47, 1]);
+        assert_eq!(version_parts("v20.11.0"), [20, 11, 0]);
+        assert_eq!(version_parts("JDK 21"), [21]);
+        assert!(version_parts("latest").is_empty());
+    }
+
+    #[test]
+    fn test_row_json() {
+        let json = serde_json::to_value(&sample()[0]).unwrap();
+        assert_eq!(json["category"], "database");
+        assert_eq!(json["status"], "hudo");
+        assert_eq!(json["installed_at"], "2025-03-01 10:00:00");
+    }
+}
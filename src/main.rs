@@ -1,86 +1,485 @@
-mod cc;
-mod cli;
-mod config;
-mod download;
-mod env;
-mod installer;
-mod profile;
-mod registry;
-mod ui;
-mod version;
+use hudo::{ai, arch, cache, catalog, cli, config, defender, download, env, installer, lang, list, mirrors, paths, profile, registry, running, status, takeover, ui, version, wsl};
+use hudo::lang::{t, tf};
+#[cfg(windows)]
+use hudo::{proc, scan, shortcut};
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use cli::{Cli, Commands, ConfigAction};
+use cli::{CacheAction, CcAction, Cli, Commands, ConfigAction, EnvVarAction, ExportTarget, MirrorsAction, WslAction};
 use config::HudoConfig;
-use dialoguer::{Confirm, Input, MultiSelect, Select, theme::ColorfulTheme};
+#[cfg(windows)]
+use config::DriveType;
+use dialoguer::{Confirm, Input, MultiSelect, Select};
 use installer::{DetectResult, InstallContext, EnvAction, all_installers};
 
-/// 确保配置已初始化（首次运行引导用户选择安装盘）
-fn ensure_config() -> Result<HudoConfig> {
+/// 全局命令行选项（对所有子命令生效）
+struct GlobalOpts {
+    /// 磁盘列表包含可移动磁盘与网络驱动器
+    all_drives: bool,
+    /// 允许安装目录位于同步目录、网络驱动器或可移动磁盘（--allow-synced-root）
+    allow_synced_root: bool,
+    /// 下载缓存目录覆盖（--cache-dir 优先于 HUDO_CACHE_DIR）
+    cache_dir: Option<std::path::PathBuf>,
+    /// 目标架构覆盖（--arch）
+    arch: Option<arch::Arch>,
+    /// 下载限速覆盖（--limit-rate），不写入配置文件
+    limit_rate: Option<u64>,
+}
+
+/// 确保配置已初始化（首次运行引导用户选择安装盘），并应用全局选项
+fn ensure_config(opts: &GlobalOpts) -> Result<HudoConfig> {
+    let mut config = load_or_init_config(opts)?;
+    config.cache_dir_override = opts.cache_dir.clone();
+    config.arch_override = opts.arch;
+    version::init(&config);
+    download::init(&config);
+    if let Some(bps) = opts.limit_rate {
+        download::set_max_bps(bps);
+    }
+    retry_pending_deletes(&config);
+    Ok(config)
+}
+
+/// 清理上次卸载时因文件被占用留下的目录
+fn retry_pending_deletes(config: &HudoConfig) {
+    let Ok(mut reg) = registry::InstallRegistry::load(&config.state_path()) else {
+        return;
+    };
+    if reg.pending_deletes.is_empty() {
+        return;
+    }
+    let cleaned = reg.retry_pending_deletes();
+    if !cleaned.is_empty() && reg.save(&config.state_path()).is_ok() {
+        for path in &cleaned {
+            ui::print_info(&format!("已清理上次卸载的残留: {}", path));
+        }
+    }
+}
+
+fn load_or_init_config(opts: &GlobalOpts) -> Result<HudoConfig> {
     if let Some(config) = HudoConfig::load()? {
-        return Ok(config);
+        return match config.missing_root_drive() {
+            Some(drive) => recover_missing_drive(config, &drive, opts),
+            None => Ok(config),
+        };
     }
 
     // 首次运行，引导用户选择安装目录
     ui::print_banner();
     ui::print_title("首次运行 — 选择安装目录");
 
-    let root_dir = {
-        #[cfg(windows)]
-        { ensure_config_windows()? }
-        #[cfg(not(windows))]
-        { ensure_config_unix()? }
-    };
+    let root_dir = select_root_dir(opts.all_drives)?;
+    guard_root_dir(&root_dir, opts.allow_synced_root)?;
 
     let config = HudoConfig {
         root_dir: root_dir.clone(),
+        check_updates: true,
+        db_autoservice: true,
+        git: Default::default(),
         java: Default::default(),
         go: Default::default(),
+        mysql: Default::default(),
+        pgsql: Default::default(),
+        install: Default::default(),
         versions: Default::default(),
         mirrors: Default::default(),
+        channels: Default::default(),
+        desktop_shortcuts: None,
+        github_token: None,
+        lang: None,
+        max_download_bps: None,
+        cache_dir_override: None,
+        arch_override: None,
+        ask_external_again: false,
+        reinstall: false,
+        yes: false,
+        dry_run: false,
     };
 
     config.save()?;
     config.ensure_dirs()?;
     ui::print_success(&format!("已创建 {}", root_dir));
+    offer_defender_exclusion(&config);
 
     Ok(config)
 }
 
+/// 首次运行时，Defender 正在工作且可以提权的情况下询问是否添加排除项（默认否）
+fn offer_defender_exclusion(config: &HudoConfig) {
+    if defender::status() != defender::DefenderStatus::Active || !defender::elevation_available() {
+        return;
+    }
+    println!();
+    ui::print_info("Windows Defender 实时扫描会明显拖慢编译和依赖安装（node_modules、cargo 构建）");
+    ui::print_info(defender::SECURITY_NOTE);
+    let wanted = Confirm::new()
+        .with_prompt("  是否将安装目录加入 Defender 排除项？（需管理员权限，之后可用 hudo config defender-exclude 设置）")
+        .default(false)
+        .interact()
+        .unwrap_or(false);
+    if wanted {
+        if let Err(e) = cmd_defender_exclude(config) {
+            ui::print_warning(&format!("未能添加排除项: {}", e));
+        }
+    }
+}
+
+/// 安装目录位于同步目录、网络驱动器或可移动磁盘时给出警告，未加 --allow-synced-root 则拒绝
+fn guard_root_dir(root_dir: &str, allow: bool) -> Result<()> {
+    let Some(risk) = config::root_dir_risk(std::path::Path::new(root_dir)) else {
+        return Ok(());
+    };
+    ui::print_warning(&format!("安装目录 {} {}", root_dir, risk.describe()));
+    ui::print_warning("同步客户端会反复上传工具文件，数据库数据目录在同步或磁盘断开时可能损坏");
+    if allow {
+        ui::print_info("已指定 --allow-synced-root，继续使用该目录");
+        return Ok(());
+    }
+    anyhow::bail!("请选择本地磁盘上不被同步的目录，或加 --allow-synced-root 继续")
+}
+
+/// 修改 root_dir 不会移动已安装的工具：安装记录（state.json）在旧目录里，切换后 hudo list 看不到它们，
+/// 环境变量与快捷方式仍指向旧目录。旧目录中有 hudo 安装的工具时列出并确认，-y 时直接继续
+fn confirm_root_change(config: &HudoConfig, new_root: &str, yes: bool) -> Result<bool> {
+    let mut changed = config.clone();
+    changed.root_dir = new_root.to_string();
+    if changed.root_path() == config.root_path() {
+        return Ok(true);
+    }
+    // 旧目录读不了（如磁盘已拔出）时无从提醒，直接修改
+    let Ok(reg) = registry::InstallRegistry::load(&config.state_path()) else {
+        return Ok(true);
+    };
+    if reg.tools.is_empty() {
+        return Ok(true);
+    }
+    let mut ids: Vec<&str> = reg.tools.keys().map(|id| id.as_str()).collect();
+    ids.sort();
+    ui::print_warning(&format!(
+        "{} 中有 {} 个由 hudo 安装的工具: {}",
+        config.root_dir,
+        ids.len(),
+        ids.join(", ")
+    ));
+    ui::print_warning("修改 root_dir 不会移动它们：之后 hudo list / uninstall 不再管理这些工具，环境变量仍指向旧目录");
+    ui::print_info(&format!("要连同工具一起搬到新目录，请改用 hudo config migrate-root {}", new_root));
+    if yes {
+        return Ok(true);
+    }
+    Confirm::with_theme(&ui::theme())
+        .with_prompt("仍要修改 root_dir？")
+        .default(false)
+        .interact()
+        .context("确认被取消")
+}
+
+/// hudo config migrate-root：把旧安装目录中的 tools / lang / ide / kept / cache 与 state.json 移到新目录，
+/// 改写安装记录中的路径并修改 root_dir，再按新路径重设环境变量、快捷方式与 PowerShell profile。
+/// 注册为 Windows 服务的数据库记录的是旧路径，需先卸载再迁移
+fn cmd_config_migrate_root(config: &HudoConfig, new_root: &str, yes: bool) -> Result<()> {
+    let mut target = config.clone();
+    target.root_dir = new_root.to_string();
+    let (old, new) = (config.root_path(), target.root_path());
+    if new == old {
+        ui::print_info(&format!("{} 已是当前安装目录", old.display()));
+        return Ok(());
+    }
+    if new.starts_with(&old) {
+        anyhow::bail!("新目录 {} 位于当前安装目录 {} 之内，请换一个目录", new.display(), old.display());
+    }
+    if target.state_path().exists() {
+        anyhow::bail!("{} 中已有 hudo 的安装记录（state.json），请换一个空目录", new.display());
+    }
+
+    let installers = all_installers();
+    let mut reg = registry::InstallRegistry::load(&config.state_path())?;
+    let installed: Vec<&dyn installer::Installer> = installers
+        .iter()
+        .map(|i| i.as_ref())
+        .filter(|i| reg.get(i.info().id).is_some())
+        .collect();
+    #[cfg(windows)]
+    {
+        let services: Vec<&str> = installed
+            .iter()
+            .filter(|i| i.service_name().is_some_and(installer::query_service_exists))
+            .map(|i| i.info().id)
+            .collect();
+        if !services.is_empty() {
+            anyhow::bail!(
+                "{} 已注册为 Windows 服务，服务记录的程序路径不会随目录移动；\
+                 请先 hudo uninstall <工具> --keep-config 卸载（数据保留在 kept 目录中一并迁移），迁移后再重新安装",
+                services.join(", ")
+            );
+        }
+    }
+
+    // 要移动的条目：存在的子目录，state.json 最后移动
+    let mut entries = vec![config.tools_dir(), config.lang_dir(), config.ide_dir(), old.join("kept")];
+    if config.cache_dir().starts_with(&old) {
+        entries.push(config.cache_dir());
+    }
+    entries.retain(|p| p.exists());
+    if config.state_path().exists() {
+        entries.push(config.state_path());
+    }
+    if entries.is_empty() {
+        ui::print_info(&format!("{} 中没有需要迁移的内容，直接修改 root_dir", old.display()));
+        return cmd_config_set(&mut config.clone(), "root_dir", new_root);
+    }
+    let moves: Vec<(std::path::PathBuf, std::path::PathBuf)> = entries
+        .iter()
+        .map(|from| (from.clone(), new.join(from.strip_prefix(&old).unwrap_or(from))))
+        .collect();
+    if let Some((_, to)) = moves.iter().find(|(_, to)| to.exists()) {
+        anyhow::bail!("{} 已存在，请换一个空目录或先清理", to.display());
+    }
+
+    let mut ids: Vec<&str> = installed.iter().map(|i| i.info().id).collect();
+    ids.sort();
+    ui::print_title("迁移安装目录");
+    ui::print_info(&format!("{} {} {}", old.display(), ui::Glyph::Arrow, new.display()));
+    if !ids.is_empty() {
+        ui::print_info(&format!("工具: {}", ids.join(", ")));
+    }
+    if !yes
+        && !Confirm::with_theme(&ui::theme())
+            .with_prompt("开始迁移？")
+            .default(true)
+            .interact()
+            .context("确认被取消")?
+    {
+        ui::print_info("已取消，root_dir 未修改");
+        return Ok(());
+    }
+    let dirs: Vec<&std::path::Path> = entries.iter().map(|p| p.as_path()).collect();
+    if !running::ensure_closed(&dirs, "hudo 安装的工具", yes)? {
+        anyhow::bail!("已取消迁移");
+    }
+
+    // 1. 移动文件；中途失败时把已移动的条目移回原处
+    std::fs::create_dir_all(&new).with_context(|| format!("无法创建目录: {}", new.display()))?;
+    let mut residual = Vec::new();
+    for (i, (from, to)) in moves.iter().enumerate() {
+        if let Err(e) = move_entry(from, to, &mut residual) {
+            for (from, to) in moves[..i].iter().rev() {
+                if let Err(e) = move_entry(to, from, &mut Vec::new()) {
+                    ui::print_warning(&format!("未能移回 {}: {:#}", to.display(), e));
+                }
+            }
+            return Err(e.context("迁移失败，已移回原目录，root_dir 未修改"));
+        }
+        ui::print_info(&format!("已移动 {}", from.display()));
+    }
+
+    // 2. 改写安装记录；复制后未能删除的旧文件下次运行时重试
+    let old_paths: Vec<std::path::PathBuf> = installed
+        .iter()
+        .filter_map(|i| reg.get(i.info().id))
+        .map(|state| std::path::PathBuf::from(&state.install_path))
+        .collect();
+    reg.rebase_paths(&old, &new);
+    for dir in &residual {
+        reg.add_pending_delete(dir);
+    }
+    reg.save(&target.state_path())?;
+    // 文件与安装记录已在新目录：立即修改 root_dir，之后的步骤失败也不会让 hudo 指向已搬空的旧目录
+    cmd_config_set(&mut config.clone(), "root_dir", new_root)?;
+
+    // 3. 按新路径重设环境变量、快捷方式与 PowerShell profile；逐个工具进行，失败只警告
+    let mut env_failed = Vec::new();
+    for (inst, old_path) in installed.iter().zip(&old_paths) {
+        let id = inst.info().id;
+        let Some(state) = reg.get(id) else {
+            continue;
+        };
+        let new_path = std::path::PathBuf::from(&state.install_path);
+        if !state.env_pending {
+            let rewired = remove_env_actions(&inst.env_actions(old_path, config))
+                .and_then(|()| apply_env_actions(&inst.env_actions(&new_path, &target)));
+            if let Err(e) = rewired {
+                ui::print_warning(&format!("未能更新 {} 的环境变量: {:#}", inst.info().name, e));
+                env_failed.push(id);
+            }
+        }
+        #[cfg(windows)]
+        {
+            shortcut::remove_all(&inst.shortcuts(old_path));
+            create_shortcuts(&target, *inst, &new_path, false);
+            match installer::remove_shell_integration(*inst, old_path) {
+                Ok(Some(_)) => installer::write_shell_integration(&InstallContext::new(&target), *inst, &new_path),
+                Ok(None) => {}
+                Err(e) => ui::print_warning(&format!("未能更新 {} 的 PowerShell profile: {:#}", inst.info().name, e)),
+            }
+        }
+    }
+    // 环境变量未更新的工具标记为待应用，之后用 hudo env apply 重试
+    if !env_failed.is_empty() {
+        for id in &env_failed {
+            reg.set_env_pending(id, true);
+        }
+        if let Err(e) = reg.save(&target.state_path()) {
+            ui::print_warning(&format!("{:#}", e));
+        }
+    }
+
+    ui::print_success(&format!("已迁移到 {}，共 {} 个工具", new.display(), installed.len()));
+    if !env_failed.is_empty() {
+        ui::print_warning(&format!(
+            "{} 的环境变量仍指向旧目录，请运行 hudo env apply 重新应用",
+            env_failed.join(", ")
+        ));
+    }
+    if !residual.is_empty() {
+        ui::print_info("部分旧文件被占用未能删除，将在下次运行 hudo 时自动清理");
+    }
+    if !reg.defender_exclusions.is_empty() {
+        ui::print_info("Windows Defender 排除项仍是旧目录，可运行 hudo config defender-exclude 为新目录添加");
+    }
+    Ok(())
+}
+
+/// 移动文件或目录：同一磁盘内直接重命名，跨磁盘时复制后删除原处；复制成功但删除失败的原目录记入 residual
+fn move_entry(from: &std::path::Path, to: &std::path::Path, residual: &mut Vec<std::path::PathBuf>) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("无法创建目录: {}", parent.display()))?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if from.is_dir() {
+        if let Err(e) = download::copy_dir_all(from, to) {
+            std::fs::remove_dir_all(to).ok();
+            return Err(e);
+        }
+        if let Err(e) = std::fs::remove_dir_all(from) {
+            ui::print_warning(&format!("已复制到新目录，但未能删除 {}: {}", from.display(), e));
+            residual.push(from.to_path_buf());
+        }
+    } else {
+        std::fs::copy(from, to).with_context(|| format!("复制文件失败: {}", from.display()))?;
+        std::fs::remove_file(from).with_context(|| format!("无法删除 {}", from.display()))?;
+    }
+    Ok(())
+}
+
+/// 交互选择安装根目录（Windows 选盘符，Unix 输入路径）
+fn select_root_dir(all_drives: bool) -> Result<String> {
+    #[cfg(windows)]
+    { ensure_config_windows(all_drives) }
+    #[cfg(not(windows))]
+    {
+        let _ = all_drives;
+        ensure_config_unix()
+    }
+}
+
+/// root_dir 所在磁盘不存在（如移动硬盘已拔出）：提示重新选择磁盘或编辑配置
+fn recover_missing_drive(mut config: HudoConfig, drive: &str, opts: &GlobalOpts) -> Result<HudoConfig> {
+    ui::print_error(&format!(
+        "安装目录 {} 所在磁盘 {} 不存在（移动硬盘或网络驱动器可能已断开）",
+        config.root_dir, drive
+    ));
+    ui::print_info("若磁盘只是暂时断开，重新连接后再运行 hudo 即可");
+
+    loop {
+        println!();
+        let items = ["重新选择安装磁盘", "编辑配置文件", "退出"];
+        let sel = Select::with_theme(&ui::theme())
+            .with_prompt("如何处理")
+            .items(&items)
+            .default(0)
+            .interact_opt()
+            .context("选择被取消")?;
+
+        match sel {
+            Some(0) => {
+                ui::print_warning("原目录中已安装的工具将不再由 hudo 管理");
+                let root_dir = select_root_dir(opts.all_drives)?;
+                guard_root_dir(&root_dir, opts.allow_synced_root)?;
+                config.root_dir = root_dir.clone();
+                config.save()?;
+                config.ensure_dirs()?;
+                ui::print_success(&format!("安装目录已切换为 {}", root_dir));
+                return Ok(config);
+            }
+            Some(1) => {
+                cmd_config_edit()?;
+                config = HudoConfig::load()?.context("配置文件不存在")?;
+                match config.missing_root_drive() {
+                    Some(d) => ui::print_error(&format!("磁盘 {} 仍不存在", d)),
+                    None => return Ok(config),
+                }
+            }
+            _ => anyhow::bail!(
+                "磁盘 {} 不存在，请重新连接，或运行 hudo config set root_dir <新目录> 修改",
+                drive
+            ),
+        }
+    }
+}
+
 /// Windows：扫描盘符让用户选择
 #[cfg(windows)]
-fn ensure_config_windows() -> Result<String> {
+fn ensure_config_windows(all_drives: bool) -> Result<String> {
     println!("  {}", console::style("所有开发工具将安装到所选磁盘的 hudo 目录下").dim());
 
-    let drives = HudoConfig::scan_drives();
+    // 本地磁盘在前；可移动磁盘、网络驱动器等默认隐藏（无本地磁盘时仍列出），--all-drives 时排在最后
+    let (mut drives, others): (Vec<_>, Vec<_>) = HudoConfig::scan_drives()
+        .into_iter()
+        .partition(|d| d.drive_type == DriveType::Fixed);
+    if all_drives || drives.is_empty() {
+        drives.extend(others);
+    } else if !others.is_empty() {
+        println!(
+            "  {}",
+            console::style(format!(
+                "已隐藏 {} 个可移动磁盘/网络驱动器，使用 --all-drives 显示",
+                others.len()
+            ))
+            .dim()
+        );
+    }
     if drives.is_empty() {
         anyhow::bail!("未检测到可用磁盘");
     }
 
+    let label_width = drives
+        .iter()
+        .map(|d| console::measure_text_width(&d.label))
+        .max()
+        .unwrap_or(0)
+        .max(4);
     let items: Vec<String> = drives
         .iter()
         .map(|d| {
+            let label = if d.label.is_empty() { "-" } else { d.label.as_str() };
+            let mut item = format!(
+                "{}:  {}  {}  {}",
+                d.letter,
+                ui::pad(label, label_width),
+                ui::pad(d.drive_type.label(), 10),
+                ui::pad(&format!("{}GB 可用", d.free_gb), 12),
+            );
             if d.is_system {
-                format!(
-                    "{}:  {}  {}",
-                    d.letter,
-                    ui::pad(&format!("{}GB 可用", d.free_gb), 12),
-                    console::style("(系统盘)").dim()
-                )
+                item.push_str(&format!("  {}", console::style("(系统盘)").dim()));
+            }
+            if d.drive_type == DriveType::Fixed {
+                item
             } else {
-                format!("{}:  {}GB 可用", d.letter, d.free_gb)
+                console::style(item).dim().to_string()
             }
         })
         .collect();
 
+    // 默认选中第一个非系统本地磁盘
     let default = drives
         .iter()
-        .position(|d| !d.is_system)
+        .position(|d| !d.is_system && d.drive_type == DriveType::Fixed)
         .unwrap_or(0);
 
     println!();
-    let selection = Select::with_theme(&ColorfulTheme::default())
+    let selection = Select::with_theme(&ui::theme())
         .items(&items)
         .default(default)
         .interact()
@@ -110,7 +509,7 @@ fn ensure_config_windows() -> Result<String> {
 
 /// Windows stub（Unix 编译时不需要）
 #[cfg(not(windows))]
-fn ensure_config_windows() -> Result<String> {
+fn ensure_config_windows(_all_drives: bool) -> Result<String> {
     unreachable!()
 }
 
@@ -122,7 +521,7 @@ fn ensure_config_unix() -> Result<String> {
     println!("  {}", console::style(format!("默认安装目录: {}", default_dir)).dim());
 
     println!();
-    let root_dir: String = Input::with_theme(&ColorfulTheme::default())
+    let root_dir: String = Input::with_theme(&ui::theme())
         .with_prompt("安装目录")
         .default(default_dir)
         .interact_text()
@@ -131,66 +530,89 @@ fn ensure_config_unix() -> Result<String> {
     Ok(root_dir)
 }
 
-/// 交互式多选安装（两级：先选分类，再选工具）
-async fn cmd_setup(config: &HudoConfig) -> Result<()> {
+/// 交互式多选安装（两级：先选分类，再选工具；flat 为 true 时直接在全部工具中筛选）
+async fn cmd_setup(config: &HudoConfig, flat: bool) -> Result<()> {
+    // 首次使用（尚无 hudo 安装记录）时，先展示系统中已有的工具
+    #[cfg(windows)]
+    if registry::InstallRegistry::load(&config.state_path())?.tools.is_empty() {
+        let found = scan::scan_registry();
+        if !found.is_empty() {
+            ui::print_title("系统中已安装的工具");
+            print_discovered(config, &found)?;
+            println!();
+            ui::print_info("这些工具在后续列表中标记为已安装（非 hudo），选中后可由 hudo 接管");
+            println!();
+            println!("  {}", console::style("按任意键继续...").dim());
+            let _ = console::Term::stderr().read_key();
+        }
+    }
+
     let installers = all_installers();
-    let categories = [
-        ui::ToolCategory::Tool,
-        ui::ToolCategory::Language,
-        ui::ToolCategory::Database,
-        ui::ToolCategory::Ide,
-    ];
+    if flat {
+        return setup_flat(config, &installers).await;
+    }
 
     loop {
-        ui::page_header("选择工具分类");
-
-        // 构建分类菜单项，显示每个分类的工具数量
-        let cat_labels: Vec<String> = categories
-            .iter()
-            .map(|cat| {
-                let count = installers
-                    .iter()
-                    .filter(|i| {
-                        std::mem::discriminant(&ui::ToolCategory::from_id(i.info().id))
-                            == std::mem::discriminant(cat)
-                    })
-                    .count();
-                let icon = cat.icon();
-                format!("{}  {}  {}", icon, ui::pad(cat.label(), 14), console::style(format!("{} 个工具", count)).dim())
-            })
-            .collect();
-
-        let cat_sel = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("选择分类 (Esc 返回)")
+        ui::page_header(t("setup.choose_category"));
+
+        // 构建分类菜单项，显示每个分类的工具数量；首项为不分类的「全部工具」
+        let mut cat_labels = vec![format!(
+            "[*]  {}  {}",
+            ui::pad(t("setup.all_tools"), 14),
+            console::style(tf("setup.tool_count", &[&installers.len()])).dim()
+        )];
+        cat_labels.extend(SETUP_CATEGORIES.iter().map(|cat| {
+            let count = category_tools(&installers, cat).len();
+            let icon = cat.icon();
+            format!("{}  {}  {}", icon, ui::pad(cat.label(), 14), console::style(tf("setup.tool_count", &[&count])).dim())
+        }));
+
+        let cat_sel = Select::with_theme(&ui::theme())
+            .with_prompt(t("setup.category_prompt"))
             .items(&cat_labels)
             .default(0)
             .interact_opt()
             .context("选择被取消")?;
 
         let cat_idx = match cat_sel {
-            Some(i) => i,
+            Some(0) => {
+                setup_flat(config, &installers).await?;
+                continue;
+            }
+            Some(i) => i - 1,
             None => break,
         };
 
-        // 筛选该分类下的工具
-        let cat = &categories[cat_idx];
-        let cat_tools: Vec<usize> = installers
-            .iter()
-            .enumerate()
-            .filter(|(_, i)| {
-                std::mem::discriminant(&ui::ToolCategory::from_id(i.info().id))
-                    == std::mem::discriminant(cat)
-            })
-            .map(|(idx, _)| idx)
-            .collect();
-
         // 进入分类内的工具多选
+        let cat = &SETUP_CATEGORIES[cat_idx];
+        let cat_tools = category_tools(&installers, cat);
         setup_category(config, &installers, &cat_tools, cat.label()).await?;
     }
 
     Ok(())
 }
 
+/// setup 菜单中的分类及显示顺序
+const SETUP_CATEGORIES: [ui::ToolCategory; 4] = [
+    ui::ToolCategory::Tool,
+    ui::ToolCategory::Language,
+    ui::ToolCategory::Database,
+    ui::ToolCategory::Ide,
+];
+
+/// 属于指定分类的工具下标
+fn category_tools(installers: &[Box<dyn installer::Installer>], cat: &ui::ToolCategory) -> Vec<usize> {
+    installers
+        .iter()
+        .enumerate()
+        .filter(|(_, i)| {
+            std::mem::discriminant(&ui::ToolCategory::from_id(i.info().id))
+                == std::mem::discriminant(cat)
+        })
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
 /// 分类内的工具多选安装
 async fn setup_category(
     config: &HudoConfig,
@@ -198,58 +620,110 @@ async fn setup_category(
     tool_indices: &[usize],
     cat_name: &str,
 ) -> Result<()> {
-    ui::page_header(&format!("{} — 选择要安装的工具", cat_name));
+    ui::page_header(&tf("setup.select_tools", &[&cat_name]));
 
-    let reg = registry::InstallRegistry::load(&config.state_path())?;
+    let labels = tool_labels(config, installers, tool_indices)?;
+    let items: Vec<_> = labels.into_iter().zip(tool_indices.iter().map(|&i| Some(i))).collect();
+    select_and_install(config, installers, &items).await
+}
 
-    // 并行检测该分类下所有工具的安装状态
-    let tool_refs: Vec<&dyn installer::Installer> =
-        tool_indices.iter().map(|&i| installers[i].as_ref()).collect();
-    let tool_data = detect_all_parallel(&tool_refs, config, &reg);
+/// 全部工具平铺多选：先按关键字筛选，再按分类分组显示
+async fn setup_flat(config: &HudoConfig, installers: &[Box<dyn installer::Installer>]) -> Result<()> {
+    ui::page_header(&tf("setup.select_tools", &[&t("setup.all_tools")]));
+
+    let keyword: String = Input::with_theme(&ui::theme())
+        .with_prompt(t("setup.filter_prompt"))
+        .allow_empty(true)
+        .interact_text()
+        .context("输入被取消")?;
+
+    let groups: Vec<(&ui::ToolCategory, Vec<usize>)> = SETUP_CATEGORIES
+        .iter()
+        .map(|cat| {
+            let matched = category_tools(installers, cat)
+                .into_iter()
+                .filter(|&i| matches_keyword(&installers[i].info(), &keyword))
+                .collect::<Vec<_>>();
+            (cat, matched)
+        })
+        .filter(|(_, matched)| !matched.is_empty())
+        .collect();
+    if groups.is_empty() {
+        ui::print_info(&tf("setup.no_match", &[&keyword.trim()]));
+        ui::wait_for_key();
+        return Ok(());
+    }
 
-    // 计算动态列宽
-    let mut name_width = 0usize;
-    let mut desc_width = 0usize;
-    for (info, _) in &tool_data {
-        name_width = name_width.max(console::measure_text_width(info.name));
-        desc_width = desc_width.max(console::measure_text_width(info.description));
+    // 所有分组一起检测、一起计算列宽，保证各组对齐
+    let tool_indices: Vec<usize> = groups.iter().flat_map(|(_, m)| m.iter().copied()).collect();
+    let mut labels = tool_labels(config, installers, &tool_indices)?.into_iter();
+    let mut items = Vec::new();
+    for (cat, matched) in &groups {
+        let rule = ui::Glyph::Rule.as_str().repeat(2);
+        let header = format!("{} {} {} {}", rule, cat.icon(), cat.label(), rule);
+        items.push((format!("{}", console::style(header).dim()), None));
+        for &idx in matched {
+            items.push((labels.next().unwrap_or_default(), Some(idx)));
+        }
     }
+    select_and_install(config, installers, &items).await
+}
+
+/// 关键字（空格分隔，均需命中）是否匹配工具的 id、名称或描述，不区分大小写
+fn matches_keyword(info: &installer::ToolInfo, keyword: &str) -> bool {
+    let haystack = format!("{} {} {}", info.id, info.name, info.description).to_lowercase();
+    keyword
+        .split_whitespace()
+        .all(|term| haystack.contains(&term.to_lowercase()))
+}
 
-    // 加 2 列间距
-    name_width += 2;
-    desc_width += 2;
+/// 并行检测工具安装状态，按动态列宽生成多选列表的标签
+fn tool_labels(
+    config: &HudoConfig,
+    installers: &[Box<dyn installer::Installer>],
+    tool_indices: &[usize],
+) -> Result<Vec<String>> {
+    let reg = registry::InstallRegistry::load(&config.state_path())?;
 
-    // 第二轮：构建标签
-    let mut labels = Vec::new();
-    let mut defaults = Vec::new();
+    // 并行检测所有工具的安装状态
+    let tool_refs: Vec<&dyn installer::Installer> =
+        tool_indices.iter().map(|&i| installers[i].as_ref()).collect();
+    let tool_data = detect_all_parallel(&tool_refs, config, Some(&reg));
 
+    // 按列对齐（列宽按去掉颜色后的显示宽度计算）
+    let mut table = ui::Table::new(0);
     for (info, detect) in &tool_data {
         let status = match detect {
             Ok(DetectResult::InstalledByHudo(ver)) => {
                 let short = truncate_version(ver, 16);
-                format!("{}", console::style(format!("✓ hudo {}", short)).green())
+                format!("{}", console::style(format!("{} hudo {}", ui::Glyph::Success, short)).green())
             }
             Ok(DetectResult::InstalledExternal(ver)) => {
                 let short = truncate_version(ver, 16);
-                format!("{}", console::style(format!("● 系统 {}", short)).yellow())
+                format!("{}", console::style(format!("{} 系统 {}", ui::Glyph::Bullet, short)).yellow())
             }
             Ok(DetectResult::NotInstalled) => String::new(),
-            Err(_) => format!("{}", console::style("✗ 检测失败").red()),
+            Err(_) => format!("{}", console::style(format!("{} 检测失败", ui::Glyph::Error)).red()),
         };
 
-        labels.push(format!(
-            "{}  {}  {}",
-            console::style(ui::pad(info.name, name_width)).bold(),
-            ui::pad(info.description, desc_width),
-            status
-        ));
-        defaults.push(false);
+        table.row(vec![console::style(info.name).bold().to_string(), info.description.to_string(), status]);
     }
+    Ok(table.render())
+}
+
+/// 多选后确认并逐个安装。items 为（标签, 工具下标），下标为 None 的是分组标题，勾选后忽略
+async fn select_and_install(
+    config: &HudoConfig,
+    installers: &[Box<dyn installer::Installer>],
+    items: &[(String, Option<usize>)],
+) -> Result<()> {
+    let labels: Vec<&str> = items.iter().map(|(label, _)| label.as_str()).collect();
+    let defaults = vec![false; labels.len()];
 
-    println!("  {}", console::style("空格勾选/取消，回车确认，Esc 返回").dim());
+    println!("  {}", console::style(t("setup.select_hint")).dim());
     println!();
 
-    let selections = MultiSelect::with_theme(&ColorfulTheme::default())
+    let selections = MultiSelect::with_theme(&ui::theme())
         .items(&labels)
         .defaults(&defaults)
         .interact_opt()
@@ -258,211 +732,629 @@ async fn setup_category(
     let selections = match selections {
         Some(s) => s,
         None => {
-            ui::print_info("已取消");
+            ui::print_info(t("common.cancelled"));
             return Ok(());
         }
     };
 
+    let selections: Vec<usize> = selections.iter().filter_map(|&i| items[i].1).collect();
     if selections.is_empty() {
-        ui::print_info("未选择任何工具");
+        ui::print_info(t("setup.nothing_selected"));
         return Ok(());
     }
 
-    // 确认
+    // 确认：列出每个工具的预计下载大小
+    println!();
+    println!("  {}", console::style(t("setup.querying_sizes")).dim());
+    let targets: Vec<(String, String)> = selections
+        .iter()
+        .map(|&i| installers[i].resolve_download(config))
+        .collect();
+    let sizes = download::estimate_sizes(&config.cache_dir(), &targets).await;
     let selected_names: Vec<_> = selections
         .iter()
-        .map(|&i| installers[tool_indices[i]].info().name)
+        .map(|&i| installers[i].info().name)
         .collect();
-    println!();
     println!(
-        "  即将安装 {} 个工具: {}",
-        console::style(selected_names.len()).cyan().bold(),
-        selected_names.join(", ")
+        "  {}",
+        tf("setup.about_to_install", &[&console::style(selected_names.len()).cyan().bold()])
     );
+    print_size_estimates(&selected_names, &sizes);
     let confirm = Confirm::new()
-        .with_prompt("  确认开始？")
+        .with_prompt(format!("  {}", t("setup.confirm")))
         .default(true)
         .interact()
         .context("确认被取消")?;
 
     if !confirm {
-        ui::print_info("已取消");
+        ui::print_info(t("common.cancelled"));
         return Ok(());
     }
 
     // 逐个安装
     let total = selections.len();
-    let mut success_count = 0u32;
-    let mut fail_names = Vec::new();
+    let started = std::time::Instant::now();
+    let mut timings = Vec::new();
 
     for (idx, &sel) in selections.iter().enumerate() {
-        let info = installers[tool_indices[sel]].info();
+        let info = installers[sel].info();
         println!();
         ui::print_step(
             (idx + 1) as u32,
             total as u32,
-            &format!("安装 {}", info.name),
+            &tf("install.title", &[&info.name]),
         );
-        if let Err(e) = cmd_install(config, info.id).await {
-            ui::print_error(&format!("{} 安装失败: {}", info.name, e));
-            fail_names.push(info.name);
+        let start = std::time::Instant::now();
+        let result = cmd_install(config, info.id, false).await;
+        timings.push((info.name, start.elapsed(), result.is_ok()));
+        if let Err(e) = result {
+            ui::print_error(&tf("install.failed", &[&info.name, &e]));
             let cont = Confirm::new()
-                .with_prompt("  是否继续安装其余工具？")
+                .with_prompt(format!("  {}", t("setup.continue_prompt")))
                 .default(true)
                 .interact()
                 .unwrap_or(false);
             if !cont {
-                anyhow::bail!("用户中止安装");
+                print_install_summary(&timings, started.elapsed());
+                anyhow::bail!(t("setup.aborted"));
             }
-        } else {
-            success_count += 1;
         }
     }
 
-    // 汇总
+    print_install_summary(&timings, started.elapsed());
+    ui::print_info(t("setup.open_new_terminal"));
+    ui::wait_for_key();
+    Ok(())
+}
+
+/// 每个工具的预计下载大小与合计，标出大小未知的工具
+fn print_size_estimates(names: &[&str], sizes: &[download::SizeEstimate]) {
+    let width = names.iter().map(|n| console::measure_text_width(n)).max().unwrap_or(0) + 2;
+    for (name, size) in names.iter().zip(sizes) {
+        let label = match size {
+            download::SizeEstimate::Bytes(n) => ui::format_size(*n),
+            download::SizeEstimate::Cached => console::style("已缓存").green().to_string(),
+            download::SizeEstimate::Unknown => console::style("大小未知").yellow().to_string(),
+        };
+        println!("    {}  {}", ui::pad(name, width), label);
+    }
+    let total: u64 = sizes.iter().filter_map(|s| s.bytes()).sum();
+    let unknown = sizes.iter().filter(|s| s.bytes().is_none()).count();
+    let note = match unknown {
+        0 => String::new(),
+        n => tf("setup.size_unknown", &[&n]),
+    };
+    println!(
+        "  {}",
+        tf(
+            "setup.download_total",
+            &[&console::style(ui::format_size(total)).cyan().bold(), &console::style(note).dim()]
+        )
+    );
+}
+
+/// 批量安装的汇总：每个工具的结果与耗时、总耗时，以及成功 / 失败数
+fn print_install_summary(timings: &[(&str, std::time::Duration, bool)], wall: std::time::Duration) {
+    println!();
+    println!("{}", console::style(ui::Glyph::Rule.as_str().repeat(40)).cyan());
+    let width = timings.iter().map(|(name, _, _)| console::measure_text_width(name)).max().unwrap_or(0) + 2;
+    for (name, elapsed, ok) in timings {
+        let mark = match ok {
+            true => console::style(ui::Glyph::Success).green(),
+            false => console::style(ui::Glyph::Error).red(),
+        };
+        println!("  {} {}  {}", mark, ui::pad(name, width), console::style(ui::format_duration(*elapsed)).dim());
+    }
+    println!("  {}", console::style(tf("install.total_time", &[&ui::format_duration(wall)])).dim());
     println!();
-    println!("{}", console::style("─".repeat(40)).cyan());
+
+    let fail_names: Vec<&str> = timings.iter().filter(|(_, _, ok)| !ok).map(|(name, _, _)| *name).collect();
+    let success_count = timings.len() - fail_names.len();
     if fail_names.is_empty() {
-        ui::print_success(&format!("全部 {} 个工具安装完成", success_count));
+        ui::print_success(&tf("install.all_done", &[&success_count]));
     } else {
-        ui::print_success(&format!("{} 个工具安装成功", success_count));
-        ui::print_warning(&format!(
-            "{} 个工具安装失败: {}",
-            fail_names.len(),
-            fail_names.join(", ")
-        ));
+        ui::print_success(&tf("install.some_done", &[&success_count]));
+        ui::print_warning(&tf("install.some_failed", &[&fail_names.len(), &fail_names.join(", ")]));
     }
-    ui::print_info("请打开新终端以使环境变量生效");
-    ui::wait_for_key();
-    Ok(())
 }
 
 /// 安装单个工具
-async fn cmd_install(config: &HudoConfig, tool_id: &str) -> Result<()> {
-    cmd_install_inner(config, tool_id, false).await
+async fn cmd_install(config: &HudoConfig, tool_id: &str, pick: bool) -> Result<()> {
+    if !pick {
+        return cmd_install_inner(config, tool_id, false).await;
+    }
+
+    // --pick：交互选择版本，仅对本次安装生效，不写入 config.toml
+    let Some(version) = pick_version(tool_id).await? else {
+        ui::print_info("已取消");
+        return Ok(());
+    };
+    let mut pinned = config.clone();
+    pinned.pin_version(tool_id, &version);
+    cmd_install_inner(&pinned, tool_id, false).await
 }
 
-/// 安装单个工具（内部实现，skip_configure 控制是否跳过交互式配置）
-async fn cmd_install_inner(config: &HudoConfig, tool_id: &str, skip_configure: bool) -> Result<()> {
+/// hudo install --list-deps：列出安装该工具会一并安装的依赖及当前状态，不执行安装
+async fn cmd_list_deps(config: &HudoConfig, tool_id: &str) -> Result<()> {
     let installers = all_installers();
-
-    let available: Vec<_> = installers.iter().map(|i| i.info().id).collect();
-    let inst = installers
-        .iter()
-        .find(|i| i.info().id == tool_id)
-        .ok_or_else(|| {
-            anyhow::anyhow!(
-                "未知工具 '{}'，可用: {}",
-                tool_id,
-                available.join(", ")
-            )
-        })?;
-
+    let find = |id: &str| installers.iter().find(|i| i.info().id == id);
+    let inst = installer::find(&installers, tool_id, "安装")?;
     let info = inst.info();
-    ui::print_title(&format!("安装 {}", info.name));
+    ui::print_title(&format!("{} 的依赖", info.name));
 
-    let ctx = InstallContext { config };
+    let deps = installer::resolve_dependencies(tool_id, |id| {
+        find(id).map(|i| i.dependencies()).unwrap_or(&[])
+    });
+    if deps.is_empty() {
+        ui::print_info(&format!("{} 不依赖其他工具", info.name));
+        return Ok(());
+    }
 
-    // 检测是否已安装
-    let detect = inst.detect_installed(&ctx).await?;
-    match &detect {
-        DetectResult::InstalledByHudo(version) => {
-            ui::print_success(&format!("{} 已安装 (hudo): {}", info.name, version));
-            if !skip_configure {
-                inst.configure(&ctx).await?;
+    let ctx = InstallContext::new(config);
+    let mut missing = 0;
+    for dep in deps {
+        let Some(dep_inst) = find(dep) else {
+            continue;
+        };
+        let status = match dep_inst.detect_installed(&ctx).await {
+            Ok(DetectResult::InstalledByHudo(ver)) => {
+                format!("{}  {}", console::style("已满足").green(), console::style(ver).dim())
             }
-            return Ok(());
-        }
-        DetectResult::InstalledExternal(version) => {
-            ui::print_warning(&format!("{} 已安装在系统其他位置: {}", info.name, version));
-            let reinstall = Confirm::new()
-                .with_prompt("  是否由 hudo 接管？（将清理旧版并重新安装到 hudo 目录）")
-                .default(false)
-                .interact()
-                .context("选择被取消")?;
-            if !reinstall {
-                ui::print_info("跳过安装，使用现有版本");
-                if !skip_configure {
-                    inst.configure(&ctx).await?;
-                }
-                return Ok(());
+            Ok(DetectResult::InstalledExternal(ver)) => format!(
+                "{}  {} {}",
+                console::style("已满足").green(),
+                console::style(ver).dim(),
+                console::style("(非 hudo)").yellow()
+            ),
+            _ => {
+                missing += 1;
+                console::style("将提示一并安装").yellow().to_string()
             }
-            ui::print_step(1, 2, "卸载旧版...");
-            #[cfg(windows)]
-            uninstall_from_system(info.id)?;
+        };
+        println!(
+            "    {}  {}",
+            console::style(ui::pad(dep_inst.info().name, 16)).bold(),
+            status
+        );
+    }
+    if missing == 0 {
+        ui::print_success("依赖均已满足");
+    }
+    Ok(())
+}
+
+/// 列出工具最近的发布版本供用户选择
+async fn pick_version(tool_id: &str) -> Result<Option<String>> {
+    if !version::PICKABLE_TOOLS.contains(&tool_id) {
+        anyhow::bail!(
+            "{} 不支持选择版本，支持: {}",
+            tool_id,
+            version::PICKABLE_TOOLS.join(", ")
+        );
+    }
+
+    ui::print_action("查询可用版本...");
+    let versions = version::recent_versions(tool_id)
+        .await
+        .and_then(|v| match v.is_empty() {
+            true => Err(version::VersionError::NotFound),
+            false => Ok(v),
+        })
+        .context("获取版本列表失败，请检查网络或使用 hudo config set versions.<工具> 指定版本")?;
+
+    let sel = Select::with_theme(&ui::theme())
+        .with_prompt("选择要安装的版本")
+        .items(&versions)
+        .default(0)
+        .interact_opt()
+        .context("选择被取消")?;
+    Ok(sel.map(|i| versions[i].clone()))
+}
+
+/// 固定的版本下载返回 404：说明原因与查看可用版本的方式，查询到最新版本时询问是否改用；
+/// 确认后写入配置文件，返回用于重新安装的配置，否则返回 None（由调用方报告原错误）
+async fn recover_missing_version(
+    config: &HudoConfig,
+    inst: &dyn installer::Installer,
+    missing: &installer::MissingVersion,
+    err: &anyhow::Error,
+) -> Result<Option<HudoConfig>> {
+    let info = inst.info();
+    ui::print_error(&format!("{:#}", err));
+    ui::print_warning(&format!(
+        "{} 配置的版本 {} 不存在（{}），可能是拼写错误或该版本已撤回",
+        info.name, missing.version, missing.key
+    ));
+    if let Some(hint) = inst.versions_hint() {
+        ui::print_info(hint);
+    }
+    if version::PICKABLE_TOOLS.contains(&info.id) {
+        ui::print_info(&format!("运行 hudo install {} --pick 从可用版本中选择", info.id));
+    }
+
+    ui::print_action(&format!("查询 {} 最新版本...", info.name));
+    let latest = match version::latest_for_key(missing.version_key).await {
+        Ok(latest) if latest != missing.version => latest,
+        _ => return Ok(None),
+    };
+    let set_hint = format!("hudo config set {} {}", missing.key, latest);
+    if config.yes {
+        ui::print_info(&format!("最新版本是 {}，运行 {} 改用", latest, set_hint));
+        return Ok(None);
+    }
+    let accept = Confirm::with_theme(&ui::theme())
+        .with_prompt(format!("配置的版本 {} 不存在，最新版本是 {}，是否使用？", missing.version, latest))
+        .default(true)
+        .interact()
+        .unwrap_or(false);
+    if !accept {
+        ui::print_info(&format!("之后可运行 {} 改用最新版本", set_hint));
+        return Ok(None);
+    }
+
+    // 配置文件中的旧值同样无效，一并改掉
+    let mut saved = HudoConfig::load()?.unwrap_or_else(|| config.clone());
+    saved.set_version_key(missing.version_key, &latest);
+    saved.save()?;
+    ui::print_success(&format!("已将 {} 改为 {}", missing.key, latest));
+
+    let mut retry = config.clone();
+    retry.set_version_key(missing.version_key, &latest);
+    Ok(Some(retry))
+}
+
+/// 安装单个工具（内部实现，skip_configure 控制是否跳过交互式配置）
+async fn cmd_install_inner(config: &HudoConfig, tool_id: &str, skip_configure: bool) -> Result<()> {
+    let installers = all_installers();
+
+    let inst = installer::find(&installers, tool_id, "安装")?;
+
+    let info = inst.info();
+    ui::print_title(&tf("install.title", &[&info.name]));
+
+    let ctx = InstallContext::new(config);
+
+    // 检测是否已安装（PATH 上未找到时再查注册表，发现后走接管流程）
+    let detect = inst.detect_installed(&ctx).await?;
+    #[cfg(windows)]
+    let detect = match detect {
+        DetectResult::NotInstalled => scan::scan_registry()
+            .into_iter()
+            .find(|d| d.tool_id == info.id)
+            .map(|d| DetectResult::InstalledExternal(d.version))
+            .unwrap_or(DetectResult::NotInstalled),
+        other => other,
+    };
+    match &detect {
+        DetectResult::InstalledByHudo(version) if config.reinstall => {
+            if latest_unchanged(config, inst, version).await {
+                ui::print_success(&format!("{} 已是最新 (hudo): {}，下载地址上的内容未变化", info.name, version));
+                return Ok(());
+            }
+            ui::print_info(&format!("{} 已安装 (hudo): {}，重新安装以切换版本", info.name, version));
+        }
+        DetectResult::InstalledByHudo(version) => {
+            ui::print_success(&format!("{} 已安装 (hudo): {}", info.name, version));
+            // 主动安装曾作为依赖自动安装的工具，此后不再随依赖方一并卸载
+            let mut reg = registry::InstallRegistry::load(&config.state_path())?;
+            if reg.mark_explicit(info.id) {
+                reg.save(&config.state_path())?;
+            }
+            if !skip_configure {
+                inst.configure(&ctx).await?;
+            }
+            return Ok(());
+        }
+        DetectResult::InstalledExternal(version) => {
+            ui::print_warning(&format!("{} 已安装在系统其他位置: {}", info.name, version));
+            if config.dry_run {
+                print_takeover_plan(info.id)?;
+                return Ok(());
+            }
+            if !decide_takeover(config, info.id)? {
+                ui::print_info("跳过安装，使用现有版本");
+                if !skip_configure {
+                    inst.configure(&ctx).await?;
+                }
+                return Ok(());
+            }
+            ui::print_step(1, 2, "卸载旧版...");
+            #[cfg(windows)]
+            uninstall_from_system(config, info.id)?;
             #[cfg(not(windows))]
             anyhow::bail!("该工具已安装在系统其他位置，请手动卸载后重试");
         }
         DetectResult::NotInstalled => {}
     }
 
-    // 执行安装
-    let result = inst.install(&ctx).await?;
-    ui::print_success(&format!(
-        "{} {} 安装完成",
-        info.name,
-        console::style(&result.version).green()
-    ));
+    // 其他包管理器提供的同名命令会与 hudo 安装的版本争夺 PATH
+    #[cfg(windows)]
+    if !confirm_package_manager_conflict(info.id, info.name)? {
+        ui::print_info("已取消安装");
+        return Ok(());
+    }
+
+    // 执行安装；配置中固定的版本不存在时提示改用最新版本
+    // （安装前已存在的 created_dirs 记下来，回滚时不删除，如已有的 rustup、FNM_DIR）
+    let existing_dirs: Vec<std::path::PathBuf> =
+        inst.created_dirs(config).into_iter().map(|d| d.path).filter(|p| p.exists()).collect();
+    installer::warn_x64_fallback(&ctx, inst);
+    let result = match inst.install(&ctx).await {
+        Ok(result) => result,
+        Err(e) => {
+            let Some(missing) = installer::missing_version(&e, config, info.id) else {
+                return Err(e);
+            };
+            let Some(retry) = recover_missing_version(config, inst, &missing, &e).await? else {
+                return Err(e);
+            };
+            return Box::pin(cmd_install_inner(&retry, tool_id, skip_configure)).await;
+        }
+    };
+    ui::print_success(&tf("install.done", &[&info.name, &console::style(&result.version).green()]));
+    // 最新版地址上的内容有变化不等于版本变了（如只是重新打包），按实际安装的版本告知
+    if let DetectResult::InstalledByHudo(previous) = &detect {
+        if inst.latest_download() && version::versions_match(&result.version, previous) {
+            ui::print_info(&format!("{} 下载地址上的内容有变化，但版本未变: {}", info.name, result.version));
+        }
+    }
+
+    // 上次 uninstall --keep-config 保留的用户数据移回安装目录
+    let kept = config.kept_dir(info.id);
+    if kept.exists() {
+        let data_dirs = inst.user_data_dirs(&result.install_path);
+        for dir in installer::restore_user_data(&data_dirs, &result.install_path, &kept)? {
+            ui::print_info(&format!("已恢复保留的用户数据: {}", dir.display()));
+        }
+        if kept.exists() {
+            ui::print_warning(&format!("部分保留的数据未恢复，仍在 {}", kept.display()));
+        }
+    }
 
-    // 配置环境变量
+    // 配置环境变量：先预览将要发生的更改，确认后应用
     let actions = inst.env_actions(&result.install_path, config);
+    let mut env_deferred = false;
     if !actions.is_empty() {
-        for action in &actions {
-            match action {
-                EnvAction::Set { name, value } => {
-                    env::EnvManager::set_var(name, value)?;
-                    ui::print_info(&format!("{} = {}", name, value));
-                }
-                EnvAction::AppendPath { path } => {
-                    env::EnvManager::append_to_path(path)?;
-                    ui::print_info(&format!("PATH += {}", path));
-                }
-            }
+        let preview = env::EnvPreview::build(&actions)?;
+        print_env_preview(&preview);
+        if !preview.is_noop() && !config.yes && !env::session_only() && !confirm_env_changes()? {
+            env_deferred = true;
+            ui::print_info(&format!("已跳过环境变量更改，之后运行 hudo env apply {} 应用", info.id));
+        } else {
+            apply_env_actions(&actions)?;
         }
-        env::EnvManager::broadcast_change();
     }
 
-    // 保存安装状态（在 configure 之前，确保安装失败不影响已安装记录）
+    // 保存安装状态（在 configure 之前：configure 失败时记录仍在，由用户选择保留或回滚）
     let mut reg = registry::InstallRegistry::load(&config.state_path())?;
     reg.mark_installed(
         info.id,
         &result.version,
         &result.install_path.to_string_lossy(),
     );
+    if let Some(mode) = inst.install_mode(config) {
+        reg.set_install_mode(info.id, &mode);
+    }
+    reg.set_fingerprint(info.id, binary_fingerprint(inst, &result.install_path));
+    if inst.latest_download() {
+        let (url, filename) = inst.resolve_download(config);
+        reg.set_artifact(info.id, download::cached_validator(&url, &config.cache_dir(), &filename));
+    }
+    reg.set_env_pending(info.id, env_deferred);
     reg.save(&config.state_path())?;
 
-    // 交互式配置
+    #[cfg(windows)]
+    create_shortcuts(config, inst, &result.install_path, !skip_configure);
+
+    // 交互式配置；全新安装时配置失败可选择回滚，避免留下装了一半的工具
     if !skip_configure {
-        inst.configure(&ctx).await?;
+        if let Err(e) = inst.configure(&ctx).await {
+            let fresh = !matches!(detect, DetectResult::InstalledByHudo(_));
+            return Err(match fresh {
+                true => rollback_after_configure_failure(config, inst, e, &existing_dirs).await?,
+                false => e,
+            });
+        }
+    }
+
+    ui::print_next_steps(info.post_install_tips);
+
+    Ok(())
+}
+
+/// 从「最新版」地址安装的工具重新安装前：与安装时记录的 ETag / Last-Modified 比较，
+/// 地址上的内容未变化时返回 true；没有记录、检查失败或要切换到与已安装版本不同的固定版本时
+/// 返回 false（照常下载）
+async fn latest_unchanged(config: &HudoConfig, inst: &dyn installer::Installer, installed: &str) -> bool {
+    if !inst.latest_download() {
+        return false;
+    }
+    let pinned = config::version_key_for(inst.info().id).and_then(|(_, key)| config.version_of(key));
+    if pinned.is_some_and(|target| !version::versions_match(installed, target)) {
+        return false;
+    }
+    let artifact = registry::InstallRegistry::load(&config.state_path())
+        .ok()
+        .and_then(|reg| reg.get(inst.info().id).and_then(|s| s.artifact.clone()));
+    let Some(artifact) = artifact else {
+        return false;
+    };
+    let (url, _) = inst.resolve_download(config);
+    ui::print_action("检查下载地址上是否有新版本...");
+    match download::is_unchanged(&url, &artifact).await {
+        Ok(unchanged) => unchanged,
+        Err(e) => {
+            ui::print_warning(&format!("检查更新失败（{:#}），重新下载", e));
+            false
+        }
+    }
+}
+
+/// 全新安装后 configure 失败：询问回滚（按卸载流程删除安装目录、环境变量和安装记录）还是保留现状，
+/// -y 时保留；existing_dirs 为安装前已存在的 created_dirs，回滚时保留。返回附带处理结果的原始错误
+async fn rollback_after_configure_failure(
+    config: &HudoConfig,
+    inst: &dyn installer::Installer,
+    err: anyhow::Error,
+    existing_dirs: &[std::path::PathBuf],
+) -> Result<anyhow::Error> {
+    let info = inst.info();
+    ui::print_warning(&format!("{} 已安装，但配置失败: {:#}", info.name, err));
+    let keep_hint = format!("{} 已安装但未完成配置，可重新运行 hudo install {} 完成配置", info.name, info.id);
+    if config.yes {
+        return Ok(err.context(keep_hint));
+    }
+
+    let choice = Select::with_theme(&ui::theme())
+        .with_prompt("  如何处理？")
+        .items(&[
+            "回滚：删除刚安装的文件、环境变量和安装记录",
+            "保留：稍后重新运行安装完成配置",
+        ])
+        .default(0)
+        .interact()
+        .context("选择被取消")?;
+    if choice == 1 {
+        return Ok(err.context(keep_hint));
+    }
+
+    ui::print_action(&format!("回滚 {}...", info.name));
+    // 不带 yes：正在运行的程序、含用户数据的目录照常询问
+    uninstall_tool(config, inst, false, false, existing_dirs).await?;
+    Ok(err.context(format!("{} 配置失败，已回滚安装", info.name)))
+}
+
+/// 为 GUI 工具创建开始菜单快捷方式；桌面快捷方式按 desktop_shortcuts 配置，未配置时询问
+#[cfg(windows)]
+fn create_shortcuts(
+    config: &HudoConfig,
+    inst: &dyn installer::Installer,
+    install_path: &std::path::Path,
+    interactive: bool,
+) {
+    let specs = inst.shortcuts(install_path);
+    if specs.is_empty() {
+        return;
+    }
+    if let Ok(dir) = shortcut::start_menu_dir() {
+        for spec in &specs {
+            match shortcut::create(&dir, spec) {
+                Ok(_) => ui::print_info(&format!("开始菜单: hudo\\{}", spec.name)),
+                Err(e) => ui::print_warning(&format!("{:#}", e)),
+            }
+        }
+    }
+
+    let desktop = match config.desktop_shortcuts {
+        Some(v) => v,
+        None if interactive => Confirm::new()
+            .with_prompt("  是否在桌面创建快捷方式？")
+            .default(false)
+            .interact()
+            .unwrap_or(false),
+        None => false,
+    };
+    if desktop {
+        if let Ok(dir) = shortcut::desktop_dir() {
+            for spec in &specs {
+                match shortcut::create(&dir, spec) {
+                    Ok(lnk) => ui::print_info(&format!("桌面: {}", lnk.display())),
+                    Err(e) => ui::print_warning(&format!("{:#}", e)),
+                }
+            }
+        }
     }
+}
 
+/// --dry-run：列出接管时将执行的清理，不询问也不做任何更改
+fn print_takeover_plan(tool_id: &str) -> Result<()> {
+    ui::print_info("接管时将执行:");
+    #[cfg(windows)]
+    if let Some(entry) = scan::find_uninstaller(tool_id) {
+        println!("    {} {} ({})", console::style("运行卸载程序").red(), entry.display_name, entry.hive);
+    }
+    match takeover::plan_for(tool_id) {
+        Ok(plan) if plan.is_empty() => {
+            plan.print();
+            println!("    {}", console::style("没有需要清理的 PATH 项或环境变量").dim());
+        }
+        Ok(plan) => plan.print(),
+        Err(e) => println!("    {}", console::style(e).dim()),
+    }
+    ui::print_info("--dry-run：未做任何更改");
     Ok(())
 }
 
+/// 系统已有工具是否由 hudo 接管：按 state.json 中记录的选择处理，未记录（或 --ask-again）时询问并记录
+fn decide_takeover(config: &HudoConfig, tool_id: &str) -> Result<bool> {
+    let mut reg = registry::InstallRegistry::load(&config.state_path())?;
+    let policy = match config.ask_external_again {
+        true => registry::ExternalPolicy::Ask,
+        false => reg.external_policy(tool_id),
+    };
+    match policy {
+        registry::ExternalPolicy::Ignore => {
+            ui::print_info("按已记录的选择保留现有版本（--ask-again 可重新选择）");
+            Ok(false)
+        }
+        registry::ExternalPolicy::Takeover => {
+            ui::print_info("按已记录的选择由 hudo 接管");
+            Ok(true)
+        }
+        registry::ExternalPolicy::Ask => {
+            let takeover = Confirm::new()
+                .with_prompt("  是否由 hudo 接管？（将清理旧版并重新安装到 hudo 目录）")
+                .default(false)
+                .interact()
+                .context("选择被取消")?;
+            let policy = match takeover {
+                true => registry::ExternalPolicy::Takeover,
+                false => registry::ExternalPolicy::Ignore,
+            };
+            reg.set_external_policy(tool_id, policy);
+            reg.save(&config.state_path())?;
+            ui::print_info(&format!(
+                "已记录该选择，修改请运行 hudo config external-policy {} <ask|ignore|takeover>",
+                tool_id
+            ));
+            Ok(takeover)
+        }
+    }
+}
+
+/// PATH 上已有 Scoop / Chocolatey / winget 提供的命令时提示冲突，返回是否继续安装
+#[cfg(windows)]
+fn confirm_package_manager_conflict(tool_id: &str, name: &str) -> Result<bool> {
+    let found = scan::package_manager_commands(tool_id);
+    if found.is_empty() {
+        return Ok(true);
+    }
+    ui::print_warning(&format!("{} 已由其他包管理器提供：", name));
+    for cmd in &found {
+        println!("    {}  {}", ui::pad(cmd.manager, 12), console::style(&cmd.path).dim());
+    }
+    ui::print_info("两份同时存在时，终端里实际使用哪份取决于 PATH 顺序；建议先用原包管理器卸载");
+    Confirm::new()
+        .with_prompt("  仍要继续安装？")
+        .default(false)
+        .interact()
+        .context("选择被取消")
+}
+
 /// 卸载 hudo 管理的工具
-async fn cmd_uninstall(config: &HudoConfig, tool_id: &str) -> Result<()> {
+async fn cmd_uninstall(config: &HudoConfig, tool_id: &str, yes: bool, keep_config: bool) -> Result<()> {
     let installers = all_installers();
 
-    let available: Vec<_> = installers.iter().map(|i| i.info().id).collect();
-    let inst = installers
-        .iter()
-        .find(|i| i.info().id == tool_id)
-        .ok_or_else(|| {
-            anyhow::anyhow!(
-                "未知工具 '{}'，可用: {}",
-                tool_id,
-                available.join(", ")
-            )
-        })?;
+    let inst = installer::find(&installers, tool_id, "卸载")?;
 
     let info = inst.info();
-    let ctx = InstallContext { config };
+    let ctx = InstallContext::new(config);
 
     // 检测是否由 hudo 安装
     let detect = inst.detect_installed(&ctx).await?;
     match &detect {
         DetectResult::InstalledByHudo(ver) => {
-            ui::print_title(&format!("卸载 {} ({})", info.name, ver));
+            ui::print_title(&tf("uninstall.title", &[&info.name, &ver]));
         }
         _ => {
             ui::print_warning(&format!("{} 未由 hudo 安装，无需卸载", info.name));
@@ -470,157 +1362,282 @@ async fn cmd_uninstall(config: &HudoConfig, tool_id: &str) -> Result<()> {
         }
     }
 
-    let confirm = Confirm::new()
-        .with_prompt(format!("  确认卸载 {}？（将删除安装目录并清理环境变量）", info.name))
-        .default(false)
-        .interact()
-        .context("选择被取消")?;
+    if !yes {
+        let confirm = Confirm::new()
+            .with_prompt(format!("  确认卸载 {}？（将删除安装目录并清理环境变量）", info.name))
+            .default(false)
+            .interact()
+            .context("选择被取消")?;
 
-    if !confirm {
-        ui::print_info("已取消");
-        return Ok(());
+        if !confirm {
+            ui::print_info("已取消");
+            return Ok(());
+        }
+    }
+
+    let orphans = uninstall_tool(config, inst, yes, keep_config, &[]).await?;
+    ui::print_info("请打开新终端以使环境变量生效");
+
+    // 随该工具自动安装的依赖（如 Maven 的 JDK）已无其他依赖方，提示一并卸载
+    for dep_id in &orphans {
+        let dep_name = installers
+            .iter()
+            .find(|i| i.info().id == dep_id.as_str())
+            .map(|i| i.info().name)
+            .unwrap_or(dep_id.as_str());
+        println!();
+        ui::print_info(&format!(
+            "{} 是随 {} 自动安装的依赖，现已无其他工具使用",
+            dep_name, info.name
+        ));
+        Box::pin(cmd_uninstall(config, dep_id, yes, keep_config)).await?;
     }
+    Ok(())
+}
+
+/// 卸载单个已确认由 hudo 安装的工具，返回因此不再被依赖的自动安装工具
+/// `yes` 时不询问，直接关闭安装目录下正在运行的程序；`keep_config` 时先移出用户数据目录；
+/// `keep_dirs` 中的 created_dirs 不删除（回滚安装时为安装前已存在的目录）
+async fn uninstall_tool(
+    config: &HudoConfig,
+    inst: &dyn installer::Installer,
+    yes: bool,
+    keep_config: bool,
+    keep_dirs: &[std::path::PathBuf],
+) -> Result<Vec<String>> {
+    let info = inst.info();
+    let ctx = InstallContext::new(config);
 
     // 获取安装路径（从 env_actions 推断或从 registry 读取）
     let reg = registry::InstallRegistry::load(&config.state_path())?;
     let install_path = reg
         .get(info.id)
         .map(|s| std::path::PathBuf::from(&s.install_path))
-        .unwrap_or_else(|| {
-            // 回退：根据工具类型推断默认路径
-            match info.id {
-                "vscode" | "pycharm" => config.ide_dir().join(info.id),
-                "go" | "jdk" => config.lang_dir().join(match info.id {
-                    "jdk" => "java",
-                    other => other,
-                }),
-                "rust" => config.lang_dir().join("cargo"),
-                _ => config.tools_dir().join(info.id),
-            }
-        });
+        .unwrap_or_else(|| default_install_path(config, info.id));
 
-    // 1. 卸载前清理（停止服务等）
+    // 1. 卸载前清理（停止服务等）；安装目录之外的目录（如 Rust 的 rustup、Node.js 的 FNM_DIR）
+    //    中有用户数据时先询问是否一并删除，再关闭仍在运行的程序（服务进程已由 pre_uninstall 停止）
     inst.pre_uninstall(&ctx).await?;
+    let created_dirs = inst.created_dirs(config).into_iter().filter(|d| !keep_dirs.contains(&d.path)).collect();
+    let (created, kept_dirs) = installer::split_created_dirs(created_dirs, keep_config, |dir| {
+        if yes {
+            return Ok(true);
+        }
+        Confirm::new()
+            .with_prompt(format!(
+                "  同时删除 {}？（其中有{}）",
+                dir.path.display(),
+                dir.user_data.unwrap_or_default()
+            ))
+            .default(true)
+            .interact()
+            .context("选择被取消")
+    })?;
+    let mut dirs = vec![install_path.as_path()];
+    dirs.extend(created.iter().map(|d| d.as_path()));
+    if !running::ensure_closed(&dirs, info.name, yes)? {
+        anyhow::bail!("已取消卸载 {}", info.name);
+    }
 
     // 2. 清理环境变量
     let actions = inst.env_actions(&install_path, config);
-    for action in &actions {
-        match action {
-            EnvAction::Set { name, .. } => {
-                if env::EnvManager::get_var(name)?.is_some() {
-                    env::EnvManager::delete_var(name)?;
-                    ui::print_info(&format!("移除环境变量: {}", name));
-                }
-            }
-            EnvAction::AppendPath { path } => {
-                env::EnvManager::remove_from_path(path)?;
-                ui::print_info(&format!("PATH -= {}", path));
-            }
-        }
+    remove_env_actions(&actions)?;
+
+    #[cfg(windows)]
+    shortcut::remove_all(&inst.shortcuts(&install_path));
+    #[cfg(windows)]
+    match installer::remove_shell_integration(inst, &install_path) {
+        Ok(Some(profile)) => ui::print_info(&format!("已从 {} 删除 {} 初始化脚本", profile.display(), info.name)),
+        Ok(None) => {}
+        Err(e) => ui::print_warning(&format!("未能清理 PowerShell profile: {:#}", e)),
     }
 
-    // 3. Rust 特殊处理：同时删除 rustup 目录
-    if info.id == "rust" {
-        let rustup_home = config.tools_dir().join("rustup");
-        if rustup_home.exists() {
-            std::fs::remove_dir_all(&rustup_home).ok();
-            ui::print_info(&format!("已删除 {}", rustup_home.display()));
+    // 3. 保留用户数据：移到 {root_dir}\kept\<id>，重新安装时移回
+    if keep_config {
+        let kept = config.kept_dir(info.id);
+        let data_dirs = inst.user_data_dirs(&install_path);
+        for dir in installer::stash_user_data(&data_dirs, &install_path, &kept)? {
+            ui::print_info(&format!("已保留用户数据: {}", dir.display()));
         }
     }
 
-    // 3. 删除安装目录
-    if install_path.exists() {
-        std::fs::remove_dir_all(&install_path)
-            .with_context(|| format!("删除目录失败: {}", install_path.display()))?;
-        ui::print_info(&format!("已删除 {}", install_path.display()));
+    // 4. 删除安装目录与 created_dirs；仍有文件被占用时记录下来，下次运行时重试
+    let mut residual = Vec::new();
+    for dir in &dirs {
+        if !dir.exists() {
+            continue;
+        }
+        match std::fs::remove_dir_all(dir) {
+            Ok(()) => ui::print_info(&format!("已删除 {}", dir.display())),
+            Err(e) => {
+                ui::print_warning(&format!("部分文件被占用，未能删除 {}: {}", dir.display(), e));
+                residual.push(*dir);
+            }
+        }
     }
 
-    // 4. 更新 state.json
+    // 5. 更新 state.json，并找出因此不再被依赖的自动安装工具
     let mut reg = registry::InstallRegistry::load(&config.state_path())?;
     reg.remove(info.id);
+    let orphans = reg.remove_dependent(info.id);
+    for dir in &residual {
+        reg.add_pending_delete(dir);
+    }
     reg.save(&config.state_path())?;
+    if !residual.is_empty() {
+        ui::print_info("残留文件将在下次运行 hudo 时自动清理（也可关闭占用的程序后手动删除）");
+    }
+    for dir in &kept_dirs {
+        ui::print_info(&format!("已保留 {}", dir.display()));
+    }
 
     if !actions.is_empty() {
         env::EnvManager::broadcast_change();
     }
 
-    ui::print_success(&format!("{} 已卸载", info.name));
-    ui::print_info("请打开新终端以使环境变量生效");
-    Ok(())
+    ui::print_success(&tf("uninstall.done", &[&info.name]));
+    Ok(orphans)
 }
 
-/// 卸载系统中已有的工具
-#[cfg(windows)]
-fn uninstall_from_system(tool_id: &str) -> Result<()> {
-    match tool_id {
-        "git" => uninstall_via_registry("Git_is1"),
-        "uv" => uninstall_uv(),
-        "rust" => uninstall_rust(),
-        "go" => uninstall_go(),
-        "miniconda" => uninstall_miniconda(),
-        "vscode" => uninstall_vscode(),
-        // 绿色安装的工具：通过 where 找到旧二进制，移除 PATH
-        "nodejs" => uninstall_green(&["fnm", "node"], &["FNM_DIR"]),
-        "bun" => uninstall_green(&["bun"], &[]),
-        "jdk" => uninstall_green(&["java"], &["JAVA_HOME"]),
-        "c" => uninstall_green(&["gcc"], &[]),
-        "mysql" => uninstall_green(&["mysql"], &[]),
-        "pgsql" => uninstall_green(&["psql"], &[]),
-        "pycharm" => uninstall_green(&["pycharm64"], &[]),
-        "claude-code" => uninstall_claude_code(),
-        _ => anyhow::bail!("不支持自动卸载: {}", tool_id),
-    }
-}
-
-/// 通过注册表查找并运行系统卸载程序（如 Git）
-#[cfg(windows)]
-fn uninstall_via_registry(uninstall_key: &str) -> Result<()> {
-    let hklm = winreg::RegKey::predef(winreg::enums::HKEY_LOCAL_MACHINE);
-    let path = format!(
-        "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\{}",
-        uninstall_key
-    );
+/// state.json 中没有记录时，根据工具类型推断默认安装路径
+fn default_install_path(config: &HudoConfig, id: &str) -> std::path::PathBuf {
+    match id {
+        "vscode" | "pycharm" => config.ide_dir().join(id),
+        "go" => config.lang_dir().join("go"),
+        "jdk" => config.lang_dir().join("java"),
+        "rust" => config.lang_dir().join("cargo"),
+        _ => config.tools_dir().join(id),
+    }
+}
 
-    let uninstall_string: String = hklm
-        .open_subkey(&path)
-        .and_then(|key| key.get_value("UninstallString"))
-        .or_else(|_| {
-            let hkcu = winreg::RegKey::predef(winreg::enums::HKEY_CURRENT_USER);
-            hkcu.open_subkey(&path)
-                .and_then(|key| key.get_value("UninstallString"))
-        })
-        .context("未找到卸载程序，请手动卸载后重试")?;
+/// 卸载 hudo 管理的全部工具：统一确认一次，逐个卸载，最后汇总结果
+async fn cmd_uninstall_all(config: &HudoConfig, yes: bool, keep_config: bool) -> Result<()> {
+    let installers = all_installers();
+    let reg = registry::InstallRegistry::load(&config.state_path())?;
+
+    // 只处理 state.json 中记录、且探测确认由 hudo 安装的工具
+    let recorded: Vec<&dyn installer::Installer> = installers
+        .iter()
+        .map(|i| i.as_ref())
+        .filter(|i| reg.get(i.info().id).is_some())
+        .collect();
+    let detected = installer::detect_parallel(&recorded, config);
+    let mut targets = Vec::new();
+    for (inst, result) in recorded.into_iter().zip(detected) {
+        if let Ok(DetectResult::InstalledByHudo(ver)) = result {
+            let is_dep = reg
+                .get(inst.info().id)
+                .is_some_and(|s| !s.installed_as_dependency_of.is_empty());
+            targets.push((inst, ver, is_dep));
+        }
+    }
+    if targets.is_empty() {
+        ui::print_info("没有由 hudo 安装的工具");
+        return Ok(());
+    }
+    // 自动安装的依赖（如 Maven 的 JDK）放到最后，先卸载依赖它的工具
+    targets.sort_by_key(|(_, _, is_dep)| *is_dep);
 
-    let uninstall_string = uninstall_string.trim_matches('"').to_string();
+    ui::print_title(&tf("uninstall.all_title", &[&targets.len()]));
+    for (inst, ver, _) in &targets {
+        println!("  {}  {}", ui::pad(inst.info().name, 20), console::style(ver).dim());
+    }
+    println!();
 
-    let status = std::process::Command::new(&uninstall_string)
-        .args(["/VERYSILENT", "/NORESTART"])
-        .status()
-        .with_context(|| format!("运行卸载程序失败: {}", uninstall_string))?;
+    if !yes {
+        let confirm = Confirm::new()
+            .with_prompt("  确认全部卸载？（将删除安装目录并清理环境变量）")
+            .default(false)
+            .interact()
+            .context("选择被取消")?;
+        if !confirm {
+            ui::print_info("已取消");
+            return Ok(());
+        }
+    }
 
-    if !status.success() {
-        anyhow::bail!("卸载程序退出码: {}", status.code().unwrap_or(-1));
+    let mut removed = 0;
+    let mut failed = Vec::new();
+    for (inst, ver, _) in &targets {
+        println!();
+        ui::print_title(&tf("uninstall.title", &[&inst.info().name, &ver]));
+        match uninstall_tool(config, *inst, yes, keep_config, &[]).await {
+            Ok(_) => removed += 1,
+            Err(e) => {
+                ui::print_error(&format!("{} 卸载失败: {:#}", inst.info().name, e));
+                failed.push(inst.info().name);
+            }
+        }
     }
 
-    ui::print_success("旧版已卸载");
+    println!();
+    if failed.is_empty() {
+        ui::print_success(&tf("uninstall.all_done", &[&removed]));
+    } else {
+        ui::print_warning(&tf("uninstall.some_failed", &[&removed, &failed.len(), &failed.join(", ")]));
+    }
+    ui::print_info(t("setup.open_new_terminal"));
     Ok(())
 }
 
-/// 卸载系统中已有的 uv（绿色安装，无注册表卸载器）
+/// 卸载系统中已有的工具
 #[cfg(windows)]
-fn uninstall_uv() -> Result<()> {
-    // 找到旧 uv 的位置
-    let output = std::process::Command::new("where")
-        .arg("uv")
-        .output()
-        .context("查找 uv 位置失败")?;
-
-    if !output.status.success() {
-        ui::print_warning("未找到旧版 uv，跳过卸载");
-        return Ok(());
-    }
-
-    let uv_path = String::from_utf8_lossy(&output.stdout);
+fn uninstall_from_system(config: &HudoConfig, tool_id: &str) -> Result<()> {
+    match tool_id {
+        "git" => uninstall_git(config),
+        "uv" => uninstall_uv(),
+        "rust" => uninstall_rust(config),
+        "go" => uninstall_go(config),
+        "miniconda" => uninstall_miniconda(config),
+        "vscode" => uninstall_vscode(config),
+        "claude-code" => uninstall_claude_code(config),
+        _ => {
+            // 注册表中有卸载程序（如 MSI 安装的 JDK、MySQL）则优先使用
+            if let Some(entry) = scan::find_uninstaller(tool_id) {
+                ui::print_info(&format!("找到 {} ({})", entry.display_name, entry.hive));
+                match scan::run_uninstaller(&entry) {
+                    Ok(()) => {
+                        env::EnvManager::broadcast_change();
+                        ui::print_success("旧版已卸载");
+                        return Ok(());
+                    }
+                    Err(e) => ui::print_warning(&format!("{}，尝试手动清理 PATH", e)),
+                }
+            }
+            // 绿色安装的工具：通过 where 找到旧二进制，移除 PATH
+            takeover::uninstall_green(config, tool_id)
+        }
+    }
+}
+
+/// 卸载系统中的 Git：按这份外部安装本身判断，注册表中有卸载程序（官方安装程序）时运行它，
+/// 没有（PortableGit 等绿色版）时清理 PATH
+#[cfg(windows)]
+fn uninstall_git(config: &HudoConfig) -> Result<()> {
+    if let Some(entry) = scan::find_uninstaller("git") {
+        ui::print_info(&format!("找到 {} ({})", entry.display_name, entry.hive));
+        scan::run_uninstaller(&entry)?;
+        ui::print_success("旧版已卸载");
+        return Ok(());
+    }
+    takeover::uninstall_green(config, "git")
+}
+
+/// 卸载系统中已有的 uv（绿色安装，无注册表卸载器）
+#[cfg(windows)]
+fn uninstall_uv() -> Result<()> {
+    // 找到旧 uv 的位置
+    let output = std::process::Command::new("where")
+        .arg("uv")
+        .output()
+        .context("查找 uv 位置失败")?;
+
+    if !output.status.success() {
+        ui::print_warning("未找到旧版 uv，跳过卸载");
+        return Ok(());
+    }
+
+    let uv_path = proc::decode_output(&output.stdout);
     let uv_path = uv_path.lines().next().unwrap_or("").trim();
     let old_dir = std::path::Path::new(uv_path)
         .parent()
@@ -659,10 +1676,9 @@ fn uninstall_uv() -> Result<()> {
 
 /// 卸载系统中已有的 Claude Code（npm 全局安装）
 #[cfg(windows)]
-fn uninstall_claude_code() -> Result<()> {
+fn uninstall_claude_code(config: &HudoConfig) -> Result<()> {
     // 尝试 npm uninstall
-    let status = std::process::Command::new("cmd")
-        .args(["/c", "npm", "uninstall", "-g", "@anthropic-ai/claude-code"])
+    let status = proc::cmd("npm", &["uninstall", "-g", "@anthropic-ai/claude-code"])
         .status();
 
     match status {
@@ -674,47 +1690,12 @@ fn uninstall_claude_code() -> Result<()> {
     }
 
     // npm 不可用或失败，尝试绿色方式清理
-    uninstall_green(&["claude"], &[])
-}
-
-/// 通用卸载：通过 where 找到旧二进制，从 PATH 移除其所在目录，并清理指定环境变量
-#[cfg(windows)]
-fn uninstall_green(binaries: &[&str], env_vars: &[&str]) -> Result<()> {
-    for bin in binaries {
-        let bin_name = format!("{}.exe", bin);
-        if let Ok(output) = std::process::Command::new("where").arg(&bin_name).output() {
-            if output.status.success() {
-                let paths = String::from_utf8_lossy(&output.stdout);
-                for line in paths.lines() {
-                    let line = line.trim();
-                    if line.is_empty() {
-                        continue;
-                    }
-                    if let Some(parent) = std::path::Path::new(line).parent() {
-                        let dir_str = parent.to_string_lossy();
-                        ui::print_info(&format!("移除 PATH: {}", dir_str));
-                        env::EnvManager::remove_from_path(&dir_str)?;
-                    }
-                }
-            }
-        }
-    }
-
-    for var in env_vars {
-        if env::EnvManager::get_var(var)?.is_some() {
-            ui::print_info(&format!("移除环境变量: {}", var));
-            env::EnvManager::delete_var(var)?;
-        }
-    }
-
-    env::EnvManager::broadcast_change();
-    ui::print_success("旧版已清理");
-    Ok(())
+    takeover::uninstall_green(config, "claude-code")
 }
 
 /// 卸载系统中的 Rust（通过 rustup self uninstall）
 #[cfg(windows)]
-fn uninstall_rust() -> Result<()> {
+fn uninstall_rust(config: &HudoConfig) -> Result<()> {
     // 先尝试 rustup self uninstall
     if let Ok(output) = std::process::Command::new("where").arg("rustup").output() {
         if output.status.success() {
@@ -742,58 +1723,38 @@ fn uninstall_rust() -> Result<()> {
     }
 
     // 回退：手动清理 PATH
-    uninstall_green(&["rustc", "cargo"], &["RUSTUP_HOME", "CARGO_HOME"])
+    takeover::uninstall_green(config, "rust")
 }
 
 /// 卸载系统中的 Go（可能是 MSI 安装或绿色安装）
 #[cfg(windows)]
-fn uninstall_go() -> Result<()> {
-    // 先尝试注册表卸载器（Go 官方 MSI 的注册表键名可能有变化）
-    let hklm = winreg::RegKey::predef(winreg::enums::HKEY_LOCAL_MACHINE);
-    let uninstall_path = "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Uninstall";
-    if let Ok(uninstall_key) = hklm.open_subkey(uninstall_path) {
-        for name in uninstall_key.enum_keys().filter_map(|k| k.ok()) {
-            if let Ok(sub) = uninstall_key.open_subkey(&name) {
-                let display: std::result::Result<String, _> = sub.get_value("DisplayName");
-                if let Ok(display) = display {
-                    if display.contains("Go Programming Language") {
-                        if let Ok(cmd) = sub.get_value::<String, _>("UninstallString") {
-                            ui::print_info(&format!("找到 Go MSI 卸载器: {}", cmd));
-                            let cmd = cmd.trim_matches('"').to_string();
-                            // MSI 静默卸载
-                            let status = std::process::Command::new("msiexec")
-                                .args(["/x", &cmd, "/qn", "/norestart"])
-                                .status();
-                            if let Ok(s) = status {
-                                if s.success() {
-                                    for var in &["GOROOT", "GOPATH"] {
-                                        if env::EnvManager::get_var(var)?.is_some() {
-                                            env::EnvManager::delete_var(var)?;
-                                        }
-                                    }
-                                    env::EnvManager::broadcast_change();
-                                    ui::print_success("旧版 Go (MSI) 已卸载");
-                                    return Ok(());
-                                }
-                            }
-                        }
-                    }
+fn uninstall_go(config: &HudoConfig) -> Result<()> {
+    // 先尝试注册表卸载器（Go 官方 MSI）
+    if let Some(entry) = scan::find_uninstaller("go") {
+        ui::print_info(&format!("找到 Go MSI 卸载器 ({})", entry.hive));
+        if scan::run_uninstaller(&entry).is_ok() {
+            for var in &["GOROOT", "GOPATH"] {
+                if env::EnvManager::get_var(var)?.is_some() {
+                    env::EnvManager::delete_var(var)?;
                 }
             }
+            env::EnvManager::broadcast_change();
+            ui::print_success("旧版 Go (MSI) 已卸载");
+            return Ok(());
         }
     }
 
     // 回退：绿色安装方式清理
-    uninstall_green(&["go"], &["GOROOT", "GOPATH"])
+    takeover::uninstall_green(config, "go")
 }
 
 /// 卸载系统中的 Miniconda
 #[cfg(windows)]
-fn uninstall_miniconda() -> Result<()> {
+fn uninstall_miniconda(config: &HudoConfig) -> Result<()> {
     // 找到 conda 位置
     if let Ok(output) = std::process::Command::new("where").arg("conda").output() {
         if output.status.success() {
-            let conda_path = String::from_utf8_lossy(&output.stdout);
+            let conda_path = proc::decode_output(&output.stdout);
             let conda_path = conda_path.lines().next().unwrap_or("").trim();
             if !conda_path.is_empty() {
                 // conda 在 Scripts/conda.exe，安装目录是上两级
@@ -822,90 +1783,115 @@ fn uninstall_miniconda() -> Result<()> {
         }
     }
 
-    uninstall_green(&["conda"], &[])
+    takeover::uninstall_green(config, "miniconda")
 }
 
 /// 卸载系统中的 VS Code
 #[cfg(windows)]
-fn uninstall_vscode() -> Result<()> {
+fn uninstall_vscode(config: &HudoConfig) -> Result<()> {
     // 检查注册表中的 VS Code 卸载器（用户安装或系统安装）
-    for (hive, hive_name) in &[
-        (winreg::enums::HKEY_CURRENT_USER, "HKCU"),
-        (winreg::enums::HKEY_LOCAL_MACHINE, "HKLM"),
-    ] {
-        let root = winreg::RegKey::predef(*hive);
-        let uninstall_path = "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Uninstall";
-        if let Ok(uninstall_key) = root.open_subkey(uninstall_path) {
-            for name in uninstall_key.enum_keys().filter_map(|k| k.ok()) {
-                if let Ok(sub) = uninstall_key.open_subkey(&name) {
-                    let display: std::result::Result<String, _> = sub.get_value("DisplayName");
-                    if let Ok(display) = display {
-                        if display.contains("Visual Studio Code") || display.contains("VS Code") {
-                            if let Ok(cmd) = sub.get_value::<String, _>("UninstallString") {
-                                ui::print_info(&format!("找到 VS Code 卸载器 ({}): {}", hive_name, cmd));
-                                let cmd = cmd.trim_matches('"').to_string();
-                                let status = std::process::Command::new(&cmd)
-                                    .args(["/VERYSILENT", "/NORESTART"])
-                                    .status();
-                                if let Ok(s) = status {
-                                    if s.success() {
-                                        env::EnvManager::broadcast_change();
-                                        ui::print_success("旧版 VS Code 已卸载");
-                                        return Ok(());
-                                    }
-                                }
-                                ui::print_warning("VS Code 卸载程序失败，尝试手动清理 PATH");
-                            }
-                        }
-                    }
-                }
-            }
+    if let Some(entry) = scan::find_uninstaller("vscode") {
+        ui::print_info(&format!("找到 VS Code 卸载器 ({})", entry.hive));
+        if scan::run_uninstaller(&entry).is_ok() {
+            env::EnvManager::broadcast_change();
+            ui::print_success("旧版 VS Code 已卸载");
+            return Ok(());
         }
+        ui::print_warning("VS Code 卸载程序失败，尝试手动清理 PATH");
     }
 
     // 回退：绿色安装方式清理（portable 模式 code.cmd 在 PATH 里）
-    uninstall_green(&["code"], &[])
+    takeover::uninstall_green(config, "vscode")
 }
 
 /// 导出 profile
-async fn cmd_export(config: &HudoConfig, file: Option<String>) -> Result<()> {
+/// `yes` 时不询问直接写入；`no_timestamp` 时不写导出时间，同样的环境导出的文件相同
+async fn cmd_export(
+    config: &HudoConfig,
+    file: Option<String>,
+    lock: bool,
+    include_secrets: bool,
+    yes: bool,
+    no_timestamp: bool,
+) -> Result<()> {
     let output_path = file.unwrap_or_else(|| "hudo-profile.toml".to_string());
     let output_path = std::path::Path::new(&output_path);
 
     ui::print_title("导出环境档案");
 
     let installers = all_installers();
-    let profile = profile::HudoProfile::build_from_current(config, &installers).await?;
+    let mut profile = profile::HudoProfile::build_from_current(config, &installers).await?;
 
     if profile.tools.is_empty() {
         ui::print_warning("未检测到任何已安装工具，无需导出");
         return Ok(());
     }
 
+    if lock {
+        ui::print_action("解析下载地址并计算 SHA256...");
+        profile.build_lock(config, &installers)?;
+    }
+
     // 展示摘要
     ui::print_info(&format!("检测到 {} 个已安装工具:", profile.tools.len()));
     for (id, ver) in &profile.tools {
+        let pinned = if profile.pinned.contains(id) {
+            format!("  {}", console::style("(已固定)").cyan())
+        } else {
+            String::new()
+        };
         println!(
-            "    {}  {}",
+            "    {}  {}{}",
             console::style(ui::pad(id, 14)).bold(),
-            console::style(ver).dim()
+            console::style(ver).dim(),
+            pinned
         );
     }
     if !profile.tool_config.is_empty() {
         println!();
         ui::print_info(&format!("包含 {} 个工具的配置", profile.tool_config.len()));
     }
+    if !profile.cc_providers.is_empty() {
+        if include_secrets {
+            ui::print_warning(&format!(
+                "包含 {} 个 AI 工具 provider，API Key 将以明文写入档案，请妥善保管",
+                profile.cc_providers.len()
+            ));
+        } else {
+            for p in &mut profile.cc_providers {
+                p.api_key.clear();
+            }
+            ui::print_info(&format!(
+                "包含 {} 个 AI 工具 provider（不含 API Key，使用 --include-secrets 导出）",
+                profile.cc_providers.len()
+            ));
+        }
+    }
+    if !profile.lock.is_empty() {
+        let hashed = profile.lock.values().filter(|l| l.sha256.is_some()).count();
+        ui::print_info(&format!(
+            "锁定 {} 个工具的下载地址，其中 {} 个含 SHA256（缓存中无安装包的不记录哈希）",
+            profile.lock.len(),
+            hashed
+        ));
+    }
 
-    println!();
-    let confirm = Confirm::new()
-        .with_prompt(format!("  导出到 {} ?", output_path.display()))
-        .default(true)
-        .interact_opt()
-        .context("确认被取消")?;
+    if no_timestamp {
+        profile.hudo.exported_at.clear();
+    }
 
-    if confirm != Some(true) {
-        ui::print_info("已取消");
-        return Ok(());
+    if !yes {
+        println!();
+        let confirm = Confirm::new()
+            .with_prompt(format!("  导出到 {} ?", output_path.display()))
+            .default(true)
+            .interact_opt()
+            .context("确认被取消")?;
+
+        if confirm != Some(true) {
+            ui::print_info("已取消");
+            return Ok(());
+        }
     }
 
     profile.save_to_file(output_path)?;
@@ -914,8 +1900,182 @@ async fn cmd_export(config: &HudoConfig, file: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// hudo export --target wsl：按已安装的工具生成在 WSL 中安装 Linux 版本的脚本
+async fn cmd_export_wsl(config: &HudoConfig, file: Option<String>) -> Result<()> {
+    let output_path = file.unwrap_or_else(|| "hudo-wsl.sh".to_string());
+    let output_path = std::path::Path::new(&output_path);
+
+    ui::print_title("导出 WSL 安装脚本");
+    let installers = all_installers();
+    let profile = profile::HudoProfile::build_from_current(config, &installers).await?;
+    if profile.tools.is_empty() {
+        ui::print_warning("未检测到任何已安装工具，无需导出");
+        return Ok(());
+    }
+
+    // 写 LF 换行，在 WSL 中可直接 bash 执行
+    std::fs::write(output_path, wsl::linux_script(&profile.tools))
+        .with_context(|| format!("无法写入 {}", output_path.display()))?;
+    ui::print_success(&format!("已导出 {} 个工具的安装命令到 {}", profile.tools.len(), output_path.display()));
+    ui::print_warning("脚本为尽力而为的转换，包名与版本以发行版仓库为准，运行前请先检查");
+    ui::print_info(&format!("在 WSL 中运行: bash {}", output_path.display()));
+    Ok(())
+}
+
+/// hudo wsl check：逐个发行版检查 PATH 中泄漏的 hudo 目录，选择过滤方式
+#[cfg(windows)]
+fn cmd_wsl_check(config: &HudoConfig) -> Result<()> {
+    ui::print_title("检查 WSL");
+    let distros = wsl::list_distros();
+    if distros.is_empty() {
+        ui::print_info("未检测到 WSL 发行版");
+        return Ok(());
+    }
+    let entries = wsl::hudo_path_entries(config)?;
+    if entries.is_empty() {
+        ui::print_info("hudo 没有向 PATH 添加任何目录，无需检查");
+        return Ok(());
+    }
+
+    for distro in &distros {
+        println!();
+        let path = match wsl::distro_path(distro) {
+            Ok(path) => path,
+            Err(e) => {
+                ui::print_warning(&format!("{}: {}", distro, e));
+                continue;
+            }
+        };
+        let leaks = wsl::leaking_entries(&entries, &path);
+        if leaks.is_empty() {
+            ui::print_success(&format!("{}: 未发现 hudo 的 Windows 路径", distro));
+            continue;
+        }
+        ui::print_warning(&format!(
+            "{}: 以下 hudo 目录通过 WSL interop 出现在 PATH 中，可能遮蔽 WSL 内的同名命令",
+            distro
+        ));
+        for leak in &leaks {
+            println!("    {}", console::style(leak).dim());
+        }
+
+        let items = [
+            "在 ~/.profile 中过滤这些目录（推荐，保留其他 Windows 命令如 code）",
+            "在 /etc/wsl.conf 中关闭 appendWindowsPath（不再追加任何 Windows PATH）",
+            "只显示设置方法",
+            "跳过",
+        ];
+        let sel = Select::with_theme(&ui::theme())
+            .with_prompt(format!("如何处理 {}", distro))
+            .items(&items)
+            .default(0)
+            .interact_opt()
+            .context("选择被取消")?;
+        match sel {
+            Some(0) => {
+                let snippet = wsl::profile_snippet(&leaks);
+                match wsl::append_in_distro(distro, "~/.profile", &snippet, wsl::PROFILE_MARKER, false)? {
+                    true => ui::print_success("已写入 ~/.profile，重新打开 WSL 终端后生效"),
+                    false => ui::print_info("~/.profile 中已有 hudo 写入的过滤片段，未重复写入（安装了新工具时请手动更新）"),
+                }
+            }
+            Some(1) => {
+                match wsl::append_in_distro(distro, "/etc/wsl.conf", wsl::WSL_CONF_SNIPPET, "appendWindowsPath", true)? {
+                    true => ui::print_success("已写入 /etc/wsl.conf，运行 wsl --shutdown 后生效"),
+                    false => ui::print_info("/etc/wsl.conf 中已有 appendWindowsPath 设置，未修改"),
+                }
+            }
+            Some(2) => {
+                ui::print_info("追加到 ~/.profile:");
+                println!("{}", wsl::profile_snippet(&leaks));
+                ui::print_info("或在 /etc/wsl.conf 中加入（之后运行 wsl --shutdown）:");
+                println!("{}", wsl::WSL_CONF_SNIPPET);
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 /// 导入 profile 并安装工具
-async fn cmd_import(config: &mut HudoConfig, file: &str) -> Result<()> {
+/// 导入时本地版本与档案记录不一致的处理方式
+#[derive(Clone, Copy, PartialEq)]
+enum VersionMismatch {
+    /// 逐个询问
+    Ask,
+    /// --strict：一律安装档案版本
+    Strict,
+    /// --prefer-local：一律保留本地版本
+    PreferLocal,
+}
+
+/// 单个工具版本不一致时的选择
+enum VersionChoice {
+    Keep,
+    Install(String),
+    Skip,
+}
+
+/// 数据目录在安装目录内且跨版本不兼容，导入时不自动切换版本
+const NO_VERSION_SWITCH: &[&str] = &["mysql", "pgsql"];
+
+/// 本地已安装版本与档案不一致时，按 mode 决定保留、安装档案版本还是跳过
+fn resolve_version_mismatch(
+    config: &HudoConfig,
+    info: &installer::ToolInfo,
+    local: &str,
+    wanted: &str,
+    mode: VersionMismatch,
+) -> Result<VersionChoice> {
+    let summary = format!("{} 本地 {}，档案 {}", info.name, local, wanted);
+    let target = match version::normalize_version(wanted) {
+        Some(v) if config.clone().pin_version(info.id, &v) => v,
+        _ => {
+            ui::print_warning(&format!("{} — 不支持指定版本，保留本地版本", summary));
+            return Ok(VersionChoice::Keep);
+        }
+    };
+    if NO_VERSION_SWITCH.contains(&info.id) {
+        ui::print_warning(&format!("{} — 数据库不自动切换版本，请迁移数据后手动重装", summary));
+        return Ok(VersionChoice::Keep);
+    }
+
+    match mode {
+        VersionMismatch::Strict => {
+            ui::print_info(&format!("{} — 安装档案版本", summary));
+            Ok(VersionChoice::Install(target))
+        }
+        VersionMismatch::PreferLocal => {
+            ui::print_info(&format!("{} — 保留本地版本", summary));
+            Ok(VersionChoice::Keep)
+        }
+        VersionMismatch::Ask => {
+            let items = [
+                "保留本地版本".to_string(),
+                format!("安装档案版本 {}", target),
+                "跳过（不导入该工具的配置）".to_string(),
+            ];
+            let sel = Select::with_theme(&ui::theme())
+                .with_prompt(format!("{} 版本不一致", summary))
+                .items(&items)
+                .default(0)
+                .interact_opt()
+                .context("选择被取消")?;
+            Ok(match sel {
+                Some(1) => VersionChoice::Install(target),
+                Some(0) => VersionChoice::Keep,
+                _ => VersionChoice::Skip,
+            })
+        }
+    }
+}
+
+async fn cmd_import(
+    config: &mut HudoConfig,
+    file: &str,
+    frozen: bool,
+    mode: VersionMismatch,
+) -> Result<()> {
     let file_path = std::path::Path::new(file);
     if !file_path.exists() {
         anyhow::bail!("文件不存在: {}", file);
@@ -923,11 +2083,14 @@ async fn cmd_import(config: &mut HudoConfig, file: &str) -> Result<()> {
 
     ui::print_title("导入环境档案");
 
-    let prof = profile::HudoProfile::load_from_file(file_path)?;
-    ui::print_info(&format!(
-        "档案版本: {}  导出时间: {}",
-        prof.hudo.version, prof.hudo.exported_at
-    ));
+    let mut prof = profile::HudoProfile::load_from_file(file_path)?;
+    match prof.hudo.exported_at.as_str() {
+        "" => ui::print_info(&format!("档案版本: {}", prof.hudo.version)),
+        at => ui::print_info(&format!("档案版本: {}  导出时间: {}", prof.hudo.version, at)),
+    }
+    if frozen && prof.lock.is_empty() {
+        anyhow::bail!("档案中没有 [lock] 段，请使用 hudo export --lock 重新导出");
+    }
 
     // 应用 settings
     let mut settings_changed = false;
@@ -947,21 +2110,10 @@ async fn cmd_import(config: &mut HudoConfig, file: &str) -> Result<()> {
     }
     // 应用 mirrors
     for (key, value) in &prof.settings.mirrors {
-        match key.as_str() {
-            "uv" => config.mirrors.uv = Some(value.clone()),
-            "fnm" => config.mirrors.fnm = Some(value.clone()),
-            "go" => config.mirrors.go = Some(value.clone()),
-            "java" => config.mirrors.java = Some(value.clone()),
-            "vscode" => config.mirrors.vscode = Some(value.clone()),
-            "pycharm" => config.mirrors.pycharm = Some(value.clone()),
-            "mysql" => config.mirrors.mysql = Some(value.clone()),
-            "pgsql" => config.mirrors.pgsql = Some(value.clone()),
-            "maven" => config.mirrors.maven = Some(value.clone()),
-            "gradle" => config.mirrors.gradle = Some(value.clone()),
-            _ => {}
+        if config.mirrors.set(key, value) {
+            ui::print_info(&format!("mirrors.{} = {}", key, value));
+            settings_changed = true;
         }
-        ui::print_info(&format!("mirrors.{} = {}", key, value));
-        settings_changed = true;
     }
     // 应用 versions
     for (key, value) in &prof.settings.versions {
@@ -990,40 +2142,121 @@ async fn cmd_import(config: &mut HudoConfig, file: &str) -> Result<()> {
 
     // 检测已安装工具，筛选出需要安装的
     let installers = all_installers();
-    let ctx = InstallContext { config };
-    let mut to_install = Vec::new();
-
-    for (tool_id, _ver) in &prof.tools {
+    let ctx = InstallContext::new(config);
+    let reg = registry::InstallRegistry::load(&config.state_path())?;
+    #[cfg(windows)]
+    let discovered = scan::scan_registry();
+    // (工具, 要切换到的档案版本)：None 为按当前配置安装
+    let mut to_install: Vec<(installer::ToolInfo, Option<String>)> = Vec::new();
+    // 切换版本的工具 → 本地原版本
+    let mut switched_from = std::collections::HashMap::new();
+    let mut ignored = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (tool_id, wanted) in &prof.tools {
         if let Some(inst) = installers.iter().find(|i| i.info().id == tool_id.as_str()) {
-            match inst.detect_installed(&ctx).await {
-                Ok(DetectResult::InstalledByHudo(ver)) => {
+            let detect = inst.detect_installed(&ctx).await;
+            // 与 hudo install 一致：PATH 上未找到时再查注册表
+            #[cfg(windows)]
+            let detect = match detect {
+                Ok(DetectResult::NotInstalled) => Ok(discovered
+                    .iter()
+                    .find(|d| d.tool_id == inst.info().id)
+                    .map(|d| DetectResult::InstalledExternal(d.version.clone()))
+                    .unwrap_or(DetectResult::NotInstalled)),
+                other => other,
+            };
+            match detect {
+                Ok(DetectResult::InstalledByHudo(ver)) if version::versions_match(&ver, wanted) => {
                     ui::print_info(&format!(
                         "{} 已安装 (hudo): {} — 跳过",
                         inst.info().name,
                         ver
                     ));
                 }
-                Ok(DetectResult::InstalledExternal(ver)) => {
+                Ok(DetectResult::InstalledByHudo(ver)) if reg.is_pinned(tool_id) => {
                     ui::print_info(&format!(
-                        "{} 已安装 (系统): {} — 跳过",
+                        "{} 已固定 (hudo pin): 保留本地版本 {}，不切换到档案版本 {}",
                         inst.info().name,
-                        ver
+                        ver,
+                        wanted
                     ));
                 }
+                Ok(DetectResult::InstalledByHudo(ver)) => {
+                    match resolve_version_mismatch(config, &inst.info(), &ver, wanted, mode)? {
+                        VersionChoice::Keep => {}
+                        VersionChoice::Install(target) => {
+                            switched_from.insert(inst.info().id, ver);
+                            to_install.push((inst.info(), Some(target)));
+                        }
+                        VersionChoice::Skip => skipped.push(tool_id.clone()),
+                    }
+                }
+                // 批量导入不逐个询问：只有记录为 takeover 的工具才接管，--ask-again 时逐个询问
+                Ok(DetectResult::InstalledExternal(_)) if config.ask_external_again => {
+                    to_install.push((inst.info(), None));
+                }
+                Ok(DetectResult::InstalledExternal(ver)) => match reg.external_policy(tool_id) {
+                    registry::ExternalPolicy::Takeover => {
+                        ui::print_info(&format!(
+                            "{} 已安装 (系统): {} — 按记录的选择由 hudo 接管",
+                            inst.info().name,
+                            ver
+                        ));
+                        to_install.push((inst.info(), None));
+                    }
+                    registry::ExternalPolicy::Ignore => ignored.push(inst.info().name),
+                    registry::ExternalPolicy::Ask => {
+                        ui::print_info(&format!(
+                            "{} 已安装 (系统): {} — 跳过",
+                            inst.info().name,
+                            ver
+                        ));
+                    }
+                },
                 _ => {
-                    to_install.push(inst.info());
+                    to_install.push((inst.info(), None));
                 }
             }
         }
     }
+    for tool_id in &skipped {
+        prof.tool_config.remove(tool_id);
+    }
+    if !ignored.is_empty() {
+        ui::print_info(&format!(
+            "按记录的选择保留系统版本，跳过: {}",
+            ignored.join(", ")
+        ));
+    }
+
+    if frozen {
+        let unlocked: Vec<&str> = to_install
+            .iter()
+            .filter(|(i, _)| !prof.lock.contains_key(i.id))
+            .map(|(i, _)| i.id)
+            .collect();
+        if !unlocked.is_empty() {
+            anyhow::bail!("以下工具在 [lock] 中没有记录: {}", unlocked.join(", "));
+        }
+    }
 
     if to_install.is_empty() {
         ui::print_success("所有工具已安装，无需操作");
     } else {
         println!();
         ui::print_info(&format!("需要安装 {} 个工具:", to_install.len()));
-        for info in &to_install {
-            println!("    {}  {}", console::style(info.name).bold(), info.description);
+        for (info, target) in &to_install {
+            match (target, switched_from.get(info.id)) {
+                (Some(target), Some(local)) => println!(
+                    "    {}  {} {} {}",
+                    console::style(info.name).bold(),
+                    local,
+                    ui::Glyph::Arrow,
+                    console::style(target).green()
+                ),
+                _ => println!("    {}  {}", console::style(info.name).bold(), info.description),
+            }
         }
 
         println!();
@@ -1040,43 +2273,79 @@ async fn cmd_import(config: &mut HudoConfig, file: &str) -> Result<()> {
 
         // 批量安装（skip_configure=true）
         let total = to_install.len();
-        let mut success_count = 0u32;
-        let mut fail_names = Vec::new();
+        let started = std::time::Instant::now();
+        let mut timings = Vec::new();
+        let mut version_changes = Vec::new();
 
-        for (idx, info) in to_install.iter().enumerate() {
+        for (idx, (info, target)) in to_install.iter().enumerate() {
             println!();
             ui::print_step(
                 (idx + 1) as u32,
                 total as u32,
                 &format!("安装 {}", info.name),
             );
-            if let Err(e) = cmd_install_inner(config, info.id, false).await {
+            // 切换版本：固定为档案版本并跳过「已安装」判断，仅对本次安装生效
+            // （--frozen 时以 [lock] 记录的版本为准）
+            let target = match frozen {
+                true => target.as_ref().map(|_| prof.lock[info.id].version.clone()),
+                false => target.clone(),
+            };
+            let mut pinned = config.clone();
+            if let Some(target) = &target {
+                pinned.pin_version(info.id, target);
+                pinned.reinstall = true;
+            }
+            let start = std::time::Instant::now();
+            let result = if frozen {
+                cmd_install_frozen(&pinned, &installers, info.id, &prof.lock[info.id]).await
+            } else {
+                cmd_install_inner(&pinned, info.id, false).await
+            };
+            timings.push((info.name, start.elapsed(), result.is_ok()));
+            // 以安装记录中的实际版本为准：没有切换成（如最新版地址上的内容未变化）时不算版本变更
+            if let (Ok(()), Some(local)) = (&result, switched_from.get(info.id)) {
+                let installed = registry::InstallRegistry::load(&config.state_path())
+                    .ok()
+                    .and_then(|reg| reg.get(info.id).map(|s| s.version.clone()));
+                if let Some(installed) = installed.filter(|v| !version::versions_match(local, v)) {
+                    version_changes.push(format!("{}: {} {} {}", info.name, local, ui::Glyph::Arrow, installed));
+                }
+            }
+            if let Err(e) = result {
                 ui::print_error(&format!("{} 安装失败: {}", info.name, e));
-                fail_names.push(info.name);
                 let cont = Confirm::new()
                     .with_prompt("  是否继续安装其余工具？")
                     .default(true)
                     .interact()
                     .unwrap_or(false);
                 if !cont {
+                    print_install_summary(&timings, started.elapsed());
                     anyhow::bail!("用户中止安装");
                 }
-            } else {
-                success_count += 1;
             }
         }
 
-        println!();
-        println!("{}", console::style("─".repeat(40)).cyan());
-        if fail_names.is_empty() {
-            ui::print_success(&format!("全部 {} 个工具安装完成", success_count));
-        } else {
-            ui::print_success(&format!("{} 个工具安装成功", success_count));
-            ui::print_warning(&format!(
-                "{} 个工具安装失败: {}",
-                fail_names.len(),
-                fail_names.join(", ")
-            ));
+        print_install_summary(&timings, started.elapsed());
+        if !version_changes.is_empty() {
+            ui::print_info("版本变更:");
+            for change in &version_changes {
+                println!("    {}", change);
+            }
+        }
+    }
+
+    // 恢复档案中的固定标记（只对 hudo 安装的工具生效）
+    if !prof.pinned.is_empty() {
+        let mut reg = registry::InstallRegistry::load(&config.state_path())?;
+        let newly: Vec<&str> = prof
+            .pinned
+            .iter()
+            .filter(|id| !reg.is_pinned(id) && reg.set_pinned(id, true))
+            .map(|id| id.as_str())
+            .collect();
+        if !newly.is_empty() {
+            reg.save(&config.state_path())?;
+            ui::print_info(&format!("按档案固定: {}", newly.join(", ")));
         }
     }
 
@@ -1086,23 +2355,33 @@ async fn cmd_import(config: &mut HudoConfig, file: &str) -> Result<()> {
         apply_tool_configs(config, &installers, &prof).await?;
     }
 
-    // 合并 cc_providers（按 name 去重，新的追加）
+    // 合并 cc_providers（按目标 + name 去重，新的追加）
     if !prof.cc_providers.is_empty() {
         println!();
-        let mut store = cc::CcProviders::load()?;
+        let mut store = ai::AiProviders::load()?;
         let mut added = 0u32;
+        let mut keyless = 0u32;
         for p in &prof.cc_providers {
-            if !store.providers.iter().any(|e| e.name == p.name) {
+            if !store.providers.iter().any(|e| e.target == p.target && e.name == p.name) {
                 store.providers.push(p.clone());
                 added += 1;
+                if p.api_key.is_empty() {
+                    keyless += 1;
+                }
             }
         }
         store.save()?;
         ui::print_info(&format!(
-            "Claude Code providers: {} 个已存在，新增 {} 个",
+            "AI 工具 providers: {} 个已存在，新增 {} 个",
             prof.cc_providers.len() as u32 - added,
             added
         ));
+        if keyless > 0 {
+            ui::print_warning(&format!(
+                "{} 个新增 provider 未包含 API Key，请运行 hudo ai 选择「编辑 Provider」补充",
+                keyless
+            ));
+        }
     }
 
     ui::print_info("请打开新终端以使环境变量生效");
@@ -1110,13 +2389,40 @@ async fn cmd_import(config: &mut HudoConfig, file: &str) -> Result<()> {
     Ok(())
 }
 
+/// 按 [lock] 记录安装：固定版本，确认解析出的下载地址一致，预先下载并校验 SHA256 后再安装
+async fn cmd_install_frozen(
+    config: &HudoConfig,
+    installers: &[Box<dyn installer::Installer>],
+    tool_id: &str,
+    lock: &profile::LockEntry,
+) -> Result<()> {
+    let inst = installer::find(installers, tool_id, "安装")?;
+
+    let mut pinned = config.clone();
+    pinned.pin_version(tool_id, &lock.version);
+    let (url, filename) = inst.resolve_download(&pinned);
+    if url != lock.url || filename != lock.filename {
+        anyhow::bail!(
+            "下载地址与 [lock] 不一致（镜像或版本配置可能已变化）\n  锁定: {}\n  当前: {}",
+            lock.url,
+            url
+        );
+    }
+
+    // 在安装器实际使用的下载中校验，「最新版」地址也不再按 ETag 重新下载未经校验的文件
+    if let Some(sha256) = &lock.sha256 {
+        download::freeze(&lock.url, sha256);
+    }
+    cmd_install_inner(&pinned, tool_id, false).await
+}
+
 /// 遍历 profile 中的 tool_config，调用各安装器的 import_config
 async fn apply_tool_configs(
     config: &HudoConfig,
     installers: &[Box<dyn installer::Installer>],
     prof: &profile::HudoProfile,
 ) -> Result<()> {
-    let ctx = InstallContext { config };
+    let ctx = InstallContext::new(config);
     for (tool_id, entries) in &prof.tool_config {
         if let Some(inst) = installers.iter().find(|i| i.info().id == tool_id.as_str()) {
             let pairs: Vec<(String, String)> = entries
@@ -1138,7 +2444,7 @@ async fn apply_tool_configs(
 async fn cmd_self_uninstall() -> Result<()> {
     ui::print_title("卸载 hudo");
 
-    let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+    let confirmed = Confirm::with_theme(&ui::theme())
         .with_prompt("确定要卸载 hudo 吗？")
         .default(false)
         .interact()
@@ -1148,7 +2454,7 @@ async fn cmd_self_uninstall() -> Result<()> {
         return Ok(());
     }
 
-    let del_config = Confirm::with_theme(&ColorfulTheme::default())
+    let del_config = Confirm::with_theme(&ui::theme())
         .with_prompt("同时删除配置文件和缓存？")
         .default(false)
         .interact()
@@ -1160,61 +2466,208 @@ async fn cmd_self_uninstall() -> Result<()> {
         .context("无法获取安装目录")?;
     let hudo_home = bin_dir.parent();
 
+    // bin 目录（删除配置时为整个 ~/.hudo）下的其他进程，如另一个终端里的 hudo
+    let in_use = match (del_config, hudo_home) {
+        (true, Some(home)) => home,
+        _ => bin_dir,
+    };
+    if !running::ensure_closed(&[in_use], "hudo 目录", false)? {
+        ui::print_info("已取消");
+        return Ok(());
+    }
+
+    // 移除 hudo 添加的 Defender 排除项，失败不影响卸载
+    if let Some(config) = HudoConfig::load().ok().flatten() {
+        let has_exclusions = registry::InstallRegistry::load(&config.state_path())
+            .is_ok_and(|reg| !reg.defender_exclusions.is_empty());
+        if has_exclusions {
+            if let Err(e) = cmd_defender_remove(&config) {
+                ui::print_warning(&format!("未能移除 Defender 排除项，请在 Windows 安全中心手动删除: {}", e));
+            }
+        }
+    }
+
     // 从 PATH 中移除 bin 目录
     let bin_str = bin_dir.to_string_lossy().to_string();
     env::EnvManager::remove_from_path(&bin_str).ok();
     env::EnvManager::broadcast_change();
     ui::print_success("已从 PATH 移除");
 
-    // 构建后台清理命令
-    let exe_str = current_exe.to_string_lossy().to_string();
+    let mut dirs = Vec::new();
+    if del_config {
+        dirs.extend(hudo_home.map(|h| h.to_path_buf()));
+    }
+    spawn_detached_cleanup(&current_exe, &dirs);
+
+    ui::print_success("hudo 已卸载，重启终端后生效");
+    Ok(())
+}
+
+/// 脱离控制台启动后台 PowerShell：等 hudo 退出后删除程序本身和指定目录
+#[cfg(windows)]
+fn spawn_detached_cleanup(exe: &std::path::Path, dirs: &[std::path::PathBuf]) {
+    let quote = |p: &std::path::Path| proc::ps_quote(&p.to_string_lossy());
     let mut ps_cmd = format!(
-        "Start-Sleep -Milliseconds 500; Remove-Item -Force '{}' -ErrorAction SilentlyContinue",
-        exe_str
+        "Start-Sleep -Milliseconds 500; Remove-Item -Force {} -ErrorAction SilentlyContinue",
+        quote(exe)
     );
-    if del_config {
-        if let Some(home) = hudo_home {
-            ps_cmd.push_str(&format!(
-                "; Remove-Item -Recurse -Force '{}' -ErrorAction SilentlyContinue",
-                home.to_string_lossy()
-            ));
-        }
+    for dir in dirs {
+        ps_cmd.push_str(&format!(
+            "; Remove-Item -Recurse -Force {} -ErrorAction SilentlyContinue",
+            quote(dir)
+        ));
     }
 
-    // 脱离控制台启动后台清理
     use std::os::windows::process::CommandExt;
     const DETACHED_PROCESS: u32 = 0x00000008;
     let _ = std::process::Command::new("powershell")
         .args(["-NoProfile", "-WindowStyle", "Hidden", "-Command", &ps_cmd])
         .creation_flags(DETACHED_PROCESS)
         .spawn();
-
-    ui::print_success("hudo 已卸载，重启终端后生效");
-    Ok(())
 }
 
-/// 更新 hudo 到最新版本（自替换）
+/// hudo nuke 需要手动输入的确认短语
 #[cfg(windows)]
-async fn cmd_update() -> Result<()> {
-    let current = env!("CARGO_PKG_VERSION");
+const NUKE_PHRASE: &str = "nuke hudo";
 
-    ui::print_action("检查最新版本...");
-    let latest = match version::hudo_latest().await {
-        Some(v) => v,
-        None => {
-            ui::print_error("无法获取版本信息，请检查网络连接");
-            return Ok(());
-        }
-    };
+/// 彻底清除 hudo：卸载全部工具，清理环境变量，删除安装根目录和 ~/.hudo，最后卸载自身
+#[cfg(windows)]
+async fn cmd_nuke() -> Result<()> {
+    let config = HudoConfig::load()?;
+    let current_exe = std::env::current_exe().context("无法获取当前程序路径")?;
+    let bin_dir = current_exe.parent().context("无法获取安装目录")?.to_path_buf();
+    let hudo_dir = HudoConfig::config_path()?
+        .parent()
+        .context("无法获取配置目录")?
+        .to_path_buf();
 
-    if latest == current {
-        ui::print_success(&format!("已是最新版本 v{}", current));
-        return Ok(());
+    let mut dirs = Vec::new();
+    if let Some(config) = &config {
+        dirs.extend(nuke_root_targets(config));
+    }
+    dirs.push(hudo_dir);
+    dirs.extend(shortcut::start_menu_dir().ok().filter(|d| d.exists()));
+
+    ui::print_title("彻底清除 hudo");
+    println!("  将执行以下操作，且无法恢复：");
+    println!("  - 卸载所有由 hudo 安装的工具（停止数据库服务）");
+    println!("  - 清理 hudo 添加的环境变量和 PATH");
+    for dir in &dirs {
+        println!("  - 删除 {}", dir.display());
+    }
+    println!("  - 删除 {}", current_exe.display());
+    println!();
+
+    let typed: String = Input::with_theme(&ui::theme())
+        .with_prompt(format!("输入 \"{}\" 确认", NUKE_PHRASE))
+        .allow_empty(true)
+        .interact_text()
+        .context("输入被取消")?;
+    if typed.trim() != NUKE_PHRASE {
+        ui::print_info("输入不匹配，已取消");
+        return Ok(());
+    }
+
+    if let Some(config) = &config {
+        cmd_uninstall_all(config, true, false).await?;
+        println!();
+        clean_leftover_env(config, &nuke_root_targets(config))?;
+    }
+
+    env::EnvManager::remove_from_path(&bin_dir.to_string_lossy()).ok();
+    env::EnvManager::broadcast_change();
+    ui::print_success("已清理环境变量");
+
+    // 已确认过清除短语，不再询问：关闭仍占用这些目录的程序，避免后台清理删不干净
+    let mut in_use: Vec<&std::path::Path> = dirs.iter().map(|d| d.as_path()).collect();
+    in_use.push(&bin_dir);
+    running::ensure_closed(&in_use, "hudo 目录", true)?;
+
+    spawn_detached_cleanup(&current_exe, &dirs);
+    ui::print_success("hudo 已彻底清除，重启终端后生效");
+    Ok(())
+}
+
+/// 要删除的安装根目录。root_dir 是磁盘根目录或用户主目录时只删除 hudo 创建的子目录
+#[cfg(windows)]
+fn nuke_root_targets(config: &HudoConfig) -> Vec<std::path::PathBuf> {
+    let root = config.root_path();
+    let is_home = dirs::home_dir().is_some_and(|h| path_eq(&h, &root));
+    if root.parent().is_none() || is_home {
+        vec![
+            config.tools_dir(),
+            config.lang_dir(),
+            config.ide_dir(),
+            config.cache_dir(),
+            config.state_path(),
+        ]
+    } else {
+        vec![root]
+    }
+}
+
+/// 清理未记录在 state.json 中、但仍指向待删除目录的环境变量和 PATH 条目
+#[cfg(windows)]
+fn clean_leftover_env(config: &HudoConfig, roots: &[std::path::PathBuf]) -> Result<()> {
+    let is_leftover = |p: &str| roots.iter().any(|root| path_under(std::path::Path::new(p), root));
+    for inst in all_installers() {
+        let install_path = default_install_path(config, inst.info().id);
+        for action in inst.env_actions(&install_path, config) {
+            if let EnvAction::Set { name, .. } = action {
+                let value = env::EnvManager::get_var(&name)?;
+                if value.is_some_and(|v| is_leftover(&v)) {
+                    env::EnvManager::delete_var(&name)?;
+                    ui::print_info(&format!("移除环境变量: {}", name));
+                }
+            }
+        }
+    }
+
+    let path = env::EnvManager::get_var("Path")?.unwrap_or_default();
+    for entry in path.split(';').filter(|p| !p.is_empty() && is_leftover(p)) {
+        env::EnvManager::remove_from_path(entry)?;
+        ui::print_info(&format!("PATH -= {}", entry));
+    }
+    Ok(())
+}
+
+/// Windows 路径不区分大小写
+#[cfg(windows)]
+fn path_eq(a: &std::path::Path, b: &std::path::Path) -> bool {
+    let a = a.to_string_lossy();
+    let b = b.to_string_lossy();
+    a.trim_end_matches('\\').eq_ignore_ascii_case(b.trim_end_matches('\\'))
+}
+
+/// path 是否位于 root 之内（含 root 本身）
+#[cfg(windows)]
+fn path_under(path: &std::path::Path, root: &std::path::Path) -> bool {
+    path.ancestors().any(|a| path_eq(a, root))
+}
+
+/// 更新 hudo 到最新版本（自替换）
+#[cfg(windows)]
+async fn cmd_update() -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+
+    ui::print_action("检查最新版本...");
+    let latest = match version::hudo_latest().await {
+        Ok(v) => v,
+        Err(e) => {
+            ui::print_error(&format!("无法获取版本信息：{}", e));
+            return Ok(());
+        }
+    };
+
+    if latest == current {
+        ui::print_success(&format!("已是最新版本 v{}", current));
+        return Ok(());
     }
 
     println!(
-        "  发现新版本: {} → {}",
+        "  发现新版本: {} {} {}",
         console::style(format!("v{}", current)).dim(),
+        ui::Glyph::Arrow,
         console::style(format!("v{}", latest)).cyan().bold()
     );
 
@@ -1273,8 +2726,8 @@ async fn cmd_update() -> Result<()> {
             "Hidden",
             "-Command",
             &format!(
-                "Start-Sleep -Milliseconds 1000; Remove-Item -Force '{}' -ErrorAction SilentlyContinue",
-                old_str
+                "Start-Sleep -Milliseconds 1000; Remove-Item -Force {} -ErrorAction SilentlyContinue",
+                proc::ps_quote(&old_str)
             ),
         ])
         .creation_flags(DETACHED_PROCESS)
@@ -1284,29 +2737,48 @@ async fn cmd_update() -> Result<()> {
     Ok(())
 }
 
-/// 快速检测：从 state.json 读取版本，仅做路径存在检查，无需子进程
-fn fast_detect(id: &str, reg: &registry::InstallRegistry) -> Option<DetectResult> {
-    let state = reg.get(id)?;
+/// 快速检测：从 state.json 读取版本，检查安装目录及主程序是否存在，无需子进程
+/// 检查不通过（如只剩空目录）时返回 None，交由 detect_installed 完整检测
+fn fast_detect(inst: &dyn installer::Installer, reg: &registry::InstallRegistry) -> Option<DetectResult> {
+    let state = reg.get(inst.info().id)?;
     let path = std::path::Path::new(&state.install_path);
-    if path.exists() {
-        Some(DetectResult::InstalledByHudo(state.version.clone()))
-    } else {
-        None
+    if !path.is_dir() {
+        return None;
+    }
+    let binaries = inst.binaries();
+    let binary = binaries.iter().map(|b| path.join(b)).find(|p| p.is_file());
+    if !binaries.is_empty() && binary.is_none() {
+        return None;
     }
+    // 主程序与安装时的大小 / 修改时间不同（工具自更新或被替换）：记录的版本可能已过时，重新探测
+    if let (Some(recorded), Some(binary)) = (&state.fingerprint, &binary) {
+        if registry::Fingerprint::of(binary).as_ref() != Some(recorded) {
+            return None;
+        }
+    }
+    Some(DetectResult::InstalledByHudo(state.version.clone()))
+}
+
+/// 主程序（binaries() 中第一个存在的）的指纹，未声明主程序的工具为 None
+fn binary_fingerprint(inst: &dyn installer::Installer, install_path: &std::path::Path) -> Option<registry::Fingerprint> {
+    let binary = inst.binaries().iter().map(|b| install_path.join(b)).find(|p| p.is_file())?;
+    registry::Fingerprint::of(&binary)
 }
 
 /// 并行检测工具安装状态：
 /// - hudo 工具：读 state.json，无子进程，近乎瞬间
 /// - 外部工具：并行在独立线程中运行子进程检测
+///
+/// `reg` 为 None 时跳过快速检测，全部重新探测（hudo list --verify）
 fn detect_all_parallel(
     tools: &[&dyn installer::Installer],
     config: &HudoConfig,
-    reg: &registry::InstallRegistry,
+    reg: Option<&registry::InstallRegistry>,
 ) -> Vec<(installer::ToolInfo, Result<DetectResult>)> {
     // 第一步：state.json 快速检测
     let mut results: Vec<Option<Result<DetectResult>>> = tools
         .iter()
-        .map(|inst| fast_detect(inst.info().id, reg).map(Ok))
+        .map(|inst| reg.and_then(|reg| fast_detect(*inst, reg)).map(Ok))
         .collect();
 
     // 找出需要子进程检测的工具（不在 state.json 中的）
@@ -1317,232 +2789,1197 @@ fn detect_all_parallel(
         .collect();
 
     if !pending.is_empty() {
-        // 获取当前 tokio runtime 句柄，供非 tokio 线程使用
-        let handle = tokio::runtime::Handle::current();
-        std::thread::scope(|s| {
-            // 并行启动所有子进程检测
-            let handles: Vec<(usize, _)> = pending
-                .iter()
-                .map(|&i| {
-                    let inst = tools[i];
-                    let handle = handle.clone();
-                    let config = config;
-                    (
-                        i,
-                        s.spawn(move || {
-                            let ctx = InstallContext { config };
-                            handle.block_on(inst.detect_installed(&ctx))
-                        }),
-                    )
-                })
+        let pending_tools: Vec<&dyn installer::Installer> = pending.iter().map(|&i| tools[i]).collect();
+        let detected = installer::detect_parallel(&pending_tools, config);
+        for (i, r) in pending.into_iter().zip(detected) {
+            results[i] = Some(r);
+        }
+    }
+
+    let results: Vec<_> = tools
+        .iter()
+        .zip(results.into_iter())
+        .map(|(inst, r)| (inst.info(), r.unwrap_or(Ok(DetectResult::NotInstalled))))
+        .collect();
+
+    #[cfg(windows)]
+    let results = mark_registry_installs(results);
+    results
+}
+
+/// 不在 PATH 上但注册表中有安装记录的工具，标记为外部安装（使用注册表中的版本）
+#[cfg(windows)]
+fn mark_registry_installs(
+    mut results: Vec<(installer::ToolInfo, Result<DetectResult>)>,
+) -> Vec<(installer::ToolInfo, Result<DetectResult>)> {
+    if !results.iter().any(|(_, r)| matches!(r, Ok(DetectResult::NotInstalled))) {
+        return results;
+    }
+    let found = scan::scan_registry();
+    for (info, r) in results.iter_mut() {
+        if !matches!(r, Ok(DetectResult::NotInstalled)) {
+            continue;
+        }
+        if let Some(d) = found.iter().find(|d| d.tool_id == info.id) {
+            *r = Ok(DetectResult::InstalledExternal(d.version.clone()));
+        }
+    }
+    results
+}
+
+/// 扫描注册表 Uninstall 项与 winget，列出系统中已有的开发工具
+#[cfg(windows)]
+fn cmd_scan(config: &HudoConfig) -> Result<()> {
+    ui::print_title("扫描系统已安装工具");
+    ui::print_action("读取注册表与 winget 列表...");
+    let found = scan::scan_all();
+    print_discovered(config, &found)?;
+    if !found.is_empty() {
+        println!();
+        ui::print_info("运行 hudo install <工具> 可由 hudo 接管（清理旧版并重新安装）");
+    }
+
+    // PATH 上由 Scoop / Chocolatey / winget 提供的命令，hudo 安装同名工具时会冲突
+    let managed: Vec<_> = all_installers()
+        .iter()
+        .flat_map(|i| {
+            let name = i.info().name;
+            scan::package_manager_commands(i.info().id)
+                .into_iter()
+                .map(move |cmd| (name, cmd))
+        })
+        .collect();
+    if !managed.is_empty() {
+        println!();
+        ui::print_title("PATH 上由其他包管理器提供的命令");
+        for (name, cmd) in &managed {
+            println!(
+                "  {}  {}  {}",
+                ui::pad(name, 16),
+                ui::pad(cmd.manager, 12),
+                console::style(&cmd.path).dim()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// 启动 / 停止 / 查看工具注册的 Windows 服务
+#[cfg(windows)]
+fn cmd_service(tool_id: &str, action: cli::ServiceAction) -> Result<()> {
+    use cli::ServiceAction;
+    use installer::{query_service_state, run_as_admin, ServiceState};
+
+    let installers = all_installers();
+    let inst = installer::find(&installers, tool_id, "管理")?;
+    let info = inst.info();
+    let Some(service) = inst.service_name() else {
+        let supported: Vec<&str> = installers
+            .iter()
+            .filter(|i| i.service_name().is_some())
+            .map(|i| i.info().id)
+            .collect();
+        anyhow::bail!("{} 没有 Windows 服务，支持: {}", info.name, supported.join(", "));
+    };
+
+    let state = query_service_state(service);
+    let (verb, done) = match action {
+        ServiceAction::Status => {
+            let label = match state {
+                ServiceState::Running => console::style(state.label()).green(),
+                ServiceState::Stopped => console::style(state.label()).yellow(),
+                ServiceState::NotFound => console::style(state.label()).dim(),
+            };
+            println!("  {}  {}", ui::pad(service, 12), label);
+            return Ok(());
+        }
+        ServiceAction::Start => ("start", "已启动"),
+        ServiceAction::Stop => ("stop", "已停止"),
+    };
+
+    match (&state, action) {
+        (ServiceState::NotFound, _) => {
+            anyhow::bail!(
+                "{} 服务未注册，请先运行 hudo install {}（db_autoservice = false 时不注册服务）",
+                service,
+                info.id
+            )
+        }
+        (ServiceState::Running, ServiceAction::Start) => {
+            ui::print_success(&format!("{} 服务已在运行", service));
+            return Ok(());
+        }
+        (ServiceState::Stopped, ServiceAction::Stop) => {
+            ui::print_success(&format!("{} 服务未在运行", service));
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    ui::print_action(&format!("net {} {}...", verb, service));
+    let direct_ok = std::process::Command::new("net")
+        .args([verb, service])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    if !direct_ok {
+        // 非管理员终端无权启停服务，触发 UAC
+        ui::print_info("需要管理员权限，请在弹出的 UAC 窗口中点击\"是\"...");
+        run_as_admin("net", &[verb, service])
+            .with_context(|| format!("net {} {} 失败", verb, service))?;
+    }
+    ui::print_success(&format!(
+        "{} 服务{}（当前状态: {}）",
+        service,
+        done,
+        query_service_state(service).label()
+    ));
+    Ok(())
+}
+
+/// hudo doctor 发现的一个问题；fix 为 None 时只能提示
+#[cfg(windows)]
+struct Problem {
+    message: String,
+    fix: Option<Fix>,
+}
+
+/// 问题的自动修复方式
+#[cfg(windows)]
+enum Fix {
+    /// 重建开始菜单快捷方式
+    Shortcut { menu_dir: std::path::PathBuf, spec: installer::ShortcutSpec },
+    /// 从 PATH 移除孤立项（系统 PATH 需要以管理员身份手动删除）
+    PathEntry { scope: takeover::PathScope, entry: String },
+    /// 把 JAVA_HOME 从失效的目录改为 hudo 安装的 JDK
+    JavaHome { home: String, jdk: std::path::PathBuf },
+}
+
+#[cfg(windows)]
+impl Problem {
+    fn new(message: String) -> Self {
+        Self { message, fix: None }
+    }
+}
+
+/// 环境检查结果，hudo doctor 与 hudo status 共用
+#[cfg(windows)]
+struct DoctorReport {
+    problems: Vec<Problem>,
+    /// PATH 中与 hudo 相关的目录及所属工具
+    path_owners: Vec<(takeover::PathScope, env::PathOwner)>,
+}
+
+/// 检查 hudo 管理的工具是否处于正常状态（只检查，不修复）
+#[cfg(windows)]
+fn doctor_checks(config: &HudoConfig) -> Result<DoctorReport> {
+    let installers = all_installers();
+    let reg = registry::InstallRegistry::load(&config.state_path())?;
+    let managed: Vec<_> = installers
+        .iter()
+        .filter_map(|i| reg.get(i.info().id).map(|s| (i.as_ref(), s)))
+        .collect();
+    let mut problems = Vec::new();
+
+    // 1. 安装目录不应位于同步目录、网络驱动器或可移动磁盘
+    if let Some(risk) = config::root_dir_risk(&config.root_path()) {
+        problems.push(Problem::new(format!(
+            "安装目录 {} {}，同步或断开时工具与数据库数据可能损坏",
+            config.root_dir,
+            risk.describe()
+        )));
+    }
+
+    // 2. 开始菜单快捷方式
+    let menu_dir = shortcut::start_menu_dir()?;
+    for (inst, state) in &managed {
+        for spec in inst.shortcuts(std::path::Path::new(&state.install_path)) {
+            let lnk = shortcut::lnk_path(&menu_dir, &spec);
+            let problem = if !lnk.exists() {
+                "缺少开始菜单快捷方式"
+            } else if !shortcut::read_target(&lnk).is_ok_and(|t| path_eq(&t, &spec.target)) {
+                "开始菜单快捷方式指向错误"
+            } else {
+                continue;
+            };
+            problems.push(Problem {
+                message: format!("{}: {}", problem, spec.name),
+                fix: Some(Fix::Shortcut { menu_dir: menu_dir.clone(), spec }),
+            });
+        }
+    }
+
+    // 3. 其他包管理器提供的同名命令会与 hudo 安装的版本争夺 PATH
+    for (inst, _) in &managed {
+        for cmd in scan::package_manager_commands(inst.info().id) {
+            problems.push(Problem::new(format!(
+                "{} 与 {} 提供的命令冲突: {}",
+                inst.info().name,
+                cmd.manager,
+                cmd.path
+            )));
+        }
+    }
+
+    // 4. 系统 PATH 等处的旧版排在 hudo 安装的命令之前（系统 PATH 总是先于用户 PATH 查找）
+    for (inst, state) in &managed {
+        let install_path = std::path::Path::new(&state.install_path);
+        for bin in inst.binaries() {
+            let Some(name) = std::path::Path::new(bin).file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if !install_path.join(bin).exists() {
+                continue;
+            }
+            if let Some(winner) = takeover::shadowing(&takeover::resolve_command(name), install_path) {
+                problems.push(Problem::new(format!(
+                    "{} 的 {} 被 {} 遮蔽，运行 hudo which {} 查看",
+                    inst.info().name,
+                    name,
+                    winner.display(),
+                    name
+                )));
+            }
+        }
+    }
+
+    // 5. PATH 中与 hudo 相关的目录归哪个工具；根目录下没有工具声明的是孤立项（卸载残留或手动添加）
+    let claims = env::path_claims(&reg, &installers, config);
+    let lookup = |name: &str| env::EnvManager::get_var(name).ok().flatten().or_else(|| std::env::var(name).ok());
+    let root = config.root_path();
+    let scopes = [
+        (takeover::PathScope::System, env::EnvManager::system_path_entries()),
+        (takeover::PathScope::User, env::EnvManager::user_path()?),
+    ];
+    let mut path_owners = Vec::new();
+    for (scope, entries) in &scopes {
+        for owner in env::path_owners(entries, &claims, &root, lookup) {
+            if owner.tool.is_none() {
+                problems.push(Problem {
+                    message: format!(
+                        "{} 中的 {} 位于安装目录下，但没有已安装的工具声明它",
+                        scope.label(),
+                        owner.entry
+                    ),
+                    fix: Some(Fix::PathEntry { scope: *scope, entry: owner.entry.clone() }),
+                });
+            }
+            path_owners.push((*scope, owner));
+        }
+    }
+
+    // 6. JAVA_HOME 指向已删除的 JDK 时 Maven / Gradle 无法运行
+    if let Some(home) = installer::jdk::stale_java_home() {
+        problems.push(match installer::jdk::hudo_jdk_home(config) {
+            Some(jdk) => Problem {
+                message: format!("JAVA_HOME 指向的目录中没有 bin\\java.exe: {}", home),
+                fix: Some(Fix::JavaHome { home, jdk }),
+            },
+            None => Problem::new(format!(
+                "JAVA_HOME 指向的目录中没有 bin\\java.exe: {}，运行 hudo install jdk 安装并重设",
+                home
+            )),
+        });
+    }
+
+    // 7. 数据库服务是否仍已注册（停止不算问题；db_autoservice = false 时本就不注册）
+    if config.db_autoservice {
+        for (inst, _) in &managed {
+            let Some(service) = inst.service_name() else {
+                continue;
+            };
+            if matches!(installer::query_service_state(service), installer::ServiceState::NotFound) {
+                problems.push(Problem::new(format!(
+                    "{} 的 Windows 服务 {} 未注册，运行 hudo install {} 重新配置",
+                    inst.info().name,
+                    service,
+                    inst.info().id
+                )));
+            }
+        }
+    }
+
+    Ok(DoctorReport { problems, path_owners })
+}
+
+/// 检查 hudo 管理的工具是否处于正常状态，fix 为 true 时修复可自动修复的问题
+#[cfg(windows)]
+fn cmd_doctor(config: &HudoConfig, fix: bool) -> Result<()> {
+    ui::print_title("环境检查");
+    let report = doctor_checks(config)?;
+
+    if !report.path_owners.is_empty() {
+        ui::print_info("PATH 中与 hudo 相关的目录:");
+        for (scope, owner) in &report.path_owners {
+            let tool = match &owner.tool {
+                Some(id) => console::style(id.as_str()).green().to_string(),
+                None => console::style("无工具声明").yellow().to_string(),
+            };
+            println!("    {}  {}  {}", owner.entry, tool, console::style(scope.label()).dim());
+        }
+    }
+
+    for problem in &report.problems {
+        let (true, Some(action)) = (fix, &problem.fix) else {
+            ui::print_warning(&problem.message);
+            continue;
+        };
+        let result = match action {
+            Fix::Shortcut { menu_dir, spec } => shortcut::create(menu_dir, spec)
+                .map(|_| format!("已重建开始菜单快捷方式: {}", spec.name)),
+            Fix::PathEntry { scope: takeover::PathScope::User, entry } => env::EnvManager::remove_from_path(entry)
+                .map(|()| format!("已从用户 PATH 移除孤立项: {}", entry)),
+            Fix::PathEntry { scope: takeover::PathScope::System, entry } => {
+                ui::print_warning(&format!("系统 PATH 中的孤立项需要以管理员身份手动删除: {}", entry));
+                continue;
+            }
+            Fix::JavaHome { home, jdk } => installer::jdk::repair_java_home(config, jdk)
+                .map(|()| format!("JAVA_HOME 已从 {} 改为 {}", home, jdk.display())),
+        };
+        match result {
+            Ok(done) => ui::print_success(&done),
+            Err(e) => ui::print_error(&format!("{:#}", e)),
+        }
+    }
+
+    println!();
+    if report.problems.is_empty() {
+        ui::print_success("未发现问题");
+    } else if !fix {
+        ui::print_info(&format!(
+            "发现 {} 个问题，运行 hudo doctor --fix 修复可自动修复的项",
+            report.problems.len()
+        ));
+    }
+    Ok(())
+}
+
+/// hudo status：版本、配置、安装目录与磁盘空间、已安装工具数、缓存、更新检查、服务与环境检查的概览
+fn cmd_status(config: &HudoConfig, json: bool) -> Result<()> {
+    let reg = registry::InstallRegistry::load(&config.state_path())?;
+    let root = config.root_path();
+    let cache_dir = config.cache_dir();
+
+    #[cfg(windows)]
+    let services: Vec<status::Service> = all_installers()
+        .iter()
+        .filter_map(|i| {
+            let name = i.service_name()?;
+            let state = installer::query_service_state(name);
+            Some(status::Service { tool: i.info().id, name, state: state.key(), label: state.label() })
+        })
+        .collect();
+    #[cfg(not(windows))]
+    let services = Vec::new();
+
+    #[cfg(windows)]
+    let warnings = Some(doctor_checks(config)?.problems.into_iter().map(|p| p.message).collect());
+    #[cfg(not(windows))]
+    let warnings = None;
+
+    let status = status::Status {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        config_path: paths::config_path()?.display().to_string(),
+        portable: paths::is_portable(),
+        root_dir: root.display().to_string(),
+        disk: config::disk_space(&root).map(|(free, total)| status::DiskSpace { free, total }),
+        tools: status::count_by_category(reg.tools.keys().map(String::as_str)),
+        cache_dir: cache_dir.display().to_string(),
+        cache_size: cache::dir_size(&cache_dir),
+        update_check: version::last_update_check().map(|(checked_at, latest)| status::UpdateCheck {
+            checked_at: registry::format_timestamp(checked_at),
+            latest,
+        }),
+        env_pending: reg.env_pending(),
+        services,
+        warnings,
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&status)?);
+    } else {
+        status::print(&status);
+    }
+    Ok(())
+}
+
+/// hudo which：命令的全部位置（按 PATH 查找顺序），标出生效的一个、所在的 PATH 以及是否由 hudo 安装
+#[cfg(windows)]
+fn cmd_which(config: &HudoConfig, command: &str) -> Result<()> {
+    let found = takeover::resolve_command(command);
+    if found.is_empty() {
+        ui::print_info(&format!("PATH 中没有找到 {}", command));
+        return Ok(());
+    }
+    let user_path = env::EnvManager::user_path()?;
+    let system_path = env::EnvManager::system_path_entries();
+    let lookup = |name: &str| env::EnvManager::get_var(name).ok().flatten().or_else(|| std::env::var(name).ok());
+    let root = config.root_path();
+
+    println!();
+    for (i, path) in found.iter().enumerate() {
+        let scope = path
+            .parent()
+            .and_then(|dir| takeover::path_scope(dir, &user_path, &system_path, lookup))
+            .map_or("应用路径或当前目录", |s| s.label());
+        let owner = match running::is_under(path, &root) {
+            true => format!("  {}", console::style("hudo").green()),
+            false => String::new(),
+        };
+        let mark = match i {
+            0 => console::style(ui::Glyph::Arrow).green().to_string(),
+            _ => " ".repeat(ui::Glyph::Arrow.as_str().len()),
+        };
+        println!("  {} {}  {}{}", mark, path.display(), console::style(scope).dim(), owner);
+    }
+    println!();
+
+    if let Some(winner) = takeover::shadowing(&found, &root) {
+        ui::print_warning(&format!("生效的是 {}，不是 hudo 安装的版本", winner.display()));
+        let in_system = winner
+            .parent()
+            .and_then(|dir| takeover::path_scope(dir, &user_path, &system_path, lookup))
+            == Some(takeover::PathScope::System);
+        if in_system {
+            ui::print_info("Windows 总是先查找系统 PATH，需要以管理员身份从系统 PATH 删除该目录（hudo install 接管时可自动处理）");
+        }
+    }
+    Ok(())
+}
+
+/// 打印扫描结果，标注已由 hudo 管理的工具
+#[cfg(windows)]
+fn print_discovered(config: &HudoConfig, found: &[scan::Discovered]) -> Result<()> {
+    if found.is_empty() {
+        ui::print_info("未发现已知的开发工具");
+        return Ok(());
+    }
+    let installers = all_installers();
+    let reg = registry::InstallRegistry::load(&config.state_path())?;
+    println!();
+    for d in found {
+        let name = installers
+            .iter()
+            .find(|i| i.info().id == d.tool_id)
+            .map(|i| i.info().name)
+            .unwrap_or(d.tool_id);
+        let managed = if reg.get(d.tool_id).is_some() {
+            console::style("(hudo)").green().to_string()
+        } else {
+            String::new()
+        };
+        println!(
+            "    {}  {}  {}  {}",
+            console::style(ui::pad(name, 12)).bold(),
+            ui::pad(&d.version, 16),
+            console::style(format!("{} — {}", d.source, d.display_name)).dim(),
+            managed,
+        );
+    }
+    Ok(())
+}
+
+/// 列出所有工具状态
+async fn cmd_list(config: &HudoConfig, opts: &list::ListOptions) -> Result<()> {
+    if !opts.json {
+        ui::print_title(if opts.show_all { t("list.all_title") } else { t("list.installed_title") });
+    }
+
+    let installers = all_installers();
+    let mut reg = registry::InstallRegistry::load(&config.state_path())?;
+
+    // 只检测要显示的分类，收集检测结果（并行）
+    let tool_refs = list::select(&installers, opts.category);
+    let all_results = detect_all_parallel(&tool_refs, config, (!opts.verify).then_some(&reg));
+
+    // --verify：实际探测到的版本与 state.json 不一致时（如工具自更新），更新记录，并重新记录主程序指纹
+    if opts.verify {
+        let mut changed = false;
+        for (inst, (info, detect)) in tool_refs.iter().zip(&all_results) {
+            let Ok(DetectResult::InstalledByHudo(ver)) = detect else {
+                continue;
+            };
+            if let Some(old) = reg.update_version(info.id, ver) {
+                if !opts.json {
+                    let new = reg.get(info.id).map_or(ver.as_str(), |s| s.version.as_str());
+                    ui::print_info(&format!("{} 版本已更新: {} {} {}", info.name, old, ui::Glyph::Arrow, new));
+                }
+                changed = true;
+            }
+            if let Some(state) = reg.get(info.id) {
+                let fingerprint = binary_fingerprint(*inst, std::path::Path::new(&state.install_path));
+                if state.fingerprint != fingerprint {
+                    reg.set_fingerprint(info.id, fingerprint);
+                    changed = true;
+                }
+            }
+        }
+        if changed {
+            reg.save(&config.state_path())?;
+        }
+    }
+
+    let mut rows = list::filter_rows(list::build_rows(&all_results, &reg), opts.show_all);
+    if let Some(sort) = opts.sort {
+        list::sort_rows(&mut rows, sort);
+    }
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    // 单元格：名称、[分类]、描述、状态；列宽按全部要显示的行计算，分组显示时各组同样对齐
+    let cells = |row: &list::Row, category: Option<&str>| {
+        let version = row.version.as_deref().unwrap_or_default();
+        let status = match row.status {
+            list::Status::Hudo => {
+                let mut extra = String::new();
+                if let Some(at) = &row.installed_at {
+                    extra.push_str(&format!("  {}", console::style(format!("({})", at)).dim()));
+                }
+                if row.pinned {
+                    extra.push_str(&format!("  {}", console::style("(已固定)").cyan()));
+                }
+                if !row.dependency_of.is_empty() {
+                    extra.push_str(&format!(
+                        "  {}",
+                        console::style(format!("（随 {} 安装）", row.dependency_of.join("、"))).dim()
+                    ));
+                }
+                format!("{}{}", console::style(version).green(), extra)
+            }
+            list::Status::External => format!(
+                "{} {}",
+                console::style(version).green(),
+                console::style("(非 hudo)").yellow()
+            ),
+            list::Status::NotInstalled => console::style("·").dim().to_string(),
+            list::Status::Failed => console::style("检测失败").red().to_string(),
+        };
+        let mut cells = vec![console::style(row.name).bold().to_string()];
+        cells.extend(category.map(|c| console::style(c).dim().to_string()));
+        cells.extend([row.description.to_string(), status]);
+        cells
+    };
+
+    match opts.sort {
+        // 指定排序时不分组，另起一列显示分类
+        Some(_) => {
+            if !rows.is_empty() {
+                println!();
+            }
+            let mut table = ui::Table::new(4);
+            for row in &rows {
+                table.row(cells(row, Some(row.category.label())));
+            }
+            for line in table.render() {
+                println!("{}", line);
+            }
+        }
+        None => {
+            let groups: Vec<(ui::ToolCategory, Vec<&list::Row>)> = ui::ToolCategory::ALL
+                .into_iter()
+                .map(|cat| (cat, rows.iter().filter(|r| r.category == cat).collect::<Vec<_>>()))
+                .filter(|(_, cat_rows)| !cat_rows.is_empty())
                 .collect();
+            let mut table = ui::Table::new(4);
+            for row in groups.iter().flat_map(|(_, cat_rows)| cat_rows) {
+                table.row(cells(row, None));
+            }
+            let mut lines = table.render().into_iter();
+            for (cat, cat_rows) in &groups {
+                ui::print_section(cat.label());
+                for line in lines.by_ref().take(cat_rows.len()) {
+                    println!("{}", line);
+                }
+            }
+        }
+    }
+
+    if rows.is_empty() {
+        match opts.category {
+            Some(cat) => ui::print_info(&tf("list.empty_category", &[&cat.label()])),
+            None => ui::print_info(t("list.empty")),
+        }
+    }
+
+    println!();
+    let hudo_count = rows.iter().filter(|r| r.status == list::Status::Hudo).count();
+    let external_count = rows.iter().filter(|r| r.status == list::Status::External).count();
+    let total = hudo_count + external_count;
+    if total > 0 {
+        ui::print_info(&tf("list.summary", &[&total, &hudo_count, &external_count]));
+    }
+    if !opts.show_all && total > 0 {
+        ui::print_info(t("list.hint_all"));
+    }
+    ui::print_info(&tf("list.root", &[&config.root_dir]));
+    Ok(())
+}
+
+fn cmd_config_show(config: &HudoConfig) -> Result<()> {
+    ui::print_title("当前配置");
+
+    let root = config.root_path().to_string_lossy().to_string();
+    match root == config.root_dir {
+        true => println!("  {}  {}", ui::pad("root_dir", 20), config.root_dir),
+        false => println!("  {}  {}  {}", ui::pad("root_dir", 20), config.root_dir, console::style(format!("{} {}", ui::Glyph::Arrow, root)).dim()),
+    }
+    println!("  {}  {}", ui::pad("check_updates", 20), config.check_updates);
+    println!("  {}  {}", ui::pad("git.install_mode", 20), config.git.install_mode);
+    println!("  {}  {}", ui::pad("java.version", 20), config.java.version);
+    println!("  {}  {}", ui::pad("go.version", 20), config.go.version);
+    println!("  {}  {}", ui::pad("mysql.port", 20), config.mysql.port);
+    println!("  {}  {}", ui::pad("pgsql.port", 20), config.pgsql.port);
+    println!("  {}  {}", ui::pad("db_autoservice", 20), config.db_autoservice);
+    println!("  {}  {}", ui::pad("install.idle_minutes", 20), config.install.idle_minutes);
+    if let Some(minutes) = config.install.silent_idle_minutes {
+        println!("  {}  {}", ui::pad("install.silent_idle_minutes", 20), minutes);
+    }
+    let desktop = match config.desktop_shortcuts {
+        Some(v) => v.to_string(),
+        None => "ask".to_string(),
+    };
+    println!("  {}  {}", ui::pad("desktop_shortcuts", 20), desktop);
+    if let Some(bps) = config.max_download_bps {
+        println!("  {}  {}", ui::pad("max_download_bps", 20), bps);
+    }
+    println!("  {}  {}", ui::pad("lang", 20), config.lang.as_deref().unwrap_or("auto"));
+    if let Some(token) = &config.github_token {
+        println!("  {}  {}", ui::pad("github_token", 20), ai::mask_key(token));
+    }
 
-            // 等待所有线程完成（已并行执行）
-            for (i, h) in handles {
-                results[i] = Some(
-                    h.join()
-                        .unwrap_or_else(|_| Err(anyhow::anyhow!("检测线程崩溃"))),
+    let has_versions = config::VERSION_KEYS.iter().any(|k| config.version_of(k).is_some());
+    if has_versions {
+        println!();
+        for key in config::VERSION_KEYS {
+            if let Some(v) = config.version_of(key) {
+                println!("  {}  {}", ui::pad(&format!("versions.{}", key), 20), v);
+            }
+        }
+    }
+
+    if !config.channels.is_empty() {
+        println!();
+        for (tool, channel) in &config.channels {
+            println!("  {}  {}", ui::pad(&format!("channels.{}", tool), 20), channel);
+        }
+    }
+
+    let has_mirrors = config::MirrorConfig::keys().any(|k| config.mirrors.get(k).is_some());
+    if has_mirrors {
+        println!();
+        for key in config::MirrorConfig::keys() {
+            if let Some(v) = config.mirrors.get(key) {
+                println!("  {}  {}", ui::pad(&format!("mirrors.{}", key), 20), v);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 解析下载限速，0 表示不限速
+fn parse_rate(value: &str) -> Result<Option<u64>> {
+    let bps = download::parse_rate(value)
+        .context("max_download_bps 必须是字节数，可带 K / M / G 后缀（如 2M、5MB/s），0 表示不限速")?;
+    Ok(Some(bps).filter(|&b| b > 0))
+}
+
+/// 解析 1-65535 之间的端口号
+fn parse_port(key: &str, value: &str) -> Result<u16> {
+    value
+        .parse::<u16>()
+        .ok()
+        .filter(|&p| p != 0)
+        .with_context(|| format!("{} 必须是 1-65535 之间的端口号", key))
+}
+
+/// 设置工具的发布渠道（校验工具支持该渠道）
+fn set_channel(config: &mut HudoConfig, tool_id: &str, channel: &str) -> Result<()> {
+    let installers = all_installers();
+    let inst = installer::find(&installers, tool_id, "设置")?;
+    installer::check_channel(inst, channel)?;
+    config.channels.insert(tool_id.to_string(), channel.to_string());
+    Ok(())
+}
+
+fn cmd_config_set(config: &mut HudoConfig, key: &str, value: &str) -> Result<()> {
+    match key {
+        "root_dir" => config.root_dir = value.to_string(),
+        "check_updates" => match value {
+            "true" => config.check_updates = true,
+            "false" => config.check_updates = false,
+            _ => anyhow::bail!("check_updates 只能是 true 或 false"),
+        },
+        "db_autoservice" => match value {
+            "true" => config.db_autoservice = true,
+            "false" => config.db_autoservice = false,
+            _ => anyhow::bail!("db_autoservice 只能是 true 或 false"),
+        },
+        "desktop_shortcuts" => match value {
+            "true" => config.desktop_shortcuts = Some(true),
+            "false" => config.desktop_shortcuts = Some(false),
+            "ask" => config.desktop_shortcuts = None,
+            _ => anyhow::bail!("desktop_shortcuts 只能是 true、false 或 ask"),
+        },
+        "git.install_mode" => match value {
+            "installer" | "portable" => config.git.install_mode = value.to_string(),
+            _ => anyhow::bail!("git.install_mode 只能是 installer 或 portable"),
+        },
+        "java.version" => {
+            config.java.version = match value.trim() {
+                "" | "latest" => config::JavaConfig::default().version,
+                v => v.to_string(),
+            }
+        }
+        "go.version" => config.go.version = value.to_string(),
+        "mysql.port" => config.mysql.port = parse_port(key, value)?,
+        "pgsql.port" => config.pgsql.port = parse_port(key, value)?,
+        "github_token" => {
+            config.github_token = Some(value.trim().to_string()).filter(|t| !t.is_empty())
+        }
+        "max_download_bps" => config.max_download_bps = parse_rate(value)?,
+        "lang" => {
+            config.lang = match value.trim() {
+                "" | "auto" => None,
+                v @ ("zh" | "en") => Some(v.to_string()),
+                _ => anyhow::bail!("lang 只能是 zh、en 或 auto（按系统语言）"),
+            }
+        }
+        "install.idle_minutes" => {
+            config.install.idle_minutes = value
+                .trim()
+                .parse()
+                .context("install.idle_minutes 必须是分钟数，0 表示一直等待")?
+        }
+        "install.silent_idle_minutes" => {
+            config.install.silent_idle_minutes = match value.trim() {
+                "" => None,
+                v => Some(v.parse().context("install.silent_idle_minutes 必须是分钟数，0 表示一直等待，留空恢复默认")?),
+            }
+        }
+        _ => {
+            let known = match key.split_once('.') {
+                Some(("versions", k)) => config.set_version_key(k, value),
+                Some(("mirrors", k)) => config.mirrors.set(k, value),
+                Some(("channels", k)) => {
+                    set_channel(config, k, value)?;
+                    true
+                }
+                _ => false,
+            };
+            if !known {
+                anyhow::bail!(
+                    "未知配置项: {}。可用: root_dir, check_updates, db_autoservice, desktop_shortcuts, git.install_mode, java.version, go.version, mysql.port, pgsql.port, github_token, max_download_bps, lang, install.idle_minutes, install.silent_idle_minutes, versions.<{}>, mirrors.<{}>, channels.<工具>",
+                    key,
+                    config::VERSION_KEYS.join("|"),
+                    config::MirrorConfig::keys().collect::<Vec<_>>().join("|")
                 );
             }
-        });
+        }
     }
+    config.save()?;
+    let shown = if key == "github_token" {
+        ai::mask_key(value)
+    } else {
+        value.to_string()
+    };
+    ui::print_success(&format!("已设置 {} = {}", key, shown));
+    Ok(())
+}
+
+fn cmd_config_edit() -> Result<()> {
+    let path = HudoConfig::config_path()?;
+    let default_editor = if cfg!(windows) { "notepad" } else { "vi" };
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| default_editor.to_string());
+    std::process::Command::new(&editor)
+        .arg(path.to_str().unwrap())
+        .status()
+        .with_context(|| format!("无法启动编辑器: {}", editor))?;
+    Ok(())
+}
+
+/// 显示 hudo 相关文件路径及是否存在
+fn cmd_config_path() -> Result<()> {
+    ui::print_title("文件路径");
+
+    let config_path = HudoConfig::config_path()?;
+    // 不触发首次运行引导：配置不存在时无法得知 state.json 位置
+    let state_path = HudoConfig::load()?.map(|c| c.state_path());
+    let cc_path = ai::AiProviders::path()?;
 
-    tools
+    let print_path = |key: &str, path: &std::path::Path| {
+        let status = if path.exists() {
+            console::style("存在").green()
+        } else {
+            console::style("不存在").dim()
+        };
+        println!("  {}  {}  {}", ui::pad(key, 14), path.display(), status);
+    };
+
+    print_path("config.toml", &config_path);
+    match &state_path {
+        Some(p) => print_path("state.json", p),
+        None => println!(
+            "  {}  {}",
+            ui::pad("state.json", 14),
+            console::style("(未配置 root_dir)").dim()
+        ),
+    }
+    print_path("cc-providers", &cc_path);
+    if env::session_only() {
+        print_path("hudo-env.ps1", &paths::session_script_path("ps1")?);
+        print_path("hudo-env.cmd", &paths::session_script_path("cmd")?);
+    }
+    if paths::is_portable() {
+        println!();
+        ui::print_info("便携模式：配置文件位于 hudo 程序旁的 .hudo 目录");
+    }
+    Ok(())
+}
+
+/// 导出 config.toml（仅配置，不含已安装工具状态）
+fn cmd_config_export(config: &HudoConfig, file: &str) -> Result<()> {
+    let path = std::path::Path::new(file);
+    config.save_to(path)?;
+    ui::print_success(&format!("配置已导出到 {}", path.display()));
+    Ok(())
+}
+
+/// 导入 config.toml：先校验能解析为 HudoConfig，再覆盖当前配置
+fn cmd_config_import(file: &str) -> Result<()> {
+    let path = std::path::Path::new(file);
+    if !path.exists() {
+        anyhow::bail!("文件不存在: {}", file);
+    }
+    let imported = HudoConfig::load_from(path)?;
+    imported.save()?;
+    imported.ensure_dirs()?;
+    ui::print_success(&format!("配置已从 {} 导入", path.display()));
+    ui::print_info(&format!("安装根目录: {}", imported.root_dir));
+    Ok(())
+}
+
+/// 修改系统已有工具的接管策略（ask 删除记录，下次重新询问）
+fn cmd_config_external_policy(
+    config: &HudoConfig,
+    tool_id: &str,
+    policy: registry::ExternalPolicy,
+) -> Result<()> {
+    let installers = all_installers();
+    installer::find(&installers, tool_id, "设置")?;
+    let mut reg = registry::InstallRegistry::load(&config.state_path())?;
+    reg.set_external_policy(tool_id, policy);
+    reg.save(&config.state_path())?;
+    let desc = match policy {
+        registry::ExternalPolicy::Ask => "每次询问",
+        registry::ExternalPolicy::Ignore => "保留系统版本",
+        registry::ExternalPolicy::Takeover => "由 hudo 接管",
+    };
+    ui::print_success(&format!("{} 的系统已有版本: {}", tool_id, desc));
+    Ok(())
+}
+
+/// hudo config defender-exclude：选择目录加入 Defender 排除项，并记录到 state.json
+fn cmd_defender_exclude(config: &HudoConfig) -> Result<()> {
+    if let Some(reason) = defender::status().explain() {
+        ui::print_warning(reason);
+        return Ok(());
+    }
+    if !defender::elevation_available() {
+        ui::print_warning("添加 Defender 排除项需要管理员权限，当前用户不在 Administrators 组中");
+        return Ok(());
+    }
+
+    ui::print_title("Windows Defender 排除项");
+    ui::print_warning(defender::SECURITY_NOTE);
+    let candidates = defender::candidates(config);
+    let labels: Vec<String> = candidates
         .iter()
-        .zip(results.into_iter())
-        .map(|(inst, r)| (inst.info(), r.unwrap_or(Ok(DetectResult::NotInstalled))))
-        .collect()
+        .map(|(label, path)| format!("{}  {}", ui::pad(label, 30), console::style(path.display()).dim()))
+        .collect();
+    println!("  {}", console::style(t("setup.select_hint")).dim());
+    let selections = MultiSelect::with_theme(&ui::theme())
+        .items(&labels)
+        .interact_opt()
+        .context("选择被取消")?;
+    let selections = match selections {
+        Some(s) if !s.is_empty() => s,
+        _ => {
+            ui::print_info("已取消，未做任何更改");
+            return Ok(());
+        }
+    };
+
+    // 选了整个安装目录时其余子目录已包含在内
+    let paths: Vec<String> = match selections.contains(&0) {
+        true => vec![candidates[0].1.to_string_lossy().to_string()],
+        false => selections.iter().map(|&i| candidates[i].1.to_string_lossy().to_string()).collect(),
+    };
+    ui::print_action("添加排除项（将弹出 UAC 提示）...");
+    defender::apply(true, &paths)?;
+
+    let mut reg = registry::InstallRegistry::load(&config.state_path())?;
+    reg.add_defender_exclusions(&paths);
+    reg.save(&config.state_path())?;
+    for path in &paths {
+        ui::print_success(&format!("已排除 {}", path));
+    }
+    ui::print_info("移除请运行 hudo config defender-exclude --remove（卸载 hudo 自身时也会移除）");
+    Ok(())
 }
 
-/// 列出所有工具状态
-async fn cmd_list(config: &HudoConfig, show_all: bool) -> Result<()> {
-    ui::print_title(if show_all { "所有可用工具" } else { "已安装工具" });
+/// hudo config defender-exclude --remove：移除 state.json 中记录的排除项
+fn cmd_defender_remove(config: &HudoConfig) -> Result<()> {
+    let mut reg = registry::InstallRegistry::load(&config.state_path())?;
+    if reg.defender_exclusions.is_empty() {
+        ui::print_info("没有由 hudo 添加的 Defender 排除项");
+        return Ok(());
+    }
+    ui::print_action("移除排除项（将弹出 UAC 提示）...");
+    defender::apply(false, &reg.defender_exclusions)?;
+    for path in &reg.defender_exclusions {
+        ui::print_success(&format!("已移除排除 {}", path));
+    }
+    reg.defender_exclusions.clear();
+    reg.save(&config.state_path())?;
+    Ok(())
+}
 
+/// hudo pin / unpin：设置工具的固定标记
+fn cmd_pin(config: &HudoConfig, tool_id: &str, pinned: bool) -> Result<()> {
     let installers = all_installers();
-    let reg = registry::InstallRegistry::load(&config.state_path())?;
-
-    // 按分类分组
-    let categories = [
-        ui::ToolCategory::Tool,
-        ui::ToolCategory::Language,
-        ui::ToolCategory::Database,
-        ui::ToolCategory::Ide,
-    ];
+    let inst = installer::find(&installers, tool_id, if pinned { "固定" } else { "取消固定" })?;
+    let name = inst.info().name;
+    let mut reg = registry::InstallRegistry::load(&config.state_path())?;
+    if reg.is_pinned(tool_id) == pinned && reg.get(tool_id).is_some() {
+        ui::print_info(&format!("{} {}", name, if pinned { "已经是固定状态" } else { "未固定" }));
+        return Ok(());
+    }
+    if !reg.set_pinned(tool_id, pinned) {
+        anyhow::bail!("{} 未由 hudo 安装，无法固定", name);
+    }
+    reg.save(&config.state_path())?;
+    let version = reg.get(tool_id).map(|s| s.version.as_str()).unwrap_or_default();
+    if pinned {
+        ui::print_success(&format!("已固定 {} {}，hudo import 将保留该版本", name, version));
+    } else {
+        ui::print_success(&format!("已取消固定 {}", name));
+    }
+    Ok(())
+}
 
-    // 收集所有工具的检测结果（并行）
-    let tool_refs: Vec<&dyn installer::Installer> =
-        installers.iter().map(|i| i.as_ref()).collect();
-    let all_results = detect_all_parallel(&tool_refs, config, &reg);
+/// hudo info：不探测 PATH，安装状态以 state.json 为准
+fn cmd_info(config: &HudoConfig, tool_id: &str) -> Result<()> {
+    let installers = all_installers();
+    let inst = installer::find(&installers, tool_id, "查看")?;
+    let info = inst.info();
+    ui::print_title(&format!("{} ({})", info.name, info.id));
+    ui::print_info(info.description);
+    if let Some(homepage) = info.homepage {
+        println!("  官网: {}", console::style(homepage).cyan().underlined());
+    }
 
-    // 计算已安装工具的动态列宽（仅基于要显示的工具）
-    let mut name_width = 0usize;
-    let mut desc_width = 0usize;
-    for (info, detect) in &all_results {
-        let is_installed = matches!(detect, Ok(DetectResult::InstalledByHudo(_)) | Ok(DetectResult::InstalledExternal(_)));
-        if show_all || is_installed {
-            name_width = name_width.max(console::measure_text_width(info.name));
-            desc_width = desc_width.max(console::measure_text_width(info.description));
+    let reg = registry::InstallRegistry::load(&config.state_path())?;
+    match reg.get(info.id) {
+        Some(state) => {
+            let pinned = if state.pinned {
+                format!("  {}", console::style("(已固定)").cyan())
+            } else {
+                String::new()
+            };
+            ui::print_success(&format!("已安装 {}{}", console::style(&state.version).green(), pinned));
+            ui::print_info(&format!("位置: {}", state.install_path));
         }
+        None => ui::print_info(&format!("未由 hudo 安装，运行 hudo install {} 安装", info.id)),
     }
-    name_width += 2;
-    desc_width += 2;
 
-    let mut hudo_count = 0u32;
-    let mut external_count = 0u32;
-    let mut any_displayed = false;
+    ui::print_next_steps(info.post_install_tips);
+    Ok(())
+}
 
-    for cat in &categories {
-        // 筛选该分类下要显示的工具
-        let cat_entries: Vec<_> = all_results
-            .iter()
-            .filter(|(info, detect)| {
-                let in_cat = std::mem::discriminant(&ui::ToolCategory::from_id(info.id))
-                    == std::mem::discriminant(cat);
-                if !in_cat {
-                    return false;
-                }
-                if show_all {
-                    return true;
+/// 撤销 env_actions：删除设置过的环境变量，从 PATH 移除追加的条目（不通知其他程序）
+fn remove_env_actions(actions: &[EnvAction]) -> Result<()> {
+    for action in actions {
+        match action {
+            EnvAction::Set { name, .. } => {
+                if env::EnvManager::get_var(name)?.is_some() {
+                    env::EnvManager::delete_var(name)?;
+                    ui::print_info(&format!("移除环境变量: {}", name));
                 }
-                matches!(detect, Ok(DetectResult::InstalledByHudo(_)) | Ok(DetectResult::InstalledExternal(_)))
-            })
-            .collect();
-
-        if cat_entries.is_empty() {
-            continue;
+            }
+            EnvAction::AppendPath { path } => {
+                env::EnvManager::remove_from_path(path)?;
+                ui::print_info(&format!("PATH -= {}", path));
+            }
         }
+    }
+    Ok(())
+}
 
-        ui::print_section(cat.label());
-        any_displayed = true;
-
-        for (info, detect) in &cat_entries {
-            let status = match detect {
-                Ok(DetectResult::InstalledByHudo(ver)) => {
-                    hudo_count += 1;
-                    let extra = reg
-                        .get(info.id)
-                        .map(|s| {
-                            format!("  {}", console::style(format!("({})", s.installed_at)).dim())
-                        })
-                        .unwrap_or_default();
-                    format!("{}{}", console::style(ver).green(), extra)
-                }
-                Ok(DetectResult::InstalledExternal(ver)) => {
-                    external_count += 1;
-                    format!(
-                        "{} {}",
-                        console::style(ver).green(),
-                        console::style("(非 hudo)").yellow()
-                    )
-                }
-                Ok(DetectResult::NotInstalled) => {
-                    console::style("·").dim().to_string()
-                }
-                Err(_) => console::style("检测失败").red().to_string(),
-            };
-            println!(
-                "    {}  {}  {}",
-                console::style(ui::pad(info.name, name_width)).bold(),
-                ui::pad(info.description, desc_width),
-                status,
-            );
+/// 设置环境变量与 PATH 并通知其他程序
+fn apply_env_actions(actions: &[EnvAction]) -> Result<()> {
+    for action in actions {
+        match action {
+            EnvAction::Set { name, value } => env::EnvManager::set_var(name, value)?,
+            EnvAction::AppendPath { path } => env::EnvManager::append_to_path(path)?,
         }
     }
+    env::EnvManager::broadcast_change();
+    Ok(())
+}
 
-    if !any_displayed {
-        ui::print_info("尚未安装任何工具，运行 hudo setup 开始安装");
+/// 列出环境变量更改：变量的旧值 → 新值，PATH 项加入后的位置，以及 PATH 展开后的总长度
+fn print_env_preview(preview: &env::EnvPreview) {
+    let width = preview
+        .changes
+        .iter()
+        .map(|c| match c {
+            env::EnvChange::Set { name, .. } => console::measure_text_width(name),
+            env::EnvChange::AppendPath { .. } => 4,
+        })
+        .max()
+        .unwrap_or(0);
+    for change in &preview.changes {
+        match change {
+            env::EnvChange::Set { name, old, new } => {
+                let old = match old {
+                    Some(old) if old == new => {
+                        ui::print_info(&format!("{}  {}（不变）", ui::pad(name, width), new));
+                        continue;
+                    }
+                    Some(old) => old.clone(),
+                    None => "新增".to_string(),
+                };
+                println!(
+                    "  {}  {} {} {}",
+                    ui::pad(name, width),
+                    console::style(old).dim(),
+                    ui::Glyph::Arrow,
+                    console::style(new).green()
+                );
+            }
+            env::EnvChange::AppendPath { path, position, total, exists: true } => {
+                ui::print_info(&format!(
+                    "{}  {}（已存在，第 {}/{} 项）",
+                    ui::pad("PATH", width),
+                    path,
+                    position,
+                    total
+                ));
+            }
+            env::EnvChange::AppendPath { path, position, total, exists: false } => {
+                println!(
+                    "  {}  {} {}",
+                    ui::pad("PATH", width),
+                    console::style(format!("+ {}", path)).green(),
+                    console::style(format!("（第 {}/{} 项）", position, total)).dim()
+                );
+            }
+        }
+    }
+    if let Some(len) = preview.path_len {
+        if preview.path_near_limit() {
+            ui::print_warning(&format!(
+                "系统 + 用户 PATH 展开后共 {} 个字符，接近 {} 的上限，超出部分会被部分程序忽略，建议清理不用的 PATH 项",
+                len,
+                env::PATH_EXPAND_LIMIT
+            ));
+        } else {
+            ui::print_info(&format!("PATH 展开后长度: {}/{}", len, env::PATH_EXPAND_LIMIT));
+        }
     }
+}
 
-    println!();
-    let total = hudo_count + external_count;
-    if total > 0 {
-        ui::print_info(&format!(
-            "共 {} 个工具已安装 (hudo: {}, 系统: {})",
-            total, hudo_count, external_count
-        ));
+/// 询问是否应用环境变量更改，选择跳过时返回 false
+fn confirm_env_changes() -> Result<bool> {
+    let items = ["应用", "跳过（稍后运行 hudo env apply）"];
+    let sel = Select::with_theme(&ui::theme())
+        .with_prompt("应用以上环境变量更改？")
+        .items(&items)
+        .default(0)
+        .interact_opt()
+        .context("选择被取消")?;
+    Ok(sel == Some(0))
+}
+
+/// hudo env apply：按安装记录重新计算环境变量并应用，清除待应用标记
+fn cmd_env_apply(config: &HudoConfig, tool: Option<&str>) -> Result<()> {
+    let installers = all_installers();
+    let mut reg = registry::InstallRegistry::load(&config.state_path())?;
+    let pending: Vec<String> = reg
+        .env_pending()
+        .into_iter()
+        .filter(|id| tool.is_none_or(|t| t == id))
+        .collect();
+    if pending.is_empty() {
+        ui::print_info("没有待应用的环境变量更改");
+        return Ok(());
     }
-    if !show_all && total > 0 {
-        ui::print_info("使用 hudo list --all 查看所有可用工具");
+
+    for id in &pending {
+        let (Some(inst), Some(state)) = (installers.iter().find(|i| i.info().id == id), reg.get(id)) else {
+            continue;
+        };
+        ui::print_title(&format!("应用 {} 环境变量", inst.info().name));
+        let actions = inst.env_actions(&std::path::PathBuf::from(&state.install_path), config);
+        print_env_preview(&env::EnvPreview::build(&actions)?);
+        apply_env_actions(&actions)?;
+        reg.set_env_pending(id, false);
+        reg.save(&config.state_path())?;
+        ui::print_success("已应用");
     }
-    ui::print_info(&format!("安装根目录: {}", config.root_dir));
     Ok(())
 }
 
-fn cmd_config_show(config: &HudoConfig) -> Result<()> {
-    ui::print_title("当前配置");
-
-    println!("  {}  {}", ui::pad("root_dir", 20), config.root_dir);
-    println!("  {}  {}", ui::pad("java.version", 20), config.java.version);
-    println!("  {}  {}", ui::pad("go.version", 20), config.go.version);
+/// hudo env restore：恢复最近一次接管清理中移除的环境变量与 PATH 项
+fn cmd_env_restore(config: &HudoConfig, tool: Option<&str>) -> Result<()> {
+    let mut reg = registry::InstallRegistry::load(&config.state_path())?;
+    let Some(removal) = reg.take_env_removal(tool) else {
+        ui::print_info("没有可恢复的接管清理记录");
+        return Ok(());
+    };
 
-    let versions = [
-        ("versions.git", &config.versions.git),
-        ("versions.fnm", &config.versions.fnm),
-        ("versions.mysql", &config.versions.mysql),
-        ("versions.pgsql", &config.versions.pgsql),
-        ("versions.pycharm", &config.versions.pycharm),
-    ];
-    let has_versions = versions.iter().any(|(_, v)| v.is_some());
-    if has_versions {
-        println!();
-        for (key, val) in &versions {
-            if let Some(v) = val {
-                println!("  {}  {}", ui::pad(key, 20), v);
-            }
-        }
+    ui::print_title(&format!("恢复 {} 接管时移除的环境变量（{}）", removal.tool, removal.removed_at));
+    for (name, value) in &removal.vars {
+        env::EnvManager::set_var(name, value)?;
+        println!("    {} {} = {}", console::style("恢复变量").green(), name, value);
     }
-
-    let mirrors = [
-        ("mirrors.uv", &config.mirrors.uv),
-        ("mirrors.fnm", &config.mirrors.fnm),
-        ("mirrors.go", &config.mirrors.go),
-        ("mirrors.java", &config.mirrors.java),
-        ("mirrors.vscode", &config.mirrors.vscode),
-        ("mirrors.pycharm", &config.mirrors.pycharm),
-    ];
-    let has_mirrors = mirrors.iter().any(|(_, v)| v.is_some());
-    if has_mirrors {
-        println!();
-        for (key, val) in &mirrors {
-            if let Some(v) = val {
-                println!("  {}  {}", ui::pad(key, 20), v);
+    for entry in &removal.path {
+        env::EnvManager::append_to_path(entry)?;
+        println!("    {} {}", console::style("恢复 PATH").green(), entry);
+    }
+    env::EnvManager::broadcast_change();
+    if !removal.system_path.is_empty() {
+        ui::print_info("需要管理员权限修改系统 PATH，请在弹出的 UAC 窗口中点击\"是\"...");
+        if takeover::edit_system_path_elevated(&removal.system_path, true) {
+            for entry in &removal.system_path {
+                println!("    {} {}", console::style("恢复系统 PATH").green(), entry);
             }
+        } else {
+            // 记录放回去，下次还能重试
+            reg.env_history.push(removal.clone());
+            reg.save(&config.state_path())?;
+            anyhow::bail!("未能恢复系统 PATH（可能拒绝了 UAC 提示）: {}", removal.system_path.join("; "));
         }
     }
+    reg.save(&config.state_path())?;
+    ui::print_success("已恢复（PATH 项追加在末尾，顺序可能与原来不同）");
+    ui::print_info("请打开新终端以使环境变量生效");
     Ok(())
 }
 
-fn cmd_config_set(config: &mut HudoConfig, key: &str, value: &str) -> Result<()> {
-    match key {
-        "root_dir" => config.root_dir = value.to_string(),
-        "java.version" => config.java.version = value.to_string(),
-        "go.version" => config.go.version = value.to_string(),
-        "versions.git" => config.versions.git = Some(value.to_string()),
-        "versions.fnm" => config.versions.fnm = Some(value.to_string()),
-        "versions.mysql" => config.versions.mysql = Some(value.to_string()),
-        "versions.pgsql" => config.versions.pgsql = Some(value.to_string()),
-        "versions.pycharm" => config.versions.pycharm = Some(value.to_string()),
-        "mirrors.uv" => config.mirrors.uv = Some(value.to_string()),
-        "mirrors.fnm" => config.mirrors.fnm = Some(value.to_string()),
-        "mirrors.go" => config.mirrors.go = Some(value.to_string()),
-        "mirrors.java" => config.mirrors.java = Some(value.to_string()),
-        "mirrors.vscode" => config.mirrors.vscode = Some(value.to_string()),
-        "mirrors.pycharm" => config.mirrors.pycharm = Some(value.to_string()),
-        _ => anyhow::bail!("未知配置项: {}。可用: root_dir, java.version, go.version, versions.*, mirrors.*", key),
+/// hudo env export：输出到标准输出，便于重定向到 .env 或在 CI 中执行
+fn cmd_env_export(config: &HudoConfig, tool: Option<&str>, format: env::ExportFormat) -> Result<()> {
+    let installers = all_installers();
+    let reg = registry::InstallRegistry::load(&config.state_path())?;
+    if let Some(tool_id) = tool {
+        let inst = installer::find(&installers, tool_id, "导出")?;
+        if reg.get(tool_id).is_none() {
+            anyhow::bail!("{} 未由 hudo 安装", inst.info().name);
+        }
     }
-    config.save()?;
-    ui::print_success(&format!("已设置 {} = {}", key, value));
-    Ok(())
-}
-
-fn cmd_config_edit() -> Result<()> {
-    let path = HudoConfig::config_path()?;
-    let default_editor = if cfg!(windows) { "notepad" } else { "vi" };
-    let editor = std::env::var("EDITOR").unwrap_or_else(|_| default_editor.to_string());
-    std::process::Command::new(&editor)
-        .arg(path.to_str().unwrap())
-        .status()
-        .with_context(|| format!("无法启动编辑器: {}", editor))?;
+    let export = env::EnvExport::collect(&reg, &installers, config, tool);
+    print!("{}", export.render(format));
     Ok(())
 }
 
@@ -1565,42 +4002,57 @@ fn truncate_version(ver: &str, max_len: usize) -> String {
         .split_whitespace()
         .find(|s| s.starts_with(|c: char| c.is_ascii_digit()))
         .unwrap_or(trimmed);
-    if version_part.len() <= max_len {
+    // 按字符截断，版本字符串中含中文时不会切在字符中间
+    if version_part.chars().count() <= max_len {
         version_part.to_string()
     } else {
-        format!("{}…", &version_part[..max_len - 1])
+        format!("{}…", version_part.chars().take(max_len - 1).collect::<String>())
     }
 }
 
 /// 交互式主菜单
 async fn interactive_menu(config: &HudoConfig) -> Result<()> {
+    let update = if config.check_updates {
+        version::hudo_update_available().await
+    } else {
+        None
+    };
+
     loop {
         ui::page_header("主菜单");
+        if let Some(latest) = &update {
+            println!(
+                "  {} {}",
+                console::style(tf("menu.update_available", &[&latest])).yellow(),
+                console::style(t("menu.update_hint")).dim()
+            );
+            println!();
+        }
 
         let menu_items = &[
-            "📦  安装工具",
-            "📋  查看已安装",
-            "🗑   卸载工具",
-            "📁  环境档案",
-            "⚙   配置",
-            "🔑  Claude Code API 来源",
-            "🚪  退出",
-        ];
-
-        let selection = Select::with_theme(&ColorfulTheme::default())
-            .with_prompt("请选择操作 (Esc 退出)")
+            t("menu.install"),
+            t("menu.list"),
+            t("menu.uninstall"),
+            t("menu.profile"),
+            t("menu.config"),
+            t("menu.ai"),
+            t("menu.exit"),
+        ].map(ui::menu_item);
+
+        let selection = Select::with_theme(&ui::theme())
+            .with_prompt(t("menu.prompt"))
             .items(menu_items)
             .default(0)
             .interact_opt()
             .context("选择被取消")?;
 
         match selection {
-            Some(0) => { cmd_setup(config).await?; }
-            Some(1) => { cmd_list(config, false).await?; ui::wait_for_key(); }
+            Some(0) => { cmd_setup(config, false).await?; }
+            Some(1) => { cmd_list(config, &list::ListOptions::default()).await?; ui::wait_for_key(); }
             Some(2) => { interactive_uninstall(config).await?; }
             Some(3) => { interactive_profile(config).await?; }
             Some(4) => { interactive_config(config).await?; }
-            Some(5) => { cc::cmd_cc()?; }
+            Some(5) => { ai::cmd_ai(None).await?; }
             Some(6) | None => break,
             _ => unreachable!(),
         }
@@ -1611,13 +4063,13 @@ async fn interactive_menu(config: &HudoConfig) -> Result<()> {
 
 /// 交互式卸载：列出已安装工具供用户选择
 async fn interactive_uninstall(config: &HudoConfig) -> Result<()> {
-    ui::page_header("卸载工具");
+    ui::page_header(t("uninstall.page"));
 
     let installers = all_installers();
     let reg = registry::InstallRegistry::load(&config.state_path())?;
 
     let refs: Vec<&dyn installer::Installer> = installers.iter().map(|b| b.as_ref()).collect();
-    let results = detect_all_parallel(&refs, config, &reg);
+    let results = detect_all_parallel(&refs, config, Some(&reg));
 
     let mut installed = Vec::new();
     for (info, result) in &results {
@@ -1627,7 +4079,7 @@ async fn interactive_uninstall(config: &HudoConfig) -> Result<()> {
     }
 
     if installed.is_empty() {
-        ui::print_info("当前没有由 hudo 安装的工具");
+        ui::print_info(t("uninstall.none"));
         ui::wait_for_key();
         return Ok(());
     }
@@ -1643,8 +4095,8 @@ async fn interactive_uninstall(config: &HudoConfig) -> Result<()> {
         })
         .collect();
 
-    let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("选择要卸载的工具 (Esc 返回)")
+    let selection = Select::with_theme(&ui::theme())
+        .with_prompt(t("uninstall.prompt"))
         .items(&labels)
         .interact_opt()
         .context("选择被取消")?;
@@ -1652,7 +4104,7 @@ async fn interactive_uninstall(config: &HudoConfig) -> Result<()> {
     match selection {
         Some(idx) => {
             let (tool_id, _, _) = &installed[idx];
-            cmd_uninstall(config, tool_id).await?;
+            cmd_uninstall(config, tool_id, false, false).await?;
             ui::wait_for_key();
         }
         None => {}
@@ -1670,9 +4122,9 @@ async fn interactive_profile(config: &HudoConfig) -> Result<()> {
             "📤  导出环境档案",
             "📥  导入环境档案",
             "↩   返回",
-        ];
+        ].map(ui::menu_item);
 
-        let selection = Select::with_theme(&ColorfulTheme::default())
+        let selection = Select::with_theme(&ui::theme())
             .with_prompt("环境档案 (Esc 返回)")
             .items(menu_items)
             .default(0)
@@ -1681,12 +4133,12 @@ async fn interactive_profile(config: &HudoConfig) -> Result<()> {
 
         match selection {
             Some(0) => {
-                cmd_export(config, None).await?;
+                cmd_export(config, None, false, false, false, false).await?;
                 ui::wait_for_key();
             }
             Some(1) => {
                 let mut config = config.clone();
-                cmd_import(&mut config, "hudo-profile.toml").await?;
+                cmd_import(&mut config, "hudo-profile.toml", false, VersionMismatch::Ask).await?;
                 ui::wait_for_key();
             }
             Some(2) | None => break,
@@ -1698,6 +4150,74 @@ async fn interactive_profile(config: &HudoConfig) -> Result<()> {
 }
 
 /// 交互式配置子菜单
+/// 交互菜单「固定版本」：列出可固定版本的配置键、当前值和最新版本，输入新值或取消固定
+async fn interactive_pin_versions(config: &HudoConfig) -> Result<()> {
+    let mut config = config.clone();
+    let keys = config::pin_keys();
+    ui::print_action("查询最新版本...");
+    let latest: Vec<Option<String>> =
+        futures_util::future::join_all(keys.iter().map(|(_, k)| version::latest_for_key(k)))
+            .await
+            .into_iter()
+            .map(Result::ok)
+            .collect();
+
+    loop {
+        ui::page_header("固定版本");
+        let items: Vec<String> = keys
+            .iter()
+            .zip(&latest)
+            .map(|((key, _), latest)| {
+                let current = match config.pinned(key) {
+                    Some(v) => v.to_string(),
+                    None => console::style("未固定").dim().to_string(),
+                };
+                let latest = latest
+                    .as_deref()
+                    .map(|v| console::style(format!("最新 {}", v)).dim().to_string())
+                    .unwrap_or_default();
+                format!("{}  {}  {}", ui::pad(key, 20), ui::pad(&current, 14), latest)
+            })
+            .collect();
+
+        let Some(idx) = Select::with_theme(&ui::theme())
+            .with_prompt("选择要固定版本的工具 (Esc 返回)")
+            .items(&items)
+            .default(0)
+            .interact_opt()
+            .context("选择被取消")?
+        else {
+            return Ok(());
+        };
+        let (key, _) = &keys[idx];
+
+        let actions = ["输入版本号", "取消固定（使用最新版或默认版本）", "返回"];
+        let action = Select::with_theme(&ui::theme())
+            .with_prompt(key.as_str())
+            .items(&actions)
+            .default(0)
+            .interact_opt()
+            .context("选择被取消")?;
+        let value = match action {
+            Some(0) => {
+                let theme = ui::theme();
+                let mut input = Input::<String>::with_theme(&theme).with_prompt(format!("输入 {} 的版本号", key));
+                if let Some(v) = config.pinned(key).or(latest[idx].as_deref()) {
+                    input = input.default(v.to_string());
+                }
+                input.interact_text().context("输入被取消")?.trim().to_string()
+            }
+            Some(1) => "latest".to_string(),
+            _ => continue,
+        };
+        if value.is_empty() {
+            continue;
+        }
+        cmd_config_set(&mut config, key, &value)?;
+        ui::wait_for_key();
+    }
+}
+
 async fn interactive_config(config: &HudoConfig) -> Result<()> {
     loop {
         ui::page_header("配置管理");
@@ -1705,12 +4225,13 @@ async fn interactive_config(config: &HudoConfig) -> Result<()> {
         let menu_items = &[
             "📄  查看配置",
             "🌐  设置镜像",
+            "📌  固定版本",
             "📝  编辑配置文件",
             "🔄  重置配置",
             "↩   返回",
-        ];
+        ].map(ui::menu_item);
 
-        let selection = Select::with_theme(&ColorfulTheme::default())
+        let selection = Select::with_theme(&ui::theme())
             .with_prompt("配置管理 (Esc 返回)")
             .items(menu_items)
             .default(0)
@@ -1723,35 +4244,31 @@ async fn interactive_config(config: &HudoConfig) -> Result<()> {
                 ui::wait_for_key();
             }
             Some(1) => {
-                let mirror_keys = &[
-                    "mirrors.uv",
-                    "mirrors.fnm",
-                    "mirrors.go",
-                    "mirrors.java",
-                    "mirrors.vscode",
-                    "mirrors.pycharm",
-                ];
-
-                let key_sel = Select::with_theme(&ColorfulTheme::default())
+                let mirror_keys: Vec<String> = config::MirrorConfig::keys()
+                    .map(|k| format!("mirrors.{}", k))
+                    .collect();
+
+                let key_sel = Select::with_theme(&ui::theme())
                     .with_prompt("选择要设置的镜像")
-                    .items(mirror_keys)
+                    .items(&mirror_keys)
                     .interact_opt()
                     .context("选择被取消")?;
 
                 if let Some(idx) = key_sel {
-                    let value: String = Input::with_theme(&ColorfulTheme::default())
+                    let value: String = Input::with_theme(&ui::theme())
                         .with_prompt(format!("输入 {} 的值", mirror_keys[idx]))
                         .interact_text()
                         .context("输入被取消")?;
 
                     let mut config = config.clone();
-                    cmd_config_set(&mut config, mirror_keys[idx], &value)?;
+                    cmd_config_set(&mut config, &mirror_keys[idx], &value)?;
                 }
                 ui::wait_for_key();
             }
-            Some(2) => cmd_config_edit()?,
-            Some(3) => { cmd_config_reset()?; ui::wait_for_key(); }
-            Some(4) | None => break,
+            Some(2) => interactive_pin_versions(config).await?,
+            Some(3) => cmd_config_edit()?,
+            Some(4) => { cmd_config_reset()?; ui::wait_for_key(); }
+            Some(5) | None => break,
             _ => unreachable!(),
         }
     }
@@ -1761,19 +4278,59 @@ async fn interactive_config(config: &HudoConfig) -> Result<()> {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    // 在输出任何内容（包括 --help）之前切换为 UTF-8，console 离开作用域时恢复
+    let console = ui::utf8_console();
+    // clap 出错或显示帮助后直接退出进程，不会执行 drop，先恢复代码页
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            drop(console);
+            std::process::exit(e.exit_code());
+        }
+    };
+    ui::set_ascii(cli.ascii || ui::detect_ascii());
+    let config_lang = HudoConfig::load().ok().flatten().and_then(|c| c.lang);
+    lang::set(lang::detect(cli.lang, config_lang.as_deref()));
+    env::set_session_only(cli.no_registry || paths::portable_no_registry());
+    let opts = GlobalOpts {
+        all_drives: cli.all_drives,
+        allow_synced_root: cli.allow_synced_root,
+        cache_dir: cli
+            .cache_dir
+            .or_else(|| std::env::var_os("HUDO_CACHE_DIR").filter(|v| !v.is_empty()).map(Into::into)),
+        arch: cli.arch,
+        limit_rate: cli.limit_rate,
+    };
 
     match cli.command {
         Some(cmd) => match cmd {
-            Commands::Setup => {
-                let config = ensure_config()?;
-                cmd_setup(&config).await?;
+            Commands::Setup { flat } => {
+                let config = ensure_config(&opts)?;
+                cmd_setup(&config, flat).await?;
             }
-            Commands::Install { tool } => {
-                let config = ensure_config()?;
-                cmd_install(&config, &tool.to_lowercase()).await?;
+            Commands::Install { tool, pick, ask_again, list_deps, yes, dry_run, channel } => {
+                let Some(tool) = tool else {
+                    ui::print_title("可安装的工具");
+                    println!("{}", installer::available_by_category());
+                    println!();
+                    ui::print_info("运行 hudo install <工具> 安装，hudo info <工具> 查看详情");
+                    return Ok(());
+                };
+                let mut config = ensure_config(&opts)?;
+                config.ask_external_again = ask_again;
+                config.yes = yes;
+                config.dry_run = dry_run;
+                if let Some(channel) = channel {
+                    set_channel(&mut config, &tool.to_lowercase(), &channel)?;
+                }
+                if list_deps {
+                    cmd_list_deps(&config, &tool.to_lowercase()).await?;
+                } else {
+                    cmd_install(&config, &tool.to_lowercase(), pick).await?;
+                }
             }
-            Commands::Uninstall { tool, uninstall_self } => {
+            Commands::Uninstall { tool, uninstall_self, all, yes, keep_config } => {
                 if uninstall_self {
                     #[cfg(windows)]
                     cmd_self_uninstall().await?;
@@ -1781,43 +4338,116 @@ async fn main() -> Result<()> {
                     {
                         ui::print_error("Linux/macOS 暂不支持自卸载，请手动删除 hudo 目录");
                     }
+                } else if all {
+                    let config = ensure_config(&opts)?;
+                    cmd_uninstall_all(&config, yes, keep_config).await?;
                 } else if let Some(t) = tool {
-                    let config = ensure_config()?;
-                    cmd_uninstall(&config, &t.to_lowercase()).await?;
+                    let config = ensure_config(&opts)?;
+                    cmd_uninstall(&config, &t.to_lowercase(), yes, keep_config).await?;
                 } else {
-                    eprintln!("请指定工具名称，或使用 --self 卸载 hudo 自身");
+                    eprintln!("请指定工具名称，或使用 --all / --self");
                     eprintln!("示例: hudo uninstall git");
+                    eprintln!("      hudo uninstall --all");
                     eprintln!("      hudo uninstall --self");
+                    drop(console);
                     std::process::exit(1);
                 }
             }
-            Commands::Export { file } => {
-                let config = ensure_config()?;
-                cmd_export(&config, file).await?;
+            Commands::Export { file, target, include_secrets, lock, yes, no_timestamp } => {
+                let config = ensure_config(&opts)?;
+                match target {
+                    ExportTarget::Hudo => cmd_export(&config, file, lock, include_secrets, yes, no_timestamp).await?,
+                    ExportTarget::Wsl => cmd_export_wsl(&config, file).await?,
+                }
+            }
+            Commands::Import {
+                file,
+                frozen,
+                ask_again,
+                strict,
+                prefer_local,
+            } => {
+                let mut config = ensure_config(&opts)?;
+                config.ask_external_again = ask_again;
+                let mode = match (strict, prefer_local) {
+                    (true, _) => VersionMismatch::Strict,
+                    (_, true) => VersionMismatch::PreferLocal,
+                    _ => VersionMismatch::Ask,
+                };
+                cmd_import(&mut config, &file, frozen, mode).await?;
             }
-            Commands::Import { file } => {
-                let mut config = ensure_config()?;
-                cmd_import(&mut config, &file).await?;
+            Commands::Pin { tool } => {
+                let config = ensure_config(&opts)?;
+                cmd_pin(&config, &tool.to_lowercase(), true)?;
             }
-            Commands::List { all } => {
-                let config = ensure_config()?;
-                cmd_list(&config, all).await?;
+            Commands::Unpin { tool } => {
+                let config = ensure_config(&opts)?;
+                cmd_pin(&config, &tool.to_lowercase(), false)?;
+            }
+            Commands::List { all, verify, category, sort, json } => {
+                let config = ensure_config(&opts)?;
+                let category = category.as_deref().map(list::parse_category).transpose()?;
+                cmd_list(&config, &list::ListOptions { show_all: all, verify, category, sort, json }).await?;
+            }
+            Commands::Info { tool } => {
+                let config = ensure_config(&opts)?;
+                cmd_info(&config, &tool.to_lowercase())?;
+            }
+            Commands::Catalog { json } => {
+                catalog::cmd_catalog(json)?;
             }
             Commands::Config { action } => match action {
                 ConfigAction::Show => {
-                    let config = ensure_config()?;
+                    let config = ensure_config(&opts)?;
                     cmd_config_show(&config)?;
                 }
-                ConfigAction::Set { key, value } => {
-                    let mut config = ensure_config()?;
+                ConfigAction::Set { key, value, yes } => {
+                    // 修改 root_dir 时跳过磁盘检查，允许直接切换到已拔出磁盘之外的目录
+                    let mut config = match HudoConfig::load()? {
+                        Some(c) if key == "root_dir" => c,
+                        _ => ensure_config(&opts)?,
+                    };
+                    if key == "root_dir" {
+                        guard_root_dir(&value, opts.allow_synced_root)?;
+                        if !confirm_root_change(&config, &value, yes)? {
+                            ui::print_info("已取消，root_dir 未修改");
+                            return Ok(());
+                        }
+                    }
                     cmd_config_set(&mut config, &key, &value)?;
                 }
+                ConfigAction::MigrateRoot { dir, yes } => {
+                    let config = ensure_config(&opts)?;
+                    guard_root_dir(&dir, opts.allow_synced_root)?;
+                    cmd_config_migrate_root(&config, &dir, yes)?;
+                }
                 ConfigAction::Edit => {
                     cmd_config_edit()?;
                 }
+                ConfigAction::Path => {
+                    cmd_config_path()?;
+                }
+                ConfigAction::Export { file } => {
+                    let config = ensure_config(&opts)?;
+                    cmd_config_export(&config, &file)?;
+                }
+                ConfigAction::Import { file } => {
+                    cmd_config_import(&file)?;
+                }
                 ConfigAction::Reset => {
                     cmd_config_reset()?;
                 }
+                ConfigAction::ExternalPolicy { tool, policy } => {
+                    let config = ensure_config(&opts)?;
+                    cmd_config_external_policy(&config, &tool.to_lowercase(), policy)?;
+                }
+                ConfigAction::DefenderExclude { remove } => {
+                    let config = ensure_config(&opts)?;
+                    match remove {
+                        true => cmd_defender_remove(&config)?,
+                        false => cmd_defender_exclude(&config)?,
+                    }
+                }
             },
             Commands::Update => {
                 #[cfg(windows)]
@@ -1827,15 +4457,142 @@ async fn main() -> Result<()> {
                     ui::print_error("Linux/macOS 暂不支持自更新，请重新下载安装");
                 }
             }
-            Commands::Cc => {
-                cc::cmd_cc()?;
+            Commands::Cc { action } => match action {
+                None => ai::cmd_ai(Some(ai::AiTarget::Claude)).await?,
+                Some(CcAction::Test { name }) => {
+                    ai::cmd_test(ai::AiTarget::Claude, name.as_deref()).await?
+                }
+                Some(CcAction::Export { file }) => ai::cmd_export(&file)?,
+                Some(CcAction::Import { file, activate }) => {
+                    ai::cmd_import(&file, activate.as_deref())?
+                }
+            },
+            Commands::Ai { target } => {
+                ai::cmd_ai(target).await?;
+            }
+            Commands::Scan => {
+                #[cfg(windows)]
+                {
+                    let config = ensure_config(&opts)?;
+                    cmd_scan(&config)?;
+                }
+                #[cfg(not(windows))]
+                {
+                    ui::print_error("hudo scan 仅支持 Windows");
+                }
+            }
+            Commands::Doctor { fix } => {
+                #[cfg(windows)]
+                {
+                    let config = ensure_config(&opts)?;
+                    cmd_doctor(&config, fix)?;
+                }
+                #[cfg(not(windows))]
+                {
+                    let _ = fix;
+                    ui::print_error("hudo doctor 仅支持 Windows");
+                }
+            }
+            Commands::Status { json } => {
+                let config = ensure_config(&opts)?;
+                cmd_status(&config, json)?;
+            }
+            Commands::Env { action } => match action {
+                EnvVarAction::Export { tool, format } => {
+                    let config = ensure_config(&opts)?;
+                    cmd_env_export(&config, tool.map(|t| t.to_lowercase()).as_deref(), format)?;
+                }
+                EnvVarAction::Apply { tool } => {
+                    let config = ensure_config(&opts)?;
+                    cmd_env_apply(&config, tool.map(|t| t.to_lowercase()).as_deref())?;
+                }
+                EnvVarAction::Restore { tool } => {
+                    let config = ensure_config(&opts)?;
+                    cmd_env_restore(&config, tool.map(|t| t.to_lowercase()).as_deref())?;
+                }
+                EnvVarAction::SystemPath { restore, entries } => {
+                    takeover::edit_system_path(&entries, restore)?;
+                }
+            },
+            Commands::Which { command } => {
+                #[cfg(windows)]
+                {
+                    let config = ensure_config(&opts)?;
+                    cmd_which(&config, &command)?;
+                }
+                #[cfg(not(windows))]
+                {
+                    let _ = command;
+                    ui::print_error("hudo which 仅支持 Windows，请使用 which -a");
+                }
+            }
+            Commands::Service { tool, action } => {
+                #[cfg(windows)]
+                cmd_service(&tool, action)?;
+                #[cfg(not(windows))]
+                {
+                    let _ = (tool, action);
+                    ui::print_error("hudo service 仅支持 Windows");
+                }
+            }
+            Commands::Cache { action: CacheAction::Info } => {
+                let config = ensure_config(&opts)?;
+                cache::cmd_info(&config)?;
+            }
+            Commands::Mirrors { action: MirrorsAction::Test } => {
+                let config = ensure_config(&opts)?;
+                mirrors::cmd_test(&config).await?;
+            }
+            Commands::Wsl { action: WslAction::Check } => {
+                #[cfg(windows)]
+                {
+                    let config = ensure_config(&opts)?;
+                    cmd_wsl_check(&config)?;
+                }
+                #[cfg(not(windows))]
+                {
+                    ui::print_error("hudo wsl 仅在 Windows 上可用");
+                }
+            }
+            Commands::Nuke => {
+                #[cfg(windows)]
+                cmd_nuke().await?;
+                #[cfg(not(windows))]
+                {
+                    ui::print_error("Linux/macOS 暂不支持 hudo nuke，请手动删除 hudo 目录");
+                }
             }
         },
         None => {
-            let config = ensure_config()?;
+            let config = ensure_config(&opts)?;
             interactive_menu(&config).await?;
         }
     }
 
+    // --no-registry：环境变量没有写入用户环境，按安装记录刷新会话脚本
+    if env::session_only() {
+        refresh_session_scripts()?;
+    }
+    Ok(())
+}
+
+/// 重新生成 hudo-env.ps1 / hudo-env.cmd，内容有变化时提示加载方式
+fn refresh_session_scripts() -> Result<()> {
+    let Some(config) = HudoConfig::load()? else {
+        return Ok(());
+    };
+    let reg = registry::InstallRegistry::load(&config.state_path())?;
+    let written = env::write_session_scripts(&reg, &all_installers(), &config)?;
+    if !written.is_empty() {
+        println!();
+        ui::print_info("未写入用户环境变量（--no-registry），已更新会话脚本：");
+        for path in &written {
+            let load = match path.extension().and_then(|e| e.to_str()) {
+                Some("ps1") => format!(". '{}'", path.display()),
+                _ => format!("call \"{}\"", path.display()),
+            };
+            ui::print_info(&format!("  {}", load));
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,195 @@
+//! hudo mirrors test：检查镜像是否可用
+//!
+//! 对每个镜像键（未设置镜像时为官方地址），用对应安装器的 resolve_download 构造一个
+//! 实际会下载的地址，发送 HEAD（被拒绝时改为只取前 1 KB 的 GET），汇总状态码、耗时和跳转目标。
+
+use anyhow::Result;
+use futures_util::StreamExt;
+use std::time::{Duration, Instant};
+
+use crate::config::HudoConfig;
+use crate::installer::Installer;
+
+/// 镜像键 → 使用该镜像的工具 id
+const MIRROR_TOOLS: &[(&str, &str)] = &[
+    ("uv", "uv"),
+    ("fnm", "nodejs"),
+    ("go", "go"),
+    ("java", "jdk"),
+    ("vscode", "vscode"),
+    ("pycharm", "pycharm"),
+    ("mysql", "mysql"),
+    ("pgsql", "pgsql"),
+    ("maven", "maven"),
+    ("gradle", "gradle"),
+    ("redis", "redis"),
+    ("sqlite", "sqlite"),
+];
+
+/// 同时进行的请求数
+const CONCURRENCY: usize = 4;
+
+/// 单个请求的超时
+const PROBE_TIMEOUT_SECS: u64 = 10;
+
+/// 要检查的一个地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub key: &'static str,
+    pub tool: &'static str,
+    /// 是否为用户设置的镜像（否则为官方地址）
+    pub custom: bool,
+    pub url: String,
+}
+
+/// 单个地址的检查结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Probe {
+    /// HTTP 状态码，请求失败时为 None
+    pub status: Option<u16>,
+    pub latency: Duration,
+    /// 跳转后的最终地址（与请求地址相同时为 None）
+    pub redirected_to: Option<String>,
+    pub error: Option<String>,
+    /// 文件大小（Content-Length，Range GET 时取 Content-Range 中的总长度），服务器未给出时为 None
+    pub size: Option<u64>,
+}
+
+impl Probe {
+    pub fn ok(&self) -> bool {
+        self.status.is_some_and(|s| (200..300).contains(&s))
+    }
+
+    /// 结果说明：404 多半是镜像的目录结构与官方不同
+    pub fn verdict(&self) -> String {
+        match (self.status, &self.error) {
+            (Some(s), _) if (200..300).contains(&s) => "可用".to_string(),
+            (Some(404), _) => "404：文件不存在，镜像的目录结构可能与官方不同".to_string(),
+            (Some(s), _) => format!("HTTP {}", s),
+            (None, Some(e)) => e.clone(),
+            (None, None) => "请求失败".to_string(),
+        }
+    }
+}
+
+/// 所有镜像键对应的检查地址（只包含本平台有安装器的工具）
+pub fn targets(config: &HudoConfig, installers: &[Box<dyn Installer>]) -> Vec<Target> {
+    MIRROR_TOOLS
+        .iter()
+        .filter_map(|&(key, tool)| {
+            let inst = installers.iter().find(|i| i.info().id == tool)?;
+            let (url, _) = inst.resolve_download(config);
+            Some(Target { key, tool, custom: config.mirrors.get(key).is_some(), url })
+        })
+        .collect()
+}
+
+/// HEAD 请求；服务器拒绝 HEAD（405、403、501 等）时改为 Range GET 前 1 KB
+pub async fn probe(client: &reqwest::Client, url: &str) -> Probe {
+    let start = Instant::now();
+    let mut result = client.head(url).send().await;
+    let rejected = match &result {
+        Ok(resp) => matches!(resp.status().as_u16(), 403 | 405 | 501),
+        Err(_) => true,
+    };
+    if rejected {
+        result = client.get(url).header(reqwest::header::RANGE, "bytes=0-1023").send().await;
+    }
+    let latency = start.elapsed();
+    match result {
+        Ok(resp) => Probe {
+            status: Some(resp.status().as_u16()),
+            latency,
+            redirected_to: Some(resp.url().to_string()).filter(|u| u != url),
+            error: None,
+            size: content_size(resp.status().as_u16(), resp.headers()),
+        },
+        Err(e) => Probe {
+            status: None,
+            latency,
+            redirected_to: None,
+            error: Some(match e.is_timeout() {
+                true => "超时".to_string(),
+                false => format!("请求失败: {}", e),
+            }),
+            size: None,
+        },
+    }
+}
+
+/// 响应头中的文件大小：206 取 Content-Range 的总长度（"bytes 0-1023/12345"），其余成功响应取 Content-Length
+pub fn content_size(status: u16, headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+    let size = match status {
+        206 => header(reqwest::header::CONTENT_RANGE)?.rsplit('/').next()?.trim().parse().ok()?,
+        200..=299 => header(reqwest::header::CONTENT_LENGTH)?.trim().parse().ok()?,
+        _ => return None,
+    };
+    (size > 0).then_some(size)
+}
+
+/// 以有限并发检查全部地址，结果与 targets 顺序一致
+pub async fn probe_all(targets: &[Target]) -> Result<Vec<Probe>> {
+    let client = crate::version::http_client(PROBE_TIMEOUT_SECS)?;
+    let results = futures_util::stream::iter(targets)
+        .map(|t| {
+            let client = &client;
+            async move { probe(client, &t.url).await }
+        })
+        .buffered(CONCURRENCY)
+        .collect()
+        .await;
+    Ok(results)
+}
+
+/// hudo mirrors test
+pub async fn cmd_test(config: &HudoConfig) -> Result<()> {
+    crate::ui::print_title("检查镜像");
+    let targets = targets(config, &crate::installer::all_installers());
+    if targets.is_empty() {
+        crate::ui::print_info("当前平台没有使用镜像的工具");
+        return Ok(());
+    }
+
+    crate::ui::print_action(&format!("检查 {} 个下载地址...", targets.len()));
+    let probes = probe_all(&targets).await?;
+
+    println!();
+    for (target, probe) in targets.iter().zip(&probes) {
+        let source = match target.custom {
+            true => console::style("镜像").cyan(),
+            false => console::style("官方").dim(),
+        };
+        let verdict = match probe.ok() {
+            true => console::style(probe.verdict()).green(),
+            false => console::style(probe.verdict()).red(),
+        };
+        println!(
+            "  {}  {}  {}  {}",
+            crate::ui::pad(target.key, 8),
+            source,
+            crate::ui::pad(&format!("{} ms", probe.latency.as_millis()), 8),
+            verdict
+        );
+        println!("      {}", console::style(&target.url).dim());
+        if let Some(to) = &probe.redirected_to {
+            println!("      {} {}", console::style(crate::ui::Glyph::Arrow).dim(), console::style(to).dim());
+        }
+    }
+
+    println!();
+    let failed: Vec<&str> = targets
+        .iter()
+        .zip(&probes)
+        .filter(|(_, p)| !p.ok())
+        .map(|(t, _)| t.key)
+        .collect();
+    match failed.is_empty() {
+        true => crate::ui::print_success("全部地址可用"),
+        false => crate::ui::print_warning(&format!(
+            "{} 不可用，可用 hudo config set mirrors.<键> <地址> 修改镜像",
+            failed.join(", ")
+        )),
+    }
+    Ok(())
+}
@@ -0,0 +1,209 @@
+//! hudo 自身文件的位置：config.toml、cc-providers.toml、更新检查缓存、Unix 的 env.sh 与会话脚本
+//!
+//! 默认放在 ~/.hudo；hudo.exe 旁有 `hudo.portable` 标记文件（或 `hudo.toml` 中 `portable = true`）时为便携模式，
+//! 这些文件改放在 exe 旁的 `.hudo` 目录，相对路径的 root_dir（如 `.\hudo`）也按 exe 所在目录解析，
+//! 整个 hudo 可以放在 U 盘上带到其他电脑使用。
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+/// 便携模式的标记文件
+pub const PORTABLE_MARKER: &str = "hudo.portable";
+
+/// 便携模式的设置文件（与标记文件二选一）
+pub const PORTABLE_SETTINGS: &str = "hudo.toml";
+
+/// exe 旁的 hudo.toml
+#[derive(Debug, Default, Deserialize)]
+struct PortableFile {
+    #[serde(default)]
+    portable: bool,
+    /// 不写用户环境变量，只生成会话脚本（等同于每次都带 --no-registry）
+    #[serde(default)]
+    no_registry: bool,
+}
+
+/// hudo 文件的存放方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    /// ~/.hudo
+    Home(PathBuf),
+    /// exe 所在目录；no_registry 来自 hudo.toml
+    Portable { exe_dir: PathBuf, no_registry: bool },
+}
+
+impl Location {
+    /// 按 exe 所在目录旁的标记判断模式；不是便携模式时需要用户主目录
+    pub fn detect(exe_dir: Option<&Path>, home: Option<&Path>) -> Result<Self> {
+        if let Some(dir) = exe_dir {
+            let settings = std::fs::read_to_string(dir.join(PORTABLE_SETTINGS))
+                .ok()
+                .map(|s| toml::from_str::<PortableFile>(&s))
+                .transpose()
+                .with_context(|| format!("便携模式设置文件格式错误: {}", dir.join(PORTABLE_SETTINGS).display()))?
+                .unwrap_or_default();
+            if settings.portable || dir.join(PORTABLE_MARKER).exists() {
+                return Ok(Location::Portable { exe_dir: dir.to_path_buf(), no_registry: settings.no_registry });
+            }
+        }
+        let home = home.context("无法获取用户主目录")?;
+        Ok(Location::Home(home.to_path_buf()))
+    }
+
+    pub fn is_portable(&self) -> bool {
+        matches!(self, Location::Portable { .. })
+    }
+
+    /// 存放 hudo 自身文件的目录
+    pub fn data_dir(&self) -> PathBuf {
+        match self {
+            Location::Home(home) => home.join(".hudo"),
+            Location::Portable { exe_dir, .. } => exe_dir.join(".hudo"),
+        }
+    }
+
+    /// 便携模式下相对路径的 root_dir 按 exe 所在目录解析（`\hudo` 这类无盘符的路径落在 exe 所在的盘）；
+    /// 其他情况原样返回
+    pub fn resolve_root(&self, root: &Path) -> PathBuf {
+        match self {
+            Location::Portable { exe_dir, .. } if !root.is_absolute() => exe_dir
+                .join(root)
+                .components()
+                .filter(|c| !matches!(c, Component::CurDir))
+                .collect(),
+            _ => root.to_path_buf(),
+        }
+    }
+}
+
+static LOCATION: OnceLock<Option<Location>> = OnceLock::new();
+
+/// 当前进程的存放方式（首次调用时检测）
+pub fn location() -> Result<&'static Location> {
+    LOCATION
+        .get_or_init(|| {
+            let exe = std::env::current_exe().ok();
+            let exe_dir = exe.as_deref().and_then(Path::parent);
+            match Location::detect(exe_dir, dirs::home_dir().as_deref()) {
+                Ok(location) => Some(location),
+                Err(e) => {
+                    crate::ui::print_warning(&format!("{:#}", e));
+                    Location::detect(None, dirs::home_dir().as_deref()).ok()
+                }
+            }
+        })
+        .as_ref()
+        .context("无法获取用户主目录")
+}
+
+pub fn is_portable() -> bool {
+    location().is_ok_and(Location::is_portable)
+}
+
+/// hudo.toml 中设置了 no_registry = true
+pub fn portable_no_registry() -> bool {
+    matches!(location(), Ok(Location::Portable { no_registry: true, .. }))
+}
+
+pub fn data_dir() -> Result<PathBuf> {
+    Ok(location()?.data_dir())
+}
+
+/// 配置文件: ~/.hudo\config.toml（便携模式为 exe 旁的 .hudo\config.toml，下同）
+pub fn config_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join("config.toml"))
+}
+
+/// AI 工具 Provider: ~/.hudo\cc-providers.toml
+pub fn providers_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join("cc-providers.toml"))
+}
+
+/// 更新检查缓存: ~/.hudo\update-check.json
+pub fn update_check_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join("update-check.json"))
+}
+
+/// Unix 的环境变量文件: ~/.hudo/env.sh
+pub fn env_sh_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join("env.sh"))
+}
+
+/// --no-registry 时生成的会话脚本：hudo-env.ps1 / hudo-env.cmd
+pub fn session_script_path(ext: &str) -> Result<PathBuf> {
+    Ok(data_dir()?.join(format!("hudo-env.{}", ext)))
+}
+
+/// 按当前模式解析 root_dir
+pub fn resolve_root(root: &Path) -> PathBuf {
+    match location() {
+        Ok(location) => location.resolve_root(root),
+        Err(_) => root.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hudo-test-paths-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_home_mode() {
+        let exe_dir = temp_dir("home");
+        let home = Path::new("/home/张三");
+        let location = Location::detect(Some(&exe_dir), Some(home)).unwrap();
+        assert_eq!(location, Location::Home(home.to_path_buf()));
+        assert_eq!(location.data_dir(), home.join(".hudo"));
+        // 普通模式下不解析相对路径
+        assert_eq!(location.resolve_root(Path::new("hudo")), PathBuf::from("hudo"));
+        assert!(Location::detect(Some(&exe_dir), None).is_err());
+        std::fs::remove_dir_all(&exe_dir).ok();
+    }
+
+    #[test]
+    fn test_portable_marker() {
+        let exe_dir = temp_dir("marker");
+        std::fs::write(exe_dir.join(PORTABLE_MARKER), "").unwrap();
+        // 便携模式不需要用户主目录
+        let location = Location::detect(Some(&exe_dir), None).unwrap();
+        assert_eq!(location, Location::Portable { exe_dir: exe_dir.clone(), no_registry: false });
+        assert_eq!(location.data_dir(), exe_dir.join(".hudo"));
+        assert_eq!(location.resolve_root(Path::new("./hudo")), exe_dir.join("hudo"));
+        assert_eq!(location.resolve_root(Path::new("data/hudo")), exe_dir.join("data").join("hudo"));
+        let absolute = std::env::temp_dir().join("elsewhere");
+        assert_eq!(location.resolve_root(&absolute), absolute);
+        std::fs::remove_dir_all(&exe_dir).ok();
+    }
+
+    #[test]
+    fn test_portable_settings() {
+        let exe_dir = temp_dir("settings");
+        std::fs::write(exe_dir.join(PORTABLE_SETTINGS), "portable = false\n").unwrap();
+        assert!(!Location::detect(Some(&exe_dir), Some(Path::new("/home/a"))).unwrap().is_portable());
+
+        std::fs::write(exe_dir.join(PORTABLE_SETTINGS), "portable = true\nno_registry = true\n").unwrap();
+        let location = Location::detect(Some(&exe_dir), None).unwrap();
+        assert_eq!(location, Location::Portable { exe_dir: exe_dir.clone(), no_registry: true });
+
+        std::fs::write(exe_dir.join(PORTABLE_SETTINGS), "portable = 1\n").unwrap();
+        let err = Location::detect(Some(&exe_dir), None).unwrap_err();
+        assert!(format!("{:#}", err).contains("hudo.toml"), "{:#}", err);
+        std::fs::remove_dir_all(&exe_dir).ok();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_portable_drive_relative_root() {
+        let location = Location::Portable { exe_dir: PathBuf::from(r"E:\apps\hudo"), no_registry: false };
+        assert_eq!(location.resolve_root(Path::new(r".\hudo")), PathBuf::from(r"E:\apps\hudo\hudo"));
+        assert_eq!(location.resolve_root(Path::new(r"\hudo")), PathBuf::from(r"E:\hudo"));
+        assert_eq!(location.resolve_root(Path::new(r"D:\hudo")), PathBuf::from(r"D:\hudo"));
+    }
+}
@@ -0,0 +1,233 @@
+//! 子进程调用：cmd / PowerShell 的参数引用与输出解码
+//!
+//! 用户名含空格或中文时，路径会原样出现在命令行和子进程输出里，
+//! 这里集中处理引号和编码，各安装器不再各自拼接。
+
+use std::process::Command;
+
+/// PowerShell 单引号字符串字面量（单引号内不做变量展开，只需把 ' 写成 ''）
+pub fn ps_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// 按 Windows 程序解析命令行的规则（CommandLineToArgvW）引用单个参数
+///
+/// 含空白或双引号时加双引号，引号前的反斜杠加倍，内部双引号写成 \"
+pub fn win_arg(value: &str) -> String {
+    if !value.is_empty() && !value.contains([' ', '\t', '\n', '"']) {
+        return value.to_string();
+    }
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in value.chars() {
+        match c {
+            '\\' => {
+                backslashes += 1;
+                quoted.push(c);
+            }
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes + 1));
+                quoted.push(c);
+                backslashes = 0;
+            }
+            _ => {
+                backslashes = 0;
+                quoted.push(c);
+            }
+        }
+    }
+    // 结尾的反斜杠紧挨着收尾引号，同样要加倍
+    quoted.push_str(&"\\".repeat(backslashes));
+    quoted.push('"');
+    quoted
+}
+
+/// 把参数按 Windows 规则引用后拼成一条命令行
+pub fn win_args(args: &[&str]) -> String {
+    args.iter().map(|a| win_arg(a)).collect::<Vec<_>>().join(" ")
+}
+
+/// `cmd /c` 中的单个参数：含空格或 cmd 特殊字符时加双引号
+fn cmd_arg(value: &str) -> String {
+    let special = [' ', '\t', '&', '|', '<', '>', '(', ')', '^', ',', ';', '='];
+    if !value.is_empty() && !value.contains(special) {
+        return value.to_string();
+    }
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// `cmd /s /c` 之后的命令行：整条命令再包一层引号，
+/// 否则 cmd 会去掉首尾引号，把 `"D:\my tools\mvn.cmd"` 拆成两段
+pub fn cmd_line(program: &str, args: &[&str]) -> String {
+    let parts: Vec<String> = std::iter::once(program)
+        .chain(args.iter().copied())
+        .map(cmd_arg)
+        .collect();
+    format!("\"{}\"", parts.join(" "))
+}
+
+/// 通过 `cmd /c` 运行程序（用于 .cmd / .bat 及 npm、code 等批处理包装的命令）
+pub fn cmd(program: &str, args: &[&str]) -> Command {
+    let mut command = Command::new("cmd");
+    command.args(["/s", "/c"]);
+    #[cfg(windows)]
+    {
+        // 命令行已自行引用，不能再让标准库按 CommandLineToArgvW 规则加一层
+        use std::os::windows::process::CommandExt;
+        command.raw_arg(cmd_line(program, args));
+    }
+    #[cfg(not(windows))]
+    command.arg(cmd_line(program, args));
+    command
+}
+
+/// 运行 PowerShell 脚本；输出编码固定为 UTF-8，避免中文路径按控制台代码页输出后被误解码
+pub fn powershell(script: &str) -> Command {
+    let mut command = Command::new("powershell");
+    command.args([
+        "-NoProfile",
+        "-Command",
+        &format!("[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; {}", script),
+    ]);
+    command
+}
+
+/// 以管理员身份运行程序并等待结束的 PowerShell 脚本（供 `run_as_admin` 使用）
+///
+/// Start-Process 的 -ArgumentList 传数组时不会给含空格的元素加引号，
+/// 所以先按 Windows 规则拼成一条命令行再整体传入
+pub fn elevate_script(program: &str, args: &[&str]) -> String {
+    let arg_list = if args.is_empty() {
+        String::new()
+    } else {
+        format!(" -ArgumentList {}", ps_quote(&win_args(args)))
+    };
+    format!(
+        "try {{ \
+           $p = Start-Process -FilePath {}{} \
+                -Verb RunAs -Wait -PassThru -WindowStyle Hidden; \
+           if ($p) {{ exit $p.ExitCode }} else {{ exit 1 }} \
+         }} catch {{ exit 1 }}",
+        ps_quote(program),
+        arg_list
+    )
+}
+
+/// 解码子进程输出：优先按 UTF-8，不是合法 UTF-8 时按控制台代码页（中文系统通常是 936）
+pub fn decode_output(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => decode_codepage(bytes).unwrap_or_else(|| String::from_utf8_lossy(bytes).into_owned()),
+    }
+}
+
+#[cfg(windows)]
+fn decode_codepage(bytes: &[u8]) -> Option<String> {
+    use windows_sys::Win32::Globalization::{GetOEMCP, CP_UTF8};
+    use windows_sys::Win32::System::Console::GetConsoleOutputCP;
+
+    // 没有控制台时（如重定向到文件）退回 OEM 代码页，与子进程的默认输出编码一致；
+    // 控制台已是 UTF-8（hudo 启动时切换）时不是合法 UTF-8 的输出同样按 OEM 代码页解码
+    let codepage = match unsafe { GetConsoleOutputCP() } {
+        0 | CP_UTF8 => unsafe { GetOEMCP() },
+        cp => cp,
+    };
+    decode_with_codepage(bytes, codepage)
+}
+
+/// 按指定代码页（如 936 即 GBK）解码
+#[cfg(windows)]
+pub fn decode_with_codepage(bytes: &[u8], codepage: u32) -> Option<String> {
+    use windows_sys::Win32::Globalization::MultiByteToWideChar;
+
+    if bytes.is_empty() {
+        return Some(String::new());
+    }
+    let len = i32::try_from(bytes.len()).ok()?;
+    let wide_len = unsafe {
+        MultiByteToWideChar(codepage, 0, bytes.as_ptr(), len, std::ptr::null_mut(), 0)
+    };
+    if wide_len <= 0 {
+        return None;
+    }
+    let mut wide = vec![0u16; wide_len as usize];
+    let written = unsafe {
+        MultiByteToWideChar(codepage, 0, bytes.as_ptr(), len, wide.as_mut_ptr(), wide_len)
+    };
+    if written <= 0 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&wide[..written as usize]))
+}
+
+#[cfg(not(windows))]
+fn decode_codepage(_bytes: &[u8]) -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPACED: &str = r"C:\Users\张 三\hudo tools\mysql";
+
+    #[test]
+    fn test_ps_quote() {
+        assert_eq!(ps_quote(SPACED), r"'C:\Users\张 三\hudo tools\mysql'");
+        assert_eq!(ps_quote(r"C:\Users\O'Brien"), r"'C:\Users\O''Brien'");
+    }
+
+    #[test]
+    fn test_win_arg() {
+        assert_eq!(win_arg("start"), "start");
+        assert_eq!(win_arg(""), "\"\"");
+        assert_eq!(win_arg(SPACED), format!("\"{}\"", SPACED));
+        assert_eq!(win_arg(r"--defaults-file=D:\a b\my.ini"), r#""--defaults-file=D:\a b\my.ini""#);
+        // 结尾反斜杠与内部引号
+        assert_eq!(win_arg(r"D:\a b\"), r#""D:\a b\\""#);
+        assert_eq!(win_arg(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(win_arg(r#"a\"b"#), r#""a\\\"b""#);
+    }
+
+    #[test]
+    fn test_cmd_line() {
+        assert_eq!(cmd_line("mvn", &["--version"]), "\"mvn --version\"");
+        assert_eq!(
+            cmd_line(r"D:\张 三\maven\bin\mvn.cmd", &["--version"]),
+            r#"""D:\张 三\maven\bin\mvn.cmd" --version""#
+        );
+        assert_eq!(cmd_line("npm", &["uninstall", "-g", "@scope/pkg"]), "\"npm uninstall -g @scope/pkg\"");
+        assert_eq!(cmd_line(r"D:\R&D\x.cmd", &[]), r#"""D:\R&D\x.cmd"""#);
+    }
+
+    #[test]
+    fn test_elevate_script() {
+        let mysqld = r"C:\Users\张 三\hudo\lang\mysql\bin\mysqld.exe";
+        let script = elevate_script(mysqld, &["--install", "MySQL", r"--defaults-file=C:\Users\张 三\my.ini"]);
+        assert!(script.contains(&format!("-FilePath '{}'", mysqld)));
+        assert!(script.contains(r#"-ArgumentList '--install MySQL "--defaults-file=C:\Users\张 三\my.ini"'"#));
+
+        let script = elevate_script("net", &["start", "O'Brien"]);
+        assert!(script.contains("-ArgumentList 'start O''Brien'"));
+
+        // 无参数时不能传空的 -ArgumentList
+        assert!(!elevate_script("net", &[]).contains("-ArgumentList"));
+    }
+
+    #[test]
+    fn test_decode_output() {
+        assert_eq!(decode_output("D:\\张 三\\uv.exe\r\n".as_bytes()), "D:\\张 三\\uv.exe\r\n");
+        assert!(!decode_output(&[0xd5, 0xc5, b' ', 0xc8, 0xfd]).is_empty());
+    }
+
+    /// 中文系统的子进程默认按 GBK（936）输出，如 sc / net 的错误信息
+    #[cfg(windows)]
+    #[test]
+    fn test_decode_gbk() {
+        // "拒绝访问。\r\n" 的 GBK 编码
+        let gbk = [0xbe, 0xdc, 0xbe, 0xf8, 0xb7, 0xc3, 0xce, 0xca, 0xa1, 0xa3, b'\r', b'\n'];
+        assert_eq!(decode_with_codepage(&gbk, 936).as_deref(), Some("拒绝访问。\r\n"));
+        assert_eq!(decode_with_codepage(&[0xd5, 0xc5, b' ', 0xc8, 0xfd], 936).as_deref(), Some("张 三"));
+        assert_eq!(decode_with_codepage(b"", 936).as_deref(), Some(""));
+    }
+}
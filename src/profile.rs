@@ -3,15 +3,18 @@ use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::path::Path;
 
-use crate::cc::{CcProvider, CcProviders};
-use crate::config::HudoConfig;
-use crate::installer::{DetectResult, InstallContext, Installer};
+use crate::ai::{AiProvider, AiProviders};
+use crate::config::{HudoConfig, MirrorConfig};
+use crate::download;
+use crate::installer::{self, DetectResult, InstallContext, Installer};
 use crate::registry;
 
 /// Profile 文件头信息
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HudoMeta {
     pub version: String,
+    /// export --no-timestamp 时为空，不写入档案
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub exported_at: String,
 }
 
@@ -28,6 +31,17 @@ pub struct ProfileSettings {
     pub versions: BTreeMap<String, String>,
 }
 
+/// 锁定的精确安装信息（hudo export --lock 生成，hudo import --frozen 使用）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LockEntry {
+    pub version: String,
+    pub url: String,
+    pub filename: String,
+    /// 导出时缓存中存在安装包才记录
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
 /// 完整的 Profile 数据结构
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HudoProfile {
@@ -36,10 +50,15 @@ pub struct HudoProfile {
     pub settings: ProfileSettings,
     #[serde(default)]
     pub tools: BTreeMap<String, String>,
+    /// 已固定（hudo pin）的工具，导入时恢复固定标记
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<String>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub tool_config: BTreeMap<String, BTreeMap<String, String>>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub cc_providers: Vec<CcProvider>,
+    pub cc_providers: Vec<AiProvider>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub lock: BTreeMap<String, LockEntry>,
 }
 
 impl HudoProfile {
@@ -48,13 +67,16 @@ impl HudoProfile {
         config: &HudoConfig,
         installers: &[Box<dyn Installer>],
     ) -> Result<Self> {
-        let ctx = InstallContext { config };
+        let ctx = InstallContext::new(config);
         let mut tools = BTreeMap::new();
         let mut tool_config = BTreeMap::new();
 
-        for inst in installers {
+        // 并行检测所有工具（各自运行子进程，串行等待很慢）
+        let refs: Vec<&dyn Installer> = installers.iter().map(|i| i.as_ref()).collect();
+        let detected = installer::detect_parallel(&refs, config);
+
+        for (inst, detect) in installers.iter().zip(detected) {
             let info = inst.info();
-            let detect = inst.detect_installed(&ctx).await;
 
             // 记录所有已安装工具（无论 hudo 还是系统安装）
             let version = match &detect {
@@ -65,7 +87,7 @@ impl HudoProfile {
 
             if let Some(ver) = version {
                 // 提取纯版本号（去掉 "git version " 等前缀）
-                let short = extract_version(&ver);
+                let short = crate::version::extract_version(&ver);
                 tools.insert(info.id.to_string(), short);
 
                 // 收集工具配置
@@ -82,35 +104,10 @@ impl HudoProfile {
 
         // 收集 settings
         let mut mirrors = BTreeMap::new();
-        if let Some(ref v) = config.mirrors.uv {
-            mirrors.insert("uv".to_string(), v.clone());
-        }
-        if let Some(ref v) = config.mirrors.fnm {
-            mirrors.insert("fnm".to_string(), v.clone());
-        }
-        if let Some(ref v) = config.mirrors.go {
-            mirrors.insert("go".to_string(), v.clone());
-        }
-        if let Some(ref v) = config.mirrors.java {
-            mirrors.insert("java".to_string(), v.clone());
-        }
-        if let Some(ref v) = config.mirrors.vscode {
-            mirrors.insert("vscode".to_string(), v.clone());
-        }
-        if let Some(ref v) = config.mirrors.pycharm {
-            mirrors.insert("pycharm".to_string(), v.clone());
-        }
-        if let Some(ref v) = config.mirrors.mysql {
-            mirrors.insert("mysql".to_string(), v.clone());
-        }
-        if let Some(ref v) = config.mirrors.pgsql {
-            mirrors.insert("pgsql".to_string(), v.clone());
-        }
-        if let Some(ref v) = config.mirrors.maven {
-            mirrors.insert("maven".to_string(), v.clone());
-        }
-        if let Some(ref v) = config.mirrors.gradle {
-            mirrors.insert("gradle".to_string(), v.clone());
+        for key in MirrorConfig::keys() {
+            if let Some(v) = config.mirrors.get(key) {
+                mirrors.insert(key.to_string(), v.clone());
+            }
         }
 
         let mut versions = BTreeMap::new();
@@ -133,6 +130,9 @@ impl HudoProfile {
             versions.insert("pycharm".to_string(), v.clone());
         }
 
+        let reg = registry::InstallRegistry::load(&config.state_path())?;
+        let pinned = tools.keys().filter(|id| reg.is_pinned(id)).cloned().collect();
+
         let settings = ProfileSettings {
             java_version: Some(config.java.version.clone()),
             go_version: Some(config.go.version.clone()),
@@ -147,15 +147,53 @@ impl HudoProfile {
             },
             settings,
             tools,
+            pinned,
             tool_config,
-            cc_providers: CcProviders::load().unwrap_or_default().providers,
+            cc_providers: AiProviders::load().unwrap_or_default().providers,
+            lock: BTreeMap::new(),
         })
     }
 
+    /// 为 tools 中的每个工具生成锁定信息：固定版本后解析下载地址，缓存中有从该地址下载的安装包则计算 SHA256
+    /// （同名但来源地址不同或没有来源记录的缓存文件不计算，sha256 留空）
+    /// hudo 安装的工具优先使用 state.json 中记录的版本（与安装器的版本格式一致）
+    pub fn build_lock(&mut self, config: &HudoConfig, installers: &[Box<dyn Installer>]) -> Result<()> {
+        let reg = registry::InstallRegistry::load(&config.state_path())?;
+        for (id, ver) in &self.tools {
+            let Some(inst) = installers.iter().find(|i| i.info().id == id.as_str()) else {
+                continue;
+            };
+            let version = reg.get(id).map(|s| s.version.clone()).unwrap_or_else(|| ver.clone());
+            let mut pinned = config.clone();
+            pinned.pin_version(id, &version);
+            let (url, filename) = inst.resolve_download(&pinned);
+            let sha256 = if download::is_cached(&url, &config.cache_dir(), &filename) {
+                Some(download::sha256_file(&config.cache_dir().join(&filename))?)
+            } else {
+                None
+            };
+            self.lock.insert(
+                id.clone(),
+                LockEntry {
+                    version,
+                    url,
+                    filename,
+                    sha256,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// 档案文件内容：各段均为 BTreeMap，键按字母排序，同样的环境导出的内容相同
+    pub fn to_toml(&self) -> Result<String> {
+        let content = toml::to_string_pretty(self).context("序列化 profile 失败")?;
+        Ok(format!("# hudo profile\n{}", content))
+    }
+
     /// 保存 profile 到文件
     pub fn save_to_file(&self, path: &Path) -> Result<()> {
-        let content = toml::to_string_pretty(self).context("序列化 profile 失败")?;
-        let content = format!("# hudo profile\n{}", content);
+        let content = self.to_toml()?;
         std::fs::write(path, content)
             .with_context(|| format!("无法写入 profile: {}", path.display()))?;
         Ok(())
@@ -171,13 +209,50 @@ impl HudoProfile {
     }
 }
 
-/// 从版本字符串中提取纯版本号
-fn extract_version(ver: &str) -> String {
-    let trimmed = ver.trim();
-    // 尝试找到以数字开头的 token（如 "git version 2.47.1" → "2.47.1"）
-    trimmed
-        .split_whitespace()
-        .find(|s| s.starts_with(|c: char| c.is_ascii_digit()))
-        .unwrap_or(trimmed)
-        .to_string()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_roundtrip() {
+        // 旧版档案没有 pinned 字段
+        let old: HudoProfile = toml::from_str(
+            "[hudo]\nversion = \"0.3.0\"\nexported_at = \"2025-01-01 00:00:00\"\n\n[tools]\nmysql = \"8.0.40\"\n",
+        )
+        .unwrap();
+        assert!(old.pinned.is_empty());
+        assert!(!toml::to_string_pretty(&old).unwrap().contains("pinned"));
+
+        let mut prof = old;
+        prof.pinned.push("mysql".to_string());
+        prof.tool_config.insert(
+            "git".to_string(),
+            BTreeMap::from([("core.autocrlf".to_string(), "true".to_string())]),
+        );
+        let content = toml::to_string_pretty(&prof).unwrap();
+        let loaded: HudoProfile = toml::from_str(&content).unwrap();
+        assert_eq!(loaded.pinned, ["mysql"]);
+        assert_eq!(loaded.tools["mysql"], "8.0.40");
+    }
+
+    #[test]
+    fn test_no_timestamp_is_stable() {
+        let build = |tools: &[(&str, &str)]| HudoProfile {
+            hudo: HudoMeta { version: "0.5.0".to_string(), exported_at: String::new() },
+            settings: ProfileSettings::default(),
+            tools: tools.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            pinned: Vec::new(),
+            tool_config: BTreeMap::new(),
+            cc_providers: Vec::new(),
+            lock: BTreeMap::new(),
+        };
+        // 检测顺序不同，输出相同
+        let a = build(&[("mysql", "8.0.40"), ("go", "1.24.0"), ("git", "2.47.1")]).to_toml().unwrap();
+        let b = build(&[("git", "2.47.1"), ("mysql", "8.0.40"), ("go", "1.24.0")]).to_toml().unwrap();
+        assert_eq!(a, b);
+        assert!(!a.contains("exported_at"));
+        assert!(a.find("git =").unwrap() < a.find("go =").unwrap());
+        let loaded: HudoProfile = toml::from_str(&a).unwrap();
+        assert!(loaded.hudo.exported_at.is_empty());
+    }
 }
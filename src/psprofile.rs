@@ -0,0 +1,412 @@
+//! PowerShell profile 中由 hudo 管理的区块
+//!
+//! 每个工具一个区块，以 `# >>> hudo <name> >>>` 开始、`# <<< hudo <name> <<<` 结束，
+//! 写入时替换区块内容（重复的区块合并为一个），卸载时只删除区块，profile 中的其他内容原样保留。
+//! 沿用文件原有的换行符（新文件用 CRLF）与 BOM；多个 hudo 进程同时修改时用锁文件排队，
+//! 写入先落到临时文件再替换，中断时不会留下写了一半的 profile。
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::installer::ShellIntegration;
+
+const BOM: &str = "\u{feff}";
+
+/// 超过这个时间的锁文件视为上次中断留下的，直接接管
+const LOCK_STALE: Duration = Duration::from_secs(30);
+
+/// 等待其他进程释放锁的最长时间
+const LOCK_WAIT: Duration = Duration::from_secs(10);
+
+pub fn begin_marker(name: &str) -> String {
+    format!("# >>> hudo {} >>>", name)
+}
+
+pub fn end_marker(name: &str) -> String {
+    format!("# <<< hudo {} <<<", name)
+}
+
+/// 按行拆分，每行保留自己的换行符
+fn split_lines(content: &str) -> Vec<&str> {
+    content.split_inclusive('\n').collect()
+}
+
+/// 行内容（去掉换行符与首尾空白），用于匹配标记
+fn line_text(line: &str) -> &str {
+    line.trim_end_matches(['\r', '\n']).trim()
+}
+
+/// 文件使用的换行符：有 CRLF 时为 CRLF，只有 LF 时为 LF，空文件为 CRLF
+fn newline_of(content: &str) -> &'static str {
+    match (content.contains("\r\n"), content.contains('\n')) {
+        (false, true) => "\n",
+        _ => "\r\n",
+    }
+}
+
+/// name 的所有完整区块（开始行、结束行的下标）；没有配对结束标记的开始行不算区块，
+/// 避免把它与后面的区块之间的用户内容当作区块删掉
+fn find_blocks(lines: &[&str], name: &str) -> Vec<(usize, usize)> {
+    let (begin, end) = (begin_marker(name), end_marker(name));
+    let mut blocks = Vec::new();
+    let mut open = None;
+    for (i, line) in lines.iter().enumerate() {
+        let text = line_text(line);
+        if text == begin {
+            open = Some(i);
+        } else if text == end {
+            if let Some(start) = open.take() {
+                blocks.push((start, i));
+            }
+        }
+    }
+    blocks
+}
+
+/// 旧版本 hudo 直接追加的内容：注释行与紧跟的一行
+fn find_legacy(lines: &[&str], comment: &str) -> Vec<(usize, usize)> {
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(i, line)| line_text(line) == comment && *i + 1 < lines.len())
+        .map(|(i, _)| (i, i + 1))
+        .collect()
+}
+
+/// 删除 ranges 中的行（闭区间），区块前由 hudo 加上的空行一并删除（区块在文件开头时删除其后的空行）；
+/// 返回剩余的行与第一个区块原来所在的位置
+fn remove_ranges<'a>(lines: &[&'a str], ranges: &[(usize, usize)]) -> (Vec<&'a str>, Option<usize>) {
+    let mut kept: Vec<&'a str> = Vec::with_capacity(lines.len());
+    let mut first = None;
+    let mut i = 0;
+    while i < lines.len() {
+        match ranges.iter().find(|(start, _)| *start == i) {
+            Some(&(_, end)) => {
+                if kept.last().is_some_and(|l| line_text(l).is_empty()) {
+                    kept.pop();
+                } else if kept.is_empty() && lines.get(end + 1).is_some_and(|l| line_text(l).is_empty()) {
+                    i = end + 1;
+                }
+                first.get_or_insert(kept.len());
+                i = i.max(end) + 1;
+            }
+            None => {
+                kept.push(lines[i]);
+                i += 1;
+            }
+        }
+    }
+    (kept, first)
+}
+
+fn target_ranges(lines: &[&str], integration: &ShellIntegration) -> Vec<(usize, usize)> {
+    let mut ranges = find_blocks(lines, integration.name);
+    if let Some(comment) = integration.legacy_comment {
+        ranges.extend(find_legacy(lines, comment));
+    }
+    ranges.sort();
+    ranges
+}
+
+/// 写入（或替换）区块：放在原来第一个区块的位置，没有时追加到末尾
+pub fn upsert(content: &str, integration: &ShellIntegration) -> String {
+    let nl = newline_of(content);
+    let lines = split_lines(content);
+    let (mut kept, position) = remove_ranges(&lines, &target_ranges(&lines, integration));
+
+    // 最后一行没有换行符时补上，否则区块会接在这一行后面
+    let last = kept.pop().map(|l| match l.ends_with('\n') {
+        true => l.to_string(),
+        false => format!("{}{}", l, nl),
+    });
+    let mut owned: Vec<String> = kept.into_iter().map(str::to_string).collect();
+    owned.extend(last);
+
+    let mut block = vec![begin_marker(integration.name)];
+    block.extend(integration.script.lines().map(str::to_string));
+    block.push(end_marker(integration.name));
+    let block: Vec<String> = block.into_iter().map(|l| format!("{}{}", l, nl)).collect();
+
+    let at = position.unwrap_or(owned.len());
+    let mut insert = Vec::new();
+    if at > 0 && !line_text(&owned[at - 1]).is_empty() {
+        insert.push(nl.to_string());
+    }
+    insert.extend(block);
+    owned.splice(at..at, insert);
+    owned.concat()
+}
+
+/// 删除区块（以及旧版本追加的内容）；没有时原样返回
+pub fn remove(content: &str, integration: &ShellIntegration) -> String {
+    let lines = split_lines(content);
+    let ranges = target_ranges(&lines, integration);
+    if ranges.is_empty() {
+        return content.to_string();
+    }
+    remove_ranges(&lines, &ranges).0.concat()
+}
+
+/// 是否已有这个区块
+pub fn contains(content: &str, name: &str) -> bool {
+    !find_blocks(&split_lines(content), name).is_empty()
+}
+
+/// 读取 profile；不存在时为空。返回（是否有 BOM，正文）
+fn read(path: &Path) -> Result<(bool, String)> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e).with_context(|| format!("无法读取 {}", path.display())),
+    };
+    let content = String::from_utf8(bytes)
+        .map_err(|_| anyhow::anyhow!("{} 不是 UTF-8 编码，hudo 不会改写它", path.display()))?;
+    Ok(match content.strip_prefix(BOM) {
+        Some(body) => (true, body.to_string()),
+        None => (false, content),
+    })
+}
+
+/// 写入临时文件后替换原文件。
+/// Windows PowerShell 5.1 把无 BOM 的脚本按系统代码页读取，原来有 BOM 或内容含非 ASCII 字符时写成带 BOM 的 UTF-8
+fn write(path: &Path, bom: bool, body: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("无法创建目录 {}", parent.display()))?;
+    }
+    let mut content = String::with_capacity(body.len() + BOM.len());
+    if bom || !body.is_ascii() {
+        content.push_str(BOM);
+    }
+    content.push_str(body);
+    let tmp = sibling(path, "hudo-tmp");
+    std::fs::write(&tmp, content).with_context(|| format!("无法写入 {}", tmp.display()))?;
+    if let Err(e) = std::fs::rename(&tmp, path) {
+        std::fs::remove_file(&tmp).ok();
+        return Err(e).with_context(|| format!("无法写入 {}", path.display()));
+    }
+    Ok(())
+}
+
+/// profile.ps1 → profile.ps1.<suffix>
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", suffix));
+    path.with_file_name(name)
+}
+
+/// 锁文件，drop 时删除
+struct ProfileLock(PathBuf);
+
+impl ProfileLock {
+    fn acquire(path: &Path) -> Result<Self> {
+        let lock = sibling(path, "hudo-lock");
+        if let Some(parent) = lock.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("无法创建目录 {}", parent.display()))?;
+        }
+        let start = Instant::now();
+        loop {
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&lock) {
+                Ok(_) => return Ok(Self(lock)),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = std::fs::metadata(&lock)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|m| m.elapsed().ok())
+                        .is_some_and(|age| age > LOCK_STALE);
+                    if stale {
+                        std::fs::remove_file(&lock).ok();
+                        continue;
+                    }
+                    if start.elapsed() > LOCK_WAIT {
+                        anyhow::bail!("{} 正被另一个 hudo 进程修改，请稍后重试（也可删除 {}）", path.display(), lock.display());
+                    }
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                Err(e) => return Err(e).with_context(|| format!("无法创建 {}", lock.display())),
+            }
+        }
+    }
+}
+
+impl Drop for ProfileLock {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.0).ok();
+    }
+}
+
+/// 加锁后读取、修改、写回；内容没有变化时不写，返回是否修改了文件
+fn edit(path: &Path, f: impl FnOnce(&str) -> String) -> Result<bool> {
+    let _lock = ProfileLock::acquire(path)?;
+    let (bom, body) = read(path)?;
+    let updated = f(&body);
+    if updated == body {
+        return Ok(false);
+    }
+    write(path, bom, &updated)?;
+    Ok(true)
+}
+
+/// 把区块写入 profile（不存在时创建），返回是否修改了文件
+pub fn write_block(path: &Path, integration: &ShellIntegration) -> Result<bool> {
+    edit(path, |body| upsert(body, integration))
+}
+
+/// 从 profile 中删除区块，profile 不存在或没有区块时返回 false
+pub fn remove_block(path: &Path, integration: &ShellIntegration) -> Result<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    edit(path, |body| remove(body, integration))
+}
+
+/// 当前用户的 Windows PowerShell profile 路径（$PROFILE），
+/// 用户名含中文时依赖 UTF-8 输出才能正确解码
+#[cfg(windows)]
+pub fn profile_path() -> Result<PathBuf> {
+    let output = crate::proc::powershell("$PROFILE")
+        .output()
+        .context("无法获取 PowerShell profile 路径")?;
+    let path = crate::proc::decode_output(&output.stdout).trim().to_string();
+    if path.is_empty() {
+        anyhow::bail!("PowerShell $PROFILE 路径为空");
+    }
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fnm(script: &str) -> ShellIntegration {
+        ShellIntegration {
+            name: "nodejs",
+            script: script.to_string(),
+            legacy_comment: Some("# fnm (Node.js version manager)"),
+        }
+    }
+
+    const BLOCK: &str = "# >>> hudo nodejs >>>\r\nfnm env | iex\r\n# <<< hudo nodejs <<<\r\n";
+
+    #[test]
+    fn test_upsert_empty_and_existing() {
+        assert_eq!(upsert("", &fnm("fnm env | iex")), BLOCK);
+
+        // 原有内容保留，区块前空一行；LF 文件继续用 LF
+        let content = "Set-Alias ll ls\n";
+        let written = upsert(content, &fnm("fnm env | iex"));
+        assert_eq!(written, "Set-Alias ll ls\n\n# >>> hudo nodejs >>>\nfnm env | iex\n# <<< hudo nodejs <<<\n");
+
+        // 幂等：再次写入内容不变，移除后恢复原样
+        assert_eq!(upsert(&written, &fnm("fnm env | iex")), written);
+        assert_eq!(remove(&written, &fnm("")), content);
+    }
+
+    #[test]
+    fn test_upsert_replaces_in_place() {
+        let content = format!("Import-Module posh-git\r\n\r\n{}Set-Alias ll ls\r\n", BLOCK);
+        let written = upsert(&content, &fnm("& 'D:\\hudo\\tools\\fnm\\fnm.exe' env | iex"));
+        assert_eq!(
+            written,
+            "Import-Module posh-git\r\n\r\n# >>> hudo nodejs >>>\r\n& 'D:\\hudo\\tools\\fnm\\fnm.exe' env | iex\r\n# <<< hudo nodejs <<<\r\nSet-Alias ll ls\r\n"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_blocks_merged() {
+        let content = format!("{}\r\nSet-Alias ll ls\r\n\r\n{}", BLOCK, BLOCK);
+        let written = upsert(&content, &fnm("fnm env --use-on-cd | iex"));
+        assert_eq!(written.matches("# >>> hudo nodejs >>>").count(), 1);
+        assert!(written.starts_with("# >>> hudo nodejs >>>\r\nfnm env --use-on-cd | iex\r\n"));
+        assert!(written.ends_with("Set-Alias ll ls\r\n"));
+        assert_eq!(remove(&content, &fnm("")), "Set-Alias ll ls\r\n");
+    }
+
+    #[test]
+    fn test_missing_trailing_newline() {
+        let written = upsert("Set-Alias ll ls", &fnm("fnm env | iex"));
+        assert_eq!(written, format!("Set-Alias ll ls\r\n\r\n{}", BLOCK));
+        assert_eq!(remove(&written, &fnm("")), "Set-Alias ll ls\r\n");
+
+        // 区块的结束行没有换行符
+        let content = format!("Set-Alias ll ls\r\n\r\n{}", BLOCK.trim_end());
+        assert_eq!(remove(&content, &fnm("")), "Set-Alias ll ls\r\n");
+    }
+
+    #[test]
+    fn test_other_blocks_and_unclosed_marker_kept() {
+        let conda = ShellIntegration { name: "miniconda", script: "conda hook".to_string(), legacy_comment: None };
+        let content = upsert(&upsert("", &fnm("fnm env | iex")), &conda);
+        assert!(contains(&content, "nodejs") && contains(&content, "miniconda"));
+        let removed = remove(&content, &fnm(""));
+        assert!(removed.starts_with("# >>> hudo miniconda >>>"), "{}", removed);
+        assert!(!contains(&removed, "nodejs") && contains(&removed, "miniconda"));
+
+        // 没有结束标记的开始行不当作区块，之后的用户内容不会被删
+        let content = "# >>> hudo nodejs >>>\nSet-Alias ll ls\n";
+        assert_eq!(remove(content, &fnm("")), content);
+        let written = upsert(content, &fnm("fnm env | iex"));
+        assert!(written.starts_with(content));
+        assert_eq!(remove(&written, &fnm("")), content);
+    }
+
+    #[test]
+    fn test_legacy_lines_migrated() {
+        let content = "Set-Alias ll ls\n\r\n# fnm (Node.js version manager)\r\n& 'D:\\hudo\\tools\\fnm\\fnm.exe' env --use-on-cd --shell power-shell | Out-String | Invoke-Expression\n";
+        let written = upsert(content, &fnm("fnm env | iex"));
+        assert!(!written.contains("# fnm (Node.js version manager)"));
+        assert_eq!(written.matches("env").count(), 1);
+        assert!(written.starts_with("Set-Alias ll ls\n\r\n# >>> hudo nodejs >>>"));
+        assert_eq!(remove(content, &fnm("")), "Set-Alias ll ls\n");
+    }
+
+    #[test]
+    fn test_write_block_file() {
+        let dir = std::env::temp_dir().join(format!("hudo-test-张 三-psprofile-{}", std::process::id()));
+        let profile = dir.join("WindowsPowerShell").join("profile.ps1");
+
+        // 不存在时创建；ASCII 内容不加 BOM
+        assert!(write_block(&profile, &fnm("fnm env | iex")).unwrap());
+        assert_eq!(std::fs::read_to_string(&profile).unwrap(), BLOCK);
+        assert!(!write_block(&profile, &fnm("fnm env | iex")).unwrap());
+
+        // 含中文时加 BOM，原有 BOM 保留
+        assert!(write_block(&profile, &fnm(&format!("& '{}' env | iex", dir.display()))).unwrap());
+        assert!(std::fs::read_to_string(&profile).unwrap().starts_with(BOM));
+        std::fs::write(&profile, format!("{}Set-Alias ll ls\r\n", BOM)).unwrap();
+        assert!(write_block(&profile, &fnm("fnm env | iex")).unwrap());
+        assert!(remove_block(&profile, &fnm("")).unwrap());
+        assert_eq!(std::fs::read_to_string(&profile).unwrap(), format!("{}Set-Alias ll ls\r\n", BOM));
+        assert!(!remove_block(&profile, &fnm("")).unwrap());
+        assert!(!sibling(&profile, "hudo-lock").exists() && !sibling(&profile, "hudo-tmp").exists());
+
+        // 非 UTF-8（如按 GBK 保存）的 profile 不改写
+        std::fs::write(&profile, [0xd5, 0xc5, 0xc8, 0xfd]).unwrap();
+        assert!(write_block(&profile, &fnm("fnm env | iex")).is_err());
+        assert_eq!(std::fs::read(&profile).unwrap(), [0xd5, 0xc5, 0xc8, 0xfd]);
+
+        assert!(!remove_block(&dir.join("missing.ps1"), &fnm("")).unwrap());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_concurrent_writers() {
+        let dir = std::env::temp_dir().join(format!("hudo-test-psprofile-concurrent-{}", std::process::id()));
+        let profile = dir.join("profile.ps1");
+        let names = ["a", "b", "c", "d", "e", "f", "g", "h"];
+        std::thread::scope(|s| {
+            for name in names {
+                let profile = &profile;
+                s.spawn(move || {
+                    let integration = ShellIntegration { name, script: format!("# {}", name), legacy_comment: None };
+                    write_block(profile, &integration).unwrap();
+                });
+            }
+        });
+        let content = std::fs::read_to_string(&profile).unwrap();
+        for name in names {
+            assert!(contains(&content, name), "{}", content);
+        }
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -9,12 +9,121 @@ pub struct ToolState {
     pub version: String,
     pub install_path: String,
     pub installed_at: String,
+    /// 安装方式（如 Git 的 installer / portable），无区分的工具不记录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_mode: Option<String>,
+    /// 作为哪些工具的依赖被自动安装（如 JDK 随 Maven 安装），为空表示用户主动安装
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub installed_as_dependency_of: Vec<String>,
+    /// 已固定（hudo pin）：导入档案时不切换版本
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+    /// 安装时选择暂不应用环境变量，待 hudo env apply 应用
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub env_pending: bool,
+    /// 从「最新版」地址安装的工具（uv、Bun 等）下载时的 ETag / Last-Modified，重新安装前据此判断是否有更新
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact: Option<crate::download::Validator>,
+    /// 安装时主程序的大小与修改时间，快速检测时据此发现工具自更新或被替换
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<Fingerprint>,
 }
 
+/// 主程序文件的大小与修改时间（Unix 秒）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+    pub size: u64,
+    pub mtime: u64,
+}
+
+impl Fingerprint {
+    /// 读取文件的指纹，文件不存在或无法读取修改时间时为 None
+    pub fn of(path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        let mtime = meta.modified().ok()?.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+        Some(Self { size: meta.len(), mtime })
+    }
+}
+
+/// 系统中已有（非 hudo 安装）的工具如何处理
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalPolicy {
+    /// 每次询问是否由 hudo 接管
+    Ask,
+    /// 保留现有版本，不再询问
+    Ignore,
+    /// 直接由 hudo 接管（清理旧版并重新安装）
+    Takeover,
+}
+
+/// 用户对某个系统已有工具做出的选择
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExternalDecision {
+    pub policy: ExternalPolicy,
+    pub decided_at: String,
+}
+
+/// state.json 的当前格式版本；修改格式时加一，并在 MIGRATIONS 末尾追加对应的迁移
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// 按顺序排列的迁移：第 i 项把 schema i 的 JSON 升级为 schema i + 1
+const MIGRATIONS: &[fn(&mut serde_json::Value)] = &[
+    // 0 → 1：加入 schema 字段之前的文件，内容与 1 相同
+    |_| {},
+];
+
+const _: () = assert!(MIGRATIONS.len() == SCHEMA_VERSION as usize);
+
 /// 所有工具的安装状态（保存在 state.json）
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct InstallRegistry {
+    /// 格式版本，缺失（早期文件）视为 0
+    #[serde(default)]
+    pub schema: u32,
     pub tools: HashMap<String, ToolState>,
+    /// 系统已有工具的接管选择，未记录的工具按 Ask 处理
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub external: HashMap<String, ExternalDecision>,
+    /// 卸载时因文件被占用未能删除的路径，下次运行 hudo 时重试
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pending_deletes: Vec<String>,
+    /// 接管系统已有工具时移除的环境变量与 PATH 项（最近的在后），供 hudo env restore 恢复
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_history: Vec<EnvRemoval>,
+    /// hudo 添加的 Windows Defender 排除路径，卸载 hudo 自身时移除
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub defender_exclusions: Vec<String>,
+}
+
+/// 一次接管清理中移除的用户环境变量与 PATH 项（以及提权移除的系统 PATH 项）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EnvRemoval {
+    pub tool: String,
+    pub removed_at: String,
+    /// (名称, 移除前的值)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vars: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub path: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub system_path: Vec<String>,
+}
+
+/// env_history 最多保留的记录数
+const ENV_HISTORY_LIMIT: usize = 20;
+
+impl Default for InstallRegistry {
+    fn default() -> Self {
+        Self {
+            schema: SCHEMA_VERSION,
+            tools: HashMap::new(),
+            external: HashMap::new(),
+            pending_deletes: Vec::new(),
+            env_history: Vec::new(),
+            defender_exclusions: Vec::new(),
+        }
+    }
 }
 
 impl InstallRegistry {
@@ -25,17 +134,63 @@ impl InstallRegistry {
         }
         let content = std::fs::read_to_string(state_path)
             .with_context(|| format!("无法读取状态文件: {}", state_path.display()))?;
-        match serde_json::from_str::<InstallRegistry>(&content) {
-            Ok(registry) => Ok(registry),
-            Err(_) => {
-                eprintln!(
-                    "  {} 状态文件损坏，已重置: {}",
-                    console::style("⚠").yellow(),
-                    state_path.display()
-                );
-                Ok(Self::default())
+        let value = serde_json::from_str::<serde_json::Value>(&content);
+        let schema = value.as_ref().map(schema_of).unwrap_or(0);
+        if schema > SCHEMA_VERSION {
+            anyhow::bail!(
+                "状态文件由更新版本的 hudo 写入（格式版本 {}，当前支持 {}），请升级 hudo: {}",
+                schema,
+                SCHEMA_VERSION,
+                state_path.display()
+            );
+        }
+
+        match value.map_err(anyhow::Error::from).and_then(|v| Self::migrate(v, schema)) {
+            Ok(registry) if schema == SCHEMA_VERSION => Ok(registry),
+            Ok(registry) => {
+                // 迁移前先备份原文件，再写回新格式
+                backup(state_path)?;
+                registry.save(state_path)?;
+                Ok(registry)
+            }
+            Err(e) => Self::reset_unreadable(state_path, e),
+        }
+    }
+
+    /// 依次执行 schema 之后的迁移，再按当前格式解析
+    fn migrate(mut value: serde_json::Value, schema: u32) -> Result<Self> {
+        for step in &MIGRATIONS[schema as usize..] {
+            step(&mut value);
+        }
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert("schema".to_string(), SCHEMA_VERSION.into());
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// 无法解析的状态文件：先备份，交互时询问是否重置（否则报错退出），非交互时直接重置
+    fn reset_unreadable(state_path: &Path, err: anyhow::Error) -> Result<Self> {
+        let saved = backup(state_path)?;
+        eprintln!(
+            "  {} 状态文件无法解析（{}），原文件已备份到 {}",
+            console::style(crate::ui::Glyph::Warning).yellow(),
+            err,
+            saved.display()
+        );
+        if console::user_attended() {
+            let reset = dialoguer::Confirm::new()
+                .with_prompt("  重置为空的安装记录？（已安装的文件不受影响，选否则退出以便手动修复）")
+                .default(false)
+                .interact()
+                .unwrap_or(false);
+            if !reset {
+                anyhow::bail!("状态文件无法解析，请修复或删除后重试: {}", state_path.display());
             }
         }
+        let registry = Self::default();
+        registry.save(state_path)?;
+        eprintln!("  {} 已重置状态文件: {}", console::style(crate::ui::Glyph::Warning).yellow(), state_path.display());
+        Ok(registry)
     }
 
     /// 保存到 state.json
@@ -49,29 +204,258 @@ impl InstallRegistry {
         Ok(())
     }
 
-    /// 记录工具安装状态
+    /// 记录工具安装状态（重装时保留固定标记）
     pub fn mark_installed(&mut self, tool_id: &str, version: &str, install_path: &str) {
         let now = current_timestamp();
+        let pinned = self.is_pinned(tool_id);
         self.tools.insert(
             tool_id.to_string(),
             ToolState {
                 version: version.to_string(),
                 install_path: install_path.to_string(),
                 installed_at: now,
+                install_mode: None,
+                installed_as_dependency_of: Vec::new(),
+                pinned,
+                env_pending: false,
+                artifact: None,
+                fingerprint: None,
             },
         );
     }
 
+    /// 记录作为依赖自动安装的工具（如 Maven 触发安装的 JDK）
+    pub fn mark_installed_as_dependency(
+        &mut self,
+        tool_id: &str,
+        version: &str,
+        install_path: &str,
+        dependent_id: &str,
+    ) {
+        self.mark_installed(tool_id, version, install_path);
+        if let Some(state) = self.tools.get_mut(tool_id) {
+            state.installed_as_dependency_of.push(dependent_id.to_string());
+        }
+    }
+
+    /// 依赖已存在时追加一个依赖方；用户主动安装的工具（依赖列表为空）不记录，返回是否追加
+    pub fn add_dependent(&mut self, tool_id: &str, dependent_id: &str) -> bool {
+        let Some(state) = self.tools.get_mut(tool_id) else {
+            return false;
+        };
+        let deps = &mut state.installed_as_dependency_of;
+        if deps.is_empty() || deps.iter().any(|d| d == dependent_id) {
+            return false;
+        }
+        deps.push(dependent_id.to_string());
+        true
+    }
+
+    /// 用户主动安装了该工具，清除依赖方记录，返回是否有变化
+    pub fn mark_explicit(&mut self, tool_id: &str) -> bool {
+        match self.tools.get_mut(tool_id) {
+            Some(state) if !state.installed_as_dependency_of.is_empty() => {
+                state.installed_as_dependency_of.clear();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 工具被卸载后，从各依赖的依赖方列表中移除它；
+    /// 返回因此不再被任何工具依赖的依赖 id（可提示用户一并卸载）
+    pub fn remove_dependent(&mut self, dependent_id: &str) -> Vec<String> {
+        let mut orphans = Vec::new();
+        for (id, state) in self.tools.iter_mut() {
+            let deps = &mut state.installed_as_dependency_of;
+            let before = deps.len();
+            deps.retain(|d| d != dependent_id);
+            if before > 0 && deps.is_empty() {
+                orphans.push(id.clone());
+            }
+        }
+        orphans.sort();
+        orphans
+    }
+
+    /// 记录工具的安装方式（需在 mark_installed 之后调用）
+    pub fn set_install_mode(&mut self, tool_id: &str, mode: &str) {
+        if let Some(state) = self.tools.get_mut(tool_id) {
+            state.install_mode = Some(mode.to_string());
+        }
+    }
+
+    pub fn set_artifact(&mut self, tool_id: &str, artifact: Option<crate::download::Validator>) {
+        if let Some(state) = self.tools.get_mut(tool_id) {
+            state.artifact = artifact;
+        }
+    }
+
+    pub fn set_fingerprint(&mut self, tool_id: &str, fingerprint: Option<Fingerprint>) {
+        if let Some(state) = self.tools.get_mut(tool_id) {
+            state.fingerprint = fingerprint;
+        }
+    }
+
+    /// 按探测到的版本（检测命令的输出，如 "git version 2.47.1.windows.2"）更新记录，
+    /// 记录为规范化后的版本并返回旧版本；只是写法不同（记录为 "2.47.1.2"）不算变化
+    pub fn update_version(&mut self, tool_id: &str, detected: &str) -> Option<String> {
+        let state = self.tools.get_mut(tool_id)?;
+        if crate::version::versions_match(detected, &state.version) {
+            return None;
+        }
+        let version = crate::version::normalize_version(detected).unwrap_or_else(|| detected.trim().to_string());
+        Some(std::mem::replace(&mut state.version, version))
+    }
+
+    /// 安装目录整体迁移后（hudo config migrate-root），把位于 old 之下的安装路径与待删除路径改到 new 之下
+    pub fn rebase_paths(&mut self, old: &Path, new: &Path) {
+        let rebase = |path: &mut String| {
+            if let Ok(rel) = Path::new(path.as_str()).strip_prefix(old) {
+                *path = new.join(rel).to_string_lossy().to_string();
+            }
+        };
+        for state in self.tools.values_mut() {
+            rebase(&mut state.install_path);
+        }
+        self.pending_deletes.iter_mut().for_each(rebase);
+    }
+
     /// 查询工具是否已安装
     #[allow(dead_code)]
     pub fn get(&self, tool_id: &str) -> Option<&ToolState> {
         self.tools.get(tool_id)
     }
 
+    /// 工具是否已固定
+    pub fn is_pinned(&self, tool_id: &str) -> bool {
+        self.tools.get(tool_id).is_some_and(|s| s.pinned)
+    }
+
+    /// 设置固定标记，工具未由 hudo 安装时返回 false
+    pub fn set_pinned(&mut self, tool_id: &str, pinned: bool) -> bool {
+        match self.tools.get_mut(tool_id) {
+            Some(state) => {
+                state.pinned = pinned;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 标记环境变量是否待应用（需在 mark_installed 之后调用）
+    pub fn set_env_pending(&mut self, tool_id: &str, pending: bool) {
+        if let Some(state) = self.tools.get_mut(tool_id) {
+            state.env_pending = pending;
+        }
+    }
+
+    /// 环境变量待应用的工具 id（按字母排序）
+    pub fn env_pending(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .tools
+            .iter()
+            .filter(|(_, s)| s.env_pending)
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+
     /// 移除工具安装记录
     pub fn remove(&mut self, tool_id: &str) {
         self.tools.remove(tool_id);
     }
+
+    /// 记录一次接管清理，超出上限时丢弃最早的记录
+    pub fn record_env_removal(&mut self, tool_id: &str, vars: Vec<(String, String)>, path: Vec<String>) {
+        self.env_history.push(EnvRemoval {
+            tool: tool_id.to_string(),
+            removed_at: current_timestamp(),
+            vars,
+            path,
+            system_path: Vec::new(),
+        });
+        let excess = self.env_history.len().saturating_sub(ENV_HISTORY_LIMIT);
+        self.env_history.drain(..excess);
+    }
+
+    /// 把提权移除的系统 PATH 项记入该工具最近一次清理记录，没有记录时新建
+    pub fn record_system_path_removal(&mut self, tool_id: &str, entries: Vec<String>) {
+        if entries.is_empty() {
+            return;
+        }
+        match self.env_history.last_mut().filter(|r| r.tool == tool_id) {
+            Some(last) => last.system_path.extend(entries),
+            None => {
+                self.record_env_removal(tool_id, Vec::new(), Vec::new());
+                self.record_system_path_removal(tool_id, entries);
+            }
+        }
+    }
+
+    /// 取出最近一次（指定工具时为该工具最近一次）清理记录
+    pub fn take_env_removal(&mut self, tool_id: Option<&str>) -> Option<EnvRemoval> {
+        let index = self
+            .env_history
+            .iter()
+            .rposition(|r| tool_id.is_none_or(|t| r.tool == t))?;
+        Some(self.env_history.remove(index))
+    }
+
+    /// 记录已添加的 Defender 排除路径（不重复）
+    pub fn add_defender_exclusions(&mut self, paths: &[String]) {
+        for path in paths {
+            if !self.defender_exclusions.iter().any(|p| p.eq_ignore_ascii_case(path)) {
+                self.defender_exclusions.push(path.clone());
+            }
+        }
+    }
+
+    /// 记录未能删除的路径，留待下次运行时清理
+    pub fn add_pending_delete(&mut self, path: &Path) {
+        let path = path.to_string_lossy().to_string();
+        if !self.pending_deletes.contains(&path) {
+            self.pending_deletes.push(path);
+        }
+    }
+
+    /// 重试删除遗留路径，返回本次清理掉的路径（仍被占用的留在列表中）
+    pub fn retry_pending_deletes(&mut self) -> Vec<String> {
+        let mut cleaned = Vec::new();
+        self.pending_deletes.retain(|p| {
+            let path = Path::new(p);
+            let removed = match path.is_dir() {
+                true => std::fs::remove_dir_all(path).is_ok(),
+                false => std::fs::remove_file(path).is_ok() || !path.exists(),
+            };
+            if removed {
+                cleaned.push(p.clone());
+            }
+            !removed
+        });
+        cleaned
+    }
+
+    /// 系统已有工具的接管策略，未记录时为 Ask
+    pub fn external_policy(&self, tool_id: &str) -> ExternalPolicy {
+        self.external
+            .get(tool_id)
+            .map(|d| d.policy)
+            .unwrap_or(ExternalPolicy::Ask)
+    }
+
+    /// 记录接管策略；设为 Ask 时删除记录
+    pub fn set_external_policy(&mut self, tool_id: &str, policy: ExternalPolicy) {
+        if policy == ExternalPolicy::Ask {
+            self.external.remove(tool_id);
+        } else {
+            self.external.insert(
+                tool_id.to_string(),
+                ExternalDecision { policy, decided_at: current_timestamp() },
+            );
+        }
+    }
 }
 
 /// 可读的本地时间戳（跨平台，不依赖 chrono）
@@ -82,11 +466,15 @@ pub fn current_timestamp() -> String {
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_secs();
+    format_timestamp(now)
+}
 
+/// Unix 秒 → "2026-01-02 03:04:05"（UTC）
+pub fn format_timestamp(secs: u64) -> String {
     // 手动计算 UTC 时间（不含时区偏移，但够用于记录用途）
     let secs_per_day = 86400u64;
-    let days = now / secs_per_day;
-    let day_secs = now % secs_per_day;
+    let days = secs / secs_per_day;
+    let day_secs = secs % secs_per_day;
     let hour = day_secs / 3600;
     let minute = (day_secs % 3600) / 60;
     let second = day_secs % 60;
@@ -100,6 +488,21 @@ pub fn current_timestamp() -> String {
     )
 }
 
+/// JSON 中的 schema 字段，缺失或不是数字时视为 0
+fn schema_of(value: &serde_json::Value) -> u32 {
+    value["schema"].as_u64().map_or(0, |v| u32::try_from(v).unwrap_or(u32::MAX))
+}
+
+/// 复制一份带时间戳的备份（state.json → state.json.2026-01-02_03-04-05.bak），返回备份路径
+fn backup(state_path: &Path) -> Result<std::path::PathBuf> {
+    let stamp = current_timestamp().replace(' ', "_").replace(':', "-");
+    let name = state_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let target = state_path.with_file_name(format!("{}.{}.bak", name, stamp));
+    std::fs::copy(state_path, &target)
+        .with_context(|| format!("无法备份状态文件: {}", target.display()))?;
+    Ok(target)
+}
+
 /// 将 Unix epoch 天数转换为 (年, 月, 日)
 fn days_to_ymd(days: u64) -> (u64, u64, u64) {
     // 基于 civil_from_days 算法（Howard Hinnant）
@@ -115,3 +518,157 @@ fn days_to_ymd(days: u64) -> (u64, u64, u64) {
     let y = if m <= 2 { y + 1 } else { y };
     (y, m, d)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jdk_installed_for_maven() {
+        // ensure_jdk：Maven 安装时自动装 JDK，随后 Gradle 复用
+        let mut reg = InstallRegistry::default();
+        reg.mark_installed("maven", "3.9.9", "D:\\hudo\\tools\\maven");
+        reg.mark_installed_as_dependency("jdk", "21", "D:\\hudo\\lang\\java", "maven");
+        assert_eq!(reg.get("jdk").unwrap().installed_as_dependency_of, ["maven"]);
+
+        assert!(reg.add_dependent("jdk", "gradle"));
+        assert!(!reg.add_dependent("jdk", "gradle"));
+        assert_eq!(
+            reg.get("jdk").unwrap().installed_as_dependency_of,
+            ["maven", "gradle"]
+        );
+
+        // 卸载 Maven 后 Gradle 仍依赖 JDK，卸载 Gradle 后 JDK 成为孤立依赖
+        reg.remove("maven");
+        assert!(reg.remove_dependent("maven").is_empty());
+        reg.remove("gradle");
+        assert_eq!(reg.remove_dependent("gradle"), ["jdk"]);
+    }
+
+    #[test]
+    fn test_mingw_installed_for_rust() {
+        // ensure_gcc：Rust 安装时自动装 MinGW（工具 id 为 c）
+        let mut reg = InstallRegistry::default();
+        reg.mark_installed_as_dependency("c", "14.2.0", "D:\\hudo\\lang\\mingw", "rust");
+        reg.mark_installed("rust", "stable", "D:\\hudo\\lang\\rust");
+
+        reg.remove("rust");
+        assert_eq!(reg.remove_dependent("rust"), ["c"]);
+        assert!(reg.get("c").unwrap().installed_as_dependency_of.is_empty());
+    }
+
+    #[test]
+    fn test_explicit_install_not_tracked() {
+        // 用户主动安装的依赖不记录依赖方，卸载依赖方时也不会提示移除
+        let mut reg = InstallRegistry::default();
+        reg.mark_installed("jdk", "21", "D:\\hudo\\lang\\java");
+        assert!(!reg.add_dependent("jdk", "maven"));
+        assert!(reg.remove_dependent("maven").is_empty());
+
+        // 依赖被主动重装后视为用户安装
+        reg.mark_installed_as_dependency("c", "14.2.0", "D:\\hudo\\lang\\mingw", "rust");
+        reg.mark_installed("c", "14.2.0", "D:\\hudo\\lang\\mingw");
+        assert!(reg.remove_dependent("rust").is_empty());
+
+        // 已安装的依赖被 hudo install 主动安装时同样清除记录
+        reg.mark_installed_as_dependency("jdk", "21", "D:\\hudo\\lang\\java", "maven");
+        assert!(reg.mark_explicit("jdk"));
+        assert!(!reg.mark_explicit("jdk"));
+        assert!(reg.remove_dependent("maven").is_empty());
+    }
+
+    #[test]
+    fn test_external_policy() {
+        let mut reg = InstallRegistry::default();
+        assert_eq!(reg.external_policy("git"), ExternalPolicy::Ask);
+
+        reg.set_external_policy("git", ExternalPolicy::Ignore);
+        assert_eq!(reg.external_policy("git"), ExternalPolicy::Ignore);
+        let json = serde_json::to_string(&reg).unwrap();
+        assert!(json.contains("\"ignore\""));
+        let loaded: InstallRegistry = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.external_policy("git"), ExternalPolicy::Ignore);
+
+        // 改回 Ask 即删除记录，旧版 state.json（无 external 字段）照常加载
+        reg.set_external_policy("git", ExternalPolicy::Ask);
+        assert!(reg.external.is_empty());
+        let old: InstallRegistry = serde_json::from_str(r#"{"tools":{}}"#).unwrap();
+        assert_eq!(old.external_policy("git"), ExternalPolicy::Ask);
+    }
+
+    #[test]
+    fn test_update_version() {
+        let mut reg = InstallRegistry::default();
+        reg.mark_installed("gh", "2.60.0", "D:\\hudo\\tools\\gh");
+        assert_eq!(reg.update_version("gh", "2.60.0"), None);
+        assert_eq!(reg.update_version("gh", "2.61.0").as_deref(), Some("2.60.0"));
+        assert_eq!(reg.get("gh").unwrap().version, "2.61.0");
+        assert_eq!(reg.update_version("git", "2.47.0"), None);
+    }
+
+    #[test]
+    fn test_update_version_detect_output() {
+        let mut reg = InstallRegistry::default();
+        reg.mark_installed("git", "2.47.1.2", "D:\\hudo\\tools\\git");
+        // git --version 的原始输出与记录是同一版本
+        assert_eq!(reg.update_version("git", "git version 2.47.1.windows.2"), None);
+        assert_eq!(reg.get("git").unwrap().version, "2.47.1.2");
+        // 自更新后记录规范化的版本
+        assert_eq!(reg.update_version("git", "git version 2.48.1.windows.1").as_deref(), Some("2.47.1.2"));
+        assert_eq!(reg.get("git").unwrap().version, "2.48.1.1");
+        assert_eq!(reg.update_version("git", "git version 2.48.1.windows.1"), None);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let dir = std::env::temp_dir().join(format!("hudo-fingerprint-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let exe = dir.join("tool.exe");
+        std::fs::write(&exe, b"v1").unwrap();
+        let before = Fingerprint::of(&exe).unwrap();
+        assert_eq!(before.size, 2);
+        assert_eq!(Fingerprint::of(&exe), Some(before.clone()));
+        std::fs::write(&exe, b"v2 longer").unwrap();
+        assert_ne!(Fingerprint::of(&exe), Some(before));
+        assert_eq!(Fingerprint::of(&dir.join("missing.exe")), None);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_rebase_paths() {
+        let old = Path::new("old-root");
+        let new = Path::new("new-root");
+        let mut reg = InstallRegistry::default();
+        reg.mark_installed("git", "2.47.1", &old.join("tools").join("git").to_string_lossy());
+        // 不在旧目录下的路径保持不变
+        reg.mark_installed("uv", "0.5.0", &Path::new("elsewhere").join("uv").to_string_lossy());
+        reg.add_pending_delete(&old.join("tools").join("redis"));
+        reg.rebase_paths(old, new);
+        assert_eq!(reg.get("git").unwrap().install_path, new.join("tools").join("git").to_string_lossy());
+        assert_eq!(reg.get("uv").unwrap().install_path, Path::new("elsewhere").join("uv").to_string_lossy());
+        assert_eq!(reg.pending_deletes, vec![new.join("tools").join("redis").to_string_lossy().to_string()]);
+    }
+
+    #[test]
+    fn test_pinned() {
+        let mut reg = InstallRegistry::default();
+        assert!(!reg.set_pinned("mysql", true));
+        reg.mark_installed("mysql", "8.0.40", "D:\\hudo\\lang\\mysql");
+        assert!(reg.set_pinned("mysql", true));
+
+        // 重装后仍保持固定
+        reg.mark_installed("mysql", "8.0.41", "D:\\hudo\\lang\\mysql");
+        assert!(reg.is_pinned("mysql"));
+
+        // 未固定时不写入 pinned 字段，旧版 state.json（无 pinned 字段）照常加载
+        let json = serde_json::to_string(&reg).unwrap();
+        assert!(json.contains("\"pinned\":true"));
+        reg.set_pinned("mysql", false);
+        assert!(!serde_json::to_string(&reg).unwrap().contains("pinned"));
+        let old: InstallRegistry = serde_json::from_str(
+            r#"{"tools":{"go":{"version":"1.24.0","install_path":"D:\\hudo\\lang\\go","installed_at":"2025-01-01 00:00:00"}}}"#,
+        )
+        .unwrap();
+        assert!(!old.is_pinned("go"));
+    }
+}
@@ -0,0 +1,119 @@
+//! 安装过程的输出事件
+//!
+//! 安装器不直接打印，而是通过 `InstallContext::reporter` 发出事件：
+//! 控制台实现保持原有样式，JSON 实现每个事件输出一行，测试中用 `Collector` 收集后断言。
+
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// 一条输出事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", content = "message", rename_all = "snake_case")]
+pub enum Event {
+    /// 标题（如「配置 Git」）
+    Title(String),
+    /// 正在进行的操作
+    Action(String),
+    /// 说明信息
+    Info(String),
+    Warning(String),
+    Success(String),
+}
+
+/// 事件的接收方
+pub trait Reporter: Send + Sync {
+    fn report(&self, event: Event);
+
+    fn title(&self, text: &str) {
+        self.report(Event::Title(text.to_string()));
+    }
+
+    fn action(&self, text: &str) {
+        self.report(Event::Action(text.to_string()));
+    }
+
+    fn info(&self, text: &str) {
+        self.report(Event::Info(text.to_string()));
+    }
+
+    fn warning(&self, text: &str) {
+        self.report(Event::Warning(text.to_string()));
+    }
+
+    fn success(&self, text: &str) {
+        self.report(Event::Success(text.to_string()));
+    }
+}
+
+/// 输出到终端，样式与 `ui::print_*` 相同
+pub struct Console;
+
+impl Reporter for Console {
+    fn report(&self, event: Event) {
+        match event {
+            Event::Title(text) => crate::ui::print_title(&text),
+            Event::Action(text) => crate::ui::print_action(&text),
+            Event::Info(text) => crate::ui::print_info(&text),
+            Event::Warning(text) => crate::ui::print_warning(&text),
+            Event::Success(text) => crate::ui::print_success(&text),
+        }
+    }
+}
+
+/// 每个事件输出一行 JSON（如 `{"event":"action","message":"解压 Go..."}`），供脚本或日志使用
+pub struct JsonLines;
+
+impl JsonLines {
+    pub fn line(event: &Event) -> String {
+        serde_json::to_string(event).unwrap_or_default()
+    }
+}
+
+impl Reporter for JsonLines {
+    fn report(&self, event: Event) {
+        println!("{}", Self::line(&event));
+    }
+}
+
+/// 收集全部事件，供测试断言
+#[derive(Default)]
+pub struct Collector {
+    events: Mutex<Vec<Event>>,
+}
+
+impl Collector {
+    pub fn events(&self) -> Vec<Event> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl Reporter for Collector {
+    fn report(&self, event: Event) {
+        self.events.lock().unwrap_or_else(|e| e.into_inner()).push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_line() {
+        assert_eq!(
+            JsonLines::line(&Event::Action("解压 Go...".to_string())),
+            r#"{"event":"action","message":"解压 Go..."}"#
+        );
+        assert_eq!(JsonLines::line(&Event::Warning("x".to_string())), r#"{"event":"warning","message":"x"}"#);
+    }
+
+    #[test]
+    fn test_collector() {
+        let collector = Collector::default();
+        collector.action("下载");
+        collector.success("完成");
+        assert_eq!(
+            collector.events(),
+            vec![Event::Action("下载".to_string()), Event::Success("完成".to_string())]
+        );
+    }
+}
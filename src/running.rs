@@ -0,0 +1,183 @@
+//! 查找安装目录下正在运行的进程
+//!
+//! VS Code、PyCharm、node 等运行时文件被占用，删除安装目录只会删掉一部分。
+//! 卸载工具、卸载 hudo 自身、hudo nuke 在删除前都先用这里列出并关闭相关进程。
+
+use anyhow::{Context, Result};
+use dialoguer::Select;
+use std::path::{Path, PathBuf};
+
+/// 正在运行的进程
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunningProcess {
+    pub pid: u32,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// 可执行文件位于任一目录之下的进程（不含 hudo 自身）
+pub fn find_under(dirs: &[&Path]) -> Vec<RunningProcess> {
+    let own = std::process::id();
+    list_processes()
+        .into_iter()
+        .filter(|p| p.pid != own && dirs.iter().any(|d| is_under(&p.path, d)))
+        .collect()
+}
+
+#[cfg(windows)]
+fn list_processes() -> Vec<RunningProcess> {
+    // 其他用户或提权进程的 Path 为空，无权结束它们，直接忽略
+    let script = "Get-Process | Where-Object Path | Select-Object Id, ProcessName, Path | ConvertTo-Json -Compress";
+    match crate::proc::powershell(script).output() {
+        Ok(out) if out.status.success() => parse_processes(&crate::proc::decode_output(&out.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(not(windows))]
+fn list_processes() -> Vec<RunningProcess> {
+    Vec::new()
+}
+
+/// 解析 `Get-Process | ConvertTo-Json` 的输出：只有一个进程时是对象而不是数组
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_processes(json: &str) -> Vec<RunningProcess> {
+    let value: serde_json::Value = match serde_json::from_str(json.trim()) {
+        Ok(v) => v,
+        Err(_) => return Vec::new(),
+    };
+    let items = match value {
+        serde_json::Value::Array(items) => items,
+        other => vec![other],
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            Some(RunningProcess {
+                pid: u32::try_from(item["Id"].as_u64()?).ok()?,
+                name: item["ProcessName"].as_str().unwrap_or_default().to_string(),
+                path: PathBuf::from(item["Path"].as_str()?),
+            })
+        })
+        .collect()
+}
+
+/// path 是否位于 dir 之内（Windows 路径不区分大小写，/ 与 \ 等价）
+pub fn is_under(path: &Path, dir: &Path) -> bool {
+    let normalize = |p: &Path| {
+        p.to_string_lossy()
+            .replace('/', "\\")
+            .trim_end_matches('\\')
+            .to_lowercase()
+    };
+    let (path, dir) = (normalize(path), normalize(dir));
+    !dir.is_empty() && (path == dir || path.starts_with(&format!("{}\\", dir)))
+}
+
+/// 删除目录前检查其中是否有程序在运行：列出后询问是否关闭（`yes` 时直接关闭）；
+/// 返回 false 表示用户选择取消
+pub fn ensure_closed(dirs: &[&Path], what: &str, yes: bool) -> Result<bool> {
+    let running = find_under(dirs);
+    if running.is_empty() {
+        return Ok(true);
+    }
+
+    crate::ui::print_warning(&format!("{} 中以下程序正在运行，文件被占用时无法删除干净:", what));
+    for p in &running {
+        println!(
+            "    {}  {}",
+            console::style(format!("{} (PID {})", p.name, p.pid)).bold(),
+            console::style(p.path.display()).dim()
+        );
+    }
+
+    if !yes {
+        let items = ["关闭这些程序后继续", "取消"];
+        let sel = Select::with_theme(&crate::ui::theme())
+            .items(&items)
+            .default(0)
+            .interact_opt()
+            .context("选择被取消")?;
+        if sel != Some(0) {
+            return Ok(false);
+        }
+    }
+
+    let left = close(&running, dirs);
+    if left.is_empty() {
+        crate::ui::print_success("已关闭");
+    } else {
+        let names: Vec<String> = left.iter().map(|p| format!("{} (PID {})", p.name, p.pid)).collect();
+        crate::ui::print_warning(&format!("以下程序未能关闭，将尽量删除其余文件: {}", names.join(", ")));
+    }
+    Ok(true)
+}
+
+/// 先正常关闭（taskkill，GUI 程序会收到关闭请求），等待几秒后强制结束仍在运行的进程；
+/// 返回最终仍未退出的进程
+fn close(procs: &[RunningProcess], dirs: &[&Path]) -> Vec<RunningProcess> {
+    taskkill(procs, false);
+    for _ in 0..10 {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        if still_running(procs, dirs).is_empty() {
+            return Vec::new();
+        }
+    }
+    taskkill(&still_running(procs, dirs), true);
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    still_running(procs, dirs)
+}
+
+fn still_running(procs: &[RunningProcess], dirs: &[&Path]) -> Vec<RunningProcess> {
+    find_under(dirs)
+        .into_iter()
+        .filter(|p| procs.iter().any(|o| o.pid == p.pid))
+        .collect()
+}
+
+fn taskkill(procs: &[RunningProcess], force: bool) {
+    if procs.is_empty() {
+        return;
+    }
+    let mut cmd = std::process::Command::new("taskkill");
+    if force {
+        cmd.arg("/F");
+    }
+    for p in procs {
+        cmd.args(["/PID", &p.pid.to_string()]);
+    }
+    let _ = cmd.output();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_processes() {
+        let list = parse_processes(
+            r#"[{"Id":1204,"ProcessName":"Code","Path":"D:\\hudo\\ide\\vscode\\Code.exe"},
+                {"Id":88,"ProcessName":"node","Path":"C:\\Users\\张 三\\node.exe"},
+                {"Id":4,"ProcessName":"System","Path":null}]"#,
+        );
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].pid, 1204);
+        assert_eq!(list[1].path, PathBuf::from(r"C:\Users\张 三\node.exe"));
+
+        // 只有一个进程时 ConvertTo-Json 输出单个对象
+        let single = parse_processes(r#"{"Id":7,"ProcessName":"gcc","Path":"D:\\hudo\\tools\\mingw64\\bin\\gcc.exe"}"#);
+        assert_eq!(single[0].name, "gcc");
+        assert!(parse_processes("").is_empty());
+    }
+
+    #[test]
+    fn test_is_under() {
+        let dir = Path::new(r"D:\hudo\ide\vscode");
+        assert!(is_under(Path::new(r"D:\HUDO\ide\VSCode\Code.exe"), dir));
+        assert!(is_under(Path::new("D:/hudo/ide/vscode/bin/code.cmd"), dir));
+        assert!(is_under(dir, Path::new(r"D:\hudo\ide\vscode\")));
+        // 同名前缀的兄弟目录不算
+        assert!(!is_under(Path::new(r"D:\hudo\ide\vscode-insiders\Code.exe"), dir));
+        assert!(!is_under(Path::new(r"D:\hudo\ide\vscode\Code.exe"), Path::new("")));
+    }
+}
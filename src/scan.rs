@@ -0,0 +1,391 @@
+use anyhow::{Context, Result};
+use winreg::enums::*;
+use winreg::RegKey;
+
+use crate::ui;
+
+// ── 已知工具的识别规则 ────────────────────────────────────────────────────────
+
+/// (工具 id, 注册表 DisplayName 规则, winget 包 Id)
+/// DisplayName 规则以 `*` 结尾表示前缀匹配，否则完全匹配（避免 "Git" 误中 GitHub Desktop 等）
+const KNOWN_TOOLS: &[(&str, &[&str], &[&str])] = &[
+    ("git", &["Git", "Git version *"], &["Git.Git"]),
+    ("gh", &["GitHub CLI"], &["GitHub.cli"]),
+    ("uv", &[], &["astral-sh.uv"]),
+    ("miniconda", &["Miniconda3 *"], &["Anaconda.Miniconda3"]),
+    ("nodejs", &["Node.js"], &["OpenJS.NodeJS", "OpenJS.NodeJS.LTS", "Schniz.fnm"]),
+    ("bun", &[], &["Oven-sh.Bun"]),
+    ("rust", &["Rust *"], &["Rustlang.Rustup"]),
+    ("go", &["Go Programming Language *"], &["GoLang.Go"]),
+    (
+        "jdk",
+        &["Eclipse Temurin JDK *", "Java(TM) SE Development Kit *", "Microsoft Build of OpenJDK *"],
+        &["EclipseAdoptium.Temurin.21.JDK", "EclipseAdoptium.Temurin.17.JDK", "Oracle.JDK.21", "Microsoft.OpenJDK.21"],
+    ),
+    ("c", &[], &["BrechtSanders.WinLibs.POSIX.UCRT"]),
+    ("mysql", &["MySQL Server *"], &["Oracle.MySQL"]),
+    ("pgsql", &["PostgreSQL *"], &["PostgreSQL.PostgreSQL"]),
+    ("redis", &["Redis", "Redis on Windows"], &["Redis.Redis"]),
+    ("sqlite", &[], &["SQLite.SQLite"]),
+    ("vscode", &["Microsoft Visual Studio Code", "Microsoft Visual Studio Code (User)"], &["Microsoft.VisualStudioCode"]),
+    ("pycharm", &["PyCharm Community Edition *"], &["JetBrains.PyCharm.Community"]),
+    ("chrome", &["Google Chrome"], &["Google.Chrome"]),
+];
+
+/// DisplayName 是否匹配某条规则
+fn display_matches(display: &str, patterns: &[&str]) -> bool {
+    patterns.iter().any(|p| match p.strip_suffix('*') {
+        Some(prefix) => display.starts_with(prefix),
+        None => display == *p,
+    })
+}
+
+// ── 注册表 Uninstall 扫描 ─────────────────────────────────────────────────────
+
+/// 注册表 Uninstall 项
+#[derive(Debug, Clone)]
+pub struct UninstallEntry {
+    pub display_name: String,
+    pub version: Option<String>,
+    pub uninstall_string: Option<String>,
+    pub quiet_uninstall_string: Option<String>,
+    /// 来源（如 "HKLM 64 位"）
+    pub hive: &'static str,
+}
+
+/// 枚举 HKLM/HKCU 的 64 位与 32 位视图下所有带 DisplayName 的 Uninstall 项
+pub fn registry_entries() -> Vec<UninstallEntry> {
+    const UNINSTALL: &str = "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Uninstall";
+    let views = [
+        (HKEY_LOCAL_MACHINE, KEY_WOW64_64KEY, "HKLM 64 位"),
+        (HKEY_LOCAL_MACHINE, KEY_WOW64_32KEY, "HKLM 32 位"),
+        (HKEY_CURRENT_USER, KEY_WOW64_64KEY, "HKCU 64 位"),
+        (HKEY_CURRENT_USER, KEY_WOW64_32KEY, "HKCU 32 位"),
+    ];
+
+    let mut entries = Vec::new();
+    for (hive, view, label) in views {
+        let Ok(root) = RegKey::predef(hive).open_subkey_with_flags(UNINSTALL, KEY_READ | view) else {
+            continue;
+        };
+        for name in root.enum_keys().filter_map(|k| k.ok()) {
+            let Ok(sub) = root.open_subkey_with_flags(&name, KEY_READ | view) else {
+                continue;
+            };
+            let Ok(display_name) = sub.get_value::<String, _>("DisplayName") else {
+                continue;
+            };
+            entries.push(UninstallEntry {
+                display_name,
+                version: sub.get_value("DisplayVersion").ok(),
+                uninstall_string: sub.get_value("UninstallString").ok(),
+                quiet_uninstall_string: sub.get_value("QuietUninstallString").ok(),
+                hive: label,
+            });
+        }
+    }
+    entries
+}
+
+/// 在注册表中查找某工具的 Uninstall 项
+pub fn find_uninstaller(tool_id: &str) -> Option<UninstallEntry> {
+    let (_, prefixes, _) = KNOWN_TOOLS.iter().find(|(id, _, _)| *id == tool_id)?;
+    registry_entries()
+        .into_iter()
+        .find(|e| display_matches(&e.display_name, prefixes))
+}
+
+// ── winget ───────────────────────────────────────────────────────────────────
+
+/// winget list 中匹配到的包：(工具 id, 包 Id, 版本)
+fn winget_matches() -> Vec<(&'static str, String, String)> {
+    let Ok(out) = std::process::Command::new("winget")
+        .args(["list", "--source", "winget", "--accept-source-agreements"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    parse_winget_list(&crate::proc::decode_output(&out.stdout))
+}
+
+/// 解析 winget list 输出：按包 Id 匹配（列名随系统语言变化，不依赖表头），版本取 Id 之后的一列
+fn parse_winget_list(output: &str) -> Vec<(&'static str, String, String)> {
+    let mut found = Vec::new();
+    for line in output.lines() {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        for (i, token) in tokens.iter().enumerate() {
+            let hit = KNOWN_TOOLS.iter().find(|(_, _, ids)| {
+                ids.iter().any(|id| id.eq_ignore_ascii_case(token))
+            });
+            if let Some((tool_id, _, _)) = hit {
+                let version = tokens.get(i + 1).unwrap_or(&"").to_string();
+                found.push((*tool_id, token.to_string(), version));
+                break;
+            }
+        }
+    }
+    found
+}
+
+// ── 扫描结果 ─────────────────────────────────────────────────────────────────
+
+/// 系统中发现的工具
+#[derive(Debug, Clone)]
+pub struct Discovered {
+    pub tool_id: &'static str,
+    pub display_name: String,
+    pub version: String,
+    /// 来源（注册表视图或 "winget"）
+    pub source: String,
+}
+
+/// 仅扫描注册表（无子进程，用于 list/setup 的检测补充）
+pub fn scan_registry() -> Vec<Discovered> {
+    let entries = registry_entries();
+    let mut found = Vec::new();
+    for (tool_id, prefixes, _) in KNOWN_TOOLS {
+        if let Some(e) = entries.iter().find(|e| display_matches(&e.display_name, prefixes)) {
+            found.push(Discovered {
+                tool_id,
+                display_name: e.display_name.clone(),
+                version: e.version.clone().unwrap_or_else(|| "unknown".to_string()),
+                source: e.hive.to_string(),
+            });
+        }
+    }
+    found
+}
+
+/// 注册表 + winget 完整扫描（hudo scan）
+pub fn scan_all() -> Vec<Discovered> {
+    let mut found = scan_registry();
+    for (tool_id, pkg_id, version) in winget_matches() {
+        if found.iter().any(|d| d.tool_id == tool_id) {
+            continue;
+        }
+        found.push(Discovered {
+            tool_id,
+            display_name: pkg_id,
+            version,
+            source: "winget".to_string(),
+        });
+    }
+    found
+}
+
+// ── 其他包管理器 ─────────────────────────────────────────────────────────────
+
+/// 包管理器名称与其安装路径特征（小写，按路径片段匹配）
+const PACKAGE_MANAGERS: &[(&str, &str)] = &[
+    ("Scoop", "\\scoop\\"),
+    ("Chocolatey", "\\chocolatey\\"),
+    ("winget", "\\winget\\packages\\"),
+    ("winget", "\\winget\\links\\"),
+];
+
+/// 各工具在 PATH 上的命令名（nodejs 由 fnm 管理，包管理器装的是 node 本身）
+const TOOL_COMMANDS: &[(&str, &[&str])] = &[
+    ("git", &["git"]),
+    ("gh", &["gh"]),
+    ("uv", &["uv"]),
+    ("nodejs", &["node", "fnm"]),
+    ("bun", &["bun"]),
+    ("rust", &["rustc", "cargo"]),
+    ("go", &["go"]),
+    ("jdk", &["java"]),
+    ("c", &["gcc"]),
+    ("miniconda", &["conda"]),
+    ("maven", &["mvn"]),
+    ("gradle", &["gradle"]),
+    ("mysql", &["mysql"]),
+    ("pgsql", &["psql"]),
+    ("redis", &["redis-server"]),
+    ("sqlite", &["sqlite3"]),
+    ("vscode", &["code"]),
+    ("pycharm", &["pycharm64"]),
+    ("claude-code", &["claude"]),
+];
+
+/// PATH 上由其他包管理器提供的命令
+pub struct ManagedCommand {
+    pub manager: &'static str,
+    pub path: String,
+}
+
+/// 路径位于哪个包管理器的目录下
+fn package_manager_of(path: &str) -> Option<&'static str> {
+    let lower = path.to_lowercase();
+    PACKAGE_MANAGERS
+        .iter()
+        .find(|(_, marker)| lower.contains(marker))
+        .map(|(name, _)| *name)
+}
+
+/// 用 where 查找工具的命令，返回其中位于 Scoop / Chocolatey / winget 目录下的项
+pub fn package_manager_commands(tool_id: &str) -> Vec<ManagedCommand> {
+    let commands = TOOL_COMMANDS
+        .iter()
+        .find(|(id, _)| *id == tool_id)
+        .map(|(_, cmds)| *cmds)
+        .unwrap_or(&[]);
+    let mut found = Vec::new();
+    for cmd in commands {
+        let Ok(out) = std::process::Command::new("where").arg(cmd).output() else {
+            continue;
+        };
+        if !out.status.success() {
+            continue;
+        }
+        for line in crate::proc::decode_output(&out.stdout).lines() {
+            let path = line.trim();
+            if let Some(manager) = package_manager_of(path) {
+                found.push(ManagedCommand { manager, path: path.to_string() });
+            }
+        }
+    }
+    found
+}
+
+// ── 运行卸载程序 ─────────────────────────────────────────────────────────────
+
+/// 将命令行拆分为 (程序, 参数)："\"C:\\a b\\unins000.exe\" /X" 或 "MsiExec.exe /I{GUID}"
+fn split_command(cmd: &str) -> (String, Vec<String>) {
+    let cmd = cmd.trim();
+    let (program, rest) = if let Some(stripped) = cmd.strip_prefix('"') {
+        match stripped.find('"') {
+            Some(end) => (&stripped[..end], &stripped[end + 1..]),
+            None => (stripped, ""),
+        }
+    } else {
+        let lower = cmd.to_ascii_lowercase();
+        match lower.find(".exe") {
+            Some(pos) => (&cmd[..pos + 4], &cmd[pos + 4..]),
+            None => match cmd.find(' ') {
+                Some(pos) => (&cmd[..pos], &cmd[pos..]),
+                None => (cmd, ""),
+            },
+        }
+    };
+    let args = rest.split_whitespace().map(|s| s.to_string()).collect();
+    (program.to_string(), args)
+}
+
+/// 静默运行注册表中记录的卸载程序（优先 QuietUninstallString，MSI 改用 msiexec /x）
+pub fn run_uninstaller(entry: &UninstallEntry) -> Result<()> {
+    let (program, args) = if let Some(quiet) = &entry.quiet_uninstall_string {
+        split_command(quiet)
+    } else {
+        let cmd = entry
+            .uninstall_string
+            .as_deref()
+            .context("注册表中未记录卸载命令，请手动卸载后重试")?;
+        let (program, args) = split_command(cmd);
+        if program.to_ascii_lowercase().ends_with("msiexec.exe") {
+            // "MsiExec.exe /I{GUID}" → msiexec /x {GUID} /qn /norestart
+            let guid = args
+                .iter()
+                .find(|a| a.starts_with('/') && a.contains('{'))
+                .map(|a| a[2..].to_string())
+                .context("无法解析 MSI 卸载命令")?;
+            (
+                "msiexec".to_string(),
+                vec!["/x".to_string(), guid, "/qn".to_string(), "/norestart".to_string()],
+            )
+        } else {
+            // 按卸载程序文件名识别安装器类型追加静默参数：Inno Setup 为 unins000.exe，NSIS 多为 Uninstall*.exe
+            let file = std::path::Path::new(&program)
+                .file_name()
+                .map(|f| f.to_string_lossy().to_ascii_lowercase())
+                .unwrap_or_default();
+            let mut args = args;
+            if file.starts_with("unins0") {
+                args.extend(["/VERYSILENT".to_string(), "/NORESTART".to_string()]);
+            } else if file.starts_with("uninstall") && !args.iter().any(|a| a == "/S") {
+                args.push("/S".to_string());
+            }
+            (program, args)
+        }
+    };
+
+    ui::print_info(&format!("运行卸载程序: {}", program));
+    let status = std::process::Command::new(&program)
+        .args(&args)
+        .status()
+        .with_context(|| format!("运行卸载程序失败: {}", program))?;
+
+    if !status.success() {
+        anyhow::bail!("卸载程序退出码: {}", status.code().unwrap_or(-1));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(tool_id: &str) -> &'static [&'static str] {
+        KNOWN_TOOLS.iter().find(|(id, _, _)| *id == tool_id).unwrap().1
+    }
+
+    #[test]
+    fn test_display_matches() {
+        let git = patterns("git");
+        assert!(display_matches("Git", git));
+        assert!(display_matches("Git version 2.47.1", git));
+        // 完全匹配的规则不会误中名称以它开头的其他软件
+        assert!(!display_matches("GitHub Desktop", git));
+        assert!(!display_matches("Git Extensions", git));
+        assert!(!display_matches("Google Chrome Beta", patterns("chrome")));
+        // 前缀规则包含末尾的空格
+        let conda = patterns("miniconda");
+        assert!(display_matches("Miniconda3 py312_24.9.2-0 (Python 3.12.7 64-bit)", conda));
+        assert!(!display_matches("Miniconda3", conda));
+        assert!(!display_matches("", patterns("uv")));
+    }
+
+    #[test]
+    fn test_parse_winget_list() {
+        // 中文系统的表头，列名不参与匹配
+        let output = "\
+名称                           ID                           版本     可用     源
+----------------------------------------------------------------------------------
+Git                            Git.Git                      2.47.1   2.48.1   winget
+GitHub Desktop                 GitHub.GitHubDesktop         3.4.13            winget
+Microsoft Visual Studio Code   Microsoft.VisualStudioCode   1.96.2            winget
+Node.js LTS                    openjs.nodejs.lts            22.11.0           winget
+";
+        assert_eq!(
+            parse_winget_list(output),
+            [
+                ("git", "Git.Git".to_string(), "2.47.1".to_string()),
+                ("vscode", "Microsoft.VisualStudioCode".to_string(), "1.96.2".to_string()),
+                ("nodejs", "openjs.nodejs.lts".to_string(), "22.11.0".to_string()),
+            ]
+        );
+
+        // 德文表头 + 行尾截断：Id 后面没有版本列时版本为空
+        let output = "Name   ID              Version   Quelle\n------------------------------------\nuv     astral-sh.uv\n";
+        assert_eq!(parse_winget_list(output), [("uv", "astral-sh.uv".to_string(), String::new())]);
+        assert!(parse_winget_list("未找到与输入条件匹配的已安装程序包。").is_empty());
+    }
+
+    #[test]
+    fn test_split_command() {
+        let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            split_command("\"C:\\Program Files\\Git\\unins000.exe\" /VERYSILENT /NORESTART"),
+            ("C:\\Program Files\\Git\\unins000.exe".to_string(), args(&["/VERYSILENT", "/NORESTART"]))
+        );
+        assert_eq!(
+            split_command("MsiExec.exe /I{8E4A3B2C-1D5F-4A6B-9C7D-0E1F2A3B4C5D}"),
+            ("MsiExec.exe".to_string(), args(&["/I{8E4A3B2C-1D5F-4A6B-9C7D-0E1F2A3B4C5D}"]))
+        );
+        // 未加引号的路径含空格：按 .exe 结尾切分
+        assert_eq!(
+            split_command("C:\\Program Files\\Go Uninstall\\uninstall.EXE /S"),
+            ("C:\\Program Files\\Go Uninstall\\uninstall.EXE".to_string(), args(&["/S"]))
+        );
+        assert_eq!(split_command("  \"C:\\a b\\unins000.exe\"  "), ("C:\\a b\\unins000.exe".to_string(), args(&[])));
+        assert_eq!(split_command("rundll32 foo.dll,Uninstall"), ("rundll32".to_string(), args(&["foo.dll,Uninstall"])));
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::installer::ShortcutSpec;
+use crate::proc::{self, ps_quote};
+
+/// 开始菜单中 hudo 的快捷方式目录：%APPDATA%\Microsoft\Windows\Start Menu\Programs\hudo
+pub fn start_menu_dir() -> Result<PathBuf> {
+    let appdata = dirs::data_dir().context("无法获取 %APPDATA% 目录")?;
+    Ok(appdata
+        .join("Microsoft")
+        .join("Windows")
+        .join("Start Menu")
+        .join("Programs")
+        .join("hudo"))
+}
+
+/// 桌面目录
+pub fn desktop_dir() -> Result<PathBuf> {
+    dirs::desktop_dir().context("无法获取桌面目录")
+}
+
+/// 快捷方式文件路径（{dir}\{name}.lnk）
+pub fn lnk_path(dir: &Path, spec: &ShortcutSpec) -> PathBuf {
+    dir.join(format!("{}.lnk", spec.name))
+}
+
+/// 通过 WScript.Shell 在 dir 下创建 .lnk（已存在则覆盖），返回快捷方式路径
+pub fn create(dir: &Path, spec: &ShortcutSpec) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).with_context(|| format!("无法创建目录: {}", dir.display()))?;
+    let lnk = lnk_path(dir, spec);
+    let icon = spec.icon.as_deref().unwrap_or(&spec.target);
+    let workdir = spec.target.parent().unwrap_or(&spec.target);
+
+    let ps_cmd = format!(
+        "$s = (New-Object -ComObject WScript.Shell).CreateShortcut({}); \
+         $s.TargetPath = {}; $s.Arguments = {}; $s.WorkingDirectory = {}; \
+         $s.IconLocation = {}; $s.Save()",
+        ps_quote(&lnk.to_string_lossy()),
+        ps_quote(&spec.target.to_string_lossy()),
+        ps_quote(&spec.args),
+        ps_quote(&workdir.to_string_lossy()),
+        ps_quote(&format!("{},0", icon.to_string_lossy())),
+    );
+    let output = proc::powershell(&ps_cmd)
+        .output()
+        .context("无法运行 PowerShell")?;
+    if !output.status.success() || !lnk.exists() {
+        anyhow::bail!(
+            "创建快捷方式失败: {}",
+            proc::decode_output(&output.stderr).trim()
+        );
+    }
+    Ok(lnk)
+}
+
+/// 读取 .lnk 指向的目标程序
+pub fn read_target(lnk: &Path) -> Result<PathBuf> {
+    let ps_cmd = format!(
+        "(New-Object -ComObject WScript.Shell).CreateShortcut({}).TargetPath",
+        ps_quote(&lnk.to_string_lossy())
+    );
+    let output = proc::powershell(&ps_cmd)
+        .output()
+        .context("无法运行 PowerShell")?;
+    let target = proc::decode_output(&output.stdout).trim().to_string();
+    if !output.status.success() || target.is_empty() {
+        anyhow::bail!("读取快捷方式失败: {}", lnk.display());
+    }
+    Ok(PathBuf::from(target))
+}
+
+/// 删除开始菜单和桌面上的快捷方式；hudo 开始菜单目录为空时一并删除
+pub fn remove_all(specs: &[ShortcutSpec]) {
+    let dirs: Vec<PathBuf> = [start_menu_dir(), desktop_dir()].into_iter().flatten().collect();
+    for spec in specs {
+        for dir in &dirs {
+            let lnk = lnk_path(dir, spec);
+            if lnk.exists() && std::fs::remove_file(&lnk).is_ok() {
+                crate::ui::print_info(&format!("已删除快捷方式 {}", lnk.display()));
+            }
+        }
+    }
+    if let Ok(menu) = start_menu_dir() {
+        // 非空目录 remove_dir 会失败，正好保留其他工具的快捷方式
+        std::fs::remove_dir(&menu).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_and_read_target() {
+        let dir = std::env::temp_dir().join("hudo-shortcut-test");
+        std::fs::remove_dir_all(&dir).ok();
+        let target = std::env::current_exe().unwrap();
+        let spec = ShortcutSpec {
+            name: "hudo test's app".to_string(),
+            target: target.clone(),
+            args: "--flag".to_string(),
+            icon: None,
+        };
+
+        let lnk = create(&dir, &spec).unwrap();
+        assert_eq!(lnk, dir.join("hudo test's app.lnk"));
+        assert!(lnk.exists());
+        let read = read_target(&lnk).unwrap();
+        assert!(read.to_string_lossy().eq_ignore_ascii_case(&target.to_string_lossy()));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
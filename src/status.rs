@@ -0,0 +1,218 @@
+//! hudo status：提交问题时附上的一屏概览
+//!
+//! 数据在 main.rs 中收集（配置、state.json、下载缓存、更新检查、服务状态、环境检查），
+//! 这里转成两列的终端输出；--json 输出同一份数据。
+
+use serde::Serialize;
+
+use crate::ui::{self, ToolCategory};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Status {
+    pub version: String,
+    pub config_path: String,
+    pub portable: bool,
+    pub root_dir: String,
+    /// 安装目录所在磁盘的空间，无法查询时为 None
+    pub disk: Option<DiskSpace>,
+    /// hudo 安装的工具数，按分类
+    pub tools: Vec<CategoryCount>,
+    pub cache_dir: String,
+    /// 下载缓存的总字节数
+    pub cache_size: u64,
+    /// 上次检查 hudo 更新的结果，从未检查时为 None
+    pub update_check: Option<UpdateCheck>,
+    /// 环境变量待应用（hudo env apply）的工具 id
+    pub env_pending: Vec<String>,
+    pub services: Vec<Service>,
+    /// hudo doctor 发现的问题；不支持环境检查的平台为 None
+    pub warnings: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskSpace {
+    pub free: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryCount {
+    #[serde(serialize_with = "crate::list::category_key")]
+    pub category: ToolCategory,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateCheck {
+    pub checked_at: String,
+    pub latest: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Service {
+    /// 工具 id
+    pub tool: &'static str,
+    /// Windows 服务名
+    pub name: &'static str,
+    /// running / stopped / not_found
+    pub state: &'static str,
+    #[serde(skip)]
+    pub label: &'static str,
+}
+
+/// 按分类统计工具数，每个分类一项（没有工具的分类计 0）
+pub fn count_by_category<'a>(ids: impl IntoIterator<Item = &'a str>) -> Vec<CategoryCount> {
+    let categories: Vec<ToolCategory> = ids.into_iter().map(ToolCategory::from_id).collect();
+    ToolCategory::ALL
+        .into_iter()
+        .map(|category| CategoryCount { category, count: categories.iter().filter(|&&c| c == category).count() })
+        .collect()
+}
+
+/// 终端输出的 (标签, 值)，环境检查发现的问题不在其中
+pub fn rows(status: &Status) -> Vec<(String, String)> {
+    let mut rows = vec![
+        ("hudo".to_string(), format!("v{}", status.version)),
+        (
+            "配置文件".to_string(),
+            match status.portable {
+                true => format!("{}（便携模式）", status.config_path),
+                false => status.config_path.clone(),
+            },
+        ),
+        (
+            "安装目录".to_string(),
+            match &status.disk {
+                Some(disk) => format!(
+                    "{}（可用 {} / 共 {}）",
+                    status.root_dir,
+                    ui::format_size(disk.free),
+                    ui::format_size(disk.total)
+                ),
+                None => status.root_dir.clone(),
+            },
+        ),
+    ];
+
+    let total: usize = status.tools.iter().map(|c| c.count).sum();
+    let by_category: Vec<String> = status
+        .tools
+        .iter()
+        .filter(|c| c.count > 0)
+        .map(|c| format!("{} {}", c.category.label(), c.count))
+        .collect();
+    rows.push((
+        "已安装".to_string(),
+        match total {
+            0 => "无".to_string(),
+            _ => format!("{} 个（{}）", total, by_category.join("、")),
+        },
+    ));
+
+    rows.push((
+        "下载缓存".to_string(),
+        format!("{}（{}）", status.cache_dir, ui::format_size(status.cache_size)),
+    ));
+    rows.push((
+        "检查更新".to_string(),
+        match &status.update_check {
+            Some(check) => format!("{}，最新版本 v{}", check.checked_at, check.latest),
+            None => "从未检查".to_string(),
+        },
+    ));
+    rows.push((
+        "待应用环境变量".to_string(),
+        match status.env_pending.is_empty() {
+            true => "无".to_string(),
+            false => format!("{}（运行 hudo env apply）", status.env_pending.join(", ")),
+        },
+    ));
+    for service in &status.services {
+        rows.push((format!("{} 服务", service.tool), format!("{} {}", service.name, service.label)));
+    }
+    if let Some(warnings) = &status.warnings {
+        rows.push((
+            "环境检查".to_string(),
+            match warnings.len() {
+                0 => "未发现问题".to_string(),
+                n => format!("{} 个问题", n),
+            },
+        ));
+    }
+    rows
+}
+
+/// 两列对齐输出，之后逐条列出环境检查发现的问题
+pub fn print(status: &Status) {
+    let rows = rows(status);
+    let width = rows.iter().map(|(label, _)| console::measure_text_width(label)).max().unwrap_or(0) + 2;
+    println!();
+    for (label, value) in &rows {
+        println!("  {}{}", console::style(ui::pad(label, width)).dim(), value);
+    }
+    if let Some(warnings) = status.warnings.as_ref().filter(|w| !w.is_empty()) {
+        println!();
+        for warning in warnings {
+            ui::print_warning(warning);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Status {
+        Status {
+            version: "0.3.0".to_string(),
+            config_path: "C:\\Users\\a\\.hudo\\config.toml".to_string(),
+            portable: false,
+            root_dir: "D:\\hudo".to_string(),
+            disk: Some(DiskSpace { free: 100 * 1024 * 1024 * 1024, total: 500 * 1024 * 1024 * 1024 }),
+            tools: count_by_category(["git", "go", "nodejs", "mysql"]),
+            cache_dir: "D:\\hudo\\cache".to_string(),
+            cache_size: 0,
+            update_check: None,
+            env_pending: vec!["go".to_string()],
+            services: vec![Service { tool: "mysql", name: "MySQL84", state: "running", label: "运行中" }],
+            warnings: Some(vec!["缺少开始菜单快捷方式: VS Code".to_string()]),
+        }
+    }
+
+    fn value<'a>(rows: &'a [(String, String)], label: &str) -> &'a str {
+        &rows.iter().find(|(l, _)| l == label).unwrap().1
+    }
+
+    #[test]
+    fn test_count_by_category() {
+        let counts = count_by_category(["git", "go", "nodejs", "mysql"]);
+        assert_eq!(counts.len(), ToolCategory::ALL.len());
+        let count = |c: ToolCategory| counts.iter().find(|x| x.category == c).unwrap().count;
+        assert_eq!(count(ToolCategory::Tool), 1);
+        assert_eq!(count(ToolCategory::Language), 2);
+        assert_eq!(count(ToolCategory::Database), 1);
+        assert_eq!(count(ToolCategory::Ide), 0);
+    }
+
+    #[test]
+    fn test_rows() {
+        let rows = rows(&sample());
+        assert_eq!(value(&rows, "hudo"), "v0.3.0");
+        assert_eq!(value(&rows, "安装目录"), "D:\\hudo（可用 100.0 GB / 共 500.0 GB）");
+        assert_eq!(value(&rows, "已安装"), "4 个（工具 1、语言环境 2、数据库 1）");
+        assert_eq!(value(&rows, "检查更新"), "从未检查");
+        assert_eq!(value(&rows, "待应用环境变量"), "go（运行 hudo env apply）");
+        assert_eq!(value(&rows, "mysql 服务"), "MySQL84 运行中");
+        assert_eq!(value(&rows, "环境检查"), "1 个问题");
+    }
+
+    #[test]
+    fn test_json() {
+        let json = serde_json::to_value(sample()).unwrap();
+        assert_eq!(json["tools"][1]["category"], "language");
+        assert_eq!(json["tools"][1]["count"], 2);
+        assert_eq!(json["services"][0]["state"], "running");
+        assert!(json["services"][0].get("label").is_none());
+        assert!(json["update_check"].is_null());
+    }
+}
@@ -0,0 +1,437 @@
+//! 接管系统已有工具时的清理（绿色安装的旧版）
+//!
+//! 通过 where 找到旧版命令所在目录，从用户 PATH 中移除这些目录并删除相关环境变量。
+//! 目录在系统 PATH（HKLM）中时，以管理员身份运行 `hudo env system-path` 移除：
+//! Windows 先查找系统 PATH 再查找用户 PATH，不移除的话旧版仍然优先。
+//! 执行前列出完整计划并确认；Windows、System32 等系统目录永远不会从 PATH 移除；
+//! 移除的内容记录到 state.json，可用 hudo env restore 恢复。
+
+use anyhow::{Context, Result};
+use dialoguer::Confirm;
+use std::path::{Path, PathBuf};
+
+use crate::config::HudoConfig;
+use crate::env::EnvManager;
+use crate::registry::InstallRegistry;
+use crate::running::is_under;
+
+/// 各工具旧版的命令名（不含 .exe）与需要清理的环境变量
+const GREEN_TARGETS: &[(&str, &[&str], &[&str])] = &[
+    ("git", &["git"], &[]),
+    ("nodejs", &["fnm", "node"], &["FNM_DIR"]),
+    ("bun", &["bun"], &[]),
+    ("jdk", &["java"], &["JAVA_HOME"]),
+    ("c", &["gcc"], &[]),
+    ("mysql", &["mysql"], &[]),
+    ("pgsql", &["psql"], &[]),
+    ("pycharm", &["pycharm64"], &[]),
+    ("claude-code", &["claude"], &[]),
+    ("rust", &["rustc", "cargo"], &["RUSTUP_HOME", "CARGO_HOME"]),
+    ("go", &["go"], &["GOROOT", "GOPATH"]),
+    ("miniconda", &["conda"], &[]),
+    ("vscode", &["code"], &[]),
+];
+
+/// 接管前的清理计划
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Plan {
+    /// 将删除的环境变量（名称, 当前值）
+    pub env_vars: Vec<(String, String)>,
+    /// 将从用户 PATH 移除的项（按 PATH 中的原样写法）
+    pub path_entries: Vec<String>,
+    /// 将从系统 PATH 移除的项（需要管理员权限）
+    pub system_entries: Vec<String>,
+    /// 找到但不会处理的目录及原因
+    pub skipped: Vec<(String, &'static str)>,
+}
+
+impl Plan {
+    /// 没有需要执行的更改
+    pub fn is_empty(&self) -> bool {
+        self.env_vars.is_empty() && self.path_entries.is_empty() && self.system_entries.is_empty()
+    }
+
+    pub fn print(&self) {
+        for (name, value) in &self.env_vars {
+            println!("    {} {} = {}", console::style("删除变量").red(), name, console::style(value).dim());
+        }
+        for entry in &self.path_entries {
+            println!("    {} {}", console::style("移除 PATH").red(), entry);
+        }
+        for entry in &self.system_entries {
+            println!("    {} {}  {}", console::style("移除系统 PATH").red(), entry, console::style("需要管理员权限").dim());
+        }
+        for (dir, reason) in &self.skipped {
+            println!("    {} {}  {}", console::style("保留").dim(), dir, console::style(reason).dim());
+        }
+    }
+}
+
+/// 找到的旧版目录 → 清理计划
+///
+/// dirs 为旧版命令所在目录；user_path / system_path 为当前用户 / 系统 PATH 各项；
+/// env_vars 为需清理的变量及当前值；lookup 用于展开 PATH 项中的 %VAR% 以及判断系统目录
+pub fn build_plan(
+    dirs: &[PathBuf],
+    user_path: &[String],
+    system_path: &[String],
+    env_vars: &[(&str, Option<String>)],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Plan {
+    let mut plan = Plan::default();
+    for dir in dirs {
+        let shown = dir.to_string_lossy().to_string();
+        if plan.skipped.iter().any(|(d, _)| same_dir(Path::new(d), dir)) {
+            continue;
+        }
+        if is_protected(dir, &lookup) {
+            plan.skipped.push((shown, "系统目录，不会从 PATH 移除"));
+            continue;
+        }
+        let user_entry = find_entry(user_path, dir, &lookup);
+        let system_entry = find_entry(system_path, dir, &lookup);
+        if let Some(entry) = user_entry.filter(|e| !plan.path_entries.contains(e)) {
+            plan.path_entries.push(entry.clone());
+        }
+        if let Some(entry) = system_entry.filter(|e| !plan.system_entries.contains(e)) {
+            plan.system_entries.push(entry.clone());
+        }
+        if user_entry.is_none() && system_entry.is_none() {
+            plan.skipped.push((shown, "不在用户或系统 PATH 中，需手动处理"));
+        }
+    }
+    for (name, value) in env_vars {
+        if let Some(value) = value {
+            plan.env_vars.push((name.to_string(), value.clone()));
+        }
+    }
+    plan
+}
+
+/// 永不从 PATH 移除的目录：%SystemRoot% 及其下所有目录、Program Files 根目录、
+/// 应用执行别名目录（%LOCALAPPDATA%\Microsoft\WindowsApps，winget、python 等依赖它）
+pub fn is_protected(dir: &Path, lookup: impl Fn(&str) -> Option<String>) -> bool {
+    let system_root = lookup("SystemRoot").unwrap_or_else(|| r"C:\Windows".to_string());
+    if is_under(dir, Path::new(&system_root)) {
+        return true;
+    }
+    let roots = ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"]
+        .iter()
+        .filter_map(|key| lookup(key).map(PathBuf::from))
+        .chain([PathBuf::from(r"C:\Program Files"), PathBuf::from(r"C:\Program Files (x86)")]);
+    if roots.into_iter().any(|root| same_dir(dir, &root)) {
+        return true;
+    }
+    lookup("LOCALAPPDATA")
+        .is_some_and(|local| same_dir(dir, &Path::new(&local).join("Microsoft").join("WindowsApps")))
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    is_under(a, b) && is_under(b, a)
+}
+
+/// PATH 各项中（展开 %VAR% 后）与 dir 相同的一项，按原样写法返回
+fn find_entry<'a>(entries: &'a [String], dir: &Path, lookup: &impl Fn(&str) -> Option<String>) -> Option<&'a String> {
+    entries
+        .iter()
+        .find(|entry| same_dir(Path::new(&crate::env::expand_vars(entry, lookup)), dir))
+}
+
+/// PATH 项的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathScope {
+    System,
+    User,
+}
+
+impl PathScope {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PathScope::System => "系统 PATH",
+            PathScope::User => "用户 PATH",
+        }
+    }
+}
+
+/// dir 所在的 PATH；两处都有时为系统 PATH（进程看到的 PATH 中系统 PATH 在前）
+pub fn path_scope(
+    dir: &Path,
+    user_path: &[String],
+    system_path: &[String],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Option<PathScope> {
+    if find_entry(system_path, dir, &lookup).is_some() {
+        Some(PathScope::System)
+    } else if find_entry(user_path, dir, &lookup).is_some() {
+        Some(PathScope::User)
+    } else {
+        None
+    }
+}
+
+/// 按 PATH 查找顺序列出命令的全部位置（where 的输出）
+#[cfg(windows)]
+pub fn resolve_command(command: &str) -> Vec<PathBuf> {
+    match std::process::Command::new("where").arg(command).output() {
+        Ok(output) if output.status.success() => crate::proc::decode_output(&output.stdout)
+            .lines()
+            .map(|line| PathBuf::from(line.trim()))
+            .filter(|p| !p.as_os_str().is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn resolve_command(_command: &str) -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// found 为命令按查找顺序的全部位置：hudo 安装目录下有该命令、但排在最前的不是它时，返回排在最前的那个
+pub fn shadowing<'a>(found: &'a [PathBuf], install_dir: &Path) -> Option<&'a PathBuf> {
+    let first = found.first()?;
+    let ours = found.iter().any(|p| is_under(p, install_dir));
+    (ours && !is_under(first, install_dir)).then_some(first)
+}
+
+/// 旧版命令所在的目录（where 可能返回多处）
+fn locate(binaries: &[&str]) -> Vec<PathBuf> {
+    binaries
+        .iter()
+        .flat_map(|bin| resolve_command(&format!("{}.exe", bin)))
+        .filter_map(|path| path.parent().filter(|p| !p.as_os_str().is_empty()).map(Path::to_path_buf))
+        .collect()
+}
+
+/// 从 PATH 值中去掉 entries（不区分大小写）
+pub fn remove_entries(value: &str, entries: &[String]) -> String {
+    value
+        .split(';')
+        .filter(|s| !s.is_empty() && !entries.iter().any(|e| e.eq_ignore_ascii_case(s)))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// 把 PATH 值中没有的 entries 追加到末尾
+pub fn append_entries(value: &str, entries: &[String]) -> String {
+    let mut parts: Vec<&str> = value.split(';').filter(|s| !s.is_empty()).collect();
+    for entry in entries {
+        if !parts.iter().any(|p| p.eq_ignore_ascii_case(entry)) {
+            parts.push(entry);
+        }
+    }
+    parts.join(";")
+}
+
+/// hudo env system-path：从系统 PATH 移除条目（restore 时加回），需要管理员权限。
+/// 由 edit_system_path_elevated 提权后调用
+pub fn edit_system_path(entries: &[String], restore: bool) -> Result<()> {
+    let current = EnvManager::system_path().context("无法读取系统 PATH")?;
+    let updated = match restore {
+        true => append_entries(&current, entries),
+        false => remove_entries(&current, entries),
+    };
+    if updated != current {
+        EnvManager::set_system_path(&updated)?;
+        EnvManager::broadcast_change();
+    }
+    Ok(())
+}
+
+/// 以管理员身份运行 hudo env system-path，返回修改是否已生效（用户拒绝 UAC 时为 false）
+#[cfg(windows)]
+pub fn edit_system_path_elevated(entries: &[String], restore: bool) -> bool {
+    let Ok(exe) = std::env::current_exe() else {
+        return false;
+    };
+    let mut args = vec!["env", "system-path"];
+    if restore {
+        args.push("--restore");
+    }
+    args.push("--");
+    args.extend(entries.iter().map(String::as_str));
+    let _ = crate::installer::run_as_admin(&exe.to_string_lossy(), &args);
+
+    // 以重新读取的系统 PATH 为准
+    let now = EnvManager::system_path_entries();
+    entries
+        .iter()
+        .all(|e| now.iter().any(|n| n.eq_ignore_ascii_case(e)) == restore)
+}
+
+#[cfg(not(windows))]
+pub fn edit_system_path_elevated(_entries: &[String], _restore: bool) -> bool {
+    false
+}
+
+/// 读取当前环境，生成某个工具的清理计划
+pub fn plan_for(tool_id: &str) -> Result<Plan> {
+    let (binaries, vars) = GREEN_TARGETS
+        .iter()
+        .find(|(id, _, _)| *id == tool_id)
+        .map(|(_, b, v)| (*b, *v))
+        .with_context(|| format!("不支持自动卸载: {}", tool_id))?;
+    let mut env_vars = Vec::new();
+    for var in vars {
+        env_vars.push((*var, EnvManager::get_var(var)?));
+    }
+    let lookup = |name: &str| EnvManager::get_var(name).ok().flatten().or_else(|| std::env::var(name).ok());
+    Ok(build_plan(
+        &locate(binaries),
+        &EnvManager::user_path()?,
+        &EnvManager::system_path_entries(),
+        &env_vars,
+        lookup,
+    ))
+}
+
+/// 通用卸载：列出清理计划，确认后（-y 时直接）从 PATH 移除旧版目录并删除环境变量，
+/// 移除的内容记录到 state.json
+pub fn uninstall_green(config: &HudoConfig, tool_id: &str) -> Result<()> {
+    let plan = plan_for(tool_id)?;
+    if plan.is_empty() {
+        plan.print();
+        crate::ui::print_info("没有需要清理的 PATH 项或环境变量");
+        return Ok(());
+    }
+
+    crate::ui::print_info("将执行以下清理:");
+    plan.print();
+    if !config.yes {
+        let go = Confirm::new()
+            .with_prompt("  执行以上清理？")
+            .default(true)
+            .interact()
+            .context("选择被取消")?;
+        if !go {
+            anyhow::bail!("已取消接管");
+        }
+    }
+
+    for entry in &plan.path_entries {
+        EnvManager::remove_from_path(entry)?;
+    }
+    for (name, _) in &plan.env_vars {
+        EnvManager::delete_var(name)?;
+    }
+    EnvManager::broadcast_change();
+
+    // 系统 PATH 排在用户 PATH 之前，留着的话旧版仍会被优先找到
+    let system_removed = match plan.system_entries.is_empty() {
+        true => true,
+        false => {
+            crate::ui::print_info("需要管理员权限修改系统 PATH，请在弹出的 UAC 窗口中点击\"是\"...");
+            edit_system_path_elevated(&plan.system_entries, false)
+        }
+    };
+
+    let mut reg = InstallRegistry::load(&config.state_path())?;
+    reg.record_env_removal(tool_id, plan.env_vars, plan.path_entries);
+    if system_removed {
+        reg.record_system_path_removal(tool_id, plan.system_entries.clone());
+    }
+    reg.save(&config.state_path())?;
+
+    if !system_removed {
+        crate::ui::print_warning("未能从系统 PATH 移除旧版目录（可能拒绝了 UAC 提示）：");
+        for entry in &plan.system_entries {
+            println!("    {}", entry);
+        }
+        crate::ui::print_info(
+            "Windows 总是先查找系统 PATH 再查找用户 PATH，hudo 无法让用户 PATH 中的版本排到前面，旧版命令仍会优先生效。",
+        );
+        crate::ui::print_info(
+            "请以管理员身份重新运行 hudo install，或在「系统属性 → 环境变量」中手动删除；用 hudo which <命令> 查看实际生效的位置",
+        );
+    }
+    crate::ui::print_success("旧版已清理（误删可运行 hudo env restore 恢复）");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "SystemRoot" => Some(r"C:\WINDOWS".to_string()),
+            "ProgramFiles" => Some(r"C:\Program Files".to_string()),
+            "LOCALAPPDATA" => Some(r"C:\Users\张 三\AppData\Local".to_string()),
+            "JAVA_HOME" => Some(r"C:\jdk-17".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_is_protected() {
+        assert!(is_protected(Path::new(r"C:\Windows\System32"), lookup));
+        assert!(is_protected(Path::new(r"c:\windows\system32\WindowsPowerShell\v1.0\"), lookup));
+        assert!(is_protected(Path::new(r"C:\Program Files"), lookup));
+        assert!(is_protected(Path::new(r"C:\Program Files (x86)\"), lookup));
+        assert!(is_protected(Path::new(r"C:\Users\张 三\AppData\Local\Microsoft\WindowsApps"), lookup));
+        // Program Files 下具体工具的目录可以移除
+        assert!(!is_protected(Path::new(r"C:\Program Files\Git\cmd"), lookup));
+        assert!(!is_protected(Path::new(r"D:\tools\node"), lookup));
+    }
+
+    #[test]
+    fn test_build_plan() {
+        let dirs = vec![
+            PathBuf::from(r"C:\Windows\System32"),
+            PathBuf::from(r"C:\jdk-17\bin"),
+            PathBuf::from(r"C:\JDK-17\bin\"),
+            PathBuf::from(r"C:\ProgramData\Oracle\Java\javapath"),
+        ];
+        let user_path = vec![r"D:\tools".to_string(), r"%JAVA_HOME%\bin".to_string()];
+        let system_path = vec![r"%SystemRoot%\system32".to_string(), r"C:\ProgramData\Oracle\Java\javapath".to_string()];
+        let vars = [("JAVA_HOME", Some(r"C:\jdk-17".to_string())), ("JRE_HOME", None)];
+        let plan = build_plan(&dirs, &user_path, &system_path, &vars, lookup);
+
+        // PATH 项按原样写法移除，重复目录只处理一次；系统 PATH 中的旧版目录单独列出
+        assert_eq!(plan.path_entries, vec![r"%JAVA_HOME%\bin"]);
+        assert_eq!(plan.system_entries, vec![r"C:\ProgramData\Oracle\Java\javapath"]);
+        assert_eq!(plan.env_vars, vec![("JAVA_HOME".to_string(), r"C:\jdk-17".to_string())]);
+        assert_eq!(plan.skipped.len(), 1);
+        assert_eq!(plan.skipped[0].0, r"C:\Windows\System32");
+        assert!(!plan.is_empty());
+
+        let nowhere = build_plan(&[PathBuf::from(r"E:\old\bin")], &user_path, &system_path, &[], lookup);
+        assert!(nowhere.is_empty());
+        assert!(nowhere.skipped[0].1.contains("系统 PATH"));
+
+        let nothing = build_plan(&[PathBuf::from(r"C:\Windows")], &user_path, &system_path, &[], lookup);
+        assert!(nothing.is_empty());
+    }
+
+    #[test]
+    fn test_path_scope() {
+        let user_path = vec![r"D:\hudo\tools\git\cmd".to_string(), r"C:\Go\bin".to_string()];
+        let system_path = vec![r"C:\Go\bin\".to_string(), r"C:\Program Files\nodejs".to_string()];
+        let scope = |dir: &str| path_scope(Path::new(dir), &user_path, &system_path, lookup);
+        assert_eq!(scope(r"c:\program files\nodejs"), Some(PathScope::System));
+        assert_eq!(scope(r"C:\Go\bin"), Some(PathScope::System));
+        assert_eq!(scope(r"D:\hudo\tools\git\cmd"), Some(PathScope::User));
+        assert_eq!(scope(r"E:\elsewhere"), None);
+    }
+
+    #[test]
+    fn test_shadowing() {
+        let install = Path::new(r"D:\hudo\tools\fnm");
+        let old = PathBuf::from(r"C:\Program Files\nodejs\node.exe");
+        let ours = PathBuf::from(r"D:\hudo\tools\fnm\node.exe");
+        assert_eq!(shadowing(&[old.clone(), ours.clone()], install), Some(&old));
+        assert_eq!(shadowing(&[ours.clone(), old.clone()], install), None);
+        // hudo 的版本不在 PATH 上时不算遮蔽
+        assert_eq!(shadowing(std::slice::from_ref(&old), install), None);
+        assert_eq!(shadowing(&[], install), None);
+    }
+
+    #[test]
+    fn test_edit_path_value() {
+        let value = r"C:\Windows;C:\Program Files\nodejs;D:\bin;";
+        let entries = vec![r"c:\program files\nodejs".to_string()];
+        assert_eq!(remove_entries(value, &entries), r"C:\Windows;D:\bin");
+        assert_eq!(append_entries(r"C:\Windows;D:\bin", &entries), r"C:\Windows;D:\bin;c:\program files\nodejs");
+        assert_eq!(append_entries(value, &entries), r"C:\Windows;C:\Program Files\nodejs;D:\bin");
+    }
+}
@@ -0,0 +1,153 @@
+//! 配置值中的模板
+//!
+//! `%NAME%` 展开为环境变量（未设置时原样保留，与 cmd 相同），`%%` 表示字面的 `%`；
+//! 镜像地址还可以使用 `{version}` / `{tool}` / `{root}` 占位符，`{{` / `}}` 表示字面的花括号。
+
+use anyhow::Result;
+
+/// 镜像地址可用的占位符：版本号、工具 id、安装根目录
+pub const MIRROR_PLACEHOLDERS: &[&str] = &["version", "tool", "root"];
+
+/// 展开 value 中的环境变量与占位符；vars 为 None 时不处理花括号（如 root_dir 中的路径）
+fn render(
+    value: &str,
+    vars: Option<&[(&str, &str)]>,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> Result<String> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        match (c, vars) {
+            ('%', _) if after.starts_with('%') => {
+                out.push('%');
+                rest = &after[1..];
+            }
+            ('%', _) => {
+                // %NAME% 中的名称不含空白；找不到或未设置时按字面保留这个 %
+                let expanded = after
+                    .find('%')
+                    .map(|end| &after[..end])
+                    .filter(|name| !name.is_empty() && !name.contains(char::is_whitespace))
+                    .and_then(|name| env(name).map(|v| (name.len(), v)));
+                match expanded {
+                    Some((len, v)) => {
+                        out.push_str(&v);
+                        rest = &after[len + 1..];
+                    }
+                    None => {
+                        out.push('%');
+                        rest = after;
+                    }
+                }
+            }
+            ('{', Some(_)) if after.starts_with('{') => {
+                out.push('{');
+                rest = &after[1..];
+            }
+            ('}', Some(_)) if after.starts_with('}') => {
+                out.push('}');
+                rest = &after[1..];
+            }
+            ('{', Some(vars)) => {
+                let end = after
+                    .find(['{', '}'])
+                    .filter(|&end| after[end..].starts_with('}'))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("「{}」中的 {{ 没有配对的 }}（字面的花括号写作 {{{{）", value)
+                    })?;
+                let name = &after[..end];
+                let v = vars.iter().find(|(k, _)| *k == name).map(|(_, v)| *v).ok_or_else(|| {
+                    let known: Vec<String> = vars.iter().map(|(k, _)| format!("{{{}}}", k)).collect();
+                    anyhow::anyhow!("未知占位符 {{{}}}，可用: {}", name, known.join(", "))
+                })?;
+                out.push_str(v);
+                rest = &after[end + 1..];
+            }
+            ('}', Some(_)) => {
+                anyhow::bail!("「{}」中有多余的 }}（字面的花括号写作 }}}}）", value)
+            }
+            _ => {
+                out.push(c);
+                rest = after;
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// 展开环境变量与占位符
+pub fn expand(value: &str, vars: &[(&str, &str)], env: impl Fn(&str) -> Option<String>) -> Result<String> {
+    render(value, Some(vars), &env)
+}
+
+/// 只展开环境变量（进程环境），用于路径类的配置值
+pub fn expand_env(value: &str) -> String {
+    render(value, None, &|name| std::env::var(name).ok()).unwrap_or_else(|_| value.to_string())
+}
+
+/// 检查模板语法：花括号成对、占位符都在 placeholders 中
+pub fn check(value: &str, placeholders: &[&str]) -> Result<()> {
+    let vars: Vec<(&str, &str)> = placeholders.iter().map(|&p| (p, "")).collect();
+    render(value, Some(&vars), &|_| None).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(name: &str) -> Option<String> {
+        match name {
+            "DATA_DRIVE" => Some("E:".to_string()),
+            "MIRROR" => Some("https://mirror.internal".to_string()),
+            _ => None,
+        }
+    }
+
+    const VARS: &[(&str, &str)] = &[("version", "1.24.0"), ("tool", "go"), ("root", "D:\\hudo")];
+
+    #[test]
+    fn test_expand_placeholders() {
+        assert_eq!(expand("https://m/{version}/go", VARS, env).unwrap(), "https://m/1.24.0/go");
+        assert_eq!(expand("https://m/{tool}/{version}", VARS, env).unwrap(), "https://m/go/1.24.0");
+        assert_eq!(expand("file:///{root}/mirror", VARS, env).unwrap(), "file:///D:\\hudo/mirror");
+        assert_eq!(expand("https://m/go", VARS, env).unwrap(), "https://m/go");
+    }
+
+    #[test]
+    fn test_expand_env() {
+        assert_eq!(expand("%MIRROR%/{tool}", VARS, env).unwrap(), "https://mirror.internal/go");
+        assert_eq!(expand("%DATA_DRIVE%\\mysql", VARS, env).unwrap(), "E:\\mysql");
+        // 未设置的变量和 URL 编码按字面保留
+        assert_eq!(expand("%NOPE%\\x", VARS, env).unwrap(), "%NOPE%\\x");
+        assert_eq!(expand("https://m/My%20Files/a%20b", VARS, env).unwrap(), "https://m/My%20Files/a%20b");
+        // 变量值中的花括号不再展开
+        assert_eq!(expand("%X%", VARS, |_| Some("{tool}".to_string())).unwrap(), "{tool}");
+    }
+
+    #[test]
+    fn test_expand_escapes() {
+        assert_eq!(expand("100%%", VARS, env).unwrap(), "100%");
+        assert_eq!(expand("%%DATA_DRIVE%%", VARS, env).unwrap(), "%DATA_DRIVE%");
+        assert_eq!(expand("{{version}}", VARS, env).unwrap(), "{version}");
+        assert_eq!(expand("a{{b}}c", VARS, env).unwrap(), "a{b}c");
+    }
+
+    #[test]
+    fn test_check_errors() {
+        assert!(check("https://m/{version}/{tool}/{root}", MIRROR_PLACEHOLDERS).is_ok());
+        let err = check("https://m/{ver}", MIRROR_PLACEHOLDERS).unwrap_err().to_string();
+        assert!(err.contains("未知占位符 {ver}") && err.contains("{version}"), "{}", err);
+        assert!(check("https://m/{version", MIRROR_PLACEHOLDERS).unwrap_err().to_string().contains("没有配对"));
+        assert!(check("https://m/{a{version}", MIRROR_PLACEHOLDERS).is_err());
+        assert!(check("https://m/version}", MIRROR_PLACEHOLDERS).unwrap_err().to_string().contains("多余的 }"));
+    }
+
+    #[test]
+    fn test_expand_env_keeps_braces() {
+        // 路径中的花括号是合法字符，只展开环境变量
+        std::env::set_var("HUDO_TEST_TEMPLATE_DRIVE", "E:");
+        assert_eq!(expand_env("%HUDO_TEST_TEMPLATE_DRIVE%\\hudo{1}"), "E:\\hudo{1}");
+        assert_eq!(expand_env("D:\\hudo"), "D:\\hudo");
+    }
+}
@@ -1,7 +1,233 @@
 use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
 use console::{measure_text_width, pad_str, style, Alignment, Style};
+use dialoguer::theme::ColorfulTheme;
 use figlet_rs::FIGfont;
 
+use crate::lang::{t, tf};
+
+// ── 符号与 ASCII 回退 ───────────────────────────────────────────────────────
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// 切换为 ASCII 符号（--ascii，或检测到控制台显示不了 emoji 与制表符时）
+pub fn set_ascii(on: bool) {
+    ASCII.store(on, Ordering::Relaxed);
+}
+
+pub fn is_ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// 是否需要 ASCII 符号：HUDO_ASCII 为 1 / 0 时按它；否则 Windows Terminal、VS Code 等现代终端可以显示，
+/// 传统控制台输出代码页不是 UTF-8（65001）时不能（codepage 为 None 表示不是 Windows 控制台）
+pub fn needs_ascii(forced: Option<&str>, codepage: Option<u32>, modern_terminal: bool) -> bool {
+    match forced.map(str::trim) {
+        Some("1") => return true,
+        Some("0") => return false,
+        _ => {}
+    }
+    !modern_terminal && codepage.is_some_and(|cp| cp != 65001)
+}
+
+/// 按当前环境检测是否需要 ASCII 符号
+pub fn detect_ascii() -> bool {
+    let forced = std::env::var("HUDO_ASCII").ok();
+    let modern = std::env::var_os("WT_SESSION").is_some() || std::env::var_os("TERM_PROGRAM").is_some();
+    needs_ascii(forced.as_deref(), console_codepage(), modern)
+}
+
+/// 控制台输出代码页；没有控制台（输出被重定向）时为 None。
+/// 已切换为 UTF-8 时返回切换前的值：旧版控制台换了代码页也显示不了 emoji
+#[cfg(windows)]
+fn console_codepage() -> Option<u32> {
+    match SAVED_OUTPUT_CP.load(Ordering::Relaxed) {
+        0 => match unsafe { windows_sys::Win32::System::Console::GetConsoleOutputCP() } {
+            0 => None,
+            cp => Some(cp),
+        },
+        cp => Some(cp),
+    }
+}
+
+#[cfg(not(windows))]
+fn console_codepage() -> Option<u32> {
+    None
+}
+
+// ── 控制台代码页 ────────────────────────────────────────────────────────────
+
+/// 切换前的输出 / 输入代码页，0 表示没有切换
+#[cfg(windows)]
+static SAVED_OUTPUT_CP: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+#[cfg(windows)]
+static SAVED_INPUT_CP: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+/// utf8_console 的返回值，drop 时恢复原来的代码页
+pub struct ConsoleGuard(());
+
+impl Drop for ConsoleGuard {
+    fn drop(&mut self) {
+        restore_console();
+    }
+}
+
+/// 把控制台输入、输出代码页切换为 UTF-8，中文不再显示为 ? 或乱码。
+/// 控制台由启动 hudo 的 cmd / PowerShell 共用，退出（包括 Ctrl+C）时恢复原来的代码页；
+/// 没有控制台或已经是 UTF-8 时不做任何修改
+#[cfg(windows)]
+pub fn utf8_console() -> ConsoleGuard {
+    use windows_sys::Win32::Globalization::CP_UTF8;
+    use windows_sys::Win32::System::Console::{
+        GetConsoleCP, GetConsoleOutputCP, SetConsoleCP, SetConsoleCtrlHandler, SetConsoleOutputCP,
+    };
+
+    /// Ctrl+C / 关闭窗口时先恢复代码页，再交给默认处理（结束进程）
+    unsafe extern "system" fn on_ctrl(_ctrl_type: u32) -> windows_sys::Win32::Foundation::BOOL {
+        restore_console();
+        0
+    }
+
+    let (output, input) = unsafe { (GetConsoleOutputCP(), GetConsoleCP()) };
+    if output == 0 || (output == CP_UTF8 && input == CP_UTF8) {
+        return ConsoleGuard(());
+    }
+    unsafe {
+        if SetConsoleOutputCP(CP_UTF8) != 0 {
+            SAVED_OUTPUT_CP.store(output, Ordering::Relaxed);
+        }
+        if input != 0 && SetConsoleCP(CP_UTF8) != 0 {
+            SAVED_INPUT_CP.store(input, Ordering::Relaxed);
+        }
+        SetConsoleCtrlHandler(Some(on_ctrl), 1);
+    }
+    ConsoleGuard(())
+}
+
+#[cfg(not(windows))]
+pub fn utf8_console() -> ConsoleGuard {
+    ConsoleGuard(())
+}
+
+/// 恢复 utf8_console 切换前的代码页，可重复调用
+#[cfg(windows)]
+pub fn restore_console() {
+    use windows_sys::Win32::System::Console::{SetConsoleCP, SetConsoleOutputCP};
+    let output = SAVED_OUTPUT_CP.swap(0, Ordering::Relaxed);
+    let input = SAVED_INPUT_CP.swap(0, Ordering::Relaxed);
+    unsafe {
+        if output != 0 {
+            SetConsoleOutputCP(output);
+        }
+        if input != 0 {
+            SetConsoleCP(input);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn restore_console() {}
+
+/// 界面符号，ASCII 模式下换成对应的 ASCII 写法（与 ToolCategory::icon 的 [T] 一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Glyph {
+    Success,
+    Warning,
+    Error,
+    Arrow,
+    Section,
+    Rule,
+    BoxTop,
+    BoxSide,
+    BoxBottom,
+    Download,
+    Bullet,
+}
+
+impl Glyph {
+    pub fn unicode(self) -> &'static str {
+        match self {
+            Glyph::Success => "✓",
+            Glyph::Warning => "⚠",
+            Glyph::Error => "✗",
+            Glyph::Arrow => "→",
+            Glyph::Section => "■",
+            Glyph::Rule => "─",
+            Glyph::BoxTop => "┌",
+            Glyph::BoxSide => "│",
+            Glyph::BoxBottom => "└",
+            Glyph::Download => "↓",
+            Glyph::Bullet => "●",
+        }
+    }
+
+    pub fn ascii(self) -> &'static str {
+        match self {
+            Glyph::Success => "[OK]",
+            Glyph::Warning => "[!]",
+            Glyph::Error => "[X]",
+            Glyph::Arrow => "->",
+            Glyph::Section => "#",
+            Glyph::Rule => "-",
+            Glyph::BoxTop => "+",
+            Glyph::BoxSide => "|",
+            Glyph::BoxBottom => "+",
+            Glyph::Download => "v",
+            Glyph::Bullet => "*",
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match is_ascii() {
+            true => self.ascii(),
+            false => self.unicode(),
+        }
+    }
+}
+
+impl std::fmt::Display for Glyph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 进度条字符
+pub fn progress_chars() -> &'static str {
+    match is_ascii() {
+        true => "=> ",
+        false => "━╸─",
+    }
+}
+
+/// 菜单项：ASCII 模式下去掉开头的 emoji（「📦  安装工具」→「安装工具」）
+pub fn menu_item(item: &str) -> String {
+    match item.split_once(' ') {
+        Some((icon, text)) if is_ascii() && !icon.is_empty() && !icon.chars().any(char::is_alphanumeric) => {
+            text.trim_start().to_string()
+        }
+        _ => item.to_string(),
+    }
+}
+
+/// dialoguer 主题：ASCII 模式下把 ✔ ❯ 等前缀换成 ASCII
+pub fn theme() -> ColorfulTheme {
+    let mut theme = ColorfulTheme::default();
+    if is_ascii() {
+        theme.prompt_prefix = style("?".to_string()).for_stderr().yellow();
+        theme.prompt_suffix = style(">".to_string()).for_stderr().black().bright();
+        theme.success_prefix = style("+".to_string()).for_stderr().green();
+        theme.success_suffix = style("-".to_string()).for_stderr().black().bright();
+        theme.error_prefix = style("x".to_string()).for_stderr().red();
+        theme.active_item_prefix = style(">".to_string()).for_stderr().green();
+        theme.checked_item_prefix = style("[x]".to_string()).for_stderr().green();
+        theme.unchecked_item_prefix = style("[ ]".to_string()).for_stderr().black().bright();
+        theme.picked_item_prefix = style(">".to_string()).for_stderr().green();
+    }
+    theme
+}
+
+// ── 输出 ────────────────────────────────────────────────────────────────────
+
 /// 打印 hudo 品牌 Banner
 pub fn print_banner() {
     let stdout = std::io::stdout();
@@ -14,7 +240,7 @@ pub fn print_banner() {
             }
         }
     }
-    let _ = writeln!(w, "  {}", style("混沌 — 开发环境一键引导工具").dim());
+    let _ = writeln!(w, "  {}", style(t("banner.tagline")).dim());
     let _ = writeln!(w);
 }
 
@@ -31,13 +257,13 @@ pub fn print_title(text: &str) {
     let s = Style::new().bold().cyan();
     println!();
     println!("{}", s.apply_to(text));
-    println!("{}", s.apply_to("─".repeat(width)));
+    println!("{}", s.apply_to(Glyph::Rule.as_str().repeat(width)));
 }
 
 /// 打印分类标题（用于 list / setup 中的分组）
 pub fn print_section(text: &str) {
     println!();
-    println!("  {} {}", style("■").cyan(), style(text).bold());
+    println!("  {} {}", style(Glyph::Section).cyan(), style(text).bold());
 }
 
 /// 打印进度步骤
@@ -50,25 +276,39 @@ pub fn print_step(step: u32, total: u32, text: &str) {
 }
 
 pub fn print_success(text: &str) {
-    println!("  {} {}", style("✓").green().bold(), text);
+    println!("  {} {}", style(Glyph::Success).green().bold(), text);
 }
 
 pub fn print_warning(text: &str) {
-    println!("  {} {}", style("⚠").yellow().bold(), text);
+    println!("  {} {}", style(Glyph::Warning).yellow().bold(), text);
 }
 
 #[allow(dead_code)]
 pub fn print_error(text: &str) {
-    println!("  {} {}", style("✗").red().bold(), text);
+    println!("  {} {}", style(Glyph::Error).red().bold(), text);
 }
 
 pub fn print_info(text: &str) {
     println!("  {}", style(text).dim());
 }
 
+/// 打印安装完成后的「下一步」提示框
+pub fn print_next_steps(tips: &[&str]) {
+    if tips.is_empty() {
+        return;
+    }
+    let border = Style::new().cyan();
+    println!();
+    println!("  {} {}", border.apply_to(Glyph::BoxTop), style(t("ui.next_steps")).bold());
+    for tip in tips {
+        println!("  {} {}", border.apply_to(Glyph::BoxSide), tip);
+    }
+    println!("  {}", border.apply_to(Glyph::BoxBottom));
+}
+
 /// 打印正在进行的操作
 pub fn print_action(text: &str) {
-    println!("  {} {}", style("→").cyan(), text);
+    println!("  {} {}", style(Glyph::Arrow).cyan(), text);
 }
 
 /// 将文本填充到指定显示宽度（处理中文双宽字符）
@@ -76,7 +316,79 @@ pub fn pad(text: &str, width: usize) -> String {
     pad_str(text, width, Alignment::Left, None).to_string()
 }
 
+/// 按列对齐的表格。列宽取该列最宽单元格的显示宽度：去掉颜色代码后计算，中文等宽字符按两列计；
+/// 单元格可以先上色，填充的空格加在颜色代码之外，最后一列不填充
+pub struct Table {
+    indent: usize,
+    gap: usize,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// indent 为行首缩进，列之间空两格
+    pub fn new(indent: usize) -> Self {
+        Self { indent, gap: 2, rows: Vec::new() }
+    }
+
+    pub fn row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    /// 每行一个字符串；各行列数可以不同，缺少的列视为空
+    pub fn render(&self) -> Vec<String> {
+        let columns = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..columns)
+            .map(|c| {
+                self.rows
+                    .iter()
+                    .filter_map(|r| r.get(c))
+                    .map(|cell| measure_text_width(cell))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        self.rows
+            .iter()
+            .map(|row| {
+                let mut line = " ".repeat(self.indent);
+                let last = row.iter().rposition(|cell| measure_text_width(cell) > 0).map_or(0, |i| i + 1);
+                for (c, cell) in row.iter().enumerate().take(last) {
+                    line.push_str(cell);
+                    if c + 1 < last {
+                        line.push_str(&" ".repeat(widths[c] - measure_text_width(cell) + self.gap));
+                    }
+                }
+                line
+            })
+            .collect()
+    }
+}
+
+/// 耗时显示：不到 1 分钟显示一位小数的秒数，否则显示分和秒
+pub fn format_duration(d: std::time::Duration) -> String {
+    match d.as_secs() {
+        0..=59 => tf("ui.seconds", &[&format!("{:.1}", d.as_secs_f64())]),
+        secs => tf("ui.minutes", &[&(secs / 60), &format!("{:02}", secs % 60)]),
+    }
+}
+
+/// 文件大小显示（按 1024 换算）：512 B、3.4 KB、120.5 MB、1.2 GB
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
 /// 工具分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ToolCategory {
     Tool,
     Language,
@@ -85,12 +397,32 @@ pub enum ToolCategory {
 }
 
 impl ToolCategory {
+    /// 全部分类（菜单与 hudo list 的显示顺序）
+    pub const ALL: [ToolCategory; 4] =
+        [ToolCategory::Tool, ToolCategory::Language, ToolCategory::Database, ToolCategory::Ide];
+
+    /// 当前界面语言的名称
     pub fn label(&self) -> &'static str {
+        self.label_in(crate::lang::current())
+    }
+
+    pub fn label_in(&self, lang: crate::lang::Lang) -> &'static str {
+        let key = match self {
+            ToolCategory::Tool => "category.tool",
+            ToolCategory::Language => "category.language",
+            ToolCategory::Database => "category.database",
+            ToolCategory::Ide => "category.ide",
+        };
+        crate::lang::text(lang, key)
+    }
+
+    /// 稳定的英文标识（hudo catalog 输出）
+    pub fn key(&self) -> &'static str {
         match self {
-            ToolCategory::Tool => "工具",
-            ToolCategory::Language => "语言环境",
-            ToolCategory::Database => "数据库",
-            ToolCategory::Ide => "编辑器 / IDE",
+            ToolCategory::Tool => "tool",
+            ToolCategory::Language => "language",
+            ToolCategory::Database => "database",
+            ToolCategory::Ide => "ide",
         }
     }
 
@@ -107,7 +439,7 @@ impl ToolCategory {
         match id {
             "git" | "gh" | "claude-code" => ToolCategory::Tool,
             "uv" | "nodejs" | "bun" | "miniconda" | "rust" | "go" | "jdk" | "c" | "maven" | "gradle" => ToolCategory::Language,
-            "mysql" | "pgsql" | "redis" => ToolCategory::Database,
+            "mysql" | "pgsql" | "redis" | "sqlite" => ToolCategory::Database,
             "vscode" | "pycharm" | "chrome" => ToolCategory::Ide,
             _ => ToolCategory::Tool,
         }
@@ -124,6 +456,76 @@ pub fn page_header(title: &str) {
 /// 暂停等待用户按键
 pub fn wait_for_key() {
     println!();
-    println!("  {}", style("按任意键返回...").dim());
+    println!("  {}", style(t("ui.press_any_key")).dim());
     let _ = console::Term::stderr().read_key();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(3250)), "3.2 秒");
+        assert_eq!(format_duration(Duration::from_millis(59_900)), "59.9 秒");
+        assert_eq!(format_duration(Duration::from_secs(65)), "1 分 05 秒");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "62 分 05 秒");
+    }
+
+    #[test]
+    fn test_needs_ascii() {
+        // 中文 Windows 传统控制台默认 936
+        assert!(needs_ascii(None, Some(936), false));
+        assert!(!needs_ascii(None, Some(65001), false));
+        assert!(!needs_ascii(None, Some(936), true));
+        assert!(!needs_ascii(None, None, false));
+        assert!(needs_ascii(Some("1"), None, true));
+        assert!(!needs_ascii(Some("0"), Some(437), false));
+    }
+
+    #[test]
+    fn test_table_aligns_wide_and_colored_cells() {
+        let bold = Style::new().bold().force_styling(true);
+        let green = Style::new().green().force_styling(true);
+        let mut table = Table::new(4);
+        let rows = [
+            ("Git", "版本控制", "2.47.1"),
+            ("数据库客户端", "Database client", "检测失败"),
+            ("PyCharm", "Python IDE（社区版）", ""),
+        ];
+        for (name, desc, status) in rows {
+            table.row(vec![
+                bold.apply_to(name).to_string(),
+                desc.to_string(),
+                green.apply_to(status).to_string(),
+            ]);
+        }
+        let lines = table.render();
+        assert!(lines[0].contains("\u{1b}["));
+
+        // 去掉颜色代码后，每一列在各行的起始显示位置相同
+        let starts: Vec<(usize, usize)> = lines
+            .iter()
+            .zip(rows)
+            .map(|(line, (_, desc, status))| {
+                let plain = console::strip_ansi_codes(line).to_string();
+                let col = |text: &str| plain.find(text).map(|i| measure_text_width(&plain[..i])).unwrap();
+                (col(desc), if status.is_empty() { 0 } else { col(status) })
+            })
+            .collect();
+        assert_eq!(starts[0], (4 + 12 + 2, 4 + 12 + 2 + 20 + 2));
+        assert_eq!(starts[1], starts[0]);
+        assert_eq!(starts[2].0, starts[0].0);
+        // 空的最后一列不留尾随空格
+        assert_eq!(console::strip_ansi_codes(&lines[2]).to_string(), "    PyCharm       Python IDE（社区版）");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(3482), "3.4 KB");
+        assert_eq!(format_size(150 * 1024 * 1024), "150.0 MB");
+        assert_eq!(format_size(1288490189), "1.2 GB");
+    }
+}
@@ -1,200 +1,534 @@
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::config::HudoConfig;
+use crate::report::Reporter;
+use crate::ui;
 
 /// GitHub 仓库（owner/repo），用于自更新检查
 pub const GITHUB_REPO: &str = "zexadev/hudo";
 
 /// 复用同一个 client，带 5 秒超时
 fn make_client() -> reqwest::Result<Client> {
+    http_client(5)
+}
+
+/// 带超时的 HTTP client（reqwest 默认读取 HTTP_PROXY / HTTPS_PROXY 环境变量）
+pub fn http_client(timeout_secs: u64) -> reqwest::Result<Client> {
     Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
+        .timeout(std::time::Duration::from_secs(timeout_secs))
         .build()
 }
 
+// ── 请求与缓存 ──────────────────────────────────────────────────────────────
+
+/// 版本查询缓存有效期：6 小时
+const VERSION_CACHE_TTL: u64 = 6 * 60 * 60;
+
+/// 版本查询缓存文件名（位于下载缓存目录下）
+const VERSION_CACHE_FILE: &str = "version-cache.json";
+
+/// 版本查询的运行时设置，由 `init` 根据配置填充
+struct Settings {
+    /// 缓存文件路径，None 表示不缓存
+    cache_path: Option<PathBuf>,
+    /// GitHub API token（GITHUB_TOKEN / GH_TOKEN 环境变量优先于配置文件）
+    github_token: Option<String>,
+}
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// 串行化缓存文件的读改写，避免并行探测时互相覆盖
+static CACHE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 限流提示每次运行只显示一次
+static RATE_LIMIT_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// 版本查询接口的替代地址：设置后 "https://go.dev/dl/..." 改为请求 "{base}/go.dev/dl/..."
+static ENDPOINT_BASE: OnceLock<String> = OnceLock::new();
+
+/// 把版本查询重定向到本地模拟服务，供集成测试使用（进程内只能设置一次）
+pub fn redirect_endpoints(base: &str) -> bool {
+    ENDPOINT_BASE.set(base.trim_end_matches('/').to_string()).is_ok()
+}
+
+fn endpoint(url: &str) -> String {
+    match (ENDPOINT_BASE.get(), url.strip_prefix("https://")) {
+        (Some(base), Some(rest)) => format!("{}/{}", base, rest),
+        _ => url.to_string(),
+    }
+}
+
+/// 加载配置后调用：确定缓存位置与 GitHub token
+pub fn init(config: &HudoConfig) {
+    let _ = SETTINGS.set(Settings {
+        cache_path: Some(config.cache_dir().join(VERSION_CACHE_FILE)),
+        github_token: env_github_token().or_else(|| config.github_token.clone()),
+    });
+}
+
+fn settings() -> &'static Settings {
+    SETTINGS.get_or_init(|| Settings {
+        cache_path: None,
+        github_token: env_github_token(),
+    })
+}
+
+fn env_github_token() -> Option<String> {
+    token_from_env(|name| std::env::var(name).ok())
+}
+
+/// GITHUB_TOKEN 优先，其次 gh 使用的 GH_TOKEN；空值视为未设置
+fn token_from_env(lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    ["GITHUB_TOKEN", "GH_TOKEN"]
+        .iter()
+        .find_map(|name| lookup(name).map(|t| t.trim().to_string()).filter(|t| !t.is_empty()))
+}
+
+/// 没有配置 token 时借用已登录的 gh 的 token（`gh auth token`），每次运行最多调用一次
+fn gh_cli_token() -> Option<&'static str> {
+    static TOKEN: OnceLock<Option<String>> = OnceLock::new();
+    TOKEN
+        .get_or_init(|| {
+            let output = std::process::Command::new("gh").args(["auth", "token"]).output().ok()?;
+            output.status.success().then(|| parse_gh_token(&output.stdout)).flatten()
+        })
+        .as_deref()
+}
+
+/// `gh auth token` 的输出 → token（未登录时输出为空）
+fn parse_gh_token(stdout: &[u8]) -> Option<String> {
+    let token = crate::proc::decode_output(stdout).trim().to_string();
+    (!token.is_empty() && !token.contains(char::is_whitespace)).then_some(token)
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 版本查询缓存：URL → 响应 JSON
+#[derive(Default, Serialize, Deserialize)]
+struct VersionCache {
+    #[serde(default)]
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// 获取时间（Unix 秒）
+    fetched_at: u64,
+    body: serde_json::Value,
+}
+
+impl VersionCache {
+    fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).ok();
+        }
+        if let Ok(s) = serde_json::to_string(self) {
+            std::fs::write(path, s).ok();
+        }
+    }
+
+    /// 未过期的缓存
+    fn fresh(&self, url: &str, now: u64) -> Option<&serde_json::Value> {
+        self.entries
+            .get(url)
+            .filter(|e| now.saturating_sub(e.fetched_at) < VERSION_CACHE_TTL)
+            .map(|e| &e.body)
+    }
+
+    /// 不论是否过期的缓存（请求失败时兜底）
+    fn any(&self, url: &str) -> Option<&serde_json::Value> {
+        self.entries.get(url).map(|e| &e.body)
+    }
+
+    fn insert(&mut self, url: &str, now: u64, body: serde_json::Value) {
+        self.entries.insert(
+            url.to_string(),
+            CacheEntry {
+                fetched_at: now,
+                body,
+            },
+        );
+    }
+}
+
+/// 版本查询失败的原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum VersionError {
+    /// 网络错误或超时
+    Network(String),
+    /// 非预期的 HTTP 状态
+    Http(u16),
+    /// 无法建立连接（离线、DNS 失败或代理不可用）
+    Offline,
+    /// GitHub API 限流
+    RateLimited,
+    /// 响应格式不符合预期
+    Parse(String),
+    /// 找不到发布信息（404）
+    NotFound,
+}
+
+impl std::fmt::Display for VersionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Network(msg) => write!(f, "网络请求失败: {}", msg),
+            Self::Http(status) => write!(f, "网络请求失败: HTTP {}", status),
+            Self::Offline => write!(f, "无法连接服务器，可能未联网或代理不可用"),
+            Self::RateLimited => write!(f, "GitHub API 请求已达上限"),
+            Self::Parse(msg) => write!(f, "无法解析版本信息: {}", msg),
+            Self::NotFound => write!(f, "未找到发布信息"),
+        }
+    }
+}
+
+impl std::error::Error for VersionError {}
+
+impl From<reqwest::Error> for VersionError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Self::Network("请求超时".to_string())
+        } else if e.is_connect() {
+            Self::Offline
+        } else if e.is_decode() {
+            Self::Parse("响应不是有效的 JSON".to_string())
+        } else {
+            Self::Network(e.without_url().to_string())
+        }
+    }
+}
+
+impl VersionError {
+    /// 临时性错误（网络、超时、5xx）值得重试一次；限流、404 和格式错误重试也无用
+    fn is_transient(&self) -> bool {
+        matches!(self, Self::Network(_) | Self::Offline | Self::Http(500..=599))
+    }
+}
+
+pub type VersionResult<T> = Result<T, VersionError>;
+
+/// 临时性错误后重试前的等待时间
+const RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// 安装时查询最新版本：失败则提示原因并退回内置默认版本
+pub fn or_default(reporter: &dyn Reporter, result: VersionResult<String>, default: &str) -> String {
+    result.unwrap_or_else(|e| {
+        reporter.warning(&format!("获取最新版本失败（{}），使用内置版本 {}", e, default));
+        default.to_string()
+    })
+}
+
+/// 按状态码与 x-ratelimit-remaining 判断响应是否可用
+fn classify_response(status: u16, ratelimit_remaining: Option<&str>) -> VersionResult<()> {
+    match status {
+        200..=299 => Ok(()),
+        429 => Err(VersionError::RateLimited),
+        403 if ratelimit_remaining.map(str::trim) == Some("0") => Err(VersionError::RateLimited),
+        404 => Err(VersionError::NotFound),
+        _ => Err(VersionError::Http(status)),
+    }
+}
+
+/// 限流提示文案，`reset_in` 为距额度恢复的秒数
+fn rate_limit_message(has_token: bool, reset_in: Option<u64>) -> String {
+    let when = match reset_in {
+        Some(secs) => format!("约 {} 分钟后恢复", secs.div_ceil(60).max(1)),
+        None => "稍后恢复".to_string(),
+    };
+    if has_token {
+        format!("GitHub API 请求已达上限（{}），部分版本信息可能不是最新", when)
+    } else {
+        format!(
+            "GitHub API 匿名请求已达上限（每小时 60 次，{}），部分版本信息可能不是最新；可设置 GITHUB_TOKEN 环境变量、运行 hudo config set github_token <token>，或用 gh auth login 登录 GitHub CLI",
+            when
+        )
+    }
+}
+
+/// 发送 GET 请求并检查状态，网络错误、超时或 5xx 时重试一次
+async fn get(url: &str) -> VersionResult<reqwest::Response> {
+    let client = make_client()?;
+    match get_once(&client, url).await {
+        Err(e) if e.is_transient() => {
+            tokio::time::sleep(RETRY_DELAY).await;
+            get_once(&client, url).await
+        }
+        result => result,
+    }
+}
+
+/// 单次 GET；GitHub API 附带 token（配置或 gh 登录），限流时显示提示
+async fn get_once(client: &Client, url: &str) -> VersionResult<reqwest::Response> {
+    let is_github = url.starts_with("https://api.github.com/");
+    let token = match is_github {
+        true => settings().github_token.as_deref().or_else(gh_cli_token),
+        false => None,
+    };
+    let mut req = client.get(endpoint(url)).header("User-Agent", "hudo");
+    if let Some(token) = token {
+        req = req.bearer_auth(token);
+    }
+    let resp = req.send().await?;
+
+    let header = |name: &str| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    };
+    let checked = classify_response(resp.status().as_u16(), header("x-ratelimit-remaining").as_deref());
+    if checked == Err(VersionError::RateLimited) && !RATE_LIMIT_WARNED.swap(true, Ordering::Relaxed) {
+        let reset_in = header("x-ratelimit-reset")
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .map(|reset| reset.saturating_sub(unix_now()));
+        ui::print_warning(&rate_limit_message(token.is_some(), reset_in));
+    }
+    checked.map(|_| resp)
+}
+
+/// GET 并解析 JSON（不经过缓存）
+async fn fetch_value(url: &str) -> VersionResult<serde_json::Value> {
+    Ok(get(url).await?.json().await?)
+}
+
+/// 带缓存的 JSON 请求：缓存未过期直接返回；请求失败时退回旧缓存，没有旧缓存才返回错误
+async fn cached_json(url: &str) -> VersionResult<serde_json::Value> {
+    let now = unix_now();
+    let path = settings().cache_path.as_deref();
+    let cache = path.map(VersionCache::load).unwrap_or_default();
+    if let Some(body) = cache.fresh(url, now) {
+        return Ok(body.clone());
+    }
+
+    match fetch_value(url).await {
+        Ok(body) => {
+            if let Some(path) = path {
+                let _guard = CACHE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+                let mut latest = VersionCache::load(path);
+                latest.insert(url, now, body.clone());
+                latest.save(path);
+            }
+            Ok(body)
+        }
+        Err(e) => cache.any(url).cloned().ok_or(e),
+    }
+}
+
+/// GET 并解析为指定类型（经过版本缓存）
+async fn fetch_json<T: serde::de::DeserializeOwned>(url: &str) -> VersionResult<T> {
+    serde_json::from_value(cached_json(url).await?)
+        .map_err(|e| VersionError::Parse(e.to_string()))
+}
+
+/// GitHub API → 仓库最新 release 的原始 JSON（经过版本缓存）
+async fn github_latest_release(repo: &str) -> VersionResult<serde_json::Value> {
+    cached_json(&format!("https://api.github.com/repos/{}/releases/latest", repo)).await
+}
+
+/// GitHub API → 仓库最新 release 的 tag（如 "v2.87.3"），前缀由调用方处理
+pub async fn github_latest(repo: &str) -> VersionResult<String> {
+    let release = github_latest_release(repo).await?;
+    release_tag(&release)
+}
+
+fn release_tag(release: &serde_json::Value) -> VersionResult<String> {
+    release["tag_name"]
+        .as_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| VersionError::Parse("缺少 tag_name".to_string()))
+}
+
+/// 从 JSON 中取字符串字段，缺失时返回 Parse 错误
+fn json_str<'a>(value: &'a serde_json::Value, field: &str) -> VersionResult<&'a str> {
+    value[field]
+        .as_str()
+        .ok_or_else(|| VersionError::Parse(format!("缺少 {}", field)))
+}
+
+// ── 最新版本 ────────────────────────────────────────────────────────────────
+
 /// GitHub CLI: GitHub API → 最新版本号（如 "2.87.3"）
-pub async fn gh_latest() -> Option<String> {
-    let client = make_client().ok()?;
-    let resp: serde_json::Value = client
-        .get("https://api.github.com/repos/cli/cli/releases/latest")
-        .header("User-Agent", "hudo")
-        .send()
-        .await
-        .ok()?
-        .json()
-        .await
-        .ok()?;
-    let tag = resp["tag_name"].as_str()?; // "v2.87.3"
-    Some(tag.trim_start_matches('v').to_string())
+pub async fn gh_latest() -> VersionResult<String> {
+    let tag = github_latest("cli/cli").await?; // "v2.87.3"
+    parse_v_tag(&tag)
+}
+
+/// "v2.87.3" → "2.87.3"
+fn parse_v_tag(tag: &str) -> VersionResult<String> {
+    let ver = tag.trim_start_matches('v');
+    if ver.starts_with(|c: char| c.is_ascii_digit()) {
+        Ok(ver.to_string())
+    } else {
+        Err(VersionError::Parse(format!("tag {}", tag)))
+    }
 }
 
 /// Git: GitHub API → tag "v2.47.1.windows.2" → "2.47.1.2"
-pub async fn git_latest() -> Option<String> {
-    let client = make_client().ok()?;
-    let resp: serde_json::Value = client
-        .get("https://api.github.com/repos/git-for-windows/git/releases/latest")
-        .header("User-Agent", "hudo")
-        .send()
-        .await
-        .ok()?
-        .json()
-        .await
-        .ok()?;
-    let tag = resp["tag_name"].as_str()?;
-    parse_git_tag(tag)
+pub async fn git_latest() -> VersionResult<String> {
+    let tag = github_latest("git-for-windows/git").await?;
+    parse_git_tag(&tag)
 }
 
 /// "v2.47.1.windows.2" → "2.47.1.2", "v2.53.0.windows.1" → "2.53.0"
-fn parse_git_tag(tag: &str) -> Option<String> {
-    let tag = tag.strip_prefix('v')?;
-    let parts: Vec<&str> = tag.split('.').collect();
+fn parse_git_tag(tag: &str) -> VersionResult<String> {
+    let invalid = || VersionError::Parse(format!("tag {}", tag));
+    let rest = tag.strip_prefix('v').ok_or_else(invalid)?;
+    let parts: Vec<&str> = rest.split('.').collect();
     // ["2","47","1","windows","2"] or ["2","53","0","windows","1"]
-    let idx = parts.iter().position(|&p| p == "windows")?;
+    let idx = parts.iter().position(|&p| p == "windows").ok_or_else(invalid)?;
     let ver_parts = &parts[..idx]; // ["2","47","1"]
-    let win_patch = parts.get(idx + 1)?; // "2" or "1"
+    let win_patch = parts.get(idx + 1).ok_or_else(invalid)?; // "2" or "1"
     if *win_patch == "1" {
-        Some(ver_parts.join(".")) // "2.53.0"
+        Ok(ver_parts.join(".")) // "2.53.0"
     } else {
-        Some(format!("{}.{}", ver_parts.join("."), win_patch)) // "2.47.1.2"
+        Ok(format!("{}.{}", ver_parts.join("."), win_patch)) // "2.47.1.2"
     }
 }
 
 /// Go: go.dev/dl API → "1.24.0"
-pub async fn go_latest() -> Option<String> {
-    let client = make_client().ok()?;
-    let resp: Vec<serde_json::Value> = client
-        .get("https://go.dev/dl/?mode=json")
-        .send()
-        .await
-        .ok()?
-        .json()
-        .await
-        .ok()?;
-    let ver = resp.first()?["version"].as_str()?; // "go1.24.0"
-    Some(ver.strip_prefix("go")?.to_string())
+pub async fn go_latest() -> VersionResult<String> {
+    let resp: Vec<serde_json::Value> = fetch_json("https://go.dev/dl/?mode=json").await?;
+    parse_go_latest(&resp)
+}
+
+/// go.dev/dl 列表第一项 "go1.24.0" → "1.24.0"
+fn parse_go_latest(resp: &[serde_json::Value]) -> VersionResult<String> {
+    let first = resp
+        .first()
+        .ok_or_else(|| VersionError::Parse("版本列表为空".to_string()))?;
+    let ver = json_str(first, "version")?; // "go1.24.0"
+    ver.strip_prefix("go")
+        .map(|s| s.to_string())
+        .ok_or_else(|| VersionError::Parse(format!("version {}", ver)))
 }
 
 /// PostgreSQL: versions.json → 当前大版本最新完整版本号（如 "18.2"）
-pub async fn pgsql_latest() -> Option<String> {
-    let client = make_client().ok()?;
-    let resp: Vec<serde_json::Value> = client
-        .get("https://www.postgresql.org/versions.json")
-        .send()
-        .await
-        .ok()?
-        .json()
-        .await
-        .ok()?;
-    resp.iter()
+pub async fn pgsql_latest() -> VersionResult<String> {
+    let resp: Vec<serde_json::Value> =
+        fetch_json("https://www.postgresql.org/versions.json").await?;
+    parse_pgsql_latest(&resp)
+}
+
+/// versions.json → current 大版本的 "major.latestMinor"
+fn parse_pgsql_latest(resp: &[serde_json::Value]) -> VersionResult<String> {
+    let current = resp
+        .iter()
         .find(|v| v["current"].as_bool() == Some(true))
-        .and_then(|v| {
-            let major = v["major"].as_str()?;
-            let minor = v["latestMinor"].as_str()?;
-            Some(format!("{}.{}", major, minor))
-        })
+        .ok_or_else(|| VersionError::Parse("没有 current 版本".to_string()))?;
+    Ok(format!(
+        "{}.{}",
+        json_str(current, "major")?,
+        json_str(current, "latestMinor")?
+    ))
 }
 
 /// Maven: GitHub API → 最新稳定版本号（如 "3.9.9"）
-pub async fn maven_latest() -> Option<String> {
-    let client = make_client().ok()?;
-    let resp: serde_json::Value = client
-        .get("https://api.github.com/repos/apache/maven/releases/latest")
-        .header("User-Agent", "hudo")
-        .send()
-        .await
-        .ok()?
-        .json()
-        .await
-        .ok()?;
-    // tag_name 格式: "maven-3.9.9"
-    let tag = resp["tag_name"].as_str()?;
-    tag.strip_prefix("maven-").map(|s| s.to_string())
+pub async fn maven_latest() -> VersionResult<String> {
+    let tag = github_latest("apache/maven").await?;
+    parse_maven_tag(&tag)
+}
+
+/// tag_name 格式: "maven-3.9.9" → "3.9.9"
+fn parse_maven_tag(tag: &str) -> VersionResult<String> {
+    tag.strip_prefix("maven-")
+        .map(|s| s.to_string())
+        .ok_or_else(|| VersionError::Parse(format!("tag {}", tag)))
 }
 
 /// Gradle: services.gradle.org API → 最新发布版本号（如 "8.12.1"）
-pub async fn gradle_latest() -> Option<String> {
-    let client = make_client().ok()?;
-    let resp: serde_json::Value = client
-        .get("https://services.gradle.org/versions/current")
-        .send()
-        .await
-        .ok()?
-        .json()
-        .await
-        .ok()?;
-    resp["version"].as_str().map(|s| s.to_string())
+pub async fn gradle_latest() -> VersionResult<String> {
+    let resp: serde_json::Value =
+        fetch_json("https://services.gradle.org/versions/current").await?;
+    Ok(json_str(&resp, "version")?.to_string())
 }
 
 /// PyCharm: JetBrains API → 最新 CE 版本号
-pub async fn pycharm_latest() -> Option<String> {
-    let client = make_client().ok()?;
-    let resp: serde_json::Value = client
-        .get("https://data.services.jetbrains.com/products/releases?code=PCC&latest=true&type=release")
-        .send()
-        .await
-        .ok()?
-        .json()
-        .await
-        .ok()?;
-    resp["PCC"][0]["version"].as_str().map(|s| s.to_string())
+pub async fn pycharm_latest() -> VersionResult<String> {
+    let resp: serde_json::Value = fetch_json(
+        "https://data.services.jetbrains.com/products/releases?code=PCC&latest=true&type=release",
+    )
+    .await?;
+    parse_pycharm_latest(&resp)
+}
+
+/// JetBrains releases API → PCC 第一项的版本号
+fn parse_pycharm_latest(resp: &serde_json::Value) -> VersionResult<String> {
+    Ok(json_str(&resp["PCC"][0], "version")?.to_string())
 }
 
 /// Claude Code: GCS → 最新版本号
-pub async fn claude_code_latest() -> Option<String> {
-    let client = make_client().ok()?;
-    let resp = client
-        .get(format!(
-            "{}/latest",
-            "https://storage.googleapis.com/claude-code-dist-86c565f3-f756-42ad-8dfa-d59b1c096819/claude-code-releases"
-        ))
-        .send()
-        .await
-        .ok()?
-        .text()
-        .await
-        .ok()?;
-    Some(resp.trim().to_string())
+pub async fn claude_code_latest() -> VersionResult<String> {
+    let text = get(&format!(
+        "{}/latest",
+        "https://storage.googleapis.com/claude-code-dist-86c565f3-f756-42ad-8dfa-d59b1c096819/claude-code-releases"
+    ))
+    .await?
+    .text()
+    .await?;
+    parse_plain_version(&text)
+}
+
+/// 纯文本版本号（如 "2.1.3\n"），内容不像版本号时（如 HTML 错误页）返回 Parse 错误
+fn parse_plain_version(text: &str) -> VersionResult<String> {
+    let ver = text.trim();
+    let valid = ver.starts_with(|c: char| c.is_ascii_digit())
+        && ver.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'));
+    if valid {
+        Ok(ver.to_string())
+    } else {
+        Err(VersionError::Parse("响应不是版本号".to_string()))
+    }
 }
 
 /// Redis: GitHub API (redis-windows) → 最新版本号（如 "8.6.1"）
+pub async fn redis_latest() -> VersionResult<String> {
+    let tag = github_latest("redis-windows/redis-windows").await?; // "8.6.1.1"
+    parse_redis_tag(&tag)
+}
+
 /// tag 格式: "8.6.1.1" → 取前三段 "8.6.1"
-pub async fn redis_latest() -> Option<String> {
-    let client = make_client().ok()?;
-    let resp: serde_json::Value = client
-        .get("https://api.github.com/repos/redis-windows/redis-windows/releases/latest")
-        .header("User-Agent", "hudo")
-        .send()
-        .await
-        .ok()?
-        .json()
-        .await
-        .ok()?;
-    let tag = resp["tag_name"].as_str()?; // "8.6.1.1"
-    // 取前三段作为 Redis 版本号
+fn parse_redis_tag(tag: &str) -> VersionResult<String> {
+    if !tag.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(VersionError::Parse(format!("tag {}", tag)));
+    }
     let parts: Vec<&str> = tag.split('.').collect();
     if parts.len() >= 3 {
-        Some(parts[..3].join("."))
+        Ok(parts[..3].join("."))
     } else {
-        Some(tag.to_string())
+        Ok(tag.to_string())
     }
 }
 
 /// MinGW-w64 via winlibs：GitHub Releases → (tag, filename, gcc_version)
+pub async fn mingw_latest() -> VersionResult<(String, String, String)> {
+    let release = github_latest_release("brechtsanders/winlibs_mingw").await?;
+    parse_mingw_release(&release)
+}
+
 /// tag 格式: "15.2.0posix-13.0.0-ucrt-r6"
 /// 文件格式: "winlibs-x86_64-posix-seh-gcc-15.2.0-mingw-w64ucrt-13.0.0-r6.zip"
-pub async fn mingw_latest() -> Option<(String, String, String)> {
-    let client = make_client().ok()?;
-    let resp: serde_json::Value = client
-        .get("https://api.github.com/repos/brechtsanders/winlibs_mingw/releases/latest")
-        .header("User-Agent", "hudo")
-        .send()
-        .await
-        .ok()?
-        .json()
-        .await
-        .ok()?;
-    let tag = resp["tag_name"].as_str()?.to_string();
+fn parse_mingw_release(release: &serde_json::Value) -> VersionResult<(String, String, String)> {
+    let tag = release_tag(release)?;
     // 从 assets 找 x86_64 posix ucrt zip
-    let filename = resp["assets"]
-        .as_array()?
+    let filename = release["assets"]
+        .as_array()
+        .ok_or_else(|| VersionError::Parse("缺少 assets".to_string()))?
         .iter()
         .filter_map(|a| a["name"].as_str())
         .find(|name| {
@@ -202,34 +536,321 @@ pub async fn mingw_latest() -> Option<(String, String, String)> {
                 && name.contains("posix")
                 && name.contains("ucrt")
                 && name.ends_with(".zip")
-        })?
+        })
+        .ok_or(VersionError::NotFound)?
         .to_string();
     // 从文件名提取 gcc 版本: "winlibs-x86_64-posix-seh-gcc-15.2.0-mingw-..."
     let gcc_version = filename
-        .strip_prefix("winlibs-x86_64-posix-seh-gcc-")?
-        .split('-')
-        .next()?
+        .strip_prefix("winlibs-x86_64-posix-seh-gcc-")
+        .and_then(|s| s.split('-').next())
+        .ok_or_else(|| VersionError::Parse(format!("文件名 {}", filename)))?
         .to_string();
-    Some((tag, filename, gcc_version))
+    Ok((tag, filename, gcc_version))
+}
+
+/// SQLite: sqlite.org 下载页 → (版本号, 相对路径)，如 ("3.50.4", "2025/sqlite-tools-win-x64-3500400.zip")
+pub async fn sqlite_latest() -> VersionResult<(String, String)> {
+    let html = get("https://www.sqlite.org/download.html").await?.text().await?;
+    parse_sqlite_download_page(&html)
+}
+
+/// 下载页的 HTML 注释中有机器可读的产品清单，每行形如
+/// `PRODUCT,3.50.4,2025/sqlite-tools-win-x64-3500400.zip,6281569,<sha3>`
+fn parse_sqlite_download_page(html: &str) -> VersionResult<(String, String)> {
+    html.lines()
+        .filter_map(|line| line.trim().strip_prefix("PRODUCT,"))
+        .filter_map(|rest| {
+            let mut fields = rest.split(',');
+            Some((fields.next()?, fields.next()?))
+        })
+        .find(|(_, path)| path.contains("/sqlite-tools-win-x64-") && path.ends_with(".zip"))
+        .map(|(ver, path)| (ver.to_string(), path.to_string()))
+        .ok_or(VersionError::NotFound)
+}
+
+// ── 版本列表（hudo install <tool> --pick） ──────────────────────────────────
+
+/// 版本列表最多返回的条数
+const VERSION_LIST_LIMIT: usize = 15;
+
+/// 支持 --pick 交互选择版本的工具
+pub const PICKABLE_TOOLS: &[&str] = &["git", "gh", "go", "pgsql", "maven", "gradle", "pycharm"];
+
+/// 按工具 id 获取最近的发布版本（新 → 旧），不支持的工具返回 NotFound
+pub async fn recent_versions(tool_id: &str) -> VersionResult<Vec<String>> {
+    match tool_id {
+        "git" => git_versions().await,
+        "gh" => gh_versions().await,
+        "go" => go_versions().await,
+        "pgsql" => pgsql_versions().await,
+        "maven" => maven_versions().await,
+        "gradle" => gradle_versions().await,
+        "pycharm" => pycharm_versions().await,
+        _ => Err(VersionError::NotFound),
+    }
+}
+
+/// 按 versions.<键>（见 config::VERSION_KEYS）获取最新版本号，没有查询接口的键（如 mysql）返回 NotFound
+pub async fn latest_for_key(key: &str) -> VersionResult<String> {
+    match key {
+        "git" => git_latest().await,
+        "gh" => gh_latest().await,
+        "go" => go_latest().await,
+        "fnm" => parse_v_tag(&github_latest("Schniz/fnm").await?),
+        "pgsql" => pgsql_latest().await,
+        "pycharm" => pycharm_latest().await,
+        "maven" => maven_latest().await,
+        "gradle" => gradle_latest().await,
+        "claude_code" => claude_code_latest().await,
+        "redis" => redis_latest().await,
+        _ => Err(VersionError::NotFound),
+    }
+}
+
+/// GitHub API → 最近的 releases 原始列表
+async fn github_releases(repo: &str) -> VersionResult<Vec<serde_json::Value>> {
+    fetch_json(&format!(
+        "https://api.github.com/repos/{}/releases?per_page={}",
+        repo,
+        VERSION_LIST_LIMIT * 2
+    ))
+    .await
+}
+
+/// GitHub CLI：最近的正式版本（如 "2.87.3"）
+pub async fn gh_versions() -> VersionResult<Vec<String>> {
+    let releases = github_releases("cli/cli").await?;
+    Ok(parse_release_versions(&releases, |t| parse_v_tag(t).ok()))
+}
+
+/// Git：最近的正式版本（tag 按 parse_git_tag 转换，如 "2.47.1.2"）
+pub async fn git_versions() -> VersionResult<Vec<String>> {
+    let releases = github_releases("git-for-windows/git").await?;
+    Ok(parse_release_versions(&releases, |t| parse_git_tag(t).ok()))
+}
+
+/// Maven：最近的正式版本（tag "maven-3.9.9" → "3.9.9"）
+pub async fn maven_versions() -> VersionResult<Vec<String>> {
+    let releases = github_releases("apache/maven").await?;
+    Ok(parse_release_versions(&releases, |t| parse_maven_tag(t).ok()))
+}
+
+/// Go：go.dev/dl 全量列表中的稳定版（如 "1.24.0"）
+pub async fn go_versions() -> VersionResult<Vec<String>> {
+    let resp: Vec<serde_json::Value> =
+        fetch_json("https://go.dev/dl/?mode=json&include=all").await?;
+    Ok(parse_go_versions(&resp))
+}
+
+/// PostgreSQL：仍受支持的各大版本的最新小版本（如 "18.2"、"17.8"）
+pub async fn pgsql_versions() -> VersionResult<Vec<String>> {
+    let resp: Vec<serde_json::Value> =
+        fetch_json("https://www.postgresql.org/versions.json").await?;
+    Ok(parse_pgsql_versions(&resp))
+}
+
+/// Gradle：services.gradle.org 全量列表中的正式版
+pub async fn gradle_versions() -> VersionResult<Vec<String>> {
+    let resp: Vec<serde_json::Value> =
+        fetch_json("https://services.gradle.org/versions/all").await?;
+    Ok(parse_gradle_versions(&resp))
+}
+
+/// PyCharm：JetBrains API → 最近的 CE 正式版本
+pub async fn pycharm_versions() -> VersionResult<Vec<String>> {
+    let resp: serde_json::Value = fetch_json(
+        "https://data.services.jetbrains.com/products/releases?code=PCC&type=release",
+    )
+    .await?;
+    Ok(parse_pycharm_versions(&resp))
+}
+
+/// GitHub releases → 版本列表：排除 draft / prerelease，tag 由 `map_tag` 转换（返回 None 则跳过）
+fn parse_release_versions(
+    releases: &[serde_json::Value],
+    map_tag: impl Fn(&str) -> Option<String>,
+) -> Vec<String> {
+    releases
+        .iter()
+        .filter(|r| r["draft"].as_bool() != Some(true) && r["prerelease"].as_bool() != Some(true))
+        .filter_map(|r| map_tag(r["tag_name"].as_str()?))
+        .take(VERSION_LIST_LIMIT)
+        .collect()
+}
+
+/// go.dev/dl/?include=all → 稳定版本号（去掉 "go" 前缀）
+fn parse_go_versions(resp: &[serde_json::Value]) -> Vec<String> {
+    resp.iter()
+        .filter(|v| v["stable"].as_bool() == Some(true))
+        .filter_map(|v| v["version"].as_str()?.strip_prefix("go").map(|s| s.to_string()))
+        .take(VERSION_LIST_LIMIT)
+        .collect()
+}
+
+/// versions.json → 受支持大版本的 "major.latestMinor"
+fn parse_pgsql_versions(resp: &[serde_json::Value]) -> Vec<String> {
+    resp.iter()
+        .filter(|v| v["supported"].as_bool() == Some(true))
+        .filter_map(|v| {
+            let major = v["major"].as_str()?;
+            let minor = v["latestMinor"].as_str()?;
+            Some(format!("{}.{}", major, minor))
+        })
+        .take(VERSION_LIST_LIMIT)
+        .collect()
+}
+
+/// services.gradle.org/versions/all → 正式版（排除 snapshot / nightly / broken / rc / milestone）
+fn parse_gradle_versions(resp: &[serde_json::Value]) -> Vec<String> {
+    resp.iter()
+        .filter(|v| {
+            v["snapshot"].as_bool() != Some(true)
+                && v["nightly"].as_bool() != Some(true)
+                && v["broken"].as_bool() != Some(true)
+                && v["rcFor"].as_str().unwrap_or("").is_empty()
+                && v["milestoneFor"].as_str().unwrap_or("").is_empty()
+        })
+        .filter_map(|v| v["version"].as_str().map(|s| s.to_string()))
+        .take(VERSION_LIST_LIMIT)
+        .collect()
+}
+
+/// JetBrains releases API → PCC 版本号列表
+fn parse_pycharm_versions(resp: &serde_json::Value) -> Vec<String> {
+    resp["PCC"]
+        .as_array()
+        .map(|a| {
+            a.iter()
+                .filter_map(|r| r["version"].as_str().map(|s| s.to_string()))
+                .take(VERSION_LIST_LIMIT)
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 /// hudo 自身：GitHub Releases → 最新版本号（如 "0.2.0"）
-pub async fn hudo_latest() -> Option<String> {
-    let client = make_client().ok()?;
-    let resp: serde_json::Value = client
-        .get(&format!(
-            "https://api.github.com/repos/{}/releases/latest",
-            GITHUB_REPO
-        ))
-        .header("User-Agent", "hudo")
-        .send()
-        .await
+/// 不经过版本缓存：`hudo update` 需要实时结果，菜单提示另有一天的缓存
+pub async fn hudo_latest() -> VersionResult<String> {
+    let url = format!("https://api.github.com/repos/{}/releases/latest", GITHUB_REPO);
+    let release = fetch_value(&url).await?;
+    parse_v_tag(&release_tag(&release)?) // "v0.2.0"
+}
+
+/// 更新检查缓存: %USERPROFILE%\.hudo\update-check.json
+#[derive(serde::Serialize, serde::Deserialize)]
+struct UpdateCheckCache {
+    /// 上次检查时间（Unix 秒）
+    checked_at: u64,
+    latest: String,
+}
+
+/// 更新检查缓存有效期：一天
+const UPDATE_CHECK_TTL: u64 = 24 * 60 * 60;
+
+/// 检查 hudo 是否有新版本（结果缓存一天，查询最多等待 2 秒），有新版本时返回版本号
+pub async fn hudo_update_available() -> Option<String> {
+    let path = crate::paths::update_check_path().ok()?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
         .ok()?
-        .json()
-        .await
-        .ok()?;
-    let tag = resp["tag_name"].as_str()?; // "v0.2.0"
-    Some(tag.trim_start_matches('v').to_string())
+        .as_secs();
+
+    let cached = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|s| serde_json::from_str::<UpdateCheckCache>(&s).ok())
+        .filter(|c| now.saturating_sub(c.checked_at) < UPDATE_CHECK_TTL);
+
+    let latest = match cached {
+        Some(c) => c.latest,
+        None => {
+            let latest = tokio::time::timeout(std::time::Duration::from_secs(2), hudo_latest())
+                .await
+                .ok()?
+                .ok()?;
+            let cache = UpdateCheckCache {
+                checked_at: now,
+                latest: latest.clone(),
+            };
+            if let Ok(s) = serde_json::to_string(&cache) {
+                std::fs::write(&path, s).ok();
+            }
+            latest
+        }
+    };
+
+    is_newer(&latest, env!("CARGO_PKG_VERSION")).then_some(latest)
+}
+
+/// 上次检查更新的结果：(检查时间的 Unix 秒, 当时的最新版本)，从未检查时为 None
+pub fn last_update_check() -> Option<(u64, String)> {
+    let path = crate::paths::update_check_path().ok()?;
+    let cache: UpdateCheckCache = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    Some((cache.checked_at, cache.latest))
+}
+
+/// 按数字逐段比较版本号："0.10.0" > "0.9.1"
+fn is_newer(latest: &str, current: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|p| p.parse().unwrap_or(0))
+            .collect()
+    };
+    parse(latest) > parse(current)
+}
+
+/// 从版本字符串中提取纯版本号
+pub fn extract_version(ver: &str) -> String {
+    let trimmed = ver.trim();
+    // 尝试找到以数字开头的 token（如 "git version 2.47.1" → "2.47.1"）
+    trimmed
+        .split_whitespace()
+        .find(|s| s.starts_with(|c: char| c.is_ascii_digit()))
+        .unwrap_or(trimmed)
+        .to_string()
+}
+
+/// 提取版本号中的各数字段，兼容检测命令输出的各种格式：
+/// "git version 2.47.1.windows.2" → [2, 47, 1, 2]，"go version go1.24.1 windows/amd64" → [1, 24, 1]
+fn version_numbers(ver: &str) -> Vec<u64> {
+    let short = extract_version(ver);
+    // 没有以数字开头的 token 时 extract_version 返回原串，再取第一个含数字的 token
+    let token = short
+        .split_whitespace()
+        .find(|s| s.contains(|c: char| c.is_ascii_digit()))
+        .unwrap_or("");
+    let start = token.find(|c: char| c.is_ascii_digit()).unwrap_or(token.len());
+
+    let mut numbers = Vec::new();
+    for part in token[start..].split('.') {
+        let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+        if digits.is_empty() {
+            continue; // "windows" 之类的非数字段
+        }
+        numbers.push(digits.parse().unwrap_or(u64::MAX));
+        if digits.len() < part.len() {
+            break; // "0_392"、"4-1"：数字后的后缀不再是版本号
+        }
+    }
+    numbers
+}
+
+/// 规范化为固定版本时使用的形式："2.47.1.windows.2" → "2.47.1.2"，无法解析时返回 None
+pub fn normalize_version(ver: &str) -> Option<String> {
+    let numbers = version_numbers(ver);
+    if numbers.is_empty() {
+        return None;
+    }
+    let parts: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+    Some(parts.join("."))
+}
+
+/// 本地版本与档案版本是否一致：逐段比较到较短的一方（档案记录 "21" 时 "21.0.2" 也算一致），
+/// 任一方无法解析时不视为不一致
+pub fn versions_match(local: &str, wanted: &str) -> bool {
+    let (a, b) = (version_numbers(local), version_numbers(wanted));
+    let n = a.len().min(b.len());
+    n == 0 || a[..n] == b[..n]
 }
 
 #[cfg(test)]
@@ -240,7 +861,7 @@ mod tests {
     fn test_parse_git_tag_with_patch() {
         assert_eq!(
             parse_git_tag("v2.47.1.windows.2"),
-            Some("2.47.1.2".to_string())
+            Ok("2.47.1.2".to_string())
         );
     }
 
@@ -248,13 +869,321 @@ mod tests {
     fn test_parse_git_tag_without_patch() {
         assert_eq!(
             parse_git_tag("v2.53.0.windows.1"),
-            Some("2.53.0".to_string())
+            Ok("2.53.0".to_string())
         );
     }
 
     #[test]
     fn test_parse_git_tag_invalid() {
-        assert_eq!(parse_git_tag("invalid"), None);
-        assert_eq!(parse_git_tag("2.47.1"), None);
+        assert!(matches!(parse_git_tag("invalid"), Err(VersionError::Parse(_))));
+        assert!(matches!(parse_git_tag("2.47.1"), Err(VersionError::Parse(_))));
+        assert!(matches!(parse_git_tag("v2.47.1.windows"), Err(VersionError::Parse(_))));
+    }
+
+    #[test]
+    fn test_parse_release_versions_skips_prerelease() {
+        let releases = vec![
+            serde_json::json!({"tag_name": "v2.48.0-rc1.windows.1", "prerelease": true, "draft": false}),
+            serde_json::json!({"tag_name": "v2.47.1.windows.2", "prerelease": false, "draft": false}),
+            serde_json::json!({"tag_name": "v2.47.0.windows.1", "prerelease": false, "draft": true}),
+            serde_json::json!({"tag_name": "v2.46.2.windows.1", "prerelease": false, "draft": false}),
+        ];
+        assert_eq!(
+            parse_release_versions(&releases, |t| parse_git_tag(t).ok()),
+            vec!["2.47.1.2", "2.46.2"]
+        );
+    }
+
+    #[test]
+    fn test_parse_release_versions_maps_tag() {
+        let releases = vec![
+            serde_json::json!({"tag_name": "maven-3.9.9"}),
+            serde_json::json!({"tag_name": "unrelated"}),
+            serde_json::json!({"tag_name": "maven-3.9.8"}),
+        ];
+        let versions =
+            parse_release_versions(&releases, |t| t.strip_prefix("maven-").map(|s| s.to_string()));
+        assert_eq!(versions, vec!["3.9.9", "3.9.8"]);
+    }
+
+    #[test]
+    fn test_parse_release_versions_bounded() {
+        let releases: Vec<_> = (0..40)
+            .map(|i| serde_json::json!({"tag_name": format!("v2.{}.0", 40 - i)}))
+            .collect();
+        let versions =
+            parse_release_versions(&releases, |t| Some(t.trim_start_matches('v').to_string()));
+        assert_eq!(versions.len(), VERSION_LIST_LIMIT);
+        assert_eq!(versions[0], "2.40.0");
+    }
+
+    #[test]
+    fn test_parse_go_versions() {
+        let resp = vec![
+            serde_json::json!({"version": "go1.25rc1", "stable": false}),
+            serde_json::json!({"version": "go1.24.1", "stable": true}),
+            serde_json::json!({"version": "go1.23.7", "stable": true}),
+        ];
+        assert_eq!(parse_go_versions(&resp), vec!["1.24.1", "1.23.7"]);
+    }
+
+    #[test]
+    fn test_parse_pgsql_versions() {
+        let resp = vec![
+            serde_json::json!({"major": "18", "latestMinor": "2", "supported": true}),
+            serde_json::json!({"major": "17", "latestMinor": "8", "supported": true}),
+            serde_json::json!({"major": "12", "latestMinor": "22", "supported": false}),
+        ];
+        assert_eq!(parse_pgsql_versions(&resp), vec!["18.2", "17.8"]);
+    }
+
+    #[test]
+    fn test_parse_gradle_versions() {
+        let resp = vec![
+            serde_json::json!({"version": "8.13-20250101", "snapshot": true, "nightly": true, "rcFor": "", "milestoneFor": ""}),
+            serde_json::json!({"version": "8.13-rc-1", "snapshot": false, "nightly": false, "rcFor": "8.13", "milestoneFor": ""}),
+            serde_json::json!({"version": "8.12.1", "snapshot": false, "nightly": false, "rcFor": "", "milestoneFor": ""}),
+            serde_json::json!({"version": "8.12", "snapshot": false, "nightly": false, "rcFor": "", "milestoneFor": ""}),
+        ];
+        assert_eq!(parse_gradle_versions(&resp), vec!["8.12.1", "8.12"]);
+    }
+
+    #[test]
+    fn test_parse_pycharm_versions() {
+        let resp = serde_json::json!({
+            "PCC": [{"version": "2024.3.2"}, {"version": "2024.3.1"}]
+        });
+        assert_eq!(parse_pycharm_versions(&resp), vec!["2024.3.2", "2024.3.1"]);
+        assert!(parse_pycharm_versions(&serde_json::json!({})).is_empty());
+    }
+
+    /// GitHub /releases/latest 响应片段
+    const MINGW_RELEASE_FIXTURE: &str = r#"{
+        "tag_name": "15.2.0posix-13.0.0-ucrt-r6",
+        "draft": false,
+        "prerelease": false,
+        "assets": [
+            {"name": "winlibs-i686-posix-dwarf-gcc-15.2.0-mingw-w64ucrt-13.0.0-r6.zip"},
+            {"name": "winlibs-x86_64-posix-seh-gcc-15.2.0-mingw-w64ucrt-13.0.0-r6.7z"},
+            {"name": "winlibs-x86_64-posix-seh-gcc-15.2.0-mingw-w64ucrt-13.0.0-r6.zip"}
+        ]
+    }"#;
+
+    /// postgresql.org/versions.json 响应片段
+    const PGSQL_VERSIONS_FIXTURE: &str = r#"[
+        {"major": "18", "latestMinor": "2", "current": true, "supported": true},
+        {"major": "17", "latestMinor": "8", "current": false, "supported": true}
+    ]"#;
+
+    #[test]
+    fn test_parse_mingw_release_fixture() {
+        let release: serde_json::Value = serde_json::from_str(MINGW_RELEASE_FIXTURE).unwrap();
+        let (tag, filename, gcc) = parse_mingw_release(&release).unwrap();
+        assert_eq!(tag, "15.2.0posix-13.0.0-ucrt-r6");
+        assert_eq!(
+            filename,
+            "winlibs-x86_64-posix-seh-gcc-15.2.0-mingw-w64ucrt-13.0.0-r6.zip"
+        );
+        assert_eq!(gcc, "15.2.0");
+        assert!(matches!(
+            parse_mingw_release(&serde_json::json!({"message": "Not Found"})),
+            Err(VersionError::Parse(_))
+        ));
+        assert!(matches!(
+            parse_mingw_release(&serde_json::json!({"tag_name": "x", "assets": "oops"})),
+            Err(VersionError::Parse(_))
+        ));
+        assert_eq!(
+            parse_mingw_release(&serde_json::json!({"tag_name": "x", "assets": [{"name": "a.7z"}]})),
+            Err(VersionError::NotFound)
+        );
+    }
+
+    #[test]
+    fn test_parse_sqlite_download_page() {
+        let html = "<!-- Download product data for scripts to read\n\
+            PRODUCT,3.50.4,2025/sqlite-amalgamation-3500400.zip,2807529,abc\n\
+            PRODUCT,3.50.4,2025/sqlite-tools-win-x64-3500400.zip,6281569,def\n\
+            PRODUCT,3.50.4,2025/sqlite-tools-linux-x64-3500400.zip,4056875,123\n\
+            -->";
+        assert_eq!(
+            parse_sqlite_download_page(html),
+            Ok(("3.50.4".to_string(), "2025/sqlite-tools-win-x64-3500400.zip".to_string()))
+        );
+        assert_eq!(parse_sqlite_download_page("<html>502</html>"), Err(VersionError::NotFound));
+    }
+
+    #[test]
+    fn test_parse_pgsql_latest_fixture() {
+        let resp: Vec<serde_json::Value> = serde_json::from_str(PGSQL_VERSIONS_FIXTURE).unwrap();
+        assert_eq!(parse_pgsql_latest(&resp), Ok("18.2".to_string()));
+        assert!(matches!(parse_pgsql_latest(&[]), Err(VersionError::Parse(_))));
+        let no_minor = vec![serde_json::json!({"major": "18", "current": true})];
+        assert!(matches!(parse_pgsql_latest(&no_minor), Err(VersionError::Parse(_))));
+    }
+
+    #[test]
+    fn test_parse_redis_tag() {
+        assert_eq!(parse_redis_tag("8.6.1.1"), Ok("8.6.1".to_string()));
+        assert_eq!(parse_redis_tag("8.6"), Ok("8.6".to_string()));
+        assert!(matches!(parse_redis_tag("latest"), Err(VersionError::Parse(_))));
+    }
+
+    #[test]
+    fn test_classify_response() {
+        assert_eq!(classify_response(200, Some("59")), Ok(()));
+        assert_eq!(classify_response(403, Some("0")), Err(VersionError::RateLimited));
+        assert_eq!(classify_response(429, None), Err(VersionError::RateLimited));
+        // 403 但仍有额度（如仓库无权限）不算限流
+        assert_eq!(classify_response(403, Some("42")), Err(VersionError::Http(403)));
+        assert_eq!(classify_response(500, None), Err(VersionError::Http(500)));
+        assert_eq!(classify_response(404, None), Err(VersionError::NotFound));
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(VersionError::Offline.is_transient());
+        assert!(VersionError::Network("请求超时".to_string()).is_transient());
+        assert!(VersionError::Http(502).is_transient());
+        assert!(!VersionError::Http(403).is_transient());
+        assert!(!VersionError::RateLimited.is_transient());
+        assert!(!VersionError::NotFound.is_transient());
+    }
+
+    #[test]
+    fn test_rate_limit_message() {
+        let msg = rate_limit_message(false, Some(90));
+        assert!(msg.contains("约 2 分钟后恢复"));
+        assert!(msg.contains("GITHUB_TOKEN"));
+        assert!(!rate_limit_message(true, None).contains("GITHUB_TOKEN"));
+    }
+
+    #[test]
+    fn test_token_sources() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| pairs.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+        assert_eq!(token_from_env(env(&[("GH_TOKEN", "gho_b"), ("GITHUB_TOKEN", "ghp_a")])).as_deref(), Some("ghp_a"));
+        assert_eq!(token_from_env(env(&[("GITHUB_TOKEN", " "), ("GH_TOKEN", "gho_b\n")])).as_deref(), Some("gho_b"));
+        assert_eq!(token_from_env(env(&[])), None);
+
+        assert_eq!(parse_gh_token(b"gho_abc123\r\n").as_deref(), Some("gho_abc123"));
+        assert_eq!(parse_gh_token(b""), None);
+        assert_eq!(parse_gh_token(b"no oauth token found"), None);
+    }
+
+    #[test]
+    fn test_version_cache_ttl() {
+        let url = "https://api.github.com/repos/cli/cli/releases/latest";
+        let mut cache = VersionCache::default();
+        cache.insert(url, 1_000, serde_json::json!({"tag_name": "v2.87.3"}));
+
+        assert!(cache.fresh(url, 1_000 + VERSION_CACHE_TTL - 1).is_some());
+        assert!(cache.fresh(url, 1_000 + VERSION_CACHE_TTL).is_none());
+        // 过期后仍可作为请求失败时的兜底
+        assert_eq!(
+            cache.any(url).map(release_tag),
+            Some(Ok("v2.87.3".to_string()))
+        );
+        assert!(cache.any("https://go.dev/dl/?mode=json").is_none());
+    }
+
+    #[test]
+    fn test_version_cache_roundtrip() {
+        let path = std::env::temp_dir()
+            .join(format!("hudo-version-test-{}", std::process::id()))
+            .join(VERSION_CACHE_FILE);
+        let release: serde_json::Value = serde_json::from_str(MINGW_RELEASE_FIXTURE).unwrap();
+        let mut cache = VersionCache::default();
+        cache.insert("mingw", 42, release.clone());
+        cache.save(&path);
+
+        let loaded = VersionCache::load(&path);
+        assert_eq!(loaded.fresh("mingw", 42), Some(&release));
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+
+        // 文件不存在或损坏时视为空缓存
+        assert!(VersionCache::load(&path).entries.is_empty());
+    }
+
+    #[test]
+    fn test_parse_tag_prefixes() {
+        assert_eq!(parse_v_tag("v2.87.3"), Ok("2.87.3".to_string()));
+        assert!(matches!(parse_v_tag("nightly"), Err(VersionError::Parse(_))));
+        assert_eq!(parse_maven_tag("maven-3.9.9"), Ok("3.9.9".to_string()));
+        assert!(matches!(parse_maven_tag("3.9.9"), Err(VersionError::Parse(_))));
+    }
+
+    #[test]
+    fn test_release_tag_malformed() {
+        assert!(matches!(
+            release_tag(&serde_json::json!({"message": "Bad credentials"})),
+            Err(VersionError::Parse(_))
+        ));
+        assert!(matches!(
+            release_tag(&serde_json::json!({"tag_name": 42})),
+            Err(VersionError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_go_latest() {
+        let resp = vec![serde_json::json!({"version": "go1.24.0", "stable": true})];
+        assert_eq!(parse_go_latest(&resp), Ok("1.24.0".to_string()));
+        assert!(matches!(parse_go_latest(&[]), Err(VersionError::Parse(_))));
+        let bad = vec![serde_json::json!({"version": "1.24.0"})];
+        assert!(matches!(parse_go_latest(&bad), Err(VersionError::Parse(_))));
+    }
+
+    #[test]
+    fn test_parse_pycharm_latest() {
+        let resp = serde_json::json!({"PCC": [{"version": "2024.3.2"}]});
+        assert_eq!(parse_pycharm_latest(&resp), Ok("2024.3.2".to_string()));
+        assert!(matches!(
+            parse_pycharm_latest(&serde_json::json!({"PCC": []})),
+            Err(VersionError::Parse(_))
+        ));
+        assert!(matches!(
+            parse_pycharm_latest(&serde_json::json!([1, 2])),
+            Err(VersionError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_plain_version() {
+        assert_eq!(parse_plain_version("2.1.3\n"), Ok("2.1.3".to_string()));
+        assert!(matches!(
+            parse_plain_version("<html><body>Error</body></html>"),
+            Err(VersionError::Parse(_))
+        ));
+        assert!(matches!(parse_plain_version(""), Err(VersionError::Parse(_))));
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("0.10.0", "0.9.1"));
+        assert!(is_newer("v1.0.0", "0.9.9"));
+        assert!(!is_newer("0.2.0", "0.2.0"));
+        assert!(!is_newer("0.1.9", "0.2.0"));
+    }
+
+    #[test]
+    fn test_normalize_version() {
+        assert_eq!(normalize_version("git version 2.47.1.windows.2").as_deref(), Some("2.47.1.2"));
+        assert_eq!(normalize_version("go version go1.24.1 windows/amd64").as_deref(), Some("1.24.1"));
+        assert_eq!(normalize_version("openjdk 21.0.2 2024-01-16").as_deref(), Some("21.0.2"));
+        assert_eq!(normalize_version("Redis server v=7.2.4 sha=00000000").as_deref(), Some("7.2.4"));
+        assert_eq!(normalize_version("v22.11.0").as_deref(), Some("22.11.0"));
+        assert_eq!(normalize_version("unknown"), None);
+    }
+
+    #[test]
+    fn test_versions_match() {
+        assert!(versions_match("git version 2.47.1.windows.2", "2.47.1"));
+        assert!(versions_match("2.47.1.windows.2", "2.47.1.2"));
+        assert!(versions_match("openjdk 21.0.2 2024-01-16", "21"));
+        assert!(!versions_match("go version go1.24.1 windows/amd64", "1.22.0"));
+        assert!(!versions_match("2.47.1.windows.2", "2.48.0"));
+        assert!(versions_match("unknown", "1.0.0"));
     }
 }
@@ -0,0 +1,287 @@
+//! 安装子进程的无输出超时：fnm install、rustup-init、静默安装程序等长时间既无输出也未退出时询问如何处理
+
+use std::io::{Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+
+/// 子进程长时间无输出也未退出时用户的选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stalled {
+    /// 再等一个超时周期
+    Wait,
+    /// 结束进程树后重新运行
+    Retry,
+    /// 结束进程树，安装失败
+    Abort,
+}
+
+/// 子进程卡住时由谁决定如何处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnStall {
+    /// 询问用户；非交互终端中无法询问时中止
+    Ask,
+    /// 不询问（-y）：再等这么多个周期仍没有输出则中止
+    Wait(u32),
+}
+
+/// run_watched 的超时设置，由 InstallConfig::watch / silent_watch 按配置生成
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watch {
+    /// 无输出超时，None 表示一直等待（子进程直接使用终端，不经转发）
+    pub idle: Option<Duration>,
+    /// 子进程本来就没有输出（msiexec /quiet 等静默安装程序）：保留终端，从启动起计时
+    pub silent: bool,
+    pub on_stall: OnStall,
+}
+
+/// 用户选择结束卡住的子进程；安装流程据此失败，不记录安装状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aborted {
+    pub what: String,
+    pub idle: Duration,
+}
+
+impl std::fmt::Display for Aborted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} 超过 {}没有输出，已结束", self.what, format_idle(self.idle))
+    }
+}
+
+impl std::error::Error for Aborted {}
+
+/// 错误（含外层附加的上下文）是否来自用户结束卡住的子进程
+pub fn is_aborted(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<Aborted>().is_some()
+}
+
+/// 整分钟显示为分钟，其余按整秒（不足一秒的部分进位）
+fn format_idle(idle: Duration) -> String {
+    match idle.as_secs() + u64::from(idle.subsec_nanos() > 0) {
+        s if s >= 60 && s % 60 == 0 => format!("{} 分钟", s / 60),
+        s => format!("{} 秒", s),
+    }
+}
+
+/// 运行安装步骤的子进程（fnm install、rustup-init、mysqld --initialize 等）并等待结束：
+/// 输出经管道转发到终端（子进程因此看不到终端，进度条和颜色可能不显示），
+/// 超过 watch.idle 既没有输出也没有退出时按 watch.on_stall 询问继续等待、结束并重试还是中止
+pub fn run_watched(cmd: &mut Command, what: &str, watch: Watch) -> Result<ExitStatus> {
+    run_watched_with(cmd, what, watch, ask_stalled)
+}
+
+/// run_watched 的实现，OnStall::Ask 时调用 ask 决定如何处理
+pub fn run_watched_with(
+    cmd: &mut Command,
+    what: &str,
+    watch: Watch,
+    mut ask: impl FnMut(&str, Duration) -> Stalled,
+) -> Result<ExitStatus> {
+    let Some(idle) = watch.idle else {
+        return cmd.status().with_context(|| format!("无法启动 {}", what));
+    };
+    if !watch.silent {
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    }
+    'run: loop {
+        let mut child = cmd.spawn().with_context(|| format!("无法启动 {}", what))?;
+        let mut waited = 0;
+        let last_output = Arc::new(Mutex::new(Instant::now()));
+        let forwarders = [
+            child.stdout.take().map(|out| forward(out, std::io::stdout(), &last_output)),
+            child.stderr.take().map(|err| forward(err, std::io::stderr(), &last_output)),
+        ];
+        loop {
+            if let Some(status) = child.try_wait().with_context(|| format!("等待 {} 失败", what))? {
+                settle(forwarders);
+                return Ok(status);
+            }
+            let quiet = last_output.lock().map(|t| t.elapsed()).unwrap_or_default();
+            if quiet >= idle {
+                let choice = match watch.on_stall {
+                    OnStall::Ask => ask(what, idle),
+                    OnStall::Wait(rounds) if waited < rounds => {
+                        waited += 1;
+                        crate::ui::print_warning(&format!(
+                            "{} 已有 {}没有输出，继续等待（{}/{}）",
+                            what,
+                            format_idle(idle * waited),
+                            waited,
+                            rounds
+                        ));
+                        Stalled::Wait
+                    }
+                    OnStall::Wait(_) => Stalled::Abort,
+                };
+                match choice {
+                    Stalled::Wait => *last_output.lock().unwrap() = Instant::now(),
+                    Stalled::Retry => {
+                        kill_tree(&mut child);
+                        settle(forwarders);
+                        crate::ui::print_info(&format!("重新运行 {}...", what));
+                        continue 'run;
+                    }
+                    Stalled::Abort => {
+                        kill_tree(&mut child);
+                        settle(forwarders);
+                        return Err(Aborted { what: what.to_string(), idle: idle * (waited + 1) }.into());
+                    }
+                }
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+    }
+}
+
+/// 把子进程的输出原样转发（不按行缓冲，进度条的 \r 也能显示），并记录最近一次输出的时间
+fn forward(
+    mut from: impl Read + Send + 'static,
+    mut to: impl Write + Send + 'static,
+    last_output: &Arc<Mutex<Instant>>,
+) -> std::thread::JoinHandle<()> {
+    let last_output = last_output.clone();
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        while let Ok(n) = from.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            if let Ok(mut t) = last_output.lock() {
+                *t = Instant::now();
+            }
+            to.write_all(&buf[..n]).ok();
+            to.flush().ok();
+        }
+    })
+}
+
+/// 等转发线程把剩余输出写完；子进程派生的后台进程（如服务）可能一直占着管道，最多等 1 秒
+fn settle(forwarders: [Option<std::thread::JoinHandle<()>>; 2]) {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    for handle in forwarders.into_iter().flatten() {
+        while !handle.is_finished() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        if handle.is_finished() {
+            handle.join().ok();
+        }
+    }
+}
+
+fn ask_stalled(what: &str, idle: Duration) -> Stalled {
+    crate::ui::print_warning(&format!("{} 已有 {}没有输出", what, format_idle(idle)));
+    let choice = dialoguer::Select::with_theme(&crate::ui::theme())
+        .with_prompt("如何处理？")
+        .items(&["继续等待", "结束并重试", "结束并中止安装"])
+        .default(0)
+        .interact_opt();
+    match choice {
+        Ok(Some(0)) => Stalled::Wait,
+        Ok(Some(1)) => Stalled::Retry,
+        _ => Stalled::Abort,
+    }
+}
+
+/// 结束子进程及其派生的进程（Windows 上用 taskkill /T，安装程序常再启动子进程干活）
+fn kill_tree(child: &mut Child) {
+    #[cfg(windows)]
+    {
+        let pid = child.id().to_string();
+        let killed = Command::new("taskkill")
+            .args(["/PID", &pid, "/T", "/F"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|s| s.success());
+        if !killed {
+            child.kill().ok();
+        }
+    }
+    #[cfg(not(windows))]
+    child.kill().ok();
+    child.wait().ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 先等待 secs 秒再输出 done 的命令
+    fn sleep_then_echo(secs: u32) -> Command {
+        #[cfg(windows)]
+        let (shell, script) = ("cmd", format!("ping -n {} 127.0.0.1 >nul & echo done", secs + 1));
+        #[cfg(not(windows))]
+        let (shell, script) = ("sh", format!("sleep {}; echo done", secs));
+        let mut command = Command::new(shell);
+        command.args([if cfg!(windows) { "/c" } else { "-c" }, &script]);
+        command
+    }
+
+    fn watch(millis: u64, on_stall: OnStall) -> Watch {
+        Watch { idle: Some(Duration::from_millis(millis)), silent: false, on_stall }
+    }
+
+    #[test]
+    fn test_run_watched() {
+        let idle = watch(300, OnStall::Ask);
+        let mut asked = 0;
+        // 有输出并按时退出：不询问
+        let status = run_watched_with(&mut sleep_then_echo(0), "echo", idle, |_, _| {
+            asked += 1;
+            Stalled::Abort
+        });
+        assert!(status.unwrap().success());
+        assert_eq!(asked, 0);
+
+        // 卡住：继续等待直到退出
+        let status = run_watched_with(&mut sleep_then_echo(1), "sleep", idle, |_, _| {
+            asked += 1;
+            Stalled::Wait
+        });
+        assert!(status.unwrap().success());
+        assert!(asked >= 1);
+
+        // 结束并重试一次，再次卡住时中止
+        let started = Instant::now();
+        let mut answers = vec![Stalled::Abort, Stalled::Retry];
+        let err = run_watched_with(&mut sleep_then_echo(30), "sleep", watch(1000, OnStall::Ask), |what, _| {
+            assert_eq!(what, "sleep");
+            answers.pop().unwrap()
+        })
+        .unwrap_err();
+        assert!(answers.is_empty());
+        assert!(is_aborted(&err));
+        assert_eq!(err.to_string(), "sleep 超过 1 秒没有输出，已结束");
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_run_watched_unattended() {
+        let never_ask = |_: &str, _: Duration| -> Stalled { panic!("-y 时不应询问") };
+        // 等待的周期内退出：成功
+        let status = run_watched_with(&mut sleep_then_echo(1), "sleep", watch(300, OnStall::Wait(10)), never_ask);
+        assert!(status.unwrap().success());
+
+        // 再等一个周期仍无输出：中止，报告总共等待的时长
+        let started = Instant::now();
+        let err = run_watched_with(&mut sleep_then_echo(30), "sleep", watch(1000, OnStall::Wait(1)), never_ask)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "sleep 超过 2 秒没有输出，已结束");
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        // 静默安装程序不转发输出，同样按超时处理
+        let silent = Watch { silent: true, ..watch(300, OnStall::Wait(0)) };
+        let err = run_watched_with(&mut sleep_then_echo(30), "msiexec", silent, never_ask).unwrap_err();
+        assert!(is_aborted(&err));
+    }
+
+    #[test]
+    fn test_format_idle() {
+        assert_eq!(format_idle(Duration::from_secs(600)), "10 分钟");
+        assert_eq!(format_idle(Duration::from_secs(90)), "90 秒");
+        assert_eq!(format_idle(Duration::from_millis(300)), "1 秒");
+        assert_eq!(format_idle(Duration::from_millis(1500)), "2 秒");
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use winreg::enums::*;
+use winreg::RegKey;
+
+/// 当前用户的文件类型注册位置（写入 HKCU，无需管理员权限）
+pub const USER_CLASSES: &str = r"Software\Classes";
+
+/// 右键菜单的三个位置：(相对 Classes 的 shell 路径, 传给程序的参数占位符)
+/// 文件夹空白处使用 %V 表示当前目录，文件/文件夹使用 %1
+const MENU_LOCATIONS: [(&str, &str); 3] = [
+    (r"*\shell", "%1"),                    // 右键文件
+    (r"Directory\shell", "%1"),            // 右键文件夹
+    (r"Directory\Background\shell", "%V"), // 右键文件夹空白处
+];
+
+fn hkcu() -> RegKey {
+    RegKey::predef(HKEY_CURRENT_USER)
+}
+
+fn icon_value(exe: &Path) -> String {
+    format!("{},0", exe.display())
+}
+
+/// 在文件、文件夹、文件夹空白处的右键菜单中注册 verb，点击后用 exe 打开
+pub fn register_context_menu(classes: &str, verb: &str, label: &str, exe: &Path) -> Result<()> {
+    for (location, placeholder) in MENU_LOCATIONS {
+        let key_path = format!(r"{}\{}\{}", classes, location, verb);
+        let (shell_key, _) = hkcu()
+            .create_subkey(&key_path)
+            .with_context(|| format!("创建注册表项 {} 失败", key_path))?;
+        shell_key.set_value("", &label)?;
+        shell_key.set_value("Icon", &icon_value(exe))?;
+
+        let (cmd_key, _) = shell_key.create_subkey("command")?;
+        cmd_key.set_value("", &format!("\"{}\" \"{}\"", exe.display(), placeholder))?;
+    }
+    Ok(())
+}
+
+/// 删除 register_context_menu 写入的三个右键菜单项
+pub fn unregister_context_menu(classes: &str, verb: &str) {
+    for (location, _) in MENU_LOCATIONS {
+        let _ = hkcu().delete_subkey_all(format!(r"{}\{}\{}", classes, location, verb));
+    }
+}
+
+/// 右键文件菜单项当前的命令行，未注册时返回 None
+pub fn context_menu_command(classes: &str, verb: &str) -> Option<String> {
+    let key_path = format!(r"{}\{}\{}\command", classes, MENU_LOCATIONS[0].0, verb);
+    hkcu().open_subkey(key_path).ok()?.get_value("").ok()
+}
+
+/// 注册 ProgID，并把它加入各扩展名的 OpenWithProgids：
+/// 只出现在「打开方式」列表中，不修改扩展名的默认程序
+pub fn register_open_with(
+    classes: &str,
+    prog_id: &str,
+    friendly_name: &str,
+    exe: &Path,
+    extensions: &[&str],
+) -> Result<()> {
+    let prog_path = format!(r"{}\{}", classes, prog_id);
+    let (prog_key, _) = hkcu()
+        .create_subkey(&prog_path)
+        .with_context(|| format!("创建注册表项 {} 失败", prog_path))?;
+    prog_key.set_value("", &friendly_name)?;
+    let (icon_key, _) = prog_key.create_subkey("DefaultIcon")?;
+    icon_key.set_value("", &icon_value(exe))?;
+    let (cmd_key, _) = prog_key.create_subkey(r"shell\open\command")?;
+    cmd_key.set_value("", &format!("\"{}\" \"%1\"", exe.display()))?;
+
+    for ext in extensions {
+        let key_path = format!(r"{}\.{}\OpenWithProgids", classes, ext);
+        let (key, _) = hkcu()
+            .create_subkey(&key_path)
+            .with_context(|| format!("创建注册表项 {} 失败", key_path))?;
+        key.set_value(prog_id, &"")?;
+    }
+    Ok(())
+}
+
+/// 删除 register_open_with 写入的 ProgID 和各扩展名下的 OpenWithProgids 值，
+/// 扩展名键本身及其他程序的值保持不变
+pub fn unregister_open_with(classes: &str, prog_id: &str, extensions: &[&str]) {
+    let _ = hkcu().delete_subkey_all(format!(r"{}\{}", classes, prog_id));
+    for ext in extensions {
+        let key_path = format!(r"{}\.{}\OpenWithProgids", classes, ext);
+        if let Ok(key) = hkcu().open_subkey_with_flags(&key_path, KEY_SET_VALUE) {
+            let _ = key.delete_value(prog_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 每个测试只写入各自的临时键，结束后整体删除
+    #[test]
+    fn test_context_menu_roundtrip() {
+        let classes = r"Software\hudo-test-winshell-menu";
+        let exe = Path::new(r"D:\hudo\ide\vscode\Code.exe");
+
+        register_context_menu(classes, "VSCode", "通过 Code 打开", exe).unwrap();
+        assert_eq!(
+            context_menu_command(classes, "VSCode").as_deref(),
+            Some(r#""D:\hudo\ide\vscode\Code.exe" "%1""#)
+        );
+        let background: String = hkcu()
+            .open_subkey(format!(r"{}\Directory\Background\shell\VSCode\command", classes))
+            .unwrap()
+            .get_value("")
+            .unwrap();
+        assert!(background.ends_with(r#""%V""#));
+
+        unregister_context_menu(classes, "VSCode");
+        assert_eq!(context_menu_command(classes, "VSCode"), None);
+        let _ = hkcu().delete_subkey_all(classes);
+    }
+
+    #[test]
+    fn test_open_with_keeps_other_progids() {
+        let classes = r"Software\hudo-test-winshell-open-with";
+        let exe = Path::new(r"D:\hudo\ide\vscode\Code.exe");
+        let (other, _) = hkcu()
+            .create_subkey(format!(r"{}\.txt\OpenWithProgids", classes))
+            .unwrap();
+        other.set_value("txtfile", &"").unwrap();
+
+        register_open_with(classes, "hudo.vscode", "VS Code", exe, &["txt", "md"]).unwrap();
+        let txt = hkcu().open_subkey(format!(r"{}\.txt\OpenWithProgids", classes)).unwrap();
+        assert!(txt.get_value::<String, _>("hudo.vscode").is_ok());
+
+        unregister_open_with(classes, "hudo.vscode", &["txt", "md"]);
+        let txt = hkcu().open_subkey(format!(r"{}\.txt\OpenWithProgids", classes)).unwrap();
+        assert!(txt.get_value::<String, _>("hudo.vscode").is_err());
+        assert!(txt.get_value::<String, _>("txtfile").is_ok());
+        assert!(hkcu().open_subkey(format!(r"{}\hudo.vscode", classes)).is_err());
+        let _ = hkcu().delete_subkey_all(classes);
+    }
+}
@@ -0,0 +1,327 @@
+//! WSL 相关：检查 hudo 的 PATH 是否通过 interop 泄漏进 WSL，以及把环境档案转换成 Linux 安装脚本
+//!
+//! WSL 默认把 Windows PATH 追加到发行版的 PATH 末尾，发行版里没装的命令（go、node 等）
+//! 会直接调用到 Windows 版本，路径和换行符都不对。hudo wsl check 找出这些路径并给出过滤方法；
+//! hudo export --target wsl 按已安装的工具生成尽力而为的 apt / rustup / nvm 安装脚本。
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+use crate::config::HudoConfig;
+use crate::env::EnvExport;
+use crate::registry::InstallRegistry;
+
+/// 写入 ~/.profile 的片段首行，用来判断是否已经写过
+pub const PROFILE_MARKER: &str = "# hudo wsl check:";
+
+/// /etc/wsl.conf 中关闭 Windows PATH 追加的设置
+pub const WSL_CONF_SNIPPET: &str = "[interop]\nappendWindowsPath = false\n";
+
+/// 解码 wsl.exe 自身的输出：默认是 UTF-16LE（设置 WSL_UTF8=1 时为 UTF-8）
+pub fn decode_wsl_output(bytes: &[u8]) -> String {
+    let looks_utf16 = bytes.len() >= 2 && bytes.len().is_multiple_of(2) && bytes.iter().skip(1).step_by(2).any(|b| *b == 0);
+    if looks_utf16 {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// 解析 `wsl -l -q` 的输出：每行一个发行版名称；Docker Desktop 自带的内部发行版不算
+pub fn parse_distros(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(|line| line.trim_matches(|c: char| c.is_whitespace() || c == '\u{feff}' || c == '\0'))
+        .filter(|name| !name.is_empty() && !name.starts_with("docker-desktop"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Windows 路径在 WSL 中的挂载路径：`D:\hudo\lang\go\bin` → `/mnt/d/hudo/lang/go/bin`；
+/// UNC 等无法挂载的路径返回 None
+pub fn to_wsl_path(path: &str) -> Option<String> {
+    let mut chars = path.chars();
+    let drive = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+    if chars.next() != Some(':') {
+        return None;
+    }
+    let rest = chars.as_str().replace('\\', "/");
+    let rest = rest.trim_end_matches('/');
+    Some(format!("/mnt/{}{}", drive.to_ascii_lowercase(), rest))
+}
+
+/// hudo 添加到 Windows PATH 的目录（%VAR% 已展开）
+pub fn hudo_path_entries(config: &HudoConfig) -> Result<Vec<String>> {
+    let reg = InstallRegistry::load(&config.state_path())?;
+    let export = EnvExport::collect(&reg, &crate::installer::all_installers(), config, None);
+    let lookup = |name: &str| export.vars.get(name).cloned().or_else(|| std::env::var(name).ok());
+    Ok(export.path.iter().map(|p| crate::env::expand_vars(p, &lookup)).collect())
+}
+
+/// 发行版 PATH（冒号分隔）中出现的 hudo 目录，返回其 WSL 路径
+pub fn leaking_entries(hudo_entries: &[String], wsl_path: &str) -> Vec<String> {
+    let present: Vec<String> = wsl_path
+        .trim()
+        .split(':')
+        .map(|p| p.trim_end_matches('/').to_lowercase())
+        .collect();
+    let mut leaks = Vec::new();
+    for mounted in hudo_entries.iter().filter_map(|e| to_wsl_path(e)) {
+        if present.contains(&mounted.to_lowercase()) && !leaks.contains(&mounted) {
+            leaks.push(mounted);
+        }
+    }
+    leaks
+}
+
+/// POSIX shell 单引号字面量
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// 追加到 ~/.profile 的片段：从 PATH 中去掉 hudo 的目录，保留其余 Windows PATH（如 VS Code 的 code）
+pub fn profile_snippet(leaks: &[String]) -> String {
+    let patterns: Vec<String> = leaks.iter().map(|p| format!("-e {}", sh_quote(p))).collect();
+    format!(
+        "\n{} 去掉 Windows 中由 hudo 安装的工具，避免遮蔽 WSL 内的同名命令\n\
+         PATH=$(printf '%s' \"$PATH\" | tr ':' '\\n' | grep -v -i -x -F {} | paste -sd: -)\n\
+         export PATH\n",
+        PROFILE_MARKER,
+        patterns.join(" ")
+    )
+}
+
+/// Linux 中对应工具的安装方式
+enum LinuxInstall {
+    /// apt 包名
+    Apt(String),
+    /// 单独执行的命令（按版本生成）
+    Script(String),
+    /// 不在 WSL 中安装的原因
+    Skip(&'static str),
+}
+
+fn major(version: &str) -> &str {
+    version.split('.').next().unwrap_or(version)
+}
+
+fn linux_install(id: &str, version: &str) -> Option<LinuxInstall> {
+    use LinuxInstall::*;
+    let numeric = version.chars().next().is_some_and(|c| c.is_ascii_digit());
+    Some(match id {
+        "git" => Apt("git".to_string()),
+        "gh" => Apt("gh".to_string()),
+        "c" => Apt("build-essential".to_string()),
+        "maven" => Apt("maven".to_string()),
+        "gradle" => Apt("gradle".to_string()),
+        "mysql" => Apt("mysql-server".to_string()),
+        "pgsql" => Apt("postgresql".to_string()),
+        "redis" => Apt("redis-server".to_string()),
+        "sqlite" => Apt("sqlite3".to_string()),
+        "jdk" if numeric => Apt(format!("openjdk-{}-jdk", major(version))),
+        "jdk" => Apt("default-jdk".to_string()),
+        "go" if numeric => Script(format!(
+            "curl -fsSL https://go.dev/dl/go{}.linux-amd64.tar.gz | sudo tar -C /usr/local -xz\n\
+             echo 'export PATH=\"/usr/local/go/bin:$PATH\"' >> ~/.profile",
+            version
+        )),
+        "go" => Apt("golang-go".to_string()),
+        "rust" => Script("curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y".to_string()),
+        "uv" => Script("curl -LsSf https://astral.sh/uv/install.sh | sh".to_string()),
+        "miniconda" => Script(
+            "curl -fsSLo /tmp/miniconda.sh https://repo.anaconda.com/miniconda/Miniconda3-latest-Linux-x86_64.sh\n\
+             bash /tmp/miniconda.sh -b -p \"$HOME/miniconda3\""
+                .to_string(),
+        ),
+        "nodejs" => Script(format!(
+            "curl -fsSL https://raw.githubusercontent.com/nvm-sh/nvm/v0.40.3/install.sh | bash\n\
+             . \"$HOME/.nvm/nvm.sh\"\n\
+             nvm install {}",
+            if numeric { major(version) } else { "--lts" }
+        )),
+        "bun" => Script("curl -fsSL https://bun.sh/install | bash".to_string()),
+        "claude-code" => Script("npm install -g @anthropic-ai/claude-code".to_string()),
+        "vscode" => Skip("在 Windows 中用 VS Code 的 WSL 扩展连接即可"),
+        "pycharm" => Skip("PyCharm 专业版可直接使用 WSL 中的解释器"),
+        "chrome" => Skip("浏览器无需在 WSL 中安装"),
+        _ => return None,
+    })
+}
+
+/// 环境档案中的工具 → bash 安装脚本（尽力而为，版本只精确到官方渠道能提供的程度）
+pub fn linux_script(tools: &BTreeMap<String, String>) -> String {
+    let mut apt = Vec::new();
+    let mut scripts = Vec::new();
+    let mut skipped = Vec::new();
+    for (id, version) in tools {
+        match linux_install(id, version) {
+            Some(LinuxInstall::Apt(pkg)) => apt.push(pkg),
+            Some(LinuxInstall::Script(cmd)) => scripts.push((id.as_str(), cmd)),
+            Some(LinuxInstall::Skip(reason)) => skipped.push(format!("{}：{}", id, reason)),
+            None => skipped.push(format!("{}：没有对应的 Linux 安装方式", id)),
+        }
+    }
+    // nvm 安装的 node 就绪后才能 npm install -g
+    scripts.sort_by_key(|(id, _)| *id == "claude-code");
+
+    let mut out = String::from("#!/usr/bin/env bash\n");
+    out.push_str("# 由 hudo export --target wsl 生成：按 Windows 上已安装的工具在 WSL 中安装 Linux 版本。\n");
+    out.push_str("# 尽力而为：包名与版本以发行版仓库与官方安装脚本为准，运行前请先检查。\n");
+    out.push_str("set -e\n");
+    if !apt.is_empty() {
+        out.push_str(&format!("\nsudo apt-get update\nsudo apt-get install -y {}\n", apt.join(" ")));
+    }
+    for (id, cmd) in &scripts {
+        out.push_str(&format!("\n# {}\n{}\n", id, cmd));
+    }
+    if !skipped.is_empty() {
+        out.push_str("\n# 未处理:\n");
+        for line in &skipped {
+            out.push_str(&format!("#   {}\n", line));
+        }
+    }
+    out
+}
+
+/// 已安装的 WSL 发行版；未安装 WSL 时为空
+#[cfg(windows)]
+pub fn list_distros() -> Vec<String> {
+    match std::process::Command::new("wsl").args(["-l", "-q"]).output() {
+        Ok(out) if out.status.success() => parse_distros(&decode_wsl_output(&out.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+/// 发行版中非登录 shell 看到的 PATH（已包含 interop 追加的 Windows PATH）
+#[cfg(windows)]
+pub fn distro_path(distro: &str) -> Result<String> {
+    use anyhow::Context;
+    let out = std::process::Command::new("wsl")
+        .args(["-d", distro, "-e", "sh", "-c", "printf '%s' \"$PATH\""])
+        .output()
+        .with_context(|| format!("无法在 {} 中执行命令", distro))?;
+    if !out.status.success() {
+        anyhow::bail!("无法读取 {} 的 PATH: {}", distro, decode_wsl_output(&out.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+/// 在发行版中把内容追加到文件（`root` 时以 root 身份，用于 /etc/wsl.conf）；
+/// 文件中已有 `marker` 时不重复追加，返回是否写入
+#[cfg(windows)]
+pub fn append_in_distro(distro: &str, file: &str, content: &str, marker: &str, root: bool) -> Result<bool> {
+    use anyhow::Context;
+    use std::io::Write;
+    let script = format!(
+        "grep -qF {marker} {file} 2>/dev/null && exit 3; cat >> {file}",
+        marker = sh_quote(marker),
+        file = file
+    );
+    let mut cmd = std::process::Command::new("wsl");
+    cmd.args(["-d", distro]);
+    if root {
+        cmd.args(["-u", "root"]);
+    }
+    let mut child = cmd
+        .args(["-e", "sh", "-c", &script])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .with_context(|| format!("无法在 {} 中执行命令", distro))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(content.as_bytes()).context("写入内容失败")?;
+    }
+    let out = child.wait_with_output().context("等待 wsl 结束失败")?;
+    match out.status.code() {
+        Some(0) => Ok(true),
+        Some(3) => Ok(false),
+        _ => anyhow::bail!("写入 {} 失败: {}", file, decode_wsl_output(&out.stderr).trim()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_distros() {
+        // wsl -l -q 的默认输出：UTF-16LE，CRLF，可能带 BOM
+        let raw: Vec<u8> = "\u{feff}Ubuntu-22.04\r\ndocker-desktop\r\nDebian\r\n\r\n"
+            .encode_utf16()
+            .flat_map(|u| u.to_le_bytes())
+            .collect();
+        assert_eq!(parse_distros(&decode_wsl_output(&raw)), vec!["Ubuntu-22.04", "Debian"]);
+        // WSL_UTF8=1 时为 UTF-8
+        assert_eq!(parse_distros(&decode_wsl_output("Ubuntu\n".as_bytes())), vec!["Ubuntu"]);
+        assert!(parse_distros("").is_empty());
+    }
+
+    #[test]
+    fn test_leaking_entries() {
+        assert_eq!(to_wsl_path(r"D:\hudo\lang\go\bin\").as_deref(), Some("/mnt/d/hudo/lang/go/bin"));
+        assert_eq!(to_wsl_path(r"C:\Users\张 三\hudo").as_deref(), Some("/mnt/c/Users/张 三/hudo"));
+        assert_eq!(to_wsl_path(r"\\server\share\bin"), None);
+
+        let entries = vec![
+            r"D:\hudo\lang\go\bin".to_string(),
+            r"D:\hudo\tools\fnm".to_string(),
+            r"D:\hudo\tools\redis".to_string(),
+        ];
+        let wsl_path = "/usr/local/bin:/usr/bin:/mnt/c/WINDOWS/system32:/mnt/d/HUDO/lang/go/bin:/mnt/d/hudo/tools/fnm/";
+        assert_eq!(leaking_entries(&entries, wsl_path), vec!["/mnt/d/hudo/lang/go/bin", "/mnt/d/hudo/tools/fnm"]);
+        assert!(leaking_entries(&entries, "/usr/bin:/bin").is_empty());
+    }
+
+    #[test]
+    fn test_profile_snippet() {
+        let snippet = profile_snippet(&["/mnt/d/hudo/lang/go/bin".to_string(), "/mnt/c/Users/O'Neil/fnm".to_string()]);
+        assert!(snippet.starts_with(&format!("\n{}", PROFILE_MARKER)));
+        assert!(snippet.contains(r"grep -v -i -x -F -e '/mnt/d/hudo/lang/go/bin' -e '/mnt/c/Users/O'\''Neil/fnm' | paste -sd: -)"));
+        assert!(snippet.ends_with("export PATH\n"));
+    }
+
+    #[test]
+    fn test_linux_script() {
+        let tools: BTreeMap<String, String> = [
+            ("claude-code", "1.0.30"),
+            ("git", "2.47.1"),
+            ("go", "1.24.0"),
+            ("jdk", "21.0.5"),
+            ("mysql", "8.4.3"),
+            ("nodejs", "22.11.0"),
+            ("vscode", "1.96.0"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert_eq!(
+            linux_script(&tools),
+            concat!(
+                "#!/usr/bin/env bash\n",
+                "# 由 hudo export --target wsl 生成：按 Windows 上已安装的工具在 WSL 中安装 Linux 版本。\n",
+                "# 尽力而为：包名与版本以发行版仓库与官方安装脚本为准，运行前请先检查。\n",
+                "set -e\n",
+                "\n",
+                "sudo apt-get update\n",
+                "sudo apt-get install -y git openjdk-21-jdk mysql-server\n",
+                "\n",
+                "# go\n",
+                "curl -fsSL https://go.dev/dl/go1.24.0.linux-amd64.tar.gz | sudo tar -C /usr/local -xz\n",
+                "echo 'export PATH=\"/usr/local/go/bin:$PATH\"' >> ~/.profile\n",
+                "\n",
+                "# nodejs\n",
+                "curl -fsSL https://raw.githubusercontent.com/nvm-sh/nvm/v0.40.3/install.sh | bash\n",
+                ". \"$HOME/.nvm/nvm.sh\"\n",
+                "nvm install 22\n",
+                "\n",
+                "# claude-code\n",
+                "npm install -g @anthropic-ai/claude-code\n",
+                "\n",
+                "# 未处理:\n",
+                "#   vscode：在 Windows 中用 VS Code 的 WSL 扩展连接即可\n",
+            )
+        );
+    }
+}
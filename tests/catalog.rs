@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use hudo::catalog;
+use hudo::config::HudoConfig;
+use hudo::installer::{all_installers, DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+
+/// 只提供元数据的安装器，catalog 结构与平台无关
+struct FakeMaven;
+
+#[async_trait::async_trait]
+impl Installer for FakeMaven {
+    fn info(&self) -> ToolInfo {
+        ToolInfo {
+            id: "maven",
+            name: "Maven",
+            description: "Java 构建工具",
+            homepage: Some("https://maven.apache.org"),
+            post_install_tips: &[],
+        }
+    }
+
+    async fn detect_installed(&self, _ctx: &InstallContext<'_>) -> anyhow::Result<DetectResult> {
+        Ok(DetectResult::NotInstalled)
+    }
+
+    fn resolve_download(&self, config: &HudoConfig) -> (String, String) {
+        let version = config.versions.maven.as_deref().unwrap_or("3.9.9");
+        (format!("https://dlcdn.apache.org/maven/{}.zip", version), String::new())
+    }
+
+    fn default_version(&self) -> Option<&'static str> {
+        Some("3.9.9")
+    }
+
+    async fn install(&self, _ctx: &InstallContext<'_>) -> anyhow::Result<InstallResult> {
+        anyhow::bail!("不支持")
+    }
+
+    fn dependencies(&self) -> &'static [&'static str] {
+        &["jdk"]
+    }
+
+    fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
+        vec![
+            EnvAction::Set { name: "MAVEN_HOME".to_string(), value: install_path.to_string_lossy().to_string() },
+            EnvAction::AppendPath { path: format!("{}\\bin", install_path.to_string_lossy()) },
+        ]
+    }
+}
+
+// catalog 的结构是对外接口，这里的快照变化意味着外部集成可能需要跟着改
+#[test]
+fn test_catalog_json_snapshot() {
+    let installers: Vec<Box<dyn Installer>> = vec![Box::new(FakeMaven)];
+    let json = serde_json::to_string_pretty(&catalog::build(&installers)).unwrap();
+    let expected = r#"{
+  "format": 1,
+  "hudo_version": "VERSION",
+  "tools": [
+    {
+      "id": "maven",
+      "name": "Maven",
+      "description": "Java 构建工具",
+      "homepage": "https://maven.apache.org",
+      "category": "language",
+      "category_label": "语言环境",
+      "default_version": "3.9.9",
+      "pickable": true,
+      "download_url": "https://dlcdn.apache.org/maven/3.9.9.zip",
+      "dependencies": [
+        "jdk"
+      ],
+      "env": [
+        {
+          "kind": "set",
+          "name": "MAVEN_HOME",
+          "value": "{install_dir}"
+        },
+        {
+          "kind": "path",
+          "path": "{install_dir}\\bin"
+        }
+      ],
+      "service": null,
+      "arm64_native": false
+    }
+  ]
+}"#;
+    assert_eq!(json, expected.replace("VERSION", env!("CARGO_PKG_VERSION")));
+}
+
+#[test]
+fn test_catalog_covers_all_installers() {
+    let installers = all_installers();
+    let catalog = catalog::build(&installers);
+    assert_eq!(catalog.tools.len(), installers.len());
+
+    // 每个工具的字段集合与快照一致，下载地址不含本机路径
+    let value = serde_json::to_value(&catalog).unwrap();
+    let fake: Vec<Box<dyn Installer>> = vec![Box::new(FakeMaven)];
+    let shape = serde_json::to_value(catalog::build(&fake)).unwrap();
+    let keys = |v: &serde_json::Value| v.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+    for tool in value["tools"].as_array().unwrap() {
+        assert_eq!(keys(tool), keys(&shape["tools"][0]));
+        assert!(tool["download_url"].as_str().unwrap().starts_with("https://"), "{}", tool["id"]);
+    }
+}
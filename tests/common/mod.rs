@@ -0,0 +1,259 @@
+//! 集成测试共用的模拟下载服务、临时安装根目录和 zip 构造工具
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use hudo::config::HudoConfig;
+
+/// 单个路由的响应
+#[derive(Clone, Default)]
+struct Route {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+    /// 有值时返回 ETag 头，请求带相同的 If-None-Match 时返回 304
+    etag: Option<String>,
+    /// 有值时返回 302 跳转到该地址
+    location: Option<String>,
+    /// 不返回 Content-Length，靠关闭连接结束响应体
+    no_length: bool,
+    /// HEAD 请求返回 405（部分镜像不支持 HEAD）
+    reject_head: bool,
+}
+
+/// 本地 HTTP 服务：按请求路径（含查询串）返回预先登记的内容，未登记的路径返回 404
+pub struct MockServer {
+    base: String,
+    routes: Arc<Mutex<HashMap<String, Route>>>,
+    hits: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("无法监听本地端口");
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let routes: Arc<Mutex<HashMap<String, Route>>> = Arc::default();
+        let hits: Arc<Mutex<Vec<String>>> = Arc::default();
+
+        let (r, h) = (routes.clone(), hits.clone());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (r, h) = (r.clone(), h.clone());
+                std::thread::spawn(move || serve(stream, &r, &h));
+            }
+        });
+        Self { base, routes, hits }
+    }
+
+    /// 服务地址，如 "http://127.0.0.1:52345"
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// 完整 URL
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    pub fn file(&self, path: &str, body: Vec<u8>) {
+        self.route(path, 200, "application/octet-stream", body);
+    }
+
+    /// 带 ETag 的文件，用于测试缓存重新验证
+    pub fn file_with_etag(&self, path: &str, body: Vec<u8>, etag: &str) {
+        self.routes.lock().unwrap().insert(
+            path.to_string(),
+            Route {
+                status: 200,
+                content_type: "application/octet-stream",
+                body,
+                etag: Some(etag.to_string()),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// 不带 Content-Length 的文件（部分镜像和跳转后的 CDN 如此）
+    pub fn file_without_length(&self, path: &str, body: Vec<u8>) {
+        self.routes.lock().unwrap().insert(
+            path.to_string(),
+            Route {
+                status: 200,
+                content_type: "application/octet-stream",
+                body,
+                no_length: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// 不支持 HEAD 的文件：HEAD 返回 405，GET 正常返回
+    pub fn file_rejecting_head(&self, path: &str, body: Vec<u8>) {
+        self.routes.lock().unwrap().insert(
+            path.to_string(),
+            Route {
+                status: 200,
+                content_type: "application/octet-stream",
+                body,
+                reject_head: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// 302 跳转到另一个地址（完整 URL）
+    pub fn redirect(&self, path: &str, to: &str) {
+        self.routes.lock().unwrap().insert(
+            path.to_string(),
+            Route {
+                status: 302,
+                content_type: "text/plain",
+                location: Some(to.to_string()),
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn json(&self, path: &str, value: serde_json::Value) {
+        self.route(path, 200, "application/json", value.to_string().into_bytes());
+    }
+
+    pub fn route(&self, path: &str, status: u16, content_type: &'static str, body: Vec<u8>) {
+        self.routes.lock().unwrap().insert(
+            path.to_string(),
+            Route {
+                status,
+                content_type,
+                body,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// 某个路径被请求的次数
+    pub fn hits(&self, path: &str) -> usize {
+        self.hits.lock().unwrap().iter().filter(|p| *p == path).count()
+    }
+}
+
+fn serve(mut stream: TcpStream, routes: &Mutex<HashMap<String, Route>>, hits: &Mutex<Vec<String>>) {
+    // 只需要请求行，读到头部结束即可
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        match stream.read(&mut chunk) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+    let head = String::from_utf8_lossy(&buf);
+    let method = head.split_whitespace().next().unwrap_or("GET").to_string();
+    let path = head.split_whitespace().nth(1).unwrap_or("/").to_string();
+    hits.lock().unwrap().push(path.clone());
+
+    let mut route = routes.lock().unwrap().get(&path).cloned().unwrap_or(Route {
+        status: 404,
+        content_type: "text/plain",
+        body: b"not found".to_vec(),
+        ..Default::default()
+    });
+    let if_none_match = head.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.eq_ignore_ascii_case("if-none-match").then(|| value.trim().to_string())
+    });
+    if route.etag.is_some() && route.etag == if_none_match {
+        route.status = 304;
+        route.body.clear();
+    }
+    if route.reject_head && method == "HEAD" {
+        route.status = 405;
+        route.body.clear();
+    }
+    let etag = route
+        .etag
+        .as_ref()
+        .map(|e| format!("ETag: {}\r\n", e))
+        .unwrap_or_default();
+    let location = route
+        .location
+        .as_ref()
+        .map(|l| format!("Location: {}\r\n", l))
+        .unwrap_or_default();
+    let length = match route.no_length {
+        true => String::new(),
+        false => format!("Content-Length: {}\r\n", route.body.len()),
+    };
+    let reason = match route.status {
+        200 => "OK",
+        302 => "Found",
+        _ => "Error",
+    };
+    let header = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\n{}{}{}Connection: close\r\n\r\n",
+        route.status, reason, route.content_type, length, etag, location
+    );
+    let _ = stream.write_all(header.as_bytes());
+    let _ = stream.write_all(&route.body);
+}
+
+/// 临时安装根目录，离开作用域时删除
+pub struct TempRoot {
+    path: PathBuf,
+}
+
+impl TempRoot {
+    pub fn new(name: &str) -> Self {
+        Self::new_in(&std::env::temp_dir(), name)
+    }
+
+    /// 在指定目录下创建，用于让两个目录落在不同文件系统上
+    pub fn new_in(base: &Path, name: &str) -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = base.join(format!(
+            "hudo-it-{}-{}-{}",
+            name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::remove_dir_all(&path).ok();
+        std::fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// root_dir 指向该目录、其余为默认值的配置
+    pub fn config(&self) -> HudoConfig {
+        let root = self.path.to_string_lossy().replace('\\', "\\\\");
+        toml::from_str(&format!("root_dir = \"{}\"", root)).unwrap()
+    }
+}
+
+impl Drop for TempRoot {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.path).ok();
+    }
+}
+
+/// 构造 zip 文件内容：名称以 "/" 结尾的条目为目录
+pub fn make_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    // 固定修改时间，同样的内容每次生成的字节相同（默认取当前时间，跨秒时会不一致）
+    let options = zip::write::SimpleFileOptions::default().last_modified_time(zip::DateTime::default());
+    for (name, content) in entries {
+        if name.ends_with('/') {
+            writer.add_directory(*name, options).unwrap();
+        } else {
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(content).unwrap();
+        }
+    }
+    writer.finish().unwrap().into_inner()
+}
@@ -0,0 +1,416 @@
+mod common;
+
+use common::{make_zip, MockServer, TempRoot};
+use hudo::download;
+
+/// 内容为 tag 的 zip：下载后会检查扩展名对应的魔数，.zip 必须是真正的 zip
+fn zipped(tag: &str) -> Vec<u8> {
+    make_zip(&[("content.txt", tag.as_bytes())])
+}
+
+#[tokio::test]
+async fn test_download_then_cache_hit() {
+    let server = MockServer::start();
+    server.file("/pkg/tool.zip", zipped("payload"));
+    let root = TempRoot::new("download");
+    let cache = root.path().join("cache");
+
+    let path = download::download(&server.url("/pkg/tool.zip"), &cache, "tool.zip")
+        .await
+        .unwrap();
+    assert_eq!(path, cache.join("tool.zip"));
+    assert_eq!(std::fs::read(&path).unwrap(), zipped("payload"));
+    assert!(!cache.join("tool.zip.tmp").exists());
+
+    // 缓存命中时不再请求
+    download::download(&server.url("/pkg/tool.zip"), &cache, "tool.zip")
+        .await
+        .unwrap();
+    assert_eq!(server.hits("/pkg/tool.zip"), 1);
+}
+
+#[tokio::test]
+async fn test_download_same_filename_from_other_url_is_miss() {
+    // pycharm-community.zip 之类：文件名不随版本变化，地址变了就要重新下载
+    let server = MockServer::start();
+    server.file("/v1/pycharm.zip", zipped("v1"));
+    server.file("/v2/pycharm.zip", zipped("v2"));
+    let root = TempRoot::new("download-url-key");
+    let cache = root.path().join("cache");
+
+    download::download(&server.url("/v1/pycharm.zip"), &cache, "pycharm-community.zip")
+        .await
+        .unwrap();
+    let path = download::download(&server.url("/v2/pycharm.zip"), &cache, "pycharm-community.zip")
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), zipped("v2"));
+    assert!(cache.join("pycharm-community.zip.meta.json").exists());
+    // export --lock 只为来源地址一致的缓存计算 SHA256
+    assert!(download::is_cached(&server.url("/v2/pycharm.zip"), &cache, "pycharm-community.zip"));
+    assert!(!download::is_cached(&server.url("/v1/pycharm.zip"), &cache, "pycharm-community.zip"));
+
+    download::download(&server.url("/v2/pycharm.zip"), &cache, "pycharm-community.zip")
+        .await
+        .unwrap();
+    assert_eq!(server.hits("/v2/pycharm.zip"), 1);
+}
+
+#[tokio::test]
+async fn test_download_latest_revalidates_with_etag() {
+    let server = MockServer::start();
+    server.file_with_etag("/latest/fnm-windows.zip", zipped("old"), "\"a1\"");
+    let root = TempRoot::new("download-etag");
+    let cache = root.path().join("cache");
+    let url = server.url("/latest/fnm-windows.zip");
+
+    download::download_latest(&url, &cache, "fnm-windows.zip").await.unwrap();
+    // 内容未变：服务器返回 304，沿用缓存
+    let path = download::download_latest(&url, &cache, "fnm-windows.zip").await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), zipped("old"));
+    assert_eq!(server.hits("/latest/fnm-windows.zip"), 2);
+
+    // 发布了新版本
+    server.file_with_etag("/latest/fnm-windows.zip", zipped("new"), "\"b2\"");
+    let path = download::download_latest(&url, &cache, "fnm-windows.zip").await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), zipped("new"));
+}
+
+#[tokio::test]
+async fn test_is_unchanged_without_download() {
+    // uv、Bun 等从「最新版」地址安装：安装时记下 ETag，之后用条件请求判断是否有更新
+    let server = MockServer::start();
+    server.file_with_etag("/releases/latest/download/bun-windows-x64.zip", zipped("1.2.0"), "\"a1\"");
+    let root = TempRoot::new("download-unchanged");
+    let cache = root.path().join("cache");
+    let url = server.url("/releases/latest/download/bun-windows-x64.zip");
+
+    download::download_latest(&url, &cache, "bun-windows-x64.zip").await.unwrap();
+    let validator = download::cached_validator(&url, &cache, "bun-windows-x64.zip").unwrap();
+    assert_eq!(validator.etag.as_deref(), Some("\"a1\""));
+    assert!(download::cached_validator(&server.url("/other.zip"), &cache, "bun-windows-x64.zip").is_none());
+
+    // 304：未变化
+    assert!(download::is_unchanged(&url, &validator).await.unwrap());
+    // 200 且 ETag 不同：有新版本，检查本身不下载（HEAD）
+    server.file_with_etag("/releases/latest/download/bun-windows-x64.zip", zipped("1.2.1"), "\"b2\"");
+    assert!(!download::is_unchanged(&url, &validator).await.unwrap());
+    assert_eq!(std::fs::read(cache.join("bun-windows-x64.zip")).unwrap(), zipped("1.2.0"));
+
+    server.route("/gone.zip", 404, "text/plain", b"not found".to_vec());
+    let err = download::is_unchanged(&server.url("/gone.zip"), &validator).await.unwrap_err();
+    assert_eq!(download::http_status(&err), Some(404));
+}
+
+#[tokio::test]
+async fn test_download_legacy_cache_without_meta() {
+    let server = MockServer::start();
+    server.file("/dl/go1.24.0.windows-amd64.zip", zipped("fresh"));
+    server.file("/latest/download/fnm-windows.zip", zipped("fresh"));
+    let root = TempRoot::new("download-legacy");
+    let cache = root.path().join("cache");
+    std::fs::create_dir_all(&cache).unwrap();
+    std::fs::write(cache.join("go1.24.0.windows-amd64.zip"), zipped("legacy")).unwrap();
+    std::fs::write(cache.join("fnm.zip"), zipped("legacy")).unwrap();
+
+    // 版本号在文件名里的旧缓存直接沿用，并补写来源记录
+    let path = download::download(
+        &server.url("/dl/go1.24.0.windows-amd64.zip"),
+        &cache,
+        "go1.24.0.windows-amd64.zip",
+    )
+    .await
+    .unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), zipped("legacy"));
+    assert_eq!(server.hits("/dl/go1.24.0.windows-amd64.zip"), 0);
+    assert!(cache.join("go1.24.0.windows-amd64.zip.meta.json").exists());
+
+    // 文件名与地址对不上时无法确认版本，重新下载
+    let path = download::download(&server.url("/latest/download/fnm-windows.zip"), &cache, "fnm.zip")
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), zipped("fresh"));
+}
+
+#[tokio::test]
+async fn test_download_follows_redirect_without_content_length() {
+    // VS Code 的 update.code.visualstudio.com/latest/... 302 到 CDN，部分 CDN 不返回 Content-Length
+    let server = MockServer::start();
+    server.redirect("/latest/win32-x64-archive/stable", &server.url("/cdn/VSCode-win32-x64.zip"));
+    server.file_without_length("/cdn/VSCode-win32-x64.zip", zipped(&"vscode".repeat(1000)));
+    let root = TempRoot::new("download-redirect");
+    let cache = root.path().join("cache");
+
+    let path = download::download_latest(
+        &server.url("/latest/win32-x64-archive/stable"),
+        &cache,
+        "vscode.zip",
+    )
+    .await
+    .unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), zipped(&"vscode".repeat(1000)));
+    assert_eq!(server.hits("/cdn/VSCode-win32-x64.zip"), 1);
+}
+
+#[tokio::test]
+async fn test_download_http_error_leaves_no_file() {
+    let server = MockServer::start();
+    let root = TempRoot::new("download-404");
+    let cache = root.path().join("cache");
+
+    let err = download::download(&server.url("/missing.zip"), &cache, "missing.zip")
+        .await
+        .unwrap_err();
+    assert!(format!("{:#}", err).contains("HTTP 错误"));
+    assert_eq!(download::http_status(&err), Some(404));
+    assert!(!cache.join("missing.zip").exists());
+    assert!(!cache.join("missing.zip.tmp").exists());
+}
+
+#[tokio::test]
+async fn test_download_rejects_html_error_page() {
+    // 镜像以 200 返回错误页面
+    let server = MockServer::start();
+    server.route(
+        "/mirror/go1.24.0.windows-amd64.zip",
+        200,
+        "text/html",
+        b"<!DOCTYPE html><html><body>404 Not Found</body></html>".to_vec(),
+    );
+    let root = TempRoot::new("download-html");
+    let cache = root.path().join("cache");
+
+    let err = download::download(
+        &server.url("/mirror/go1.24.0.windows-amd64.zip"),
+        &cache,
+        "go1.24.0.windows-amd64.zip",
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("下载内容不是预期的文件类型"));
+    assert!(!cache.join("go1.24.0.windows-amd64.zip").exists());
+    assert!(!cache.join("go1.24.0.windows-amd64.zip.tmp").exists());
+}
+
+#[tokio::test]
+async fn test_download_verified_rejects_wrong_sha256() {
+    let server = MockServer::start();
+    server.file("/pkg/tool.zip", zipped("payload"));
+    let root = TempRoot::new("download-sha");
+    let cache = root.path().join("cache");
+
+    let err = download::download_verified(
+        &server.url("/pkg/tool.zip"),
+        &cache,
+        "tool.zip",
+        Some("0000"),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("SHA256 校验失败"));
+    assert!(!cache.join("tool.zip").exists());
+
+    let expected = {
+        std::fs::write(root.path().join("probe"), zipped("payload")).unwrap();
+        download::sha256_file(&root.path().join("probe")).unwrap()
+    };
+    let path = download::download_verified(
+        &server.url("/pkg/tool.zip"),
+        &cache,
+        "tool.zip",
+        Some(&expected.to_uppercase()),
+    )
+    .await
+    .unwrap();
+    assert!(path.exists());
+}
+
+#[tokio::test]
+async fn test_frozen_download_latest_uses_verified_cache() {
+    // import --frozen：安装器走 download_latest 时也按 [lock] 的 SHA256 校验，不因 ETag 变化换成新文件
+    let server = MockServer::start();
+    server.file_with_etag("/latest/uv.zip", zipped("locked"), "\"a1\"");
+    let root = TempRoot::new("download-frozen");
+    let cache = root.path().join("cache");
+    let url = server.url("/latest/uv.zip");
+
+    let path = download::download_latest(&url, &cache, "uv.zip").await.unwrap();
+    let locked = download::sha256_file(&path).unwrap();
+    server.file_with_etag("/latest/uv.zip", zipped("changed"), "\"b2\"");
+    download::freeze(&url, &locked);
+    let path = download::download_latest(&url, &cache, "uv.zip").await.unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), zipped("locked"));
+    assert_eq!(server.hits("/latest/uv.zip"), 1);
+
+    // 缓存不符时重新下载，新文件仍不符则报错且不留下文件
+    download::freeze(&url, "0000");
+    let err = download::download_latest(&url, &cache, "uv.zip").await.unwrap_err();
+    assert!(err.to_string().contains("SHA256 校验失败"));
+    assert!(!cache.join("uv.zip").exists());
+}
+
+#[test]
+fn test_extract_zip_and_find_single_subdir() {
+    let root = TempRoot::new("extract");
+    let zip_path = root.path().join("tool.zip");
+    std::fs::write(
+        &zip_path,
+        make_zip(&[
+            ("tool-1.0/", b""),
+            ("tool-1.0/bin/tool.exe", b"exe"),
+            ("tool-1.0/README.md", b"readme"),
+        ]),
+    )
+    .unwrap();
+
+    let dest = root.path().join("extract");
+    download::extract_zip(&zip_path, &dest).unwrap();
+    let inner = download::find_single_subdir(&dest).unwrap();
+    assert_eq!(inner, dest.join("tool-1.0"));
+    assert_eq!(std::fs::read(inner.join("bin").join("tool.exe")).unwrap(), b"exe");
+
+    // 顶层有多个目录时不猜测
+    std::fs::create_dir_all(dest.join("other")).unwrap();
+    assert_eq!(download::find_single_subdir(&dest), None);
+}
+
+#[test]
+fn test_swap_dir_replaces_old_version() {
+    let root = TempRoot::new("swap");
+    let final_dir = root.path().join("tools").join("tool");
+    std::fs::create_dir_all(&final_dir).unwrap();
+    std::fs::write(final_dir.join("old.txt"), b"old").unwrap();
+
+    let staged = root.path().join("staged");
+    std::fs::create_dir_all(&staged).unwrap();
+    std::fs::write(staged.join("new.txt"), b"new").unwrap();
+
+    download::swap_dir(&staged, &final_dir).unwrap();
+    assert!(final_dir.join("new.txt").exists());
+    assert!(!final_dir.join("old.txt").exists());
+    assert!(!root.path().join("tools").join("tool.old").exists());
+    assert!(!root.path().join("tools").join("tool.new").exists());
+}
+
+/// 另一块盘上的临时目录：Linux 上 /dev/shm 是 tmpfs，与临时目录不在同一文件系统，
+/// 没有时退回普通临时目录（只是不再覆盖跨盘复制）
+fn other_volume(name: &str) -> TempRoot {
+    let shm = std::path::Path::new("/dev/shm");
+    if shm.is_dir() {
+        TempRoot::new_in(shm, name)
+    } else {
+        TempRoot::new(name)
+    }
+}
+
+#[tokio::test]
+async fn test_install_zip_across_volumes() {
+    let server = MockServer::start();
+    server.file(
+        "/pkg/pgsql.zip",
+        make_zip(&[
+            ("pgsql/", b""),
+            ("pgsql/bin/psql.exe", b"psql"),
+            ("doc/", b""),
+        ]),
+    );
+    let cache_root = other_volume("install-zip-cache");
+    let tools_root = TempRoot::new("install-zip-tools");
+    let cache = cache_root.path().join("cache");
+    let install_dir = tools_root.path().join("pgsql");
+    std::fs::create_dir_all(&install_dir).unwrap();
+    std::fs::write(install_dir.join("old.txt"), b"old").unwrap();
+
+    download::install_zip(
+        &server.url("/pkg/pgsql.zip"),
+        "pgsql.zip",
+        &cache,
+        &install_dir,
+        &download::ZipInstall {
+            name: "PostgreSQL",
+            subdir: Some("pgsql"),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    // 指定的子目录优先于「唯一子目录」规则，旧版本被替换
+    assert_eq!(std::fs::read(install_dir.join("bin").join("psql.exe")).unwrap(), b"psql");
+    assert!(!install_dir.join("old.txt").exists());
+    assert!(!cache.join("pgsql-extract").exists());
+    assert!(!tools_root.path().join("pgsql.new").exists());
+    assert!(cache.join("pgsql.zip").exists());
+}
+
+#[tokio::test]
+async fn test_install_zip_missing_required_file_keeps_old_version() {
+    let server = MockServer::start();
+    server.file(
+        "/pkg/mingw.zip",
+        make_zip(&[("mingw64/", b""), ("mingw64/bin/ld.exe", b"ld")]),
+    );
+    let cache_root = other_volume("install-zip-broken-cache");
+    let tools_root = TempRoot::new("install-zip-broken-tools");
+    let cache = cache_root.path().join("cache");
+    let install_dir = tools_root.path().join("mingw64");
+    std::fs::create_dir_all(&install_dir).unwrap();
+    std::fs::write(install_dir.join("old.txt"), b"old").unwrap();
+
+    let err = download::install_zip(
+        &server.url("/pkg/mingw.zip"),
+        "mingw.zip",
+        &cache,
+        &install_dir,
+        &download::ZipInstall {
+            name: "MinGW-w64",
+            subdir: Some("mingw64"),
+            required_file: Some("bin/gcc.exe"),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("bin/gcc.exe"));
+    assert!(install_dir.join("old.txt").exists());
+    assert!(!cache.join("mingw64-extract").exists());
+}
+
+#[tokio::test]
+async fn test_estimate_sizes() {
+    use download::SizeEstimate;
+    let server = MockServer::start();
+    server.file("/go/go.zip", vec![0u8; 2048]);
+    server.file_rejecting_head("/sqlite/sqlite.zip", vec![0u8; 512]);
+    server.file_without_length("/cdn/latest.zip", vec![0u8; 100]);
+    server.file("/git/git.zip", zipped("git"));
+    let root = TempRoot::new("download-sizes");
+    let cache = root.path().join("cache");
+    download::download(&server.url("/git/git.zip"), &cache, "git.zip").await.unwrap();
+
+    let targets = vec![
+        (server.url("/go/go.zip"), "go.zip".to_string()),
+        (server.url("/sqlite/sqlite.zip"), "sqlite.zip".to_string()),
+        (server.url("/cdn/latest.zip"), "latest.zip".to_string()),
+        (server.url("/missing.zip"), "missing.zip".to_string()),
+        (server.url("/git/git.zip"), "git.zip".to_string()),
+    ];
+    let sizes = download::estimate_sizes(&cache, &targets).await;
+    assert_eq!(
+        sizes,
+        vec![
+            SizeEstimate::Bytes(2048),
+            SizeEstimate::Bytes(512),
+            SizeEstimate::Unknown,
+            SizeEstimate::Unknown,
+            SizeEstimate::Cached,
+        ]
+    );
+    assert_eq!(server.hits("/git/git.zip"), 1);
+
+    // 已知的大小记录在缓存目录中，再次预估不再请求
+    let again = download::estimate_sizes(&cache, &targets[..2]).await;
+    assert_eq!(again, sizes[..2]);
+    assert_eq!(server.hits("/go/go.zip"), 1);
+    assert_eq!(server.hits("/sqlite/sqlite.zip"), 2);
+}
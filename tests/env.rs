@@ -0,0 +1,264 @@
+mod common;
+
+use std::path::PathBuf;
+
+use common::TempRoot;
+use hudo::config::HudoConfig;
+use hudo::env::{self, EnvChange, EnvExport, EnvManager, EnvPreview, ExportFormat};
+use hudo::installer::{DetectResult, EnvAction, InstallContext, InstallResult, Installer, ToolInfo};
+use hudo::registry::InstallRegistry;
+
+// 重定向在进程内只能设置一次，全部断言放在同一个测试里
+#[test]
+fn test_env_roundtrip_in_sandbox() {
+    let root = TempRoot::new("env");
+    #[cfg(windows)]
+    let location = format!(r"Software\hudo-test-env-{}", std::process::id());
+    #[cfg(not(windows))]
+    let location = root.path().join("env.sh").to_string_lossy().to_string();
+    assert!(env::redirect_to(&location));
+
+    EnvManager::set_var("HUDO_TEST_HOME", r"D:\hudo\lang\go").unwrap();
+    assert_eq!(
+        EnvManager::get_var("HUDO_TEST_HOME").unwrap().as_deref(),
+        Some(r"D:\hudo\lang\go")
+    );
+    EnvManager::delete_var("HUDO_TEST_HOME").unwrap();
+    assert_eq!(EnvManager::get_var("HUDO_TEST_HOME").unwrap(), None);
+
+    let bin = root.path().join("bin").to_string_lossy().to_string();
+    EnvManager::append_to_path(&bin).unwrap();
+    EnvManager::append_to_path(&bin).unwrap();
+    #[cfg(windows)]
+    {
+        let path = EnvManager::get_var("Path").unwrap().unwrap_or_default();
+        assert_eq!(path.split(';').filter(|p| *p == bin).count(), 1);
+    }
+    #[cfg(not(windows))]
+    {
+        let content = std::fs::read_to_string(&location).unwrap();
+        assert_eq!(content.matches(bin.as_str()).count(), 1);
+    }
+
+    EnvManager::remove_from_path(&bin).unwrap();
+    #[cfg(windows)]
+    {
+        let path = EnvManager::get_var("Path").unwrap().unwrap_or_default();
+        assert!(!path.split(';').any(|p| p == bin));
+        use winreg::{enums::HKEY_CURRENT_USER, RegKey};
+        let _ = RegKey::predef(HKEY_CURRENT_USER).delete_subkey_all(&location);
+    }
+    #[cfg(not(windows))]
+    {
+        let content = std::fs::read_to_string(&location).unwrap();
+        assert!(!content.contains(bin.as_str()));
+    }
+}
+
+/// 只提供 env_actions 的安装器，用于构造导出场景
+struct FakeTool {
+    id: &'static str,
+    home_var: &'static str,
+}
+
+#[async_trait::async_trait]
+impl Installer for FakeTool {
+    fn info(&self) -> ToolInfo {
+        ToolInfo {
+            id: self.id,
+            name: self.id,
+            description: "",
+            homepage: None,
+            post_install_tips: &[],
+        }
+    }
+
+    async fn detect_installed(&self, _ctx: &InstallContext<'_>) -> anyhow::Result<DetectResult> {
+        Ok(DetectResult::NotInstalled)
+    }
+
+    fn resolve_download(&self, _config: &HudoConfig) -> (String, String) {
+        (String::new(), String::new())
+    }
+
+    async fn install(&self, _ctx: &InstallContext<'_>) -> anyhow::Result<InstallResult> {
+        anyhow::bail!("不支持")
+    }
+
+    fn env_actions(&self, install_path: &PathBuf, _config: &HudoConfig) -> Vec<EnvAction> {
+        vec![
+            EnvAction::Set {
+                name: self.home_var.to_string(),
+                value: install_path.to_string_lossy().to_string(),
+            },
+            EnvAction::AppendPath {
+                path: format!(r"{}\bin", install_path.to_string_lossy()),
+            },
+        ]
+    }
+}
+
+#[test]
+fn test_env_export_formats() {
+    let root = TempRoot::new("env-export");
+    let config = root.config();
+    let installers: Vec<Box<dyn Installer>> = vec![
+        Box::new(FakeTool { id: "jdk", home_var: "JAVA_HOME" }),
+        Box::new(FakeTool { id: "go", home_var: "GOROOT" }),
+        Box::new(FakeTool { id: "redis", home_var: "REDIS_HOME" }),
+    ];
+    let mut reg = InstallRegistry::default();
+    reg.mark_installed("jdk", "21", r"C:\Users\张 三\hudo\lang\java");
+    reg.mark_installed("go", "1.24.0", r"D:\hudo\lang\go");
+
+    let export = EnvExport::collect(&reg, &installers, &config, None);
+    assert_eq!(
+        export.render(ExportFormat::Dotenv),
+        concat!(
+            "GOROOT=D:\\hudo\\lang\\go\n",
+            "JAVA_HOME=\"C:\\Users\\张 三\\hudo\\lang\\java\"\n",
+            "HUDO_PATH=\"C:\\Users\\张 三\\hudo\\lang\\java\\bin;D:\\hudo\\lang\\go\\bin\"\n",
+        )
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&export.render(ExportFormat::Json)).unwrap();
+    assert_eq!(json["vars"]["GOROOT"], r"D:\hudo\lang\go");
+    assert_eq!(json["path"][0], r"C:\Users\张 三\hudo\lang\java\bin");
+
+    assert_eq!(
+        export.render(ExportFormat::GithubActions),
+        concat!(
+            "echo 'GOROOT=D:\\hudo\\lang\\go' >> \"$GITHUB_ENV\"\n",
+            "echo 'JAVA_HOME=C:\\Users\\张 三\\hudo\\lang\\java' >> \"$GITHUB_ENV\"\n",
+            "echo 'C:\\Users\\张 三\\hudo\\lang\\java\\bin' >> \"$GITHUB_PATH\"\n",
+            "echo 'D:\\hudo\\lang\\go\\bin' >> \"$GITHUB_PATH\"\n",
+        )
+    );
+
+    assert_eq!(
+        export.render(ExportFormat::Powershell),
+        concat!(
+            "$env:GOROOT = 'D:\\hudo\\lang\\go'\n",
+            "$env:JAVA_HOME = 'C:\\Users\\张 三\\hudo\\lang\\java'\n",
+            "$env:Path = 'C:\\Users\\张 三\\hudo\\lang\\java\\bin;D:\\hudo\\lang\\go\\bin' + ';' + $env:Path\n",
+        )
+    );
+    assert_eq!(
+        export.render(ExportFormat::Cmd),
+        concat!(
+            "@echo off\r\n",
+            "set \"GOROOT=D:\\hudo\\lang\\go\"\r\n",
+            "set \"JAVA_HOME=C:\\Users\\张 三\\hudo\\lang\\java\"\r\n",
+            "set \"PATH=C:\\Users\\张 三\\hudo\\lang\\java\\bin;D:\\hudo\\lang\\go\\bin;%PATH%\"\r\n",
+        )
+    );
+
+    // --tool 只导出单个工具
+    let only_go = EnvExport::collect(&reg, &installers, &config, Some("go"));
+    assert_eq!(
+        only_go.render(ExportFormat::Dotenv),
+        "GOROOT=D:\\hudo\\lang\\go\nHUDO_PATH=D:\\hudo\\lang\\go\\bin\n"
+    );
+}
+
+#[test]
+fn test_expanded_path_len() {
+    let lookup = |name: &str| match name {
+        "SystemRoot" => Some(r"C:\Windows".to_string()),
+        "JAVA_HOME" => Some(r"D:\张三\java".to_string()),
+        _ => None,
+    };
+    // REG_EXPAND_SZ 中的 %VAR% 按展开后的长度计算，未定义的变量保持原样
+    assert_eq!(env::expanded_path_len(r"%SystemRoot%\system32", "", lookup), r"C:\Windows\system32".len());
+    assert_eq!(
+        env::expanded_path_len(r"%SystemRoot%;%NOPE%\bin;", r"%JAVA_HOME%\bin", lookup),
+        r"C:\Windows;%NOPE%\bin;D:\张三\java\bin".chars().count()
+    );
+    assert_eq!(env::expanded_path_len("", r"D:\go\bin", lookup), 9);
+    assert_eq!(env::expanded_path_len("100%;50%", "", lookup), 8);
+}
+
+#[test]
+fn test_env_preview() {
+    let actions = vec![
+        EnvAction::Set { name: "JAVA_HOME".to_string(), value: r"D:\hudo\lang\java".to_string() },
+        EnvAction::Set { name: "GOROOT".to_string(), value: r"D:\hudo\lang\go".to_string() },
+        EnvAction::AppendPath { path: r"%JAVA_HOME%\bin".to_string() },
+        EnvAction::AppendPath { path: r"D:\hudo\lang\go\bin".to_string() },
+    ];
+    let user_path = vec![r"C:\tools".to_string(), r"d:\hudo\lang\go\BIN".to_string()];
+    let user_var = |name: &str| (name == "GOROOT").then(|| r"D:\hudo\lang\go".to_string());
+    let system = "x".repeat(1800);
+    let preview = EnvPreview::compute(&actions, user_path, user_var, Some(&system), |_| None);
+
+    assert_eq!(
+        preview.changes[0],
+        EnvChange::Set { name: "JAVA_HOME".into(), old: None, new: r"D:\hudo\lang\java".into() }
+    );
+    // Windows 追加到用户 PATH 末尾，Unix 的 env.sh 加在 $PATH 前面
+    let (new_pos, go_pos) = if cfg!(unix) { (1, 3) } else { (3, 2) };
+    assert_eq!(
+        preview.changes[2],
+        EnvChange::AppendPath { path: r"%JAVA_HOME%\bin".into(), position: new_pos, total: 3, exists: false }
+    );
+    assert!(matches!(&preview.changes[3], EnvChange::AppendPath { exists: true, position, .. } if *position == go_pos));
+    assert!(!preview.is_noop());
+
+    // 本次设置的 JAVA_HOME 参与展开
+    let user_len = r"C:\tools;d:\hudo\lang\go\BIN;D:\hudo\lang\java\bin".len();
+    assert_eq!(preview.path_len, Some(1800 + 1 + user_len));
+    assert!(preview.path_near_limit());
+
+    // 无法读取系统 PATH（非 Windows）时不计算长度
+    let unchanged = EnvPreview::compute(&actions[1..2], Vec::new(), user_var, None, |_| None);
+    assert_eq!(unchanged.path_len, None);
+    assert!(unchanged.is_noop());
+}
+
+#[test]
+fn test_path_owners() {
+    let root = TempRoot::new("env-path-owner");
+    let config = root.config();
+    let installers: Vec<Box<dyn Installer>> = vec![
+        Box::new(FakeTool { id: "jdk", home_var: "JAVA_HOME" }),
+        Box::new(FakeTool { id: "go", home_var: "GOROOT" }),
+    ];
+    let mut reg = InstallRegistry::default();
+    reg.mark_installed("jdk", "21", r"D:\hudo\lang\java");
+    reg.mark_installed("go", "1.24.0", r"E:\sdk\go");
+
+    let claims = env::path_claims(&reg, &installers, &config);
+    assert_eq!(
+        claims,
+        [
+            ("jdk".to_string(), r"D:\hudo\lang\java\bin".to_string()),
+            ("go".to_string(), r"E:\sdk\go\bin".to_string()),
+        ]
+    );
+
+    let lookup = |name: &str| (name == "JAVA_HOME").then(|| r"D:\hudo\lang\java".to_string());
+    let entries: Vec<String> = [
+        r"C:\Windows\system32",
+        r"%JAVA_HOME%\bin",
+        r"e:\SDK\go\bin\",
+        r"D:\hudo\tools\redis",
+        r"D:\hudo\tools\fnm\aliases\default",
+        "",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+    let owners = env::path_owners(&entries, &claims, std::path::Path::new(r"D:\hudo"), lookup);
+    let summary: Vec<_> = owners.iter().map(|o| (o.entry.as_str(), o.tool.as_deref())).collect();
+    // 不在根目录下的 go 按声明归属；根目录下没有工具声明的是孤立项；系统目录不列出
+    assert_eq!(
+        summary,
+        [
+            (r"%JAVA_HOME%\bin", Some("jdk")),
+            (r"e:\SDK\go\bin\", Some("go")),
+            (r"D:\hudo\tools\redis", None),
+            (r"D:\hudo\tools\fnm\aliases\default", None),
+        ]
+    );
+}
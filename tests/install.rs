@@ -0,0 +1,141 @@
+//! 安装器端到端测试：版本查询与下载都指向本地模拟服务，安装到临时根目录
+#![cfg(windows)]
+
+mod common;
+
+use std::sync::OnceLock;
+
+use common::{make_zip, MockServer, TempRoot};
+use hudo::installer::{
+    gh::GhInstaller, go::GoInstaller, missing_version, nodejs::NodejsInstaller, split_created_dirs, EnvAction, InstallContext,
+    Installer, MissingVersion,
+};
+use hudo::psprofile;
+use hudo::report::{Collector, Event};
+use hudo::version;
+use serde_json::json;
+
+/// 版本查询的重定向在进程内只能设置一次，本文件的测试共用一个服务
+fn server() -> &'static MockServer {
+    static SERVER: OnceLock<MockServer> = OnceLock::new();
+    SERVER.get_or_init(|| {
+        let server = MockServer::start();
+        assert!(version::redirect_endpoints(server.base()));
+        server
+    })
+}
+
+#[tokio::test]
+async fn test_go_install_from_mirror() {
+    let server = server();
+    server.json("/go.dev/dl/?mode=json", json!([{ "version": "go1.24.1", "stable": true }]));
+    server.file(
+        "/go/go1.24.1.windows-amd64.zip",
+        make_zip(&[("go/", b""), ("go/bin/go.exe", b"not a real exe"), ("go/VERSION", b"go1.24.1")]),
+    );
+
+    let root = TempRoot::new("install-go");
+    let mut config = root.config();
+    config.mirrors.go = Some(server.url("/go"));
+    config.arch_override = Some(hudo::arch::Arch::X64);
+    let ctx = InstallContext::new(&config);
+
+    let result = GoInstaller.install(&ctx).await.unwrap();
+    assert_eq!(result.install_path, config.lang_dir().join("go"));
+    assert_eq!(result.version, "1.24.1");
+    assert!(result.install_path.join("bin").join("go.exe").exists());
+    assert!(config.lang_dir().join("gopath").exists());
+    assert!(!config.cache_dir().join("go-extract").exists());
+    assert_eq!(server.hits("/go/go1.24.1.windows-amd64.zip"), 1);
+
+    // 再次安装复用缓存的安装包
+    GoInstaller.install(&ctx).await.unwrap();
+    assert_eq!(server.hits("/go/go1.24.1.windows-amd64.zip"), 1);
+}
+
+#[tokio::test]
+async fn test_go_pinned_version_missing() {
+    let server = server();
+    let root = TempRoot::new("install-go-missing");
+    let mut config = root.config();
+    config.mirrors.go = Some(server.url("/go"));
+    config.arch_override = Some(hudo::arch::Arch::X64);
+    config.go.version = "1.99.0".to_string();
+    let ctx = InstallContext::new(&config);
+
+    // 镜像上没有这个版本：识别为固定版本不存在，而不是普通的下载失败
+    let err = GoInstaller.install(&ctx).await.unwrap_err();
+    assert_eq!(
+        missing_version(&err, &config, "go"),
+        Some(MissingVersion { key: "go.version".to_string(), version_key: "go", version: "1.99.0".to_string() })
+    );
+    assert_eq!(missing_version(&err, &config, "gh"), None);
+    assert!(GoInstaller.versions_hint().is_some_and(|h| h.contains("go.dev/dl")));
+}
+
+#[tokio::test]
+async fn test_gh_install_reports_events() {
+    server().json("/api.github.com/repos/cli/cli/releases/latest", json!({ "tag_name": "v2.87.3" }));
+
+    let root = TempRoot::new("install-gh");
+    let mut config = root.config();
+    config.arch_override = Some(hudo::arch::Arch::X64);
+    // 安装包固定从 GitHub 下载，预先放入缓存
+    std::fs::create_dir_all(config.cache_dir()).unwrap();
+    std::fs::write(
+        config.cache_dir().join("gh_2.87.3_windows_amd64.zip"),
+        make_zip(&[("gh_2.87.3_windows_amd64/", b""), ("gh_2.87.3_windows_amd64/bin/gh.exe", b"not a real exe")]),
+    )
+    .unwrap();
+
+    let collector = Collector::default();
+    let ctx = InstallContext { config: &config, reporter: &collector };
+    let result = GhInstaller.install(&ctx).await.unwrap();
+    assert_eq!(result.version, "2.87.3");
+    assert!(config.tools_dir().join("gh").join("bin").join("gh.exe").exists());
+    assert_eq!(
+        collector.events(),
+        vec![
+            Event::Action("查询 GitHub CLI 最新版本...".to_string()),
+            Event::Action("解压 GitHub CLI...".to_string()),
+        ]
+    );
+}
+
+/// 模拟安装后卸载 Node.js：configure 写入的 profile 区块与 FNM_DIR 都要在卸载时清理
+#[test]
+fn test_nodejs_uninstall_cleans_profile_and_fnm_dir() {
+    let root = TempRoot::new("nodejs-uninstall");
+    let config = root.config();
+    let fnm_dir = config.tools_dir().join("fnm");
+    let node_dir = config.lang_dir().join("node");
+    std::fs::create_dir_all(node_dir.join("node-versions").join("v22.12.0")).unwrap();
+    let profile = root.path().join("WindowsPowerShell").join("profile.ps1");
+    std::fs::create_dir_all(profile.parent().unwrap()).unwrap();
+    std::fs::write(&profile, "Set-Alias ll ls\r\n").unwrap();
+
+    // 安装：FNM_DIR 指向 lang\node，profile 中写入 fnm env 区块
+    let inst = NodejsInstaller;
+    let fnm_env = inst.env_actions(&fnm_dir, &config).into_iter().find_map(|a| match a {
+        EnvAction::Set { name, value } if name == "FNM_DIR" => Some(value),
+        _ => None,
+    });
+    assert_eq!(fnm_env.map(std::path::PathBuf::from), Some(node_dir.clone()));
+    assert!(psprofile::write_block(&profile, &inst.shell_integration(&fnm_dir).unwrap()).unwrap());
+    assert!(std::fs::read_to_string(&profile).unwrap().contains("fnm.exe"));
+
+    // 卸载：FNM_DIR 含用户数据，确认后删除；--keep-config 时保留
+    let created = inst.created_dirs(&config);
+    let (remove, keep) = split_created_dirs(created.clone(), true, |_| Ok(true)).unwrap();
+    assert!(remove.is_empty() && keep == [node_dir.clone()]);
+    let (remove, _) = split_created_dirs(created, false, |_| Ok(true)).unwrap();
+    assert_eq!(remove, vec![node_dir.clone()]);
+    for dir in &remove {
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+    assert!(!node_dir.exists());
+
+    // 区块删除后 profile 恢复原样，不再留下找不到 fnm 的初始化脚本
+    assert!(psprofile::remove_block(&profile, &inst.shell_integration(&fnm_dir).unwrap()).unwrap());
+    assert_eq!(std::fs::read_to_string(&profile).unwrap(), "Set-Alias ll ls\r\n");
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::MockServer;
+use hudo::mirrors::{self, Target};
+
+fn target(key: &'static str, url: String) -> Target {
+    Target { key, tool: key, custom: true, url }
+}
+
+#[tokio::test]
+async fn test_probe_reports_status_and_redirect() {
+    let server = MockServer::start();
+    server.file("/go/go1.24.0.windows-amd64.zip", b"zip".to_vec());
+    server.redirect("/vscode/latest", &server.url("/cdn/VSCode.zip"));
+    server.file("/cdn/VSCode.zip", b"zip".to_vec());
+    server.file_rejecting_head("/sqlite/2025/sqlite-tools.zip", b"zip".to_vec());
+
+    let targets = vec![
+        target("go", server.url("/go/go1.24.0.windows-amd64.zip")),
+        target("vscode", server.url("/vscode/latest")),
+        target("mysql", server.url("/mysql/Downloads/MySQL-8.4/mysql.zip")),
+        target("sqlite", server.url("/sqlite/2025/sqlite-tools.zip")),
+    ];
+    let probes = mirrors::probe_all(&targets).await.unwrap();
+
+    assert_eq!(probes[0].status, Some(200));
+    assert!(probes[0].ok() && probes[0].redirected_to.is_none());
+    assert_eq!(probes[0].size, Some(3));
+
+    assert!(probes[1].ok());
+    assert_eq!(probes[1].redirected_to.as_deref(), Some(server.url("/cdn/VSCode.zip").as_str()));
+
+    assert_eq!(probes[2].status, Some(404));
+    assert!(probes[2].verdict().contains("目录结构"));
+
+    // HEAD 被拒绝后改用 GET
+    assert!(probes[3].ok());
+    assert_eq!(server.hits("/sqlite/2025/sqlite-tools.zip"), 2);
+}
+
+#[test]
+fn test_content_size() {
+    use reqwest::header::{HeaderMap, HeaderValue, CONTENT_LENGTH, CONTENT_RANGE};
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_LENGTH, HeaderValue::from_static("1024"));
+    assert_eq!(mirrors::content_size(200, &headers), Some(1024));
+    assert_eq!(mirrors::content_size(404, &headers), None);
+    // Range GET：Content-Length 只是这一段，总长度在 Content-Range 里
+    headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes 0-1023/73400320"));
+    assert_eq!(mirrors::content_size(206, &headers), Some(73400320));
+    headers.insert(CONTENT_RANGE, HeaderValue::from_static("bytes 0-1023/*"));
+    assert_eq!(mirrors::content_size(206, &headers), None);
+    assert_eq!(mirrors::content_size(200, &HeaderMap::new()), None);
+}
+
+#[tokio::test]
+async fn test_probe_connection_refused() {
+    // 监听后立即关闭，端口上没有服务
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let probes = mirrors::probe_all(&[target("go", format!("http://127.0.0.1:{}/go.zip", port))])
+        .await
+        .unwrap();
+    assert_eq!(probes[0].status, None);
+    assert!(!probes[0].ok());
+    assert!(probes[0].verdict().starts_with("请求失败"));
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use common::TempRoot;
+use hudo::download::Validator;
+use hudo::registry::{ExternalPolicy, InstallRegistry, SCHEMA_VERSION};
+
+#[test]
+fn test_registry_roundtrip() {
+    let root = TempRoot::new("registry");
+    let state = root.config().state_path();
+    assert!(InstallRegistry::load(&state).unwrap().tools.is_empty());
+
+    let mut reg = InstallRegistry::default();
+    reg.mark_installed("go", "1.24.0", r"D:\hudo\lang\go");
+    reg.mark_installed_as_dependency("jdk", "21", r"D:\hudo\lang\java", "maven");
+    reg.set_external_policy("git", ExternalPolicy::Ignore);
+    let artifact = Validator { etag: Some("\"a1\"".to_string()), last_modified: None };
+    reg.mark_installed("uv", "uv 0.8.0", r"D:\hudo\tools\uv");
+    reg.set_artifact("uv", Some(artifact.clone()));
+    reg.save(&state).unwrap();
+
+    let loaded = InstallRegistry::load(&state).unwrap();
+    assert_eq!(loaded.get("go").unwrap().version, "1.24.0");
+    assert_eq!(loaded.get("jdk").unwrap().installed_as_dependency_of, vec!["maven"]);
+    assert_eq!(loaded.external_policy("git"), ExternalPolicy::Ignore);
+    assert_eq!(loaded.external_policy("go"), ExternalPolicy::Ask);
+    assert_eq!(loaded.get("uv").unwrap().artifact, Some(artifact));
+    assert!(loaded.get("go").unwrap().artifact.is_none());
+}
+
+#[test]
+fn test_retry_pending_deletes() {
+    let root = TempRoot::new("registry-pending");
+    let leftover = root.path().join("ide").join("vscode");
+    std::fs::create_dir_all(leftover.join("resources")).unwrap();
+    std::fs::write(leftover.join("resources").join("app.asar"), b"locked").unwrap();
+
+    let mut reg = InstallRegistry::default();
+    reg.add_pending_delete(&leftover);
+    reg.add_pending_delete(&leftover);
+    reg.add_pending_delete(&root.path().join("already-gone"));
+    assert_eq!(reg.pending_deletes.len(), 2);
+
+    let state = root.config().state_path();
+    reg.save(&state).unwrap();
+    let mut loaded = InstallRegistry::load(&state).unwrap();
+    assert_eq!(loaded.retry_pending_deletes().len(), 2);
+    assert!(!leftover.exists());
+    assert!(loaded.pending_deletes.is_empty());
+}
+
+#[test]
+fn test_env_pending() {
+    let root = TempRoot::new("registry-env-pending");
+    let state = root.config().state_path();
+    let mut reg = InstallRegistry::default();
+    reg.mark_installed("jdk", "21", r"D:\hudo\lang\java");
+    reg.mark_installed("go", "1.24.0", r"D:\hudo\lang\go");
+    reg.set_env_pending("jdk", true);
+    reg.set_env_pending("go", true);
+    reg.set_env_pending("uv", true);
+    reg.save(&state).unwrap();
+
+    let mut loaded = InstallRegistry::load(&state).unwrap();
+    assert_eq!(loaded.env_pending(), vec!["go", "jdk"]);
+    // 重新安装时重新询问，旧的待应用标记不保留
+    loaded.mark_installed("go", "1.25.0", r"D:\hudo\lang\go");
+    assert_eq!(loaded.env_pending(), vec!["jdk"]);
+}
+
+#[test]
+fn test_env_history() {
+    let root = TempRoot::new("registry-env-history");
+    let state = root.config().state_path();
+    let mut reg = InstallRegistry::default();
+    reg.record_env_removal("jdk", vec![("JAVA_HOME".into(), r"C:\jdk-17".into())], vec![r"%JAVA_HOME%\bin".into()]);
+    reg.record_env_removal("go", Vec::new(), vec![r"C:\Go\bin".into()]);
+    reg.record_system_path_removal("go", vec![r"C:\Program Files\Go\bin".into()]);
+    reg.record_system_path_removal("nodejs", vec![r"C:\Program Files\nodejs".into()]);
+    reg.save(&state).unwrap();
+
+    let mut loaded = InstallRegistry::load(&state).unwrap();
+    let jdk = loaded.take_env_removal(Some("jdk")).unwrap();
+    assert_eq!(jdk.vars, vec![("JAVA_HOME".to_string(), r"C:\jdk-17".to_string())]);
+    assert_eq!(jdk.path, vec![r"%JAVA_HOME%\bin"]);
+    assert!(loaded.take_env_removal(Some("jdk")).is_none());
+    // 系统 PATH 项记入同一工具最近的记录，没有时新建一条
+    assert_eq!(loaded.take_env_removal(None).unwrap().system_path, vec![r"C:\Program Files\nodejs"]);
+    // 不指定工具时取最近一次
+    let go = loaded.take_env_removal(None).unwrap();
+    assert_eq!((go.tool.as_str(), go.system_path), ("go", vec![r"C:\Program Files\Go\bin".to_string()]));
+    assert!(loaded.take_env_removal(None).is_none());
+
+    // 只保留最近的记录
+    for i in 0..30 {
+        loaded.record_env_removal(&format!("t{}", i), Vec::new(), Vec::new());
+    }
+    assert_eq!(loaded.env_history.len(), 20);
+    assert_eq!(loaded.env_history[0].tool, "t10");
+}
+
+#[test]
+fn test_corrupt_state_resets() {
+    let root = TempRoot::new("registry-corrupt");
+    let state = root.config().state_path();
+    std::fs::write(&state, "{ not json").unwrap();
+    assert!(InstallRegistry::load(&state).unwrap().tools.is_empty());
+
+    // 重置前保留了原文件的备份
+    assert_eq!(backups(&state), vec!["{ not json".to_string()]);
+}
+
+/// state.json 同目录下的备份文件内容
+fn backups(state: &std::path::Path) -> Vec<String> {
+    std::fs::read_dir(state.parent().unwrap())
+        .unwrap()
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().ends_with(".bak"))
+        .map(|e| std::fs::read_to_string(e.path()).unwrap())
+        .collect()
+}
+
+/// 加入 schema 字段之前写入的 state.json
+const SCHEMA_0_FIXTURE: &str = r#"{
+  "tools": {
+    "go": { "version": "1.24.0", "install_path": "D:\\hudo\\lang\\go", "installed_at": "2025-03-01 08:00:00" },
+    "jdk": {
+      "version": "21",
+      "install_path": "D:\\hudo\\lang\\java",
+      "installed_at": "2025-03-01 08:01:00",
+      "installed_as_dependency_of": ["maven"]
+    }
+  },
+  "external": { "git": { "policy": "ignore", "decided_at": "2025-03-01 07:59:00" } }
+}"#;
+
+#[test]
+fn test_migrate_from_schema_0() {
+    let root = TempRoot::new("registry-migrate");
+    let state = root.config().state_path();
+    std::fs::write(&state, SCHEMA_0_FIXTURE).unwrap();
+
+    let reg = InstallRegistry::load(&state).unwrap();
+    assert_eq!(reg.schema, SCHEMA_VERSION);
+    assert_eq!(reg.get("go").unwrap().version, "1.24.0");
+    assert_eq!(reg.get("jdk").unwrap().installed_as_dependency_of, vec!["maven"]);
+    assert_eq!(reg.external_policy("git"), ExternalPolicy::Ignore);
+
+    // 迁移后写回新格式，原文件留有备份；再次读取不再迁移
+    assert_eq!(backups(&state), vec![SCHEMA_0_FIXTURE.to_string()]);
+    let saved: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&state).unwrap()).unwrap();
+    assert_eq!(saved["schema"], SCHEMA_VERSION);
+    InstallRegistry::load(&state).unwrap();
+    assert_eq!(backups(&state).len(), 1);
+}
+
+#[test]
+fn test_newer_schema_is_not_reset() {
+    let root = TempRoot::new("registry-newer");
+    let state = root.config().state_path();
+    let content = format!(r#"{{ "schema": {}, "tools": {{}}, "future": true }}"#, SCHEMA_VERSION + 1);
+    std::fs::write(&state, &content).unwrap();
+
+    let err = InstallRegistry::load(&state).unwrap_err();
+    assert!(err.to_string().contains("升级 hudo"));
+    assert_eq!(std::fs::read_to_string(&state).unwrap(), content);
+}
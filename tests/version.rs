@@ -0,0 +1,83 @@
+mod common;
+
+use std::sync::OnceLock;
+
+use common::MockServer;
+use hudo::version::{self, VersionError};
+use serde_json::json;
+
+/// 版本查询的重定向在进程内只能设置一次，本文件的测试共用一个服务
+fn server() -> &'static MockServer {
+    static SERVER: OnceLock<MockServer> = OnceLock::new();
+    SERVER.get_or_init(|| {
+        let server = MockServer::start();
+        assert!(version::redirect_endpoints(server.base()));
+        server
+    })
+}
+
+#[tokio::test]
+async fn test_go_latest_from_mock() {
+    server().json(
+        "/go.dev/dl/?mode=json",
+        json!([{ "version": "go1.24.1", "stable": true }, { "version": "go1.23.7", "stable": true }]),
+    );
+    assert_eq!(version::go_latest().await, Ok("1.24.1".to_string()));
+}
+
+#[tokio::test]
+async fn test_pgsql_latest_from_mock() {
+    server().json(
+        "/www.postgresql.org/versions.json",
+        json!([
+            { "major": "16", "latestMinor": "8", "current": false },
+            { "major": "17", "latestMinor": "4", "current": true }
+        ]),
+    );
+    assert_eq!(version::pgsql_latest().await, Ok("17.4".to_string()));
+}
+
+#[tokio::test]
+async fn test_gh_latest_from_mock() {
+    server().json(
+        "/api.github.com/repos/cli/cli/releases/latest",
+        json!({ "tag_name": "v2.87.3" }),
+    );
+    assert_eq!(version::gh_latest().await, Ok("2.87.3".to_string()));
+}
+
+#[tokio::test]
+async fn test_sqlite_latest_from_mock() {
+    server().route(
+        "/www.sqlite.org/download.html",
+        200,
+        "text/html",
+        b"<!--\nPRODUCT,3.50.4,2025/sqlite-tools-win-x64-3500400.zip,6281569,abc\n-->".to_vec(),
+    );
+    assert_eq!(
+        version::sqlite_latest().await,
+        Ok(("3.50.4".to_string(), "2025/sqlite-tools-win-x64-3500400.zip".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_missing_release_is_not_found() {
+    let _ = server();
+    assert_eq!(version::maven_latest().await, Err(VersionError::NotFound));
+    // 404 不重试
+    assert_eq!(server().hits("/api.github.com/repos/apache/maven/releases/latest"), 1);
+}
+
+#[tokio::test]
+async fn test_server_error_is_retried_once() {
+    let path = "/api.github.com/repos/git-for-windows/git/releases/latest";
+    server().route(path, 503, "text/plain", b"unavailable".to_vec());
+    assert_eq!(version::git_latest().await, Err(VersionError::Http(503)));
+    assert_eq!(server().hits(path), 2);
+}
+
+#[tokio::test]
+async fn test_malformed_response_is_parse_error() {
+    server().json("/services.gradle.org/versions/current", json!({ "name": "8.12" }));
+    assert!(matches!(version::gradle_latest().await, Err(VersionError::Parse(_))));
+}